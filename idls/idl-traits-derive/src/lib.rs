@@ -12,24 +12,45 @@ use syn::{parse_macro_input, DeriveInput};
 /// 
 /// # 要求
 /// - 结构体所在的 crate 必须有 `ID` 常量 (程序ID)
-/// - 结构体所在的 crate 必须有 `parsers::instructions` 模块
+/// - 结构体所在的 crate 必须有 `parsers::instructions::ProgramInstruction::try_parse`，解析结果提供 `name()`
+/// - 结构体所在的 crate 启用 `serde` feature 时，解析结果需实现 `Serialize`，
+///   且需启用 `idl-traits/serde`，`ParsedInstruction::to_json` 可用
+/// 
+/// - 使用 `#[instruction_parser(events)]` 时还需要 `parsers::events` 模块，
+///   `try_parse_any_event` / `try_parse_inner_event` 将解析事件
 /// 
 /// # 示例
-/// ```rust
+/// ```ignore
 /// #[derive(InstructionParser)]
+/// #[instruction_parser(events)]
 /// pub struct MyInstructionParser;
 /// ```
-#[proc_macro_derive(InstructionParser)]
+#[proc_macro_derive(InstructionParser, attributes(instruction_parser))]
 pub fn derive_instruction_parser(input: TokenStream) -> TokenStream {
     let input = parse_macro_input!(input as DeriveInput);
     let name = &input.ident;
+    let with_events = match parse_events_flag(&input) {
+        Ok(with_events) => with_events,
+        Err(e) => return e.to_compile_error().into(),
+    };
+    let try_parse_any_event = if with_events {
+        try_parse_any_event()
+    } else {
+        quote! {
+            // 默认空实现 - 可以通过 #[instruction_parser(events)] 启用
+            fn try_parse_any_event(&self, _data: &[u8]) -> ::std::option::Option<::idl_traits::ParsedEvent> {
+                ::std::option::Option::None
+            }
+        }
+    };
     
+    let wrap_payload = wrap_payload(quote!(::idl_traits::ParsedInstruction));
     let expanded = quote! {
         impl ::idl_traits::Parser for #name {
             type Input = ::idl_traits::InstructionUpdate;
             type Output = ::idl_traits::ParsedInstruction;
             
-            fn id(&self) -> ::std::borrow::Cow<str> {
+            fn id(&self) -> ::std::borrow::Cow<'_, str> {
                 concat!(module_path!(), "::", stringify!(#name)).into()
             }
             
@@ -43,11 +64,12 @@ pub fn derive_instruction_parser(input: TokenStream) -> TokenStream {
             fn parse(&self, ix_update: &Self::Input) -> ::idl_traits::ParseResult<Self::Output> {
                 if ix_update.program == crate::ID {
                     // 使用生成的解析函数
-                    let parsed = crate::parsers::instructions::parse_instruction(&ix_update.data, &ix_update.accounts)
+                    let parsed = crate::parsers::instructions::ProgramInstruction::try_parse(&ix_update.data, &ix_update.accounts)
                         .map_err(|e| ::idl_traits::ParseError::InvalidInstructionData(e.to_string()))?;
-                    ::std::result::Result::Ok(::idl_traits::ParsedInstruction::custom(parsed))
+                    #wrap_payload
+                    ::std::result::Result::Ok(parsed)
                 } else {
-                    ::error_stack::bail!(::idl_traits::ParseError::Filtered)
+                    ::idl_traits::error_stack::bail!(::idl_traits::ParseError::Filtered)
                 }
             }
         }
@@ -57,10 +79,7 @@ pub fn derive_instruction_parser(input: TokenStream) -> TokenStream {
                 crate::ID
             }
             
-            // 默认空实现 - 可以通过手动实现覆盖
-            fn try_parse_any_event(&self, _data: &[u8]) -> ::std::option::Option<::idl_traits::ParsedEvent> {
-                ::std::option::Option::None
-            }
+            #try_parse_any_event
         }
     };
     
@@ -69,14 +88,19 @@ pub fn derive_instruction_parser(input: TokenStream) -> TokenStream {
 
 /// #[derive(AccountParser)] 派生宏
 /// 
-/// 自动为结构体实现 Parser + ProgramParser trait，用于解析账户数据
+/// 自动为结构体实现 Parser + ProgramParser trait，用于解析账户数据。
+/// 预过滤器按 `parsers::accounts::PREFILTERS` 为每个账户类型生成数据长度 / memcmp 过滤器，
+/// 订阅只接收已知类型的账户
 /// 
 /// # 要求
 /// - 结构体所在的 crate 必须有 `ID` 常量 (程序ID)
-/// - 结构体所在的 crate 必须有 `parsers::accounts` 模块
+/// - 结构体所在的 crate 必须有 `parsers::accounts` 模块及其中的 `ProgramAccount::try_parse` 与 `PREFILTERS` 表，
+///   解析结果提供 `name()`
+/// - 结构体所在的 crate 启用 `serde` feature 时，解析结果需实现 `Serialize`，
+///   且需启用 `idl-traits/serde`，`ParsedAccount::to_json` 可用
 /// 
 /// # 示例
-/// ```rust
+/// ```ignore
 /// #[derive(AccountParser)]
 /// pub struct MyAccountParser;
 /// ```
@@ -84,31 +108,41 @@ pub fn derive_instruction_parser(input: TokenStream) -> TokenStream {
 pub fn derive_account_parser(input: TokenStream) -> TokenStream {
     let input = parse_macro_input!(input as DeriveInput);
     let name = &input.ident;
+    let wrap_payload = wrap_payload(quote!(::idl_traits::ParsedAccount));
     
     let expanded = quote! {
         impl ::idl_traits::Parser for #name {
             type Input = ::idl_traits::AccountUpdate;
-            type Output = ::idl_traits::ParsedInstruction;
+            type Output = ::idl_traits::ParsedAccount;
             
-            fn id(&self) -> ::std::borrow::Cow<str> {
+            fn id(&self) -> ::std::borrow::Cow<'_, str> {
                 concat!(module_path!(), "::", stringify!(#name)).into()
             }
             
             fn prefilter(&self) -> ::idl_traits::Prefilter {
-                ::idl_traits::Prefilter::builder()
-                    .account_owners([crate::ID])
-                    .build()
-                    .unwrap()
+                let mut builder = ::idl_traits::Prefilter::builder().account_owners([crate::ID]);
+                for prefilter in crate::parsers::accounts::PREFILTERS {
+                    let mut filter = ::idl_traits::AccountFilter::new(crate::ID);
+                    if let ::std::option::Option::Some(size) = prefilter.data_size {
+                        filter = filter.data_size(size);
+                    }
+                    for &(offset, bytes) in prefilter.memcmp {
+                        filter = filter.memcmp(offset, bytes);
+                    }
+                    builder = builder.account_filter(filter);
+                }
+                builder.build().unwrap()
             }
             
             fn parse(&self, account_update: &Self::Input) -> ::idl_traits::ParseResult<Self::Output> {
                 if account_update.owner == crate::ID {
                     // 使用生成的账户解析函数
-                    let parsed = crate::parsers::accounts::try_unpack_account(&account_update.data)
+                    let parsed = crate::parsers::accounts::ProgramAccount::try_parse(&account_update.data)
                         .map_err(|e| ::idl_traits::ParseError::DeserializationFailed(e.to_string()))?;
-                    ::std::result::Result::Ok(::idl_traits::ParsedInstruction::custom(parsed))
+                    #wrap_payload
+                    ::std::result::Result::Ok(parsed)
                 } else {
-                    ::error_stack::bail!(::idl_traits::ParseError::Filtered)
+                    ::idl_traits::error_stack::bail!(::idl_traits::ParseError::Filtered)
                 }
             }
         }
@@ -126,4 +160,106 @@ pub fn derive_account_parser(input: TokenStream) -> TokenStream {
     };
     
     TokenStream::from(expanded)
-}
\ No newline at end of file
+}
+
+/// #[derive(EventParser)] 派生宏
+/// 
+/// 自动为结构体实现 Parser + ProgramParser trait，用于解析程序事件：
+/// `parse` 处理 `emit_cpi!` 产生的内部指令，`try_parse_any_event` 处理日志数据
+/// 
+/// # 要求
+/// - 结构体所在的 crate 必须有 `ID` 常量 (程序ID)
+/// - 结构体所在的 crate 必须有 `parsers::events` 模块，解析结果提供 `name()`
+/// - 结构体所在的 crate 启用 `serde` feature 时，解析结果需实现 `Serialize`，
+///   且需启用 `idl-traits/serde`，`ParsedEvent::to_json` 可用
+/// 
+/// # 示例
+/// ```ignore
+/// #[derive(EventParser)]
+/// pub struct MyEventParser;
+/// ```
+#[proc_macro_derive(EventParser)]
+pub fn derive_event_parser(input: TokenStream) -> TokenStream {
+    let input = parse_macro_input!(input as DeriveInput);
+    let name = &input.ident;
+    let wrap_payload = wrap_payload(quote!(::idl_traits::ParsedEvent));
+    let try_parse_any_event = try_parse_any_event();
+    
+    let expanded = quote! {
+        impl ::idl_traits::Parser for #name {
+            type Input = ::idl_traits::InstructionUpdate;
+            type Output = ::idl_traits::ParsedEvent;
+            
+            fn id(&self) -> ::std::borrow::Cow<'_, str> {
+                concat!(module_path!(), "::", stringify!(#name)).into()
+            }
+            
+            fn prefilter(&self) -> ::idl_traits::Prefilter {
+                ::idl_traits::Prefilter::builder()
+                    .transaction_accounts([crate::ID])
+                    .build()
+                    .unwrap()
+            }
+            
+            fn parse(&self, ix_update: &Self::Input) -> ::idl_traits::ParseResult<Self::Output> {
+                if ix_update.program == crate::ID {
+                    // 使用生成的事件解析函数
+                    let parsed = crate::parsers::events::ProgramEvent::try_parse(&ix_update.data)
+                        .map_err(|e| ::idl_traits::ParseError::DeserializationFailed(e.to_string()))?;
+                    #wrap_payload
+                    ::std::result::Result::Ok(parsed)
+                } else {
+                    ::idl_traits::error_stack::bail!(::idl_traits::ParseError::Filtered)
+                }
+            }
+        }
+        
+        impl ::idl_traits::ProgramParser for #name {
+            fn program_id(&self) -> ::idl_traits::Pubkey {
+                crate::ID
+            }
+            
+            #try_parse_any_event
+        }
+    };
+    
+    TokenStream::from(expanded)
+}
+
+/// 将 `parsed` 包装为 `wrapper` 载荷；生成 crate 启用 `serde` 时载荷可通过 `to_json()` 序列化
+fn wrap_payload(wrapper: proc_macro2::TokenStream) -> proc_macro2::TokenStream {
+    quote! {
+        #[cfg(feature = "serde")]
+        let parsed = #wrapper::serializable(crate::ID, parsed.name(), parsed);
+        #[cfg(not(feature = "serde"))]
+        let parsed = #wrapper::new(crate::ID, parsed.name(), parsed);
+    }
+}
+
+/// 从日志数据解析事件的 `try_parse_any_event` 实现，载荷包装方式同 [`wrap_payload`]
+fn try_parse_any_event() -> proc_macro2::TokenStream {
+    let wrap_payload = wrap_payload(quote!(::idl_traits::ParsedEvent));
+    quote! {
+        fn try_parse_any_event(&self, data: &[u8]) -> ::std::option::Option<::idl_traits::ParsedEvent> {
+            let parsed = crate::parsers::events::ProgramEvent::try_parse(data).ok()?;
+            #wrap_payload
+            ::std::option::Option::Some(parsed)
+        }
+    }
+}
+
+/// 解析 `#[instruction_parser(events)]` 属性
+fn parse_events_flag(input: &DeriveInput) -> syn::Result<bool> {
+    let mut with_events = false;
+    for attr in input.attrs.iter().filter(|attr| attr.path().is_ident("instruction_parser")) {
+        attr.parse_nested_meta(|meta| {
+            if meta.path.is_ident("events") {
+                with_events = true;
+                Ok(())
+            } else {
+                Err(meta.error("不支持的 instruction_parser 参数，可选: events"))
+            }
+        })?;
+    }
+    Ok(with_events)
+}
@@ -7,43 +7,104 @@ pub const ID: Pubkey = solana_pubkey::pubkey!("6EF8rrecthR5Dkzon8Nwu78hRvfCKubJ1
 
 // 模拟生成的指令类型
 #[derive(Debug, Clone)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize))]
 pub enum ProgramInstruction {
     Buy { amount: u64 },
     Sell { amount: u64 },
 }
 
+impl ProgramInstruction {
+    pub fn try_parse(_data: &[u8], _accounts: &[Pubkey]) -> Result<Self, String> {
+        // 模拟解析逻辑
+        Ok(ProgramInstruction::Buy { amount: 1000 })
+    }
+
+    pub fn name(&self) -> &'static str {
+        match self {
+            ProgramInstruction::Buy { .. } => "Buy",
+            ProgramInstruction::Sell { .. } => "Sell",
+        }
+    }
+}
+
 // 模拟生成的账户类型  
 #[derive(Debug, Clone)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize))]
 pub enum ProgramAccount {
     Global { authority: Pubkey },
     BondingCurve { reserves: u64 },
 }
 
+impl ProgramAccount {
+    pub fn try_parse(_data: &[u8]) -> Result<Self, std::io::Error> {
+        // 模拟解析逻辑
+        Ok(ProgramAccount::Global { authority: crate::ID })
+    }
+
+    pub fn name(&self) -> &'static str {
+        match self {
+            ProgramAccount::Global { .. } => "Global",
+            ProgramAccount::BondingCurve { .. } => "BondingCurve",
+        }
+    }
+}
+
 // 模拟 parsers 模块
 pub mod parsers {
     pub mod instructions {
-        use super::super::ProgramInstruction;
-        
-        pub fn parse_instruction(_data: &[u8], _accounts: &[crate::Pubkey]) -> Result<ProgramInstruction, String> {
-            // 模拟解析逻辑
-            Ok(ProgramInstruction::Buy { amount: 1000 })
+        pub use super::super::ProgramInstruction;
+    }
+    
+    pub mod events {
+        /// 模拟生成的事件类型
+        #[derive(Debug, Clone)]
+        #[cfg_attr(feature = "serde", derive(serde::Serialize))]
+        pub enum ProgramEvent {
+            TradeEvent { amount: u64 },
+        }
+
+        impl ProgramEvent {
+            pub fn try_parse(data: &[u8]) -> Result<Self, std::io::Error> {
+                // 模拟解析逻辑
+                match data.first() {
+                    Some(1) => Ok(ProgramEvent::TradeEvent { amount: 1000 }),
+                    _ => Err(std::io::ErrorKind::InvalidData.into()),
+                }
+            }
+
+            pub fn name(&self) -> &'static str {
+                match self {
+                    ProgramEvent::TradeEvent { .. } => "TradeEvent",
+                }
+            }
         }
     }
     
     pub mod accounts {
-        use super::super::ProgramAccount;
-        
-        pub fn try_unpack_account(_data: &[u8]) -> Result<ProgramAccount, std::io::Error> {
-            // 模拟解析逻辑
-            Ok(ProgramAccount::Global { authority: crate::ID })
+        pub use super::super::ProgramAccount;
+
+        /// 模拟生成的账户预过滤表
+        pub struct AccountPrefilter {
+            pub name: &'static str,
+            pub data_size: Option<u64>,
+            pub memcmp: &'static [(usize, &'static [u8])],
         }
+
+        pub const PREFILTERS: &[AccountPrefilter] = &[
+            AccountPrefilter { name: "Global", data_size: None, memcmp: &[(0, &[167, 232, 232, 177, 200, 108, 114, 127])] },
+            AccountPrefilter { name: "BondingCurve", data_size: None, memcmp: &[(0, &[23, 183, 248, 55, 96, 216, 172, 96])] },
+        ];
     }
 }
 
 // 使用派生宏自动实现解析器
 #[derive(InstructionParser, Debug, Clone, Copy)]
+#[instruction_parser(events)]
 pub struct MyInstructionParser;
 
+#[derive(EventParser, Debug, Clone, Copy)]
+pub struct MyEventParser;
+
 #[derive(AccountParser, Debug, Clone, Copy)]  
 pub struct MyAccountParser;
 
@@ -62,12 +123,55 @@ fn main() {
     println!("📋 账户解析器:");
     println!("  ID: {}", account_parser.id());
     println!("  程序ID: {}", account_parser.program_id());
+    println!("  账户过滤器: {} 个", account_parser.prefilter().account_filters.len());
     
-    // 测试事件解析 (默认空实现)
+    // 测试事件解析
     let test_data = vec![1, 2, 3, 4, 5, 6, 7, 8];
     match instruction_parser.try_parse_any_event(&test_data) {
-        Some(_event) => println!("✅ 解析到事件"),
-        None => println!("📭 无事件解析支持 (默认行为)"),
+        Some(event) => println!("✅ 解析到事件: {} ({})", event.name(), event.program_id()),
+        None => println!("📭 未解析到事件"),
+    }
+    
+    // 从内部指令 (emit_cpi!) 中提取事件
+    let inner_ix = InstructionUpdate {
+        program: ID,
+        data: test_data,
+        accounts: vec![],
+        stack_height: 2,
+    };
+    let event_parser = MyEventParser;
+    if let Ok(event) = event_parser.parse(&inner_ix) {
+        if let Some(parsers::events::ProgramEvent::TradeEvent { amount }) = event.try_as() {
+            println!("✅ 内部指令事件: {} amount={}", event.name(), amount);
+        }
+    }
+    
+    // 组合多个程序的解析器，按程序ID路由
+    let registry = InstructionParserRegistry::new().with(instruction_parser);
+    println!("📋 注册表: {} 个解析器, 预过滤账户 {:?}", registry.len(), registry.prefilter().transaction_accounts);
+    let updates = [inner_ix];
+    for parsed in registry.parse_all(&updates).flatten() {
+        // 按类型恢复生成的指令枚举
+        if let Some(ProgramInstruction::Buy { amount }) = parsed.try_as::<ProgramInstruction>() {
+            println!("✅ 注册表解析结果: {} amount={} ({})", parsed.name(), amount, parsed.program_id());
+        }
+        #[cfg(feature = "serde")]
+        println!("  JSON: {:?}", parsed.to_json());
+    }
+
+    // 账户解析结果同样携带名称与强类型载荷
+    let account_update = AccountUpdate {
+        pubkey: ID,
+        owner: ID,
+        data: vec![],
+        lamports: 0,
+        executable: false,
+        rent_epoch: 0,
+    };
+    if let Ok(account) = account_parser.parse(&account_update) {
+        if let Ok(account) = account.into_inner::<ProgramAccount>() {
+            println!("✅ 账户: {} {:?}", account.name(), account);
+        }
     }
     
     println!("🎉 示例完成！");
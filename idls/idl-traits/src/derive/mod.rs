@@ -14,7 +14,7 @@ pub fn derive_instruction_parser(input: TokenStream) -> TokenStream {
             type Input = ::idl_traits::InstructionUpdate;
             type Output = ::idl_traits::ProgramInstruction;
             
-            fn id(&self) -> ::std::borrow::Cow<str> {
+            fn id(&self) -> ::std::borrow::Cow<'_, str> {
                 concat!(module_path!(), "::", stringify!(#name)).into()
             }
             
@@ -62,7 +62,7 @@ pub fn derive_account_parser(input: TokenStream) -> TokenStream {
             type Input = ::idl_traits::AccountUpdate;
             type Output = ::idl_traits::ProgramAccount;
             
-            fn id(&self) -> ::std::borrow::Cow<str> {
+            fn id(&self) -> ::std::borrow::Cow<'_, str> {
                 concat!(module_path!(), "::", stringify!(#name)).into()
             }
             
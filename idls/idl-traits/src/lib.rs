@@ -6,30 +6,70 @@
 //! 
 //! - **Parser Traits** - 统一的解析器接口定义
 //! - **ProgramParser** - 扩展的程序解析器 (支持事件解析)
+//! - **ParsedEvent** - 携带程序ID、事件名称与载荷的统一事件类型
+//! - **ParsedInstruction / ParsedAccount** - 携带程序ID、名称与强类型载荷的指令 / 账户解析结果
+//! - **ParserRegistry** - 组合多个程序解析器，按程序ID路由更新
 //! - **Derive Macros** - 零配置自动实现
 //! 
 //! # 使用示例
 //! 
-//! ```rust
+//! ```no_run
 //! use idl_traits::*;
-//! 
-//! // 指令解析器 (自动支持事件解析扩展)
+//! # // 派生宏从 crate 根引用生成 crate 的 `ID` 与 `parsers` 模块，这里以最小实现代替
+//! # pub const ID: Pubkey = Pubkey::new_from_array([7; 32]);
+//! # pub mod parsers {
+//! #     macro_rules! payload {
+//! #         ($name:ident $(, $arg:ty)*) => {
+//! #             #[derive(Debug)]
+//! #             #[cfg_attr(feature = "serde", derive(serde::Serialize))]
+//! #             pub struct $name;
+//! #             impl $name {
+//! #                 pub fn try_parse(_data: &[u8] $(, _: $arg)*) -> Result<Self, String> { Ok(Self) }
+//! #                 pub fn name(&self) -> &'static str { stringify!($name) }
+//! #             }
+//! #         };
+//! #     }
+//! #     pub mod instructions { payload!(ProgramInstruction, &[idl_traits::Pubkey]); }
+//! #     pub mod events { payload!(ProgramEvent); }
+//! #     pub mod accounts {
+//! #         payload!(ProgramAccount);
+//! #         pub struct AccountPrefilter {
+//! #             pub data_size: Option<u64>,
+//! #             pub memcmp: &'static [(usize, &'static [u8])],
+//! #         }
+//! #         pub const PREFILTERS: &[AccountPrefilter] = &[];
+//! #     }
+//! # }
+//!
+//! // 指令解析器，同时从内部指令数据中提取事件
 //! #[derive(InstructionParser)]
+//! #[instruction_parser(events)]
 //! pub struct MyInstructionParser;
-//! 
+//!
+//! // 事件解析器
+//! #[derive(EventParser)]
+//! pub struct MyEventParser;
+//!
 //! // 账户解析器
 //! #[derive(AccountParser)]
 //! pub struct MyAccountParser;
+//! # fn main() {}
 //! ```
 
 // 重新导出核心模块
+pub mod registry;
 pub mod traits;
 pub mod types;
 
 // 导出派生宏
-pub use idl_traits_derive::{InstructionParser, AccountParser};
+pub use idl_traits_derive::{InstructionParser, AccountParser, EventParser};
+
+// 派生宏展开使用，使用方无需直接依赖 error-stack
+#[doc(hidden)]
+pub use error_stack;
 
 // 便利重导出
+pub use registry::*;
 pub use traits::*;
 pub use types::*;
 
@@ -0,0 +1,270 @@
+//! 解析器注册表
+//!
+//! 持有多个 `ProgramParser`，按程序ID路由数据更新，
+//! 并合并各解析器的预过滤器供 Yellowstone 订阅使用。
+//! 解析器按程序ID排序保存，遍历与合并结果不随注册顺序或运行变化。
+
+use std::collections::BTreeMap;
+
+use crate::traits::ProgramParser;
+use crate::types::{
+    AccountUpdate, InstructionUpdate, ParseResult, ParsedAccount, ParsedEvent, ParsedInstruction,
+    Prefilter, Pubkey,
+};
+
+/// 可按程序ID路由的数据更新
+pub trait RoutedUpdate {
+    /// 负责解析该更新的程序ID
+    fn route_program_id(&self) -> Pubkey;
+}
+
+impl RoutedUpdate for InstructionUpdate {
+    fn route_program_id(&self) -> Pubkey {
+        self.program
+    }
+}
+
+impl RoutedUpdate for AccountUpdate {
+    fn route_program_id(&self) -> Pubkey {
+        self.owner
+    }
+}
+
+/// 注册表中存放的解析器类型
+pub type BoxedProgramParser<I, O> = Box<dyn ProgramParser<Input = I, Output = O> + Send + Sync>;
+
+/// 指令解析器注册表
+pub type InstructionParserRegistry = ParserRegistry<InstructionUpdate, ParsedInstruction>;
+
+/// 账户解析器注册表
+pub type AccountParserRegistry = ParserRegistry<AccountUpdate, ParsedAccount>;
+
+/// 事件解析器注册表
+pub type EventParserRegistry = ParserRegistry<InstructionUpdate, ParsedEvent>;
+
+/// 解析器注册表
+///
+/// 每个程序ID对应一个解析器，重复注册时替换旧解析器
+pub struct ParserRegistry<I, O> {
+    parsers: BTreeMap<Pubkey, BoxedProgramParser<I, O>>,
+}
+
+impl<I, O> Default for ParserRegistry<I, O> {
+    fn default() -> Self {
+        Self {
+            parsers: BTreeMap::new(),
+        }
+    }
+}
+
+impl<I: RoutedUpdate, O> ParserRegistry<I, O> {
+    /// 创建空注册表
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// 注册解析器，返回同一程序ID下被替换的旧解析器
+    pub fn register<P>(&mut self, parser: P) -> Option<BoxedProgramParser<I, O>>
+    where
+        P: ProgramParser<Input = I, Output = O> + Send + Sync + 'static,
+    {
+        self.parsers.insert(parser.program_id(), Box::new(parser))
+    }
+
+    /// 链式注册解析器
+    pub fn with<P>(mut self, parser: P) -> Self
+    where
+        P: ProgramParser<Input = I, Output = O> + Send + Sync + 'static,
+    {
+        self.register(parser);
+        self
+    }
+
+    /// 按程序ID查找解析器
+    pub fn get(&self, program_id: &Pubkey) -> Option<&BoxedProgramParser<I, O>> {
+        self.parsers.get(program_id)
+    }
+
+    /// 已注册的解析器数量
+    pub fn len(&self) -> usize {
+        self.parsers.len()
+    }
+
+    /// 注册表是否为空
+    pub fn is_empty(&self) -> bool {
+        self.parsers.is_empty()
+    }
+
+    /// 按程序ID顺序遍历已注册的解析器
+    pub fn iter(&self) -> impl Iterator<Item = &BoxedProgramParser<I, O>> {
+        self.parsers.values()
+    }
+
+    /// 按程序ID顺序合并所有解析器的预过滤器（去重，见 [`Prefilter::merge`]）
+    pub fn prefilter(&self) -> Prefilter {
+        self.parsers
+            .values()
+            .map(|parser| parser.prefilter())
+            .fold(Prefilter::default(), Prefilter::merge)
+    }
+
+    /// 将更新路由到对应程序的解析器
+    ///
+    /// 没有注册该程序的解析器时返回 `None`
+    pub fn parse(&self, update: &I) -> Option<ParseResult<O>> {
+        self.parsers
+            .get(&update.route_program_id())
+            .map(|parser| parser.parse(update))
+    }
+
+    /// 依次解析多个更新，跳过未注册程序的更新
+    pub fn parse_all<'a, U>(&'a self, updates: U) -> impl Iterator<Item = ParseResult<O>> + 'a
+    where
+        U: IntoIterator<Item = &'a I>,
+        U::IntoIter: 'a,
+        I: 'a,
+    {
+        updates.into_iter().filter_map(move |update| self.parse(update))
+    }
+}
+
+impl<O> ParserRegistry<InstructionUpdate, O> {
+    /// 从本程序的内部指令中提取事件，路由到对应程序的解析器
+    pub fn try_parse_inner_event(&self, ix_update: &InstructionUpdate) -> Option<ParsedEvent> {
+        self.parsers
+            .get(&ix_update.program)
+            .and_then(|parser| parser.try_parse_inner_event(ix_update))
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use std::borrow::Cow;
+
+    use super::*;
+    use crate::traits::Parser;
+    use crate::types::{AccountFilter, ParseError};
+
+    /// 事件数据的首字节
+    const EVENT_TAG: u8 = 0xE4;
+
+    struct StubParser {
+        program_id: Pubkey,
+        name: &'static str,
+        prefilter: Prefilter,
+    }
+
+    impl StubParser {
+        fn new(name: &'static str, prefilter: impl FnOnce(Pubkey) -> Prefilter) -> Self {
+            let program_id = Pubkey::new_unique();
+            Self { program_id, name, prefilter: prefilter(program_id) }
+        }
+    }
+
+    impl Parser for StubParser {
+        type Input = InstructionUpdate;
+        type Output = ParsedInstruction;
+
+        fn id(&self) -> Cow<'_, str> {
+            Cow::Borrowed(self.name)
+        }
+
+        fn prefilter(&self) -> Prefilter {
+            self.prefilter.clone()
+        }
+
+        fn parse(&self, value: &InstructionUpdate) -> ParseResult<ParsedInstruction> {
+            match value.data.first() {
+                Some(&tag) => Ok(ParsedInstruction::new(self.program_id, self.name, tag)),
+                None => Err(ParseError::InvalidInstructionData("empty".to_string()).into()),
+            }
+        }
+    }
+
+    impl ProgramParser for StubParser {
+        fn program_id(&self) -> Pubkey {
+            self.program_id
+        }
+
+        fn try_parse_any_event(&self, data: &[u8]) -> Option<ParsedEvent> {
+            (data.first() == Some(&EVENT_TAG)).then(|| ParsedEvent::new(self.program_id, self.name, data[1..].to_vec()))
+        }
+    }
+
+    fn update(program: Pubkey, data: &[u8]) -> InstructionUpdate {
+        InstructionUpdate { program, data: data.to_vec(), accounts: vec![], stack_height: 1 }
+    }
+
+    fn registry() -> (InstructionParserRegistry, Pubkey, Pubkey) {
+        let alpha = StubParser::new("alpha", |id| {
+            Prefilter::builder()
+                .transaction_accounts([id])
+                .account_filter(AccountFilter::new(id).data_size(8))
+                .build()
+                .unwrap()
+        });
+        let beta = StubParser::new("beta", |id| Prefilter::builder().transaction_accounts([id]).account_owners([id]).build().unwrap());
+        let ids = (alpha.program_id, beta.program_id);
+        (InstructionParserRegistry::new().with(alpha).with(beta), ids.0, ids.1)
+    }
+
+    #[test]
+    fn routes_updates_by_program_id() {
+        let (mut registry, alpha, beta) = registry();
+        assert_eq!(registry.len(), 2);
+        assert_eq!(registry.get(&alpha).unwrap().id(), "alpha");
+
+        let parsed = registry.parse(&update(beta, &[7])).unwrap().unwrap();
+        assert_eq!((parsed.program_id(), parsed.name()), (beta, "beta"));
+        assert_eq!(parsed.try_as::<u8>(), Some(&7));
+        assert!(registry.parse(&update(alpha, &[])).unwrap().is_err());
+        assert!(registry.parse(&update(Pubkey::new_unique(), &[7])).is_none());
+
+        // 同一程序ID重复注册时替换旧解析器
+        let replaced = registry.register(StubParser { program_id: alpha, name: "gamma", prefilter: Prefilter::default() });
+        assert_eq!(replaced.unwrap().id(), "alpha");
+        assert_eq!(registry.len(), 2);
+        assert_eq!(registry.parse(&update(alpha, &[1])).unwrap().unwrap().name(), "gamma");
+    }
+
+    #[test]
+    fn parse_all_skips_unregistered_programs() {
+        let (registry, alpha, beta) = registry();
+        let updates = [update(alpha, &[1]), update(Pubkey::new_unique(), &[2]), update(beta, &[3]), update(beta, &[])];
+        let results: Vec<_> = registry.parse_all(&updates).collect();
+        assert_eq!(results.len(), 3);
+        let parsed: Vec<_> = results.iter().flatten().map(|ix| (ix.name(), *ix.try_as::<u8>().unwrap())).collect();
+        assert_eq!(parsed, [("alpha", 1), ("beta", 3)]);
+        assert!(results[2].is_err());
+    }
+
+    #[test]
+    fn prefilter_merges_all_parsers() {
+        let (registry, alpha, beta) = registry();
+        let prefilter = registry.prefilter();
+        // 按程序ID顺序合并，与注册顺序无关
+        let mut expected = vec![alpha, beta];
+        expected.sort();
+        assert_eq!(prefilter.transaction_accounts, expected);
+        assert_eq!(prefilter.account_owners, expected);
+        let reversed = InstructionParserRegistry::new()
+            .with(StubParser { program_id: beta, name: "beta", prefilter: registry.get(&beta).unwrap().prefilter() })
+            .with(StubParser { program_id: alpha, name: "alpha", prefilter: registry.get(&alpha).unwrap().prefilter() });
+        assert_eq!(reversed.prefilter(), prefilter);
+        let ids: Vec<_> = reversed.iter().map(|parser| parser.program_id()).collect();
+        assert_eq!(ids, expected);
+        // beta 无条件订阅其全部账户，alpha 保留数据长度过滤
+        assert_eq!(prefilter.account_filters, [AccountFilter::new(alpha).data_size(8)]);
+        assert!(InstructionParserRegistry::new().prefilter().account_owners.is_empty());
+    }
+
+    #[test]
+    fn inner_events_are_routed_to_the_emitting_program() {
+        let (registry, alpha, beta) = registry();
+        let event = registry.try_parse_inner_event(&update(beta, &[EVENT_TAG, 5])).unwrap();
+        assert_eq!((event.program_id(), event.name()), (beta, "beta"));
+        assert_eq!(event.try_as::<Vec<u8>>(), Some(&vec![5]));
+        assert!(registry.try_parse_inner_event(&update(alpha, &[1])).is_none());
+        assert!(registry.try_parse_inner_event(&update(Pubkey::new_unique(), &[EVENT_TAG])).is_none());
+    }
+}
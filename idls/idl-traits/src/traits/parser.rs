@@ -1,7 +1,7 @@
 //! 核心解析器 trait 定义
 
 use std::borrow::Cow;
-use crate::types::{Prefilter, Pubkey, ParseResult, ParsedEvent};
+use crate::types::{InstructionUpdate, Prefilter, Pubkey, ParseResult, ParsedEvent};
 
 /// 基础解析器 trait
 /// 
@@ -15,7 +15,7 @@ pub trait Parser {
     /// 解析器唯一标识符
     /// 
     /// 用于注册和查找解析器实例
-    fn id(&self) -> Cow<str>;
+    fn id(&self) -> Cow<'_, str>;
 
     /// 预过滤器配置
     /// 
@@ -45,15 +45,28 @@ pub trait ProgramParser: Parser {
     /// * `None` - 不支持的事件类型或解析失败 (默认实现)
     /// 
     /// # Example
-    /// ```rust
-    /// let parser = PumpFunInstructionParser;
+    /// ```no_run
+    /// # use idl_traits::ProgramParser;
+    /// # fn handle(parser: impl ProgramParser, cpi_log_data: Vec<u8>) {
     /// if let Some(event) = parser.try_parse_any_event(&cpi_log_data) {
     ///     // 处理解析到的事件
+    /// #   let _ = event;
     /// }
+    /// # }
     /// ```
     fn try_parse_any_event(&self, _data: &[u8]) -> Option<ParsedEvent> {
         None  // 默认实现：不支持事件解析
     }
+
+    /// 从内部指令中提取事件 (`emit_cpi!` 的 Self CPI)
+    /// 
+    /// 非本程序的指令返回 `None`，其余交给 `try_parse_any_event`
+    fn try_parse_inner_event(&self, ix_update: &InstructionUpdate) -> Option<ParsedEvent> {
+        if ix_update.program != self.program_id() {
+            return None;
+        }
+        self.try_parse_any_event(&ix_update.data)
+    }
 }
 
 // 移除了冗余的辅助 trait，直接使用 Parser 和 ProgramParser trait
\ No newline at end of file
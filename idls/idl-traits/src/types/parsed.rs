@@ -1,44 +1,10 @@
 //! 解析结果类型定义
 
-use crate::types::Pubkey;
-
-/// 统一的解析事件类型
-#[derive(Debug)]
-pub enum ParsedEvent {
-    /// 自定义事件类型 - 使用 Box<dyn Any> 支持任意事件类型
-    Custom(Box<dyn std::any::Any + Send + Sync>),
-}
-
-// 手动实现 Clone
-impl Clone for ParsedEvent {
-    fn clone(&self) -> Self {
-        match self {
-            ParsedEvent::Custom(_) => {
-                // 无法 clone Any 类型，创建空占位符
-                ParsedEvent::Custom(Box::new(()))
-            }
-        }
-    }
-}
+use std::any::Any;
+use std::borrow::Cow;
+use std::sync::Arc;
 
-impl ParsedEvent {
-    /// 尝试转换为特定事件类型
-    pub fn try_as<T: 'static>(&self) -> Option<&T> {
-        match self {
-            ParsedEvent::Custom(custom) => custom.downcast_ref::<T>(),
-        }
-    }
-    
-    /// 检查是否为特定事件类型
-    pub fn is_type<T: 'static>(&self) -> bool {
-        matches!(self, ParsedEvent::Custom(custom) if custom.is::<T>())
-    }
-    
-    /// 创建自定义事件
-    pub fn custom<T: 'static + Send + Sync>(event: T) -> Self {
-        ParsedEvent::Custom(Box::new(event))
-    }
-}
+use crate::types::Pubkey;
 
 /// 解析结果容器
 #[derive(Debug, Clone)]
@@ -51,35 +17,168 @@ pub struct ParsedResult {
     pub parser_name: String,
 }
 
-/// 解析指令类型 (简化版)
-#[derive(Debug)]
-pub enum ParsedInstruction {
-    /// 自定义指令类型
-    Custom(Box<dyn std::any::Any + Send + Sync>),
+/// 载荷转 JSON 的函数，在载荷类型仍然已知时捕获
+#[cfg(feature = "serde")]
+type ToJson = fn(&(dyn Any + Send + Sync)) -> Option<serde_json::Value>;
+
+#[cfg(feature = "serde")]
+fn payload_to_json<T: serde::Serialize + 'static>(payload: &(dyn Any + Send + Sync)) -> Option<serde_json::Value> {
+    payload.downcast_ref::<T>().and_then(|payload| serde_json::to_value(payload).ok())
 }
 
-// 手动实现 Clone
-impl Clone for ParsedInstruction {
-    fn clone(&self) -> Self {
-        match self {
-            ParsedInstruction::Custom(_) => {
-                // 无法 clone Any 类型，创建空占位符
-                ParsedInstruction::Custom(Box::new(()))
+/// 定义携带程序ID、名称与类型擦除载荷的解析结果类型
+macro_rules! parsed_payload {
+    ($(#[$meta:meta])* $name:ident, $kind:literal) => {
+        $(#[$meta])*
+        #[derive(Clone)]
+        pub struct $name {
+            program_id: Pubkey,
+            name: Cow<'static, str>,
+            payload: Arc<dyn Any + Send + Sync>,
+            #[cfg(feature = "serde")]
+            to_json: Option<ToJson>,
+        }
+
+        impl std::fmt::Debug for $name {
+            fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+                f.debug_struct(stringify!($name))
+                    .field("program_id", &self.program_id)
+                    .field("name", &self.name)
+                    .finish_non_exhaustive()
             }
         }
-    }
-}
 
-impl ParsedInstruction {
-    /// 尝试转换为特定指令类型
-    pub fn try_as<T: 'static>(&self) -> Option<&T> {
-        match self {
-            ParsedInstruction::Custom(custom) => custom.downcast_ref::<T>(),
+        impl $name {
+            #[doc = concat!("创建解析", $kind)]
+            pub fn new<T: Any + Send + Sync>(
+                program_id: Pubkey,
+                name: impl Into<Cow<'static, str>>,
+                payload: T,
+            ) -> Self {
+                Self {
+                    program_id,
+                    name: name.into(),
+                    payload: Arc::new(payload),
+                    #[cfg(feature = "serde")]
+                    to_json: None,
+                }
+            }
+
+            #[doc = concat!("创建可通过 [`Self::to_json`] 序列化的解析", $kind)]
+            #[cfg(feature = "serde")]
+            pub fn serializable<T: Any + Send + Sync + serde::Serialize>(
+                program_id: Pubkey,
+                name: impl Into<Cow<'static, str>>,
+                payload: T,
+            ) -> Self {
+                Self {
+                    to_json: Some(payload_to_json::<T>),
+                    ..Self::new(program_id, name, payload)
+                }
+            }
+
+            /// 来源程序ID
+            pub fn program_id(&self) -> Pubkey {
+                self.program_id
+            }
+
+            #[doc = concat!("IDL 中声明的", $kind, "名称")]
+            pub fn name(&self) -> &str {
+                &self.name
+            }
+
+            #[doc = concat!("尝试转换为特定", $kind, "类型")]
+            pub fn try_as<T: 'static>(&self) -> Option<&T> {
+                self.payload.downcast_ref::<T>()
+            }
+
+            #[doc = concat!("检查是否为特定", $kind, "类型")]
+            pub fn is_type<T: 'static>(&self) -> bool {
+                self.payload.is::<T>()
+            }
+
+            /// 取出共享的载荷，类型不匹配时原样返回
+            pub fn into_inner<T: Any + Send + Sync>(self) -> Result<Arc<T>, Self> {
+                match self.payload.downcast::<T>() {
+                    Ok(payload) => Ok(payload),
+                    Err(payload) => Err(Self { payload, ..self }),
+                }
+            }
+
+            /// 载荷的 JSON 表示；以 [`Self::new`] 创建（载荷不可序列化）时返回 `None`
+            #[cfg(feature = "serde")]
+            pub fn to_json(&self) -> Option<serde_json::Value> {
+                self.to_json.and_then(|to_json| to_json(self.payload.as_ref()))
+            }
         }
+    };
+}
+
+parsed_payload!(
+    /// 统一的解析指令类型
+    ///
+    /// 携带来源程序ID、IDL 中的指令名称以及类型擦除的指令载荷
+    /// （生成代码中的 `ProgramInstruction`），通过 `try_as` / `into_inner` 恢复强类型
+    ParsedInstruction,
+    "指令"
+);
+
+parsed_payload!(
+    /// 统一的解析账户类型
+    ///
+    /// 携带所属程序ID、IDL 中的账户名称以及类型擦除的账户载荷
+    /// （生成代码中的 `ProgramAccount`），通过 `try_as` / `into_inner` 恢复强类型
+    ParsedAccount,
+    "账户"
+);
+
+parsed_payload!(
+    /// 统一的解析事件类型
+    ///
+    /// 携带来源程序ID、IDL 中的事件名称以及类型擦除的事件载荷
+    /// （生成代码中的 `ProgramEvent`），通过 `try_as` / `into_inner` 恢复强类型
+    ParsedEvent,
+    "事件"
+);
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[derive(Debug, PartialEq)]
+    #[cfg_attr(feature = "serde", derive(serde::Serialize))]
+    struct Trade {
+        amount: u64,
     }
-    
-    /// 创建自定义指令
-    pub fn custom<T: 'static + Send + Sync>(instruction: T) -> Self {
-        ParsedInstruction::Custom(Box::new(instruction))
+
+    #[test]
+    fn event_exposes_the_same_api_as_instructions_and_accounts() {
+        let program_id = Pubkey::new_unique();
+        let event = ParsedEvent::new(program_id, "TradeEvent", Trade { amount: 7 });
+        assert_eq!((event.program_id(), event.name()), (program_id, "TradeEvent"));
+        assert!(event.is_type::<Trade>() && !event.is_type::<u64>());
+        assert_eq!(event.try_as::<Trade>(), Some(&Trade { amount: 7 }));
+        assert!(format!("{:?}", event).starts_with("ParsedEvent {"));
+
+        let event = event.into_inner::<u64>().unwrap_err();
+        assert_eq!(event.name(), "TradeEvent");
+        assert_eq!(*event.into_inner::<Trade>().unwrap(), Trade { amount: 7 });
+
+        let instruction = ParsedInstruction::new(program_id, "Buy", Trade { amount: 1 });
+        let account = ParsedAccount::new(program_id, "Pool", Trade { amount: 2 });
+        assert_eq!(instruction.into_inner::<Trade>().unwrap().amount, 1);
+        assert_eq!(account.into_inner::<Trade>().unwrap().amount, 2);
     }
-}
\ No newline at end of file
+
+    #[test]
+    #[cfg(feature = "serde")]
+    fn only_serializable_payloads_convert_to_json() {
+        let program_id = Pubkey::new_unique();
+        assert_eq!(ParsedEvent::new(program_id, "TradeEvent", Trade { amount: 7 }).to_json(), None);
+        let event = ParsedEvent::serializable(program_id, "TradeEvent", Trade { amount: 7 });
+        assert_eq!(event.to_json(), Some(serde_json::json!({ "amount": 7 })));
+        // 载荷被取出后再恢复仍保留序列化函数
+        let event = event.into_inner::<u64>().unwrap_err();
+        assert_eq!(event.to_json(), Some(serde_json::json!({ "amount": 7 })));
+    }
+}
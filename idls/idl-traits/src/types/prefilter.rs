@@ -1,12 +1,21 @@
-//! 预过滤器类型定义 (简化版)
+//! 预过滤器类型定义
+//!
+//! 交易按账户过滤；账户按 owner 过滤，并可为每个 owner 附加数据长度与 memcmp 条件，
+//! 让订阅在服务端只推送已知类型的账户，而不是推送 owner 下的全部账户再由客户端筛选。
 
 use crate::types::Pubkey;
 
+/// 单个 memcmp 条件的最大字节数（与 RPC / Yellowstone 的限制一致）
+pub const MAX_MEMCMP_BYTES: usize = 128;
+
 /// 预过滤器 - 用于 Yellowstone 数据过滤
-#[derive(Debug, Clone)]
+#[derive(Debug, Clone, Default, PartialEq, Eq)]
 pub struct Prefilter {
     pub transaction_accounts: Vec<Pubkey>,
     pub account_owners: Vec<Pubkey>,
+    /// 按数据过滤的账户订阅：同一 owner 的多个过滤器任一匹配即可，
+    /// 没有过滤器的 owner 接收其全部账户
+    pub account_filters: Vec<AccountFilter>,
 }
 
 impl Prefilter {
@@ -14,6 +23,103 @@ impl Prefilter {
     pub fn builder() -> PrefilterBuilder {
         PrefilterBuilder::default()
     }
+
+    /// 合并另一个预过滤器（去重）
+    ///
+    /// 任一方对某个 owner 不按数据过滤时，合并结果也不再过滤该 owner。
+    pub fn merge(mut self, other: Prefilter) -> Prefilter {
+        let unfiltered: Vec<Pubkey> = self
+            .unfiltered_owners()
+            .chain(other.unfiltered_owners())
+            .collect();
+        for account in other.transaction_accounts {
+            if !self.transaction_accounts.contains(&account) {
+                self.transaction_accounts.push(account);
+            }
+        }
+        for owner in other.account_owners {
+            if !self.account_owners.contains(&owner) {
+                self.account_owners.push(owner);
+            }
+        }
+        for filter in other.account_filters {
+            if !self.account_filters.contains(&filter) {
+                self.account_filters.push(filter);
+            }
+        }
+        self.account_filters.retain(|filter| !unfiltered.contains(&filter.owner));
+        self
+    }
+
+    /// 指定 owner 的数据过滤器
+    pub fn filters_for<'a>(&'a self, owner: &'a Pubkey) -> impl Iterator<Item = &'a AccountFilter> + 'a {
+        self.account_filters.iter().filter(move |filter| &filter.owner == owner)
+    }
+
+    /// 没有任何数据过滤器的 owner
+    fn unfiltered_owners(&self) -> impl Iterator<Item = Pubkey> + '_ {
+        self.account_owners
+            .iter()
+            .filter(|owner| self.filters_for(owner).next().is_none())
+            .copied()
+    }
+}
+
+/// 账户数据的 memcmp 条件
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct Memcmp {
+    pub offset: usize,
+    pub bytes: Vec<u8>,
+}
+
+/// 一个 owner 的账户数据过滤器，所有条件同时满足时匹配
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct AccountFilter {
+    pub owner: Pubkey,
+    /// 数据长度必须等于该值
+    pub data_size: Option<u64>,
+    pub memcmp: Vec<Memcmp>,
+}
+
+impl AccountFilter {
+    /// 创建无条件的过滤器
+    pub fn new(owner: Pubkey) -> Self {
+        Self { owner, data_size: None, memcmp: Vec::new() }
+    }
+
+    /// 要求数据长度等于 `size`
+    pub fn data_size(mut self, size: u64) -> Self {
+        self.data_size = Some(size);
+        self
+    }
+
+    /// 要求从 `offset` 开始的数据等于 `bytes`
+    pub fn memcmp(mut self, offset: usize, bytes: impl Into<Vec<u8>>) -> Self {
+        self.memcmp.push(Memcmp { offset, bytes: bytes.into() });
+        self
+    }
+
+    /// 是否没有任何条件（匹配 owner 的全部账户）
+    pub fn is_unconstrained(&self) -> bool {
+        self.data_size.is_none() && self.memcmp.is_empty()
+    }
+
+    /// 检查账户数据是否满足全部条件，供客户端复核
+    pub fn matches(&self, data: &[u8]) -> bool {
+        self.data_size.is_none_or(|size| data.len() as u64 == size)
+            && self.memcmp.iter().all(|memcmp| {
+                data.get(memcmp.offset..memcmp.offset + memcmp.bytes.len()) == Some(memcmp.bytes.as_slice())
+            })
+    }
+}
+
+/// 预过滤器构建错误
+#[derive(Debug, thiserror::Error)]
+pub enum PrefilterError {
+    #[error("memcmp 条件为空 (owner {owner}, offset {offset})")]
+    EmptyMemcmp { owner: Pubkey, offset: usize },
+    #[error("memcmp 条件超过 {MAX_MEMCMP_BYTES} 字节 (owner {owner}, offset {offset}, {len} 字节)")]
+    MemcmpTooLong { owner: Pubkey, offset: usize, len: usize },
 }
 
 /// 预过滤器构建器
@@ -21,18 +127,19 @@ impl Prefilter {
 pub struct PrefilterBuilder {
     transaction_accounts: Vec<Pubkey>,
     account_owners: Vec<Pubkey>,
+    account_filters: Vec<AccountFilter>,
 }
 
 impl PrefilterBuilder {
     /// 添加交易账户过滤
-    pub fn transaction_accounts<I>(mut self, accounts: I) -> Self 
+    pub fn transaction_accounts<I>(mut self, accounts: I) -> Self
     where
         I: IntoIterator<Item = Pubkey>,
     {
         self.transaction_accounts.extend(accounts);
         self
     }
-    
+
     /// 添加账户所有者过滤
     pub fn account_owners<I>(mut self, owners: I) -> Self
     where
@@ -41,12 +148,56 @@ impl PrefilterBuilder {
         self.account_owners.extend(owners);
         self
     }
-    
+
+    /// 添加账户数据过滤器，其 owner 自动加入账户所有者过滤
+    pub fn account_filter(mut self, filter: AccountFilter) -> Self {
+        self.account_filters.push(filter);
+        self
+    }
+
     /// 构建预过滤器
+    ///
+    /// 某个 owner 存在无条件的过滤器时，该 owner 的全部过滤器被丢弃（接收全部账户）。
     pub fn build(self) -> Result<Prefilter, Box<dyn std::error::Error>> {
+        for filter in &self.account_filters {
+            for memcmp in &filter.memcmp {
+                if memcmp.bytes.is_empty() {
+                    return Err(PrefilterError::EmptyMemcmp { owner: filter.owner, offset: memcmp.offset }.into());
+                }
+                if memcmp.bytes.len() > MAX_MEMCMP_BYTES {
+                    return Err(PrefilterError::MemcmpTooLong {
+                        owner: filter.owner,
+                        offset: memcmp.offset,
+                        len: memcmp.bytes.len(),
+                    }
+                    .into());
+                }
+            }
+        }
+
+        let mut account_owners = Vec::new();
+        for owner in self.account_owners.into_iter().chain(self.account_filters.iter().map(|f| f.owner)) {
+            if !account_owners.contains(&owner) {
+                account_owners.push(owner);
+            }
+        }
+        let unconstrained: Vec<Pubkey> = self
+            .account_filters
+            .iter()
+            .filter(|filter| filter.is_unconstrained())
+            .map(|filter| filter.owner)
+            .collect();
+        let mut account_filters = Vec::new();
+        for filter in self.account_filters {
+            if !unconstrained.contains(&filter.owner) && !account_filters.contains(&filter) {
+                account_filters.push(filter);
+            }
+        }
+
         Ok(Prefilter {
             transaction_accounts: self.transaction_accounts,
-            account_owners: self.account_owners,
+            account_owners,
+            account_filters,
         })
     }
-}
\ No newline at end of file
+}
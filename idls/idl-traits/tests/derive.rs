@@ -0,0 +1,92 @@
+//! 派生宏展开测试：本文件的 crate 根模拟生成 crate 的 `ID` 与 `parsers` 模块
+
+use idl_traits::{EventParser, InstructionParser, InstructionUpdate, Parser, ProgramParser, Pubkey};
+
+pub const ID: Pubkey = Pubkey::new_from_array([7; 32]);
+
+pub mod parsers {
+    pub mod events {
+        #[derive(Debug, PartialEq)]
+        #[cfg_attr(feature = "serde", derive(serde::Serialize))]
+        pub enum ProgramEvent {
+            Trade { amount: u64 },
+        }
+
+        impl ProgramEvent {
+            pub fn try_parse(data: &[u8]) -> Result<Self, String> {
+                match data {
+                    [1, amount @ ..] => Ok(Self::Trade { amount: amount.iter().map(|&b| u64::from(b)).sum() }),
+                    _ => Err("unknown event".to_string()),
+                }
+            }
+
+            pub fn name(&self) -> &'static str {
+                "Trade"
+            }
+        }
+    }
+
+    pub mod instructions {
+        #[derive(Debug, PartialEq)]
+        #[cfg_attr(feature = "serde", derive(serde::Serialize))]
+        pub enum ProgramInstruction {
+            Buy,
+        }
+
+        impl ProgramInstruction {
+            pub fn try_parse(data: &[u8], _accounts: &[idl_traits::Pubkey]) -> Result<Self, String> {
+                match data {
+                    [0] => Ok(Self::Buy),
+                    _ => Err("unknown instruction".to_string()),
+                }
+            }
+
+            pub fn name(&self) -> &'static str {
+                "Buy"
+            }
+        }
+    }
+}
+
+use parsers::events::ProgramEvent;
+
+#[derive(EventParser)]
+struct Events;
+
+#[derive(InstructionParser)]
+#[instruction_parser(events)]
+struct Instructions;
+
+fn update(program: Pubkey, data: &[u8]) -> InstructionUpdate {
+    InstructionUpdate { program, data: data.to_vec(), accounts: Vec::new(), stack_height: 2 }
+}
+
+#[test]
+fn event_parser_wraps_events_with_program_id_and_name() {
+    let event = Events.parse(&update(ID, &[1, 2, 3])).unwrap();
+    assert_eq!((event.program_id(), event.name()), (ID, "Trade"));
+    assert_eq!(event.try_as::<ProgramEvent>(), Some(&ProgramEvent::Trade { amount: 5 }));
+    assert!(Events.parse(&update(Pubkey::new_unique(), &[1])).is_err());
+
+    let logged = Events.try_parse_any_event(&[1, 4]).unwrap();
+    assert_eq!(*logged.into_inner::<ProgramEvent>().unwrap(), ProgramEvent::Trade { amount: 4 });
+    assert!(Events.try_parse_any_event(&[9]).is_none());
+}
+
+#[test]
+fn instruction_parser_with_events_parses_inner_events() {
+    assert_eq!(Instructions.parse(&update(ID, &[0])).unwrap().name(), "Buy");
+    let event = Instructions.try_parse_inner_event(&update(ID, &[1, 6])).unwrap();
+    assert_eq!(event.try_as::<ProgramEvent>(), Some(&ProgramEvent::Trade { amount: 6 }));
+    assert!(Instructions.try_parse_inner_event(&update(Pubkey::new_unique(), &[1, 6])).is_none());
+}
+
+#[test]
+#[cfg(feature = "serde")]
+fn derived_events_are_serializable() {
+    let expected = serde_json::json!({ "Trade": { "amount": 5 } });
+    assert_eq!(Events.parse(&update(ID, &[1, 2, 3])).unwrap().to_json(), Some(expected.clone()));
+    assert_eq!(Events.try_parse_any_event(&[1, 5]).unwrap().to_json(), Some(expected.clone()));
+    assert_eq!(Instructions.try_parse_any_event(&[1, 5]).unwrap().to_json(), Some(expected));
+    assert_eq!(Instructions.parse(&update(ID, &[0])).unwrap().to_json(), Some(serde_json::json!("Buy")));
+}
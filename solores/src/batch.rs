@@ -0,0 +1,505 @@
+//! 批量生成库 API
+//!
+//! [`run_batch`] 扫描目录中的 IDL 文件并逐个生成 crate，通过回调报告进度，
+//! 返回结构化的 [`BatchResult`]。命令行 `--batch` 模式与 GUI、构建服务等
+//! 嵌入方共用这一实现，无需解析日志即可显示进度条和处理失败。
+//!
+//! ```ignore
+//! let opts = solores::batch::BatchOptions::with_args("idls/", "generated/", ["--generate-parser"])?;
+//! let result = solores::batch::run_batch(opts, |event| {
+//!     if let solores::batch::BatchEvent::FileStarted { index, total, .. } = event {
+//!         println!("{}/{}", index + 1, total);
+//!     }
+//! })?;
+//! assert!(result.is_success());
+//! ```
+//!
+//! 每个 crate 生成完成后，批量输出目录中的断点文件 [`CHECKPOINT_FILE`] 被原子地更新。
+//! 运行被中断（Ctrl-C、OOM）后以 `--resume` 重新运行：IDL 与生成选项均未变化、且输出完整
+//! （存在 Cargo.toml、src/lib.rs 与各模块目录的 mod.rs）的 crate 被跳过，其余重新生成。
+
+use std::collections::BTreeMap;
+use std::ffi::OsString;
+use std::fs;
+use std::path::{Path, PathBuf};
+
+use clap::Parser;
+use serde::{Deserialize, Serialize};
+
+use crate::cargo::idl_sha256;
+use crate::error::SoloresError;
+use crate::idl_format::{IdlFormat, IdlFormatEnum};
+use crate::warnings::Warning;
+use crate::workspace::{add_workspace_member, finalize_workspace, validate_workspace_config};
+use crate::Args;
+
+/// 批量输出目录的组织方式
+#[derive(clap::ValueEnum, Debug, Default, Clone, Copy, PartialEq, Eq)]
+pub enum GroupOutputBy {
+    /// 所有 crate 直接位于输出目录下
+    #[default]
+    Flat,
+    /// 按程序地址分组，缺少地址的 IDL 归入 `unknown`
+    Address,
+    /// 按 IDL 文件名前缀（第一个 `_` / `-` 之前的部分，如 `raydium_clmm.json` → `raydium`）分组
+    Name,
+}
+
+impl GroupOutputBy {
+    /// 相对于批量输出目录的分组子目录，`Flat` 时为 None
+    pub fn group_dir(self, idl_path: &Path, idl: &IdlFormatEnum) -> Option<String> {
+        match self {
+            GroupOutputBy::Flat => None,
+            GroupOutputBy::Address => Some(idl.program_address().unwrap_or("unknown").to_string()),
+            GroupOutputBy::Name => {
+                let stem = idl_path.file_stem().map(|stem| stem.to_string_lossy()).unwrap_or_default();
+                let prefix = stem.split(['_', '-']).next().unwrap_or_default().to_lowercase();
+                Some(if prefix.is_empty() { "unknown".to_string() } else { prefix })
+            }
+        }
+    }
+}
+
+/// 批量生成选项
+#[derive(Debug, Clone)]
+pub struct BatchOptions {
+    /// 存放 IDL 文件的目录
+    pub input_dir: PathBuf,
+    /// 生成 crate 的输出目录
+    pub output_dir: PathBuf,
+    /// 仅包含的 JSON 文件名，支持 `*` 通配符，为空时包含全部
+    pub include: Vec<String>,
+    /// 排除的 JSON 文件名，支持 `*` 通配符
+    pub exclude: Vec<String>,
+    /// 跳过断点中记录为已完成且输出完整的 crate（`--resume`）
+    pub resume: bool,
+    /// 每个 IDL 共用的生成参数；IDL 路径、crate 名称与输出目录由批量模式决定
+    pub args: Args,
+}
+
+impl BatchOptions {
+    /// 使用默认生成参数
+    pub fn new(input_dir: impl Into<PathBuf>, output_dir: impl Into<PathBuf>) -> Self {
+        Self::with_args(input_dir, output_dir, std::iter::empty::<OsString>())
+            .expect("默认生成参数必定有效")
+    }
+
+    /// `generator_args` 为命令行生成参数（如 `--generate-parser`）
+    pub fn with_args<I, S>(
+        input_dir: impl Into<PathBuf>,
+        output_dir: impl Into<PathBuf>,
+        generator_args: I,
+    ) -> Result<Self, SoloresError>
+    where
+        I: IntoIterator<Item = S>,
+        S: Into<OsString>,
+    {
+        let input_dir = input_dir.into();
+        let args = Args::try_parse_from(
+            [OsString::from("solores"), input_dir.clone().into_os_string()]
+                .into_iter()
+                .chain(generator_args.into_iter().map(Into::into)),
+        )
+        .map_err(|e| SoloresError::ValidationError {
+            message: format!("生成参数无效: {}", e),
+            field_path: None,
+            expected: None,
+            actual: None,
+        })?;
+        Ok(Self {
+            input_dir,
+            output_dir: output_dir.into(),
+            include: Vec::new(),
+            exclude: Vec::new(),
+            resume: false,
+            args,
+        })
+    }
+
+    /// 从命令行参数构造（`--batch` 模式）
+    pub fn from_args(args: Args) -> Self {
+        Self {
+            input_dir: args.idl_path.clone(),
+            output_dir: args.batch_output_dir.clone(),
+            include: crate::parse_file_patterns(&args.batch_include),
+            exclude: crate::parse_file_patterns(&args.batch_exclude),
+            resume: args.resume,
+            args,
+        }
+    }
+
+    /// 合并为流水线使用的参数
+    fn into_args(self) -> Args {
+        Args {
+            idl_path: self.input_dir,
+            batch_output_dir: self.output_dir,
+            batch_include: self.include.join(","),
+            batch_exclude: self.exclude.join(","),
+            resume: self.resume,
+            batch: true,
+            ..self.args
+        }
+    }
+}
+
+/// 批量生成进度事件
+#[derive(Debug)]
+pub enum BatchEvent<'a> {
+    /// 扫描完成，共 `total` 个 IDL 待处理
+    Started { total: usize },
+    /// 开始处理第 `index` 个（从 0 开始）IDL
+    FileStarted { index: usize, total: usize, idl_path: &'a Path },
+    /// IDL 生成成功
+    FileSucceeded { index: usize, total: usize, generated: &'a GeneratedCrate },
+    /// `--resume` 时 IDL 已在之前的运行中生成且输出完整，未重新生成
+    FileSkipped { index: usize, total: usize, generated: &'a GeneratedCrate },
+    /// IDL 生成失败，继续处理其余文件
+    FileFailed { index: usize, total: usize, failure: &'a BatchFailure },
+    /// 全部处理完成
+    Finished { succeeded: usize, failed: usize },
+}
+
+/// 生成成功的 crate
+#[derive(Debug)]
+pub struct GeneratedCrate {
+    pub idl_path: PathBuf,
+    pub crate_name: String,
+    pub output_dir: PathBuf,
+    /// 未屏蔽的生成警告；`--resume` 跳过的 crate 为空
+    pub warnings: Vec<Warning>,
+}
+
+/// 生成失败的 IDL
+#[derive(Debug)]
+pub struct BatchFailure {
+    pub idl_path: PathBuf,
+    pub error: SoloresError,
+}
+
+/// 批量生成结果
+#[derive(Debug, Default)]
+pub struct BatchResult {
+    pub generated: Vec<GeneratedCrate>,
+    pub failures: Vec<BatchFailure>,
+    /// 启用 workspace 时生成的 workspace 目录
+    pub workspace_dir: Option<PathBuf>,
+    /// 统一库模式下生成的库目录
+    pub unified_library_dir: Option<PathBuf>,
+}
+
+impl BatchResult {
+    /// 没有失败的 IDL
+    pub fn is_success(&self) -> bool {
+        self.failures.is_empty()
+    }
+
+    /// 全部 crate 的警告总数
+    pub fn warning_count(&self) -> usize {
+        self.generated.iter().map(|c| c.warnings.len()).sum()
+    }
+}
+
+/// 批量输出目录中的断点文件名
+pub const CHECKPOINT_FILE: &str = ".solores-batch.json";
+
+/// 批量生成断点：已完成的 crate 与生成时的哈希
+#[derive(Debug, Default, Serialize, Deserialize)]
+struct Checkpoint {
+    /// 生成选项哈希，与本次运行不同时断点作废
+    options_hash: String,
+    /// 按 IDL 路径记录的已完成 crate
+    completed: BTreeMap<String, CompletedCrate>,
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+struct CompletedCrate {
+    /// 生成时 IDL 的 sha256，格式同生成文件头
+    idl_hash: String,
+    crate_name: String,
+    output_dir: PathBuf,
+}
+
+impl Checkpoint {
+    /// 读取断点；文件不存在、无法解析或选项已变化时返回空断点
+    fn load(output_dir: &Path, options_hash: &str) -> Self {
+        let path = output_dir.join(CHECKPOINT_FILE);
+        let empty = Self { options_hash: options_hash.to_string(), ..Self::default() };
+        let Ok(content) = fs::read_to_string(&path) else {
+            log::info!("📍 {} 中没有断点，生成全部 IDL", output_dir.display());
+            return empty;
+        };
+        match serde_json::from_str::<Self>(&content) {
+            Ok(checkpoint) if checkpoint.options_hash == options_hash => checkpoint,
+            Ok(_) => {
+                log::warn!("⚠️ 生成选项与断点记录的不同，重新生成全部 IDL");
+                empty
+            }
+            Err(e) => {
+                log::warn!("⚠️ 断点文件 {} 无法解析（{}），重新生成全部 IDL", path.display(), e);
+                empty
+            }
+        }
+    }
+
+    /// 先写临时文件再重命名，中断时断点文件保持上一次的完整内容
+    fn save(&self, output_dir: &Path) -> Result<(), SoloresError> {
+        let path = output_dir.join(CHECKPOINT_FILE);
+        let temp = output_dir.join(format!("{}.tmp", CHECKPOINT_FILE));
+        let content = serde_json::to_string_pretty(self).map_err(|e| SoloresError::ValidationError {
+            message: format!("断点序列化失败: {}", e),
+            field_path: None,
+            expected: None,
+            actual: None,
+        })?;
+        fs::write(&temp, content + "\n")
+            .map_err(|e| SoloresError::file_operation_error("写入批量断点", temp.display().to_string(), e))?;
+        fs::rename(&temp, &path)
+            .map_err(|e| SoloresError::file_operation_error("替换批量断点", path.display().to_string(), e))
+    }
+
+    /// 可以跳过的已完成 crate：IDL 未变化且输出完整
+    fn resumable(&self, idl_path: &Path, idl_hash: &str) -> Option<&CompletedCrate> {
+        let completed = self.completed.get(&idl_path.display().to_string())?;
+        if completed.idl_hash != idl_hash {
+            log::info!("🔄 {} 在上次运行后已修改，重新生成", idl_path.display());
+            return None;
+        }
+        match incomplete_reason(&completed.output_dir, idl_hash) {
+            Some(reason) => {
+                log::warn!("⚠️ {} 的输出不完整（{}），重新生成", completed.output_dir.display(), reason);
+                None
+            }
+            None => Some(completed),
+        }
+    }
+}
+
+/// 生成的 crate 目录不完整的原因：缺少 Cargo.toml / src/lib.rs、lib.rs 文件头的 IDL 哈希不一致，
+/// 或 src 下的模块目录缺少 mod.rs；完整时返回 None
+fn incomplete_reason(crate_dir: &Path, idl_hash: &str) -> Option<String> {
+    if !crate_dir.join("Cargo.toml").is_file() {
+        return Some("缺少 Cargo.toml".to_string());
+    }
+    let src_dir = crate_dir.join("src");
+    let Ok(lib_rs) = fs::read_to_string(src_dir.join("lib.rs")) else {
+        return Some("缺少 src/lib.rs".to_string());
+    };
+    if !lib_rs.lines().next().is_some_and(|header| header.contains(idl_hash)) {
+        return Some("src/lib.rs 不是由该 IDL 生成".to_string());
+    }
+    let entries = fs::read_dir(&src_dir).ok()?;
+    entries
+        .flatten()
+        .map(|entry| entry.path())
+        .filter(|path| path.is_dir() && !path.join("mod.rs").is_file())
+        .map(|path| format!("缺少 src/{}/mod.rs", path.file_name().unwrap_or_default().to_string_lossy()))
+        .next()
+}
+
+/// 批量生成目录中的 IDL
+///
+/// 单个 IDL 失败不会中断批量处理，记录在 [`BatchResult::failures`] 中；
+/// 输出目录、workspace 与统一库生成失败时返回错误
+pub fn run_batch(opts: BatchOptions, mut progress: impl FnMut(BatchEvent)) -> Result<BatchResult, SoloresError> {
+    let args = opts.into_args();
+    let mut result = BatchResult::default();
+
+    let mut workspace_config = validate_workspace_config(&args)?;
+    fs::create_dir_all(&args.batch_output_dir).map_err(|e| {
+        SoloresError::file_operation_error("创建批量输出目录", args.batch_output_dir.display().to_string(), e)
+    })?;
+
+    let idl_files = crate::scan_idl_files_with_filters(&args.idl_path, &args);
+    let total = idl_files.len();
+    progress(BatchEvent::Started { total });
+
+    if args.unified_library && total > 0 {
+        result.unified_library_dir = crate::process_unified_library(args, &idl_files)?;
+        progress(BatchEvent::Finished { succeeded: total, failed: 0 });
+        return Ok(result);
+    }
+
+    let options_hash = crate::provenance::options_hash(&args)?;
+    let mut checkpoint = if args.resume {
+        Checkpoint::load(&args.batch_output_dir, &options_hash)
+    } else {
+        Checkpoint { options_hash, ..Checkpoint::default() }
+    };
+
+    for (index, idl_path) in idl_files.into_iter().enumerate() {
+        progress(BatchEvent::FileStarted { index, total, idl_path: &idl_path });
+
+        // 无法读取的 IDL 交给生成流程报告错误
+        let idl_hash = fs::read(&idl_path).map(|idl| idl_sha256(&idl)).unwrap_or_default();
+        if let Some(completed) = checkpoint.resumable(&idl_path, &idl_hash).cloned() {
+            if let Some(ref mut workspace) = workspace_config {
+                add_workspace_member(workspace, workspace_member(&args.batch_output_dir, &completed.output_dir));
+            }
+            result.generated.push(GeneratedCrate {
+                idl_path,
+                crate_name: completed.crate_name,
+                output_dir: completed.output_dir,
+                warnings: Vec::new(),
+            });
+            let generated = result.generated.last().expect("刚刚写入");
+            progress(BatchEvent::FileSkipped { index, total, generated });
+            continue;
+        }
+
+        match crate::process_single_idl_file(&args, &idl_path) {
+            Ok((output_dir, warnings)) => {
+                let crate_name = output_dir
+                    .file_name()
+                    .map(|name| name.to_string_lossy().to_string())
+                    .unwrap_or_default();
+                if let Some(ref mut workspace) = workspace_config {
+                    add_workspace_member(workspace, workspace_member(&args.batch_output_dir, &output_dir));
+                }
+                checkpoint.completed.insert(
+                    idl_path.display().to_string(),
+                    CompletedCrate { idl_hash, crate_name: crate_name.clone(), output_dir: output_dir.clone() },
+                );
+                // 断点写入失败只影响之后的 --resume，不中断本次生成
+                if let Err(e) = checkpoint.save(&args.batch_output_dir) {
+                    log::warn!("⚠️ 无法更新批量断点: {}", e);
+                }
+                result.generated.push(GeneratedCrate { idl_path, crate_name, output_dir, warnings });
+                let generated = result.generated.last().expect("刚刚写入");
+                progress(BatchEvent::FileSucceeded { index, total, generated });
+            }
+            Err(error) => {
+                result.failures.push(BatchFailure { idl_path, error });
+                let failure = result.failures.last().expect("刚刚写入");
+                progress(BatchEvent::FileFailed { index, total, failure });
+            }
+        }
+    }
+
+    if let Some(mut workspace) = workspace_config {
+        if args.pda_registry
+            && !workspace.members.is_empty()
+            && crate::pda_registry::generate_pda_registry(&workspace.output_dir, &result.generated)?
+        {
+            add_workspace_member(&mut workspace, crate::pda_registry::REGISTRY_CRATE.to_string());
+        }
+        finalize_workspace(&workspace)?;
+        if !workspace.members.is_empty() {
+            result.workspace_dir = Some(workspace.output_dir);
+        }
+    }
+
+    progress(BatchEvent::Finished {
+        succeeded: result.generated.len(),
+        failed: result.failures.len(),
+    });
+    Ok(result)
+}
+
+/// workspace 成员路径：分组输出时为 `分组/crate` 相对路径
+fn workspace_member(batch_output_dir: &Path, output_dir: &Path) -> String {
+    let member = output_dir.strip_prefix(batch_output_dir).unwrap_or(output_dir);
+    member.to_string_lossy().replace('\\', "/")
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn run_batch_reports_progress_and_failures() {
+        let dir = std::env::temp_dir().join(format!("solores-batch-{}", std::process::id()));
+        let input_dir = dir.join("idls");
+        fs::create_dir_all(&input_dir).unwrap();
+        fs::copy(
+            Path::new(env!("CARGO_MANIFEST_DIR")).join("../idls/pump_amm.json"),
+            input_dir.join("pump_amm.json"),
+        )
+        .unwrap();
+        fs::write(input_dir.join("broken.json"), r#"{"instructions": 1}"#).unwrap();
+
+        let mut events = Vec::new();
+        let result = run_batch(BatchOptions::new(&input_dir, dir.join("out")), |event| {
+            events.push(match event {
+                BatchEvent::Started { total } => format!("started {}", total),
+                BatchEvent::FileStarted { index, .. } => format!("file {}", index),
+                BatchEvent::FileSucceeded { generated, .. } => format!("ok {}", generated.crate_name),
+                BatchEvent::FileSkipped { generated, .. } => format!("skipped {}", generated.crate_name),
+                BatchEvent::FileFailed { failure, .. } => {
+                    format!("failed {}", failure.idl_path.file_name().unwrap().to_string_lossy())
+                }
+                BatchEvent::Finished { succeeded, failed } => format!("finished {} {}", succeeded, failed),
+            })
+        })
+        .unwrap();
+        let _ = fs::remove_dir_all(&dir);
+
+        assert_eq!(
+            events,
+            [
+                "started 2",
+                "file 0",
+                "failed broken.json",
+                "file 1",
+                "ok sol_pump_amm_interface",
+                "finished 1 1",
+            ]
+        );
+        assert!(!result.is_success());
+        assert!(result.generated[0].output_dir.ends_with("sol_pump_amm_interface"));
+    }
+
+    #[test]
+    fn resume_skips_complete_crates_and_regenerates_partial_ones() {
+        let dir = std::env::temp_dir().join(format!("solores-batch-resume-{}", std::process::id()));
+        let input_dir = dir.join("idls");
+        fs::create_dir_all(&input_dir).unwrap();
+        for name in ["pump_amm.json", "serum.json"] {
+            fs::copy(Path::new(env!("CARGO_MANIFEST_DIR")).join("../idls").join(name), input_dir.join(name)).unwrap();
+        }
+        let run = |resume: bool, args: &[&str]| {
+            let mut events = Vec::new();
+            let mut opts = BatchOptions::with_args(&input_dir, dir.join("out"), args.iter().copied()).unwrap();
+            opts.resume = resume;
+            run_batch(opts, |event| match event {
+                BatchEvent::FileSucceeded { generated, .. } => events.push(format!("ok {}", generated.crate_name)),
+                BatchEvent::FileSkipped { generated, .. } => events.push(format!("skipped {}", generated.crate_name)),
+                _ => {}
+            })
+            .unwrap();
+            events
+        };
+
+        run(false, &[]);
+        // 模拟在写入模块目录时被中断
+        fs::remove_file(dir.join("out/sol_serum_interface/src/accounts/mod.rs")).unwrap();
+        let resumed = run(true, &[]);
+        let resumed_again = run(true, &[]);
+        let with_other_options = run(true, &["--typed-keys"]);
+        let _ = fs::remove_dir_all(&dir);
+
+        assert_eq!(resumed, ["skipped sol_pump_amm_interface", "ok sol_serum_interface"]);
+        assert_eq!(resumed_again, ["skipped sol_pump_amm_interface", "skipped sol_serum_interface"]);
+        assert_eq!(with_other_options, ["ok sol_pump_amm_interface", "ok sol_serum_interface"]);
+    }
+
+    #[test]
+    fn grouped_output_nests_crates_and_workspace_members() {
+        let dir = std::env::temp_dir().join(format!("solores-batch-grouped-{}", std::process::id()));
+        let input_dir = dir.join("idls");
+        fs::create_dir_all(&input_dir).unwrap();
+        fs::copy(
+            Path::new(env!("CARGO_MANIFEST_DIR")).join("../idls/pump_amm.json"),
+            input_dir.join("pump_amm.json"),
+        )
+        .unwrap();
+
+        let opts = BatchOptions::with_args(&input_dir, dir.join("out"), ["--workspace", "--group-output-by", "name"])
+            .unwrap();
+        let result = run_batch(opts, |_| {}).unwrap();
+        let workspace_toml = fs::read_to_string(dir.join("out/Cargo.toml")).unwrap();
+        let _ = fs::remove_dir_all(&dir);
+
+        assert!(result.generated[0].output_dir.ends_with("pump/sol_pump_amm_interface"));
+        assert!(workspace_toml.contains("\"pump/sol_pump_amm_interface\""));
+    }
+}
@@ -0,0 +1,3 @@
+fn main() {
+    solores::cargo_main();
+}
@@ -0,0 +1,225 @@
+//! build.rs 集成
+//!
+//! 在构建脚本中从 IDL 生成接口代码，生成结果不需要提交到仓库。
+//! 流水线先在临时目录生成完整 crate，再把 `src/` 下的模块树展开为内联模块，
+//! 得到一个可以 `include!` 的单文件（或 TokenStream）。
+//!
+//! ```ignore
+//! // build.rs
+//! fn main() {
+//!     let out_dir = std::env::var("OUT_DIR").unwrap();
+//!     solores::build_rs_generate("idls/my_program.json", &out_dir).unwrap();
+//! }
+//!
+//! // src/lib.rs —— 需位于 crate 根，生成代码通过 `crate::` 路径互相引用
+//! include!(concat!(env!("OUT_DIR"), "/sol_my_program_interface.rs"));
+//! ```
+//!
+//! 引用方 crate 需要声明与生成的 Cargo.toml 相同的依赖，`#[cfg(feature = ...)]`
+//! 模块对应引用方 crate 自己的 feature。
+//!
+//! `--stdout-single-file` / `--single-file` 复用同一展开逻辑（[`amalgamate`]），
+//! 但保留 crate 级属性与文档，输出可直接作为 `lib.rs` 使用的单文件。
+
+use std::ffi::OsString;
+use std::fs::{self, OpenOptions};
+use std::path::{Path, PathBuf};
+
+use clap::Parser;
+use proc_macro2::TokenStream;
+use quote::ToTokens;
+use syn::{AttrStyle, Item};
+
+use crate::{
+    error::SoloresError,
+    idl_format::{IdlFormat, IdlFormatEnum},
+    pipeline,
+    warnings::Warning,
+    Args,
+};
+
+/// 在构建脚本中生成接口代码，写入 `<out_dir>/<crate_name>.rs` 并返回该路径
+///
+/// 同时输出 `cargo:rerun-if-changed`，IDL 变化时重新生成
+pub fn build_rs_generate(idl_path: impl AsRef<Path>, out_dir: impl AsRef<Path>) -> Result<PathBuf, SoloresError> {
+    build_rs_generate_with_args(idl_path, out_dir, std::iter::empty::<OsString>())
+}
+
+/// 同 [`build_rs_generate`]，`generator_args` 为命令行生成参数（如 `--generate-parser`）
+pub fn build_rs_generate_with_args<I, S>(
+    idl_path: impl AsRef<Path>,
+    out_dir: impl AsRef<Path>,
+    generator_args: I,
+) -> Result<PathBuf, SoloresError>
+where
+    I: IntoIterator<Item = S>,
+    S: Into<OsString>,
+{
+    let idl_path = idl_path.as_ref();
+    let out_dir = out_dir.as_ref();
+    println!("cargo:rerun-if-changed={}", idl_path.display());
+
+    let (crate_name, file) = generate_file(idl_path, &out_dir.join("solores"), generator_args)?;
+    let output_path = out_dir.join(format!("{}.rs", crate_name));
+    fs::write(&output_path, prettyplease::unparse(&file))
+        .map_err(|e| SoloresError::file_operation_error("写入生成代码", output_path.display().to_string(), e))?;
+    Ok(output_path)
+}
+
+/// 生成接口代码的 TokenStream，不写入任何输出目录（仅使用系统临时目录）
+pub fn generate_tokens<I, S>(idl_path: impl AsRef<Path>, generator_args: I) -> Result<TokenStream, SoloresError>
+where
+    I: IntoIterator<Item = S>,
+    S: Into<OsString>,
+{
+    let scratch_dir = std::env::temp_dir().join(format!("solores-build-rs-{}", std::process::id()));
+    let result = generate_file(idl_path.as_ref(), &scratch_dir, generator_args);
+    let _ = fs::remove_dir_all(&scratch_dir);
+    result.map(|(_, file)| file.into_token_stream())
+}
+
+/// 在 `scratch_dir` 中运行流水线，返回 crate 名称与展开后的单文件
+fn generate_file<I, S>(idl_path: &Path, scratch_dir: &Path, generator_args: I) -> Result<(String, syn::File), SoloresError>
+where
+    I: IntoIterator<Item = S>,
+    S: Into<OsString>,
+{
+    let mut args = Args::try_parse_from(
+        [OsString::from("solores"), idl_path.as_os_str().to_owned()]
+            .into_iter()
+            .chain(generator_args.into_iter().map(Into::into)),
+    )
+    .map_err(|e| SoloresError::ValidationError {
+        message: format!("生成参数无效: {}", e),
+        field_path: None,
+        expected: None,
+        actual: None,
+    })?;
+
+    let mut file = OpenOptions::new()
+        .read(true)
+        .open(idl_path)
+        .map_err(|e| SoloresError::file_operation_error("读取IDL文件", idl_path.display().to_string(), e))?;
+    let idl = crate::load_idl_as_enum(&mut file)?;
+
+    if args.output_crate_name == crate::DEFAULT_OUTPUT_CRATE_NAME_MSG {
+        args.output_crate_name = format!("sol_{}_interface", idl.program_name());
+    }
+    args.program_id = args.program_id.filter(|id| id != crate::DEFAULT_PROGRAM_ID_MSG);
+    args.output_dir = scratch_dir.join(&args.output_crate_name);
+
+    for warning in pipeline::run(&args, &idl)? {
+        println!("cargo:warning={}", warning);
+    }
+
+    let mut lib = inline_crate(&args.output_dir.join("src"))?;
+    // include! 的内容是普通条目，不允许 crate 级内部属性与 `//!` 文档
+    lib.attrs.retain(|attr| matches!(attr.style, AttrStyle::Outer));
+    Ok((args.output_crate_name, lib))
+}
+
+/// 在临时目录中运行流水线，把生成的 crate 合并为单个 `lib.rs` 的源码
+///
+/// 所有模块以内联 `mod x { ... }` 形式展开，保留 crate 级属性、文档与 `@generated` 文件头；
+/// Cargo.toml 等非 Rust 文件不包含在内。返回源码与未被屏蔽的警告
+pub fn amalgamate(args: &Args, ir: &IdlFormatEnum) -> Result<(String, Vec<Warning>), SoloresError> {
+    let scratch_dir = std::env::temp_dir().join(format!("solores-single-file-{}", std::process::id()));
+    let mut scratch_args = args.clone();
+    scratch_args.output_dir = scratch_dir.join(&args.output_crate_name);
+
+    let result = pipeline::run(&scratch_args, ir).and_then(|warnings| {
+        let src_dir = scratch_args.output_dir.join("src");
+        let lib = inline_crate(&src_dir)?;
+        // prettyplease 会丢弃注释，文件头取自展开前的 lib.rs
+        let header = fs::read_to_string(src_dir.join("lib.rs"))
+            .ok()
+            .and_then(|content| content.lines().next().filter(|line| line.starts_with("//")).map(str::to_string));
+        let source = prettyplease::unparse(&lib);
+        Ok((header.map(|header| format!("{}\n{}", header, source)).unwrap_or(source), warnings))
+    });
+
+    let _ = fs::remove_dir_all(&scratch_dir);
+    result
+}
+
+/// 解析 `src_dir/lib.rs` 并展开其模块树
+fn inline_crate(src_dir: &Path) -> Result<syn::File, SoloresError> {
+    let mut lib = parse_file(&src_dir.join("lib.rs"))?;
+    inline_modules(&mut lib.items, src_dir)?;
+    Ok(lib)
+}
+
+/// 将 `mod x;` 替换为 `mod x { ... }`，文件内容（含内部文档）移入模块体
+fn inline_modules(items: &mut [Item], module_dir: &Path) -> Result<(), SoloresError> {
+    for item in items {
+        let Item::Mod(module) = item else { continue };
+        if module.content.is_some() {
+            continue;
+        }
+        let name = module.ident.to_string();
+        let (path, child_dir) = if module_dir.join(format!("{}.rs", name)).is_file() {
+            (module_dir.join(format!("{}.rs", name)), module_dir.join(&name))
+        } else {
+            (module_dir.join(&name).join("mod.rs"), module_dir.join(&name))
+        };
+
+        let mut file = parse_file(&path)?;
+        inline_modules(&mut file.items, &child_dir)?;
+        module.attrs.extend(file.attrs);
+        module.content = Some((Default::default(), file.items));
+        module.semi = None;
+    }
+    Ok(())
+}
+
+fn parse_file(path: &Path) -> Result<syn::File, SoloresError> {
+    let content = fs::read_to_string(path)
+        .map_err(|e| SoloresError::file_operation_error("读取生成代码", path.display().to_string(), e))?;
+    syn::parse_file(&content).map_err(|e| SoloresError::CodeGenError {
+        module: path.display().to_string(),
+        reason: format!("生成代码解析失败: {}", e),
+        context: None,
+    })
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn generated_tokens_have_no_out_of_line_modules() {
+        let idl = Path::new(env!("CARGO_MANIFEST_DIR")).join("../idls/serum.json");
+        let file: syn::File = syn::parse2(generate_tokens(idl, ["--generate-parser"]).unwrap()).unwrap();
+
+        fn out_of_line(items: &[Item]) -> usize {
+            items
+                .iter()
+                .map(|item| match item {
+                    Item::Mod(module) => match &module.content {
+                        Some((_, items)) => out_of_line(items),
+                        None => 1,
+                    },
+                    _ => 0,
+                })
+                .sum()
+        }
+        assert!(file.attrs.iter().all(|attr| matches!(attr.style, AttrStyle::Outer)));
+        assert_eq!(out_of_line(&file.items), 0);
+        assert!(file.items.iter().any(|item| matches!(item, Item::Mod(m) if m.ident == "instructions")));
+    }
+
+    #[test]
+    fn amalgamated_crate_keeps_header_and_crate_attributes() {
+        let idl_path = Path::new(env!("CARGO_MANIFEST_DIR")).join("../idls/serum.json");
+        let mut file = OpenOptions::new().read(true).open(&idl_path).unwrap();
+        let idl = crate::load_idl_as_enum(&mut file).unwrap();
+        let mut args = Args::try_parse_from(["solores".as_ref(), idl_path.as_os_str()]).unwrap();
+        args.output_crate_name = "sol_serum_interface".to_string();
+
+        let (source, _) = amalgamate(&args, &idl).unwrap();
+        assert!(source.starts_with("// @generated by solores"));
+        let file = syn::parse_file(&source).unwrap();
+        assert!(file.attrs.iter().any(|attr| matches!(attr.style, AttrStyle::Inner(_))));
+        assert!(file.items.iter().all(|item| !matches!(item, Item::Mod(m) if m.content.is_none())));
+    }
+}
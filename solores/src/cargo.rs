@@ -14,7 +14,7 @@ pub fn write_workspace_member_cargo_toml(args: &Args, idl: &dyn IdlFormat) -> st
     
     let cargo_toml_content = generate_member_cargo_toml(
         &args.output_crate_name,
-        &program_id,
+        program_id,
         args.generate_parser,
         args.test,
         &args.zero_copy,
@@ -132,4 +132,139 @@ serde = ["dep:serde", "dep:serde_json"]
     }
 
     content
-}
\ No newline at end of file
+}
+/// 生成 crate 的包元数据（版本、许可证等及 `[package.metadata.solores]` 溯源信息）
+///
+/// 未设置的字段由模板使用各自的默认值
+#[derive(serde::Serialize, Debug, Default, Clone, PartialEq, Eq)]
+pub struct PackageMetadata {
+    pub version: Option<String>,
+    pub license: Option<String>,
+    pub repository: Option<String>,
+    pub description: Option<String>,
+    pub authors: Vec<String>,
+    /// 源IDL文件的 sha256，格式为 `sha256:<hex>`
+    pub idl_hash: Option<String>,
+    /// 源IDL规范化 JSON（键排序、无空白）的 sha256，与链上IDL账户比较时不受格式影响
+    pub idl_json_hash: Option<String>,
+    pub generator_version: String,
+    pub program_address: Option<String>,
+    /// `--min-rust-version`，写入 `rust-version`
+    pub rust_version: Option<String>,
+}
+
+impl PackageMetadata {
+    /// 从命令行参数构建，不含IDL相关的溯源信息
+    pub fn from_args(args: &Args) -> Self {
+        Self {
+            version: args.crate_version.clone(),
+            license: args.license.clone(),
+            repository: args.repository.clone(),
+            description: args.description.clone(),
+            authors: args.authors.clone(),
+            idl_hash: None,
+            idl_json_hash: None,
+            generator_version: env!("CARGO_PKG_VERSION").to_string(),
+            program_address: None,
+            rust_version: args.min_rust_version.map(|version| version.to_string()),
+        }
+    }
+
+    /// 附加源IDL文件哈希与程序地址
+    pub fn with_idl_provenance(
+        mut self,
+        idl_path: &std::path::Path,
+        program_address: Option<&str>,
+    ) -> Self {
+        match std::fs::read(idl_path) {
+            Ok(bytes) => {
+                self.idl_hash = Some(idl_sha256(&bytes));
+                self.idl_json_hash = idl_json_sha256(&bytes);
+            }
+            Err(e) => log::warn!("无法读取IDL文件计算哈希 {}: {}", idl_path.display(), e),
+        }
+        self.program_address = program_address
+            .filter(|address| !address.is_empty())
+            .map(str::to_string);
+        self
+    }
+}
+
+/// 计算IDL内容的 sha256，格式为 `sha256:<hex>`
+pub fn idl_sha256(bytes: &[u8]) -> String {
+    use sha2::{Digest, Sha256};
+    use std::fmt::Write;
+
+    let digest = Sha256::digest(bytes);
+    let mut hex = String::with_capacity(digest.len() * 2);
+    for byte in digest {
+        let _ = write!(hex, "{:02x}", byte);
+    }
+    format!("sha256:{}", hex)
+}
+
+/// 计算IDL规范化 JSON 的 sha256，格式为 `sha256:<hex>`；内容不是合法 JSON 时返回 `None`
+///
+/// 规范化规则：对象键按字节序排序、去除空白，标量按 serde_json 紧凑格式输出。
+/// 生成的 upgrade.rs 以相同规则处理链上IDL账户中的 JSON。
+pub fn idl_json_sha256(bytes: &[u8]) -> Option<String> {
+    let value: serde_json::Value = serde_json::from_slice(bytes).ok()?;
+    let mut canonical = String::new();
+    write_canonical_json(&value, &mut canonical);
+    Some(idl_sha256(canonical.as_bytes()))
+}
+
+fn write_canonical_json(value: &serde_json::Value, out: &mut String) {
+    match value {
+        serde_json::Value::Object(map) => {
+            let mut entries: Vec<_> = map.iter().collect();
+            entries.sort_by(|a, b| a.0.cmp(b.0));
+            out.push('{');
+            for (i, (key, value)) in entries.into_iter().enumerate() {
+                if i > 0 {
+                    out.push(',');
+                }
+                out.push_str(&serde_json::Value::from(key.as_str()).to_string());
+                out.push(':');
+                write_canonical_json(value, out);
+            }
+            out.push('}');
+        }
+        serde_json::Value::Array(items) => {
+            out.push('[');
+            for (i, item) in items.iter().enumerate() {
+                if i > 0 {
+                    out.push(',');
+                }
+                write_canonical_json(item, out);
+            }
+            out.push(']');
+        }
+        scalar => out.push_str(&scalar.to_string()),
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn idl_sha256_is_prefixed_hex() {
+        assert_eq!(
+            idl_sha256(b""),
+            "sha256:e3b0c44298fc1c149afbf4c8996fb92427ae41e4649b934ca495991b7852b855"
+        );
+    }
+
+    #[test]
+    fn idl_json_sha256_ignores_formatting_and_key_order() {
+        let pretty = br#"{
+  "name": "demo",
+  "instructions": [ { "name": "a", "args": [] } ]
+}"#;
+        let compact = br#"{"instructions":[{"args":[],"name":"a"}],"name":"demo"}"#;
+        assert_eq!(idl_json_sha256(pretty), idl_json_sha256(compact));
+        assert_eq!(idl_json_sha256(compact), Some(idl_sha256(compact)));
+        assert_eq!(idl_json_sha256(b"not json"), None);
+    }
+}
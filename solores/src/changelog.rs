@@ -0,0 +1,433 @@
+//! 重新生成时的 CHANGELOG
+//!
+//! 输出目录中已有上次生成时复制的 `idl.json` 时，比较新旧IDL的结构：指令、账户、事件与类型的
+//! 增删，字段的增删、类型与顺序变化，discriminator 变化，以及指令账户的增删与读写/签名标记。
+//! 有结构变化时在 `CHANGELOG.md` 顶部写入一条条目，审阅重新生成的 PR 时无需阅读上千行代码 diff。
+//! 只修改文档等不影响结构的IDL变化不写入条目。
+
+use std::collections::BTreeMap;
+use std::fs;
+use std::path::Path;
+
+use crate::cargo::idl_sha256;
+use crate::error::SoloresError;
+use crate::idl_format::anchor_idl::{AnchorField, AnchorIdl, AnchorTypeKind};
+use crate::idl_format::non_anchor_idl::{NonAnchorField, NonAnchorIdl, NonAnchorTypeKind};
+use crate::idl_format::{parse_idl_json_with_format, FormatKind, IdlFormat, IdlFormatEnum};
+use crate::minijinja::builders::anchor::convert_field_type_to_rust;
+use crate::minijinja::builders::non_anchor::convert_non_anchor_field_type_to_rust;
+
+/// CHANGELOG 文件名
+pub const CHANGELOG_FILE: &str = "CHANGELOG.md";
+
+const TITLE: &str = "# Changelog";
+
+/// 条目的结构摘要
+#[derive(Debug, Clone, Default, PartialEq, Eq)]
+struct ItemShape {
+    discriminator: Option<Vec<u8>>,
+    /// (字段名, 类型)；枚举为 (变体名, "variant")
+    fields: Vec<(String, String)>,
+    /// 指令账户 (名称, 标记)
+    accounts: Vec<(String, String)>,
+}
+
+/// 按类别（Instructions / Accounts / Events / Types）组织的IDL结构摘要
+type Shape = Vec<(&'static str, BTreeMap<String, ItemShape>)>;
+
+/// 上次生成的IDL
+pub struct PreviousIdl {
+    pub idl: IdlFormatEnum,
+    /// `sha256:<hex>`
+    pub hash: String,
+}
+
+/// 读取输出目录中上次生成时复制的 `idl.json`；不存在或无法解析时返回 None
+pub fn previous_idl(output_dir: &Path, force_format: Option<FormatKind>) -> Option<PreviousIdl> {
+    let path = output_dir.join("idl.json");
+    let content = fs::read_to_string(&path).ok()?;
+    match parse_idl_json_with_format(&content, force_format) {
+        Ok((idl, _)) => Some(PreviousIdl { idl, hash: idl_sha256(content.as_bytes()) }),
+        Err(e) => {
+            log::warn!("⚠️ 无法解析上次生成的 {}，跳过 CHANGELOG: {}", path.display(), e);
+            None
+        }
+    }
+}
+
+/// 比较新旧IDL并在 `CHANGELOG.md` 顶部写入条目，返回是否写入
+pub fn update_changelog(
+    output_dir: &Path,
+    previous: &PreviousIdl,
+    current: &IdlFormatEnum,
+    current_hash: &str,
+) -> Result<bool, SoloresError> {
+    if previous.hash == current_hash {
+        return Ok(false);
+    }
+    let Some(entry) = changelog_entry(&previous.idl, current, &previous.hash, current_hash) else {
+        return Ok(false);
+    };
+
+    let path = output_dir.join(CHANGELOG_FILE);
+    let existing = match fs::read_to_string(&path) {
+        Ok(content) => content,
+        Err(e) if e.kind() == std::io::ErrorKind::NotFound => String::new(),
+        Err(e) => return Err(SoloresError::file_operation_error("读取CHANGELOG", path.display().to_string(), e)),
+    };
+    // 文件头由 provenance 重新写入；保留标题之后的历史条目
+    let history = existing
+        .lines()
+        .skip_while(|line| line.contains("@generated by solores") || line.trim().is_empty())
+        .skip_while(|line| *line == TITLE || line.trim().is_empty())
+        .collect::<Vec<_>>()
+        .join("\n");
+    let mut content = format!("{}\n\n{}", TITLE, entry);
+    if !history.is_empty() {
+        content.push('\n');
+        content.push_str(&history);
+        content.push('\n');
+    }
+    fs::write(&path, content)
+        .map_err(|e| SoloresError::file_operation_error("写入CHANGELOG", path.display().to_string(), e))?;
+    log::info!("📝 IDL结构变化已写入 {}", path.display());
+    Ok(true)
+}
+
+/// 生成 Markdown 条目；没有结构变化时返回 None
+pub fn changelog_entry(
+    previous: &IdlFormatEnum,
+    current: &IdlFormatEnum,
+    previous_hash: &str,
+    current_hash: &str,
+) -> Option<String> {
+    let sections: Vec<(&str, Vec<String>)> = shape(previous)
+        .into_iter()
+        .zip(shape(current))
+        .map(|((category, old), (_, new))| (category, diff_items(&old, &new)))
+        .filter(|(_, lines)| !lines.is_empty())
+        .collect();
+    if sections.is_empty() {
+        return None;
+    }
+
+    let version = match (previous.program_version(), current.program_version()) {
+        (old, new) if old == new => new.to_string(),
+        (old, new) => format!("{} → {}", old, new),
+    };
+    let mut entry = format!(
+        "## {} {}\n\nIDL `{}` → `{}`\n",
+        current.program_name(),
+        version,
+        previous_hash,
+        current_hash
+    );
+    for (category, lines) in sections {
+        entry.push_str(&format!("\n### {}\n\n", category));
+        for line in lines {
+            entry.push_str(&line);
+            entry.push('\n');
+        }
+    }
+    Some(entry)
+}
+
+/// 逐条目比较，返回 Markdown 列表行
+fn diff_items(old: &BTreeMap<String, ItemShape>, new: &BTreeMap<String, ItemShape>) -> Vec<String> {
+    let mut lines = Vec::new();
+    for (name, item) in new {
+        match old.get(name) {
+            None => lines.push(format!("- Added `{}`", name)),
+            Some(previous) if previous != item => {
+                lines.push(format!("- Changed `{}`", name));
+                if previous.discriminator != item.discriminator {
+                    lines.push(format!(
+                        "  - discriminator `{}` → `{}`",
+                        discriminator_str(&previous.discriminator),
+                        discriminator_str(&item.discriminator)
+                    ));
+                }
+                diff_members("field", &previous.fields, &item.fields, &mut lines);
+                diff_members("account", &previous.accounts, &item.accounts, &mut lines);
+            }
+            Some(_) => {}
+        }
+    }
+    for name in old.keys().filter(|name| !new.contains_key(*name)) {
+        lines.push(format!("- Removed `{}`", name));
+    }
+    lines
+}
+
+/// 比较字段或指令账户：增删、类型或标记变化与顺序变化
+fn diff_members(kind: &str, old: &[(String, String)], new: &[(String, String)], lines: &mut Vec<String>) {
+    let find = |members: &[(String, String)], name: &str| {
+        members.iter().find(|(member, _)| member == name).map(|(_, detail)| detail.clone())
+    };
+    for (name, detail) in new {
+        match find(old, name) {
+            None => lines.push(format!("  - {} `{}` added (`{}`)", kind, name, detail)),
+            Some(previous) if previous != *detail => {
+                lines.push(format!("  - {} `{}`: `{}` → `{}`", kind, name, previous, detail))
+            }
+            Some(_) => {}
+        }
+    }
+    for (name, _) in old.iter().filter(|(name, _)| find(new, name).is_none()) {
+        lines.push(format!("  - {} `{}` removed", kind, name));
+    }
+    // 共有成员的相对顺序变化会改变序列化布局或账户位置
+    let common = |members: &[(String, String)], other: &[(String, String)]| {
+        members
+            .iter()
+            .map(|(name, _)| name.clone())
+            .filter(|name| find(other, name).is_some())
+            .collect::<Vec<_>>()
+    };
+    if common(old, new) != common(new, old) {
+        lines.push(format!("  - {} order changed", kind));
+    }
+}
+
+fn discriminator_str(discriminator: &Option<Vec<u8>>) -> String {
+    discriminator.as_ref().map(|bytes| format!("{:?}", bytes)).unwrap_or_else(|| "none".to_string())
+}
+
+fn shape(idl: &IdlFormatEnum) -> Shape {
+    match idl {
+        IdlFormatEnum::Anchor(idl) => anchor_shape(idl),
+        IdlFormatEnum::NonAnchor(idl) => non_anchor_shape(idl),
+    }
+}
+
+/// 类型字符串去掉生成代码中的模块路径
+fn type_str(rust_type: String) -> String {
+    rust_type.replace("crate::types::", "")
+}
+
+fn account_flags(is_mut: bool, is_signer: bool, is_optional: bool) -> String {
+    let flags: Vec<&str> = [(is_mut, "mut"), (is_signer, "signer"), (is_optional, "optional")]
+        .into_iter()
+        .filter_map(|(set, flag)| set.then_some(flag))
+        .collect();
+    if flags.is_empty() {
+        "readonly".to_string()
+    } else {
+        flags.join(", ")
+    }
+}
+
+fn anchor_fields(fields: &[AnchorField]) -> Vec<(String, String)> {
+    fields
+        .iter()
+        .map(|field| (field.name.clone(), type_str(convert_field_type_to_rust(&field.field_type))))
+        .collect()
+}
+
+fn anchor_shape(idl: &AnchorIdl) -> Shape {
+    let type_fields = |name: &str| -> Vec<(String, String)> {
+        idl.types
+            .iter()
+            .flatten()
+            .find(|t| t.name == name)
+            .and_then(|t| match &t.kind {
+                Some(AnchorTypeKind::Struct(fields)) => Some(anchor_fields(fields)),
+                _ => None,
+            })
+            .unwrap_or_default()
+    };
+
+    let instructions = idl
+        .instructions
+        .iter()
+        .flatten()
+        .map(|ix| {
+            let item = ItemShape {
+                discriminator: Some(ix.discriminator.to_vec()),
+                fields: anchor_fields(ix.args.as_deref().unwrap_or_default()),
+                accounts: ix
+                    .accounts
+                    .iter()
+                    .flatten()
+                    .map(|a| (a.name.clone(), account_flags(a.is_mut, a.is_signer, a.is_optional.unwrap_or(false))))
+                    .collect(),
+            };
+            (ix.name.clone(), item)
+        })
+        .collect();
+    let accounts = idl
+        .accounts
+        .iter()
+        .flatten()
+        .map(|account| {
+            let fields = match &account.fields {
+                Some(fields) => anchor_fields(fields),
+                None => type_fields(&account.name),
+            };
+            let item = ItemShape { discriminator: Some(account.discriminator.to_vec()), fields, ..Default::default() };
+            (account.name.clone(), item)
+        })
+        .collect();
+    let events = idl
+        .events
+        .iter()
+        .flatten()
+        .map(|event| {
+            let fields = match &event.fields {
+                Some(fields) => anchor_fields(fields),
+                None => type_fields(&event.name),
+            };
+            let item = ItemShape { discriminator: Some(event.discriminator.to_vec()), fields, ..Default::default() };
+            (event.name.clone(), item)
+        })
+        .collect();
+    let types = idl
+        .types
+        .iter()
+        .flatten()
+        .map(|t| {
+            let fields = match &t.kind {
+                Some(AnchorTypeKind::Struct(fields)) => anchor_fields(fields),
+                Some(AnchorTypeKind::Enum(variants)) => {
+                    variants.iter().map(|v| (v.name.clone(), "variant".to_string())).collect()
+                }
+                Some(AnchorTypeKind::Alias(alias)) => vec![("=".to_string(), type_str(convert_field_type_to_rust(alias)))],
+                None => Vec::new(),
+            };
+            (t.name.clone(), ItemShape { discriminator: t.discriminator.clone(), fields, ..Default::default() })
+        })
+        .collect();
+    vec![("Instructions", instructions), ("Accounts", accounts), ("Events", events), ("Types", types)]
+}
+
+fn non_anchor_fields(fields: &[NonAnchorField]) -> Vec<(String, String)> {
+    fields
+        .iter()
+        .map(|field| (field.name.clone(), type_str(convert_non_anchor_field_type_to_rust(&field.field_type))))
+        .collect()
+}
+
+fn non_anchor_shape(idl: &NonAnchorIdl) -> Shape {
+    let instructions = idl
+        .instructions
+        .iter()
+        .flatten()
+        .map(|ix| {
+            let item = ItemShape {
+                discriminator: ix.discriminator.clone(),
+                fields: non_anchor_fields(ix.args.as_deref().unwrap_or_default()),
+                accounts: ix
+                    .accounts
+                    .iter()
+                    .flatten()
+                    .map(|a| (a.name.clone(), account_flags(a.is_mut, a.is_signer, false)))
+                    .collect(),
+            };
+            (ix.name.clone(), item)
+        })
+        .collect();
+    let accounts = idl
+        .accounts
+        .iter()
+        .flatten()
+        .map(|account| {
+            let item = ItemShape {
+                discriminator: account.discriminator.clone(),
+                fields: non_anchor_fields(account.fields.as_deref().unwrap_or_default()),
+                ..Default::default()
+            };
+            (account.name.clone(), item)
+        })
+        .collect();
+    let events = idl
+        .events
+        .iter()
+        .flatten()
+        .map(|event| {
+            let item = ItemShape {
+                discriminator: event.discriminator.clone(),
+                fields: non_anchor_fields(event.fields.as_deref().unwrap_or_default()),
+                ..Default::default()
+            };
+            (event.name.clone(), item)
+        })
+        .collect();
+    let types = idl
+        .types
+        .iter()
+        .flatten()
+        .map(|t| {
+            let fields = match &t.type_def {
+                NonAnchorTypeKind::Struct { fields } => non_anchor_fields(fields),
+                NonAnchorTypeKind::Enum { variants } => {
+                    variants.iter().map(|v| (v.name.clone(), "variant".to_string())).collect()
+                }
+                NonAnchorTypeKind::Alias { value } => {
+                    vec![("=".to_string(), type_str(convert_non_anchor_field_type_to_rust(value)))]
+                }
+            };
+            (t.name.clone(), ItemShape { fields, ..Default::default() })
+        })
+        .collect();
+    vec![("Instructions", instructions), ("Accounts", accounts), ("Events", events), ("Types", types)]
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::idl_format::parse_idl_json;
+
+    fn idl(instructions: serde_json::Value, types: serde_json::Value) -> IdlFormatEnum {
+        parse_idl_json(
+            &serde_json::json!({
+                "address": "11111111111111111111111111111111",
+                "metadata": {"name": "demo", "version": "0.1.0", "spec": "0.1.0"},
+                "instructions": instructions,
+                "accounts": [{"name": "Pool", "discriminator": [1, 1, 1, 1, 1, 1, 1, 1]}],
+                "types": types
+            })
+            .to_string(),
+        )
+        .unwrap()
+    }
+
+    #[test]
+    fn entry_lists_semantic_changes() {
+        let swap = |discriminator: [u8; 8], args: serde_json::Value, accounts: serde_json::Value| {
+            serde_json::json!({"name": "swap", "discriminator": discriminator, "accounts": accounts, "args": args})
+        };
+        let previous = idl(
+            serde_json::json!([
+                swap(
+                    [2; 8],
+                    serde_json::json!([{"name": "amount", "type": "u32"}, {"name": "min_out", "type": "u64"}]),
+                    serde_json::json!([{"name": "pool", "writable": true}, {"name": "user", "signer": true}])
+                ),
+                {"name": "close", "discriminator": [3, 3, 3, 3, 3, 3, 3, 3], "accounts": [], "args": []}
+            ]),
+            serde_json::json!([{"name": "Pool", "type": {"kind": "struct", "fields": [{"name": "fee", "type": "u16"}]}}]),
+        );
+        let current = idl(
+            serde_json::json!([swap(
+                [4; 8],
+                serde_json::json!([{"name": "min_out", "type": "u64"}, {"name": "amount", "type": "u64"}]),
+                serde_json::json!([{"name": "pool"}, {"name": "user", "signer": true}, {"name": "vault"}])
+            )]),
+            serde_json::json!([{"name": "Pool", "type": {"kind": "struct", "fields": [{"name": "fee", "type": "u16"}]}}]),
+        );
+
+        let entry = changelog_entry(&previous, &current, "sha256:a", "sha256:b").unwrap();
+        assert_eq!(
+            entry,
+            "## demo 0.1.0\n\nIDL `sha256:a` → `sha256:b`\n\n### Instructions\n\n\
+             - Changed `swap`\n\
+             \x20 - discriminator `[2, 2, 2, 2, 2, 2, 2, 2]` → `[4, 4, 4, 4, 4, 4, 4, 4]`\n\
+             \x20 - field `amount`: `u32` → `u64`\n\
+             \x20 - field order changed\n\
+             \x20 - account `pool`: `mut` → `readonly`\n\
+             \x20 - account `vault` added (`readonly`)\n\
+             - Removed `close`\n"
+        );
+        assert!(changelog_entry(&current, &current, "sha256:b", "sha256:b").is_none());
+    }
+}
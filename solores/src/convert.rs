@@ -0,0 +1,831 @@
+//! IDL 方言转换
+//!
+//! `solores convert --to <dialect> in.json out.json` 复用内部解析表示，在
+//! Anchor 0.30、Anchor 旧版（≤0.29）与 Shank（NonAnchor）之间重新序列化 IDL：
+//! 统一字段拼写（`writable`/`isMut`、`pubkey`/`publicKey`、`defined` 的两种写法），
+//! 并为缺失的 discriminator 按 Anchor 规则计算。
+
+use std::fs;
+use std::path::{Path, PathBuf};
+
+use heck::ToSnakeCase;
+use serde_json::{json, Value};
+
+use crate::error::{diagnose_json_error, SoloresError};
+use crate::idl_format::anchor_idl::{anchor_discriminator, AnchorFieldType, AnchorTypeKind};
+use crate::idl_format::arg_encoding::ArgEncoding;
+use crate::idl_format::non_anchor_idl::{NonAnchorFieldType, NonAnchorTypeKind};
+use crate::idl_format::{parse_idl_json, AnchorIdl, IdlFormatEnum, NonAnchorIdl};
+
+/// IDL 方言
+#[derive(clap::ValueEnum, Debug, Clone, Copy, PartialEq, Eq)]
+pub enum IdlDialect {
+    /// Anchor 0.30+：顶层 address，8字节 discriminator，账户/事件布局位于 types
+    #[value(name = "anchor-0.30", alias = "anchor")]
+    Anchor030,
+    /// Anchor ≤0.29：顶层 name/version，isMut/isSigner，无 discriminator
+    #[value(name = "anchor-legacy", alias = "legacy")]
+    AnchorLegacy,
+    /// Shank / NonAnchor：metadata.origin = shank，显式 discriminator 数组
+    #[value(name = "shank", alias = "non-anchor")]
+    Shank,
+}
+
+/// `solores convert` 参数
+#[derive(clap::Args, Debug, Clone)]
+pub struct ConvertArgs {
+    /// 目标方言
+    #[arg(long, value_enum)]
+    pub to: IdlDialect,
+
+    /// 输入方言（默认自动检测）
+    #[arg(long, value_enum)]
+    pub from: Option<IdlDialect>,
+
+    /// 输入 IDL 文件
+    pub input: PathBuf,
+
+    /// 输出 IDL 文件
+    pub output: PathBuf,
+}
+
+/// 执行转换并写出结果，返回无法无损表达的条目说明
+pub fn run(args: &ConvertArgs) -> Result<Vec<String>, SoloresError> {
+    let json_str = fs::read_to_string(&args.input)
+        .map_err(|e| SoloresError::file_operation_error("读取IDL文件", args.input.display().to_string(), e))?;
+    let idl = load(&json_str, args.from).map_err(|e| diagnose_json_error(&json_str, &e))?;
+    let (value, warnings) = convert(&idl, args.to);
+
+    let rendered = serde_json::to_string_pretty(&value).expect("IDL JSON 序列化不会失败");
+    write_output(&args.output, rendered + "\n")?;
+    Ok(warnings)
+}
+
+/// 解析 IDL；`from` 为空时自动检测，旧版 Anchor IDL 先补齐 discriminator 与 metadata
+pub fn load(json_str: &str, from: Option<IdlDialect>) -> Result<IdlFormatEnum, serde_json::Error> {
+    let mut value: Value = serde_json::from_str(json_str)?;
+    match from {
+        Some(IdlDialect::AnchorLegacy) => {}
+        None if is_legacy_anchor(&value) => log::debug!("🔍 检测到旧版Anchor IDL"),
+        Some(IdlDialect::Anchor030) => return Ok(IdlFormatEnum::Anchor(AnchorIdl::parse_json(json_str)?)),
+        Some(IdlDialect::Shank) => return Ok(IdlFormatEnum::NonAnchor(NonAnchorIdl::parse_json(json_str)?)),
+        None => return parse_idl_json(json_str),
+    }
+    normalize_legacy_anchor(&mut value);
+    Ok(IdlFormatEnum::Anchor(AnchorIdl::parse_json(&value.to_string())?))
+}
+
+/// 旧版 Anchor IDL：metadata 既无 spec 也无 origin，且指令不带 discriminator 或带有 `state` 段
+pub(crate) fn is_legacy_anchor(value: &Value) -> bool {
+    let metadata = value.get("metadata");
+    let has_marker = |key| metadata.and_then(|m| m.get(key)).is_some();
+    let instructions = value.get("instructions").and_then(Value::as_array);
+    !has_marker("spec")
+        && !has_marker("origin")
+        && (crate::idl_format::format_detection::has_legacy_state(value)
+            || instructions.is_some_and(|ixs| !ixs.is_empty() && ixs.iter().all(|ix| ix.get("discriminator").is_none())))
+}
+
+/// 补齐旧版 Anchor IDL 缺失的 address、metadata 与指令/事件 discriminator
+///
+/// 指令名按 Anchor（heck）规则转为 snake_case，`swapV2` 对应 `global:swap_v2`
+pub(crate) fn normalize_legacy_anchor(value: &mut Value) {
+    let Some(obj) = value.as_object_mut() else {
+        return;
+    };
+    let name = obj.get("name").cloned().unwrap_or_else(|| json!("unknown"));
+    let version = obj.get("version").cloned().unwrap_or_else(|| json!("0.0.0"));
+    let metadata = obj
+        .entry("metadata")
+        .or_insert_with(|| json!({}))
+        .as_object_mut()
+        .expect("metadata 必须是对象");
+    metadata.entry("name").or_insert(name);
+    metadata.entry("version").or_insert(version);
+    metadata.entry("spec").or_insert(json!("anchor"));
+    if let Some(address) = metadata.get("address").cloned() {
+        obj.entry("address").or_insert(address);
+    }
+
+    for (section, namespace) in [("instructions", "global"), ("events", "event")] {
+        for item in obj.get_mut(section).and_then(Value::as_array_mut).into_iter().flatten() {
+            let Some(item) = item.as_object_mut() else { continue };
+            let Some(name) = item.get("name").and_then(Value::as_str) else { continue };
+            let name = if namespace == "global" { name.to_snake_case() } else { name.to_string() };
+            item.entry("discriminator").or_insert_with(|| json!(anchor_discriminator(namespace, &name)));
+        }
+    }
+}
+
+/// 按目标方言序列化 IDL，返回 JSON 与无法无损表达的条目说明
+pub fn convert(idl: &IdlFormatEnum, to: IdlDialect) -> (Value, Vec<String>) {
+    let program = Program::from_idl(idl);
+    let mut warnings = Vec::new();
+    let value = match to {
+        IdlDialect::Anchor030 => program.to_anchor_030(),
+        IdlDialect::AnchorLegacy => program.to_anchor_legacy(&mut warnings),
+        IdlDialect::Shank => program.to_shank(&mut warnings),
+    };
+    (value, warnings)
+}
+
+fn write_output(path: &Path, content: String) -> Result<(), SoloresError> {
+    if let Some(parent) = path.parent().filter(|parent| !parent.as_os_str().is_empty()) {
+        fs::create_dir_all(parent)
+            .map_err(|e| SoloresError::file_operation_error("创建输出目录", parent.display().to_string(), e))?;
+    }
+    fs::write(path, content).map_err(|e| SoloresError::file_operation_error("写入IDL文件", path.display().to_string(), e))
+}
+
+/// 方言无关的类型引用
+#[derive(Debug, Clone)]
+enum Ty {
+    Primitive(String),
+    Defined(String),
+    Option(Box<Ty>),
+    COption(Box<Ty>),
+    Vec(Box<Ty>),
+    Array(Box<Ty>, usize),
+    HashMap(Box<Ty>, Box<Ty>),
+    /// 无法归类的复合类型，原样保留
+    Raw(Value),
+}
+
+impl Ty {
+    fn primitive(name: &str) -> Self {
+        match name {
+            "publicKey" | "pubkey" | "Pubkey" => Ty::Primitive("pubkey".to_string()),
+            _ => Ty::Primitive(name.to_string()),
+        }
+    }
+
+    fn raw(kind: &str, params: &Option<Vec<Value>>) -> Self {
+        let mut value = json!({ "kind": kind });
+        if let Some(param) = params.as_ref().and_then(|params| params.first()) {
+            value["params"] = param.clone();
+        }
+        Ty::Raw(value)
+    }
+
+    fn from_anchor(ty: &AnchorFieldType) -> Self {
+        match ty {
+            AnchorFieldType::Basic(name) | AnchorFieldType::PrimitiveOrPubkey(name) => Ty::primitive(name),
+            AnchorFieldType::defined(name) => Ty::Defined(name.clone()),
+            AnchorFieldType::array(inner, len) => Ty::Array(Box::new(Ty::from_anchor(inner)), *len),
+            AnchorFieldType::vec(inner) => Ty::Vec(Box::new(Ty::from_anchor(inner))),
+            AnchorFieldType::option(inner) => Ty::Option(Box::new(Ty::from_anchor(inner))),
+            AnchorFieldType::coption(inner) => Ty::COption(Box::new(Ty::from_anchor(inner))),
+            AnchorFieldType::Complex { kind, params } => Ty::raw(kind, params),
+        }
+    }
+
+    fn from_non_anchor(ty: &NonAnchorFieldType) -> Self {
+        match ty {
+            NonAnchorFieldType::Basic(name) => Ty::primitive(name),
+            NonAnchorFieldType::Defined { defined } => Ty::Defined(defined.clone()),
+            NonAnchorFieldType::Array { array: (inner, len) } => Ty::Array(Box::new(Ty::from_non_anchor(inner)), *len),
+            NonAnchorFieldType::Vec { vec } => Ty::Vec(Box::new(Ty::from_non_anchor(vec))),
+            NonAnchorFieldType::Option { option } => Ty::Option(Box::new(Ty::from_non_anchor(option))),
+            NonAnchorFieldType::COption { coption } => Ty::COption(Box::new(Ty::from_non_anchor(coption))),
+            NonAnchorFieldType::HashMap { key, value } => {
+                Ty::HashMap(Box::new(Ty::from_non_anchor(key)), Box::new(Ty::from_non_anchor(value)))
+            }
+            NonAnchorFieldType::Complex { kind, params } => Ty::raw(kind, params),
+        }
+    }
+
+    fn to_json(&self, dialect: IdlDialect) -> Value {
+        match self {
+            Ty::Primitive(name) if name == "pubkey" && dialect != IdlDialect::Anchor030 => json!("publicKey"),
+            Ty::Primitive(name) => json!(name),
+            Ty::Defined(name) if dialect == IdlDialect::Anchor030 => json!({ "defined": { "name": name } }),
+            Ty::Defined(name) => json!({ "defined": name }),
+            Ty::Option(inner) => json!({ "option": inner.to_json(dialect) }),
+            Ty::COption(inner) => json!({ "coption": inner.to_json(dialect) }),
+            Ty::Vec(inner) => json!({ "vec": inner.to_json(dialect) }),
+            Ty::Array(inner, len) => json!({ "array": [inner.to_json(dialect), len] }),
+            Ty::HashMap(key, value) => json!({ "hashMap": [key.to_json(dialect), value.to_json(dialect)] }),
+            Ty::Raw(value) => value.clone(),
+        }
+    }
+}
+
+#[derive(Debug, Clone)]
+struct Field {
+    name: String,
+    ty: Ty,
+    docs: Vec<String>,
+    encoding: Option<ArgEncoding>,
+}
+
+#[derive(Debug, Clone)]
+struct IxAccount {
+    name: String,
+    writable: bool,
+    signer: bool,
+    optional: bool,
+    address: Option<String>,
+    pda: Option<Value>,
+    docs: Vec<String>,
+}
+
+#[derive(Debug, Clone)]
+struct Instruction {
+    name: String,
+    discriminator: Vec<u8>,
+    accounts: Vec<IxAccount>,
+    args: Vec<Field>,
+    docs: Vec<String>,
+}
+
+/// 带 discriminator 的数据布局（账户、事件）
+#[derive(Debug, Clone)]
+struct Layout {
+    name: String,
+    discriminator: Option<Vec<u8>>,
+    fields: Vec<Field>,
+    docs: Vec<String>,
+}
+
+#[derive(Debug, Clone)]
+struct Variant {
+    name: String,
+    fields: Option<Vec<Field>>,
+    docs: Vec<String>,
+    value: Option<u64>,
+}
+
+#[derive(Debug, Clone)]
+enum TypeBody {
+    Struct(Vec<Field>),
+    Enum(Vec<Variant>),
+    Alias(Ty),
+}
+
+#[derive(Debug, Clone)]
+struct TypeDef {
+    name: String,
+    body: TypeBody,
+    docs: Vec<String>,
+}
+
+#[derive(Debug, Clone)]
+struct ErrorCode {
+    code: u32,
+    name: String,
+    msg: Option<String>,
+}
+
+/// 方言无关的程序描述
+#[derive(Debug, Clone)]
+struct Program {
+    name: String,
+    version: String,
+    address: String,
+    description: Option<String>,
+    instructions: Vec<Instruction>,
+    accounts: Vec<Layout>,
+    events: Vec<Layout>,
+    types: Vec<TypeDef>,
+    errors: Vec<ErrorCode>,
+    constants: Vec<Value>,
+}
+
+fn docs(docs: &Option<Vec<String>>) -> Vec<String> {
+    docs.clone().unwrap_or_default()
+}
+
+impl Program {
+    fn from_idl(idl: &IdlFormatEnum) -> Self {
+        match idl {
+            IdlFormatEnum::Anchor(idl) => Self::from_anchor(idl),
+            IdlFormatEnum::NonAnchor(idl) => Self::from_non_anchor(idl),
+        }
+    }
+
+    fn from_anchor(idl: &AnchorIdl) -> Self {
+        let fields = |fields: &[crate::idl_format::anchor_idl::AnchorField]| -> Vec<Field> {
+            fields
+                .iter()
+                .map(|f| Field {
+                    name: f.name.clone(),
+                    ty: Ty::from_anchor(&f.field_type),
+                    docs: docs(&f.docs),
+                    encoding: f.encoding,
+                })
+                .collect()
+        };
+        Program {
+            name: idl.program_name().to_string(),
+            version: idl.program_version().to_string(),
+            address: idl.address.clone(),
+            description: idl.metadata.description.clone(),
+            instructions: idl
+                .instructions
+                .iter()
+                .flatten()
+                .map(|ix| Instruction {
+                    name: ix.name.clone(),
+                    discriminator: ix.discriminator.to_vec(),
+                    accounts: ix
+                        .accounts
+                        .iter()
+                        .flatten()
+                        .map(|a| IxAccount {
+                            name: a.name.clone(),
+                            writable: a.is_mut,
+                            signer: a.is_signer,
+                            optional: a.is_optional.unwrap_or(false),
+                            address: a.address.clone(),
+                            pda: a.pda.as_ref().and_then(|pda| serde_json::to_value(pda).ok()),
+                            docs: docs(&a.docs),
+                        })
+                        .collect(),
+                    args: fields(ix.args.as_deref().unwrap_or_default()),
+                    docs: docs(&ix.docs),
+                })
+                .collect(),
+            accounts: idl
+                .accounts
+                .iter()
+                .flatten()
+                .map(|a| Layout {
+                    name: a.name.clone(),
+                    discriminator: Some(a.discriminator.to_vec()),
+                    fields: fields(a.fields.as_deref().unwrap_or_default()),
+                    docs: docs(&a.docs),
+                })
+                .collect(),
+            events: idl
+                .events
+                .iter()
+                .flatten()
+                .map(|e| Layout {
+                    name: e.name.clone(),
+                    discriminator: Some(e.discriminator.to_vec()),
+                    fields: fields(e.fields.as_deref().unwrap_or_default()),
+                    docs: docs(&e.docs),
+                })
+                .collect(),
+            types: idl
+                .types
+                .iter()
+                .flatten()
+                .filter_map(|t| {
+                    let body = match t.kind.as_ref()? {
+                        AnchorTypeKind::Struct(f) => TypeBody::Struct(fields(f)),
+                        AnchorTypeKind::Enum(variants) => TypeBody::Enum(
+                            variants
+                                .iter()
+                                .map(|v| Variant {
+                                    name: v.name.clone(),
+                                    fields: v.fields.as_deref().map(fields),
+                                    docs: docs(&v.docs),
+                                    value: v.value,
+                                })
+                                .collect(),
+                        ),
+                        AnchorTypeKind::Alias(ty) => TypeBody::Alias(Ty::from_anchor(ty)),
+                    };
+                    Some(TypeDef { name: t.name.clone(), body, docs: docs(&t.docs) })
+                })
+                .collect(),
+            errors: idl
+                .errors
+                .iter()
+                .flatten()
+                .map(|e| ErrorCode { code: e.code, name: e.name.clone(), msg: e.msg.clone() })
+                .collect(),
+            constants: idl
+                .constants
+                .iter()
+                .flatten()
+                .map(|c| json!({ "name": c.name, "type": c.const_type, "value": c.value }))
+                .collect(),
+        }
+    }
+
+    fn from_non_anchor(idl: &NonAnchorIdl) -> Self {
+        let fields = |fields: &[crate::idl_format::non_anchor_idl::NonAnchorField]| -> Vec<Field> {
+            fields
+                .iter()
+                .map(|f| Field {
+                    name: f.name.clone(),
+                    ty: Ty::from_non_anchor(&f.field_type),
+                    docs: docs(&f.docs),
+                    encoding: f.encoding,
+                })
+                .collect()
+        };
+        Program {
+            name: idl.program_name().to_string(),
+            version: idl.program_version().to_string(),
+            address: idl.address.clone(),
+            description: idl.metadata.as_ref().and_then(|m| m.description.clone()),
+            instructions: idl
+                .instructions()
+                .iter()
+                .enumerate()
+                .map(|(index, ix)| Instruction {
+                    name: ix.name.clone(),
+                    discriminator: ix.get_discriminator_with_fallback(index),
+                    accounts: ix
+                        .accounts
+                        .iter()
+                        .flatten()
+                        .map(|a| IxAccount {
+                            name: a.name.clone(),
+                            writable: a.is_mut,
+                            signer: a.is_signer,
+                            optional: false,
+                            address: a.address.clone(),
+                            pda: None,
+                            docs: docs(&a.docs),
+                        })
+                        .collect(),
+                    args: fields(ix.args.as_deref().unwrap_or_default()),
+                    docs: docs(&ix.docs),
+                })
+                .collect(),
+            accounts: idl
+                .accounts
+                .iter()
+                .flatten()
+                .map(|a| Layout {
+                    name: a.name.clone(),
+                    discriminator: a.discriminator.clone(),
+                    fields: fields(a.fields.as_deref().unwrap_or_default()),
+                    docs: docs(&a.docs),
+                })
+                .collect(),
+            events: idl
+                .events
+                .iter()
+                .flatten()
+                .map(|e| Layout {
+                    name: e.name.clone(),
+                    discriminator: e.discriminator.clone(),
+                    fields: fields(e.fields.as_deref().unwrap_or_default()),
+                    docs: docs(&e.docs),
+                })
+                .collect(),
+            types: idl
+                .types
+                .iter()
+                .flatten()
+                .map(|t| {
+                    let body = match &t.type_def {
+                        NonAnchorTypeKind::Struct { fields: f } => TypeBody::Struct(fields(f)),
+                        NonAnchorTypeKind::Enum { variants } => TypeBody::Enum(
+                            variants
+                                .iter()
+                                .map(|v| Variant {
+                                    name: v.name.clone(),
+                                    fields: v.fields.as_deref().map(fields),
+                                    docs: docs(&v.docs),
+                                    value: v.value,
+                                })
+                                .collect(),
+                        ),
+                        NonAnchorTypeKind::Alias { value } => TypeBody::Alias(Ty::from_non_anchor(value)),
+                    };
+                    TypeDef { name: t.name.clone(), body, docs: docs(&t.docs) }
+                })
+                .collect(),
+            errors: idl
+                .errors
+                .iter()
+                .flatten()
+                .map(|e| ErrorCode { code: e.code, name: e.name.clone(), msg: Some(e.msg.clone()) })
+                .collect(),
+            constants: Vec::new(),
+        }
+    }
+
+    fn to_anchor_030(&self) -> Value {
+        let dialect = IdlDialect::Anchor030;
+        let mut metadata = json!({ "name": self.name, "version": self.version, "spec": "0.1.0" });
+        if let Some(description) = &self.description {
+            metadata["description"] = json!(description);
+        }
+        let instructions: Vec<Value> = self
+            .instructions
+            .iter()
+            .map(|ix| {
+                let accounts: Vec<Value> = ix
+                    .accounts
+                    .iter()
+                    .map(|a| {
+                        let mut account = with_docs(json!({ "name": a.name }), &a.docs);
+                        set_flag(&mut account, "writable", a.writable);
+                        set_flag(&mut account, "signer", a.signer);
+                        set_flag(&mut account, "optional", a.optional);
+                        if let Some(address) = &a.address {
+                            account["address"] = json!(address);
+                        }
+                        if let Some(pda) = &a.pda {
+                            account["pda"] = pda.clone();
+                        }
+                        account
+                    })
+                    .collect();
+                with_docs(
+                    json!({
+                        "name": ix.name,
+                        "discriminator": ix.discriminator,
+                        "accounts": accounts,
+                        "args": fields_json(&ix.args, dialect),
+                    }),
+                    &ix.docs,
+                )
+            })
+            .collect();
+        let layout_ref = |layout: &Layout, namespace: &str| {
+            let discriminator = layout
+                .discriminator
+                .clone()
+                .unwrap_or_else(|| anchor_discriminator(namespace, &layout.name).to_vec());
+            json!({ "name": layout.name, "discriminator": discriminator })
+        };
+
+        // 0.30 中账户与事件只保留引用，布局放入 types
+        let mut types = self.types_json(dialect, |_| true);
+        for layout in self.accounts.iter().chain(&self.events) {
+            if !self.types.iter().any(|t| t.name == layout.name) {
+                types.push(with_docs(
+                    json!({ "name": layout.name, "type": { "kind": "struct", "fields": fields_json(&layout.fields, dialect) } }),
+                    &layout.docs,
+                ));
+            }
+        }
+
+        let mut idl = json!({
+            "address": self.address,
+            "metadata": metadata,
+            "instructions": instructions,
+            "accounts": self.accounts.iter().map(|a| layout_ref(a, "account")).collect::<Vec<_>>(),
+            "events": self.events.iter().map(|e| layout_ref(e, "event")).collect::<Vec<_>>(),
+            "errors": self.errors_json(),
+            "types": types,
+        });
+        if !self.constants.is_empty() {
+            idl["constants"] = json!(self.constants);
+        }
+        idl
+    }
+
+    fn to_anchor_legacy(&self, warnings: &mut Vec<String>) -> Value {
+        let dialect = IdlDialect::AnchorLegacy;
+        let instructions: Vec<Value> = self
+            .instructions
+            .iter()
+            .map(|ix| {
+                if ix.discriminator != anchor_discriminator("global", &ix.name.to_snake_case()) {
+                    warnings.push(format!("指令 {} 的自定义 discriminator 无法在旧版Anchor IDL中表达", ix.name));
+                }
+                let accounts: Vec<Value> = ix
+                    .accounts
+                    .iter()
+                    .map(|a| {
+                        let mut account = json!({ "name": a.name, "isMut": a.writable, "isSigner": a.signer });
+                        set_flag(&mut account, "isOptional", a.optional);
+                        with_docs(account, &a.docs)
+                    })
+                    .collect();
+                with_docs(
+                    json!({ "name": ix.name, "accounts": accounts, "args": fields_json(&ix.args, dialect) }),
+                    &ix.docs,
+                )
+            })
+            .collect();
+        for (layouts, namespace) in [(&self.accounts, "account"), (&self.events, "event")] {
+            for layout in layouts.iter() {
+                let expected = anchor_discriminator(namespace, &layout.name);
+                if layout.discriminator.as_deref().is_some_and(|d| d != expected) {
+                    warnings.push(format!("{} 的自定义 discriminator 无法在旧版Anchor IDL中表达", layout.name));
+                }
+            }
+        }
+        let events: Vec<Value> = self
+            .events
+            .iter()
+            .map(|e| {
+                let fields: Vec<Value> = e
+                    .fields
+                    .iter()
+                    .map(|f| json!({ "name": f.name, "type": f.ty.to_json(dialect), "index": false }))
+                    .collect();
+                json!({ "name": e.name, "fields": fields })
+            })
+            .collect();
+
+        let mut idl = json!({
+            "version": self.version,
+            "name": self.name,
+            "instructions": instructions,
+            "accounts": self.layouts_json(&self.accounts, dialect, false),
+            "types": self.types_json(dialect, |t| !self.is_layout(&t.name)),
+            "events": events,
+            "errors": self.errors_json(),
+            "metadata": { "address": self.address },
+        });
+        if !self.constants.is_empty() {
+            idl["constants"] = json!(self.constants);
+        }
+        idl
+    }
+
+    fn to_shank(&self, warnings: &mut Vec<String>) -> Value {
+        let dialect = IdlDialect::Shank;
+        let instructions: Vec<Value> = self
+            .instructions
+            .iter()
+            .map(|ix| {
+                if ix.discriminator.len() != 1 {
+                    warnings.push(format!(
+                        "指令 {} 使用 {} 字节 discriminator，Shank 生成的代码只读取首字节",
+                        ix.name,
+                        ix.discriminator.len()
+                    ));
+                }
+                let accounts: Vec<Value> = ix
+                    .accounts
+                    .iter()
+                    .map(|a| {
+                        let mut account = json!({ "name": a.name, "isMut": a.writable, "isSigner": a.signer });
+                        if let Some(address) = &a.address {
+                            account["address"] = json!(address);
+                        }
+                        with_docs(account, &a.docs)
+                    })
+                    .collect();
+                with_docs(
+                    json!({
+                        "name": ix.name,
+                        "accounts": accounts,
+                        "args": fields_json(&ix.args, dialect),
+                        "discriminator": ix.discriminator,
+                    }),
+                    &ix.docs,
+                )
+            })
+            .collect();
+
+        let mut idl = json!({
+            "version": self.version,
+            "name": self.name,
+            "instructions": instructions,
+            "accounts": self.layouts_json(&self.accounts, dialect, true),
+            "types": self.types_json(dialect, |t| !self.is_layout(&t.name)),
+            "errors": self.errors_json(),
+            "metadata": {
+                "name": self.name,
+                "version": self.version,
+                "spec": "shank",
+                "origin": "shank",
+                "address": self.address,
+            },
+        });
+        if !self.events.is_empty() {
+            idl["events"] = json!(self.layouts_json(&self.events, dialect, true));
+        }
+        idl
+    }
+
+    /// 账户/事件布局已单独输出的类型，旧版与 Shank 方言不在 types 中重复
+    fn is_layout(&self, name: &str) -> bool {
+        self.accounts.iter().chain(&self.events).any(|layout| layout.name == name)
+    }
+
+    fn layouts_json(&self, layouts: &[Layout], dialect: IdlDialect, with_discriminator: bool) -> Vec<Value> {
+        layouts
+            .iter()
+            .map(|layout| {
+                let mut value = with_docs(
+                    json!({ "name": layout.name, "type": { "kind": "struct", "fields": fields_json(&layout.fields, dialect) } }),
+                    &layout.docs,
+                );
+                if let Some(discriminator) = layout.discriminator.as_ref().filter(|_| with_discriminator) {
+                    value["discriminator"] = json!(discriminator);
+                }
+                value
+            })
+            .collect()
+    }
+
+    fn types_json(&self, dialect: IdlDialect, include: impl Fn(&TypeDef) -> bool) -> Vec<Value> {
+        self.types
+            .iter()
+            .filter(|t| include(t))
+            .map(|t| {
+                let ty = match &t.body {
+                    TypeBody::Struct(fields) => json!({ "kind": "struct", "fields": fields_json(fields, dialect) }),
+                    TypeBody::Enum(variants) => {
+                        let variants: Vec<Value> = variants
+                            .iter()
+                            .map(|v| {
+                                let mut variant = with_docs(json!({ "name": v.name }), &v.docs);
+                                if let Some(fields) = &v.fields {
+                                    variant["fields"] = json!(fields_json(fields, dialect));
+                                }
+                                if let Some(value) = v.value {
+                                    variant["value"] = json!(value);
+                                }
+                                variant
+                            })
+                            .collect();
+                        json!({ "kind": "enum", "variants": variants })
+                    }
+                    TypeBody::Alias(ty) if dialect == IdlDialect::Anchor030 => {
+                        json!({ "kind": "type", "alias": ty.to_json(dialect) })
+                    }
+                    TypeBody::Alias(ty) => json!({ "kind": "alias", "value": ty.to_json(dialect) }),
+                };
+                with_docs(json!({ "name": t.name, "type": ty }), &t.docs)
+            })
+            .collect()
+    }
+
+    fn errors_json(&self) -> Vec<Value> {
+        self.errors
+            .iter()
+            .map(|e| {
+                let mut error = json!({ "code": e.code, "name": e.name });
+                if let Some(msg) = &e.msg {
+                    error["msg"] = json!(msg);
+                }
+                error
+            })
+            .collect()
+    }
+}
+
+/// 元组字段（名称为 "0"、"1"…）只输出类型
+fn fields_json(fields: &[Field], dialect: IdlDialect) -> Vec<Value> {
+    let is_tuple = !fields.is_empty() && fields.iter().all(|f| f.name.parse::<usize>().is_ok());
+    fields
+        .iter()
+        .map(|f| {
+            if is_tuple {
+                f.ty.to_json(dialect)
+            } else {
+                let mut value = json!({ "name": f.name, "type": f.ty.to_json(dialect) });
+                if let Some(encoding) = f.encoding {
+                    value["encoding"] = json!(encoding);
+                }
+                with_docs(value, &f.docs)
+            }
+        })
+        .collect()
+}
+
+fn with_docs(mut value: Value, docs: &[String]) -> Value {
+    if !docs.is_empty() {
+        value["docs"] = json!(docs);
+    }
+    value
+}
+
+/// 仅输出为 true 的布尔标记，与 Anchor 0.30 生成的 IDL 一致
+fn set_flag(value: &mut Value, key: &str, flag: bool) {
+    if flag {
+        if let Some(obj) = value.as_object_mut() {
+            obj.insert(key.to_string(), json!(true));
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn legacy_anchor_round_trips_through_anchor_030() {
+        let legacy = json!({
+            "version": "0.1.0",
+            "name": "vault",
+            "instructions": [{
+                "name": "depositFunds",
+                "accounts": [{"name": "owner", "isMut": true, "isSigner": true}],
+                "args": [{"name": "amount", "type": "u64"}, {"name": "mint", "type": "publicKey"}]
+            }],
+            "types": [{"name": "Side", "type": {"kind": "enum", "variants": [{"name": "Bid"}, {"name": "Ask"}]}}],
+            "metadata": {"address": "11111111111111111111111111111111"}
+        })
+        .to_string();
+
+        let idl = load(&legacy, None).unwrap();
+        let (modern, warnings) = convert(&idl, IdlDialect::Anchor030);
+        assert!(warnings.is_empty());
+        assert_eq!(modern["address"], "11111111111111111111111111111111");
+        let ix = &modern["instructions"][0];
+        assert_eq!(ix["discriminator"], json!(anchor_discriminator("global", "deposit_funds")));
+        assert_eq!(ix["accounts"][0], json!({"name": "owner", "writable": true, "signer": true}));
+        assert_eq!(ix["args"][1]["type"], "pubkey");
+
+        let reparsed = load(&modern.to_string(), None).unwrap();
+        let (legacy_again, _) = convert(&reparsed, IdlDialect::AnchorLegacy);
+        assert_eq!(legacy_again["instructions"][0]["args"][1]["type"], "publicKey");
+        assert!(legacy_again["instructions"][0].get("discriminator").is_none());
+    }
+}
@@ -0,0 +1,98 @@
+//! 预演模式
+//!
+//! `--dry-run` 在临时目录中执行完整的生成流水线，打印将要生成的文件树、
+//! 每个文件的条目数与行数，不向输出目录写入任何内容。
+//! 用于在落盘前评估 `--generate-parser`、命名前后缀等选项的影响。
+
+use std::fs;
+use std::path::{Path, PathBuf};
+
+use crate::{error::SoloresError, idl_format::IdlFormatEnum, pipeline, Args};
+
+/// 计划生成的单个文件
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct PlannedFile {
+    /// 相对 crate 根目录的路径
+    pub path: PathBuf,
+    /// 顶层条目数（不含 use），非 Rust 文件为 None
+    pub items: Option<usize>,
+    /// 非空行数
+    pub lines: usize,
+}
+
+/// 在临时目录中运行流水线，返回计划生成的文件列表（按路径排序）
+pub fn plan(args: &Args, ir: &IdlFormatEnum) -> Result<Vec<PlannedFile>, SoloresError> {
+    let scratch_dir = std::env::temp_dir().join(format!("solores-dry-run-{}", std::process::id()));
+    let crate_dir = scratch_dir.join(&args.output_crate_name);
+
+    let mut scratch_args = args.clone();
+    scratch_args.output_dir = crate_dir.clone();
+    let result = pipeline::run(&scratch_args, ir).and_then(|_| collect_files(&crate_dir));
+
+    let _ = fs::remove_dir_all(&scratch_dir);
+    result
+}
+
+/// 打印文件树与统计
+pub fn print_plan(output_dir: &Path, files: &[PlannedFile]) {
+    println!("📋 预演模式：以下文件将写入 {}（未写入任何内容）", output_dir.display());
+    for file in files {
+        let items = file.items.map(|items| items.to_string()).unwrap_or_else(|| "-".to_string());
+        println!("  {:<56} {:>5} items {:>7} lines", file.path.display(), items, file.lines);
+    }
+    let rust_files = files.iter().filter(|file| file.items.is_some()).count();
+    let items: usize = files.iter().filter_map(|file| file.items).sum();
+    let lines: usize = files.iter().map(|file| file.lines).sum();
+    println!(
+        "📊 共 {} 个文件（{} 个 Rust 文件），{} 个条目，约 {} 行",
+        files.len(),
+        rust_files,
+        items,
+        lines
+    );
+}
+
+fn collect_files(crate_dir: &Path) -> Result<Vec<PlannedFile>, SoloresError> {
+    let mut files = Vec::new();
+    let mut pending = vec![crate_dir.to_path_buf()];
+    while let Some(dir) = pending.pop() {
+        let entries = fs::read_dir(&dir)
+            .map_err(|e| SoloresError::file_operation_error("读取预演目录", dir.display().to_string(), e))?;
+        for entry in entries.flatten() {
+            let path = entry.path();
+            if path.is_dir() {
+                pending.push(path);
+                continue;
+            }
+            let content = fs::read_to_string(&path)
+                .map_err(|e| SoloresError::file_operation_error("读取预演文件", path.display().to_string(), e))?;
+            let items = (path.extension().and_then(|ext| ext.to_str()) == Some("rs"))
+                .then(|| count_items(&content));
+            files.push(PlannedFile {
+                path: path.strip_prefix(crate_dir).unwrap_or(&path).to_path_buf(),
+                items,
+                lines: content.lines().filter(|line| !line.trim().is_empty()).count(),
+            });
+        }
+    }
+    files.sort_by(|a, b| a.path.cmp(&b.path));
+    Ok(files)
+}
+
+/// 统计顶层条目（struct / enum / fn / impl / mod 等），无法解析时记为 0
+fn count_items(content: &str) -> usize {
+    syn::parse_file(content)
+        .map(|file| file.items.iter().filter(|item| !matches!(item, syn::Item::Use(_))).count())
+        .unwrap_or(0)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn counts_top_level_items_without_uses() {
+        let source = "use std::fmt;\npub mod a;\npub struct A;\nimpl A { fn f() {} }\n";
+        assert_eq!(count_items(source), 3);
+    }
+}
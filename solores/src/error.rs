@@ -204,7 +204,7 @@ fn suggest_syntax_fix(content: &str, line: usize, column: usize) -> String {
     let chars: Vec<char> = error_line.chars().collect();
     
     if column > 0 && column <= chars.len() {
-        let context_start = (column.saturating_sub(10)).max(0);
+        let context_start = column.saturating_sub(10);
         let context_end = (column + 10).min(chars.len());
         let context: String = chars[context_start..context_end].iter().collect();
         
@@ -371,7 +371,24 @@ pub fn format_user_error(error: &SoloresError) -> String {
             output.push_str("\n💡 建议: 请检查模板语法和数据类型是否正确");
             output
         }
-        
+
+        SoloresError::ValidationError { message, field_path, expected, actual } => {
+            let mut output = format!("❌ 校验失败:\n{}", message);
+
+            if let Some(path) = field_path {
+                output.push_str(&format!("\n📍 位置: {}", path));
+            }
+
+            if let Some(e) = expected {
+                output.push_str(&format!("\n期望: {}", e));
+            }
+
+            if let Some(a) = actual {
+                output.push_str(&format!("\n实际: {}", a));
+            }
+            output
+        }
+
         _ => format!("❌ 错误: {}", error)
     }
 }
@@ -0,0 +1,184 @@
+//! 主网交易解析测试素材
+//!
+//! `--fixtures-from-rpc <签名列表>` 在生成时通过 RPC `getTransaction` 获取列出的交易，
+//! 提取其中调用目标程序的指令（含内部指令）的数据与账户，生成 tests/rpc_fixtures.rs：
+//! 每条指令一个测试，断言生成的解析器能解码并得到按 discriminator 预期的指令，
+//! 启用 serde 时再与 tests/snapshots 下的参数 JSON 快照比较。
+//!
+//! 签名列表每行一个签名，`#` 开头为注释。请求经由 `curl` 发出，不引入 HTTP 依赖。
+
+use std::fs;
+use std::path::Path;
+use std::process::Command;
+
+use serde::Serialize;
+use serde_json::{json, Value};
+
+use crate::error::SoloresError;
+
+/// `--rpc-url` 默认值
+pub const DEFAULT_RPC_URL: &str = "https://api.mainnet-beta.solana.com";
+
+/// 交易中调用目标程序的一条指令
+#[derive(Debug, Clone, PartialEq, Eq, Serialize)]
+pub struct InstructionFixture {
+    /// 交易签名
+    pub signature: String,
+    /// 指令位置：外层指令为 `"2"`，内部指令为 `"2.1"`
+    pub index: String,
+    /// 指令数据
+    pub data: Vec<u8>,
+    /// 指令账户（base58）
+    pub accounts: Vec<String>,
+}
+
+/// 读取签名列表并获取其中调用 `program_id` 的全部指令
+pub fn fetch_fixtures(signatures_path: &Path, rpc_url: &str, program_id: &str) -> Result<Vec<InstructionFixture>, SoloresError> {
+    let content = fs::read_to_string(signatures_path)
+        .map_err(|e| SoloresError::file_operation_error("读取签名列表", signatures_path.display().to_string(), e))?;
+    let mut signatures: Vec<&str> = Vec::new();
+    for line in content.lines().map(str::trim) {
+        if !line.is_empty() && !line.starts_with('#') && !signatures.contains(&line) {
+            signatures.push(line);
+        }
+    }
+
+    let mut fixtures = Vec::new();
+    for signature in &signatures {
+        let transaction = get_transaction(rpc_url, signature)?;
+        let found = extract_fixtures(signature, &transaction, program_id).map_err(|message| rpc_error(signature, message))?;
+        if found.is_empty() {
+            log::warn!("⚠️ 交易 {} 中没有调用程序 {} 的指令", signature, program_id);
+        }
+        fixtures.extend(found);
+    }
+    log::info!("📥 从 {} 笔交易中提取 {} 条指令", signatures.len(), fixtures.len());
+    Ok(fixtures)
+}
+
+/// 调用 `getTransaction`（json 编码，支持 v0 交易），返回 `result`
+fn get_transaction(rpc_url: &str, signature: &str) -> Result<Value, SoloresError> {
+    let request = json!({
+        "jsonrpc": "2.0",
+        "id": 1,
+        "method": "getTransaction",
+        "params": [signature, {"encoding": "json", "maxSupportedTransactionVersion": 0}],
+    });
+    let output = Command::new("curl")
+        .args(["-sS", "--fail", "-X", "POST", "-H", "Content-Type: application/json", "--data-binary"])
+        .arg(request.to_string())
+        .arg(rpc_url)
+        .output()
+        .map_err(|e| SoloresError::file_operation_error("运行 curl", rpc_url, e))?;
+    if !output.status.success() {
+        return Err(rpc_error(signature, String::from_utf8_lossy(&output.stderr).trim().to_string()));
+    }
+
+    let response: Value = serde_json::from_slice(&output.stdout).map_err(|e| rpc_error(signature, e.to_string()))?;
+    if let Some(error) = response.get("error") {
+        return Err(rpc_error(signature, error.to_string()));
+    }
+    match response.get("result") {
+        Some(result) if !result.is_null() => Ok(result.clone()),
+        _ => Err(rpc_error(signature, "交易不存在或已超出节点的历史范围".to_string())),
+    }
+}
+
+/// 从 `getTransaction` 结果中提取调用 `program_id` 的外层与内部指令
+pub fn extract_fixtures(signature: &str, transaction: &Value, program_id: &str) -> Result<Vec<InstructionFixture>, String> {
+    let message = &transaction["transaction"]["message"];
+    let meta = &transaction["meta"];
+    // v0 交易的账户表 = 静态账户 + 地址查找表加载的可写账户 + 只读账户
+    let keys: Vec<&str> = [
+        &message["accountKeys"],
+        &meta["loadedAddresses"]["writable"],
+        &meta["loadedAddresses"]["readonly"],
+    ]
+    .into_iter()
+    .filter_map(Value::as_array)
+    .flatten()
+    .filter_map(Value::as_str)
+    .collect();
+    if keys.is_empty() {
+        return Err("交易缺少 accountKeys（需要 json 编码）".to_string());
+    }
+
+    let mut instructions: Vec<(String, &Value)> = message["instructions"]
+        .as_array()
+        .into_iter()
+        .flatten()
+        .enumerate()
+        .map(|(index, instruction)| (index.to_string(), instruction))
+        .collect();
+    for inner in meta["innerInstructions"].as_array().into_iter().flatten() {
+        let outer = inner["index"].as_u64().unwrap_or_default();
+        let nested = inner["instructions"].as_array().into_iter().flatten().enumerate();
+        instructions.extend(nested.map(|(index, instruction)| (format!("{}.{}", outer, index), instruction)));
+    }
+    // 内部指令紧跟其外层指令
+    instructions.sort_by_key(|(index, _)| {
+        index.split('.').map(|part| part.parse::<usize>().unwrap_or_default()).collect::<Vec<_>>()
+    });
+
+    let key = |index: &Value| {
+        index.as_u64().and_then(|index| keys.get(index as usize).copied()).ok_or_else(|| format!("账户索引 {} 越界", index))
+    };
+    let mut fixtures = Vec::new();
+    for (index, instruction) in instructions {
+        if key(&instruction["programIdIndex"])? != program_id {
+            continue;
+        }
+        let data = instruction["data"].as_str().ok_or_else(|| format!("指令 {} 缺少 data", index))?;
+        let data = bs58::decode(data).into_vec().map_err(|e| format!("指令 {} 的 data 不是 base58: {}", index, e))?;
+        let accounts = instruction["accounts"]
+            .as_array()
+            .into_iter()
+            .flatten()
+            .map(|account| key(account).map(str::to_string))
+            .collect::<Result<_, _>>()?;
+        fixtures.push(InstructionFixture { signature: signature.to_string(), index, data, accounts });
+    }
+    Ok(fixtures)
+}
+
+fn rpc_error(signature: &str, message: String) -> SoloresError {
+    SoloresError::ValidationError {
+        message: format!("获取交易失败: {}", message),
+        field_path: Some(format!("--fixtures-from-rpc {}", signature)),
+        expected: None,
+        actual: None,
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn extracts_outer_and_inner_instructions_of_the_program() {
+        let program = "whirLbMiicVdio4qvUfM5KAg6Ct8VwpYzGff3uctyCc";
+        let transaction = json!({
+            "transaction": {"message": {
+                "accountKeys": ["Payer111", program, "Pool1111"],
+                "instructions": [
+                    {"programIdIndex": 1, "accounts": [0, 2, 3], "data": bs58::encode([1u8, 2, 3]).into_string()},
+                    {"programIdIndex": 0, "accounts": [], "data": ""}
+                ]
+            }},
+            "meta": {
+                "loadedAddresses": {"writable": ["Vault111"], "readonly": []},
+                "innerInstructions": [{"index": 1, "instructions": [
+                    {"programIdIndex": 1, "accounts": [3], "data": bs58::encode([9u8]).into_string()}
+                ]}]
+            }
+        });
+
+        let fixtures = extract_fixtures("sig", &transaction, program).unwrap();
+        assert_eq!(fixtures.len(), 2);
+        assert_eq!(fixtures[0].index, "0");
+        assert_eq!(fixtures[0].data, [1, 2, 3]);
+        assert_eq!(fixtures[0].accounts, ["Payer111", "Pool1111", "Vault111"]);
+        assert_eq!(fixtures[1].index, "1.0");
+        assert_eq!(fixtures[1].accounts, ["Vault111"]);
+    }
+}
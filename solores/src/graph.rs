@@ -0,0 +1,168 @@
+//! IDL 依赖图可视化
+//!
+//! `solores graph idl.json` 输出指令 → 账户 / 参数类型 → 嵌套类型，以及事件引用类型的
+//! 依赖图（DOT 或 mermaid），依赖图与 `--include-types` 闭包分析相同，可配合
+//! `--include-types` / `--exclude-types` 检查裁剪结果。生成 crate 时 `--readme-graph`
+//! 把 mermaid 图嵌入 README.md。
+
+use std::fmt::Write;
+use std::fs::{self, OpenOptions};
+use std::path::PathBuf;
+
+use crate::error::SoloresError;
+use crate::idl_format::type_selection::{DependencyGraph, NodeKind};
+use crate::idl_format::{IdlFormat, IdlFormatEnum};
+
+/// 依赖图输出格式
+#[derive(clap::ValueEnum, Debug, Default, Clone, Copy, PartialEq, Eq)]
+pub enum GraphFormat {
+    /// Graphviz DOT
+    Dot,
+    /// mermaid flowchart，可直接嵌入 Markdown
+    #[default]
+    Mermaid,
+}
+
+/// `solores graph` 参数
+#[derive(clap::Args, Debug, Clone)]
+pub struct GraphArgs {
+    /// IDL 文件
+    pub idl: PathBuf,
+
+    /// 输出格式
+    #[arg(long, value_enum, default_value_t = GraphFormat::Mermaid)]
+    pub format: GraphFormat,
+
+    /// 输出文件，缺省时打印到标准输出
+    #[arg(long, short)]
+    pub output: Option<PathBuf>,
+
+    /// 仅展示指定条目及其引用类型的闭包，规则同生成参数 --include-types
+    #[arg(long, value_delimiter = ',')]
+    pub include_types: Vec<String>,
+
+    /// 移除指定条目，规则同生成参数 --exclude-types
+    #[arg(long, value_delimiter = ',')]
+    pub exclude_types: Vec<String>,
+}
+
+/// 执行 `solores graph`
+pub fn run(args: &GraphArgs) -> Result<(), SoloresError> {
+    let mut file = OpenOptions::new()
+        .read(true)
+        .open(&args.idl)
+        .map_err(|e| SoloresError::file_operation_error("读取IDL文件", args.idl.display().to_string(), e))?;
+    let mut idl = crate::load_idl_as_enum(&mut file)?;
+    idl.apply_type_selection(&args.include_types, &args.exclude_types)?;
+
+    let rendered = render(&idl, args.format);
+    match &args.output {
+        Some(path) => fs::write(path, rendered)
+            .map_err(|e| SoloresError::file_operation_error("写入依赖图", path.display().to_string(), e)),
+        None => {
+            print!("{}", rendered);
+            Ok(())
+        }
+    }
+}
+
+/// 渲染 IR 的依赖图
+pub fn render(idl: &IdlFormatEnum, format: GraphFormat) -> String {
+    let graph = idl.dependency_graph();
+    match format {
+        GraphFormat::Dot => render_dot(&graph, idl.program_name()),
+        GraphFormat::Mermaid => render_mermaid(&graph),
+    }
+}
+
+fn render_dot(graph: &DependencyGraph, program_name: &str) -> String {
+    let mut out = format!("digraph \"{}\" {{\n    rankdir=LR;\n    node [fontname=\"Helvetica\"];\n", program_name);
+    for (index, (kind, name)) in graph.nodes.iter().enumerate() {
+        let (shape, color) = match kind {
+            NodeKind::Instruction => ("box", "#dbeafe"),
+            NodeKind::Account => ("cylinder", "#dcfce7"),
+            NodeKind::Event => ("hexagon", "#fef3c7"),
+            NodeKind::Type => ("ellipse", "#f3f4f6"),
+        };
+        let _ = writeln!(
+            out,
+            "    n{} [label=\"{}\", shape={}, style=filled, fillcolor=\"{}\"];",
+            index,
+            name.replace('"', "\\\""),
+            shape,
+            color
+        );
+    }
+    for (from, to) in &graph.edges {
+        let _ = writeln!(out, "    n{} -> n{};", from, to);
+    }
+    out.push_str("}\n");
+    out
+}
+
+fn render_mermaid(graph: &DependencyGraph) -> String {
+    let mut out = String::from("flowchart LR\n");
+    for (index, (kind, name)) in graph.nodes.iter().enumerate() {
+        let name = name.replace('"', "#quot;");
+        let node = match kind {
+            NodeKind::Instruction => format!("[\"{}\"]", name),
+            NodeKind::Account => format!("[(\"{}\")]", name),
+            NodeKind::Event => format!("{{{{\"{}\"}}}}", name),
+            NodeKind::Type => format!("(\"{}\")", name),
+        };
+        let _ = writeln!(out, "    n{}{}:::{}", index, node, kind.as_str());
+    }
+    for (from, to) in &graph.edges {
+        let _ = writeln!(out, "    n{} --> n{}", from, to);
+    }
+    out.push_str(
+        "    classDef instruction fill:#dbeafe,stroke:#1d4ed8\n\
+         \x20   classDef account fill:#dcfce7,stroke:#15803d\n\
+         \x20   classDef event fill:#fef3c7,stroke:#b45309\n\
+         \x20   classDef type fill:#f3f4f6,stroke:#4b5563\n",
+    );
+    out
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::idl_format::parse_idl_json;
+
+    const IDL: &str = r#"{
+        "address": "11111111111111111111111111111111",
+        "metadata": {"name": "demo", "version": "0.1.0", "spec": "0.1.0"},
+        "instructions": [
+            {"name": "swap", "discriminator": [1,0,0,0,0,0,0,0], "accounts": [{"name": "pool", "writable": true}],
+             "args": [{"name": "params", "type": {"defined": {"name": "SwapParams"}}}]}
+        ],
+        "accounts": [{"name": "Pool", "discriminator": [2,0,0,0,0,0,0,0]}],
+        "events": [{"name": "SwapEvent", "discriminator": [3,0,0,0,0,0,0,0]}],
+        "types": [
+            {"name": "Pool", "type": {"kind": "struct", "fields": [{"name": "fees", "type": {"vec": {"defined": {"name": "FeeTier"}}}}]}},
+            {"name": "FeeTier", "type": {"kind": "struct", "fields": [{"name": "bps", "type": "u16"}]}},
+            {"name": "SwapParams", "type": {"kind": "struct", "fields": [{"name": "amount", "type": "u64"}]}},
+            {"name": "SwapEvent", "type": {"kind": "struct", "fields": [{"name": "fee", "type": {"defined": {"name": "FeeTier"}}}]}}
+        ]
+    }"#;
+
+    #[test]
+    fn graph_links_instructions_accounts_and_types() {
+        let idl = parse_idl_json(IDL).unwrap();
+        let graph = idl.dependency_graph();
+        let node = |name: &str| graph.nodes.iter().position(|(_, n)| n == name).unwrap();
+        let edge = |from: &str, to: &str| graph.edges.contains(&(node(from), node(to)));
+
+        assert_eq!(graph.nodes.len(), 5);
+        assert_eq!(graph.nodes[node("Pool")].0, NodeKind::Account);
+        assert_eq!(graph.nodes[node("SwapEvent")].0, NodeKind::Event);
+        assert!(edge("swap", "Pool") && edge("swap", "SwapParams"));
+        assert!(edge("Pool", "FeeTier") && edge("SwapEvent", "FeeTier"));
+        assert_eq!(graph.edges.len(), 4);
+
+        let mermaid = render(&idl, GraphFormat::Mermaid);
+        assert!(mermaid.starts_with("flowchart LR\n"));
+        assert!(mermaid.contains(&format!("n{}[(\"Pool\")]:::account", node("Pool"))));
+        assert!(render(&idl, GraphFormat::Dot).contains(&format!("n{} -> n{};", node("swap"), node("Pool"))));
+    }
+}
@@ -0,0 +1,298 @@
+//! 同名账户的多种布局（账户联合）
+//!
+//! 部分程序用账户内部的版本字段（而非 discriminator）区分同一账户名下的多种布局。
+//! IDL 账户对象上的 `variants` 或 `--account-variants` 文件声明版本字段及各取值对应的
+//! 布局类型（`types` 中的结构体）：
+//!
+//! ```json
+//! {"Pool": {"field": "version", "layouts": {"1": "PoolV1", "2": "PoolV2"}}}
+//! ```
+//!
+//! 版本字段须为无符号整数，且在每个布局中位于同一偏移、之前只有定长字段。
+//! 生成代码为 `{Account}Variant` 枚举：读取版本字段后按对应布局解码，并为所有布局中
+//! 同名同类型的字段生成访问方法。Anchor 账户的布局类型描述 8 字节 discriminator 之后的
+//! 数据，NonAnchor 账户的布局类型描述完整账户数据。
+
+use std::collections::BTreeMap;
+use std::path::Path;
+
+use convert_case::{Case, Casing};
+use serde::Deserialize;
+
+use super::anchor_idl::{AnchorField, AnchorFieldType, AnchorIdl, AnchorTypeKind};
+use super::non_anchor_idl::{NonAnchorField, NonAnchorFieldType, NonAnchorIdl, NonAnchorTypeKind};
+use super::zero_copy::{anchor_fixed_size, non_anchor_fixed_size};
+use super::IdlFormatEnum;
+use crate::error::SoloresError;
+
+/// 账户的布局变体声明
+#[derive(Debug, Clone, PartialEq, Eq, Deserialize)]
+pub struct AccountVariants {
+    /// 区分布局的版本字段
+    pub field: String,
+    /// 版本字段取值 → 布局类型名
+    pub layouts: BTreeMap<u64, String>,
+}
+
+impl AccountVariants {
+    /// 读取 IDL 账户对象上的 `variants` 声明
+    pub fn from_idl_item(item: &serde_json::Value) -> Option<Self> {
+        serde_json::from_value(item.get("variants")?.clone()).ok()
+    }
+}
+
+/// 校验后的布局变体：版本字段的偏移（相对布局类型起始）与整数类型
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct ResolvedVariants {
+    /// 版本字段（snake_case）
+    pub field: String,
+    pub offset: usize,
+    pub int_type: &'static str,
+    /// (取值, IDL 中的布局类型名)，按取值排序
+    pub layouts: Vec<(u64, String)>,
+}
+
+/// 布局字段的名称、紧凑布局长度与整数类型
+struct LayoutField {
+    name: String,
+    size: Option<usize>,
+    int_type: Option<&'static str>,
+}
+
+fn unsigned_int(type_name: &str) -> Option<&'static str> {
+    ["u8", "u16", "u32", "u64"].into_iter().find(|int| *int == type_name)
+}
+
+/// 校验每个布局都是结构体，且版本字段位于相同偏移、类型相同
+fn resolve(
+    variants: &AccountVariants,
+    layout_fields: impl Fn(&str) -> Result<Vec<LayoutField>, String>,
+) -> Result<ResolvedVariants, String> {
+    if variants.layouts.is_empty() {
+        return Err("至少需要声明一个布局".to_string());
+    }
+    let field = variants.field.to_case(Case::Snake);
+    let mut resolved: Option<(usize, &'static str)> = None;
+    for (value, type_name) in &variants.layouts {
+        let fields = layout_fields(type_name)?;
+        let index = fields
+            .iter()
+            .position(|f| f.name == field)
+            .ok_or_else(|| format!("布局 {} 没有字段 {}", type_name, field))?;
+        let offset = fields[..index]
+            .iter()
+            .map(|f| f.size)
+            .sum::<Option<usize>>()
+            .ok_or_else(|| format!("布局 {} 的字段 {} 之前有变长字段", type_name, field))?;
+        let int_type = fields[index]
+            .int_type
+            .ok_or_else(|| format!("布局 {} 的字段 {} 不是 u8 / u16 / u32 / u64", type_name, field))?;
+        if *value > u64::MAX >> (64 - 8 * fields[index].size.unwrap_or(8)) {
+            return Err(format!("取值 {} 超出 {} 的范围", value, int_type));
+        }
+        match resolved {
+            Some((expected, expected_type)) if (expected, expected_type) != (offset, int_type) => {
+                return Err(format!(
+                    "字段 {} 在布局 {} 中为偏移 {} 的 {}，与其他布局的偏移 {} 的 {} 不一致",
+                    field, type_name, offset, int_type, expected, expected_type
+                ))
+            }
+            _ => resolved = Some((offset, int_type)),
+        }
+    }
+    let (offset, int_type) = resolved.unwrap_or((0, "u8"));
+    Ok(ResolvedVariants {
+        field,
+        offset,
+        int_type,
+        layouts: variants.layouts.iter().map(|(value, name)| (*value, name.clone())).collect(),
+    })
+}
+
+/// Anchor 布局类型的字段
+pub fn anchor_layout_fields<'a>(idl: &'a AnchorIdl, type_name: &str) -> Result<&'a [AnchorField], String> {
+    let type_def = idl
+        .types
+        .iter()
+        .flatten()
+        .find(|t| t.name == type_name)
+        .ok_or_else(|| format!("未定义的布局类型 {}", type_name))?;
+    match type_def.kind.as_ref() {
+        Some(AnchorTypeKind::Struct(fields)) => Ok(fields),
+        _ => Err(format!("布局类型 {} 不是结构体", type_name)),
+    }
+}
+
+/// NonAnchor 布局类型的字段
+pub fn non_anchor_layout_fields<'a>(idl: &'a NonAnchorIdl, type_name: &str) -> Result<&'a [NonAnchorField], String> {
+    let type_def = idl
+        .types
+        .iter()
+        .flatten()
+        .find(|t| t.name == type_name)
+        .ok_or_else(|| format!("未定义的布局类型 {}", type_name))?;
+    match &type_def.type_def {
+        NonAnchorTypeKind::Struct { fields } => Ok(fields),
+        _ => Err(format!("布局类型 {} 不是结构体", type_name)),
+    }
+}
+
+/// 校验 Anchor 账户的布局变体
+pub fn resolve_anchor(variants: &AccountVariants, idl: &AnchorIdl) -> Result<ResolvedVariants, String> {
+    resolve(variants, |type_name| {
+        Ok(anchor_layout_fields(idl, type_name)?
+            .iter()
+            .map(|field| LayoutField {
+                name: field.name.to_case(Case::Snake),
+                size: anchor_fixed_size(&field.field_type, idl),
+                int_type: match &field.field_type {
+                    AnchorFieldType::Basic(name) | AnchorFieldType::PrimitiveOrPubkey(name) => unsigned_int(name),
+                    _ => None,
+                },
+            })
+            .collect())
+    })
+}
+
+/// 校验 NonAnchor 账户的布局变体
+pub fn resolve_non_anchor(variants: &AccountVariants, idl: &NonAnchorIdl) -> Result<ResolvedVariants, String> {
+    resolve(variants, |type_name| {
+        Ok(non_anchor_layout_fields(idl, type_name)?
+            .iter()
+            .map(|field| LayoutField {
+                name: field.name.to_case(Case::Snake),
+                size: non_anchor_fixed_size(&field.field_type, idl),
+                int_type: match &field.field_type {
+                    NonAnchorFieldType::Basic(name) => unsigned_int(name),
+                    _ => None,
+                },
+            })
+            .collect())
+    })
+}
+
+impl IdlFormatEnum {
+    /// 合并 `--account-variants` 声明并校验所有账户的布局变体
+    pub fn apply_account_variants(&mut self, variants_file: Option<&Path>) -> Result<(), SoloresError> {
+        if let Some(path) = variants_file {
+            for (name, variants) in load_variants_file(path)? {
+                let key = name.trim().to_case(Case::Pascal);
+                let target = match self {
+                    IdlFormatEnum::Anchor(idl) => idl
+                        .accounts
+                        .iter_mut()
+                        .flatten()
+                        .find(|a| a.name.to_case(Case::Pascal) == key)
+                        .map(|a| &mut a.variants),
+                    IdlFormatEnum::NonAnchor(idl) => idl
+                        .accounts
+                        .iter_mut()
+                        .flatten()
+                        .find(|a| a.name.to_case(Case::Pascal) == key)
+                        .map(|a| &mut a.variants),
+                };
+                match target {
+                    Some(target) => *target = Some(variants),
+                    None => {
+                        return Err(SoloresError::ValidationError {
+                            message: format!("未找到账户 '{}'", name),
+                            field_path: Some(format!("--account-variants {}", path.display())),
+                            expected: None,
+                            actual: Some(key),
+                        })
+                    }
+                }
+            }
+        }
+
+        let invalid = |name: &str, message: String| SoloresError::ValidationError {
+            message: format!("账户 {} 的布局变体无效: {}", name, message),
+            field_path: Some(format!("accounts.{}.variants", name)),
+            expected: None,
+            actual: None,
+        };
+        match self {
+            IdlFormatEnum::Anchor(idl) => {
+                for account in idl.accounts.iter().flatten() {
+                    if let Some(variants) = &account.variants {
+                        resolve_anchor(variants, idl).map_err(|e| invalid(&account.name, e))?;
+                    }
+                }
+            }
+            IdlFormatEnum::NonAnchor(idl) => {
+                for account in idl.accounts.iter().flatten() {
+                    let Some(variants) = &account.variants else { continue };
+                    if account.c_layout.is_some() {
+                        return Err(invalid(&account.name, "C 布局账户不支持布局变体".to_string()));
+                    }
+                    resolve_non_anchor(variants, idl).map_err(|e| invalid(&account.name, e))?;
+                }
+            }
+        }
+        Ok(())
+    }
+}
+
+/// 读取 `--account-variants`：`{"账户": {"field": "字段", "layouts": {"取值": "类型"}}}`
+fn load_variants_file(path: &Path) -> Result<BTreeMap<String, AccountVariants>, SoloresError> {
+    let content = std::fs::read_to_string(path)
+        .map_err(|e| SoloresError::file_operation_error("读取账户布局变体文件", path.display().to_string(), e))?;
+    serde_json::from_str(&content).map_err(|e| SoloresError::ValidationError {
+        message: format!("账户布局变体文件格式错误: {}", e),
+        field_path: Some(path.display().to_string()),
+        expected: Some("{\"账户\": {\"field\": \"字段\", \"layouts\": {\"取值\": \"类型\"}}}".to_string()),
+        actual: None,
+    })
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn version_field_must_share_offset_and_type() {
+        let idl = NonAnchorIdl::parse_json(
+            &serde_json::json!({
+                "address": "11111111111111111111111111111111",
+                "metadata": {"name": "pools", "version": "0.1.0", "spec": "0.1.0"},
+                "accounts": [{
+                    "name": "Pool",
+                    "fields": [{"name": "authority", "type": "pubkey"}, {"name": "version", "type": "u8"}],
+                    "variants": {"field": "version", "layouts": {"1": "PoolV1", "2": "PoolV2"}}
+                }],
+                "types": [
+                    {"name": "PoolV1", "type": {"kind": "struct", "fields": [
+                        {"name": "authority", "type": "pubkey"},
+                        {"name": "version", "type": "u8"},
+                        {"name": "fee", "type": "u16"}
+                    ]}},
+                    {"name": "PoolV2", "type": {"kind": "struct", "fields": [
+                        {"name": "authority", "type": "pubkey"},
+                        {"name": "version", "type": "u8"},
+                        {"name": "fee", "type": "u32"},
+                        {"name": "name", "type": "string"}
+                    ]}},
+                    {"name": "PoolV3", "type": {"kind": "struct", "fields": [
+                        {"name": "name", "type": "string"},
+                        {"name": "version", "type": "u8"}
+                    ]}}
+                ]
+            })
+            .to_string(),
+        )
+        .unwrap();
+        let variants = idl.accounts.as_ref().unwrap()[0].variants.clone().unwrap();
+        let resolved = resolve_non_anchor(&variants, &idl).unwrap();
+        assert_eq!((resolved.field.as_str(), resolved.offset, resolved.int_type), ("version", 32, "u8"));
+        assert_eq!(resolved.layouts, [(1, "PoolV1".to_string()), (2, "PoolV2".to_string())]);
+
+        let variable_prefix = AccountVariants {
+            field: "version".to_string(),
+            layouts: BTreeMap::from([(1, "PoolV1".to_string()), (3, "PoolV3".to_string())]),
+        };
+        assert!(resolve_non_anchor(&variable_prefix, &idl).unwrap_err().contains("变长"));
+        let out_of_range =
+            AccountVariants { field: "version".to_string(), layouts: BTreeMap::from([(256, "PoolV1".to_string())]) };
+        assert!(resolve_non_anchor(&out_of_range, &idl).unwrap_err().contains("范围"));
+    }
+}
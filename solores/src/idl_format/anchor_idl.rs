@@ -4,6 +4,9 @@
 //! Anchor特有的字段约定等Anchor特性
 
 use serde::{Deserialize, Serialize};
+
+use super::account_variants::AccountVariants;
+use super::arg_encoding::ArgEncoding;
 // use std::sync::OnceLock;
 
 // 类型别名用于兼容Legacy系统
@@ -65,6 +68,12 @@ pub struct AnchorMetadata {
     pub spec: String,
     /// 描述
     pub description: Option<String>,
+    /// 各集群的部署地址（cluster -> address）
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub addresses: Option<std::collections::BTreeMap<String, String>>,
+    /// 引用的其他程序接口 crate
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub dependencies: Option<Vec<super::dependencies::IdlDependency>>,
 }
 
 /// Anchor指令定义
@@ -93,6 +102,25 @@ pub struct AnchorAccount {
     pub fields: Option<Vec<AnchorField>>,
     /// 文档注释
     pub docs: Option<Vec<String>>,
+    /// 零拷贝账户（同名类型 `serialization` 为 bytemuck，或通过 `--zero-copy` 指定）
+    #[serde(skip_serializing)]
+    pub zero_copy: bool,
+    /// 按版本字段区分的多种布局（IDL 中 `variants` 或 `--account-variants`）
+    #[serde(skip_serializing)]
+    pub variants: Option<AccountVariants>,
+    /// 旧版 Anchor `state` 段声明的单例状态账户
+    #[serde(skip_serializing)]
+    pub is_state: bool,
+}
+
+/// Anchor discriminator：`sha256("<namespace>:<name>")` 的前8字节
+pub fn anchor_discriminator(namespace: &str, name: &str) -> [u8; 8] {
+    use sha2::{Digest, Sha256};
+
+    let digest = Sha256::digest(format!("{}:{}", namespace, name).as_bytes());
+    let mut discriminator = [0u8; 8];
+    discriminator.copy_from_slice(&digest[..8]);
+    discriminator
 }
 
 impl<'de> serde::Deserialize<'de> for AnchorAccount {
@@ -116,10 +144,11 @@ impl<'de> serde::Deserialize<'de> for AnchorAccount {
             where
                 V: MapAccess<'de>,
             {
-                let mut name = None;
+                let mut name: Option<String> = None;
                 let mut discriminator = None;
                 let mut fields = None;
                 let mut docs = None;
+                let mut variants = None;
 
                 while let Some(key) = map.next_key::<String>()? {
                     match key.as_str() {
@@ -174,6 +203,8 @@ impl<'de> serde::Deserialize<'de> for AnchorAccount {
                                                                 field_type,
                                                                 kind: None,
                                                                 docs: field_docs,
+                                                                encoding: None,
+                                                                substitute: None,
                                                             });
                                                         }
                                                     }
@@ -195,6 +226,10 @@ impl<'de> serde::Deserialize<'de> for AnchorAccount {
                             }
                             docs = Some(map.next_value()?);
                         }
+                        "variants" => {
+                            let value: serde_json::Value = map.next_value()?;
+                            variants = Some(serde_json::from_value(value).map_err(de::Error::custom)?);
+                        }
                         _ => {
                             // 忽略未知字段
                             let _: serde_json::Value = map.next_value()?;
@@ -203,13 +238,18 @@ impl<'de> serde::Deserialize<'de> for AnchorAccount {
                 }
 
                 let name = name.ok_or_else(|| de::Error::missing_field("name"))?;
-                let discriminator = discriminator.ok_or_else(|| de::Error::missing_field("discriminator"))?;
+                // 旧版IDL不含账户discriminator，按Anchor规则推导
+                let discriminator = discriminator
+                    .unwrap_or_else(|| anchor_discriminator("account", &name));
 
                 Ok(AnchorAccount {
                     name,
                     discriminator,
                     fields,
                     docs,
+                    zero_copy: false,
+                    variants,
+                    is_state: false,
                 })
             }
         }
@@ -259,7 +299,8 @@ pub struct AnchorAccountConstraint {
     /// 是否签名者 - 映射到标准字段名，支持signer, isSigner等
     #[serde(alias = "signer", alias = "isSigner", default)]
     pub is_signer: bool,
-    /// 是否可选
+    /// 是否可选 - Anchor 0.30 写作 optional
+    #[serde(alias = "optional", alias = "isOptional")]
     pub is_optional: Option<bool>,
     /// 约束条件
     pub constraints: Option<Vec<String>>,
@@ -267,6 +308,13 @@ pub struct AnchorAccountConstraint {
     pub docs: Option<Vec<String>>,
     /// PDA定义（如果该账户是PDA）
     pub pda: Option<PdaDefinition>,
+    /// 固定地址（如系统程序）
+    pub address: Option<String>,
+    /// `has_one` 关系：本账户地址须等于所列账户状态中的同名字段
+    pub relations: Option<Vec<String>>,
+    /// 重命名后 `relations` 中相关账户的状态类型名；未记录时按相关账户名称匹配账户类型
+    #[serde(skip)]
+    pub relation_types: std::collections::BTreeMap<String, String>,
 }
 
 /// Anchor类型定义
@@ -278,6 +326,26 @@ pub struct AnchorType {
     pub kind: Option<AnchorTypeKind>,
     /// 文档注释
     pub docs: Option<Vec<String>>,
+    /// 序列化方式（Anchor 0.30：`borsh` / `bytemuck` / `bytemuckunsafe`）
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub serialization: Option<String>,
+    /// 内存布局（Anchor 0.30 `repr`）
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub repr: Option<TypeRepr>,
+    /// 类型自带的 discriminator（`#[derive(Discriminator)]` 等）
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub discriminator: Option<Vec<u8>>,
+}
+
+/// 类型的内存布局：`{"kind": "c", "packed": true, "align": 8}`
+#[derive(Debug, Clone, Default, PartialEq, Eq, Serialize, Deserialize)]
+pub struct TypeRepr {
+    /// `rust` / `c` / `transparent`
+    pub kind: String,
+    #[serde(default)]
+    pub packed: bool,
+    #[serde(default)]
+    pub align: Option<usize>,
 }
 
 impl<'de> serde::Deserialize<'de> for AnchorType {
@@ -304,6 +372,10 @@ impl<'de> serde::Deserialize<'de> for AnchorType {
                 let mut name = None;
                 let mut docs = None;
                 let mut type_info = None;
+                let mut serialization = None;
+                let mut repr = None;
+                let mut discriminator = None;
+                let mut unknown = Vec::new();
 
                 while let Some(key) = map.next_key::<String>()? {
                     match key.as_str() {
@@ -319,6 +391,15 @@ impl<'de> serde::Deserialize<'de> for AnchorType {
                             }
                             docs = Some(map.next_value()?);
                         }
+                        "serialization" => {
+                            serialization = map.next_value()?;
+                        }
+                        "repr" => {
+                            repr = map.next_value()?;
+                        }
+                        "discriminator" => {
+                            discriminator = map.next_value()?;
+                        }
                         "type" => {
                             if type_info.is_some() {
                                 return Err(de::Error::duplicate_field("type"));
@@ -337,7 +418,14 @@ impl<'de> serde::Deserialize<'de> for AnchorType {
                                             // 手动解析字段数组以处理简化的JSON格式
                                             let mut fields = Vec::new();
                                             if let serde_json::Value::Array(fields_array) = fields_value {
-                                                for field_value in fields_array {
+                                                for (index, field_value) in fields_array.iter().enumerate() {
+                                                    // 元组结构体：字段只有类型，按位置命名 "0"、"1"…
+                                                    if !matches!(field_value, serde_json::Value::Object(obj) if obj.contains_key("name")) {
+                                                        let field_type = AnchorFieldType::parse_value(field_value.clone())
+                                                            .map_err(de::Error::custom)?;
+                                                        fields.push(AnchorField::new(index.to_string(), field_type));
+                                                        continue;
+                                                    }
                                                     if let serde_json::Value::Object(field_obj) = field_value {
                                                         if let (Some(name), Some(type_value)) = (
                                                             field_obj.get("name").and_then(|v| v.as_str()),
@@ -355,6 +443,8 @@ impl<'de> serde::Deserialize<'de> for AnchorType {
                                                                 field_type,
                                                                 kind: None, // 对于结构体字段，kind通常为None
                                                                 docs,
+                                                                encoding: None,
+                                                                substitute: None,
                                                             });
                                                         }
                                                     }
@@ -399,6 +489,8 @@ impl<'de> serde::Deserialize<'de> for AnchorType {
                                                                                     field_type,
                                                                                     kind: None,
                                                                                     docs: field_docs,
+                                                                                    encoding: None,
+                                                                                    substitute: None,
                                                                                 });
                                                                             }
                                                                         }
@@ -413,6 +505,7 @@ impl<'de> serde::Deserialize<'de> for AnchorType {
                                                                 name: name.to_string(),
                                                                 fields,
                                                                 docs,
+                                                                value: super::enum_tags::explicit_value(variant_obj),
                                                             });
                                                         }
                                                     }
@@ -440,18 +533,25 @@ impl<'de> serde::Deserialize<'de> for AnchorType {
                             }
                         }
                         _ => {
-                            // 忽略未知字段
+                            // 未知字段不影响生成，保留告警以便发现新的 IDL 规范字段
                             let _: serde_json::Value = map.next_value()?;
+                            unknown.push(key);
                         }
                     }
                 }
 
                 let name = name.ok_or_else(|| de::Error::missing_field("name"))?;
+                if !unknown.is_empty() {
+                    log::warn!("⚠️ 类型 {} 含未识别的字段 {}，已忽略", name, unknown.join(", "));
+                }
                 
                 Ok(AnchorType {
                     name,
                     kind: type_info.flatten(),
                     docs,
+                    serialization,
+                    repr,
+                    discriminator,
                 })
             }
         }
@@ -487,6 +587,9 @@ pub struct AnchorEnumVariant {
     pub fields: Option<Vec<AnchorField>>,
     /// 文档注释
     pub docs: Option<Vec<String>>,
+    /// 显式判别值（IDL `value` / `discriminant`），缺省时为上一变体 + 1
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub value: Option<u64>,
 }
 
 /// Anchor字段定义
@@ -501,6 +604,12 @@ pub struct AnchorField {
     pub kind: Option<String>,
     /// 文档注释
     pub docs: Option<Vec<String>>,
+    /// 参数编码方式（仅指令参数使用）
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub encoding: Option<ArgEncoding>,
+    /// 替换生成的 Rust 类型（`--field-type`），编码仍按 IDL 类型
+    #[serde(skip)]
+    pub substitute: Option<String>,
 }
 
 impl AnchorField {
@@ -511,6 +620,8 @@ impl AnchorField {
             field_type,
             kind: None,
             docs: None,
+            encoding: None,
+            substitute: None,
         }
     }
 
@@ -551,6 +662,10 @@ pub enum AnchorFieldType {
     #[serde(rename = "option")]
     #[allow(non_camel_case_types)]
     option(Box<AnchorFieldType>),
+    /// SPL风格的COption（4字节标签 + 定长载荷）
+    #[serde(rename = "coption")]
+    #[allow(non_camel_case_types)]
+    coption(Box<AnchorFieldType>),
 }
 
 
@@ -600,7 +715,12 @@ impl AnchorIdl {
         log::debug!("🔧 使用手动解析器处理Anchor IDL");
         
         // 首先解析为通用JSON值
-        let json_value: serde_json::Value = serde_json::from_str(json_str)?;
+        let mut json_value: serde_json::Value = serde_json::from_str(json_str)?;
+        // 泛型类型按实际参数单态化，以常量或表达式给出的数组长度先求值为整数
+        super::generics::monomorphize_generics(&mut json_value);
+        super::array_sizes::resolve_array_sizes(&mut json_value).map_err(serde_json::Error::custom)?;
+        super::docs::normalize_docs(&mut json_value);
+        let state_account = super::legacy_state::fold_state(&mut json_value);
         
         let obj = json_value.as_object().ok_or_else(|| {
             serde_json::Error::custom("IDL must be a JSON object")
@@ -640,6 +760,8 @@ impl AnchorIdl {
                 version: version.clone(),
                 spec: "anchor".to_string(),
                 description: Some("Generated metadata".to_string()),
+                addresses: None,
+                dependencies: None,
             }
         };
         
@@ -755,7 +877,7 @@ impl AnchorIdl {
         
         log::debug!("✅ Anchor手动解析完成 - name: {:?}, version: {:?}", name, version);
         
-        Ok(AnchorIdl {
+        let mut idl = AnchorIdl {
             name,
             version,
             address,
@@ -767,7 +889,41 @@ impl AnchorIdl {
             errors,
             constants,
             // field_allocation_cache: OnceLock::new(),
-        })
+        };
+        idl.resolve_account_layouts();
+        for account in idl.accounts.iter_mut().flatten() {
+            account.is_state = state_account.as_deref() == Some(account.name.as_str());
+        }
+        Ok(idl)
+    }
+
+    /// 统一账户布局：内联 `type` 与引用 `types` 的账户得到相同的字段列表
+    ///
+    /// 仅有名称的账户从同名struct类型获取字段；IDL显式声明的
+    /// `discriminator` 字段被移除，由生成代码统一作为常量与首字段处理
+    fn resolve_account_layouts(&mut self) {
+        let types = self.types.as_deref().unwrap_or_default();
+        for account in self.accounts.iter_mut().flatten() {
+            account.zero_copy = types.iter().any(|type_def| {
+                type_def.name == account.name
+                    && type_def.serialization.as_deref().is_some_and(|s| s.starts_with("bytemuck"))
+            });
+            if account.fields.is_none() {
+                account.fields = types
+                    .iter()
+                    .find(|type_def| type_def.name == account.name)
+                    .and_then(|type_def| match &type_def.kind {
+                        Some(AnchorTypeKind::Struct(fields)) => Some(fields.clone()),
+                        _ => None,
+                    });
+            }
+            if let Some(fields) = account.fields.as_mut() {
+                if fields.first().is_some_and(|field| field.name == "discriminator") {
+                    log::debug!("🔄 移除账户 {} 显式声明的discriminator字段", account.name);
+                    fields.remove(0);
+                }
+            }
+        }
     }
 
     /// 创建一个空的AnchorIdl
@@ -782,6 +938,8 @@ impl AnchorIdl {
                 version: "0.1.0".to_string(),
                 spec: "anchor".to_string(),
                 description: None,
+                addresses: None,
+                dependencies: None,
             },
             instructions: None,
             accounts: None,
@@ -1003,7 +1161,7 @@ impl AnchorFieldType {
                             let size = if let serde_json::Value::Number(n) = &arr[1] {
                                 n.as_u64().unwrap_or(0) as usize
                             } else {
-                                return Err("Array size must be a number".to_string());
+                                return Err(format!("Array size must be a number or an IDL constant, got {}", arr[1]));
                             };
                             log::trace!("✅ AnchorFieldType: array({:?}, {})", inner, size);
                             return Ok(AnchorFieldType::array(Box::new(inner), size));
@@ -1031,6 +1189,13 @@ impl AnchorFieldType {
                     return Ok(AnchorFieldType::option(Box::new(inner)));
                 }
                 
+                // 检查 "coption" 类型（SPL Token等原生程序使用）
+                if let Some(coption_value) = map.get("coption") {
+                    let inner = Self::parse_value(coption_value.clone())?;
+                    log::trace!("✅ AnchorFieldType: coption({:?})", inner);
+                    return Ok(AnchorFieldType::coption(Box::new(inner)));
+                }
+                
                 // 处理Complex类型
                 if let Some(serde_json::Value::String(kind_str)) = map.get("kind") {
                     log::trace!("✅ AnchorFieldType: Complex(kind: {})", kind_str);
@@ -1138,6 +1303,8 @@ impl AnchorTypeKind {
                     field_type,
                     kind: None,
                     docs,
+                    encoding: None,
+                    substitute: None,
                 })
             }
             _ => Err("AnchorField must be an object".to_string())
@@ -1177,9 +1344,65 @@ impl AnchorTypeKind {
                     name,
                     fields,
                     docs,
+                    value: super::enum_tags::explicit_value(map),
                 })
             }
             _ => Err("AnchorEnumVariant must be an object".to_string())
         }
     }
-}
\ No newline at end of file
+}
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn parse(json: serde_json::Value) -> AnchorIdl {
+        AnchorIdl::parse_json(&json.to_string()).unwrap()
+    }
+
+    #[test]
+    fn inline_and_referenced_accounts_resolve_identically() {
+        let fields = serde_json::json!([
+            {"name": "discriminator", "type": {"array": ["u8", 8]}},
+            {"name": "authority", "type": "pubkey"},
+            {"name": "amount", "type": "u64"}
+        ]);
+        let inline = parse(serde_json::json!({
+            "address": "11111111111111111111111111111111",
+            "accounts": [{"name": "Vault", "type": {"kind": "struct", "fields": fields}}]
+        }));
+        let referenced = parse(serde_json::json!({
+            "address": "11111111111111111111111111111111",
+            "accounts": [{"name": "Vault", "discriminator": anchor_discriminator("account", "Vault")}],
+            "types": [{"name": "Vault", "type": {"kind": "struct", "fields": fields}}]
+        }));
+
+        let inline = &inline.accounts.unwrap()[0];
+        let referenced = &referenced.accounts.unwrap()[0];
+        assert_eq!(inline.discriminator, referenced.discriminator);
+        let names = |account: &AnchorAccount| -> Vec<String> {
+            account.fields.iter().flatten().map(|field| field.name.clone()).collect()
+        };
+        assert_eq!(names(inline), ["authority", "amount"]);
+        assert_eq!(names(inline), names(referenced));
+    }
+
+    #[test]
+    fn tuple_and_unit_struct_types() {
+        let idl = parse(serde_json::json!({
+            "address": "11111111111111111111111111111111",
+            "types": [
+                {"name": "WrappedU128", "type": {"kind": "struct", "fields": ["u128"]}},
+                {"name": "Marker", "type": {"kind": "struct"}}
+            ]
+        }));
+        let types = idl.types.unwrap();
+        match &types[0].kind {
+            Some(AnchorTypeKind::Struct(fields)) => {
+                assert_eq!(fields.len(), 1);
+                assert_eq!(fields[0].name, "0");
+            }
+            other => panic!("unexpected kind: {:?}", other),
+        }
+        assert!(matches!(&types[1].kind, Some(AnchorTypeKind::Struct(fields)) if fields.is_empty()));
+    }
+}
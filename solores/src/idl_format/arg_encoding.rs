@@ -0,0 +1,196 @@
+//! 指令参数编码方式
+//!
+//! 部分程序（如 memo 类）的 `bytes` / `string` 参数表示"指令数据的剩余部分"，
+//! 而不是 borsh 的 u32 长度前缀向量。可在 IDL 参数上写 `"encoding": "raw_tail"`，
+//! 或通过 `--arg-encoding Instruction.arg=raw_tail` 标注，生成的 IxData 会直接
+//! 写出原始字节并在解码时读取剩余全部数据。
+//!
+//! 标注在 IR 上进行，随后统一校验：raw_tail 只允许用于最后一个参数，
+//! 且类型必须是 `Vec<u8>` 或 `String`。
+
+use std::str::FromStr;
+
+use convert_case::{Case, Casing};
+use serde::{Deserialize, Serialize};
+
+use super::anchor_idl::AnchorFieldType;
+use super::non_anchor_idl::NonAnchorFieldType;
+use super::IdlFormatEnum;
+use crate::error::SoloresError;
+use crate::minijinja::type_mapper;
+
+/// 参数编码方式
+#[derive(Serialize, Deserialize, Debug, Clone, Copy, PartialEq, Eq)]
+#[serde(rename_all = "snake_case")]
+pub enum ArgEncoding {
+    /// borsh 默认编码（u32 长度前缀）
+    #[serde(alias = "lengthPrefixed")]
+    LengthPrefixed,
+    /// 无长度前缀，占用指令数据的剩余全部字节
+    #[serde(alias = "rawTail")]
+    RawTail,
+}
+
+impl FromStr for ArgEncoding {
+    type Err = String;
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        match s {
+            "length_prefixed" | "borsh" => Ok(ArgEncoding::LengthPrefixed),
+            "raw_tail" | "rest" => Ok(ArgEncoding::RawTail),
+            other => Err(format!("未知的参数编码 '{}'，可选值: length_prefixed, raw_tail", other)),
+        }
+    }
+}
+
+/// 单条参数编码标注：`Instruction.arg=encoding`
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct ArgEncodingHint {
+    pub instruction: String,
+    pub arg: String,
+    pub encoding: ArgEncoding,
+}
+
+impl FromStr for ArgEncodingHint {
+    type Err = String;
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        let format_error = || format!("参数编码标注 '{}' 格式应为 Instruction.arg=encoding", s);
+        let (path, encoding) = s.split_once('=').ok_or_else(format_error)?;
+        let (instruction, arg) = path
+            .split_once('.')
+            .filter(|(instruction, arg)| !instruction.is_empty() && !arg.is_empty())
+            .ok_or_else(format_error)?;
+        Ok(ArgEncodingHint {
+            instruction: instruction.trim().to_string(),
+            arg: arg.trim().to_string(),
+            encoding: encoding.trim().parse()?,
+        })
+    }
+}
+
+/// 指令参数视图：(参数名, Rust 类型, 编码)
+type ArgView<'a> = (&'a str, String, &'a mut Option<ArgEncoding>);
+
+impl IdlFormatEnum {
+    /// 应用 `--arg-encoding` 标注，并校验 IDL 与标注中的全部 raw_tail 参数
+    pub fn apply_arg_encodings(&mut self, hints: &[ArgEncodingHint]) -> Result<(), SoloresError> {
+        let mut instructions = self.instruction_args();
+
+        for hint in hints {
+            let field_path = format!("{}.{}", hint.instruction, hint.arg);
+            let instruction_name = hint.instruction.to_case(Case::Pascal);
+            let arg_name = hint.arg.to_case(Case::Snake);
+            let (_, args) = instructions
+                .iter_mut()
+                .find(|(name, _)| name.to_case(Case::Pascal) == instruction_name)
+                .ok_or_else(|| validation_error(&field_path, format!("未找到指令 '{}'", hint.instruction)))?;
+            let (_, _, encoding) = args
+                .iter_mut()
+                .find(|(name, _, _)| name.to_case(Case::Snake) == arg_name)
+                .ok_or_else(|| {
+                    validation_error(&field_path, format!("指令 '{}' 中未找到参数 '{}'", instruction_name, hint.arg))
+                })?;
+            **encoding = Some(hint.encoding);
+        }
+
+        for (instruction, args) in &instructions {
+            let last = args.len().saturating_sub(1);
+            for (index, (name, rust_type, encoding)) in args.iter().enumerate() {
+                if **encoding != Some(ArgEncoding::RawTail) {
+                    continue;
+                }
+                let field_path = format!("{}.{}", instruction, name);
+                if index != last {
+                    return Err(validation_error(&field_path, "raw_tail 参数必须是指令的最后一个参数".to_string()));
+                }
+                if !matches!(rust_type.as_str(), "std::vec::Vec<u8>" | "std::string::String") {
+                    return Err(SoloresError::ValidationError {
+                        message: "raw_tail 参数类型不受支持".to_string(),
+                        field_path: Some(field_path),
+                        expected: Some("bytes / string / Vec<u8>".to_string()),
+                        actual: Some(rust_type.clone()),
+                    });
+                }
+            }
+        }
+        Ok(())
+    }
+
+    fn instruction_args(&mut self) -> Vec<(&str, Vec<ArgView<'_>>)> {
+        match self {
+            IdlFormatEnum::Anchor(idl) => idl
+                .instructions
+                .iter_mut()
+                .flatten()
+                .map(|ix| {
+                    let args = ix
+                        .args
+                        .iter_mut()
+                        .flatten()
+                        .map(|arg| {
+                            let rust_type = type_mapper::map_field_type::<AnchorFieldType>(&arg.field_type);
+                            (arg.name.as_str(), rust_type, &mut arg.encoding)
+                        })
+                        .collect();
+                    (ix.name.as_str(), args)
+                })
+                .collect(),
+            IdlFormatEnum::NonAnchor(idl) => idl
+                .instructions
+                .iter_mut()
+                .flatten()
+                .map(|ix| {
+                    let args = ix
+                        .args
+                        .iter_mut()
+                        .flatten()
+                        .map(|arg| {
+                            let rust_type = type_mapper::map_field_type::<NonAnchorFieldType>(&arg.field_type);
+                            (arg.name.as_str(), rust_type, &mut arg.encoding)
+                        })
+                        .collect();
+                    (ix.name.as_str(), args)
+                })
+                .collect(),
+        }
+    }
+}
+
+fn validation_error(field_path: &str, message: String) -> SoloresError {
+    SoloresError::ValidationError {
+        message,
+        field_path: Some(format!("--arg-encoding {}", field_path)),
+        expected: None,
+        actual: None,
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::idl_format::parse_idl_json;
+
+    const MEMO_IDL: &str = r#"{
+        "address": "MemoSq4gqABAXKb96qnH8TysNcWxMyWCqXgDLGmfcHr",
+        "metadata": {"name": "memo", "version": "0.1.0", "spec": "0.1.0"},
+        "instructions": [{
+            "name": "write_memo",
+            "discriminator": [1, 2, 3, 4, 5, 6, 7, 8],
+            "accounts": [],
+            "args": [{"name": "tag", "type": "u8"}, {"name": "memo", "type": "bytes"}]
+        }]
+    }"#;
+
+    #[test]
+    fn raw_tail_must_be_last_bytes_arg() {
+        let mut idl = parse_idl_json(MEMO_IDL).unwrap();
+        let hint: ArgEncodingHint = "WriteMemo.memo=raw_tail".parse().unwrap();
+        assert!(idl.apply_arg_encodings(&[hint]).is_ok());
+
+        let mut idl = parse_idl_json(MEMO_IDL).unwrap();
+        let hint: ArgEncodingHint = "write_memo.tag=raw_tail".parse().unwrap();
+        assert!(idl.apply_arg_encodings(&[hint]).is_err());
+        assert!("WriteMemo=raw_tail".parse::<ArgEncodingHint>().is_err());
+    }
+}
@@ -0,0 +1,292 @@
+//! 数组长度常量求值
+//!
+//! IDL 中的数组长度可能引用 `constants` 段的常量（`["u64", {"defined": "MAX_BINS"}]`、
+//! `["u8", "MAX_BINS"]`）或写成表达式（`"MAX_BINS * 2"`）。解析前在 JSON 上把这些长度
+//! 求值为整数，支持十进制/十六进制字面量、常量引用、`+ - * / % << >> & |`、括号与
+//! `as` 类型转换；无法求值的长度报错，指出表达式及其在 IDL 中的位置。
+
+use serde_json::{Map, Value};
+
+/// 常量之间引用的最大深度，防止循环引用
+const MAX_DEPTH: usize = 16;
+
+/// 将 IDL JSON 中所有以常量或表达式给出的数组长度替换为整数
+///
+/// 有长度无法求值时返回错误，说明表达式及其 JSON Pointer 位置
+pub fn resolve_array_sizes(idl: &mut Value) -> Result<(), String> {
+    let constants: Map<String, Value> = idl
+        .get("constants")
+        .and_then(Value::as_array)
+        .map(|constants| {
+            constants
+                .iter()
+                .filter_map(|constant| {
+                    let name = constant.get("name")?.as_str()?;
+                    Some((name.to_string(), constant.get("value")?.clone()))
+                })
+                .collect()
+        })
+        .unwrap_or_default();
+
+    visit(idl, &constants, &mut String::new())
+}
+
+/// 递归求值数组长度，`path` 为当前节点的 JSON Pointer
+fn visit(value: &mut Value, constants: &Map<String, Value>, path: &mut String) -> Result<(), String> {
+    let len = path.len();
+    let result = match value {
+        Value::Object(map) => {
+            if let Some(Value::Array(array)) = map.get_mut("array") {
+                if let [_, size] = array.as_mut_slice() {
+                    if !size.is_number() {
+                        let Some(len) = size_expression(size).and_then(|expr| eval(&expr, constants, 0)) else {
+                            return Err(format!("无法求值数组长度 {}（位于 {}/array/1）", size, path));
+                        };
+                        log::debug!("🔢 数组长度 {} 求值为 {}", size, len);
+                        *size = Value::from(len);
+                    }
+                }
+            }
+            map.iter_mut().try_for_each(|(key, child)| {
+                path.truncate(len);
+                path.push('/');
+                // JSON Pointer 转义
+                path.push_str(&key.replace('~', "~0").replace('/', "~1"));
+                visit(child, constants, path)
+            })
+        }
+        Value::Array(items) => items.iter_mut().enumerate().try_for_each(|(index, child)| {
+            path.truncate(len);
+            path.push('/');
+            path.push_str(&index.to_string());
+            visit(child, constants, path)
+        }),
+        _ => Ok(()),
+    };
+    path.truncate(len);
+    result
+}
+
+/// 数组长度的表达式文本：字符串本身，或 `{"defined": "NAME"}` / `{"defined": {"name": "NAME"}}`
+fn size_expression(size: &Value) -> Option<String> {
+    match size {
+        Value::String(expr) => Some(expr.clone()),
+        Value::Object(map) => match map.get("defined")? {
+            Value::String(name) => Some(name.clone()),
+            Value::Object(defined) => defined.get("name")?.as_str().map(str::to_string),
+            _ => None,
+        },
+        _ => None,
+    }
+}
+
+/// 对表达式求值，结果须能放入 u64
+fn eval(expr: &str, constants: &Map<String, Value>, depth: usize) -> Option<u64> {
+    if depth > MAX_DEPTH {
+        return None;
+    }
+    let tokens = tokenize(expr)?;
+    let mut parser = Parser { tokens: &tokens, pos: 0, constants, depth };
+    let value = parser.bit_or()?;
+    (parser.pos == tokens.len()).then_some(())?;
+    u64::try_from(value).ok()
+}
+
+#[derive(Debug, Clone, PartialEq)]
+enum Token {
+    Number(u128),
+    Ident(String),
+    Op(&'static str),
+}
+
+fn tokenize(expr: &str) -> Option<Vec<Token>> {
+    const OPS: [&str; 11] = ["<<", ">>", "+", "-", "*", "/", "%", "&", "|", "(", ")"];
+
+    let mut tokens = Vec::new();
+    let mut rest = expr.trim();
+    while !rest.is_empty() {
+        if let Some(op) = OPS.iter().find(|op| rest.starts_with(**op)) {
+            tokens.push(Token::Op(op));
+            rest = &rest[op.len()..];
+        } else {
+            let end = rest
+                .find(|c: char| !(c.is_ascii_alphanumeric() || c == '_' || c == ':'))
+                .unwrap_or(rest.len());
+            if end == 0 {
+                return None;
+            }
+            let word = &rest[..end];
+            rest = rest[end..].trim_start();
+            if word == "as" {
+                // `as usize` 之类的类型转换不改变数值，跳过类型名
+                let type_end = rest
+                    .find(|c: char| !(c.is_ascii_alphanumeric() || c == '_'))
+                    .unwrap_or(rest.len());
+                rest = &rest[type_end..];
+            } else if word.starts_with(|c: char| c.is_ascii_digit()) {
+                tokens.push(Token::Number(parse_literal(word)?));
+            } else {
+                // `crate::MAX_BINS` 等路径取最后一段
+                let name = word.rsplit("::").next().unwrap_or(word);
+                tokens.push(Token::Ident(name.to_string()));
+            }
+        }
+        rest = rest.trim_start();
+    }
+    Some(tokens)
+}
+
+/// 整数字面量：`70`、`1_000`、`0x46`、`70u64`
+fn parse_literal(word: &str) -> Option<u128> {
+    let digits = word.replace('_', "");
+    let (digits, radix) = match digits.strip_prefix("0x").or_else(|| digits.strip_prefix("0X")) {
+        Some(hex) => (hex.to_string(), 16),
+        None => (digits, 10),
+    };
+    let suffix = ["u8", "u16", "u32", "u64", "u128", "usize", "i8", "i16", "i32", "i64", "i128", "isize"]
+        .iter()
+        .find(|suffix| radix == 10 && digits.ends_with(**suffix));
+    let digits = suffix.map_or(digits.as_str(), |suffix| &digits[..digits.len() - suffix.len()]);
+    u128::from_str_radix(digits, radix).ok()
+}
+
+struct Parser<'a> {
+    tokens: &'a [Token],
+    pos: usize,
+    constants: &'a Map<String, Value>,
+    depth: usize,
+}
+
+impl Parser<'_> {
+    fn eat(&mut self, op: &str) -> bool {
+        let matched = matches!(self.tokens.get(self.pos), Some(Token::Op(next)) if *next == op);
+        if matched {
+            self.pos += 1;
+        }
+        matched
+    }
+
+    fn bit_or(&mut self) -> Option<u128> {
+        let mut value = self.bit_and()?;
+        while self.eat("|") {
+            value |= self.bit_and()?;
+        }
+        Some(value)
+    }
+
+    fn bit_and(&mut self) -> Option<u128> {
+        let mut value = self.shift()?;
+        while self.eat("&") {
+            value &= self.shift()?;
+        }
+        Some(value)
+    }
+
+    fn shift(&mut self) -> Option<u128> {
+        let mut value = self.additive()?;
+        loop {
+            if self.eat("<<") {
+                value = value.checked_shl(u32::try_from(self.additive()?).ok()?)?;
+            } else if self.eat(">>") {
+                value = value.checked_shr(u32::try_from(self.additive()?).ok()?)?;
+            } else {
+                return Some(value);
+            }
+        }
+    }
+
+    fn additive(&mut self) -> Option<u128> {
+        let mut value = self.multiplicative()?;
+        loop {
+            if self.eat("+") {
+                value = value.checked_add(self.multiplicative()?)?;
+            } else if self.eat("-") {
+                value = value.checked_sub(self.multiplicative()?)?;
+            } else {
+                return Some(value);
+            }
+        }
+    }
+
+    fn multiplicative(&mut self) -> Option<u128> {
+        let mut value = self.primary()?;
+        loop {
+            if self.eat("*") {
+                value = value.checked_mul(self.primary()?)?;
+            } else if self.eat("/") {
+                value = value.checked_div(self.primary()?)?;
+            } else if self.eat("%") {
+                value = value.checked_rem(self.primary()?)?;
+            } else {
+                return Some(value);
+            }
+        }
+    }
+
+    fn primary(&mut self) -> Option<u128> {
+        if self.eat("(") {
+            let value = self.bit_or()?;
+            return self.eat(")").then_some(value);
+        }
+        let token = self.tokens.get(self.pos)?.clone();
+        self.pos += 1;
+        match token {
+            Token::Number(value) => Some(value),
+            Token::Ident(name) => {
+                let value = match self.constants.get(&name)? {
+                    Value::Number(n) => n.as_u64()?,
+                    Value::String(expr) => eval(expr, self.constants, self.depth + 1)?,
+                    _ => return None,
+                };
+                Some(u128::from(value))
+            }
+            Token::Op(_) => None,
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use serde_json::json;
+
+    #[test]
+    fn resolves_constant_array_sizes() {
+        let mut idl = json!({
+            "constants": [
+                { "name": "MAX_BINS", "type": "u64", "value": "70" },
+                { "name": "HALF", "type": "usize", "value": "MAX_BINS / 2" },
+            ],
+            "types": [{ "name": "BinArray", "type": { "kind": "struct", "fields": [
+                { "name": "a", "type": { "array": ["u64", { "defined": "MAX_BINS" }] } },
+                { "name": "b", "type": { "array": ["u8", { "defined": { "name": "HALF" } }] } },
+                { "name": "c", "type": { "array": [{ "array": ["u8", "(HALF + 1) * 2"] }, "MAX_BINS as usize"] } },
+                { "name": "d", "type": { "array": ["u8", "0x10 << 1"] } },
+            ] } }],
+        });
+        resolve_array_sizes(&mut idl).unwrap();
+
+        let sizes: Vec<Value> = idl["types"][0]["type"]["fields"]
+            .as_array()
+            .unwrap()
+            .iter()
+            .map(|field| field["type"]["array"][1].clone())
+            .collect();
+        assert_eq!(sizes, vec![json!(70), json!(35), json!(70), json!(32)]);
+        assert_eq!(idl["types"][0]["type"]["fields"][2]["type"]["array"][0]["array"][1], json!(72));
+    }
+
+    #[test]
+    fn unresolved_sizes_name_the_expression_and_location() {
+        let mut idl = json!({
+            "types": [{ "name": "BinArray", "type": { "kind": "struct", "fields": [
+                { "name": "a", "type": { "array": ["u8", 4] } },
+                { "name": "b", "type": { "array": ["u8", "UNKNOWN * 2"] } },
+            ] } }],
+        });
+        assert_eq!(
+            resolve_array_sizes(&mut idl).unwrap_err(),
+            "无法求值数组长度 \"UNKNOWN * 2\"（位于 /types/0/type/fields/1/type/array/1）"
+        );
+    }
+}
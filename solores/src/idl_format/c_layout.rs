@@ -0,0 +1,315 @@
+//! C 布局（layout-first）NonAnchor 账户
+//!
+//! Pyth / Switchboard 等程序直接把 `#[repr(C)]` 结构体写入账户，不经 Borsh 序列化。
+//! IDL 中声明 `"layout": "c"` 的账户 / 类型按 C 规则排布：字段按自然对齐依次放置，
+//! 字段的 `offset` 与账户 / 类型的 `size` 可显式给出偏移和总长度（中间与尾部的空隙
+//! 成为填充字段）。普通 IDL 可通过 `--layout-file` 补充同样的声明：
+//!
+//! ```json
+//! {"Price": {"size": 3312, "offsets": {"agg": 208, "comp": 240}}}
+//! ```
+//!
+//! C 布局结构体只能由定长数值、Pubkey、数组和其他 C 布局结构体组成；bool 与枚举
+//! 不是任意位模式都合法，需声明为整数。生成代码为带显式填充字段的 `bytemuck::Pod`
+//! 结构体，不依赖 borsh 读取账户。
+
+use std::collections::BTreeMap;
+use std::path::Path;
+
+use convert_case::{Case, Casing};
+use serde::Deserialize;
+
+use super::non_anchor_idl::{NonAnchorField, NonAnchorFieldType, NonAnchorIdl, NonAnchorTypeKind};
+use super::IdlFormatEnum;
+use crate::error::SoloresError;
+
+/// C 布局展开的最大嵌套深度，防止自引用类型无限递归
+const MAX_DEPTH: usize = 16;
+
+/// 账户 / 类型的 C 布局声明
+#[derive(Debug, Clone, Default, PartialEq, Eq, Deserialize)]
+#[serde(default)]
+pub struct CLayout {
+    /// 总长度（含尾部填充），缺省为按对齐取整后的自然长度
+    pub size: Option<usize>,
+    /// 显式字段偏移（snake_case 字段名），缺省的字段紧随前一字段按自然对齐放置
+    pub offsets: BTreeMap<String, usize>,
+}
+
+impl CLayout {
+    /// 读取 IDL 账户 / 类型对象上的 `"layout": "c"` 声明及 `size` 与字段 `offset`
+    pub fn from_idl_item(item: &serde_json::Value) -> Option<Self> {
+        let layout = item.get("layout")?.as_str()?;
+        if !layout.eq_ignore_ascii_case("c") {
+            return None;
+        }
+        let fields = item
+            .get("fields")
+            .or_else(|| item.get("type").and_then(|type_def| type_def.get("fields")))
+            .and_then(serde_json::Value::as_array);
+        let offsets = fields
+            .into_iter()
+            .flatten()
+            .filter_map(|field| {
+                let name = field.get("name")?.as_str()?;
+                let offset = field.get("offset")?.as_u64()?;
+                Some((name.to_case(Case::Snake), offset as usize))
+            })
+            .collect();
+        Some(CLayout {
+            size: item.get("size").and_then(serde_json::Value::as_u64).map(|size| size as usize),
+            offsets,
+        })
+    }
+
+    /// 合并 `--layout-file` 中的声明，后者优先
+    fn merge(&mut self, other: CLayout) {
+        if other.size.is_some() {
+            self.size = other.size;
+        }
+        self.offsets.extend(other.offsets.into_iter().map(|(name, offset)| (name.to_case(Case::Snake), offset)));
+    }
+}
+
+/// 解析后的 C 结构体布局
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct CStructLayout {
+    pub size: usize,
+    pub align: usize,
+    pub fields: Vec<CFieldLayout>,
+}
+
+/// C 结构体字段的偏移与长度
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct CFieldLayout {
+    pub name: String,
+    pub offset: usize,
+    pub size: usize,
+}
+
+/// 按 C 规则排布结构体字段，校验显式偏移（对齐、递增不重叠）与总长度
+pub fn c_struct_layout(fields: &[NonAnchorField], layout: &CLayout, idl: &NonAnchorIdl) -> Result<CStructLayout, String> {
+    struct_layout(fields, layout, idl, 0)
+}
+
+fn struct_layout(fields: &[NonAnchorField], layout: &CLayout, idl: &NonAnchorIdl, depth: usize) -> Result<CStructLayout, String> {
+    if let Some(unknown) = layout.offsets.keys().find(|name| !fields.iter().any(|f| f.name.to_case(Case::Snake) == **name)) {
+        return Err(format!("字段 {} 不存在", unknown));
+    }
+
+    let mut end = 0;
+    let mut align = 1;
+    let mut resolved = Vec::with_capacity(fields.len());
+    for field in fields {
+        let name = field.name.to_case(Case::Snake);
+        let (size, field_align) =
+            c_type_layout(&field.field_type, idl, depth + 1).map_err(|e| format!("字段 {}: {}", name, e))?;
+        let offset = match layout.offsets.get(&name) {
+            Some(&offset) if offset < end => {
+                return Err(format!("字段 {} 的偏移 {} 与前一字段重叠（应不小于 {}）", name, offset, end))
+            }
+            Some(&offset) if !offset.is_multiple_of(field_align) => {
+                return Err(format!("字段 {} 的偏移 {} 未按 {} 字节对齐", name, offset, field_align))
+            }
+            Some(&offset) => offset,
+            None => end.next_multiple_of(field_align),
+        };
+        align = align.max(field_align);
+        end = offset + size;
+        resolved.push(CFieldLayout { name, offset, size });
+    }
+
+    let size = match layout.size {
+        Some(size) if size < end => return Err(format!("总长度 {} 小于字段结束位置 {}", size, end)),
+        Some(size) if !size.is_multiple_of(align) => {
+            return Err(format!("总长度 {} 不是结构体对齐 {} 的倍数", size, align))
+        }
+        Some(size) => size,
+        None => end.next_multiple_of(align),
+    };
+    Ok(CStructLayout { size, align, fields: resolved })
+}
+
+/// 字段类型的 C 长度与对齐
+fn c_type_layout(field_type: &NonAnchorFieldType, idl: &NonAnchorIdl, depth: usize) -> Result<(usize, usize), String> {
+    if depth > MAX_DEPTH {
+        return Err("类型嵌套过深".to_string());
+    }
+    match field_type {
+        NonAnchorFieldType::Basic(name) => match name.as_str() {
+            "u8" | "i8" => Ok((1, 1)),
+            "u16" | "i16" => Ok((2, 2)),
+            "u32" | "i32" | "f32" => Ok((4, 4)),
+            "u64" | "i64" | "f64" => Ok((8, 8)),
+            "u128" | "i128" => Ok((16, 16)),
+            "pubkey" | "Pubkey" | "publicKey" => Ok((32, 1)),
+            "bool" => Err("bool 不是任意字节值都合法，请声明为 u8".to_string()),
+            other => Err(format!("不支持类型 {}", other)),
+        },
+        NonAnchorFieldType::Array { array: (inner, len) } => {
+            let (size, align) = c_type_layout(inner, idl, depth + 1)?;
+            Ok((size * len, align))
+        }
+        NonAnchorFieldType::Defined { defined } => {
+            let type_def = idl
+                .types
+                .iter()
+                .flatten()
+                .find(|t| t.name == *defined)
+                .ok_or_else(|| format!("未定义的类型 {}", defined))?;
+            match (&type_def.type_def, &type_def.c_layout) {
+                (NonAnchorTypeKind::Struct { fields }, Some(layout)) => {
+                    struct_layout(fields, layout, idl, depth + 1).map(|layout| (layout.size, layout.align))
+                }
+                (NonAnchorTypeKind::Alias { value }, _) => c_type_layout(value, idl, depth + 1),
+                _ => Err(format!("类型 {} 未声明 C 布局（\"layout\": \"c\"）", defined)),
+            }
+        }
+        _ => Err("只支持定长数值、Pubkey、数组与 C 布局结构体".to_string()),
+    }
+}
+
+impl NonAnchorIdl {
+    /// 是否存在 C 布局账户或类型（生成代码依赖 bytemuck）
+    pub fn has_c_layout(&self) -> bool {
+        self.accounts.iter().flatten().any(|a| a.c_layout.is_some())
+            || self.types.iter().flatten().any(|t| t.c_layout.is_some())
+    }
+
+    /// C 布局类型的总长度；非 C 布局类型返回 None
+    pub fn c_type_size(&self, type_name: &str) -> Option<usize> {
+        let type_def = self.types.iter().flatten().find(|t| t.name == type_name)?;
+        match &type_def.type_def {
+            NonAnchorTypeKind::Struct { fields } => {
+                c_struct_layout(fields, type_def.c_layout.as_ref()?, self).ok().map(|layout| layout.size)
+            }
+            _ => None,
+        }
+    }
+}
+
+impl IdlFormatEnum {
+    /// 合并 `--layout-file` 声明并校验所有 C 布局账户 / 类型
+    pub fn apply_c_layouts(&mut self, layout_file: Option<&Path>) -> Result<(), SoloresError> {
+        let idl = match self {
+            IdlFormatEnum::NonAnchor(idl) => idl,
+            IdlFormatEnum::Anchor(_) => {
+                return match layout_file {
+                    Some(path) => Err(SoloresError::ValidationError {
+                        message: "C 布局只支持 NonAnchor IDL".to_string(),
+                        field_path: Some(format!("--layout-file {}", path.display())),
+                        expected: Some("NonAnchor IDL（可用 --force-format non-anchor 指定）".to_string()),
+                        actual: Some("Anchor IDL".to_string()),
+                    }),
+                    None => Ok(()),
+                };
+            }
+        };
+
+        if let Some(path) = layout_file {
+            for (name, layout) in load_layout_file(path)? {
+                let key = name.trim().to_case(Case::Pascal);
+                let target = match idl.accounts.iter_mut().flatten().find(|a| a.name.to_case(Case::Pascal) == key) {
+                    Some(account) => &mut account.c_layout,
+                    None => match idl.types.iter_mut().flatten().find(|t| t.name.to_case(Case::Pascal) == key) {
+                        Some(type_def) => &mut type_def.c_layout,
+                        None => {
+                            return Err(SoloresError::ValidationError {
+                                message: format!("未找到账户或类型 '{}'", name),
+                                field_path: Some(format!("--layout-file {}", path.display())),
+                                expected: None,
+                                actual: Some(key),
+                            })
+                        }
+                    },
+                };
+                target.get_or_insert_with(CLayout::default).merge(layout);
+            }
+        }
+
+        let invalid = |(kind, section): (&str, &str), name: &str, message: String| SoloresError::ValidationError {
+            message: format!("C 布局{} {} 无效: {}", kind, name, message),
+            field_path: Some(format!("{}.{}", section, name)),
+            expected: None,
+            actual: None,
+        };
+        for account in idl.accounts.iter().flatten() {
+            if let Some(layout) = &account.c_layout {
+                let fields = account.fields.as_deref().unwrap_or_default();
+                c_struct_layout(fields, layout, idl).map_err(|e| invalid(("账户", "accounts"), &account.name, e))?;
+            }
+        }
+        for type_def in idl.types.iter().flatten() {
+            match (&type_def.type_def, &type_def.c_layout) {
+                (NonAnchorTypeKind::Struct { fields }, Some(layout)) => {
+                    c_struct_layout(fields, layout, idl).map_err(|e| invalid(("类型", "types"), &type_def.name, e))?;
+                }
+                (_, Some(_)) => return Err(invalid(("类型", "types"), &type_def.name, "只有结构体可以声明 C 布局".to_string())),
+                (_, None) => {}
+            }
+        }
+        Ok(())
+    }
+}
+
+/// 读取 `--layout-file`：`{"名称": {"size": N, "offsets": {"字段": 偏移}}}`
+fn load_layout_file(path: &Path) -> Result<BTreeMap<String, CLayout>, SoloresError> {
+    let content = std::fs::read_to_string(path)
+        .map_err(|e| SoloresError::file_operation_error("读取C布局文件", path.display().to_string(), e))?;
+    serde_json::from_str(&content).map_err(|e| SoloresError::ValidationError {
+        message: format!("C 布局文件格式错误: {}", e),
+        field_path: Some(path.display().to_string()),
+        expected: Some("{\"名称\": {\"size\": N, \"offsets\": {\"字段\": 偏移}}}".to_string()),
+        actual: None,
+    })
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn c_layout_inserts_padding_and_validates_offsets() {
+        let idl = NonAnchorIdl::parse_json(
+            &serde_json::json!({
+                "address": "11111111111111111111111111111111",
+                "metadata": {"name": "oracle", "version": "0.1.0", "spec": "0.1.0"},
+                "accounts": [{
+                    "name": "Price",
+                    "layout": "c",
+                    "size": 64,
+                    "fields": [
+                        {"name": "magic", "type": "u32"},
+                        {"name": "expo", "type": "i32"},
+                        {"name": "status", "type": "u8"},
+                        {"name": "agg", "type": {"defined": "PriceInfo"}, "offset": 16},
+                        {"name": "product", "type": "pubkey"}
+                    ]
+                }],
+                "types": [{
+                    "name": "PriceInfo",
+                    "layout": "c",
+                    "type": {"kind": "struct", "fields": [
+                        {"name": "price", "type": "i64"},
+                        {"name": "conf", "type": "u64"}
+                    ]}
+                }]
+            })
+            .to_string(),
+        )
+        .unwrap();
+        let account = &idl.accounts.as_ref().unwrap()[0];
+        let layout = c_struct_layout(account.fields.as_ref().unwrap(), account.c_layout.as_ref().unwrap(), &idl).unwrap();
+        let offsets: Vec<usize> = layout.fields.iter().map(|f| f.offset).collect();
+        assert_eq!(offsets, [0, 4, 8, 16, 32]);
+        assert_eq!((layout.size, layout.align), (64, 8));
+        assert_eq!(idl.c_type_size("PriceInfo"), Some(16));
+
+        let misaligned = CLayout { size: None, offsets: BTreeMap::from([("agg".to_string(), 12)]) };
+        assert!(c_struct_layout(account.fields.as_ref().unwrap(), &misaligned, &idl).unwrap_err().contains("对齐"));
+        let overlapping = CLayout { size: None, offsets: BTreeMap::from([("expo".to_string(), 2)]) };
+        assert!(c_struct_layout(account.fields.as_ref().unwrap(), &overlapping, &idl).unwrap_err().contains("重叠"));
+        let too_small = CLayout { size: Some(56), offsets: BTreeMap::new() };
+        assert!(c_struct_layout(account.fields.as_ref().unwrap(), &too_small, &idl).is_err());
+    }
+}
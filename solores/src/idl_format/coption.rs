@@ -0,0 +1,268 @@
+//! COption 字段位置校验
+//!
+//! SPL 风格的 `COption<T>`（4 字节标签 + 始终占位的载荷）生成为 `Option<T>`，Borsh 经由
+//! `crate::coption` 的字段级编解码函数读写。字段级属性只作用于账户、类型、事件结构体的字段
+//! 与指令参数本身；嵌套在 Vec / Option / 数组 / HashMap 中、位于枚举变体字段或类型别名中的
+//! COption 只能退化为 1 字节标签的 Borsh Option，解码结果错误，因此在生成前报错。
+
+use super::anchor_idl::{AnchorField, AnchorFieldType, AnchorTypeKind};
+use super::non_anchor_idl::{NonAnchorField, NonAnchorFieldType, NonAnchorTypeKind};
+use super::IdlFormatEnum;
+use crate::error::SoloresError;
+
+impl IdlFormatEnum {
+    /// 拒绝字段级编解码覆盖不到的 COption 位置
+    pub fn validate_coption_positions(&self) -> Result<(), SoloresError> {
+        match self {
+            IdlFormatEnum::Anchor(idl) => {
+                let top_level = idl
+                    .accounts
+                    .iter()
+                    .flatten()
+                    .flat_map(|account| owned_fields(&account.name, account.fields.as_deref()))
+                    .chain(idl.events.iter().flatten().flat_map(|event| owned_fields(&event.name, event.fields.as_deref())))
+                    .chain(idl.instructions.iter().flatten().flat_map(|ix| owned_fields(&ix.name, ix.args.as_deref())));
+                for (path, field_type) in top_level {
+                    check_anchor_field(&path, field_type, true)?;
+                }
+                for type_def in idl.types.iter().flatten() {
+                    match &type_def.kind {
+                        Some(AnchorTypeKind::Struct(fields)) => {
+                            for (path, field_type) in owned_fields(&type_def.name, Some(fields)) {
+                                check_anchor_field(&path, field_type, true)?;
+                            }
+                        }
+                        Some(AnchorTypeKind::Enum(variants)) => {
+                            for variant in variants {
+                                let owner = format!("{}::{}", type_def.name, variant.name);
+                                for (path, field_type) in owned_fields(&owner, variant.fields.as_deref()) {
+                                    check_anchor_field(&path, field_type, false)?;
+                                }
+                            }
+                        }
+                        Some(AnchorTypeKind::Alias(field_type)) => check_anchor_field(&type_def.name, field_type, false)?,
+                        None => {}
+                    }
+                }
+            }
+            IdlFormatEnum::NonAnchor(idl) => {
+                let top_level = idl
+                    .accounts
+                    .iter()
+                    .flatten()
+                    .flat_map(|account| non_anchor_owned_fields(&account.name, account.fields.as_deref()))
+                    .chain(idl.events.iter().flatten().flat_map(|event| non_anchor_owned_fields(&event.name, event.fields.as_deref())))
+                    .chain(idl.instructions().iter().flat_map(|ix| non_anchor_owned_fields(&ix.name, ix.args.as_deref())));
+                for (path, field_type) in top_level {
+                    check_non_anchor_field(&path, field_type, true)?;
+                }
+                for type_def in idl.types.iter().flatten() {
+                    match &type_def.type_def {
+                        NonAnchorTypeKind::Struct { fields } => {
+                            for (path, field_type) in non_anchor_owned_fields(&type_def.name, Some(fields)) {
+                                check_non_anchor_field(&path, field_type, true)?;
+                            }
+                        }
+                        NonAnchorTypeKind::Enum { variants } => {
+                            for variant in variants {
+                                let owner = format!("{}::{}", type_def.name, variant.name);
+                                for (path, field_type) in non_anchor_owned_fields(&owner, variant.fields.as_deref()) {
+                                    check_non_anchor_field(&path, field_type, false)?;
+                                }
+                            }
+                        }
+                        NonAnchorTypeKind::Alias { value } => check_non_anchor_field(&type_def.name, value, false)?,
+                    }
+                }
+            }
+        }
+        Ok(())
+    }
+}
+
+pub(super) fn owned_fields<'a>(owner: &str, fields: Option<&'a [AnchorField]>) -> Vec<(String, &'a AnchorFieldType)> {
+    fields
+        .unwrap_or_default()
+        .iter()
+        .map(|field| (format!("{}.{}", owner, field.name), &field.field_type))
+        .collect()
+}
+
+pub(super) fn non_anchor_owned_fields<'a>(owner: &str, fields: Option<&'a [NonAnchorField]>) -> Vec<(String, &'a NonAnchorFieldType)> {
+    fields
+        .unwrap_or_default()
+        .iter()
+        .map(|field| (format!("{}.{}", owner, field.name), &field.field_type))
+        .collect()
+}
+
+/// `top_level` 为 true 时允许字段类型本身是 COption，其载荷中仍不能再出现
+fn check_anchor_field(path: &str, field_type: &AnchorFieldType, top_level: bool) -> Result<(), SoloresError> {
+    let nested = match field_type {
+        AnchorFieldType::coption(inner) if top_level => anchor_contains_coption(inner),
+        other => anchor_contains_coption(other),
+    };
+    if nested {
+        return Err(position_error(path, top_level));
+    }
+    Ok(())
+}
+
+fn check_non_anchor_field(path: &str, field_type: &NonAnchorFieldType, top_level: bool) -> Result<(), SoloresError> {
+    let nested = match field_type {
+        NonAnchorFieldType::COption { coption } if top_level => non_anchor_contains_coption(coption),
+        other => non_anchor_contains_coption(other),
+    };
+    if nested {
+        return Err(position_error(path, top_level));
+    }
+    Ok(())
+}
+
+fn anchor_contains_coption(field_type: &AnchorFieldType) -> bool {
+    match field_type {
+        AnchorFieldType::coption(_) => true,
+        AnchorFieldType::option(inner) | AnchorFieldType::vec(inner) | AnchorFieldType::array(inner, _) => {
+            anchor_contains_coption(inner)
+        }
+        AnchorFieldType::defined(name) => name.contains("COption<"),
+        AnchorFieldType::Complex { kind, params } => {
+            kind == "coption" || params.iter().flatten().any(json_contains_coption)
+        }
+        AnchorFieldType::Basic(_) | AnchorFieldType::PrimitiveOrPubkey(_) => false,
+    }
+}
+
+fn non_anchor_contains_coption(field_type: &NonAnchorFieldType) -> bool {
+    match field_type {
+        NonAnchorFieldType::COption { .. } => true,
+        NonAnchorFieldType::Option { option: inner }
+        | NonAnchorFieldType::Vec { vec: inner }
+        | NonAnchorFieldType::Array { array: (inner, _) } => non_anchor_contains_coption(inner),
+        NonAnchorFieldType::HashMap { key, value } => non_anchor_contains_coption(key) || non_anchor_contains_coption(value),
+        NonAnchorFieldType::Defined { defined } => defined.contains("COption<"),
+        NonAnchorFieldType::Complex { kind, params } => {
+            kind == "coption" || params.iter().flatten().any(json_contains_coption)
+        }
+        NonAnchorFieldType::Basic(_) => false,
+    }
+}
+
+/// 泛型参数等原样保存的 JSON 类型中是否出现 COption
+fn json_contains_coption(value: &serde_json::Value) -> bool {
+    match value {
+        serde_json::Value::Object(map) => map.contains_key("coption") || map.values().any(json_contains_coption),
+        serde_json::Value::Array(items) => items.iter().any(json_contains_coption),
+        serde_json::Value::String(name) => name.contains("COption<"),
+        _ => false,
+    }
+}
+
+fn position_error(path: &str, top_level: bool) -> SoloresError {
+    let position = if top_level { "嵌套在其他类型中" } else { "位于枚举变体字段或类型别名中" };
+    SoloresError::ValidationError {
+        message: format!("{} 的 COption {}，无法按 4 字节标签编解码", path, position),
+        field_path: Some(path.to_string()),
+        expected: Some("COption 只作为账户、类型、事件字段或指令参数本身的类型".to_string()),
+        actual: None,
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use crate::idl_format::parse_idl_json;
+    use crate::test_support::run_decode_test;
+
+    fn shank_idl(extra: &str) -> String {
+        format!(
+            r#"{{"name": "vault", "version": "0.1.0", "metadata": {{"name": "vault", "version": "0.1.0", "spec": "0.1.0", "origin": "shank", "address": "Vau1t11111111111111111111111111111111111111"}}, {}}}"#,
+            extra
+        )
+    }
+
+    #[test]
+    fn coption_is_only_accepted_as_a_field_type() {
+        let direct = shank_idl(
+            r#""instructions": [], "accounts": [{"name": "Mint", "type": {"kind": "struct", "fields": [{"name": "authority", "type": {"coption": "publicKey"}}]}}]"#,
+        );
+        assert!(parse_idl_json(&direct).unwrap().validate_coption_positions().is_ok());
+
+        for (extra, path) in [
+            (
+                r#""instructions": [], "accounts": [{"name": "Mint", "type": {"kind": "struct", "fields": [{"name": "owners", "type": {"vec": {"coption": "publicKey"}}}]}}]"#,
+                "Mint.owners",
+            ),
+            (
+                r#""instructions": [], "types": [{"name": "Authority", "type": {"kind": "enum", "variants": [{"name": "Set", "fields": [{"name": "key", "type": {"coption": "publicKey"}}]}]}}]"#,
+                "Authority::Set.key",
+            ),
+            (
+                r#""instructions": [], "types": [{"name": "Slot", "type": {"kind": "struct", "fields": [{"name": "next", "type": {"option": {"coption": "u64"}}}]}}]"#,
+                "Slot.next",
+            ),
+        ] {
+            let idl = parse_idl_json(&shank_idl(extra)).unwrap();
+            let error = idl.validate_coption_positions().unwrap_err();
+            assert!(
+                matches!(&error, crate::error::SoloresError::ValidationError { field_path: Some(found), .. } if found == path),
+                "{:?}",
+                error
+            );
+        }
+    }
+
+    #[test]
+    fn coption_fields_round_trip_with_four_byte_tags() {
+        let idl = shank_idl(
+            r#""instructions": [{"name": "setAuthority", "discriminator": [3], "accounts": [{"name": "mint", "isMut": true, "isSigner": false}],
+                "args": [{"name": "newAuthority", "type": {"coption": "publicKey"}}, {"name": "amount", "type": "u64"}]}],
+              "accounts": [{"name": "Mint", "type": {"kind": "struct", "fields": [{"name": "authority", "type": {"coption": "publicKey"}}, {"name": "supply", "type": "u64"}]}}],
+              "events": [{"name": "AuthoritySet", "fields": [{"name": "authority", "type": {"coption": "publicKey"}, "index": false}]}]"#,
+        );
+        let test_source = r#"
+use sol_vault_interface::{accounts::Mint, events::AuthoritySet, instructions::SetAuthorityIxData};
+use solana_pubkey::Pubkey;
+
+fn coption_bytes(key: Option<Pubkey>) -> Vec<u8> {
+    let mut bytes = vec![u8::from(key.is_some()), 0, 0, 0];
+    bytes.extend_from_slice(key.unwrap_or_default().as_ref());
+    bytes
+}
+
+#[test]
+fn instruction_args_use_four_byte_tags() {
+    for key in [Some(Pubkey::new_unique()), None] {
+        let mut bytes = vec![3];
+        bytes.extend(coption_bytes(key));
+        bytes.extend(7u64.to_le_bytes());
+        let data = SetAuthorityIxData::from_bytes(&bytes).unwrap();
+        assert_eq!(data, SetAuthorityIxData::new(key, 7));
+        assert_eq!(data.try_to_vec().unwrap(), bytes);
+    }
+}
+
+#[test]
+fn event_and_account_fields_use_four_byte_tags() {
+    let key = Some(Pubkey::new_unique());
+    let bytes = coption_bytes(key);
+    let event = AuthoritySet::from_bytes(&bytes).unwrap();
+    assert_eq!(event.authority, key);
+    assert_eq!(event.try_to_vec().unwrap(), bytes);
+
+    let mut bytes = coption_bytes(None);
+    bytes.extend(9u64.to_le_bytes());
+    let mint = Mint::from_bytes(&bytes).unwrap();
+    assert_eq!((mint.authority, mint.supply), (None, 9));
+    assert_eq!(mint.try_to_vec().unwrap(), bytes);
+}
+
+#[test]
+fn invalid_tags_are_rejected() {
+    let mut bytes = vec![3, 2, 0, 0, 0];
+    bytes.extend([0; 40]);
+    assert!(SetAuthorityIxData::from_bytes(&bytes).is_err());
+}
+"#;
+        run_decode_test("coption", &idl, &[], &[], test_source);
+    }
+}
@@ -0,0 +1,30 @@
+//! IDL 依赖声明
+//!
+//! `metadata.dependencies`（`[{"name": "shared", "version": "0.3"}]`）声明 IDL
+//! 引用了其他程序接口 crate 中定义的类型。依赖本身不描述类型来源，由 `--extern`
+//! 指定接口 crate 后才会生成导入；`--extern name` 省略来源时使用此处声明的版本。
+
+use serde::{Deserialize, Serialize};
+
+use super::IdlFormatEnum;
+
+/// `metadata.dependencies` 中的一项
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+pub struct IdlDependency {
+    /// 依赖的 crate 名称
+    pub name: String,
+    /// 版本要求
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub version: Option<String>,
+}
+
+impl IdlFormatEnum {
+    /// IDL 元数据中声明的依赖
+    pub fn dependencies(&self) -> &[IdlDependency] {
+        let dependencies = match self {
+            IdlFormatEnum::Anchor(idl) => idl.metadata.dependencies.as_deref(),
+            IdlFormatEnum::NonAnchor(idl) => idl.metadata.as_ref().and_then(|m| m.dependencies.as_deref()),
+        };
+        dependencies.unwrap_or_default()
+    }
+}
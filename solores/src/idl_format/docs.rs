@@ -0,0 +1,177 @@
+//! 文档注释规范化
+//!
+//! IDL 中的 `docs` 可能是字符串数组、单个字符串或嵌套数组，内容里还可能带有
+//! 代码块、`/* */`、方括号与尖括号，直接写入 `///` 会产生失效的 intra-doc 链接、
+//! HTML 标签警告或被当作 doctest 编译。解析前把 `docs` 统一为字符串数组，渲染时
+//! 由 [`doc_comment`] 统一转义、补全代码块并按宽度折行。
+
+use serde_json::Value;
+
+/// 单行文档的最大宽度（不含 `/// ` 前缀）
+pub const DOC_LINE_WIDTH: usize = 100;
+
+/// 将 IDL JSON 中所有 `docs` 统一为字符串数组
+pub fn normalize_docs(idl: &mut Value) {
+    match idl {
+        Value::Object(map) => {
+            if let Some(docs) = map.get_mut("docs") {
+                let mut lines = Vec::new();
+                collect_lines(docs, &mut lines);
+                *docs = Value::from(lines);
+            }
+            map.iter_mut()
+                .filter(|(key, _)| *key != "docs")
+                .for_each(|(_, child)| normalize_docs(child));
+        }
+        Value::Array(items) => items.iter_mut().for_each(normalize_docs),
+        _ => {}
+    }
+}
+
+fn collect_lines(docs: &Value, lines: &mut Vec<String>) {
+    match docs {
+        Value::String(text) => lines.extend(text.lines().map(str::to_string)),
+        Value::Array(items) => items.iter().for_each(|item| collect_lines(item, lines)),
+        _ => {}
+    }
+}
+
+/// 合并文档行，供模板上下文使用
+pub fn join_docs(docs: Option<&Vec<String>>) -> String {
+    docs.map(|docs| docs.join("\n")).unwrap_or_default()
+}
+
+/// 渲染为 `///` 文档注释
+///
+/// 代码块外：去除缩进、转义 `/*`、`*/`、非链接的方括号与非 URL 的尖括号，并折行；
+/// 未标注语言或标注为 rust 的代码块改为 `text`，避免作为 doctest 编译；未闭合的代码块自动闭合
+pub fn doc_comment(text: &str) -> String {
+    let mut lines = Vec::new();
+    let mut in_fence = false;
+    for line in text.lines() {
+        let trimmed = line.trim();
+        if let Some(lang) = trimmed.strip_prefix("```") {
+            if !in_fence && matches!(lang.trim(), "" | "rust" | "rs") {
+                lines.push("```text".to_string());
+            } else {
+                lines.push(trimmed.to_string());
+            }
+            in_fence = !in_fence;
+        } else if in_fence {
+            lines.push(line.trim_end().to_string());
+        } else {
+            lines.extend(wrap(&escape_markdown(trimmed), DOC_LINE_WIDTH));
+        }
+    }
+    if in_fence {
+        lines.push("```".to_string());
+    }
+
+    // 去掉首尾空行
+    let start = lines.iter().position(|line| !line.is_empty()).unwrap_or(lines.len());
+    let end = lines.iter().rposition(|line| !line.is_empty()).map_or(start, |end| end + 1);
+    lines[start..end]
+        .iter()
+        .map(|line| if line.is_empty() { "///".to_string() } else { format!("/// {}", line) })
+        .collect::<Vec<_>>()
+        .join("\n")
+}
+
+/// 转义行内代码以外会破坏文档渲染的字符
+fn escape_markdown(line: &str) -> String {
+    let chars: Vec<char> = line.chars().collect();
+    let mut out = String::with_capacity(line.len());
+    let mut in_code = false;
+    for (i, &c) in chars.iter().enumerate() {
+        let next = chars.get(i + 1).copied();
+        if c == '`' {
+            in_code = !in_code;
+        }
+        if in_code || c == '`' {
+            out.push(c);
+            continue;
+        }
+        match c {
+            '/' if next == Some('*') => out.push_str("/\\"),
+            '*' if next == Some('/') => out.push_str("*\\"),
+            '[' if !is_markdown_link(&chars[i..]) => out.push_str("\\["),
+            ']' if !closes_markdown_link(&chars[..=i], next) => out.push_str("\\]"),
+            '<' if !chars[i + 1..].starts_with(&['h', 't', 't', 'p']) => out.push_str("\\<"),
+            _ => out.push(c),
+        }
+    }
+    out
+}
+
+/// `[text](url)` 形式的链接从此处开始
+fn is_markdown_link(rest: &[char]) -> bool {
+    rest.iter()
+        .position(|&c| c == ']')
+        .is_some_and(|close| rest.get(close + 1) == Some(&'('))
+}
+
+/// 此处的 `]` 结束一个 `[text](url)` 链接
+fn closes_markdown_link(before: &[char], next: Option<char>) -> bool {
+    next == Some('(') && before.contains(&'[')
+}
+
+/// 按空白折行，单个超长单词保持完整
+fn wrap(line: &str, width: usize) -> Vec<String> {
+    if line.chars().count() <= width {
+        return vec![line.to_string()];
+    }
+    let mut lines = Vec::new();
+    let mut current = String::new();
+    for word in line.split_whitespace() {
+        if !current.is_empty() && current.chars().count() + 1 + word.chars().count() > width {
+            lines.push(std::mem::take(&mut current));
+        }
+        if !current.is_empty() {
+            current.push(' ');
+        }
+        current.push_str(word);
+    }
+    if !current.is_empty() {
+        lines.push(current);
+    }
+    lines
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use serde_json::json;
+
+    #[test]
+    fn normalizes_doc_shapes() {
+        let mut idl = json!({
+            "instructions": [
+                { "name": "a", "docs": "single line\nsecond line" },
+                { "name": "b", "docs": ["x", ["nested", 1], null] },
+                { "name": "c", "args": [{ "name": "amount", "docs": [] }] },
+            ],
+        });
+        normalize_docs(&mut idl);
+
+        assert_eq!(idl["instructions"][0]["docs"], json!(["single line", "second line"]));
+        assert_eq!(idl["instructions"][1]["docs"], json!(["x", "nested"]));
+        assert_eq!(idl["instructions"][2]["args"][0]["docs"], json!([]));
+    }
+
+    #[test]
+    fn escapes_and_fences_doc_comments() {
+        let rendered = doc_comment(
+            "\n  Swap /* legacy */ tokens [see pool] or [docs](https://x.y) for Vec<u8>, `a[0] <b>`\n```\nlet x = [1];\n",
+        );
+        assert_eq!(
+            rendered,
+            "/// Swap /\\* legacy *\\/ tokens \\[see pool\\] or [docs](https://x.y) for Vec\\<u8>, `a[0] <b>`\n\
+             /// ```text\n/// let x = [1];\n/// ```"
+        );
+
+        let long = "word ".repeat(30);
+        let rendered = doc_comment(&long);
+        assert_eq!(rendered.lines().count(), 2);
+        assert!(rendered.lines().all(|line| line.len() <= DOC_LINE_WIDTH + 4));
+    }
+}
@@ -0,0 +1,113 @@
+//! 枚举判别值布局
+//!
+//! borsh 默认按变体下标写入 u8 标签。IDL 中带显式判别值（`value` / `discriminant`）
+//! 的稀疏枚举在值域不超过 u8 时生成 `#[borsh(use_discriminant = true)]`；
+//! 变体数超过 256 或判别值超出 u8 时改用 u16 / u32 / u64 标签，生成手写的
+//! BorshSerialize / BorshDeserialize 实现。
+
+/// 枚举标签的生成方式
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum EnumTagMode {
+    /// borsh 默认：u8 变体下标
+    Index,
+    /// u8 显式判别值，`#[borsh(use_discriminant = true)]`
+    Discriminant,
+    /// 宽标签，手写序列化实现
+    Wide,
+}
+
+impl EnumTagMode {
+    pub fn as_str(self) -> &'static str {
+        match self {
+            EnumTagMode::Index => "index",
+            EnumTagMode::Discriminant => "discriminant",
+            EnumTagMode::Wide => "wide",
+        }
+    }
+}
+
+/// 枚举的标签布局
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct EnumTagLayout {
+    pub mode: EnumTagMode,
+    /// 标签字节数
+    pub width: usize,
+    /// 每个变体的判别值，未显式给出时为上一变体 + 1（首个为 0）
+    pub values: Vec<u64>,
+    /// 重复出现的判别值
+    pub duplicates: Vec<u64>,
+}
+
+impl EnumTagLayout {
+    /// 由各变体的显式判别值计算布局
+    pub fn from_values(explicit: impl IntoIterator<Item = Option<u64>>) -> Self {
+        let mut values = Vec::new();
+        let mut next = 0u64;
+        for value in explicit {
+            let value = value.unwrap_or(next);
+            values.push(value);
+            next = value.wrapping_add(1);
+        }
+
+        let mut sorted = values.clone();
+        sorted.sort_unstable();
+        let mut duplicates: Vec<u64> = sorted.windows(2).filter(|w| w[0] == w[1]).map(|w| w[0]).collect();
+        duplicates.dedup();
+
+        let max = values.iter().copied().max().unwrap_or(0);
+        let width = if max <= 0xff && values.len() <= 256 {
+            1
+        } else if max <= 0xffff {
+            2
+        } else if max <= 0xffff_ffff {
+            4
+        } else {
+            8
+        };
+        let sequential = values.iter().enumerate().all(|(i, v)| *v == i as u64);
+        let mode = match width {
+            1 if sequential => EnumTagMode::Index,
+            1 => EnumTagMode::Discriminant,
+            _ => EnumTagMode::Wide,
+        };
+
+        Self { mode, width, values, duplicates }
+    }
+
+    /// 标签的 Rust 类型
+    pub fn tag_type(&self) -> &'static str {
+        match self.width {
+            1 => "u8",
+            2 => "u16",
+            4 => "u32",
+            _ => "u64",
+        }
+    }
+}
+
+/// 从 IDL 变体对象读取显式判别值
+pub fn explicit_value(variant: &serde_json::Map<String, serde_json::Value>) -> Option<u64> {
+    ["value", "discriminant", "discriminator"]
+        .iter()
+        .find_map(|key| variant.get(*key).and_then(|v| v.as_u64()))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn layout_picks_tag_width_and_mode() {
+        let index = EnumTagLayout::from_values([None, None, None]);
+        assert_eq!((index.mode, index.width, index.values), (EnumTagMode::Index, 1, vec![0, 1, 2]));
+
+        let sparse = EnumTagLayout::from_values([Some(1), None, Some(10)]);
+        assert_eq!((sparse.mode, sparse.values), (EnumTagMode::Discriminant, vec![1, 2, 10]));
+
+        let many = EnumTagLayout::from_values((0..300).map(|_| None));
+        assert_eq!((many.mode, many.tag_type()), (EnumTagMode::Wide, "u16"));
+
+        let large = EnumTagLayout::from_values([Some(0), Some(70_000), Some(70_000)]);
+        assert_eq!((large.tag_type(), large.duplicates), ("u32", vec![70_000]));
+    }
+}
@@ -0,0 +1,273 @@
+//! 字段类型替换
+//!
+//! `--field-type Owner.field=path::Type` 把账户 / 事件 / 类型的字段或指令参数生成为指定的
+//! Rust 类型，如以 `Hash` newtype 替换 `[u8; 32]`、以 `UnixTimestamp` 替换 `i64`。替换较多时
+//! 可写入 `--field-types-file`，每行一条，`#` 开头为注释。
+//!
+//! 编码不随之改变：Borsh、serde 与 arbitrary 经由 IDL 原类型转换（生成的 `field_types.rs`），
+//! 替换类型需实现 `Into<原类型>`、`TryFrom<原类型>`（错误类型实现 `Display`）与 `Default`，
+//! 并与原类型一样满足所在结构体派生的 trait（Clone / Debug / PartialEq，原类型满足时的
+//! Copy / Eq / Hash）。零拷贝与 C 布局结构体按内存布局读取，COption、SmallVec 与 raw_tail 字段
+//! 另有编解码，均不支持替换。
+//!
+//! 替换在 IR 上进行：同名的账户、事件、类型与指令中的该字段都被替换。
+
+use std::path::Path;
+use std::str::FromStr;
+
+use convert_case::{Case, Casing};
+
+use super::anchor_idl::{AnchorField, AnchorFieldType, AnchorTypeKind};
+use super::arg_encoding::ArgEncoding;
+use super::non_anchor_idl::{NonAnchorField, NonAnchorFieldType, NonAnchorTypeKind};
+use super::IdlFormatEnum;
+use crate::error::SoloresError;
+use crate::minijinja::utils;
+
+/// 单条字段类型替换：`Owner.field=path::Type`
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct FieldTypeOverride {
+    pub owner: String,
+    pub field: String,
+    pub rust_type: String,
+}
+
+impl FromStr for FieldTypeOverride {
+    type Err = String;
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        let format_error = || format!("字段类型替换 '{}' 格式应为 Owner.field=path::Type", s);
+        let (path, rust_type) = s.split_once('=').ok_or_else(format_error)?;
+        let (owner, field) = path
+            .split_once('.')
+            .filter(|(owner, field)| !owner.trim().is_empty() && !field.trim().is_empty())
+            .ok_or_else(format_error)?;
+        let rust_type = rust_type.trim();
+        let valid = rust_type.starts_with(|c: char| c.is_ascii_alphabetic() || c == '_' || c == '[' || c == '(')
+            && rust_type
+                .chars()
+                .all(|c| c.is_ascii_alphanumeric() || "_:<>[]();, ".contains(c));
+        if !valid {
+            return Err(format!("字段类型替换 '{}' 的类型 '{}' 不是有效的 Rust 类型路径", s, rust_type));
+        }
+        Ok(FieldTypeOverride {
+            owner: owner.trim().to_string(),
+            field: field.trim().to_string(),
+            rust_type: rust_type.to_string(),
+        })
+    }
+}
+
+/// 读取 `--field-types-file`：每行一条 `Owner.field=path::Type`，忽略空行与 `#` 注释
+pub fn load_field_types_file(path: &Path) -> Result<Vec<FieldTypeOverride>, SoloresError> {
+    let content = std::fs::read_to_string(path)
+        .map_err(|e| SoloresError::file_operation_error("读取字段类型替换文件", path.display().to_string(), e))?;
+    content
+        .lines()
+        .enumerate()
+        .map(|(index, line)| (index, line.split('#').next().unwrap_or_default().trim()))
+        .filter(|(_, line)| !line.is_empty())
+        .map(|(index, line)| {
+            line.parse().map_err(|message| SoloresError::ValidationError {
+                message,
+                field_path: Some(format!("{}:{}", path.display(), index + 1)),
+                expected: Some("Owner.field=path::Type".to_string()),
+                actual: Some(line.to_string()),
+            })
+        })
+        .collect()
+}
+
+/// 可替换类型的字段视图：(所属条目名, 字段名, 不支持替换的原因, 替换类型)
+type FieldView<'a> = (&'a str, &'a str, Option<&'static str>, &'a mut Option<String>);
+
+const ZERO_COPY: &str = "零拷贝账户按内存布局读取";
+const C_LAYOUT: &str = "C 布局结构体按内存布局读取";
+const COPTION: &str = "COption 字段经 crate::coption 编解码";
+const SMALL_VEC: &str = "SmallVec 字段经 crate::small_vec 编解码";
+const RAW_TAIL: &str = "raw_tail 参数经 crate::raw_tail 编解码";
+
+impl IdlFormatEnum {
+    /// 应用 `--field-type` 替换；条目或字段不存在、字段不支持替换时返回 ValidationError
+    pub fn apply_field_types(&mut self, overrides: &[FieldTypeOverride]) -> Result<(), SoloresError> {
+        if overrides.is_empty() {
+            return Ok(());
+        }
+        let mut fields = self.substitutable_fields();
+
+        for field_type in overrides {
+            let field_path = format!("{}.{}", field_type.owner, field_type.field);
+            let owner_name = field_type.owner.to_case(Case::Pascal);
+            let field_name = field_type.field.to_case(Case::Snake);
+            let mut owner_found = false;
+            let mut replaced = false;
+            for (owner, name, unsupported, substitute) in fields.iter_mut() {
+                if owner.to_case(Case::Pascal) != owner_name {
+                    continue;
+                }
+                owner_found = true;
+                if name.to_case(Case::Snake) != field_name {
+                    continue;
+                }
+                if let Some(reason) = unsupported {
+                    return Err(validation_error(&field_path, format!("字段不支持类型替换：{}", reason)));
+                }
+                **substitute = Some(field_type.rust_type.clone());
+                replaced = true;
+            }
+            if !owner_found {
+                return Err(validation_error(&field_path, format!("未找到账户、事件、类型或指令 '{}'", field_type.owner)));
+            }
+            if !replaced {
+                return Err(validation_error(&field_path, format!("'{}' 中未找到字段 '{}'", owner_name, field_type.field)));
+            }
+        }
+        Ok(())
+    }
+
+    fn substitutable_fields(&mut self) -> Vec<FieldView<'_>> {
+        fn anchor_fields<'a>(owner: &'a str, owner_reason: Option<&'static str>, fields: &'a mut [AnchorField]) -> Vec<FieldView<'a>> {
+            fields
+                .iter_mut()
+                .map(|field| {
+                    let reason = owner_reason
+                        .or(matches!(field.field_type, AnchorFieldType::coption(_)).then_some(COPTION))
+                        .or(utils::anchor_small_vec_len(&field.field_type).map(|_| SMALL_VEC))
+                        .or((field.encoding == Some(ArgEncoding::RawTail)).then_some(RAW_TAIL));
+                    (owner, field.name.as_str(), reason, &mut field.substitute)
+                })
+                .collect()
+        }
+        fn non_anchor_fields<'a>(owner: &'a str, owner_reason: Option<&'static str>, fields: &'a mut [NonAnchorField]) -> Vec<FieldView<'a>> {
+            fields
+                .iter_mut()
+                .map(|field| {
+                    let reason = owner_reason
+                        .or(matches!(field.field_type, NonAnchorFieldType::COption { .. }).then_some(COPTION))
+                        .or(utils::non_anchor_small_vec_len(&field.field_type).map(|_| SMALL_VEC))
+                        .or((field.encoding == Some(ArgEncoding::RawTail)).then_some(RAW_TAIL));
+                    (owner, field.name.as_str(), reason, &mut field.substitute)
+                })
+                .collect()
+        }
+
+        let mut views = Vec::new();
+        match self {
+            IdlFormatEnum::Anchor(idl) => {
+                for account in idl.accounts.iter_mut().flatten() {
+                    let reason = account.zero_copy.then_some(ZERO_COPY);
+                    views.extend(anchor_fields(&account.name, reason, account.fields.as_deref_mut().unwrap_or_default()));
+                }
+                for event in idl.events.iter_mut().flatten() {
+                    views.extend(anchor_fields(&event.name, None, event.fields.as_deref_mut().unwrap_or_default()));
+                }
+                for type_def in idl.types.iter_mut().flatten() {
+                    let reason = type_def.serialization.as_deref().is_some_and(|s| s.starts_with("bytemuck")).then_some(ZERO_COPY);
+                    if let Some(AnchorTypeKind::Struct(fields)) = type_def.kind.as_mut() {
+                        views.extend(anchor_fields(&type_def.name, reason, fields));
+                    }
+                }
+                for instruction in idl.instructions.iter_mut().flatten() {
+                    views.extend(anchor_fields(&instruction.name, None, instruction.args.as_deref_mut().unwrap_or_default()));
+                }
+            }
+            IdlFormatEnum::NonAnchor(idl) => {
+                for account in idl.accounts.iter_mut().flatten() {
+                    let reason = if account.zero_copy {
+                        Some(ZERO_COPY)
+                    } else {
+                        account.c_layout.is_some().then_some(C_LAYOUT)
+                    };
+                    views.extend(non_anchor_fields(&account.name, reason, account.fields.as_deref_mut().unwrap_or_default()));
+                }
+                for event in idl.events.iter_mut().flatten() {
+                    views.extend(non_anchor_fields(&event.name, None, event.fields.as_deref_mut().unwrap_or_default()));
+                }
+                for type_def in idl.types.iter_mut().flatten() {
+                    let reason = type_def.c_layout.is_some().then_some(C_LAYOUT);
+                    if let NonAnchorTypeKind::Struct { fields } = &mut type_def.type_def {
+                        views.extend(non_anchor_fields(&type_def.name, reason, fields));
+                    }
+                }
+                for instruction in idl.instructions.iter_mut().flatten() {
+                    views.extend(non_anchor_fields(&instruction.name, None, instruction.args.as_deref_mut().unwrap_or_default()));
+                }
+            }
+        }
+        views
+    }
+
+    /// 是否有字段替换了类型（决定是否生成 field_types.rs）
+    pub fn has_field_types(&self) -> bool {
+        let anchor = |fields: &[AnchorField]| fields.iter().any(|field| field.substitute.is_some());
+        let non_anchor = |fields: &[NonAnchorField]| fields.iter().any(|field| field.substitute.is_some());
+        match self {
+            IdlFormatEnum::Anchor(idl) => {
+                idl.accounts.iter().flatten().any(|a| anchor(a.fields.as_deref().unwrap_or_default()))
+                    || idl.events.iter().flatten().any(|e| anchor(e.fields.as_deref().unwrap_or_default()))
+                    || idl.instructions.iter().flatten().any(|i| anchor(i.args.as_deref().unwrap_or_default()))
+                    || idl.types.iter().flatten().any(|t| matches!(&t.kind, Some(AnchorTypeKind::Struct(fields)) if anchor(fields)))
+            }
+            IdlFormatEnum::NonAnchor(idl) => {
+                idl.accounts.iter().flatten().any(|a| non_anchor(a.fields.as_deref().unwrap_or_default()))
+                    || idl.events.iter().flatten().any(|e| non_anchor(e.fields.as_deref().unwrap_or_default()))
+                    || idl.instructions.iter().flatten().any(|i| non_anchor(i.args.as_deref().unwrap_or_default()))
+                    || idl.types.iter().flatten().any(|t| matches!(&t.type_def, NonAnchorTypeKind::Struct { fields } if non_anchor(fields)))
+            }
+        }
+    }
+}
+
+fn validation_error(field_path: &str, message: String) -> SoloresError {
+    SoloresError::ValidationError {
+        message,
+        field_path: Some(format!("--field-type {}", field_path)),
+        expected: None,
+        actual: None,
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::idl_format::parse_idl_json;
+
+    const POOL_IDL: &str = r#"{
+        "address": "11111111111111111111111111111111",
+        "metadata": {"name": "pool", "version": "0.1.0", "spec": "0.1.0"},
+        "instructions": [{
+            "name": "initialize",
+            "discriminator": [1, 2, 3, 4, 5, 6, 7, 8],
+            "accounts": [],
+            "args": [{"name": "openTime", "type": "i64"}]
+        }],
+        "accounts": [{"name": "Pool", "discriminator": [8, 7, 6, 5, 4, 3, 2, 1]}],
+        "types": [{
+            "name": "Pool",
+            "type": {"kind": "struct", "fields": [
+                {"name": "configHash", "type": {"array": ["u8", 32]}},
+                {"name": "openTime", "type": "i64"}
+            ]}
+        }]
+    }"#;
+
+    #[test]
+    fn substitutes_fields_of_every_owner_with_the_name() {
+        let mut idl = parse_idl_json(POOL_IDL).unwrap();
+        let overrides: Vec<FieldTypeOverride> = vec![
+            "Pool.config_hash=my_types::Hash".parse().unwrap(),
+            "initialize.openTime=my_types::UnixTimestamp".parse().unwrap(),
+        ];
+        idl.apply_field_types(&overrides).unwrap();
+        assert!(idl.has_field_types());
+        let IdlFormatEnum::Anchor(anchor) = &idl else { panic!("expected Anchor IDL") };
+        let args = anchor.instructions.as_ref().unwrap()[0].args.as_ref().unwrap();
+        assert_eq!(args[0].substitute.as_deref(), Some("my_types::UnixTimestamp"));
+
+        let mut idl = parse_idl_json(POOL_IDL).unwrap();
+        assert!(idl.apply_field_types(&["Pool.missing=u64".parse().unwrap()]).is_err());
+        assert!(idl.apply_field_types(&["Vault.open_time=u64".parse().unwrap()]).is_err());
+        assert!("Pool.open_time".parse::<FieldTypeOverride>().is_err());
+        assert!("Pool.open_time=my types::Hash!".parse::<FieldTypeOverride>().is_err());
+    }
+}
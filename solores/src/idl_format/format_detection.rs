@@ -0,0 +1,169 @@
+//! Anchor / NonAnchor 格式判定
+//!
+//! 默认按 IDL 内容推断：`metadata.spec` / `metadata.origin` 标记优先，其次看是否存在
+//! 8 字节 discriminator。混合 IDL 可能被误判，可在 IDL 的 `metadata.format`
+//! （`anchor` / `non-anchor`）中声明，或通过 `--force-format` 指定，CLI 优先。
+//! 判定结果决定解析出的 IR 变体，所有模板据此选择 anchor / non_anchor 模板；
+//! 判定依据随生成结果输出，便于排查格式误判。
+
+use std::fmt;
+
+use serde_json::Value;
+
+/// IDL 格式
+#[derive(clap::ValueEnum, Debug, Clone, Copy, PartialEq, Eq)]
+pub enum FormatKind {
+    /// 8字节 discriminator 的 Anchor 合约
+    Anchor,
+    /// 1字节 discriminator 或其他识别方式（Shank / Native / SPL）
+    #[value(name = "non-anchor", alias = "non_anchor")]
+    NonAnchor,
+}
+
+impl FormatKind {
+    /// `metadata.format` 中接受的写法
+    fn from_metadata(value: &str) -> Option<Self> {
+        match value {
+            "anchor" => Some(FormatKind::Anchor),
+            "non-anchor" | "non_anchor" | "shank" => Some(FormatKind::NonAnchor),
+            _ => None,
+        }
+    }
+}
+
+impl fmt::Display for FormatKind {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            FormatKind::Anchor => f.write_str("Anchor"),
+            FormatKind::NonAnchor => f.write_str("NonAnchor"),
+        }
+    }
+}
+
+/// 格式判定结果及依据
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct FormatDetection {
+    pub kind: FormatKind,
+    pub reason: String,
+}
+
+impl FormatDetection {
+    fn new(kind: FormatKind, reason: impl Into<String>) -> Self {
+        Self { kind, reason: reason.into() }
+    }
+}
+
+impl fmt::Display for FormatDetection {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(f, "{}（{}）", self.kind, self.reason)
+    }
+}
+
+/// 是否带有旧版 Anchor `#[state]` 段（含 `struct` 或 `methods`）
+///
+/// Shank 没有对应结构；原生程序 IDL 可能带空的 `state: {}`，不作为 Anchor 依据
+pub(crate) fn has_legacy_state(json: &Value) -> bool {
+    json.get("state").is_some_and(|state| state.get("struct").is_some() || state.get("methods").is_some())
+}
+
+/// 判定 IDL 格式；`forced` 为 `--force-format` 指定的格式
+pub fn detect_format(json: &Value, forced: Option<FormatKind>) -> FormatDetection {
+    if let Some(kind) = forced {
+        return FormatDetection::new(kind, "--force-format 指定");
+    }
+
+    let metadata = json.get("metadata");
+    let metadata_str = |key: &str| metadata.and_then(|m| m.get(key)).and_then(Value::as_str);
+    if let Some(format) = metadata_str("format") {
+        match FormatKind::from_metadata(format) {
+            Some(kind) => return FormatDetection::new(kind, format!("metadata.format = \"{}\"", format)),
+            None => log::warn!("⚠️ 忽略无法识别的 metadata.format \"{}\"（应为 anchor / non-anchor）", format),
+        }
+    }
+    for key in ["spec", "origin"] {
+        match metadata_str(key) {
+            Some("anchor") => return FormatDetection::new(FormatKind::Anchor, format!("metadata.{} = \"anchor\"", key)),
+            // shank 生成的是 NonAnchor 格式
+            Some("shank") => return FormatDetection::new(FormatKind::NonAnchor, format!("metadata.{} = \"shank\"", key)),
+            _ => {}
+        }
+    }
+
+    if has_legacy_state(json) {
+        return FormatDetection::new(FormatKind::Anchor, "顶层 state 段（旧版 Anchor #[state]）");
+    }
+    let is_anchor_discriminator =
+        |item: &Value| item.get("discriminator").and_then(Value::as_array).is_some_and(|arr| arr.len() == 8);
+    if is_anchor_discriminator(json) {
+        return FormatDetection::new(FormatKind::Anchor, "顶层 discriminator 为 8 字节");
+    }
+    for section in ["instructions", "accounts"] {
+        let items = json.get(section).and_then(Value::as_array).map(Vec::as_slice).unwrap_or_default();
+        let anchor_items: Vec<&str> = items
+            .iter()
+            .filter(|item| is_anchor_discriminator(item))
+            .map(|item| item.get("name").and_then(Value::as_str).unwrap_or("?"))
+            .collect();
+        if let Some(first) = anchor_items.first() {
+            return FormatDetection::new(
+                FormatKind::Anchor,
+                format!(
+                    "{} 中 {}/{} 项使用 8 字节 discriminator，如 `{}`",
+                    section,
+                    anchor_items.len(),
+                    items.len(),
+                    first
+                ),
+            );
+        }
+    }
+
+    FormatDetection::new(FormatKind::NonAnchor, "未发现 Anchor 标记或 8 字节 discriminator")
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use serde_json::json;
+
+    #[test]
+    fn detection_prefers_cli_then_metadata_then_discriminators() {
+        let hybrid = json!({
+            "metadata": {"name": "hybrid", "version": "0.1.0", "spec": "0.1.0"},
+            "instructions": [
+                {"name": "init", "discriminator": [0, 1, 2, 3, 4, 5, 6, 7]},
+                {"name": "swap", "discriminator": [1]}
+            ]
+        });
+        let detected = detect_format(&hybrid, None);
+        assert_eq!(detected.kind, FormatKind::Anchor);
+        assert_eq!(detected.reason, "instructions 中 1/2 项使用 8 字节 discriminator，如 `init`");
+        assert_eq!(detect_format(&hybrid, Some(FormatKind::NonAnchor)).kind, FormatKind::NonAnchor);
+
+        let mut declared = hybrid.clone();
+        declared["metadata"]["format"] = json!("non-anchor");
+        assert_eq!(
+            detect_format(&declared, None),
+            FormatDetection::new(FormatKind::NonAnchor, "metadata.format = \"non-anchor\"")
+        );
+        assert_eq!(detect_format(&declared, Some(FormatKind::Anchor)).kind, FormatKind::Anchor);
+
+        assert_eq!(detect_format(&json!({"instructions": []}), None).kind, FormatKind::NonAnchor);
+    }
+
+    #[test]
+    fn only_populated_state_sections_mark_legacy_anchor() {
+        let native = json!({"name": "system", "instructions": [{"name": "transfer", "accounts": [], "args": []}], "state": {}});
+        assert_eq!(detect_format(&native, None).kind, FormatKind::NonAnchor);
+        assert!(!crate::convert::is_legacy_anchor(&json!({"metadata": {"spec": "solana-native"}, "state": {}})));
+
+        let legacy = json!({"name": "counter", "instructions": [], "state": {"struct": {"name": "Counter"}, "methods": []}});
+        assert_eq!(detect_format(&legacy, None).kind, FormatKind::Anchor);
+        assert!(crate::convert::is_legacy_anchor(&legacy));
+
+        let path = std::path::Path::new(env!("CARGO_MANIFEST_DIR")).join("../idls/native/system.json");
+        let (idl, detection) = super::super::parse_idl_json_with_format(&std::fs::read_to_string(path).unwrap(), None).unwrap();
+        assert_eq!(detection.kind, FormatKind::NonAnchor);
+        assert!(matches!(idl, super::super::IdlFormatEnum::NonAnchor(_)));
+    }
+}
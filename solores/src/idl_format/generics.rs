@@ -0,0 +1,207 @@
+//! 泛型类型单态化
+//!
+//! Anchor 0.30 `idl-build` 为泛型类型输出 `generics`（`{"kind": "type", "name": "T"}`、
+//! `{"kind": "const", "name": "N", "type": "usize"}`），类型体中以 `{"generic": "T"}` 引用
+//! 类型参数，数组长度也可以是 `{"generic": "N"}`；引用处写成
+//! `{"defined": {"name": "Wrapper", "generics": [{"kind": "type", "type": "u64"}, {"kind": "const", "value": "32"}]}}`。
+//!
+//! 生成代码不含泛型类型：解析前在 JSON 上为每组实际参数生成一个单态化类型
+//! （`Wrapper` + `u64` + `32` → `WrapperU6432`），替换引用，并移除泛型类型本身。
+
+use std::collections::HashMap;
+
+use convert_case::{Case, Casing};
+use serde_json::{Map, Value};
+
+/// 实例化的最大嵌套深度，防止自引用的泛型类型无限展开
+const MAX_DEPTH: usize = 16;
+
+/// 泛型类型定义
+struct GenericType {
+    /// 参数名，按声明顺序
+    params: Vec<String>,
+    /// 类型定义（不含 `generics`）
+    definition: Value,
+}
+
+/// 将 IDL JSON 中的泛型类型替换为单态化类型
+pub fn monomorphize_generics(idl: &mut Value) {
+    let Some(types) = idl.get_mut("types").and_then(Value::as_array_mut) else { return };
+    let mut generics: HashMap<String, GenericType> = HashMap::new();
+    types.retain(|type_def| {
+        let Some(params) = type_def.get("generics").and_then(Value::as_array) else { return true };
+        let Some(name) = type_def.get("name").and_then(Value::as_str) else { return true };
+        let params = params.iter().filter_map(|param| param.get("name")?.as_str().map(str::to_string)).collect();
+        let mut definition = type_def.clone();
+        if let Some(definition) = definition.as_object_mut() {
+            definition.remove("generics");
+        }
+        generics.insert(name.to_string(), GenericType { params, definition });
+        false
+    });
+    if generics.is_empty() {
+        return;
+    }
+
+    let mut instances = Instances { generics: &generics, names: HashMap::new(), created: Vec::new() };
+    instances.visit(idl, 0);
+    for name in generics.keys() {
+        if !instances.names.keys().any(|(generic, _)| generic == name) {
+            log::warn!("⚠️ 泛型类型 {} 未以具体参数引用，已跳过", name);
+        }
+    }
+    let created = std::mem::take(&mut instances.created);
+    if let Some(types) = idl.get_mut("types").and_then(Value::as_array_mut) {
+        types.extend(created);
+    }
+}
+
+struct Instances<'a> {
+    generics: &'a HashMap<String, GenericType>,
+    /// (泛型类型名, 实际参数 JSON) → 单态化类型名
+    names: HashMap<(String, String), String>,
+    /// 已生成的单态化类型定义
+    created: Vec<Value>,
+}
+
+impl Instances<'_> {
+    /// 替换 `value` 中带实际参数的泛型类型引用
+    fn visit(&mut self, value: &mut Value, depth: usize) {
+        match value {
+            Value::Object(map) => {
+                if let Some(name) = self.instantiate_reference(map, depth) {
+                    map.insert("defined".to_string(), Value::from(name));
+                    return;
+                }
+                map.values_mut().for_each(|child| self.visit(child, depth));
+            }
+            Value::Array(items) => items.iter_mut().for_each(|child| self.visit(child, depth)),
+            _ => {}
+        }
+    }
+
+    /// `{"defined": {"name": X, "generics": [...]}}` 引用泛型类型 X 时返回单态化类型名
+    fn instantiate_reference(&mut self, map: &Map<String, Value>, depth: usize) -> Option<String> {
+        let defined = map.get("defined")?.as_object()?;
+        let name = defined.get("name")?.as_str()?;
+        let generics = self.generics;
+        let generic = generics.get(name)?;
+        let mut args: Vec<Value> = defined.get("generics").and_then(Value::as_array).cloned().unwrap_or_default();
+        // 实际参数本身可能引用其他泛型类型
+        args.iter_mut().for_each(|arg| self.visit(arg, depth));
+
+        let key = (name.to_string(), Value::Array(args.clone()).to_string());
+        if let Some(instance) = self.names.get(&key) {
+            return Some(instance.clone());
+        }
+        if depth >= MAX_DEPTH {
+            log::warn!("⚠️ 泛型类型 {} 嵌套实例化过深，保留原引用", name);
+            return None;
+        }
+        if args.len() != generic.params.len() {
+            log::warn!("⚠️ 泛型类型 {} 需要 {} 个参数，引用处给出 {} 个", name, generic.params.len(), args.len());
+            return None;
+        }
+
+        let instance = format!("{}{}", name, args.iter().map(arg_label).collect::<String>());
+        self.names.insert(key, instance.clone());
+        let bindings: HashMap<&str, Value> =
+            generic.params.iter().map(String::as_str).zip(args.iter().map(arg_value)).collect();
+        let mut definition = generic.definition.clone();
+        substitute(&mut definition, &bindings);
+        definition["name"] = Value::from(instance.clone());
+        self.visit(&mut definition, depth + 1);
+        log::debug!("🧬 泛型类型 {} 以参数 {} 实例化为 {}", name, Value::Array(args), instance);
+        self.created.push(definition);
+        Some(instance)
+    }
+}
+
+/// 实际参数替换进类型体的值：类型参数为类型，常量参数为数字
+fn arg_value(arg: &Value) -> Value {
+    match arg.get("kind").and_then(Value::as_str) {
+        Some("const") => {
+            let value = arg.get("value").cloned().unwrap_or(Value::Null);
+            value.as_str().and_then(|value| value.parse::<u64>().ok()).map(Value::from).unwrap_or(value)
+        }
+        _ => arg.get("type").cloned().unwrap_or(Value::Null),
+    }
+}
+
+/// 单态化类型名中实际参数的部分
+fn arg_label(arg: &Value) -> String {
+    match arg.get("kind").and_then(Value::as_str) {
+        Some("const") => match arg.get("value") {
+            Some(Value::String(value)) => value.clone(),
+            Some(value) => value.to_string(),
+            None => String::new(),
+        },
+        _ => type_label(arg.get("type").unwrap_or(&Value::Null)),
+    }
+}
+
+fn type_label(ty: &Value) -> String {
+    match ty {
+        Value::String(name) => name.to_case(Case::Pascal),
+        Value::Object(map) => {
+            if let Some(defined) = map.get("defined") {
+                let name = defined.as_str().or_else(|| defined.get("name")?.as_str()).unwrap_or_default();
+                return name.to_case(Case::Pascal);
+            }
+            if let Some(Value::Array(array)) = map.get("array") {
+                let len = array.get(1).map(|len| len.to_string()).unwrap_or_default();
+                return format!("{}{}", array.first().map(type_label).unwrap_or_default(), len);
+            }
+            ["vec", "option", "coption"]
+                .iter()
+                .find_map(|wrapper| map.get(*wrapper).map(|inner| format!("{}{}", wrapper.to_case(Case::Pascal), type_label(inner))))
+                .unwrap_or_default()
+        }
+        _ => String::new(),
+    }
+}
+
+/// 将 `{"generic": "T"}` 替换为绑定的值
+fn substitute(value: &mut Value, bindings: &HashMap<&str, Value>) {
+    if let Some(bound) = value.get("generic").and_then(Value::as_str).and_then(|name| bindings.get(name)) {
+        *value = bound.clone();
+        return;
+    }
+    match value {
+        Value::Object(map) => map.values_mut().for_each(|child| substitute(child, bindings)),
+        Value::Array(items) => items.iter_mut().for_each(|child| substitute(child, bindings)),
+        _ => {}
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use serde_json::json;
+
+    #[test]
+    fn generic_types_are_instantiated_per_argument_list() {
+        let mut idl = json!({
+            "types": [
+                {"name": "Wrapper", "generics": [{"kind": "type", "name": "T"}, {"kind": "const", "name": "N", "type": "usize"}],
+                 "type": {"kind": "struct", "fields": [
+                    {"name": "value", "type": {"generic": "T"}},
+                    {"name": "items", "type": {"array": [{"generic": "T"}, {"generic": "N"}]}}
+                 ]}},
+                {"name": "Pool", "type": {"kind": "struct", "fields": [
+                    {"name": "a", "type": {"defined": {"name": "Wrapper", "generics": [{"kind": "type", "type": "u64"}, {"kind": "const", "value": "4"}]}}},
+                    {"name": "b", "type": {"defined": {"name": "Wrapper", "generics": [{"kind": "type", "type": "u64"}, {"kind": "const", "value": "4"}]}}}
+                 ]}}
+            ]
+        });
+        monomorphize_generics(&mut idl);
+
+        let types = idl["types"].as_array().unwrap();
+        assert_eq!(types.len(), 2);
+        assert_eq!(types[0]["type"]["fields"][0]["type"], json!({"defined": "WrapperU644"}));
+        assert_eq!(types[0]["type"]["fields"][1]["type"], json!({"defined": "WrapperU644"}));
+        assert_eq!(types[1]["name"], "WrapperU644");
+        assert_eq!(types[1]["type"]["fields"][1]["type"], json!({"array": ["u64", 4]}));
+        assert!(types[1].get("generics").is_none());
+    }
+}
@@ -0,0 +1,275 @@
+//! 标识符清理与命名冲突处理
+//!
+//! 各生成器共用的命名规则：
+//! - 与 Rust 关键字同名的标识符使用原始标识符（`r#type`）；`self` / `super` / `crate` /
+//!   `Self` 不能作为原始标识符，加 `_` 后缀；
+//! - 账户、类型、事件名与标准库 prelude 中的条目（`Option`、`Box`）同名时加类别后缀
+//!   （`OptionType`），同一类别中转换为帕斯卡命名后相同的名称依次加数字后缀（`Pool2`），
+//!   重命名在 IR 上进行，与 `--type-prefix` 相同；蛇形命名为关键字的指令账户改名为
+//!   `{name}_account`；
+//! - 模块名统一按蛇形命名生成，按小写去重（大小写不敏感的文件系统上 `Pool.rs` 与
+//!   `pool.rs` 是同一文件），并避开 `mod`、`compiled` 等生成器自用的文件名。
+
+use std::collections::{HashMap, HashSet};
+
+use convert_case::{Case, Casing};
+
+use super::anchor_idl::PdaSeed;
+use super::naming::{NameCategory, RenameMap};
+use super::IdlFormatEnum;
+
+/// Rust 2021 的严格关键字与保留关键字（含 2024 的 `gen`）
+const RUST_KEYWORDS: [&str; 52] = [
+    "as", "async", "await", "break", "const", "continue", "crate", "dyn", "else", "enum", "extern", "false", "fn",
+    "for", "if", "impl", "in", "let", "loop", "match", "mod", "move", "mut", "pub", "ref", "return", "self", "Self",
+    "static", "struct", "super", "trait", "true", "type", "unsafe", "use", "where", "while", "abstract", "become",
+    "box", "do", "final", "gen", "macro", "override", "priv", "try", "typeof", "unsized", "virtual", "yield",
+];
+
+/// 不能写成原始标识符的关键字
+const NON_RAW_KEYWORDS: [&str; 4] = ["crate", "self", "super", "Self"];
+
+/// 生成代码依赖的 prelude 条目及常用外部类型，账户、类型、事件不能以此命名
+const RESERVED_TYPE_NAMES: [&str; 30] = [
+    "Option", "Some", "None", "Result", "Ok", "Err", "Vec", "String", "Box", "ToString", "ToOwned", "Clone", "Copy",
+    "Default", "Eq", "PartialEq", "Ord", "PartialOrd", "Send", "Sync", "Sized", "Drop", "From", "Into", "TryFrom",
+    "TryInto", "Iterator", "IntoIterator", "AsRef", "Pubkey",
+];
+
+/// 生成器在各目录中自用的模块名
+pub fn reserved_modules(folder: &str) -> &'static [&'static str] {
+    match folder {
+        "instructions" => &["mod", "account_meta", "compiled"],
+        _ => &["mod"],
+    }
+}
+
+/// 是否为 Rust 关键字
+pub fn is_keyword(name: &str) -> bool {
+    RUST_KEYWORDS.contains(&name)
+}
+
+/// 可直接用于生成代码的标识符：关键字使用原始标识符，不能作为原始标识符的加 `_` 后缀
+pub fn rust_ident(name: &str) -> String {
+    if NON_RAW_KEYWORDS.contains(&name) {
+        format!("{}_", name)
+    } else if is_keyword(name) {
+        format!("r#{}", name)
+    } else {
+        name.to_string()
+    }
+}
+
+/// 条目对应的模块
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct ModuleName {
+    /// `pub mod` 使用的标识符，可能为原始标识符
+    pub ident: String,
+    /// 文件名（不含 `.rs`）
+    pub file_stem: String,
+}
+
+/// 按顺序为同一目录中的条目分配模块名，同一输入序列总是得到同一结果
+#[derive(Debug, Clone)]
+pub struct ModuleNames {
+    /// 已占用的文件名（小写）
+    taken: HashSet<String>,
+}
+
+impl ModuleNames {
+    pub fn new(folder: &str) -> Self {
+        Self { taken: reserved_modules(folder).iter().map(|name| name.to_string()).collect() }
+    }
+
+    /// 分配条目的模块名：蛇形命名，不以数字开头，与已分配的文件名大小写不敏感地去重
+    pub fn allocate(&mut self, name: &str) -> ModuleName {
+        let mut stem = name.to_case(Case::Snake);
+        if stem.is_empty() || stem.starts_with(|c: char| c.is_ascii_digit()) {
+            stem.insert(0, '_');
+        }
+        if NON_RAW_KEYWORDS.contains(&stem.as_str()) {
+            stem.push('_');
+        }
+        let stem = unique(&stem, "_", |candidate| self.taken.contains(&candidate.to_lowercase()));
+        self.taken.insert(stem.to_lowercase());
+        ModuleName { ident: rust_ident(&stem), file_stem: stem }
+    }
+}
+
+/// `base` 未被占用时原样返回，否则依次尝试 `{base}{sep}2`、`{base}{sep}3`…
+fn unique(base: &str, separator: &str, taken: impl Fn(&str) -> bool) -> String {
+    if !taken(base) {
+        return base.to_string();
+    }
+    (2..)
+        .map(|n| format!("{}{}{}", base, separator, n))
+        .find(|candidate| !taken(candidate))
+        .expect("unbounded suffix range")
+}
+
+fn category_suffix(category: NameCategory) -> &'static str {
+    match category {
+        NameCategory::Accounts => "Account",
+        NameCategory::Types => "Type",
+        NameCategory::Events => "Event",
+        NameCategory::Instructions => "Instruction",
+    }
+}
+
+impl IdlFormatEnum {
+    /// 重命名与 prelude 条目同名或在同一类别中重名的账户、类型、事件与指令，
+    /// 同步更新类型引用，返回 (原名称, 新名称)
+    pub fn resolve_name_conflicts(&mut self) -> Vec<(String, String)> {
+        let mut renames: Vec<(String, String)> = Vec::new();
+        let mut by_category: HashMap<NameCategory, Vec<&str>> = HashMap::new();
+        for (category, name) in self.named_items() {
+            let names = by_category.entry(category).or_default();
+            // Anchor 账户同时出现在 types 中，同一原始名称视为同一条目
+            if !names.contains(&name) {
+                names.push(name);
+            }
+        }
+
+        for category in [NameCategory::Accounts, NameCategory::Events, NameCategory::Types, NameCategory::Instructions] {
+            let names = by_category.remove(&category).unwrap_or_default();
+            let mut taken: HashSet<String> = names.iter().map(|name| name.to_case(Case::Pascal)).collect();
+            let mut seen: HashSet<String> = HashSet::new();
+            for name in names {
+                if renames.iter().any(|(from, _)| from == name) {
+                    continue;
+                }
+                let pascal = name.to_case(Case::Pascal);
+                let reserved = pascal == "Self"
+                    || (category != NameCategory::Instructions && RESERVED_TYPE_NAMES.contains(&pascal.as_str()));
+                let renamed = if reserved {
+                    let base = format!("{}{}", pascal, category_suffix(category));
+                    Some(unique(&base, "", |candidate| taken.contains(candidate)))
+                } else if seen.contains(&pascal) {
+                    Some(unique(&pascal, "", |candidate| taken.contains(candidate)))
+                } else {
+                    None
+                };
+                match renamed {
+                    Some(renamed) => {
+                        taken.insert(renamed.clone());
+                        renames.push((name.to_string(), renamed));
+                    }
+                    None => {
+                        seen.insert(pascal);
+                    }
+                }
+            }
+        }
+
+        if !renames.is_empty() {
+            let map = RenameMap::from_names(renames.iter().cloned().collect());
+            self.rename_items(&map);
+        }
+        renames.extend(self.rename_keyword_accounts());
+        renames
+    }
+
+    /// 指令账户名是 Keys 结构体的字段名，也拼接进函数与常量名，不能使用原始标识符；
+    /// 蛇形命名为关键字的账户改名为 `{name}_account`，并同步更新引用它的 PDA seed
+    fn rename_keyword_accounts(&mut self) -> Vec<(String, String)> {
+        let renamed = |instruction: &str, name: &mut String, renames: &mut Vec<(String, String)>| {
+            let snake = name.to_case(Case::Snake);
+            if !is_keyword(&snake) {
+                return None;
+            }
+            let old = std::mem::replace(name, format!("{}_account", snake));
+            renames.push((format!("{}.{}", instruction, old), name.clone()));
+            Some((old, name.clone()))
+        };
+
+        let mut renames = Vec::new();
+        match self {
+            IdlFormatEnum::Anchor(idl) => {
+                for instruction in idl.instructions.iter_mut().flatten() {
+                    let accounts = instruction.accounts.iter_mut().flatten();
+                    let changed: Vec<(String, String)> =
+                        accounts.filter_map(|account| renamed(&instruction.name, &mut account.name, &mut renames)).collect();
+                    for account in instruction.accounts.iter_mut().flatten() {
+                        let Some(pda) = account.pda.as_mut() else { continue };
+                        for seed in pda.seeds.iter_mut().chain(pda.program.as_mut()) {
+                            if let PdaSeed::Account { path } = seed {
+                                rename_seed_path(path, &changed);
+                            }
+                        }
+                    }
+                }
+            }
+            IdlFormatEnum::NonAnchor(idl) => {
+                for instruction in idl.instructions.iter_mut().flatten() {
+                    for account in instruction.accounts.iter_mut().flatten() {
+                        renamed(&instruction.name, &mut account.name, &mut renames);
+                    }
+                }
+            }
+        }
+        renames
+    }
+}
+
+/// seed 路径 `account` 或 `account.field` 的账户部分改名
+fn rename_seed_path(path: &mut String, changed: &[(String, String)]) {
+    let (account, rest) = match path.split_once('.') {
+        Some((account, rest)) => (account, Some(rest)),
+        None => (path.as_str(), None),
+    };
+    if let Some((_, new)) = changed.iter().find(|(old, _)| old == account) {
+        *path = match rest {
+            Some(rest) => format!("{}.{}", new, rest),
+            None => new.clone(),
+        };
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::idl_format::non_anchor_idl::{NonAnchorFieldType, NonAnchorTypeKind};
+    use crate::idl_format::parse_idl_json;
+
+    #[test]
+    fn module_names_avoid_keywords_and_case_collisions() {
+        let mut modules = ModuleNames::new("instructions");
+        let names: Vec<ModuleName> =
+            ["type", "self", "Pool", "pool", "compiled", "2x"].into_iter().map(|name| modules.allocate(name)).collect();
+        let idents: Vec<&str> = names.iter().map(|name| name.ident.as_str()).collect();
+        assert_eq!(idents, ["r#type", "self_", "pool", "pool_2", "compiled_2", "_2_x"]);
+        assert_eq!(names[0].file_stem, "type");
+        assert_eq!(rust_ident("mut"), "r#mut");
+        assert_eq!(rust_ident("amount"), "amount");
+    }
+
+    #[test]
+    fn reserved_and_colliding_item_names_are_renamed() {
+        let mut ir = parse_idl_json(
+            r#"{
+                "address": "11111111111111111111111111111111",
+                "metadata": {"name": "demo", "version": "0.1.0", "spec": "0.1.0"},
+                "instructions": [],
+                "types": [
+                    {"name": "Option", "type": {"kind": "struct", "fields": []}},
+                    {"name": "pool", "type": {"kind": "struct", "fields": []}},
+                    {"name": "Pool", "type": {"kind": "struct", "fields": [
+                        {"name": "fallback", "type": {"defined": "Option"}}
+                    ]}}
+                ]
+            }"#,
+        )
+        .unwrap();
+
+        let renames = ir.resolve_name_conflicts();
+        assert_eq!(
+            renames,
+            [("Option".to_string(), "OptionType".to_string()), ("Pool".to_string(), "Pool2".to_string())]
+        );
+        let names: Vec<&str> = ir.named_items().into_iter().map(|(_, name)| name).collect();
+        assert_eq!(names, ["OptionType", "pool", "Pool2"]);
+        let IdlFormatEnum::NonAnchor(idl) = &ir else { panic!("expected non-anchor IDL") };
+        let NonAnchorTypeKind::Struct { fields } = &idl.types.as_deref().unwrap()[2].type_def else { panic!("expected struct") };
+        assert!(matches!(&fields[0].field_type, NonAnchorFieldType::Defined { defined } if defined == "OptionType"));
+    }
+}
@@ -0,0 +1,98 @@
+//! 旧版 Anchor `state` 段
+//!
+//! Anchor ≤0.29 的 `#[state]` 程序在 IDL 顶层 `state` 中声明单例状态账户（`struct`）
+//! 及其方法（`methods`）。解析前在 JSON 上把它折叠为普通账户与指令：状态结构体加入
+//! `accounts`（discriminator 为 `account:<Name>`），方法加入 `instructions`
+//! （discriminator 为 `state:<snake_name>`）。运行时状态账户总是方法的第一个账户；
+//! 构造函数 `new` 则先接收 Anchor `Ctor` 的 from / to / base / system_program / program。
+
+use heck::ToSnakeCase;
+use serde_json::{json, Value};
+
+use super::anchor_idl::anchor_discriminator;
+
+/// 状态方法 discriminator 的命名空间
+pub const STATE_NAMESPACE: &str = "state";
+
+/// 构造函数名称
+const CTOR_NAME: &str = "new";
+
+/// 将 `state` 段折叠为账户与指令，返回状态结构体名称
+pub fn fold_state(idl: &mut Value) -> Option<String> {
+    let obj = idl.as_object_mut()?;
+    let state = obj.remove("state")?;
+    let state_struct = state.get("struct")?.clone();
+    let name = state_struct.get("name")?.as_str()?.to_string();
+
+    let accounts = obj.entry("accounts").or_insert_with(|| json!([])).as_array_mut()?;
+    if !accounts.iter().any(|account| account.get("name").and_then(Value::as_str) == Some(name.as_str())) {
+        accounts.push(state_struct);
+    }
+
+    let methods = state.get("methods").and_then(Value::as_array).cloned().unwrap_or_default();
+    let instructions = obj.entry("instructions").or_insert_with(|| json!([])).as_array_mut()?;
+    for mut method in methods {
+        let Some(method_obj) = method.as_object_mut() else { continue };
+        let Some(method_name) = method_obj.get("name").and_then(Value::as_str).map(str::to_string) else { continue };
+        method_obj
+            .entry("discriminator")
+            .or_insert_with(|| json!(anchor_discriminator(STATE_NAMESPACE, &method_name.to_snake_case())));
+
+        let implicit = if method_name == CTOR_NAME { ctor_accounts() } else { vec![state_account()] };
+        let accounts = method_obj.entry("accounts").or_insert_with(|| json!([]));
+        if let Some(accounts) = accounts.as_array_mut() {
+            accounts.splice(0..0, implicit);
+        }
+        instructions.push(method);
+    }
+    log::debug!("🔍 旧版 Anchor state 段折叠为账户 {}", name);
+    Some(name)
+}
+
+/// 方法隐含的状态账户（地址见生成的 `state::address`）
+fn state_account() -> Value {
+    json!({"name": "state", "isMut": true, "isSigner": false})
+}
+
+/// 构造函数隐含的 Anchor `Ctor` 账户：付款人、待创建的状态账户、派生地址的程序签名者、系统程序与本程序
+fn ctor_accounts() -> Vec<Value> {
+    vec![
+        json!({"name": "from", "isMut": true, "isSigner": true}),
+        json!({"name": "to", "isMut": true, "isSigner": false}),
+        json!({"name": "base", "isMut": false, "isSigner": false}),
+        json!({"name": "systemProgram", "isMut": false, "isSigner": false, "address": "11111111111111111111111111111111"}),
+        json!({"name": "program", "isMut": false, "isSigner": false}),
+    ]
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn state_methods_become_instructions_with_implicit_accounts() {
+        let mut idl = json!({
+            "instructions": [],
+            "state": {
+                "struct": {"name": "Counter", "type": {"kind": "struct", "fields": [{"name": "count", "type": "u64"}]}},
+                "methods": [
+                    {"name": "new", "accounts": [], "args": []},
+                    {"name": "incrementBy", "accounts": [{"name": "authority", "isMut": false, "isSigner": true}], "args": []}
+                ]
+            }
+        });
+        assert_eq!(fold_state(&mut idl).as_deref(), Some("Counter"));
+        assert!(idl.get("state").is_none());
+        assert_eq!(idl["accounts"][0]["name"], "Counter");
+
+        let ctor = &idl["instructions"][0];
+        assert_eq!(ctor["discriminator"], json!(anchor_discriminator("state", "new")));
+        let names: Vec<&str> = ctor["accounts"].as_array().unwrap().iter().map(|a| a["name"].as_str().unwrap()).collect();
+        assert_eq!(names, ["from", "to", "base", "systemProgram", "program"]);
+
+        let method = &idl["instructions"][1];
+        assert_eq!(method["discriminator"], json!(anchor_discriminator("state", "increment_by")));
+        assert_eq!(method["accounts"][0]["name"], "state");
+        assert_eq!(method["accounts"][1]["name"], "authority");
+    }
+}
@@ -0,0 +1,316 @@
+//! 生成类型命名控制
+//!
+//! 通过 `--type-prefix` / `--type-suffix` 为生成的类型名添加前后缀，可按类别
+//! （accounts / types / events / instructions）单独指定，使多个生成的 crate
+//! 可以 glob 导入同一命名空间而不发生同名冲突。
+//!
+//! 重命名在 IR 上进行：定义与所有 `defined` 引用同步改名，模板与解析器无需感知。
+//! discriminator 在解析阶段已按原始名称确定，不受影响。
+
+use std::collections::HashMap;
+use std::str::FromStr;
+
+use convert_case::{Case, Casing};
+use regex::{Captures, Regex};
+
+use super::anchor_idl::{AnchorField, AnchorFieldType, AnchorTypeKind};
+use super::non_anchor_idl::{NonAnchorField, NonAnchorFieldType, NonAnchorTypeKind};
+use super::IdlFormatEnum;
+
+/// 可单独指定前后缀的类型类别
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
+pub enum NameCategory {
+    Accounts,
+    Types,
+    Events,
+    Instructions,
+}
+
+impl FromStr for NameCategory {
+    type Err = String;
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        match s {
+            "accounts" | "account" => Ok(NameCategory::Accounts),
+            "types" | "type" => Ok(NameCategory::Types),
+            "events" | "event" => Ok(NameCategory::Events),
+            "instructions" | "instruction" => Ok(NameCategory::Instructions),
+            other => Err(format!(
+                "未知的类型类别 '{}'，可选值: accounts, types, events, instructions",
+                other
+            )),
+        }
+    }
+}
+
+/// 单条前/后缀设置：`Value`（所有类别）或 `category=Value`
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct NameAffix {
+    pub category: Option<NameCategory>,
+    pub value: String,
+}
+
+impl FromStr for NameAffix {
+    type Err = String;
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        let (category, value) = match s.split_once('=') {
+            Some((category, value)) => (Some(category.trim().parse()?), value.trim()),
+            None => (None, s.trim()),
+        };
+        if !value.chars().all(|c| c.is_ascii_alphanumeric() || c == '_') {
+            return Err(format!("前后缀 '{}' 只能包含字母、数字和下划线", value));
+        }
+        Ok(NameAffix { category, value: value.to_string() })
+    }
+}
+
+/// 类型命名配置
+#[derive(Debug, Clone, Default)]
+pub struct NamingConfig {
+    prefixes: Vec<NameAffix>,
+    suffixes: Vec<NameAffix>,
+}
+
+impl NamingConfig {
+    /// 由命令行前后缀设置构建，按类别指定的设置优先于全局设置
+    pub fn new(prefixes: Vec<NameAffix>, suffixes: Vec<NameAffix>) -> Self {
+        Self { prefixes, suffixes }
+    }
+
+    /// 是否未设置任何前后缀
+    pub fn is_empty(&self) -> bool {
+        self.prefixes.iter().chain(&self.suffixes).all(|affix| affix.value.is_empty())
+    }
+
+    fn affix(affixes: &[NameAffix], category: NameCategory) -> &str {
+        affixes
+            .iter()
+            .rev()
+            .find(|affix| affix.category == Some(category))
+            .or_else(|| affixes.iter().rev().find(|affix| affix.category.is_none()))
+            .map(|affix| affix.value.as_str())
+            .unwrap_or("")
+    }
+
+    /// 计算某类别下的新名称，未设置前后缀时返回 None
+    pub fn rename(&self, category: NameCategory, name: &str) -> Option<String> {
+        let prefix = Self::affix(&self.prefixes, category);
+        let suffix = Self::affix(&self.suffixes, category);
+        if prefix.is_empty() && suffix.is_empty() {
+            return None;
+        }
+        Some(format!("{}{}{}", prefix, name.to_case(Case::Pascal), suffix))
+    }
+}
+
+/// 原始名称 -> 新名称，账户与事件优先于同名类型（Anchor 中二者是同一结构体）
+pub(super) struct RenameMap {
+    names: HashMap<String, String>,
+    ident: Regex,
+}
+
+impl RenameMap {
+    fn new(naming: &NamingConfig, entries: Vec<(NameCategory, &str)>) -> Self {
+        let mut names = HashMap::new();
+        for (category, name) in entries {
+            if let Some(renamed) = naming.rename(category, name) {
+                names.entry(name.to_string()).or_insert(renamed);
+            }
+        }
+        Self::from_names(names)
+    }
+
+    pub(super) fn from_names(names: HashMap<String, String>) -> Self {
+        Self { names, ident: Regex::new(r"[A-Za-z_][A-Za-z0-9_]*").expect("valid regex") }
+    }
+
+    fn name(&self, name: &mut String) {
+        if let Some(renamed) = self.names.get(name.as_str()) {
+            *name = renamed.clone();
+        }
+    }
+
+    /// 类型名字符串中可能内联 Rust 语法（`Vec<Fees>`），逐个标识符替换
+    fn type_str(&self, value: &mut String) {
+        let replaced = self.ident.replace_all(value, |caps: &Captures| {
+            self.names.get(&caps[0]).cloned().unwrap_or_else(|| caps[0].to_string())
+        });
+        *value = replaced.into_owned();
+    }
+
+    fn json(&self, value: &mut serde_json::Value) {
+        match value {
+            serde_json::Value::String(s) => self.type_str(s),
+            serde_json::Value::Array(values) => values.iter_mut().for_each(|v| self.json(v)),
+            serde_json::Value::Object(map) => map.values_mut().for_each(|v| self.json(v)),
+            _ => {}
+        }
+    }
+
+    fn anchor_type(&self, field_type: &mut AnchorFieldType) {
+        match field_type {
+            AnchorFieldType::Basic(name)
+            | AnchorFieldType::PrimitiveOrPubkey(name)
+            | AnchorFieldType::defined(name) => self.type_str(name),
+            AnchorFieldType::array(inner, _)
+            | AnchorFieldType::vec(inner)
+            | AnchorFieldType::option(inner)
+            | AnchorFieldType::coption(inner) => self.anchor_type(inner),
+            AnchorFieldType::Complex { params, .. } => {
+                params.iter_mut().flatten().for_each(|param| self.json(param))
+            }
+        }
+    }
+
+    fn anchor_fields(&self, fields: Option<&mut Vec<AnchorField>>) {
+        for field in fields.into_iter().flatten() {
+            self.anchor_type(&mut field.field_type);
+        }
+    }
+
+    fn non_anchor_type(&self, field_type: &mut NonAnchorFieldType) {
+        match field_type {
+            NonAnchorFieldType::Basic(name) | NonAnchorFieldType::Defined { defined: name } => {
+                self.type_str(name)
+            }
+            NonAnchorFieldType::Option { option: inner }
+            | NonAnchorFieldType::COption { coption: inner }
+            | NonAnchorFieldType::Vec { vec: inner }
+            | NonAnchorFieldType::Array { array: (inner, _) } => self.non_anchor_type(inner),
+            NonAnchorFieldType::HashMap { key, value } => {
+                self.non_anchor_type(key);
+                self.non_anchor_type(value);
+            }
+            NonAnchorFieldType::Complex { params, .. } => {
+                params.iter_mut().flatten().for_each(|param| self.json(param))
+            }
+        }
+    }
+
+    fn non_anchor_fields(&self, fields: Option<&mut Vec<NonAnchorField>>) {
+        for field in fields.into_iter().flatten() {
+            self.non_anchor_type(&mut field.field_type);
+        }
+    }
+}
+
+impl IdlFormatEnum {
+    /// 按命名配置重命名账户、类型、事件与指令，并同步更新所有类型引用
+    pub fn apply_naming(&mut self, naming: &NamingConfig) {
+        if naming.is_empty() {
+            return;
+        }
+        let map = RenameMap::new(naming, self.named_items());
+        self.rename_items(&map);
+    }
+
+    /// 各类别的条目名称，账户与事件在类型之前
+    pub(super) fn named_items(&self) -> Vec<(NameCategory, &str)> {
+        match self {
+            IdlFormatEnum::Anchor(idl) => idl.accounts.iter().flatten().map(|a| (NameCategory::Accounts, a.name.as_str()))
+                .chain(idl.events.iter().flatten().map(|e| (NameCategory::Events, e.name.as_str())))
+                .chain(idl.types.iter().flatten().map(|t| (NameCategory::Types, t.name.as_str())))
+                .chain(idl.instructions.iter().flatten().map(|i| (NameCategory::Instructions, i.name.as_str())))
+                .collect(),
+            IdlFormatEnum::NonAnchor(idl) => idl.accounts.iter().flatten().map(|a| (NameCategory::Accounts, a.name.as_str()))
+                .chain(idl.events.iter().flatten().map(|e| (NameCategory::Events, e.name.as_str())))
+                .chain(idl.types.iter().flatten().map(|t| (NameCategory::Types, t.name.as_str())))
+                .chain(idl.instructions.iter().flatten().map(|i| (NameCategory::Instructions, i.name.as_str())))
+                .collect(),
+        }
+    }
+
+    /// 按重命名表改名所有条目及其类型引用
+    pub(super) fn rename_items(&mut self, map: &RenameMap) {
+        match self {
+            IdlFormatEnum::Anchor(idl) => {
+                for account in idl.accounts.iter_mut().flatten() {
+                    map.name(&mut account.name);
+                    map.anchor_fields(account.fields.as_mut());
+                    for layout in account.variants.iter_mut().flat_map(|variants| variants.layouts.values_mut()) {
+                        map.name(layout);
+                    }
+                }
+                for event in idl.events.iter_mut().flatten() {
+                    map.name(&mut event.name);
+                    map.anchor_fields(event.fields.as_mut());
+                }
+                for type_def in idl.types.iter_mut().flatten() {
+                    map.name(&mut type_def.name);
+                    match type_def.kind.as_mut() {
+                        Some(AnchorTypeKind::Struct(fields)) => map.anchor_fields(Some(fields)),
+                        Some(AnchorTypeKind::Enum(variants)) => {
+                            for variant in variants {
+                                map.anchor_fields(variant.fields.as_mut());
+                            }
+                        }
+                        Some(AnchorTypeKind::Alias(field_type)) => map.anchor_type(field_type),
+                        None => {}
+                    }
+                }
+                for instruction in idl.instructions.iter_mut().flatten() {
+                    map.name(&mut instruction.name);
+                    map.anchor_fields(instruction.args.as_mut());
+                    for account in instruction.accounts.iter_mut().flatten() {
+                        for relation in account.relations.iter().flatten() {
+                            let mut state_type = account
+                                .relation_types
+                                .get(relation)
+                                .cloned()
+                                .unwrap_or_else(|| relation.to_case(Case::Pascal));
+                            map.name(&mut state_type);
+                            account.relation_types.insert(relation.clone(), state_type);
+                        }
+                    }
+                }
+            }
+            IdlFormatEnum::NonAnchor(idl) => {
+                for account in idl.accounts.iter_mut().flatten() {
+                    map.name(&mut account.name);
+                    map.non_anchor_fields(account.fields.as_mut());
+                    for layout in account.variants.iter_mut().flat_map(|variants| variants.layouts.values_mut()) {
+                        map.name(layout);
+                    }
+                }
+                for event in idl.events.iter_mut().flatten() {
+                    map.name(&mut event.name);
+                    map.non_anchor_fields(event.fields.as_mut());
+                }
+                for type_def in idl.types.iter_mut().flatten() {
+                    map.name(&mut type_def.name);
+                    match &mut type_def.type_def {
+                        NonAnchorTypeKind::Struct { fields } => map.non_anchor_fields(Some(fields)),
+                        NonAnchorTypeKind::Enum { variants } => {
+                            for variant in variants {
+                                map.non_anchor_fields(variant.fields.as_mut());
+                            }
+                        }
+                        NonAnchorTypeKind::Alias { value } => map.non_anchor_type(value),
+                    }
+                }
+                for instruction in idl.instructions.iter_mut().flatten() {
+                    map.name(&mut instruction.name);
+                    map.non_anchor_fields(instruction.args.as_mut());
+                }
+            }
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn category_affix_overrides_global() {
+        let naming = NamingConfig::new(
+            vec!["Ray".parse().unwrap(), "events=Evt".parse().unwrap()],
+            vec!["Interface".parse().unwrap()],
+        );
+        assert_eq!(naming.rename(NameCategory::Accounts, "ammInfo").as_deref(), Some("RayAmmInfoInterface"));
+        assert_eq!(naming.rename(NameCategory::Events, "Deposit").as_deref(), Some("EvtDepositInterface"));
+        assert!(NamingConfig::default().rename(NameCategory::Types, "Fees").is_none());
+        assert!("pools=Ray".parse::<NameAffix>().is_err());
+    }
+}
@@ -6,6 +6,10 @@
 use serde::{Deserialize, Serialize, Deserializer};
 use serde::de::Error;
 
+use super::arg_encoding::ArgEncoding;
+use super::account_variants::AccountVariants;
+use super::c_layout::CLayout;
+
 /// 非Anchor合约的统一IDL格式
 #[derive(Debug, Clone, Serialize, Deserialize)]
 pub struct NonAnchorIdl {
@@ -56,24 +60,27 @@ pub struct NonAnchorMetadata {
     pub spec: String,
     /// 描述
     pub description: Option<String>,
+    /// 各集群的部署地址（cluster -> address）
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub addresses: Option<std::collections::BTreeMap<String, String>>,
+    /// 引用的其他程序接口 crate
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub dependencies: Option<Vec<super::dependencies::IdlDependency>>,
 }
 
 /// 序列化格式枚举
 #[derive(Debug, Clone, Serialize, Deserialize)]
+#[derive(Default)]
 pub enum SerializationFormat {
     /// Bincode序列化
     Bincode,
     /// Borsh序列化
+    #[default]
     Borsh,
     /// 自定义序列化格式
     Custom(String),
 }
 
-impl Default for SerializationFormat {
-    fn default() -> Self {
-        Self::Borsh // NonAnchor合约常用Borsh序列化
-    }
-}
 
 /// NonAnchor默认discriminator大小：1字节
 fn default_discriminator_size() -> u8 {
@@ -101,13 +108,15 @@ struct RawNonAnchorAccount {
     /// 账户名称
     pub name: String,
     /// 是否可变 - 支持writable, is_write, is_mut等多种命名，默认false
-    #[serde(alias = "writable", alias = "is_write", default)]
+    #[serde(alias = "writable", alias = "is_write", alias = "isMut", default)]
     pub is_mut: bool,
     /// 是否签名者 - 映射到标准字段名，默认false
-    #[serde(alias = "signer", default)]
+    #[serde(alias = "signer", alias = "isSigner", default)]
     pub is_signer: bool,
     /// 账户discriminator
     pub discriminator: Option<Vec<u8>>,
+    /// 固定地址（如系统程序）
+    pub address: Option<String>,
     /// 文档注释
     pub docs: Option<Vec<String>>,
     
@@ -125,17 +134,29 @@ pub struct NonAnchorAccount {
     /// 账户名称
     pub name: String,
     /// 是否可变 - 支持writable, is_write, is_mut等多种命名，默认false
-    #[serde(alias = "writable", alias = "is_write", default)]
+    #[serde(alias = "writable", alias = "is_write", alias = "isMut", default)]
     pub is_mut: bool,
     /// 是否签名者 - 映射到标准字段名，默认false
-    #[serde(alias = "signer", default)]
+    #[serde(alias = "signer", alias = "isSigner", default)]
     pub is_signer: bool,
     /// 账户discriminator
     pub discriminator: Option<Vec<u8>>,
+    /// 固定地址（如系统程序）
+    #[serde(default)]
+    pub address: Option<String>,
     /// 账户字段
     pub fields: Option<Vec<NonAnchorField>>,
     /// 文档注释
     pub docs: Option<Vec<String>>,
+    /// 零拷贝账户（通过 `--zero-copy` 指定）
+    #[serde(skip)]
+    pub zero_copy: bool,
+    /// C 布局声明（IDL 中 `"layout": "c"` 或 `--layout-file`）
+    #[serde(skip)]
+    pub c_layout: Option<CLayout>,
+    /// 按版本字段区分的多种布局（IDL 中 `variants` 或 `--account-variants`）
+    #[serde(skip)]
+    pub variants: Option<AccountVariants>,
 }
 
 /// 实现从RawNonAnchorAccount到NonAnchorAccount的智能转换
@@ -159,8 +180,12 @@ impl From<RawNonAnchorAccount> for NonAnchorAccount {
             is_mut: raw.is_mut,
             is_signer: raw.is_signer,
             discriminator: raw.discriminator,
+            address: raw.address,
             fields,
             docs: raw.docs,
+            zero_copy: false,
+            c_layout: None,
+            variants: None,
         }
     }
 }
@@ -176,6 +201,9 @@ pub struct NonAnchorType {
     pub type_def: NonAnchorTypeKind,
     /// 文档注释
     pub docs: Option<Vec<String>>,
+    /// C 布局声明（IDL 中 `"layout": "c"` 或 `--layout-file`）
+    #[serde(skip)]
+    pub c_layout: Option<CLayout>,
 }
 
 /// 默认类型名称
@@ -190,6 +218,7 @@ pub enum NonAnchorTypeKind {
     /// 结构体
     #[serde(rename = "struct")]
     Struct {
+        #[serde(default, deserialize_with = "deserialize_struct_fields")]
         fields: Vec<NonAnchorField>,
     },
     /// 枚举
@@ -214,9 +243,42 @@ pub struct NonAnchorEnumVariant {
     pub fields: Option<Vec<NonAnchorField>>,
     /// 文档注释
     pub docs: Option<Vec<String>>,
+    /// 显式判别值（IDL `value` / `discriminant`），缺省时为上一变体 + 1
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub value: Option<u64>,
 }
 
 /// 默认变体名称
+/// 结构体字段：命名字段为对象，元组结构体字段只有类型，按位置命名 "0"、"1"…
+fn deserialize_struct_fields<'de, D>(deserializer: D) -> Result<Vec<NonAnchorField>, D::Error>
+where
+    D: Deserializer<'de>,
+{
+    let values = Vec::<serde_json::Value>::deserialize(deserializer)?;
+    values
+        .into_iter()
+        .enumerate()
+        .map(|(index, value)| {
+            if !is_tuple_field(&value) {
+                serde_json::from_value(value).map_err(serde::de::Error::custom)
+            } else {
+                Ok(NonAnchorField {
+                    name: index.to_string(),
+                    field_type: serde_json::from_value(value).map_err(serde::de::Error::custom)?,
+                    docs: None,
+                    encoding: None,
+                    substitute: None,
+                })
+            }
+        })
+        .collect()
+}
+
+/// 元组结构体字段直接是类型（`"u64"`、`{"defined": ...}`），命名字段是带 name/type 的对象
+fn is_tuple_field(value: &serde_json::Value) -> bool {
+    !matches!(value, serde_json::Value::Object(obj) if obj.contains_key("name") || obj.contains_key("type"))
+}
+
 fn default_variant_name() -> String {
     "UnnamedVariant".to_string()
 }
@@ -237,6 +299,12 @@ pub struct NonAnchorField {
     pub field_type: NonAnchorFieldType,
     /// 文档注释
     pub docs: Option<Vec<String>>,
+    /// 参数编码方式（仅指令参数使用）
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub encoding: Option<ArgEncoding>,
+    /// 替换生成的 Rust 类型（`--field-type`），编码仍按 IDL 类型
+    #[serde(skip)]
+    pub substitute: Option<String>,
 }
 
 /// 非Anchor字段类型
@@ -248,6 +316,10 @@ pub enum NonAnchorFieldType {
     Option {
         option: Box<NonAnchorFieldType>,
     },
+    /// SPL风格的COption：{"coption": "内部类型"}，4字节标签 + 定长载荷
+    COption {
+        coption: Box<NonAnchorFieldType>,
+    },
     /// 向量类型：{"vec": "内部类型"}
     Vec {
         vec: Box<NonAnchorFieldType>,
@@ -349,6 +421,8 @@ impl NonAnchorIdl {
                 version: "0.1.0".to_string(),
                 spec: "non-anchor".to_string(),
                 description: None,
+                addresses: None,
+                dependencies: None,
             }),
             instructions: None,
             accounts: None,
@@ -374,6 +448,16 @@ impl NonAnchorIdl {
         self.instructions.as_deref().unwrap_or(&[])
     }
 
+    /// 程序只有一条指令且未声明 discriminator 时返回该指令
+    ///
+    /// 这类单指令程序的指令数据就是参数本身，生成时不添加索引 discriminator。
+    pub fn undiscriminated_instruction(&self) -> Option<&NonAnchorInstruction> {
+        match self.instructions() {
+            [instruction] if instruction.discriminator.as_ref().is_none_or(|disc| disc.is_empty()) => Some(instruction),
+            _ => None,
+        }
+    }
+
     /// 获取程序名称（智能获取：优先使用根级别name，fallback到metadata.name）
     pub fn program_name(&self) -> &str {
         // 优先使用根级别的name
@@ -512,6 +596,15 @@ impl NonAnchorFieldType {
                     });
                 }
                 
+                // 检查 "coption" 类型 - {"coption": "内部类型"}
+                if let Some(coption_value) = map.get("coption") {
+                    let inner = Self::parse_value(coption_value.clone())?;
+                    log::trace!("✅ NonAnchorFieldType: COption({:?})", inner);
+                    return Ok(NonAnchorFieldType::COption {
+                        coption: Box::new(inner),
+                    });
+                }
+                
                 // 检查 "vec" 类型 - {"vec": "内部类型"}
                 if let Some(vec_value) = map.get("vec") {
                     log::trace!("🔍 Found vec type, processing...");
@@ -537,7 +630,7 @@ impl NonAnchorFieldType {
                             let size = if let serde_json::Value::Number(n) = &arr[1] {
                                 n.as_u64().unwrap_or(0) as usize
                             } else {
-                                return Err("Array size must be a number".to_string());
+                                return Err(format!("Array size must be a number or an IDL constant, got {}", arr[1]));
                             };
                             log::trace!("✅ NonAnchorFieldType: Array({:?}, {})", inner, size);
                             return Ok(NonAnchorFieldType::Array {
@@ -573,16 +666,20 @@ impl NonAnchorFieldType {
                     }
                 }
                 
-                // 检查 "defined" 类型 - {"defined": "类型名"}
+                // 检查 "defined" 类型 - {"defined": "类型名"} 或 {"defined": {"name": "类型名"}}
                 if let Some(defined_value) = map.get("defined") {
-                    if let serde_json::Value::String(type_name) = defined_value {
-                        log::trace!("✅ NonAnchorFieldType: Defined({})", type_name);
-                        return Ok(NonAnchorFieldType::Defined {
-                            defined: type_name.clone(),
-                        });
-                    } else {
-                        return Err("Defined type name must be a string".to_string());
-                    }
+                    let type_name = match defined_value {
+                        serde_json::Value::String(type_name) => type_name,
+                        serde_json::Value::Object(defined_obj) => match defined_obj.get("name") {
+                            Some(serde_json::Value::String(type_name)) => type_name,
+                            _ => return Err("Defined type object must have a string name".to_string()),
+                        },
+                        _ => return Err("Defined type name must be a string".to_string()),
+                    };
+                    log::trace!("✅ NonAnchorFieldType: Defined({})", type_name);
+                    return Ok(NonAnchorFieldType::Defined {
+                        defined: type_name.clone(),
+                    });
                 }
                 
                 // 检查复合类型 - {"kind": "类型", "params": [...]}
@@ -727,7 +824,10 @@ impl NonAnchorIdl {
         log::debug!("🔧 使用手动解析器处理NonAnchor IDL");
         
         // 首先解析为通用JSON值
-        let json_value: serde_json::Value = serde_json::from_str(json_str)?;
+        let mut json_value: serde_json::Value = serde_json::from_str(json_str)?;
+        // 以常量或表达式给出的数组长度先求值为整数
+        super::array_sizes::resolve_array_sizes(&mut json_value).map_err(serde_json::Error::custom)?;
+        super::docs::normalize_docs(&mut json_value);
         
         let obj = json_value.as_object().ok_or_else(|| {
             serde_json::Error::custom("IDL must be a JSON object")
@@ -813,10 +913,15 @@ impl NonAnchorIdl {
         log::debug!("✅ accounts解析完成，结果: {}", 
             accounts.as_ref().map_or(0, |v| v.len()));
         
-        // 转换accounts格式
+        // 转换accounts格式，C 布局与布局变体声明从原始JSON读取
+        let raw_account_values = obj.get("accounts").and_then(|v| v.as_array()).map(Vec::as_slice).unwrap_or_default();
         let accounts = accounts.map(|raw_accounts| {
             log::debug!("🔄 正在转换 {} 个RawNonAnchorAccount为NonAnchorAccount", raw_accounts.len());
-            raw_accounts.into_iter().map(|raw| NonAnchorAccount::from(raw)).collect()
+            raw_accounts.into_iter().zip(raw_account_values).map(|(raw, value)| NonAnchorAccount {
+                c_layout: CLayout::from_idl_item(value),
+                variants: AccountVariants::from_idl_item(value),
+                ..NonAnchorAccount::from(raw)
+            }).collect()
         });
             
         // 解析types字段 - 使用手动解析避免递归类型冲突
@@ -863,7 +968,7 @@ impl NonAnchorIdl {
         
         log::debug!("✅ 手动解析完成 - name: {:?}, version: {:?}", name, version);
         
-        Ok(NonAnchorIdl {
+        let mut idl = NonAnchorIdl {
             name,
             version,
             address,
@@ -876,7 +981,26 @@ impl NonAnchorIdl {
             discriminator_size: 1, // 默认1字节
             serialization_format: SerializationFormat::Borsh, // 默认Borsh
             // field_allocation_cache: std::sync::OnceLock::new(),
-        })
+        };
+        idl.resolve_account_layouts();
+        Ok(idl)
+    }
+
+    /// 统一账户布局：仅有名称的账户从同名struct类型获取字段与C布局声明，与内联 `type` 的账户一致
+    fn resolve_account_layouts(&mut self) {
+        let types = self.types.as_deref().unwrap_or_default();
+        for account in self.accounts.iter_mut().flatten() {
+            if account.fields.is_none() {
+                let type_def = types.iter().find(|type_def| type_def.name == account.name);
+                account.fields = type_def.and_then(|type_def| match &type_def.type_def {
+                    NonAnchorTypeKind::Struct { fields } => Some(fields.clone()),
+                    _ => None,
+                });
+                if account.c_layout.is_none() {
+                    account.c_layout = type_def.and_then(|type_def| type_def.c_layout.clone());
+                }
+            }
+        }
     }
 
     /// 手动解析types数组，避免serde递归类型冲突
@@ -924,6 +1048,7 @@ impl NonAnchorIdl {
                 name,
                 type_def: type_kind,
                 docs,
+                c_layout: CLayout::from_idl_item(type_value),
             });
             
             log::debug!("✅ 第{}个类型解析完成: {}", index + 1, parsed_types.last().unwrap().name);
@@ -947,12 +1072,11 @@ impl NonAnchorIdl {
             
         match kind {
             "struct" => {
-                let fields_value = type_obj.get("fields")
-                    .ok_or_else(|| {
-                        serde_json::Error::custom("struct类型缺少fields字段")
-                    })?;
-                    
-                let fields = Self::parse_fields_manually(fields_value)?;
+                // 缺少fields视为单元结构体
+                let fields = match type_obj.get("fields") {
+                    Some(fields_value) => Self::parse_fields_manually(fields_value)?,
+                    None => Vec::new(),
+                };
                 Ok(NonAnchorTypeKind::Struct { fields })
             }
             "enum" => {
@@ -987,6 +1111,16 @@ impl NonAnchorIdl {
         let mut parsed_fields = Vec::new();
         
         for (index, field_value) in fields_array.iter().enumerate() {
+            if is_tuple_field(field_value) {
+                parsed_fields.push(NonAnchorField {
+                    name: index.to_string(),
+                    field_type: Self::parse_field_type_manually(field_value)?,
+                    docs: None,
+                    encoding: None,
+                    substitute: None,
+                });
+                continue;
+            }
             let field_obj = field_value.as_object().ok_or_else(|| {
                 serde_json::Error::custom(format!("fields[{}]必须是对象", index))
             })?;
@@ -1016,6 +1150,8 @@ impl NonAnchorIdl {
                 name,
                 field_type,
                 docs,
+                encoding: None,
+                substitute: None,
             });
         }
         
@@ -1064,6 +1200,8 @@ impl NonAnchorIdl {
                                                         defined: defined.to_string() 
                                                     },
                                                     docs: None,
+                                                    encoding: None,
+                                                    substitute: None,
                                                 });
                                             }
                                         }
@@ -1099,6 +1237,7 @@ impl NonAnchorIdl {
                 name,
                 fields,
                 docs,
+                value: super::enum_tags::explicit_value(variant_obj),
             });
         }
         
@@ -1107,49 +1246,7 @@ impl NonAnchorIdl {
     
     /// 手动解析字段类型 - 处理字符串或复杂类型
     fn parse_field_type_manually(type_value: &serde_json::Value) -> Result<NonAnchorFieldType, serde_json::Error> {
-        match type_value {
-            serde_json::Value::String(s) => {
-                // 简单字符串类型如 "u64", "publicKey"
-                Ok(NonAnchorFieldType::Basic(s.clone()))
-            }
-            serde_json::Value::Object(obj) => {
-                // 复杂类型如 {"defined": "OrderPacket"}, {"array": ["u64", 32]}
-                if let Some(defined) = obj.get("defined").and_then(|v| v.as_str()) {
-                    Ok(NonAnchorFieldType::Defined { 
-                        defined: defined.to_string() 
-                    })
-                } else if let Some(option) = obj.get("option") {
-                    let inner = Self::parse_field_type_manually(option)?;
-                    Ok(NonAnchorFieldType::Option {
-                        option: Box::new(inner)
-                    })
-                } else if let Some(vec) = obj.get("vec") {
-                    let inner = Self::parse_field_type_manually(vec)?;
-                    Ok(NonAnchorFieldType::Vec {
-                        vec: Box::new(inner)
-                    })
-                } else if let Some(array) = obj.get("array") {
-                    let array_value = array.as_array().ok_or_else(|| {
-                        serde_json::Error::custom("array类型必须是数组")
-                    })?;
-                    
-                    if array_value.len() != 2 {
-                        return Err(serde_json::Error::custom("array类型必须有2个元素[type, size]"));
-                    }
-                    
-                    let inner_type = Self::parse_field_type_manually(&array_value[0])?;
-                    let size = array_value[1].as_u64().ok_or_else(|| {
-                        serde_json::Error::custom("array size必须是数字")
-                    })? as usize;
-                    
-                    Ok(NonAnchorFieldType::Array { 
-                        array: (Box::new(inner_type), size) 
-                    })
-                } else {
-                    Err(serde_json::Error::custom("无法识别的复杂类型"))
-                }
-            }
-            _ => Err(serde_json::Error::custom("type字段必须是字符串或对象"))
-        }
+        // 与反序列化共用同一解析逻辑，避免两处支持的类型不一致
+        NonAnchorFieldType::parse_value(type_value.clone()).map_err(serde_json::Error::custom)
     }
 }
\ No newline at end of file
@@ -0,0 +1,219 @@
+//! 多集群程序地址
+//!
+//! 同一程序在 mainnet / devnet 等集群部署在不同地址时，可在 IDL 的
+//! `metadata.addresses`（`{"devnet": "..."}`）中声明，或通过
+//! `--program-id mainnet=...,devnet=...` 指定，CLI 优先。
+//!
+//! 地址表记录在 IR 的 metadata 上；`mainnet` 为默认集群，缺省时取 IDL 地址。
+//! 生成的 crate 包含 `program_ids` 模块与 `Cluster` 枚举，其余集群各对应一个
+//! 同名 feature，用于切换 `ID`。
+
+use std::collections::BTreeMap;
+
+use convert_case::{Case, Casing};
+use minijinja::{context, Value};
+
+use super::non_anchor_idl::NonAnchorMetadata;
+use super::IdlFormatEnum;
+use crate::error::SoloresError;
+use crate::utils::is_valid_pubkey;
+
+/// 默认集群，对应不启用任何集群 feature 时的 `ID`
+pub const DEFAULT_CLUSTER: &str = "mainnet";
+
+/// 生成的 Cargo.toml 已使用的 feature 名，不能作为集群名
+const RESERVED_FEATURES: [&str; 15] = [
+    "account-info",
+    "actions",
+    "anchor-cpi",
+    "arbitrary",
+    "chrono",
+    "cpi",
+    "decimal",
+    "default",
+    "full-solana",
+    "layout-checks",
+    "program-entrypoint",
+    "proposal",
+    "serde",
+    "sqlx-postgres",
+    "versioned-tx",
+];
+
+impl IdlFormatEnum {
+    /// 合并 `--program-id`（单个地址或 `cluster=address` 列表）与 IDL 中的集群地址表
+    pub fn apply_program_ids(&mut self, program_id: Option<&str>) -> Result<(), SoloresError> {
+        let mut addresses = self.cluster_addresses().clone();
+        let mut default_address = None;
+
+        match program_id.map(str::trim).filter(|value| !value.is_empty()) {
+            Some(value) if !value.contains('=') => default_address = Some(validate_address("--program-id", value)?),
+            Some(value) => {
+                for entry in value.split(',').map(str::trim).filter(|entry| !entry.is_empty()) {
+                    let (cluster, address) = entry.split_once('=').ok_or_else(|| {
+                        validation_error(entry, "格式应为 cluster=address".to_string(), None)
+                    })?;
+                    let cluster = validate_cluster(cluster.trim())?;
+                    addresses.insert(cluster, validate_address(entry, address.trim())?);
+                }
+            }
+            None => {}
+        }
+
+        for (cluster, address) in &addresses {
+            validate_cluster(cluster)?;
+            validate_address(&format!("metadata.addresses.{}", cluster), address)?;
+        }
+
+        let address = default_address
+            .or_else(|| addresses.get(DEFAULT_CLUSTER).cloned())
+            .unwrap_or_else(|| self.address().to_string());
+        if !addresses.is_empty() {
+            addresses.insert(DEFAULT_CLUSTER.to_string(), address.clone());
+        }
+        self.set_program_ids(address, addresses);
+        Ok(())
+    }
+
+    /// 集群地址表，默认集群在首位；未声明多集群时为空
+    pub fn program_ids(&self) -> Vec<(String, String)> {
+        let addresses = self.cluster_addresses();
+        let default = addresses.get_key_value(DEFAULT_CLUSTER);
+        default
+            .into_iter()
+            .chain(addresses.iter().filter(|(cluster, _)| cluster.as_str() != DEFAULT_CLUSTER))
+            .map(|(cluster, address)| (cluster.clone(), address.clone()))
+            .collect()
+    }
+
+    fn address(&self) -> &str {
+        match self {
+            IdlFormatEnum::Anchor(idl) => &idl.address,
+            IdlFormatEnum::NonAnchor(idl) => &idl.address,
+        }
+    }
+
+    fn cluster_addresses(&self) -> &BTreeMap<String, String> {
+        static EMPTY: BTreeMap<String, String> = BTreeMap::new();
+        let addresses = match self {
+            IdlFormatEnum::Anchor(idl) => idl.metadata.addresses.as_ref(),
+            IdlFormatEnum::NonAnchor(idl) => idl.metadata.as_ref().and_then(|m| m.addresses.as_ref()),
+        };
+        addresses.unwrap_or(&EMPTY)
+    }
+
+    fn set_program_ids(&mut self, address: String, addresses: BTreeMap<String, String>) {
+        let addresses = (!addresses.is_empty()).then_some(addresses);
+        match self {
+            IdlFormatEnum::Anchor(idl) => {
+                if idl.metadata.address.is_some() {
+                    idl.metadata.address = Some(address.clone());
+                }
+                idl.metadata.addresses = addresses;
+                idl.address = address;
+            }
+            IdlFormatEnum::NonAnchor(idl) => {
+                let metadata = idl.metadata.get_or_insert_with(|| NonAnchorMetadata {
+                    address: None,
+                    name: idl.name.clone().unwrap_or_default(),
+                    version: idl.version.clone().unwrap_or_default(),
+                    spec: "non-anchor".to_string(),
+                    description: None,
+                    addresses: None,
+                    dependencies: None,
+                });
+                if metadata.address.is_some() {
+                    metadata.address = Some(address.clone());
+                }
+                metadata.addresses = addresses;
+                idl.address = address;
+            }
+        }
+    }
+}
+
+/// 模板使用的集群列表：名称、常量名、枚举变体、feature 以及选择 `ID` 的 cfg 条件
+pub fn cluster_values(program_ids: &[(String, String)]) -> Vec<Value> {
+    let features: Vec<String> = program_ids
+        .iter()
+        .filter(|(cluster, _)| cluster != DEFAULT_CLUSTER)
+        .map(|(cluster, _)| format!("feature = \"{}\"", cluster))
+        .collect();
+
+    program_ids
+        .iter()
+        .map(|(cluster, address)| {
+            let is_default = cluster == DEFAULT_CLUSTER;
+            // 多个集群 feature 同时启用时，按声明顺序取第一个
+            let cfg = match features.iter().position(|f| *f == format!("feature = \"{}\"", cluster)) {
+                None => format!("not(any({}))", features.join(", ")),
+                Some(0) => features[0].clone(),
+                Some(index) => format!("all({}, not(any({})))", features[index], features[..index].join(", ")),
+            };
+            context! {
+                name => cluster,
+                const_name => cluster.to_case(Case::UpperSnake),
+                variant => cluster.to_case(Case::Pascal),
+                address => address,
+                is_default => is_default,
+                cfg => cfg,
+            }
+        })
+        .collect()
+}
+
+fn validate_cluster(cluster: &str) -> Result<String, SoloresError> {
+    let valid = cluster.starts_with(|c: char| c.is_ascii_lowercase())
+        && cluster.chars().all(|c| c.is_ascii_lowercase() || c.is_ascii_digit() || c == '-' || c == '_');
+    if !valid || RESERVED_FEATURES.contains(&cluster) {
+        return Err(validation_error(
+            cluster,
+            "集群名无效：需为小写字母开头的 feature 名，且不能与生成 crate 已有的 feature 重名".to_string(),
+            Some(cluster.to_string()),
+        ));
+    }
+    Ok(cluster.to_string())
+}
+
+fn validate_address(field_path: &str, address: &str) -> Result<String, SoloresError> {
+    if !is_valid_pubkey(address) {
+        return Err(validation_error(field_path, "程序地址不是有效的 base58 公钥".to_string(), Some(address.to_string())));
+    }
+    Ok(address.to_string())
+}
+
+fn validation_error(field_path: &str, message: String, actual: Option<String>) -> SoloresError {
+    SoloresError::ValidationError {
+        message,
+        field_path: Some(format!("--program-id {}", field_path)),
+        expected: None,
+        actual,
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::idl_format::parse_idl_json;
+
+    const TOKEN: &str = "TokenkegQfeZyiNwAJbNbGKPFXCWuBvf9Ss623VQ5DA";
+    const SYSTEM: &str = "11111111111111111111111111111111";
+
+    #[test]
+    fn merges_cli_clusters_with_idl_address() {
+        let mut idl = parse_idl_json(&format!(
+            r#"{{"address": "{}", "metadata": {{"name": "demo", "version": "0.1.0", "spec": "0.1.0"}}, "instructions": []}}"#,
+            TOKEN
+        ))
+        .unwrap();
+        idl.apply_program_ids(Some(&format!("devnet={}", SYSTEM))).unwrap();
+        let ids = idl.program_ids();
+        assert_eq!(ids, [("mainnet".to_string(), TOKEN.to_string()), ("devnet".to_string(), SYSTEM.to_string())]);
+
+        let cfgs: Vec<String> = cluster_values(&ids).iter().map(|v| v.get_attr("cfg").unwrap().to_string()).collect();
+        assert_eq!(cfgs, ["not(any(feature = \"devnet\"))", "feature = \"devnet\""]);
+
+        assert!(idl.apply_program_ids(Some("serde=11111111111111111111111111111111")).is_err());
+        assert!(idl.apply_program_ids(Some("devnet=not-a-key")).is_err());
+    }
+}
@@ -0,0 +1,255 @@
+//! SmallVec 字段位置校验
+//!
+//! `SmallVec<L, T>`（L 为 u8 / u16 / u32 长度前缀）生成为 `Vec<T>`，Borsh 经由 `crate::small_vec`
+//! 的字段级编解码函数按 L 读写长度。与 COption 一样，字段级属性只作用于账户、类型、事件结构体的
+//! 字段与指令参数本身；嵌套在其他类型中、位于枚举变体字段或类型别名中的 SmallVec 会退化为
+//! 4 字节长度前缀的 Vec，长度前缀为其他类型的 SmallVec 无法编解码，均在生成前报错。
+
+use super::anchor_idl::{AnchorFieldType, AnchorTypeKind};
+use super::coption::{non_anchor_owned_fields, owned_fields};
+use super::non_anchor_idl::{NonAnchorFieldType, NonAnchorTypeKind};
+use super::IdlFormatEnum;
+use crate::error::SoloresError;
+use crate::minijinja::type_mapper::{small_vec_len_type, split_generic};
+
+impl IdlFormatEnum {
+    /// 拒绝字段级编解码覆盖不到的 SmallVec 位置与长度前缀
+    pub fn validate_small_vec_positions(&self) -> Result<(), SoloresError> {
+        match self {
+            IdlFormatEnum::Anchor(idl) => {
+                let top_level = idl
+                    .accounts
+                    .iter()
+                    .flatten()
+                    .flat_map(|account| owned_fields(&account.name, account.fields.as_deref()))
+                    .chain(idl.events.iter().flatten().flat_map(|event| owned_fields(&event.name, event.fields.as_deref())))
+                    .chain(idl.instructions.iter().flatten().flat_map(|ix| owned_fields(&ix.name, ix.args.as_deref())));
+                for (path, field_type) in top_level {
+                    check_anchor_field(&path, field_type, true)?;
+                }
+                for type_def in idl.types.iter().flatten() {
+                    match &type_def.kind {
+                        Some(AnchorTypeKind::Struct(fields)) => {
+                            for (path, field_type) in owned_fields(&type_def.name, Some(fields)) {
+                                check_anchor_field(&path, field_type, true)?;
+                            }
+                        }
+                        Some(AnchorTypeKind::Enum(variants)) => {
+                            for variant in variants {
+                                let owner = format!("{}::{}", type_def.name, variant.name);
+                                for (path, field_type) in owned_fields(&owner, variant.fields.as_deref()) {
+                                    check_anchor_field(&path, field_type, false)?;
+                                }
+                            }
+                        }
+                        Some(AnchorTypeKind::Alias(field_type)) => check_anchor_field(&type_def.name, field_type, false)?,
+                        None => {}
+                    }
+                }
+            }
+            IdlFormatEnum::NonAnchor(idl) => {
+                let top_level = idl
+                    .accounts
+                    .iter()
+                    .flatten()
+                    .flat_map(|account| non_anchor_owned_fields(&account.name, account.fields.as_deref()))
+                    .chain(idl.events.iter().flatten().flat_map(|event| non_anchor_owned_fields(&event.name, event.fields.as_deref())))
+                    .chain(idl.instructions().iter().flat_map(|ix| non_anchor_owned_fields(&ix.name, ix.args.as_deref())));
+                for (path, field_type) in top_level {
+                    check_non_anchor_field(&path, field_type, true)?;
+                }
+                for type_def in idl.types.iter().flatten() {
+                    match &type_def.type_def {
+                        NonAnchorTypeKind::Struct { fields } => {
+                            for (path, field_type) in non_anchor_owned_fields(&type_def.name, Some(fields)) {
+                                check_non_anchor_field(&path, field_type, true)?;
+                            }
+                        }
+                        NonAnchorTypeKind::Enum { variants } => {
+                            for variant in variants {
+                                let owner = format!("{}::{}", type_def.name, variant.name);
+                                for (path, field_type) in non_anchor_owned_fields(&owner, variant.fields.as_deref()) {
+                                    check_non_anchor_field(&path, field_type, false)?;
+                                }
+                            }
+                        }
+                        NonAnchorTypeKind::Alias { value } => check_non_anchor_field(&type_def.name, value, false)?,
+                    }
+                }
+            }
+        }
+        Ok(())
+    }
+}
+
+/// `top_level` 为 true 时允许字段类型本身是 SmallVec，其元素类型中仍不能再出现
+fn check_anchor_field(path: &str, field_type: &AnchorFieldType, top_level: bool) -> Result<(), SoloresError> {
+    match field_type {
+        AnchorFieldType::defined(name) if top_level => check_defined(path, name),
+        other if anchor_contains_small_vec(other) => Err(position_error(path, top_level)),
+        _ => Ok(()),
+    }
+}
+
+fn check_non_anchor_field(path: &str, field_type: &NonAnchorFieldType, top_level: bool) -> Result<(), SoloresError> {
+    match field_type {
+        NonAnchorFieldType::Defined { defined } if top_level => check_defined(path, defined),
+        other if non_anchor_contains_small_vec(other) => Err(position_error(path, top_level)),
+        _ => Ok(()),
+    }
+}
+
+/// 字段类型本身为 `{"defined": "SmallVec<L, T>"}` 时检查长度前缀与元素类型
+fn check_defined(path: &str, name: &str) -> Result<(), SoloresError> {
+    if !name.contains("SmallVec<") {
+        return Ok(());
+    }
+    let element = split_generic(name.trim()).and_then(|(_, args)| args.get(1).copied());
+    match (small_vec_len_type(name), element) {
+        (Some(_), Some(element)) if !element.contains("SmallVec<") => Ok(()),
+        (Some(_), _) => Err(position_error(path, true)),
+        (None, _) => Err(SoloresError::ValidationError {
+            message: format!("{} 的 SmallVec 长度前缀无法识别：{}", path, name),
+            field_path: Some(path.to_string()),
+            expected: Some("SmallVec<u8, T>、SmallVec<u16, T> 或 SmallVec<u32, T>".to_string()),
+            actual: Some(name.to_string()),
+        }),
+    }
+}
+
+fn anchor_contains_small_vec(field_type: &AnchorFieldType) -> bool {
+    match field_type {
+        AnchorFieldType::option(inner)
+        | AnchorFieldType::coption(inner)
+        | AnchorFieldType::vec(inner)
+        | AnchorFieldType::array(inner, _) => anchor_contains_small_vec(inner),
+        AnchorFieldType::defined(name) => name.contains("SmallVec<"),
+        AnchorFieldType::Complex { params, .. } => params.iter().flatten().any(json_contains_small_vec),
+        AnchorFieldType::Basic(_) | AnchorFieldType::PrimitiveOrPubkey(_) => false,
+    }
+}
+
+fn non_anchor_contains_small_vec(field_type: &NonAnchorFieldType) -> bool {
+    match field_type {
+        NonAnchorFieldType::Option { option: inner }
+        | NonAnchorFieldType::COption { coption: inner }
+        | NonAnchorFieldType::Vec { vec: inner }
+        | NonAnchorFieldType::Array { array: (inner, _) } => non_anchor_contains_small_vec(inner),
+        NonAnchorFieldType::HashMap { key, value } => non_anchor_contains_small_vec(key) || non_anchor_contains_small_vec(value),
+        NonAnchorFieldType::Defined { defined } => defined.contains("SmallVec<"),
+        NonAnchorFieldType::Complex { params, .. } => params.iter().flatten().any(json_contains_small_vec),
+        NonAnchorFieldType::Basic(_) => false,
+    }
+}
+
+/// 泛型参数等原样保存的 JSON 类型中是否出现 SmallVec
+fn json_contains_small_vec(value: &serde_json::Value) -> bool {
+    match value {
+        serde_json::Value::Object(map) => map.values().any(json_contains_small_vec),
+        serde_json::Value::Array(items) => items.iter().any(json_contains_small_vec),
+        serde_json::Value::String(name) => name.contains("SmallVec<"),
+        _ => false,
+    }
+}
+
+fn position_error(path: &str, top_level: bool) -> SoloresError {
+    let position = if top_level { "嵌套在其他类型中" } else { "位于枚举变体字段或类型别名中" };
+    SoloresError::ValidationError {
+        message: format!("{} 的 SmallVec {}，无法按其长度前缀编解码", path, position),
+        field_path: Some(path.to_string()),
+        expected: Some("SmallVec 只作为账户、类型、事件字段或指令参数本身的类型".to_string()),
+        actual: None,
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use crate::idl_format::parse_idl_json;
+    use crate::test_support::run_decode_test;
+
+    fn shank_idl(extra: &str) -> String {
+        format!(
+            r#"{{"name": "multisig", "version": "0.1.0", "metadata": {{"name": "multisig", "version": "0.1.0", "spec": "0.1.0", "origin": "shank", "address": "Mu1tisig11111111111111111111111111111111111"}}, {}}}"#,
+            extra
+        )
+    }
+
+    #[test]
+    fn small_vec_is_only_accepted_as_a_field_type() {
+        let direct = shank_idl(
+            r#""instructions": [], "types": [{"name": "Message", "type": {"kind": "struct", "fields": [{"name": "keys", "type": {"defined": "SmallVec<u8,Pubkey>"}}]}}]"#,
+        );
+        assert!(parse_idl_json(&direct).unwrap().validate_small_vec_positions().is_ok());
+
+        for (extra, path) in [
+            (
+                r#""instructions": [], "types": [{"name": "Message", "type": {"kind": "struct", "fields": [{"name": "keys", "type": {"vec": {"defined": "SmallVec<u8,Pubkey>"}}}]}}]"#,
+                "Message.keys",
+            ),
+            (
+                r#""instructions": [], "types": [{"name": "Payload", "type": {"kind": "enum", "variants": [{"name": "Data", "fields": [{"name": "bytes", "type": {"defined": "SmallVec<u16,u8>"}}]}]}}]"#,
+                "Payload::Data.bytes",
+            ),
+            (
+                r#""instructions": [], "types": [{"name": "Message", "type": {"kind": "struct", "fields": [{"name": "keys", "type": {"defined": "SmallVec<u64,Pubkey>"}}]}}]"#,
+                "Message.keys",
+            ),
+        ] {
+            let idl = parse_idl_json(&shank_idl(extra)).unwrap();
+            let error = idl.validate_small_vec_positions().unwrap_err();
+            assert!(
+                matches!(&error, crate::error::SoloresError::ValidationError { field_path: Some(found), .. } if found == path),
+                "{:?}",
+                error
+            );
+        }
+    }
+
+    #[test]
+    fn small_vec_fields_use_their_length_prefix() {
+        let idl = shank_idl(
+            r#""instructions": [{"name": "execute", "discriminator": [4], "accounts": [{"name": "multisig", "isMut": true, "isSigner": false}],
+                "args": [{"name": "indexes", "type": {"defined": "SmallVec<u8,u64>"}}, {"name": "nonce", "type": "u8"}]}],
+              "types": [{"name": "Message", "type": {"kind": "struct", "fields": [
+                {"name": "keys", "type": {"defined": "SmallVec<u8,Pubkey>"}},
+                {"name": "data", "type": {"defined": "SmallVec<u16,u8>"}},
+                {"name": "fee", "type": "u32"}]}}]"#,
+        );
+        let test_source = r#"
+use borsh::BorshDeserialize;
+use sol_multisig_interface::{instructions::ExecuteIxData, types::Message};
+use solana_pubkey::Pubkey;
+
+#[test]
+fn struct_fields_use_u8_and_u16_prefixes() {
+    let message = Message { keys: vec![Pubkey::new_unique(), Pubkey::new_unique()], data: vec![7; 300], fee: 5 };
+    let bytes = borsh::to_vec(&message).unwrap();
+
+    let mut expected = vec![2];
+    expected.extend(message.keys.iter().flat_map(|key| key.to_bytes()));
+    expected.extend(300u16.to_le_bytes());
+    expected.extend([7; 300]);
+    expected.extend(5u32.to_le_bytes());
+    assert_eq!(bytes, expected);
+    assert_eq!(Message::try_from_slice(&bytes).unwrap(), message);
+}
+
+#[test]
+fn instruction_args_use_u8_prefixes() {
+    let bytes = [4, 2, 1, 0, 0, 0, 0, 0, 0, 0, 2, 0, 0, 0, 0, 0, 0, 0, 9];
+    let data = ExecuteIxData::from_bytes(&bytes).unwrap();
+    assert_eq!(data, ExecuteIxData::new(vec![1, 2], 9));
+    assert_eq!(data.try_to_vec().unwrap(), bytes);
+}
+
+#[test]
+fn lengths_beyond_the_prefix_are_rejected() {
+    let message = Message { keys: vec![Pubkey::default(); 256], data: vec![], fee: 0 };
+    let error = borsh::to_vec(&message).unwrap_err();
+    assert_eq!(error.kind(), std::io::ErrorKind::InvalidInput);
+    assert!(Message::try_from_slice(&[3, 0]).is_err());
+}
+"#;
+        run_decode_test("small_vec", &idl, &[], &[], test_source);
+    }
+}
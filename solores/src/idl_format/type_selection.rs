@@ -0,0 +1,441 @@
+//! 按类型裁剪生成范围
+//!
+//! `--include-types` 只保留指定的账户 / 类型 / 事件 / 指令，以及它们引用的类型的
+//! 传递闭包，其余指令与无关类型不再生成；`--exclude-types` 移除指定条目，若仍被
+//! 保留的条目引用则报错并列出引用方。
+//!
+//! 依赖图在 IR 上构建：节点为条目名称（按 PascalCase 比较），边为字段、枚举变体、
+//! 别名与指令参数中的类型引用。同名的账户、类型与事件（Anchor 0.30 中账户结构体
+//! 定义在 types 中）视为同一节点。同一张图也用于 `solores graph` 的可视化输出。
+
+use std::collections::{BTreeMap, BTreeSet};
+
+use convert_case::{Case, Casing};
+use regex::Regex;
+
+use super::anchor_idl::{AnchorField, AnchorFieldType, AnchorTypeKind};
+use super::non_anchor_idl::{NonAnchorField, NonAnchorFieldType, NonAnchorTypeKind};
+use super::IdlFormatEnum;
+use crate::error::SoloresError;
+
+/// 依赖图节点类别，同名条目合并时取靠前的类别
+#[derive(Debug, Clone, Copy, PartialEq, Eq, PartialOrd, Ord)]
+pub enum NodeKind {
+    Instruction,
+    Account,
+    Event,
+    Type,
+}
+
+/// IDL 条目依赖图：指令 → 账户 / 参数类型 → 嵌套类型，以及事件引用的类型
+#[derive(Debug, Default)]
+pub struct DependencyGraph {
+    /// 节点类别与原始名称
+    pub nodes: Vec<(NodeKind, String)>,
+    /// 有向边，元素为 `nodes` 中的下标
+    pub edges: BTreeSet<(usize, usize)>,
+}
+
+/// 依赖图中的条目
+struct Item {
+    /// 类别与原始名称，用于报错
+    label: String,
+    name: String,
+    key: String,
+    kind: NodeKind,
+    references: BTreeSet<String>,
+    /// 指令账户列表中的账户名称（PascalCase）
+    accounts: BTreeSet<String>,
+}
+
+impl NodeKind {
+    pub fn as_str(&self) -> &'static str {
+        match self {
+            NodeKind::Instruction => "instruction",
+            NodeKind::Account => "account",
+            NodeKind::Event => "event",
+            NodeKind::Type => "type",
+        }
+    }
+}
+
+impl Item {
+    fn is_instruction(&self) -> bool {
+        self.kind == NodeKind::Instruction
+    }
+}
+
+/// 收集类型引用中出现的标识符（类型名字符串中可能内联 `Vec<Fees>` 等 Rust 语法）
+struct ReferenceCollector {
+    ident: Regex,
+    references: BTreeSet<String>,
+}
+
+impl ReferenceCollector {
+    fn new() -> Self {
+        Self { ident: Regex::new(r"[A-Za-z_][A-Za-z0-9_]*").expect("valid regex"), references: BTreeSet::new() }
+    }
+
+    fn take(&mut self) -> BTreeSet<String> {
+        std::mem::take(&mut self.references)
+    }
+
+    fn type_str(&mut self, value: &str) {
+        for ident in self.ident.find_iter(value) {
+            self.references.insert(ident.as_str().to_case(Case::Pascal));
+        }
+    }
+
+    fn json(&mut self, value: &serde_json::Value) {
+        match value {
+            serde_json::Value::String(s) => self.type_str(s),
+            serde_json::Value::Array(values) => values.iter().for_each(|v| self.json(v)),
+            serde_json::Value::Object(map) => map.values().for_each(|v| self.json(v)),
+            _ => {}
+        }
+    }
+
+    fn anchor_type(&mut self, field_type: &AnchorFieldType) {
+        match field_type {
+            AnchorFieldType::Basic(name)
+            | AnchorFieldType::PrimitiveOrPubkey(name)
+            | AnchorFieldType::defined(name) => self.type_str(name),
+            AnchorFieldType::array(inner, _)
+            | AnchorFieldType::vec(inner)
+            | AnchorFieldType::option(inner)
+            | AnchorFieldType::coption(inner) => self.anchor_type(inner),
+            AnchorFieldType::Complex { params, .. } => params.iter().flatten().for_each(|param| self.json(param)),
+        }
+    }
+
+    fn anchor_fields(&mut self, fields: Option<&Vec<AnchorField>>) -> &mut Self {
+        for field in fields.into_iter().flatten() {
+            self.anchor_type(&field.field_type);
+        }
+        self
+    }
+
+    fn non_anchor_type(&mut self, field_type: &NonAnchorFieldType) {
+        match field_type {
+            NonAnchorFieldType::Basic(name) | NonAnchorFieldType::Defined { defined: name } => self.type_str(name),
+            NonAnchorFieldType::Option { option: inner }
+            | NonAnchorFieldType::COption { coption: inner }
+            | NonAnchorFieldType::Vec { vec: inner }
+            | NonAnchorFieldType::Array { array: (inner, _) } => self.non_anchor_type(inner),
+            NonAnchorFieldType::HashMap { key, value } => {
+                self.non_anchor_type(key);
+                self.non_anchor_type(value);
+            }
+            NonAnchorFieldType::Complex { params, .. } => params.iter().flatten().for_each(|param| self.json(param)),
+        }
+    }
+
+    fn non_anchor_fields(&mut self, fields: Option<&Vec<NonAnchorField>>) -> &mut Self {
+        for field in fields.into_iter().flatten() {
+            self.non_anchor_type(&field.field_type);
+        }
+        self
+    }
+}
+
+impl IdlFormatEnum {
+    /// 按 `--include-types` / `--exclude-types` 裁剪 IR，二者均为空时不做任何修改
+    pub fn apply_type_selection(&mut self, include: &[String], exclude: &[String]) -> Result<(), SoloresError> {
+        if include.is_empty() && exclude.is_empty() {
+            return Ok(());
+        }
+
+        let items = self.dependency_items();
+        let (types, instructions) = select(&items, include, exclude)?;
+        log::info!(
+            "✂️ 类型裁剪：保留 {} 个类型条目、{} 个指令（共 {} 个条目）",
+            types.len(),
+            instructions.len(),
+            items.len()
+        );
+
+        let keep_type = |name: &str| types.contains(&name.to_case(Case::Pascal));
+        let keep_instruction = |name: &str| instructions.contains(&name.to_case(Case::Pascal));
+        match self {
+            IdlFormatEnum::Anchor(idl) => {
+                idl.accounts.iter_mut().for_each(|v| v.retain(|a| keep_type(&a.name)));
+                idl.types.iter_mut().for_each(|v| v.retain(|t| keep_type(&t.name)));
+                idl.events.iter_mut().for_each(|v| v.retain(|e| keep_type(&e.name)));
+                idl.instructions.iter_mut().for_each(|v| v.retain(|i| keep_instruction(&i.name)));
+            }
+            IdlFormatEnum::NonAnchor(idl) => {
+                idl.accounts.iter_mut().for_each(|v| v.retain(|a| keep_type(&a.name)));
+                idl.types.iter_mut().for_each(|v| v.retain(|t| keep_type(&t.name)));
+                idl.events.iter_mut().for_each(|v| v.retain(|e| keep_type(&e.name)));
+                idl.instructions.iter_mut().for_each(|v| v.retain(|i| keep_instruction(&i.name)));
+            }
+        }
+        Ok(())
+    }
+
+    /// 构建依赖图，同名的账户 / 类型 / 事件合并为一个节点
+    pub fn dependency_graph(&self) -> DependencyGraph {
+        let items = self.dependency_items();
+        let mut graph = DependencyGraph::default();
+        let mut type_nodes: BTreeMap<&str, usize> = BTreeMap::new();
+        let mut item_nodes = Vec::with_capacity(items.len());
+        for item in &items {
+            let index = match type_nodes.get(item.key.as_str()) {
+                Some(&index) if !item.is_instruction() => {
+                    let node = &mut graph.nodes[index];
+                    node.0 = node.0.min(item.kind);
+                    index
+                }
+                _ => {
+                    graph.nodes.push((item.kind, item.name.clone()));
+                    let index = graph.nodes.len() - 1;
+                    if !item.is_instruction() {
+                        type_nodes.insert(&item.key, index);
+                    }
+                    index
+                }
+            };
+            item_nodes.push(index);
+        }
+
+        for (item, &from) in items.iter().zip(&item_nodes) {
+            let references = item.references.iter().filter_map(|key| type_nodes.get(key.as_str()));
+            // 指令账户按名称对应到同名账户类型
+            let accounts = item
+                .accounts
+                .iter()
+                .filter_map(|key| type_nodes.get(key.as_str()))
+                .filter(|&&index| graph.nodes[index].0 == NodeKind::Account);
+            let targets: Vec<usize> = references.chain(accounts).copied().filter(|&to| to != from).collect();
+            graph.edges.extend(targets.into_iter().map(|to| (from, to)));
+        }
+        graph
+    }
+
+    /// 依赖图的节点：账户、类型、事件与指令，各自带有引用的标识符
+    fn dependency_items(&self) -> Vec<Item> {
+        let mut refs = ReferenceCollector::new();
+        let item = |kind: NodeKind, name: &str, references: BTreeSet<String>| Item {
+            label: format!("{} {}", kind.as_str(), name),
+            name: name.to_string(),
+            key: name.to_case(Case::Pascal),
+            kind,
+            references,
+            accounts: BTreeSet::new(),
+        };
+        let instruction_accounts = |names: Vec<&String>| names.into_iter().map(|name| name.to_case(Case::Pascal)).collect();
+
+        let mut items = Vec::new();
+        match self {
+            IdlFormatEnum::Anchor(idl) => {
+                for account in idl.accounts.iter().flatten() {
+                    let references = refs.anchor_fields(account.fields.as_ref()).take();
+                    items.push(item(NodeKind::Account, &account.name, references));
+                }
+                for type_def in idl.types.iter().flatten() {
+                    match &type_def.kind {
+                        Some(AnchorTypeKind::Struct(fields)) => {
+                            refs.anchor_fields(Some(fields));
+                        }
+                        Some(AnchorTypeKind::Enum(variants)) => {
+                            for variant in variants {
+                                refs.anchor_fields(variant.fields.as_ref());
+                            }
+                        }
+                        Some(AnchorTypeKind::Alias(field_type)) => refs.anchor_type(field_type),
+                        None => {}
+                    }
+                    items.push(item(NodeKind::Type, &type_def.name, refs.take()));
+                }
+                for event in idl.events.iter().flatten() {
+                    let references = refs.anchor_fields(event.fields.as_ref()).take();
+                    items.push(item(NodeKind::Event, &event.name, references));
+                }
+                for instruction in idl.instructions.iter().flatten() {
+                    let references = refs.anchor_fields(instruction.args.as_ref()).take();
+                    items.push(Item {
+                        accounts: instruction_accounts(instruction.accounts.iter().flatten().map(|a| &a.name).collect()),
+                        ..item(NodeKind::Instruction, &instruction.name, references)
+                    });
+                }
+            }
+            IdlFormatEnum::NonAnchor(idl) => {
+                for account in idl.accounts.iter().flatten() {
+                    let references = refs.non_anchor_fields(account.fields.as_ref()).take();
+                    items.push(item(NodeKind::Account, &account.name, references));
+                }
+                for type_def in idl.types.iter().flatten() {
+                    match &type_def.type_def {
+                        NonAnchorTypeKind::Struct { fields } => {
+                            refs.non_anchor_fields(Some(fields));
+                        }
+                        NonAnchorTypeKind::Enum { variants } => {
+                            for variant in variants {
+                                refs.non_anchor_fields(variant.fields.as_ref());
+                            }
+                        }
+                        NonAnchorTypeKind::Alias { value } => refs.non_anchor_type(value),
+                    }
+                    items.push(item(NodeKind::Type, &type_def.name, refs.take()));
+                }
+                for event in idl.events.iter().flatten() {
+                    let references = refs.non_anchor_fields(event.fields.as_ref()).take();
+                    items.push(item(NodeKind::Event, &event.name, references));
+                }
+                for instruction in idl.instructions.iter().flatten() {
+                    let references = refs.non_anchor_fields(instruction.args.as_ref()).take();
+                    items.push(Item {
+                        accounts: instruction_accounts(instruction.accounts.iter().flatten().map(|a| &a.name).collect()),
+                        ..item(NodeKind::Instruction, &instruction.name, references)
+                    });
+                }
+            }
+        }
+        items
+    }
+}
+
+/// 计算保留的类型节点与指令，返回二者的 PascalCase 名称集合
+fn select(
+    items: &[Item],
+    include: &[String],
+    exclude: &[String],
+) -> Result<(BTreeSet<String>, BTreeSet<String>), SoloresError> {
+    // 同名的账户 / 类型 / 事件合并为一个节点，引用取并集
+    let mut type_refs: BTreeMap<&str, BTreeSet<&str>> = BTreeMap::new();
+    for item in items.iter().filter(|item| !item.is_instruction()) {
+        type_refs.entry(&item.key).or_default();
+    }
+    for item in items.iter().filter(|item| !item.is_instruction()) {
+        let known: BTreeSet<&str> = item
+            .references
+            .iter()
+            .filter(|r| **r != item.key && type_refs.contains_key(r.as_str()))
+            .map(String::as_str)
+            .collect();
+        type_refs.entry(&item.key).or_default().extend(known);
+    }
+    let instruction_keys: BTreeSet<&str> =
+        items.iter().filter(|item| item.is_instruction()).map(|item| item.key.as_str()).collect();
+
+    let resolve = |flag: &str, names: &[String]| -> Result<BTreeSet<String>, SoloresError> {
+        names
+            .iter()
+            .map(|name| {
+                let key = name.trim().to_case(Case::Pascal);
+                if type_refs.contains_key(key.as_str()) || instruction_keys.contains(key.as_str()) {
+                    Ok(key)
+                } else {
+                    Err(SoloresError::ValidationError {
+                        message: format!("未找到账户、类型、事件或指令 '{}'", name),
+                        field_path: Some(format!("--{} {}", flag, name)),
+                        expected: Some(
+                            items.iter().map(|item| item.label.as_str()).collect::<Vec<_>>().join(", "),
+                        ),
+                        actual: Some(key),
+                    })
+                }
+            })
+            .collect()
+    };
+    let included = resolve("include-types", include)?;
+    let excluded = resolve("exclude-types", exclude)?;
+
+    // 根节点：显式包含的条目，未指定时为全部条目
+    let (mut pending, mut instructions): (Vec<&str>, BTreeSet<String>) = if included.is_empty() {
+        (type_refs.keys().copied().collect(), instruction_keys.iter().map(|k| k.to_string()).collect())
+    } else {
+        (
+            included.iter().map(String::as_str).filter(|k| type_refs.contains_key(k)).collect(),
+            included.iter().filter(|k| instruction_keys.contains(k.as_str())).cloned().collect(),
+        )
+    };
+    instructions.retain(|key| !excluded.contains(key));
+    for item in items.iter().filter(|item| item.is_instruction() && instructions.contains(&item.key)) {
+        pending.extend(item.references.iter().map(String::as_str).filter(|r| type_refs.contains_key(r)));
+    }
+
+    let mut types = BTreeSet::new();
+    while let Some(key) = pending.pop() {
+        if excluded.contains(key) || !types.insert(key.to_string()) {
+            continue;
+        }
+        pending.extend(type_refs[key].iter().copied());
+    }
+
+    // 被排除的类型仍被保留条目引用时无法生成可编译的代码
+    let conflicts: Vec<String> = items
+        .iter()
+        .filter(|item| {
+            if item.is_instruction() { instructions.contains(&item.key) } else { types.contains(&item.key) }
+        })
+        .flat_map(|item| {
+            item.references
+                .iter()
+                .filter(|r| excluded.contains(*r) && type_refs.contains_key(r.as_str()))
+                .map(move |r| format!("{} -> {}", item.label, r))
+        })
+        .collect();
+    if !conflicts.is_empty() {
+        return Err(SoloresError::ValidationError {
+            message: "被排除的类型仍被保留的条目引用，请一并排除引用方".to_string(),
+            field_path: Some("--exclude-types".to_string()),
+            expected: None,
+            actual: Some(conflicts.join(", ")),
+        });
+    }
+
+    Ok((types, instructions))
+}
+
+#[cfg(test)]
+mod tests {
+    use crate::idl_format::{parse_idl_json, IdlFormatEnum};
+
+    const IDL: &str = r#"{
+        "address": "11111111111111111111111111111111",
+        "metadata": {"name": "demo", "version": "0.1.0", "spec": "0.1.0"},
+        "instructions": [
+            {"name": "swap", "discriminator": [1,0,0,0,0,0,0,0], "accounts": [],
+             "args": [{"name": "params", "type": {"defined": {"name": "SwapParams"}}}]}
+        ],
+        "accounts": [{"name": "Pool", "discriminator": [2,0,0,0,0,0,0,0]}],
+        "types": [
+            {"name": "Pool", "type": {"kind": "struct", "fields": [{"name": "fees", "type": {"vec": {"defined": {"name": "FeeTier"}}}}]}},
+            {"name": "FeeTier", "type": {"kind": "struct", "fields": [{"name": "bps", "type": "u16"}]}},
+            {"name": "SwapParams", "type": {"kind": "struct", "fields": [{"name": "amount", "type": "u64"}]}},
+            {"name": "Unused", "type": {"kind": "struct", "fields": [{"name": "x", "type": "u8"}]}}
+        ]
+    }"#;
+
+    fn names(idl: &IdlFormatEnum) -> (Vec<String>, usize) {
+        let IdlFormatEnum::Anchor(idl) = idl else { unreachable!() };
+        let types = idl.types.iter().flatten().map(|t| t.name.clone()).collect();
+        (types, idl.instructions.iter().flatten().count())
+    }
+
+    #[test]
+    fn include_keeps_transitive_closure() {
+        let mut idl = parse_idl_json(IDL).unwrap();
+        idl.apply_type_selection(&["pool".to_string()], &[]).unwrap();
+        assert_eq!(names(&idl), (vec!["Pool".to_string(), "FeeTier".to_string()], 0));
+
+        let mut idl = parse_idl_json(IDL).unwrap();
+        idl.apply_type_selection(&["Swap".to_string()], &[]).unwrap();
+        assert_eq!(names(&idl), (vec!["SwapParams".to_string()], 1));
+
+        assert!(parse_idl_json(IDL).unwrap().apply_type_selection(&["Missing".to_string()], &[]).is_err());
+    }
+
+    #[test]
+    fn exclude_rejects_referenced_types() {
+        let mut idl = parse_idl_json(IDL).unwrap();
+        idl.apply_type_selection(&[], &["Unused".to_string()]).unwrap();
+        assert_eq!(names(&idl).0.len(), 3);
+
+        let mut idl = parse_idl_json(IDL).unwrap();
+        assert!(idl.apply_type_selection(&[], &["FeeTier".to_string()]).is_err());
+        idl.apply_type_selection(&[], &["Pool".to_string(), "FeeTier".to_string()]).unwrap();
+        assert_eq!(names(&idl), (vec!["SwapParams".to_string(), "Unused".to_string()], 1));
+    }
+}
@@ -0,0 +1,139 @@
+//! 零拷贝账户
+//!
+//! Anchor 0.30 IDL 中 `serialization` 为 `bytemuck` / `bytemuckunsafe` 的同名类型
+//! 对应零拷贝账户，也可以通过 `--zero-copy Account` 指定。零拷贝账户按定长的
+//! 紧凑（packed）布局读取，字段偏移不保证满足对齐要求，生成代码不做引用转换。
+
+use convert_case::{Case, Casing};
+
+use super::anchor_idl::{AnchorFieldType, AnchorIdl, AnchorTypeKind};
+use super::enum_tags::EnumTagLayout;
+use super::non_anchor_idl::{NonAnchorFieldType, NonAnchorIdl, NonAnchorTypeKind};
+use super::IdlFormatEnum;
+use crate::error::SoloresError;
+
+/// 定长类型展开的最大嵌套深度，防止自引用类型无限递归
+const MAX_DEPTH: usize = 16;
+
+impl IdlFormatEnum {
+    /// 是否有账户生成零拷贝视图（视图以 `unsafe` 读取未对齐字段）
+    pub fn has_zero_copy_accounts(&self) -> bool {
+        match self {
+            IdlFormatEnum::Anchor(idl) => idl.accounts.iter().flatten().any(|account| account.zero_copy),
+            IdlFormatEnum::NonAnchor(idl) => idl.accounts.iter().flatten().any(|account| account.zero_copy),
+        }
+    }
+
+    /// 将 `--zero-copy` 指定的账户标记为零拷贝；只匹配到同名类型时忽略并告警
+    pub fn apply_zero_copy(&mut self, names: &[String]) -> Result<(), SoloresError> {
+        for name in names {
+            let key = name.trim().to_case(Case::Pascal);
+            let (account, has_type) = match self {
+                IdlFormatEnum::Anchor(idl) => (
+                    idl.accounts.iter_mut().flatten().find(|a| a.name.to_case(Case::Pascal) == key).map(|a| &mut a.zero_copy),
+                    idl.types.iter().flatten().any(|t| t.name.to_case(Case::Pascal) == key),
+                ),
+                IdlFormatEnum::NonAnchor(idl) => (
+                    idl.accounts.iter_mut().flatten().find(|a| a.name.to_case(Case::Pascal) == key).map(|a| &mut a.zero_copy),
+                    idl.types.iter().flatten().any(|t| t.name.to_case(Case::Pascal) == key),
+                ),
+            };
+            match account {
+                Some(zero_copy) => *zero_copy = true,
+                None if has_type => log::warn!("⚠️ --zero-copy {}：零拷贝视图只为账户生成，已忽略类型", name),
+                None => {
+                    return Err(SoloresError::ValidationError {
+                        message: format!("未找到账户 '{}'", name),
+                        field_path: Some(format!("--zero-copy {}", name)),
+                        expected: None,
+                        actual: Some(key),
+                    })
+                }
+            }
+        }
+        Ok(())
+    }
+}
+
+/// 基础类型的紧凑布局长度，非定长（string / bytes）或未知类型返回 None
+fn primitive_size(type_name: &str) -> Option<usize> {
+    match type_name {
+        "bool" | "u8" | "i8" => Some(1),
+        "u16" | "i16" => Some(2),
+        "u32" | "i32" | "f32" => Some(4),
+        "u64" | "i64" | "f64" => Some(8),
+        "u128" | "i128" => Some(16),
+        "pubkey" | "Pubkey" | "publicKey" => Some(32),
+        _ => None,
+    }
+}
+
+/// Anchor 字段的定长布局长度；Vec / Option / 带数据的枚举等变长类型返回 None
+pub fn anchor_fixed_size(field_type: &AnchorFieldType, idl: &AnchorIdl) -> Option<usize> {
+    anchor_size(field_type, idl, 0)
+}
+
+fn anchor_size(field_type: &AnchorFieldType, idl: &AnchorIdl, depth: usize) -> Option<usize> {
+    if depth > MAX_DEPTH {
+        return None;
+    }
+    match field_type {
+        AnchorFieldType::Basic(name) | AnchorFieldType::PrimitiveOrPubkey(name) => primitive_size(name),
+        AnchorFieldType::array(inner, len) => Some(anchor_size(inner, idl, depth + 1)? * len),
+        AnchorFieldType::defined(name) => {
+            let type_def = idl.types.iter().flatten().find(|t| t.name == *name)?;
+            match type_def.kind.as_ref()? {
+                AnchorTypeKind::Struct(fields) => {
+                    fields.iter().map(|f| anchor_size(&f.field_type, idl, depth + 1)).sum()
+                }
+                AnchorTypeKind::Enum(variants) => {
+                    variants.iter().all(|v| v.fields.as_ref().is_none_or(|f| f.is_empty())).then(|| {
+                        EnumTagLayout::from_values(variants.iter().map(|v| v.value)).width
+                    })
+                }
+                AnchorTypeKind::Alias(inner) => anchor_size(inner, idl, depth + 1),
+            }
+        }
+        AnchorFieldType::vec(_)
+        | AnchorFieldType::option(_)
+        | AnchorFieldType::coption(_)
+        | AnchorFieldType::Complex { .. } => None,
+    }
+}
+
+/// NonAnchor 字段的定长布局长度，规则同 [`anchor_fixed_size`]
+pub fn non_anchor_fixed_size(field_type: &NonAnchorFieldType, idl: &NonAnchorIdl) -> Option<usize> {
+    non_anchor_size(field_type, idl, 0)
+}
+
+fn non_anchor_size(field_type: &NonAnchorFieldType, idl: &NonAnchorIdl, depth: usize) -> Option<usize> {
+    if depth > MAX_DEPTH {
+        return None;
+    }
+    match field_type {
+        NonAnchorFieldType::Basic(name) => primitive_size(name),
+        NonAnchorFieldType::Array { array: (inner, len) } => Some(non_anchor_size(inner, idl, depth + 1)? * len),
+        NonAnchorFieldType::Defined { defined } => {
+            if let Some(size) = idl.c_type_size(defined) {
+                return Some(size);
+            }
+            let type_def = idl.types.iter().flatten().find(|t| t.name == *defined)?;
+            match &type_def.type_def {
+                NonAnchorTypeKind::Struct { fields } => {
+                    fields.iter().map(|f| non_anchor_size(&f.field_type, idl, depth + 1)).sum()
+                }
+                NonAnchorTypeKind::Enum { variants } => {
+                    variants.iter().all(|v| v.fields.as_ref().is_none_or(|f| f.is_empty())).then(|| {
+                        EnumTagLayout::from_values(variants.iter().map(|v| v.value)).width
+                    })
+                }
+                NonAnchorTypeKind::Alias { value } => non_anchor_size(value, idl, depth + 1),
+            }
+        }
+        NonAnchorFieldType::Option { .. }
+        | NonAnchorFieldType::COption { .. }
+        | NonAnchorFieldType::Vec { .. }
+        | NonAnchorFieldType::HashMap { .. }
+        | NonAnchorFieldType::Complex { .. } => None,
+    }
+}
@@ -1,41 +1,63 @@
 #![doc = include_str!("../README.md")]
+// SoloresError 携带丰富的诊断上下文，体积较大是有意为之
+#![allow(clippy::result_large_err)]
 
 use std::{
     env,
     fs::{self, File, OpenOptions},
     io::Read,
     path::{Path, PathBuf},
-    process::Command,
 };
 
-use clap::{command, Parser};
-use idl_format::{parse_idl_json, IdlFormat, IdlFormatEnum};
+use clap::{CommandFactory, FromArgMatches, Parser, Subcommand};
+use idl_format::{parse_idl_json, parse_idl_json_with_format, FormatDetection, FormatKind, IdlFormat, IdlFormatEnum};
 
 use crate::error::{diagnose_json_error, format_user_error, validate_idl_structure, SoloresError};
 
 // Just make all mods pub to allow ppl to use the lib
 
+pub mod batch; // 批量生成库 API
+pub mod build_rs; // build.rs 集成
 pub mod cargo; // Cargo.toml 生成功能
+pub mod changelog; // 重新生成时的 CHANGELOG
+pub mod convert; // solores convert IDL方言转换
+pub mod dry_run; // --dry-run 预演模式
 pub mod error;
+pub mod fixtures; // --fixtures-from-rpc 主网交易解析测试
+pub mod graph; // solores graph 依赖图可视化
 pub mod idl_format;
 pub mod minijinja; // MiniJinja 模块化模板系统
                    // pub mod templates;  // 传统模板系统 - 已移除
 // unified_library功能已集成到minijinja模块中
+pub mod pda_registry; // workspace 级 PDA 注册表
+pub mod pipeline; // 统一代码生成流水线
+pub mod post_process; // --post-process 生成文件后处理
+pub mod project; // cargo solores 项目配置
+pub mod provenance; // 生成文件头与可复现输出
+pub mod snippet; // solores snippet 单条目代码片段
+#[cfg(test)]
+pub(crate) mod test_support; // 生成 crate 的解码测试
 pub mod utils;
+pub mod warnings; // 结构化生成警告
+pub mod watch; // solores watch 监听模式
 pub mod workspace; // 新增workspace生成功能
 pub mod write_gitignore;
 pub mod write_readme;
 pub mod write_src;
 
-use cargo::{
-    should_use_workspace_cargo_toml, write_fine_grained_cargo_toml,
-    write_workspace_member_cargo_toml,
-};
-use workspace::{add_workspace_member, finalize_workspace, validate_workspace_config};
-use minijinja::generator::{auto_group_protocols, UnifiedLibraryConfig};
-use write_gitignore::write_gitignore;
-use write_readme::write_readme;
-use write_src::*;
+use idl_format::arg_encoding::ArgEncodingHint;
+use idl_format::field_types::FieldTypeOverride;
+use idl_format::naming::NameAffix;
+use minijinja::compute_units::ComputeUnitHint;
+use minijinja::externs::ExternCrate;
+use minijinja::field_hints::FieldHint;
+use minijinja::generators::interop::InteropCrate;
+use minijinja::pubkey_crate::PubkeyCrate;
+use minijinja::rust_version::RustVersion;
+use minijinja::generator::{auto_group_protocols, CompatLayout, SerdeRename, TrailingBytes, UnifiedLibraryConfig};
+use pipeline::Engine;
+
+pub use build_rs::build_rs_generate;
 
 const DEFAULT_OUTPUT_CRATE_NAME_MSG: &str = "<name-of-program>_interface";
 const DEFAULT_PROGRAM_ID_MSG: &str = "program ID in IDL else system program ID if absent";
@@ -61,16 +83,34 @@ pub struct Args {
     )]
     pub output_crate_name: String,
 
-    #[arg(long, short, help = "program ID / address / pubkey", default_value = DEFAULT_PROGRAM_ID_MSG)]
+    #[arg(
+        long,
+        short,
+        help = "program ID / address / pubkey；多集群部署写作 mainnet=<addr>,devnet=<addr>，非 mainnet 集群生成同名 feature 切换 ID",
+        default_value = DEFAULT_PROGRAM_ID_MSG
+    )]
     pub program_id: Option<String>,
 
     #[arg(
         long,
         short,
-        help = "typedefs and accounts to derive bytemuck::Pod for. Does not currently check validity of derivation."
+        help = "零拷贝账户：生成借用账户数据、按未对齐偏移读取字段的 {Account}Ref 视图（IDL 中 serialization 为 bytemuck 的账户自动启用），可重复"
     )]
     pub zero_copy: Vec<String>,
 
+    #[arg(
+        long,
+        help = "C 布局文件（JSON：{\"账户或类型\": {\"size\": N, \"offsets\": {\"字段\": 偏移}}}），将 NonAnchor 账户按 #[repr(C)] 布局生成 bytemuck 读取代码，与 IDL 中的 \"layout\": \"c\" 声明合并"
+    )]
+    pub layout_file: Option<PathBuf>,
+
+    #[arg(
+        long,
+        value_name = "FILE",
+        help = "账户布局变体文件（JSON：{\"账户\": {\"field\": \"版本字段\", \"layouts\": {\"取值\": \"布局类型\"}}}），为按版本字段区分多种布局的账户生成 {Account}Variant 枚举，与 IDL 账户上的 \"variants\" 声明合并"
+    )]
+    pub account_variants: Option<PathBuf>,
+
     #[arg(
         long,
         short,
@@ -143,6 +183,47 @@ pub struct Args {
     )]
     pub serde_json_vers: String,
 
+    #[arg(long, help = "生成crate的版本号（默认 0.2.0）")]
+    pub crate_version: Option<String>,
+
+    #[arg(long, help = "生成crate的许可证（默认 MIT OR Apache-2.0）")]
+    pub license: Option<String>,
+
+    #[arg(long, help = "生成crate的仓库地址")]
+    pub repository: Option<String>,
+
+    #[arg(long, help = "生成crate的描述")]
+    pub description: Option<String>,
+
+    #[arg(long, value_delimiter = ',', help = "生成crate的作者，多个用逗号分隔")]
+    pub authors: Vec<String>,
+
+    #[arg(
+        long,
+        help = "生成crate的最低 Rust 版本（不低于 solana-* 2.x 依赖要求的 1.79）：写入 Cargo.toml 的 rust-version，生成代码避免使用更新的语法与标准库 API"
+    )]
+    pub min_rust_version: Option<RustVersion>,
+
+    #[arg(
+        long,
+        value_enum,
+        value_delimiter = ',',
+        help = "生成crate的 lint 配置，多个用逗号分隔：forbid-unsafe 写入 #![forbid(unsafe_code)]（存在零拷贝视图时为 deny 并在读取处局部允许），deny-missing-docs 写入 #![deny(missing_docs)]（IDL 生成的模块局部允许）；lib.rs 总是允许 clippy::too_many_arguments"
+    )]
+    pub lints: Vec<minijinja::generator::Lint>,
+
+    #[arg(
+        long,
+        help = "为程序升级预留演进空间：ProgramInstruction、ProgramAccount 与错误枚举标记为 #[non_exhaustive]，解析器遇到未知 discriminator 时返回携带原始数据的 #[doc(hidden)] __Unknown(Vec<u8>) 变体而不是报错"
+    )]
+    pub non_exhaustive: bool,
+
+    #[arg(
+        long,
+        help = "在指令解析器中生成 parse_instruction_lossy：未知 discriminator 的指令返回携带 discriminator、原始数据与账户的 ParsedIx::Unknown 而不是报错，便于索引器完整记录程序活动"
+    )]
+    pub lossy_parser: bool,
+
     #[arg(
         long,
         default_value = "true",
@@ -170,6 +251,13 @@ pub struct Args {
     )]
     pub batch_output_dir: PathBuf,
 
+    #[arg(
+        long,
+        requires = "batch",
+        help = "继续被中断的批量生成：跳过断点（输出目录中的 .solores-batch.json）记录为已完成、IDL 与生成选项未变化且输出完整的 crate，只重新生成其余 IDL"
+    )]
+    pub resume: bool,
+
     #[arg(
         long,
         help = "generate test code for parsers (默认不生成测试以减少文件数量)"
@@ -182,6 +270,21 @@ pub struct Args {
     #[arg(long, help = "指定workspace名称", default_value = "solana_workspace")]
     pub workspace_name: String,
 
+    #[arg(
+        long,
+        requires = "workspace",
+        help = "在workspace中生成 pda_registry crate：汇总各程序PDA的seeds模式（常量前缀 → 所属程序），通过派生校验判断任意地址可能属于哪个程序"
+    )]
+    pub pda_registry: bool,
+
+    #[arg(
+        long,
+        value_enum,
+        default_value_t = batch::GroupOutputBy::default(),
+        help = "批量输出目录的组织方式：flat 平铺（默认），address 按程序地址分组，name 按IDL文件名前缀（第一个 _ 或 - 之前）分组；workspace 成员列表使用分组后的相对路径"
+    )]
+    pub group_output_by: batch::GroupOutputBy,
+
     #[arg(
         long,
         help = "批量处理时排除的JSON文件名，支持通配符，多个用逗号分隔",
@@ -212,6 +315,306 @@ pub struct Args {
         default_value = "solana_protocols"
     )]
     pub unified_library_name: String,
+
+    #[arg(
+        long,
+        help = "字段语义标注 Owner.field=kind（kind: unix_timestamp / bps / q64_64），生成 chrono / decimal 便捷访问方法，可重复"
+    )]
+    pub field_hint: Vec<FieldHint>,
+
+    #[arg(
+        long,
+        help = "字段标注文件，每行一条 Owner.field=kind（# 开头为注释），与 --field-hint 合并"
+    )]
+    pub field_hints_file: Option<PathBuf>,
+
+    #[arg(
+        long,
+        help = "字段类型替换 Owner.field=path::Type（如 Pool.config_hash=my_types::Hash），生成的结构体使用替换类型，编码仍按 IDL 类型经 Into / TryFrom 转换，可重复"
+    )]
+    pub field_type: Vec<FieldTypeOverride>,
+
+    #[arg(
+        long,
+        help = "字段类型替换文件，每行一条 Owner.field=path::Type（# 开头为注释），与 --field-type 合并"
+    )]
+    pub field_types_file: Option<PathBuf>,
+
+    #[arg(
+        long,
+        help = "指令参数编码 Instruction.arg=encoding（encoding: length_prefixed / raw_tail），raw_tail 表示参数占用指令数据剩余全部字节，可重复"
+    )]
+    pub arg_encoding: Vec<ArgEncodingHint>,
+
+    #[arg(
+        long,
+        value_enum,
+        default_value_t = TrailingBytes::Allow,
+        help = "账户解析对尾部多余字节的处理策略：strict 要求长度完全一致，allow 只读取已知前缀"
+    )]
+    pub trailing_bytes: TrailingBytes,
+
+    #[arg(
+        long,
+        value_enum,
+        default_value_t = SerdeRename::Snake,
+        help = "serde 序列化的字段名：original 保留 IDL 原名，camel 使用 camelCase（与 JS 生态一致），snake 使用 Rust 字段名；作用于指令参数、Keys 账户、账户、事件与类型字段"
+    )]
+    pub serde_rename: SerdeRename,
+
+    #[arg(
+        long,
+        help = "生成类型名前缀：Prefix 作用于所有类别，category=Prefix 只作用于 accounts / types / events / instructions，可重复"
+    )]
+    pub type_prefix: Vec<NameAffix>,
+
+    #[arg(
+        long,
+        help = "生成类型名后缀：Suffix 作用于所有类别，category=Suffix 只作用于 accounts / types / events / instructions，可重复"
+    )]
+    pub type_suffix: Vec<NameAffix>,
+
+    #[arg(
+        long,
+        value_delimiter = ',',
+        help = "仅生成指定的账户 / 类型 / 事件 / 指令及其引用类型的传递闭包，其余指令与无关类型不生成，多个用逗号分隔"
+    )]
+    pub include_types: Vec<String>,
+
+    #[arg(
+        long,
+        value_delimiter = ',',
+        help = "不生成指定的账户 / 类型 / 事件 / 指令，仍被保留条目引用时报错，多个用逗号分隔"
+    )]
+    pub exclude_types: Vec<String>,
+
+    #[arg(
+        long,
+        help = "生成 tests/program_test.rs：基于 solana-program-test 校验指令构建器与解析器的往返一致性（需启用解析器）"
+    )]
+    pub gen_program_tests: bool,
+
+    #[arg(
+        long,
+        help = "生成 benches/parsers.rs：基于 criterion 测量各指令、账户及未知判别码的解析耗时，可用 --save-baseline / --baseline 对比模板改动前后的性能（需启用解析器）"
+    )]
+    pub gen_benches: bool,
+
+    #[arg(
+        long,
+        help = "生成 tests/compile_fail.rs 与 tests/compile_fail/*.rs：基于 trybuild 断言 Keys 不能缺少账户字段、不同指令的 Keys / IxData 不能互换，重新生成后 API 被放宽时测试失败；首次运行以 TRYBUILD=overwrite 记录期望的编译错误"
+    )]
+    pub gen_compile_fail_tests: bool,
+
+    #[arg(
+        long,
+        help = "生成 history.rs：analyze_history(dir) 用解析器统计抓取的原始指令数据（每个文件一条）中各指令的数量、平均数据大小与失败率（需启用解析器）"
+    )]
+    pub gen_history_stats: bool,
+
+    #[arg(
+        long,
+        value_name = "SIGNATURES_FILE",
+        help = "签名列表文件（每行一个交易签名），生成时经 RPC 获取交易并提取调用本程序的指令，生成 tests/rpc_fixtures.rs 校验解析器能解码真实指令（需启用解析器）"
+    )]
+    pub fixtures_from_rpc: Option<PathBuf>,
+
+    #[arg(
+        long,
+        default_value = fixtures::DEFAULT_RPC_URL,
+        help = "--fixtures-from-rpc 使用的 RPC 地址"
+    )]
+    pub rpc_url: String,
+
+    #[arg(
+        long,
+        help = "生成 anchor_cpi.rs（anchor-cpi feature）：实现 ToAccountMetas / ToAccountInfos 的账户结构体与 CPI 函数，供 Anchor 程序通过 CpiContext 调用"
+    )]
+    pub gen_anchor_cpi: bool,
+
+    #[arg(
+        long,
+        help = "生成 cache.rs：按地址缓存解码后的账户（AccountCache<T>），支持通过 AccountFetcher 异步刷新、推送 AccountUpdate 与过期检查，并为每个账户类型提供类型化 getter"
+    )]
+    pub gen_cache: bool,
+
+    #[arg(
+        long,
+        value_enum,
+        help = "生成数据库行映射：db.rs（sqlx-postgres feature）为每个账户与事件提供 {Name}Row 行结构体、to_row() 转换、建表 DDL 与插入语句，并写出 migrations/0001_create_<crate>_tables.sql；Pubkey 存为 TEXT，u64 存为 NUMERIC"
+    )]
+    pub gen_db: Option<minijinja::generators::db::DbTarget>,
+
+    #[arg(
+        long,
+        value_name = "NAME[::MODULE][=PATH_OR_VERSION]",
+        help = "生成 interop.rs（interop feature）：账户与自定义类型和另一个 Anchor 接口 crate 中同名类型之间按字段名双向 From 转换，便于从 Anchor 生成的 CPI crate 逐步迁移；来源格式同 --extern，省略 MODULE 时按 declare_program! 布局在 accounts / types 模块中查找"
+    )]
+    pub interop_with: Option<InteropCrate>,
+
+    #[arg(
+        long,
+        help = "生成 upgrade.rs：解析程序的 ProgramData 账户（升级权限、最近部署 slot）；Anchor 程序另提供 idl-verify feature，比对链上IDL账户与生成所用IDL的哈希（verify_onchain_idl）"
+    )]
+    pub gen_upgrade: bool,
+
+    #[arg(
+        long,
+        help = "生成 web.rs（web feature）：由指令构建未签名的 legacy / v0 交易并序列化为 base64，交给浏览器钱包适配器签名，无需 Keypair；Anchor 程序启用 serde 时另提供 wasm feature，为每条指令导出 wasm-bindgen 函数 build{Name}Transaction"
+    )]
+    pub gen_web: bool,
+
+    #[arg(long, help = "在生成的 README.md 中嵌入指令 → 账户 → 类型的 mermaid 依赖图")]
+    pub readme_graph: bool,
+
+    #[arg(
+        long,
+        help = "为出现在至少 3 个指令中的账户生成 Pubkey 新类型（typed_keys.rs），Keys 结构体字段改用新类型，传错账户位置时编译报错"
+    )]
+    pub typed_keys: bool,
+
+    #[arg(
+        long,
+        help = "生成 account_ext.rs：所有账户结构体实现 ProgramAccountExt trait（NAME、DISCRIMINATOR、owner()、try_unpack()），便于对任意账户编写泛型存储 / 索引代码（仅 Anchor IDL）"
+    )]
+    pub accounts_as_traits: bool,
+
+    #[arg(
+        long,
+        value_enum,
+        help = "兼容旧版 solores 的 crate 布局：0.2 生成单文件 instructions.rs / typedefs.rs 与 *_keys_to_meta 等旧 API 名称"
+    )]
+    pub compat: Option<CompatLayout>,
+
+    #[arg(
+        long,
+        default_value_t = PubkeyCrate::default(),
+        help = "生成代码使用的 Pubkey 来源：solana-pubkey（默认）、solana-program 或外部 crate 的模块路径（如 my_sdk::pubkey），非默认来源时生成 pubkey.rs 别名模块，供 SVM 分叉 SDK 使用"
+    )]
+    pub pubkey_crate: PubkeyCrate,
+
+    #[arg(
+        long = "extern",
+        value_name = "NAME[=PATH_OR_VERSION]",
+        help = "提供 IDL 中未定义类型的外部接口 crate：crate 目录或版本要求（省略时取 IDL metadata.dependencies 中的版本），未定义的类型从中重新导出并添加 Cargo 依赖，可重复"
+    )]
+    pub externs: Vec<ExternCrate>,
+
+    #[arg(
+        long,
+        help = "指令计算单元估算 Instruction=units，写入生成的 compute.rs，可重复"
+    )]
+    pub compute_units: Vec<ComputeUnitHint>,
+
+    #[arg(
+        long,
+        help = "未通过 --compute-units 配置的指令使用的计算单元估算",
+        default_value_t = minijinja::generator::DEFAULT_COMPUTE_UNITS
+    )]
+    pub default_compute_units: u32,
+
+    #[arg(
+        long,
+        help = "在 crate 根目录生成 SIZES.md 与 sizes.json：每个账户的数据长度（定长 / 变长）与免租最低余额"
+    )]
+    pub size_report: bool,
+
+    #[arg(
+        long,
+        value_name = "LAMPORTS",
+        help = "账户大小报告计算免租余额使用的每字节每年租金（lamports）",
+        default_value_t = minijinja::generators::sizes::DEFAULT_LAMPORTS_PER_BYTE_YEAR
+    )]
+    pub rent_lamports_per_byte_year: u64,
+
+    #[arg(
+        long,
+        help = "为账户、事件、指令参数与类型派生 schemars::JsonSchema（jsonschema feature），并在 crate 根目录生成 schemas/：每个账户 / 事件 / 指令参数的 JSON Schema 与包含全部定义的 OpenAPI components（openapi.json）"
+    )]
+    pub gen_jsonschema: bool,
+
+    #[arg(
+        long,
+        value_enum,
+        help = "屏蔽指定代码的生成警告（unknown-type / empty-event / ambiguous-account-size / name-collision / enum-tag / missing-discriminator / skipped-output），可重复"
+    )]
+    pub allow: Vec<warnings::WarningCode>,
+
+    #[arg(long, help = "将生成警告视为错误，存在未屏蔽的警告时不生成代码")]
+    pub deny_warnings: bool,
+
+    #[arg(
+        long,
+        conflicts_with = "batch",
+        help = "预演模式：打印将生成的文件树、每个文件的条目数与行数，不写入输出目录"
+    )]
+    pub dry_run: bool,
+
+    #[arg(
+        long,
+        conflicts_with_all = ["batch", "dry_run", "single_file"],
+        help = "将生成的所有模块合并为单个 lib.rs（内联 mod 块）并打印到标准输出，不写入输出目录；Cargo.toml 等非 Rust 文件不输出"
+    )]
+    pub stdout_single_file: bool,
+
+    #[arg(
+        long,
+        value_name = "PATH",
+        conflicts_with_all = ["batch", "dry_run"],
+        help = "将生成的所有模块合并为单个 lib.rs（内联 mod 块）写入指定文件，不写入输出目录"
+    )]
+    pub single_file: Option<PathBuf>,
+
+    #[arg(
+        long,
+        value_name = "COMMAND",
+        help = "生成完成后对每个生成文件运行的 shell 命令，文件路径作为最后一个参数（如 \"rustfmt --config-path ./rustfmt.toml\"），单个与批量模式均适用，失败时报告文件与命令输出"
+    )]
+    pub post_process: Option<String>,
+
+    #[arg(
+        long,
+        requires = "post_process",
+        help = "后处理命令从标准输入读取文件内容，以标准输出替换文件"
+    )]
+    pub post_process_stdin: bool,
+
+    #[arg(
+        long,
+        value_delimiter = ',',
+        default_value = "*.rs",
+        requires = "post_process",
+        help = "后处理的文件名模式，支持 * 通配符，逗号分隔"
+    )]
+    pub post_process_files: Vec<String>,
+
+    #[arg(
+        long,
+        value_enum,
+        help = "强制按 anchor / non-anchor 格式解析与生成，覆盖自动判定与 IDL 的 metadata.format（用于被误判的混合 IDL）"
+    )]
+    pub force_format: Option<FormatKind>,
+
+    #[arg(
+        long,
+        value_enum,
+        default_value_t = Engine::Minijinja,
+        help = "代码生成引擎（过渡期参数，legacy 保留旧版 README 输出）"
+    )]
+    pub engine: Engine,
+}
+
+// 子命令，与生成参数互斥（不用文档注释，避免覆盖顶层 about）
+#[derive(Subcommand, Debug, Clone)]
+pub enum CliCommand {
+    /// 在 IDL 方言之间转换（anchor-0.30 / anchor-legacy / shank）
+    Convert(convert::ConvertArgs),
+    /// 监听 IDL 目录，文件变化时重新生成对应 crate
+    Watch(watch::WatchArgs),
+    /// 输出指令、账户、类型与事件的依赖图（DOT / mermaid）
+    Graph(graph::GraphArgs),
+    /// 只打印单个指令、账户、类型或事件的生成代码
+    Snippet(snippet::SnippetArgs),
 }
 
 /// 获取用于错误显示的绝对路径字符串
@@ -323,8 +726,8 @@ fn setup_logging() {
         .init();
 
     // 终端只显示日志文件位置
-    println!("🔍 Debug日志输出到: {}/{}", current_dir, log_file_path);
-    println!("📊 当前日志级别: {}", log_level);
+    eprintln!("🔍 Debug日志输出到: {}/{}", current_dir, log_file_path);
+    eprintln!("📊 当前日志级别: {}", log_level);
 }
 
 /// The CLI entrypoint
@@ -337,7 +740,47 @@ pub fn main() {
     setup_logging();
     log_panics::init();
 
-    let args = Args::parse();
+    let matches = CliCommand::augment_subcommands(Args::command())
+        .args_conflicts_with_subcommands(true)
+        .subcommand_negates_reqs(true)
+        .get_matches();
+    if matches.subcommand_name().is_some() {
+        let command = CliCommand::from_arg_matches(&matches).unwrap_or_else(|e| e.exit());
+        match command {
+            CliCommand::Convert(convert_args) => match convert::run(&convert_args) {
+                Ok(warnings) => {
+                    for warning in warnings {
+                        eprintln!("⚠️  {}", warning);
+                    }
+                    println!("✅ {} -> {}", convert_args.input.display(), convert_args.output.display());
+                }
+                Err(e) => {
+                    eprintln!("{}", format_user_error(&e));
+                    std::process::exit(1);
+                }
+            },
+            CliCommand::Watch(watch_args) => {
+                if let Err(e) = watch::run(&watch_args) {
+                    eprintln!("{}", format_user_error(&e));
+                    std::process::exit(1);
+                }
+            }
+            CliCommand::Graph(graph_args) => {
+                if let Err(e) = graph::run(&graph_args) {
+                    eprintln!("{}", format_user_error(&e));
+                    std::process::exit(1);
+                }
+            }
+            CliCommand::Snippet(snippet_args) => {
+                if let Err(e) = snippet::run(&snippet_args) {
+                    eprintln!("{}", format_user_error(&e));
+                    std::process::exit(1);
+                }
+            }
+        }
+        return;
+    }
+    let args = Args::from_arg_matches(&matches).unwrap_or_else(|e| e.exit());
     
     // 🔍 简单的文件路径验证和调试输出
     if !args.batch {
@@ -360,12 +803,28 @@ pub fn main() {
         process_batch(args);
     } else {
         if let Err(e) = process_single_file(args) {
+            eprintln!("{}", format_user_error(&e));
             log::error!("处理文件失败: {}", e);
             std::process::exit(1);
         }
     }
 }
 
+/// `cargo solores` 入口：按项目配置重新生成接口 crate
+pub fn cargo_main() {
+    if env::var(RUST_LOG_ENV_VAR).is_err() {
+        env::set_var(RUST_LOG_ENV_VAR, "debug")
+    }
+    setup_logging();
+    log_panics::init();
+
+    let project::CargoCli::Solores(project_args) = project::CargoCli::parse();
+    if let Err(e) = project::run(&project_args) {
+        eprintln!("{}", format_user_error(&e));
+        std::process::exit(1);
+    }
+}
+
 /// Process a single IDL file (original functionality)
 fn process_single_file(mut args: Args) -> Result<(), SoloresError> {
     let mut file = OpenOptions::new()
@@ -379,7 +838,9 @@ fn process_single_file(mut args: Args) -> Result<(), SoloresError> {
             )
         })?;
 
-    let idl = load_idl_with_diagnostics(&mut file)?;
+    let (idl, detection) = load_idl_with_format(&mut file, args.force_format)?;
+    log::info!("IDL格式: {}", detection);
+    eprintln!("🔍 IDL格式: {}", detection);
 
     if args.output_crate_name == DEFAULT_OUTPUT_CRATE_NAME_MSG {
         args.output_crate_name = format!("sol_{}_interface", idl.program_name());
@@ -394,81 +855,38 @@ fn process_single_file(mut args: Args) -> Result<(), SoloresError> {
     });
 
     args.output_dir.push(&args.output_crate_name);
-    fs::create_dir_all(args.output_dir.join("src/")).map_err(|e| {
-        SoloresError::file_operation_error("创建输出目录", args.output_dir.display().to_string(), e)
-    })?;
-
-    // TODO: multithread, 1 thread per generated file
-    write_gitignore(&args).map_err(|e| {
-        SoloresError::file_operation_error(
-            "创建.gitignore文件",
-            args.output_dir.join(".gitignore").display().to_string(),
-            e,
-        )
-    })?;
 
-    // Choose appropriate Cargo.toml generation based on workspace mode
-    if should_use_workspace_cargo_toml(&args) {
-        write_workspace_member_cargo_toml(&args, idl.as_ref()).map_err(|e| {
-            SoloresError::file_operation_error(
-                "创建workspace Cargo.toml文件",
-                args.output_dir.join("Cargo.toml").display().to_string(),
-                e,
-            )
-        })?;
-    } else {
-        write_fine_grained_cargo_toml(&args, idl.as_ref()).map_err(|e| {
-            SoloresError::file_operation_error(
-                "创建Cargo.toml文件",
-                args.output_dir.join("Cargo.toml").display().to_string(),
-                e,
-            )
-        })?;
+    if args.dry_run {
+        let files = dry_run::plan(&args, &idl)?;
+        dry_run::print_plan(&args.output_dir, &files);
+        return Ok(());
     }
 
-    // 调用 write_lib，内部会根据配置选择合适的模板系统
-    log::info!("Writing lib.rs for IDL: {}", idl.program_name());
-    log::debug!("IDL address: {:?}", idl.program_address());
-    write_lib(&args, idl.as_ref()).map_err(|e| {
-        SoloresError::file_operation_error(
-            "创建lib.rs文件",
-            args.output_dir.join("src/lib.rs").display().to_string(),
-            e,
-        )
-    })?;
-    write_readme(&args, idl.as_ref()).map_err(|e| {
-        SoloresError::file_operation_error(
-            "创建README.md文件",
-            args.output_dir.join("README.md").display().to_string(),
-            e,
-        )
-    })?;
-
-    // Copy IDL file to output directory
-    let idl_dest = args.output_dir.join("idl.json");
-    if let Err(e) = std::fs::copy(&args.idl_path, &idl_dest) {
-        log::warn!("Failed to copy IDL file: {}", e);
-    } else {
-        log::info!("IDL file copied to {}", idl_dest.display());
+    if args.stdout_single_file || args.single_file.is_some() {
+        let (source, warnings) = build_rs::amalgamate(&args, &idl)?;
+        match &args.single_file {
+            Some(path) => {
+                std::fs::write(path, &source).map_err(|e| {
+                    SoloresError::file_operation_error("写入单文件输出", path.display().to_string(), e)
+                })?;
+                log::info!("{} written to {}", args.output_crate_name, path.display());
+            }
+            None => print!("{}", source),
+        }
+        warnings::print(&warnings);
+        return Ok(());
     }
 
-    // Format generated code with prettyplease
-    log::debug!("🎨 使用prettyplease格式化生成的代码...");
-
-    // 格式化所有生成的Rust文件
-    let src_dir = args.output_dir.join("src");
-    if src_dir.exists() {
-        format_rust_files_with_prettyplease(&src_dir)?;
-        log::info!("✅ 代码格式化完成 (prettyplease)");
-    } else {
-        log::warn!("⚠️ src目录不存在，跳过格式化");
-    }
+    log::info!("Generating crate for IDL: {}", idl.program_name());
+    log::debug!("IDL address: {:?}", idl.program_address());
+    let warnings = pipeline::run(&args, &idl)?;
 
     log::info!(
         "{} crate written to {}",
         args.output_crate_name,
         args.output_dir.to_string_lossy()
     );
+    warnings::print(&warnings);
 
     Ok(())
 }
@@ -486,111 +904,87 @@ fn process_batch(args: Args) {
     if !args.batch_include.is_empty() {
         log::info!("✅ 包含模式: {}", args.batch_include);
     }
+    if args.workspace {
+        log::info!("🏗️  Workspace模式已启用: '{}'", args.workspace_name);
+    }
 
-    // Initialize workspace configuration if enabled
-    let mut workspace_config = match validate_workspace_config(&args) {
-        Ok(Some(config)) => {
-            log::info!("🏗️  Workspace模式已启用: '{}'", config.name);
-            Some(config)
+    let has_filters = !args.batch_exclude.is_empty() || !args.batch_include.is_empty();
+    let unified_library = args.unified_library;
+    let output_dir = args.batch_output_dir.clone();
+    let result = batch::run_batch(batch::BatchOptions::from_args(args), |event| match event {
+        batch::BatchEvent::Started { total: 0 } => {
+            log::warn!("⚠️  未找到匹配的IDL文件");
+            if has_filters {
+                log::info!("💡 提示: 检查您的过滤模式是否正确");
+            }
         }
-        Ok(None) => None,
-        Err(e) => {
-            log::error!("❌ Workspace配置验证失败: {}", e);
-            panic!("Failed to validate workspace configuration: {}", e);
+        batch::BatchEvent::Started { total } if unified_library => {
+            log::info!("🚀 启用统一库生成模式，共 {} 个IDL文件", total);
         }
-    };
-
-    // Create batch output directory
-    if let Err(e) = fs::create_dir_all(&args.batch_output_dir) {
-        log::error!("❌ 无法创建批量输出目录: {}", e);
-        panic!("Failed to create batch output directory: {}", e);
-    }
+        batch::BatchEvent::Started { total } => {
+            log::info!("📋 找到 {} 个IDL文件待处理（应用过滤器后）", total);
+        }
+        batch::BatchEvent::FileStarted { index, total, idl_path } => {
+            log::info!("🔄 处理文件 {}/{}: {}", index + 1, total, idl_path.display());
+        }
+        batch::BatchEvent::FileSucceeded { generated, .. } => {
+            log::info!("✅ 成功生成: {}", generated.output_dir.display());
+        }
+        batch::BatchEvent::FileSkipped { generated, .. } => {
+            log::info!("⏭️  已完成，跳过: {}", generated.output_dir.display());
+        }
+        batch::BatchEvent::FileFailed { failure, .. } => {
+            log::error!("❌ 处理失败 {}: {}", failure.idl_path.display(), failure.error);
+        }
+        batch::BatchEvent::Finished { .. } => log::info!("🎉 批量处理完成!"),
+    });
 
-    // 扫描IDL文件并应用过滤器
-    let idl_files = scan_idl_files_with_filters(&args.idl_path, &args);
-    if idl_files.is_empty() {
-        log::warn!(
-            "⚠️  在目录 {} 中未找到匹配的IDL文件",
-            args.idl_path.display()
-        );
-        if !args.batch_exclude.is_empty() || !args.batch_include.is_empty() {
-            log::info!("💡 提示: 检查您的过滤模式是否正确");
+    let result = match result {
+        Ok(result) => result,
+        Err(e) => {
+            eprintln!("{}", format_user_error(&e));
+            log::error!("批量处理失败: {}", e);
+            std::process::exit(1);
         }
-        return;
-    }
+    };
 
-    // 检查是否启用统一库模式
-    if args.unified_library {
-        log::info!("🚀 启用统一库生成模式");
-        process_unified_library(args, &idl_files);
+    if let Some(dir) = &result.unified_library_dir {
+        log::info!("📁 统一库输出位置: {}", dir.display());
         return;
     }
 
-    log::info!(
-        "📋 找到 {} 个IDL文件待处理（应用过滤器后）",
-        idl_files.len()
-    );
-
-    let mut success_count = 0;
-    let mut failure_count = 0;
-    let mut failed_files = Vec::new();
-    let mut generated_crates = Vec::new();
-
-    for (idx, idl_file) in idl_files.iter().enumerate() {
-        log::info!(
-            "🔄 处理文件 {}/{}: {}",
-            idx + 1,
-            idl_files.len(),
-            idl_file.display()
-        );
-
-        match process_single_idl_file(&args, &idl_file) {
-            Ok(output_dir) => {
-                success_count += 1;
-                log::info!("✅ 成功生成: {}", output_dir.display());
-
-                // Add to workspace if enabled
-                if let Some(ref mut workspace) = workspace_config {
-                    if let Some(crate_name) = output_dir.file_name() {
-                        let crate_name_str = crate_name.to_string_lossy().to_string();
-                        add_workspace_member(workspace, crate_name_str.clone());
-                        generated_crates.push(crate_name_str);
-                    }
-                }
-            }
-            Err(e) => {
-                failure_count += 1;
-                log::error!("❌ 处理失败 {}: {}", idl_file.display(), e);
-                failed_files.push((idl_file.clone(), e));
-            }
-        }
-    }
-
-    // Finalize workspace if enabled
-    if let Some(workspace) = workspace_config {
-        if let Err(e) = finalize_workspace(&workspace) {
-            log::error!("❌ Workspace生成失败: {}", e);
+    // Print summary
+    log::info!("✅ 成功: {} 个文件", result.generated.len());
+    if !result.is_success() {
+        log::warn!("❌ 失败: {} 个文件", result.failures.len());
+        for failure in &result.failures {
+            log::warn!("   {} - {}", failure.idl_path.display(), failure.error);
         }
     }
 
-    // Print summary
-    log::info!("🎉 批量处理完成!");
-    log::info!("✅ 成功: {} 个文件", success_count);
-    if failure_count > 0 {
-        log::warn!("❌ 失败: {} 个文件", failure_count);
-        for (file, error) in failed_files {
-            log::warn!("   {} - {}", file.display(), error);
+    // 警告同时输出到终端，避免只留在日志文件中
+    let warned: Vec<_> = result.generated.iter().filter(|c| !c.warnings.is_empty()).collect();
+    if !warned.is_empty() {
+        let total = result.warning_count();
+        log::warn!("⚠️  警告: {} 个文件共 {} 条", warned.len(), total);
+        eprintln!("⚠️  {} 个文件存在 {} 条生成警告:", warned.len(), total);
+        for generated in &warned {
+            eprintln!("   {}", generated.idl_path.display());
+            for warning in &generated.warnings {
+                log::warn!("   {} - {}", generated.idl_path.display(), warning);
+                eprintln!("     {}", warning);
+            }
         }
     }
 
-    if !generated_crates.is_empty() {
-        log::info!("📦 生成的crate:");
-        for crate_name in &generated_crates {
-            log::info!("   - {}", crate_name);
+    if let Some(dir) = &result.workspace_dir {
+        log::info!("📦 Workspace: {}", dir.display());
+        for generated in &result.generated {
+            log::info!("   - {}", generated.crate_name);
         }
     }
 
-    log::info!("📁 所有生成的库位于: {}", args.batch_output_dir.display());
+    log::info!("📁 所有生成的库位于: {}", output_dir.display());
 }
 
 /// 解析文件模式字符串
@@ -677,23 +1071,20 @@ fn scan_idl_files(dir_path: &PathBuf) -> Vec<PathBuf> {
 
     match fs::read_dir(dir_path) {
         Ok(entries) => {
-            for entry in entries {
-                if let Ok(entry) = entry {
-                    let path = entry.path();
-                    if path.is_file() {
-                        if let Some(extension) = path.extension() {
-                            if extension == "json" {
-                                // Quick validation - check if it's a valid JSON file
-                                if let Ok(mut file) = File::open(&path) {
-                                    let mut content = String::new();
-                                    if file.read_to_string(&mut content).is_ok() {
-                                        if let Ok(_) =
-                                            serde_json::from_str::<serde_json::Value>(&content)
-                                        {
-                                            idl_files.push(path);
-                                        } else {
-                                            log::debug!("⚠️  跳过无效JSON文件: {}", path.display());
-                                        }
+            for entry in entries.flatten() {
+                let path = entry.path();
+                if path.is_file() {
+                    if let Some(extension) = path.extension() {
+                        if extension == "json" {
+                            // Quick validation - check if it's a valid JSON file
+                            if let Ok(mut file) = File::open(&path) {
+                                let mut content = String::new();
+                                if file.read_to_string(&mut content).is_ok() {
+                                    if serde_json::from_str::<serde_json::Value>(&content).is_ok()
+                                    {
+                                        idl_files.push(path);
+                                    } else {
+                                        log::debug!("⚠️  跳过无效JSON文件: {}", path.display());
                                     }
                                 }
                             }
@@ -713,24 +1104,19 @@ fn scan_idl_files(dir_path: &PathBuf) -> Vec<PathBuf> {
 }
 
 /// Process a single IDL file for batch mode
-fn process_single_idl_file(base_args: &Args, idl_file_path: &PathBuf) -> Result<PathBuf, String> {
+///
+/// 返回生成的 crate 目录与未屏蔽的生成警告
+fn process_single_idl_file(base_args: &Args, idl_file_path: &Path) -> Result<(PathBuf, Vec<warnings::Warning>), SoloresError> {
     // Clone base args and customize for this specific file
     let mut args = base_args.clone();
-    args.idl_path = idl_file_path.clone();
+    args.idl_path = idl_file_path.to_path_buf();
 
     // Load and validate IDL
-    let mut file = match OpenOptions::new().read(true).open(&args.idl_path) {
-        Ok(f) => f,
-        Err(e) => {
-            let abs_path = get_absolute_path_for_error(&args.idl_path);
-            return Err(format!("无法打开文件 {}: {}", abs_path, e));
-        }
-    };
-
-    let idl = match load_idl_safely(&mut file) {
-        Ok(idl) => idl,
-        Err(e) => return Err(format!("IDL解析失败: {}", e)),
-    };
+    let mut file = OpenOptions::new().read(true).open(&args.idl_path).map_err(|e| {
+        SoloresError::file_operation_error("读取IDL文件", get_absolute_path_for_error(&args.idl_path), e)
+    })?;
+    let (idl, detection) = load_idl_with_format(&mut file, args.force_format)?;
+    log::info!("🔍 {} IDL格式: {}", idl.program_name(), detection);
 
     // Generate output crate name
     if args.output_crate_name == DEFAULT_OUTPUT_CRATE_NAME_MSG {
@@ -738,7 +1124,10 @@ fn process_single_idl_file(base_args: &Args, idl_file_path: &PathBuf) -> Result<
     }
 
     // Set up output directory in batch output directory
-    args.output_dir = base_args.batch_output_dir.join(&args.output_crate_name);
+    args.output_dir = match base_args.group_output_by.group_dir(idl_file_path, &idl) {
+        Some(group) => base_args.batch_output_dir.join(group).join(&args.output_crate_name),
+        None => base_args.batch_output_dir.join(&args.output_crate_name),
+    };
 
     // Handle program ID
     args.program_id = args.program_id.and_then(|s| {
@@ -749,70 +1138,8 @@ fn process_single_idl_file(base_args: &Args, idl_file_path: &PathBuf) -> Result<
         }
     });
 
-    // Create output directory
-    if let Err(e) = fs::create_dir_all(args.output_dir.join("src/")) {
-        return Err(format!("无法创建输出目录: {}", e));
-    }
-
-    // Generate files
-    if let Err(e) = write_gitignore(&args) {
-        return Err(format!("生成.gitignore失败: {}", e));
-    }
-
-    // Choose appropriate Cargo.toml generation based on workspace mode
-    if should_use_workspace_cargo_toml(&args) {
-        if let Err(e) = write_workspace_member_cargo_toml(&args, idl.as_ref()) {
-            return Err(format!("生成workspace成员Cargo.toml失败: {}", e));
-        }
-    } else {
-        if let Err(e) = write_fine_grained_cargo_toml(&args, idl.as_ref()) {
-            return Err(format!("生成Cargo.toml失败: {}", e));
-        }
-    }
-
-    if let Err(e) = write_lib(&args, idl.as_ref()) {
-        return Err(format!("生成lib.rs失败: {}", e));
-    }
-
-    if let Err(e) = write_readme(&args, idl.as_ref()) {
-        return Err(format!("生成README.md失败: {}", e));
-    }
-
-    // Copy IDL file
-    let idl_dest = args.output_dir.join("idl.json");
-    if let Err(e) = std::fs::copy(&args.idl_path, &idl_dest) {
-        log::warn!("复制IDL文件失败: {}", e);
-    }
-
-    // Format generated code with prettyplease
-    log::debug!("🎨 使用prettyplease格式化生成的代码...");
-
-    // 格式化所有生成的Rust文件
-    let output_src_dir = args.output_dir.join("src");
-    if output_src_dir.exists() {
-        if let Err(e) = format_rust_files_with_prettyplease(&output_src_dir) {
-            log::warn!("⚠️ prettyplease格式化失败: {}，尝试使用cargo fmt", e);
-            // Fallback to cargo fmt if prettyplease fails
-            let _ = Command::new("cargo")
-                .args(&["fmt"])
-                .current_dir(&args.output_dir)
-                .output();
-        } else {
-            log::debug!("✅ 代码格式化完成 (prettyplease)");
-        }
-    } else {
-        log::warn!("⚠️ src目录不存在，跳过格式化");
-    }
-
-    Ok(args.output_dir)
-}
-
-/// Safe IDL loading that doesn't panic
-fn load_idl_safely(file: &mut File) -> Result<Box<dyn IdlFormat>, String> {
-    match load_idl_with_diagnostics(file) {
-        Ok(idl) => Ok(idl),
-        Err(e) => Err(format!("{}", e)),
-    }
+    let warnings = pipeline::run_with(&args, &idl, pipeline::FormatFallback::CargoFmt)?;
+    Ok((args.output_dir, warnings))
 }
 
 pub fn load_idl(file: &mut File) -> Box<dyn IdlFormat> {
@@ -853,6 +1180,14 @@ pub fn load_idl_with_diagnostics(file: &mut File) -> Result<Box<dyn IdlFormat>,
 
 /// 加载IDL文件并返回IdlFormatEnum（用于统一库生成）
 pub fn load_idl_as_enum(file: &mut File) -> Result<IdlFormatEnum, SoloresError> {
+    load_idl_with_format(file, None).map(|(idl, _)| idl)
+}
+
+/// 加载IDL文件，`force_format` 覆盖格式判定；同时返回判定依据
+pub fn load_idl_with_format(
+    file: &mut File,
+    force_format: Option<FormatKind>,
+) -> Result<(IdlFormatEnum, FormatDetection), SoloresError> {
     log::debug!("开始IDL解析诊断流程（返回枚举）");
 
     // 1. 读取文件内容
@@ -874,7 +1209,7 @@ pub fn load_idl_as_enum(file: &mut File) -> Result<IdlFormatEnum, SoloresError>
     log::debug!("IDL基本结构验证通过");
 
     // 4. 解析为IdlFormatEnum
-    parse_idl_json(&content).map_err(|e| {
+    parse_idl_json_with_format(&content, force_format).map_err(|e| {
         log::error!("IDL格式解析失败");
         let error_msg = e.to_string();
         if error_msg.contains("duplicate field") {
@@ -1030,7 +1365,7 @@ fn format_rust_files_with_prettyplease(dir: &Path) -> Result<(), SoloresError> {
         if path.is_dir() {
             // 递归处理子目录
             format_rust_files_with_prettyplease(&path)?;
-        } else if path.extension().map_or(false, |ext| ext == "rs") {
+        } else if path.extension().is_some_and(|ext| ext == "rs") {
             // 格式化Rust文件
             format_single_rust_file(&path)?;
         }
@@ -1083,22 +1418,15 @@ fn format_single_rust_file(file_path: &Path) -> Result<(), SoloresError> {
     Ok(())
 }
 
-/// 处理统一库生成
-fn process_unified_library(args: Args, idl_files: &[PathBuf]) {
+/// 处理统一库生成，返回生成的库目录；没有任何协议组时返回 None
+fn process_unified_library(args: Args, idl_files: &[PathBuf]) -> Result<Option<PathBuf>, SoloresError> {
     log::info!("🔄 开始统一库生成流程");
-    
-    // 自动分组协议
-    let protocol_groups = match auto_group_protocols(idl_files) {
-        Ok(groups) => groups,
-        Err(e) => {
-            log::error!("❌ 协议分组失败: {}", e);
-            return;
-        }
-    };
 
+    // 自动分组协议
+    let protocol_groups = auto_group_protocols(idl_files, args.force_format)?;
     if protocol_groups.is_empty() {
         log::warn!("⚠️  没有找到任何协议组");
-        return;
+        return Ok(None);
     }
 
     // 创建统一库配置
@@ -1110,13 +1438,9 @@ fn process_unified_library(args: Args, idl_files: &[PathBuf]) {
     };
 
     // 生成统一库
-    match minijinja::generator::MinijinjaTemplateGenerator::generate_unified_library(&config) {
-        Ok(()) => {
-            log::info!("✅ 统一库生成成功");
-            log::info!("📁 输出位置: {}/{}", config.output_dir.display(), config.library_name);
-        }
-        Err(e) => {
-            log::error!("❌ 统一库生成失败: {}", e);
-        }
-    }
+    minijinja::generator::MinijinjaTemplateGenerator::generate_unified_library(&config)?;
+    let library_dir = config.output_dir.join(&config.library_name);
+    post_process::run(&library_dir, &config.base_args)?;
+    log::info!("✅ 统一库生成成功");
+    Ok(Some(library_dir))
 }
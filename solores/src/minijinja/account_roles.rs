@@ -0,0 +1,101 @@
+//! 语义账户角色的 Pubkey 新类型
+//!
+//! `--typed-keys` 时，为出现在至少 [`MIN_INSTRUCTIONS`] 个指令中的账户名生成
+//! `#[repr(transparent)]` 新类型（如 `MsolMint(Pubkey)`），Keys 结构体对应字段改用该类型，
+//! 账户多的指令中传错位置的 Pubkey 会在编译期报错。
+
+use std::collections::BTreeMap;
+
+use convert_case::{Case, Casing};
+use minijinja::{context, Value};
+
+/// 账户名至少出现在多少个指令中才生成新类型
+pub const MIN_INSTRUCTIONS: usize = 3;
+
+/// 按账户字段名（snake_case）收集需要生成新类型的账户角色，按字段名排序
+pub fn resolve_typed_keys(context: &Value) -> Vec<Value> {
+    let mut usages: BTreeMap<String, Vec<String>> = BTreeMap::new();
+    let instructions = context
+        .get_attr("instructions")
+        .ok()
+        .and_then(|instructions| instructions.try_iter().ok())
+        .into_iter()
+        .flatten();
+    for instruction in instructions {
+        let instruction_name = attr_str(&instruction, "name").unwrap_or_default();
+        let accounts = instruction
+            .get_attr("accounts")
+            .ok()
+            .and_then(|accounts| accounts.try_iter().ok())
+            .into_iter()
+            .flatten();
+        for account in accounts {
+            let Some(field) = attr_str(&account, "name").map(|name| name.to_case(Case::Snake)) else {
+                continue;
+            };
+            let users = usages.entry(field).or_default();
+            if users.last() != Some(&instruction_name) {
+                users.push(instruction_name.clone());
+            }
+        }
+    }
+
+    usages
+        .into_iter()
+        .filter(|(_, instructions)| instructions.len() >= MIN_INSTRUCTIONS)
+        .map(|(field, instructions)| {
+            context! {
+                name => field.to_case(Case::Pascal),
+                field => field,
+                instructions => instructions,
+            }
+        })
+        .collect()
+}
+
+/// 字段名 → 新类型名，供指令模板查找
+pub fn typed_key_types(typed_keys: &[Value]) -> BTreeMap<String, String> {
+    typed_keys
+        .iter()
+        .filter_map(|key| Some((attr_str(key, "field")?, attr_str(key, "name")?)))
+        .collect()
+}
+
+fn attr_str(value: &Value, name: &str) -> Option<String> {
+    value
+        .get_attr(name)
+        .ok()
+        .and_then(|attr| attr.as_str().map(str::to_string))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn shared_accounts_get_newtypes() {
+        let ix = |name: &str, accounts: &[&str]| {
+            context! {
+                name => name,
+                accounts => accounts.iter().map(|a| context! { name => *a }).collect::<Vec<_>>(),
+            }
+        };
+        let context = context! {
+            instructions => vec![
+                ix("Deposit", &["state", "msolMint", "user"]),
+                ix("Withdraw", &["state", "msol_mint", "user"]),
+                ix("Stake", &["state", "msolMint"]),
+            ],
+        };
+
+        let keys = resolve_typed_keys(&context);
+        let types = typed_key_types(&keys);
+        assert_eq!(
+            types.into_iter().collect::<Vec<_>>(),
+            vec![
+                ("msol_mint".to_string(), "MsolMint".to_string()),
+                ("state".to_string(), "State".to_string()),
+            ]
+        );
+    }
+}
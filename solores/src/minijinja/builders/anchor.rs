@@ -2,14 +2,16 @@
 //! 
 //! 负责将 Anchor IDL 数据结构转换为模板可用的 Value 对象
 
-use crate::idl_format::{IdlFormatEnum, anchor_idl::*};
-use crate::minijinja::generators::pda::PdaInfo;
+use crate::idl_format::{IdlFormatEnum, account_variants, anchor_idl::*, arg_encoding::ArgEncoding, docs::join_docs, enum_tags::EnumTagLayout};
+use crate::minijinja::generators::pda::{derived_keys_plan, PdaInfo};
 use convert_case::{Case, Casing};
 use minijinja::{context, Value};
 use log;
 use super::super::utils;
-use super::super::filters::extract_smallvec_inner;
-use std::collections::HashMap;
+use super::super::type_mapper;
+use super::super::zero_copy::{account_view, field_offsets, lazy_view, ViewField};
+use crate::idl_format::zero_copy;
+use std::collections::{HashMap, HashSet};
 
 /// 类型特征支持注册表
 #[derive(Debug, Clone)]
@@ -18,6 +20,14 @@ pub struct TypeTraitRegistry {
     pub copy_supported: HashMap<String, bool>,
     /// 记录哪些类型支持Eq trait  
     pub eq_supported: HashMap<String, bool>,
+    /// 不支持Hash trait的类型（Rust 类型名），见 [`utils::idl_unhashable_types`]
+    pub hash_unsupported: HashSet<String>,
+}
+
+impl Default for TypeTraitRegistry {
+    fn default() -> Self {
+        Self::new()
+    }
 }
 
 impl TypeTraitRegistry {
@@ -25,6 +35,7 @@ impl TypeTraitRegistry {
         Self {
             copy_supported: HashMap::new(),
             eq_supported: HashMap::new(),
+            hash_unsupported: HashSet::new(),
         }
     }
     
@@ -66,6 +77,7 @@ impl TypeTraitRegistry {
         
         // 进行递归依赖解析，多次迭代直到稳定
         registry.resolve_recursive_dependencies(idl_enum);
+        registry.hash_unsupported = utils::idl_unhashable_types(idl_enum);
         registry
     }
     
@@ -125,6 +137,11 @@ impl TypeTraitRegistry {
         self.eq_supported.get(type_name).copied().unwrap_or(true)
     }
     
+    /// 检查类型是否支持Hash trait（Eq 兼容且不含 HashMap）
+    pub fn is_type_hash_compatible(&self, type_name: &str) -> bool {
+        self.is_type_eq_compatible(type_name) && !self.hash_unsupported.contains(&type_name.to_case(Case::Pascal))
+    }
+    
     /// 使用注册表检查字段类型是否支持Copy trait
     fn is_field_copy_compatible_with_registry(&self, field_type: &AnchorFieldType) -> bool {
         match field_type {
@@ -142,7 +159,7 @@ impl TypeTraitRegistry {
             AnchorFieldType::array(element_type, _size) => {
                 self.is_field_copy_compatible_with_registry(element_type)
             },
-            AnchorFieldType::option(inner_type) => {
+            AnchorFieldType::option(inner_type) | AnchorFieldType::coption(inner_type) => {
                 self.is_field_copy_compatible_with_registry(inner_type)
             },
             AnchorFieldType::vec(_) => false,
@@ -160,11 +177,7 @@ impl TypeTraitRegistry {
                 } else if name.starts_with('[') && name.contains(';') && name.contains(']') {
                     // 数组类型检查：现代Rust支持所有大小数组的Copy trait
                     if let Some((_, size_str)) = extract_array_parts_from_string(name) {
-                        if let Ok(_size) = size_str.parse::<usize>() {
-                            true
-                        } else {
-                            false
-                        }
+                        size_str.parse::<usize>().is_ok()
                     } else {
                         false
                     }
@@ -187,7 +200,7 @@ impl TypeTraitRegistry {
             AnchorFieldType::array(element_type, _size) => {
                 self.is_field_eq_compatible_with_registry(element_type)
             },
-            AnchorFieldType::option(inner_type) => {
+            AnchorFieldType::option(inner_type) | AnchorFieldType::coption(inner_type) => {
                 self.is_field_eq_compatible_with_registry(inner_type)
             },
             AnchorFieldType::vec(element_type) => {
@@ -213,11 +226,17 @@ impl TypeTraitRegistry {
         fields.iter().all(|field| self.is_field_copy_compatible_with_registry(&field.field_type))
     }
     
-    /// 使用注册表检查struct是否支持Eq trait
-    fn is_struct_eq_compatible_with_registry(&self, fields: &[AnchorField]) -> bool {
+    /// 使用注册表检查struct是否支持Eq trait（账户、事件与指令参数同样适用）
+    pub fn is_struct_eq_compatible_with_registry(&self, fields: &[AnchorField]) -> bool {
         fields.iter().all(|field| self.is_field_eq_compatible_with_registry(&field.field_type))
     }
     
+    /// 使用注册表检查struct是否支持Hash trait：Eq 兼容的字段中只有 HashMap 不支持 Hash
+    pub fn is_struct_hash_compatible_with_registry(&self, fields: &[AnchorField]) -> bool {
+        self.is_struct_eq_compatible_with_registry(fields)
+            && utils::are_field_types_hashable(fields.iter().map(|field| &field.field_type), &self.hash_unsupported)
+    }
+    
     /// 使用注册表检查enum是否支持Copy trait
     fn is_enum_copy_compatible_with_registry(&self, variants: &[AnchorEnumVariant]) -> bool {
         variants.iter().all(|variant| {
@@ -239,45 +258,84 @@ impl TypeTraitRegistry {
             }
         })
     }
+    
+    /// 使用注册表检查enum是否支持Hash trait
+    fn is_enum_hash_compatible_with_registry(&self, variants: &[AnchorEnumVariant]) -> bool {
+        self.is_enum_eq_compatible_with_registry(variants)
+            && utils::are_field_types_hashable(
+                variants.iter().flat_map(|variant| variant.fields.iter().flatten()).map(|field| &field.field_type),
+                &self.hash_unsupported,
+            )
+    }
 }
 
 /// 构建账户Value，确保字段信息完整并修复命名问题
-pub fn build_account_value(account: &AnchorAccount, idl_enum: &IdlFormatEnum) -> Value {
-    // 首先尝试使用账户自己的字段
-    let mut fields: Vec<Value> = if let Some(ref fields_vec) = account.fields {
-        log::debug!("  └─ Account {} 有 {} 个直接字段", account.name, fields_vec.len());
-        fields_vec.iter().map(|field| {
-            build_field_value(field)
-        }).collect()
-    } else {
-        Vec::new()
-    };
-
-    // 如果账户没有字段，尝试从当前IDL的types中查找同名类型的字段
-    if fields.is_empty() {
-        if let Some(matching_fields) = utils::find_fields_from_types(&account.name, idl_enum) {
-            log::debug!("  └─ Account {} 从types获取 {} 个字段", account.name, matching_fields.len());
-            fields = matching_fields;
-        } else {
-            log::debug!("  └─ Account {} 无可用字段", account.name);
-        }
-    }
+pub fn build_account_value(account: &AnchorAccount, idl_enum: &IdlFormatEnum, registry: &TypeTraitRegistry) -> Value {
+    // 字段已在解析阶段统一（内联type或引用同名types）
+    let fields: Vec<Value> = account.fields.iter().flatten().map(build_field_value).collect();
+    log::debug!("  └─ Account {} 有 {} 个字段", account.name, fields.len());
 
     // 计算packed_size
     let packed_size = utils::calculate_anchor_account_packed_size(account, idl_enum);
     log::debug!("🎯 Account {} 计算得到 PACKED_LEN: {} 字节", account.name, packed_size);
 
+    let layout_fixed = utils::fields_have_fixed_layout(&fields);
+
+    let len_range = utils::anchor_account_len_range(account, idl_enum);
+    let view_fields: Vec<ViewField> = match idl_enum {
+        IdlFormatEnum::Anchor(idl) => account.fields.iter().flatten().map(|field| ViewField {
+            name: field.name.to_case(Case::Snake),
+            rust_type: convert_field_type_to_rust(&field.field_type),
+            size: zero_copy::anchor_fixed_size(&field.field_type, idl),
+        }).collect(),
+        _ => Vec::new(),
+    };
+    let field_offsets = field_offsets(&view_fields, 8);
+    let lazy_view = lazy_view(&view_fields, 8);
+    let zero_copy_view = if account.zero_copy {
+        account_view(&account.name, view_fields, 8)
+    } else {
+        None
+    };
+    let variants = match (idl_enum, &account.variants) {
+        (IdlFormatEnum::Anchor(idl), Some(variants)) => account_variants::resolve_anchor(variants, idl).ok().map(|resolved| {
+            let layout_fields: Vec<Vec<(String, String)>> = resolved
+                .layouts
+                .iter()
+                .map(|(_, name)| {
+                    account_variants::anchor_layout_fields(idl, name)
+                        .unwrap_or_default()
+                        .iter()
+                        .map(|field| (field.name.to_case(Case::Snake), convert_field_type_to_rust(&field.field_type)))
+                        .collect()
+                })
+                .collect();
+            utils::account_variants_value(&resolved, &layout_fields, 8)
+        }),
+        _ => None,
+    };
+
     context! {
         name => account.name.to_case(Case::Pascal),  // 确保PascalCase
         discriminator => account.discriminator,
         fields => fields,
         packed_size => packed_size,
-        docs => account.docs.as_ref().map(|docs| docs.join("\n")).unwrap_or_default()
+        min_len => len_range.min,
+        max_len => len_range.max,
+        variable_len => !len_range.is_fixed(),
+        layout_fixed => layout_fixed,
+        zero_copy_view => zero_copy_view,
+        lazy_view => lazy_view,
+        field_offsets => field_offsets,
+        variants => variants,
+        can_eq => registry.is_struct_eq_compatible_with_registry(account.fields.as_deref().unwrap_or_default()),
+        can_hash => registry.is_struct_hash_compatible_with_registry(account.fields.as_deref().unwrap_or_default()),
+        docs => join_docs(account.docs.as_ref())
     }
 }
 
 /// 构建指令Value，修复命名和字段问题
-pub fn build_instruction_value(instruction: &AnchorInstruction) -> Value {
+pub fn build_instruction_value(instruction: &AnchorInstruction, idl_enum: &IdlFormatEnum, registry: &TypeTraitRegistry) -> Value {
     let args: Vec<Value> = if let Some(ref args_vec) = instruction.args {
         log::debug!("  └─ Instruction {} 有 {} 个参数", instruction.name, args_vec.len());
         args_vec.iter().map(|field| {
@@ -288,9 +346,14 @@ pub fn build_instruction_value(instruction: &AnchorInstruction) -> Value {
         Vec::new()
     };
 
+    let event_cpi = event_cpi_roles(instruction.accounts.as_deref().unwrap_or_default());
+    let checks = relation_checks(instruction.accounts.as_deref().unwrap_or_default(), idl_enum);
     let accounts: Vec<Value> = if let Some(ref accounts_vec) = instruction.accounts {
-        accounts_vec.iter().map(|acc| {
-            let mut account_value = Value::from_serialize(acc);
+        accounts_vec.iter().zip(&event_cpi).map(|(acc, role)| {
+            let mut acc_map = match serde_json::to_value(acc) {
+                Ok(serde_json::Value::Object(map)) => map,
+                _ => serde_json::Map::new(),
+            };
             
             // 如果账户有PDA定义，添加PDA处理信息
             if let Some(pda_def) = &acc.pda {
@@ -309,34 +372,178 @@ pub fn build_instruction_value(instruction: &AnchorInstruction) -> Value {
                         seeds_code => pda_info.seeds_code
                     };
                     
-                    // 创建包含PDA信息的新上下文
-                    if let Ok(mut acc_map) = serde_json::from_value::<serde_json::Map<String, serde_json::Value>>(
-                        serde_json::to_value(acc).unwrap_or_default()
-                    ) {
-                        acc_map.insert("pda".to_string(), serde_json::to_value(pda_context).unwrap_or_default());
-                        account_value = Value::from_serialize(&acc_map);
-                    }
+                    acc_map.insert("pda".to_string(), serde_json::to_value(pda_context).unwrap_or_default());
                 }
             }
+            if let Some(role) = role {
+                acc_map.insert("event_cpi".to_string(), serde_json::Value::from(*role));
+            }
+            if let Some(relations) = &acc.relations {
+                let account = acc.name.to_case(Case::Snake);
+                let docs: Vec<String> = relations
+                    .iter()
+                    .map(|relation| {
+                        let state = relation.to_case(Case::Snake);
+                        match checks.iter().find(|check| check.state == state && check.account == account) {
+                            Some(check) => format!(
+                                "Must equal `{}.{}`, see [`{}Keys::verify_relations`]",
+                                state,
+                                check.field,
+                                instruction.name.to_case(Case::Pascal)
+                            ),
+                            None => format!("Related to the `{}` account (`relations` in the IDL)", state),
+                        }
+                    })
+                    .collect();
+                acc_map.insert("relation_docs".to_string(), serde_json::Value::from(docs));
+            }
             
-            account_value
+            Value::from_serialize(&acc_map)
         }).collect()
     } else {
         Vec::new()
     };
 
+    let has_fixed_addresses = instruction.accounts.iter().flatten().any(|acc| acc.address.is_some());
+    let derived_keys = derived_keys_plan(
+        &instruction.name,
+        instruction.accounts.as_deref().unwrap_or_default(),
+        instruction.args.as_deref().unwrap_or_default(),
+        &event_cpi,
+    );
+
     context! {
         name => instruction.name.to_case(Case::Pascal),  // 修复PascalCase命名
         discriminator => instruction.discriminator,
+        has_fixed_addresses => has_fixed_addresses,
+        has_event_cpi => event_cpi.iter().any(Option::is_some),
+        derived_keys => derived_keys,
+        relation_checks => relation_checks_value(&checks),
+        data_min_len => 8 + utils::anchor_args_min_len(instruction.args.as_deref().unwrap_or_default(), idl_enum),
         args => args.clone(),
         fields => args,  // 模板中使用fields，确保字段数据传递
         accounts => accounts,
-        docs => instruction.docs.as_ref().map(|docs| docs.join("\n")).unwrap_or_default()
+        can_eq => registry.is_struct_eq_compatible_with_registry(instruction.args.as_deref().unwrap_or_default()),
+        can_hash => registry.is_struct_hash_compatible_with_registry(instruction.args.as_deref().unwrap_or_default()),
+        docs => join_docs(instruction.docs.as_ref())
+    }
+}
+
+/// `#[event_cpi]` 自动添加的账户：seed 为 `__event_authority` 的本程序PDA `event_authority`，
+/// 以及紧随其后的本程序账户 `program`。旧版IDL不带PDA信息时按名称识别
+pub fn event_cpi_roles(accounts: &[AnchorAccountConstraint]) -> Vec<Option<&'static str>> {
+    let mut roles = vec![None; accounts.len()];
+    for (index, account) in accounts.iter().enumerate() {
+        if account.name.to_case(Case::Snake) != "event_authority" {
+            continue;
+        }
+        let Some(program) = accounts.get(index + 1).filter(|next| next.name == "program") else {
+            continue;
+        };
+        let seeds_match = match &account.pda {
+            Some(pda) => {
+                pda.program.is_none()
+                    && matches!(pda.seeds.as_slice(), [PdaSeed::Const { value }] if value == b"__event_authority")
+            }
+            None => account.address.is_none(),
+        };
+        if seeds_match && !program.is_mut && !program.is_signer {
+            roles[index] = Some("event_authority");
+            roles[index + 1] = Some("program");
+        }
+    }
+    roles
+}
+
+/// `relations` 声明的一项可校验关系：`account` 的地址须等于 `state` 账户状态的 `field` 字段
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct RelationCheck {
+    /// 被约束的指令账户（snake_case）
+    pub account: String,
+    /// 相关的指令账户（snake_case）
+    pub state: String,
+    /// 相关账户的类型名
+    pub state_type: String,
+    /// 状态中的 Pubkey 字段（snake_case）
+    pub field: String,
+}
+
+/// 解析指令账户的 `relations`：相关账户须是本指令的账户、有同名账户类型，且该类型有与被约束账户
+/// 同名的 Pubkey 字段；无法解析的关系只写入文档
+pub fn relation_checks(accounts: &[AnchorAccountConstraint], idl_enum: &IdlFormatEnum) -> Vec<RelationCheck> {
+    let IdlFormatEnum::Anchor(idl) = idl_enum else {
+        return Vec::new();
+    };
+    let mut checks = Vec::new();
+    for account in accounts {
+        let name = account.name.to_case(Case::Snake);
+        for relation in account.relations.iter().flatten() {
+            let state = relation.to_case(Case::Snake);
+            if !accounts.iter().any(|a| a.name.to_case(Case::Snake) == state) {
+                continue;
+            }
+            let state_type = account
+                .relation_types
+                .get(relation)
+                .cloned()
+                .unwrap_or_else(|| relation.to_case(Case::Pascal));
+            let Some(state_account) =
+                idl.accounts.iter().flatten().find(|a| a.name.to_case(Case::Pascal) == state_type)
+            else {
+                continue;
+            };
+            let is_pubkey_field = state_account.fields.iter().flatten().any(|field| {
+                field.name.to_case(Case::Snake) == name
+                    && convert_field_type_to_rust(&field.field_type) == "solana_pubkey::Pubkey"
+            });
+            if is_pubkey_field {
+                checks.push(RelationCheck {
+                    account: name.clone(),
+                    state,
+                    state_type: state_account.name.to_case(Case::Pascal),
+                    field: name.clone(),
+                });
+            }
+        }
     }
+    checks
+}
+
+/// 按相关账户分组的模板上下文：`[{state, state_type, checks: [{account, field}]}]`，
+/// 分组顺序为相关账户首次出现的顺序
+fn relation_checks_value(checks: &[RelationCheck]) -> Vec<Value> {
+    let mut states: Vec<(&str, &str)> = Vec::new();
+    for check in checks {
+        if !states.iter().any(|(state, _)| *state == check.state) {
+            states.push((&check.state, &check.state_type));
+        }
+    }
+    states
+        .into_iter()
+        .map(|(state, state_type)| {
+            let group: Vec<Value> = checks
+                .iter()
+                .filter(|check| check.state == state)
+                .map(|check| context! { account => check.account, field => check.field })
+                .collect();
+            context! { state => state, state_type => state_type, checks => group }
+        })
+        .collect()
+}
+
+/// IDL 中是否有指令使用 `#[event_cpi]`
+pub fn idl_has_event_cpi(idl_enum: &IdlFormatEnum) -> bool {
+    let IdlFormatEnum::Anchor(idl) = idl_enum else {
+        return false;
+    };
+    idl.instructions
+        .iter()
+        .flatten()
+        .any(|ix| event_cpi_roles(ix.accounts.as_deref().unwrap_or_default()).iter().any(Option::is_some))
 }
 
 /// 构建事件Value，确保字段完整
-pub fn build_event_value(event: &AnchorEvent, idl_enum: &IdlFormatEnum) -> Value {
+pub fn build_event_value(event: &AnchorEvent, idl_enum: &IdlFormatEnum, registry: &TypeTraitRegistry) -> Value {
     let mut fields: Vec<Value> = if let Some(ref fields_vec) = event.fields {
         log::debug!("  └─ Event {} 有 {} 个直接字段", event.name, fields_vec.len());
         fields_vec.iter().map(|field| {
@@ -357,11 +564,22 @@ pub fn build_event_value(event: &AnchorEvent, idl_enum: &IdlFormatEnum) -> Value
         }
     }
 
+    // 字段取自同名类型时沿用该类型的Eq / Hash支持情况
+    let (can_eq, can_hash) = match event.fields {
+        Some(ref fields_vec) if !fields_vec.is_empty() => (
+            registry.is_struct_eq_compatible_with_registry(fields_vec),
+            registry.is_struct_hash_compatible_with_registry(fields_vec),
+        ),
+        _ => (registry.is_type_eq_compatible(&event.name), registry.is_type_hash_compatible(&event.name)),
+    };
+
     context! {
         name => event.name.to_case(Case::Pascal),  // 确保PascalCase
         discriminator => event.discriminator,
         fields => fields,
-        docs => event.docs.as_ref().map(|docs| docs.join("\n")).unwrap_or_default()
+        can_eq => can_eq,
+        can_hash => can_hash,
+        docs => join_docs(event.docs.as_ref())
     }
 }
 
@@ -381,22 +599,26 @@ pub fn build_type_value(type_def: &AnchorType) -> Value {
                 name => type_def.name.to_case(Case::Pascal),
                 fields => fields_values,
                 kind => "struct",
+                shape => utils::struct_shape(fields.iter().map(|field| field.name.as_str())),
                 can_copy => can_copy,
                 can_eq => can_eq,
-                docs => type_def.docs.as_ref().map(|docs| docs.join("\n")).unwrap_or_default()
+                docs => join_docs(type_def.docs.as_ref()),
+                ..type_layout_value(type_def)
             }
         },
         Some(AnchorTypeKind::Enum(variants)) => {
-            let variants_values: Vec<Value> = variants.iter().map(|variant| {
+            let tags = EnumTagLayout::from_values(variants.iter().map(|variant| variant.value));
+            let variants_values: Vec<Value> = variants.iter().zip(&tags.values).map(|(variant, discriminant)| {
                 let fields: Vec<Value> = if let Some(ref fields_vec) = variant.fields {
-                    fields_vec.iter().map(|f| build_field_value(f)).collect()
+                    fields_vec.iter().map(build_field_value).collect()
                 } else {
                     Vec::new()
                 };
                 context! {
                     name => variant.name.clone(),
                     fields => fields,
-                    docs => variant.docs.as_ref().map(|docs| docs.join("\n")).unwrap_or_default()
+                    discriminant => discriminant,
+                    docs => join_docs(variant.docs.as_ref())
                 }
             }).collect();
             
@@ -408,23 +630,26 @@ pub fn build_type_value(type_def: &AnchorType) -> Value {
                 name => type_def.name.to_case(Case::Pascal),
                 variants => variants_values,
                 kind => "enum",
+                tag_mode => tags.mode.as_str(),
+                tag_type => tags.tag_type(),
                 can_copy => can_copy,
                 can_eq => can_eq,
-                docs => type_def.docs.as_ref().map(|docs| docs.join("\n")).unwrap_or_default()
+                docs => join_docs(type_def.docs.as_ref()),
+                ..type_layout_value(type_def)
             }
         },
         Some(AnchorTypeKind::Alias(_)) => {
             context! {
                 name => type_def.name.to_case(Case::Pascal),
                 kind => "alias",
-                docs => type_def.docs.as_ref().map(|docs| docs.join("\n")).unwrap_or_default()
+                docs => join_docs(type_def.docs.as_ref())
             }
         },
         None => {
             context! {
                 name => type_def.name.to_case(Case::Pascal),
                 kind => "unknown",
-                docs => type_def.docs.as_ref().map(|docs| docs.join("\n")).unwrap_or_default()
+                docs => join_docs(type_def.docs.as_ref())
             }
         }
     }
@@ -441,57 +666,97 @@ pub fn build_type_value_with_registry(type_def: &AnchorType, registry: &TypeTrai
             // 使用注册表检查struct是否支持Copy和Eq traits
             let can_copy = registry.is_struct_copy_compatible_with_registry(fields);
             let can_eq = registry.is_struct_eq_compatible_with_registry(fields);
+            let can_hash = registry.is_struct_hash_compatible_with_registry(fields);
             
             context! {
                 name => type_def.name.to_case(Case::Pascal),
                 fields => fields_values,
                 kind => "struct",
+                shape => utils::struct_shape(fields.iter().map(|field| field.name.as_str())),
                 can_copy => can_copy,
                 can_eq => can_eq,
-                docs => type_def.docs.as_ref().map(|docs| docs.join("\n")).unwrap_or_default()
+                can_hash => can_hash,
+                docs => join_docs(type_def.docs.as_ref()),
+                ..type_layout_value(type_def)
             }
         },
         Some(AnchorTypeKind::Enum(variants)) => {
-            let variants_values: Vec<Value> = variants.iter().map(|variant| {
+            let tags = EnumTagLayout::from_values(variants.iter().map(|variant| variant.value));
+            let variants_values: Vec<Value> = variants.iter().zip(&tags.values).map(|(variant, discriminant)| {
                 let fields: Vec<Value> = if let Some(ref fields_vec) = variant.fields {
-                    fields_vec.iter().map(|f| build_field_value(f)).collect()
+                    fields_vec.iter().map(build_field_value).collect()
                 } else {
                     Vec::new()
                 };
                 context! {
                     name => variant.name.clone(),
                     fields => fields,
-                    docs => variant.docs.as_ref().map(|docs| docs.join("\n")).unwrap_or_default()
+                    discriminant => discriminant,
+                    docs => join_docs(variant.docs.as_ref())
                 }
             }).collect();
             
             // 使用注册表检查enum是否支持Copy和Eq traits
             let can_copy = registry.is_enum_copy_compatible_with_registry(variants);
             let can_eq = registry.is_enum_eq_compatible_with_registry(variants);
+            let can_hash = registry.is_enum_hash_compatible_with_registry(variants);
             
             context! {
                 name => type_def.name.to_case(Case::Pascal),
                 variants => variants_values,
                 kind => "enum",
+                tag_mode => tags.mode.as_str(),
+                tag_type => tags.tag_type(),
                 can_copy => can_copy,
                 can_eq => can_eq,
-                docs => type_def.docs.as_ref().map(|docs| docs.join("\n")).unwrap_or_default()
+                can_hash => can_hash,
+                docs => join_docs(type_def.docs.as_ref()),
+                ..type_layout_value(type_def)
             }
         },
         Some(AnchorTypeKind::Alias(_)) => {
             context! {
                 name => type_def.name.to_case(Case::Pascal),
                 kind => "alias",
-                docs => type_def.docs.as_ref().map(|docs| docs.join("\n")).unwrap_or_default()
+                docs => join_docs(type_def.docs.as_ref())
             }
         },
         None => {
             context! {
                 name => type_def.name.to_case(Case::Pascal),
                 kind => "unknown",
-                docs => type_def.docs.as_ref().map(|docs| docs.join("\n")).unwrap_or_default()
+                docs => join_docs(type_def.docs.as_ref())
+            }
+        }
+    }
+}
+
+/// 类型的布局相关上下文：`repr` 属性内容、`packed`、`serialization` 与 `discriminator`
+///
+/// Borsh 派生宏借用字段，不能用于 `repr(packed)` 结构体；packed 类型生成为不带 `packed`
+/// 的布局（Borsh 编码本身没有填充），只在文档中注明
+fn type_layout_value(type_def: &AnchorType) -> Value {
+    let is_struct = matches!(type_def.kind, Some(AnchorTypeKind::Struct(_)));
+    let repr = type_def.repr.as_ref().and_then(|repr| {
+        let mut parts: Vec<String> = Vec::new();
+        match repr.kind.as_str() {
+            "c" => parts.push("C".to_string()),
+            "transparent" if is_struct && matches!(&type_def.kind, Some(AnchorTypeKind::Struct(fields)) if fields.len() == 1) => {
+                parts.push("transparent".to_string())
             }
+            "rust" | "transparent" => {}
+            other => log::warn!("⚠️ 类型 {} 的 repr {} 未识别，已忽略", type_def.name, other),
         }
+        if let Some(align) = repr.align.filter(|_| parts.first().map(String::as_str) != Some("transparent")) {
+            parts.push(format!("align({})", align));
+        }
+        (!parts.is_empty()).then(|| parts.join(", "))
+    });
+    context! {
+        repr => repr,
+        packed => type_def.repr.as_ref().is_some_and(|repr| repr.packed),
+        serialization => type_def.serialization.clone(),
+        discriminator => type_def.discriminator.clone(),
     }
 }
 
@@ -499,116 +764,44 @@ pub fn build_type_value_with_registry(type_def: &AnchorType, registry: &TypeTrai
 pub fn build_field_value(field: &AnchorField) -> Value {
     // 转换字段类型为Rust类型字符串
     let rust_type = convert_field_type_to_rust(&field.field_type);
+    // 替换类型的字段按替换类型生成，编解码经由 IDL 原类型（idl_rust_type）
+    if let Some(substitute) = &field.substitute {
+        return context! {
+            name => field.name.to_case(Case::Snake),
+            idl_name => field.name.clone(),
+            rust_type => substitute.clone(),
+            idl_rust_type => rust_type,
+            is_pubkey => false,
+            is_big_array => false,
+            big_array_as => None::<String>,
+            default_expr => "Default::default()",
+            is_coption => false,
+            small_vec_len => None::<&str>,
+            raw_tail => false,
+            docs => join_docs(field.docs.as_ref())
+        };
+    }
+    let big_array_as = utils::nested_big_array_serde_as(&rust_type);
+    let default_expr = utils::field_default_expr(&rust_type);
     
     context! {
         name => field.name.to_case(Case::Snake),
+        idl_name => field.name.clone(),
         rust_type => rust_type,
         is_pubkey => is_anchor_field_pubkey(&field.field_type),
         is_big_array => is_big_array(&field.field_type),
-        docs => field.docs.as_ref().map(|docs| docs.join("\n")).unwrap_or_default()
+        big_array_as => big_array_as,
+        default_expr => default_expr,
+        is_coption => matches!(field.field_type, AnchorFieldType::coption(_)),
+        small_vec_len => utils::anchor_small_vec_len(&field.field_type),
+        raw_tail => field.encoding == Some(ArgEncoding::RawTail),
+        docs => join_docs(field.docs.as_ref())
     }
 }
 
 /// 将AnchorFieldType转换为Rust类型字符串，使用完整路径引用
 pub fn convert_field_type_to_rust(field_type: &AnchorFieldType) -> String {
-    match field_type {
-        AnchorFieldType::Basic(type_name) => {
-            // 基础类型转换，使用完整路径
-            match type_name.as_str() {
-                "u8" => "u8".to_string(),
-                "i8" => "i8".to_string(),
-                "u16" => "u16".to_string(),
-                "i16" => "i16".to_string(),
-                "u32" => "u32".to_string(),
-                "i32" => "i32".to_string(),
-                "u64" => "u64".to_string(),
-                "i64" => "i64".to_string(),
-                "u128" => "u128".to_string(),
-                "i128" => "i128".to_string(),
-                "bool" => "bool".to_string(),
-                "f32" => "f32".to_string(),
-                "f64" => "f64".to_string(),
-                "string" => "std::string::String".to_string(),
-                "publicKey" => "solana_pubkey::Pubkey".to_string(),
-                "pubkey" => "solana_pubkey::Pubkey".to_string(),
-                "bytes" => "std::vec::Vec<u8>".to_string(),
-                _ => type_name.clone(),
-            }
-        },
-        AnchorFieldType::PrimitiveOrPubkey(type_name) => {
-            match type_name.as_str() {
-                "publicKey" => "solana_pubkey::Pubkey".to_string(),
-                "pubkey" => "solana_pubkey::Pubkey".to_string(),
-                _ => type_name.clone(),
-            }
-        },
-        AnchorFieldType::Complex { kind, params: _ } => {
-            // 复合类型处理，使用完整路径
-            match kind.as_str() {
-                "option" => "std::option::Option<T>".to_string(),
-                "vec" => "std::vec::Vec<T>".to_string(),
-                "array" => "[T; N]".to_string(),
-                _ => kind.clone(),
-            }
-        },
-        AnchorFieldType::defined(name) => {
-            log::debug!("🔍 Processing defined type: '{}'", name);
-            
-            // SmallVec特殊处理 - 转换为Vec
-            if name.starts_with("SmallVec<") {
-                log::debug!("🔧 SmallVec detected, converting to Vec: '{}'", name);
-                if let Some(inner) = extract_smallvec_inner(name) {
-                    let processed_inner = if inner == "Pubkey" {
-                        "solana_pubkey::Pubkey".to_string()
-                    } else if inner == "CompiledInstruction" || inner == "MessageAddressTableLookup" {
-                        format!("crate::types::{}", inner)
-                    } else if matches!(inner.as_str(), "u8" | "u16" | "u32" | "u64") {
-                        inner
-                    } else {
-                        format!("crate::types::{}", inner)
-                    };
-                    let result = format!("Vec<{}>", processed_inner);
-                    log::debug!("✅ SmallVec converted: '{}' -> '{}'", name, result);
-                    return result;
-                } else {
-                    log::warn!("⚠️ SmallVec parsing failed for: '{}'", name);
-                }
-            }
-            
-            // 检查是否是基础数组类型语法，避免为内置数组类型添加前缀
-            let is_array_syntax = name.starts_with('[') && name.ends_with(']');
-            let is_std_type = name.starts_with("std::") || name.starts_with("solana_");
-            let is_basic_type = matches!(name.as_str(), "u8" | "i8" | "u16" | "i16" | "u32" | "i32" | 
-                                      "u64" | "i64" | "u128" | "i128" | "bool" | 
-                                      "f32" | "f64" | "string");
-            let has_array_pattern = name.contains('[') && name.contains(';') && name.contains(']');
-            
-            log::debug!("  is_array_syntax: {}, is_std_type: {}, is_basic_type: {}, has_array_pattern: {}", 
-                       is_array_syntax, is_std_type, is_basic_type, has_array_pattern);
-            
-            if is_array_syntax || is_std_type || is_basic_type || has_array_pattern {
-                // 这是内置类型或数组，不需要添加前缀
-                log::debug!("✅ Keeping as-is: '{}'", name);
-                name.clone()
-            } else {
-                // 这是真正的用户定义类型，需要添加前缀
-                log::debug!("🏷️ Adding crate::types prefix: '{}'", name);
-                format!("crate::types::{}", name)
-            }
-        },
-        AnchorFieldType::array(element_type, size) => {
-            let element_rust_type = convert_field_type_to_rust(element_type);
-            format!("[{}; {}]", element_rust_type, size)
-        },
-        AnchorFieldType::vec(element_type) => {
-            let element_rust_type = convert_field_type_to_rust(element_type);
-            format!("std::vec::Vec<{}>", element_rust_type)
-        },
-        AnchorFieldType::option(inner_type) => {
-            let inner_rust_type = convert_field_type_to_rust(inner_type);
-            format!("std::option::Option<{}>", inner_rust_type)
-        },
-    }
+    type_mapper::map_field_type(field_type)
 }
 
 /// 检查是否是大数组类型（需要serde_big_array处理）
@@ -650,7 +843,7 @@ pub fn is_field_copy_compatible(field_type: &AnchorFieldType) -> bool {
             // 数组支持Copy当且仅当元素支持Copy：移除32字节限制（Rust 1.51+支持任意大小数组Copy）
             is_field_copy_compatible(element_type)
         },
-        AnchorFieldType::option(inner_type) => {
+        AnchorFieldType::option(inner_type) | AnchorFieldType::coption(inner_type) => {
             // Option支持Copy当且仅当内部类型支持Copy
             is_field_copy_compatible(inner_type)
         },
@@ -665,7 +858,7 @@ pub fn is_field_copy_compatible(field_type: &AnchorFieldType) -> bool {
                     // 数组类型：现代Rust支持所有大小数组的Copy trait
                     if let Some(params) = params {
                         if params.len() >= 2 {
-                            if let (Some(_element_type), Some(size_val)) = (params.get(0), params.get(1)) {
+                            if let (Some(_element_type), Some(size_val)) = (params.first(), params.get(1)) {
                                 if let Some(_size) = size_val.as_u64() {
                                     // 移除32字节限制：Rust 1.51+支持任意大小数组Copy
                                     return true;
@@ -678,7 +871,7 @@ pub fn is_field_copy_compatible(field_type: &AnchorFieldType) -> bool {
                 "option" => {
                     // Option类型：需要内部类型支持Copy
                     if let Some(params) = params {
-                        if let Some(_inner_type_val) = params.get(0) {
+                        if let Some(_inner_type_val) = params.first() {
                             // 这里需要递归检查，暂时简化处理
                             return true;
                         }
@@ -729,7 +922,7 @@ pub fn is_field_eq_compatible(field_type: &AnchorFieldType) -> bool {
             // 数组支持Eq当且仅当元素支持Eq
             is_field_eq_compatible(element_type)
         },
-        AnchorFieldType::option(inner_type) => {
+        AnchorFieldType::option(inner_type) | AnchorFieldType::coption(inner_type) => {
             // Option支持Eq当且仅当内部类型支持Eq
             is_field_eq_compatible(inner_type)
         },
@@ -806,7 +999,7 @@ pub fn is_field_copy_compatible_basic(field_type: &AnchorFieldType) -> bool {
             // 数组支持Copy当且仅当元素支持Copy
             is_field_copy_compatible_basic(element_type)
         },
-        AnchorFieldType::option(inner_type) => {
+        AnchorFieldType::option(inner_type) | AnchorFieldType::coption(inner_type) => {
             // Option支持Copy当且仅当内部类型支持Copy
             is_field_copy_compatible_basic(inner_type)
         },
@@ -840,7 +1033,7 @@ pub fn is_field_eq_compatible_basic(field_type: &AnchorFieldType) -> bool {
         AnchorFieldType::array(element_type, _size) => {
             is_field_eq_compatible_basic(element_type)
         },
-        AnchorFieldType::option(inner_type) => {
+        AnchorFieldType::option(inner_type) | AnchorFieldType::coption(inner_type) => {
             is_field_eq_compatible_basic(inner_type)
         },
         AnchorFieldType::vec(element_type) => {
@@ -908,4 +1101,84 @@ fn extract_array_parts_from_string(value: &str) -> Option<(String, String)> {
     } else {
         None
     }
-}
\ No newline at end of file
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn account(name: &str, pda_seed: Option<&[u8]>) -> AnchorAccountConstraint {
+        AnchorAccountConstraint {
+            name: name.to_string(),
+            is_mut: false,
+            is_signer: false,
+            is_optional: None,
+            constraints: None,
+            docs: None,
+            pda: pda_seed.map(|seed| PdaDefinition { seeds: vec![PdaSeed::Const { value: seed.to_vec() }], program: None }),
+            address: None,
+            relations: None,
+            relation_types: Default::default(),
+        }
+    }
+
+    #[test]
+    fn event_cpi_accounts_detected_by_seed_and_position() {
+        let accounts = [
+            account("pool", None),
+            account("event_authority", Some(b"__event_authority")),
+            account("program", None),
+        ];
+        assert_eq!(event_cpi_roles(&accounts), [None, Some("event_authority"), Some("program")]);
+
+        let legacy = [account("eventAuthority", None), account("program", None)];
+        assert_eq!(event_cpi_roles(&legacy), [Some("event_authority"), Some("program")]);
+
+        let other_seed = [account("event_authority", Some(b"authority")), account("program", None)];
+        assert_eq!(event_cpi_roles(&other_seed), [None, None]);
+
+        let external = [account("event_authority", None), account("cp_amm_program", None)];
+        assert_eq!(event_cpi_roles(&external), [None, None]);
+    }
+
+    #[test]
+    fn relations_resolve_to_pubkey_fields_of_renamed_state_accounts() {
+        let mut idl = crate::idl_format::parse_idl_json(
+            &serde_json::json!({
+                "address": "11111111111111111111111111111111",
+                "metadata": {"name": "demo", "version": "0.1.0", "spec": "0.1.0"},
+                "instructions": [{
+                    "name": "swap",
+                    "discriminator": [1, 1, 1, 1, 1, 1, 1, 1],
+                    "accounts": [
+                        {"name": "pool"},
+                        {"name": "mint", "relations": ["pool"]},
+                        {"name": "fee", "relations": ["pool"]},
+                        {"name": "vault", "relations": ["config"]}
+                    ],
+                    "args": []
+                }],
+                "accounts": [{"name": "Pool", "discriminator": [2, 2, 2, 2, 2, 2, 2, 2]}],
+                "types": [{"name": "Pool", "type": {"kind": "struct", "fields": [
+                    {"name": "mint", "type": "pubkey"},
+                    {"name": "fee", "type": "u64"}
+                ]}}]
+            })
+            .to_string(),
+        )
+        .unwrap();
+        idl.apply_naming(&crate::idl_format::naming::NamingConfig::new(vec!["accounts=My".parse().unwrap()], vec![]));
+        let IdlFormatEnum::Anchor(anchor) = &idl else { unreachable!() };
+        let accounts = anchor.instructions.as_ref().unwrap()[0].accounts.clone().unwrap();
+
+        assert_eq!(
+            relation_checks(&accounts, &idl),
+            [RelationCheck {
+                account: "mint".to_string(),
+                state: "pool".to_string(),
+                state_type: "MyPool".to_string(),
+                field: "mint".to_string(),
+            }]
+        );
+    }
+}
@@ -2,14 +2,21 @@
 //! 
 //! 负责将 NonAnchor IDL 数据结构转换为模板可用的 Value 对象
 
-use crate::idl_format::non_anchor_idl::*;
+use crate::idl_format::{account_variants, arg_encoding::ArgEncoding, c_layout::{c_struct_layout, CLayout}, docs::join_docs, enum_tags::EnumTagLayout, non_anchor_idl::*};
 use convert_case::{Case, Casing};
 use minijinja::{context, Value};
 use log;
-use super::super::utils;
+use super::super::{type_mapper, utils};
+use super::super::zero_copy::{account_view, field_offsets, lazy_view, ViewField};
+use crate::idl_format::zero_copy;
+use std::collections::HashSet;
 
 /// NonAnchor账户构建方法 - 完整实现
-pub fn build_non_anchor_account_value(account: &NonAnchorAccount, idl_enum: &crate::idl_format::IdlFormatEnum) -> Value {
+pub fn build_non_anchor_account_value(
+    account: &NonAnchorAccount,
+    idl_enum: &crate::idl_format::IdlFormatEnum,
+    unhashable: &HashSet<String>,
+) -> Value {
     let fields: Vec<Value> = if let Some(ref fields_vec) = account.fields {
         fields_vec.iter().map(|field| {
             build_non_anchor_field_value(field)
@@ -22,17 +29,135 @@ pub fn build_non_anchor_account_value(account: &NonAnchorAccount, idl_enum: &cra
     let packed_size = utils::calculate_non_anchor_account_packed_size(account, idl_enum);
     log::debug!("🎯 NonAnchor Account {} 计算得到 PACKED_LEN: {} 字节", account.name, packed_size);
 
+    let layout_fixed = utils::fields_have_fixed_layout(&fields);
+
+    let len_range = utils::non_anchor_account_len_range(account, idl_enum);
+    let view_fields: Vec<ViewField> = match idl_enum {
+        crate::idl_format::IdlFormatEnum::NonAnchor(idl) => account.fields.iter().flatten().map(|field| ViewField {
+            name: field.name.to_case(Case::Snake),
+            rust_type: convert_non_anchor_field_type_to_rust(&field.field_type),
+            size: zero_copy::non_anchor_fixed_size(&field.field_type, idl),
+        }).collect(),
+        _ => Vec::new(),
+    };
+    let c_layout = match (idl_enum, &account.c_layout) {
+        (crate::idl_format::IdlFormatEnum::NonAnchor(idl), Some(layout)) => {
+            c_layout_value(account.fields.as_deref().unwrap_or_default(), layout, idl)
+        }
+        _ => None,
+    };
+    if let Some(c_layout) = c_layout {
+        // C 布局账户按字节直接读取：长度固定，字段偏移取自 C 布局，不生成紧凑布局视图
+        let len = c_layout.get_attr("len").ok().and_then(|len| len.as_usize()).unwrap_or_default();
+        return context! {
+            name => account.name.to_case(Case::Pascal),
+            c_layout => c_layout.clone(),
+            field_offsets => c_layout.get_attr("field_offsets").unwrap_or_default(),
+            fields => fields,
+            discriminator => account.discriminator.as_ref().unwrap_or(&Vec::new()),
+            packed_size => len,
+            min_len => len,
+            max_len => len,
+            variable_len => false,
+            layout_fixed => false,
+            docs => join_docs(account.docs.as_ref())
+        };
+    }
+
+    let field_offsets = field_offsets(&view_fields, 0);
+    let lazy_view = lazy_view(&view_fields, 0);
+    let zero_copy_view = if account.zero_copy {
+        account_view(&account.name, view_fields, 0)
+    } else {
+        None
+    };
+    let variants = match (idl_enum, &account.variants) {
+        (crate::idl_format::IdlFormatEnum::NonAnchor(idl), Some(variants)) => {
+            account_variants::resolve_non_anchor(variants, idl).ok().map(|resolved| {
+                let layout_fields: Vec<Vec<(String, String)>> = resolved
+                    .layouts
+                    .iter()
+                    .map(|(_, name)| {
+                        account_variants::non_anchor_layout_fields(idl, name)
+                            .unwrap_or_default()
+                            .iter()
+                            .map(|field| {
+                                (field.name.to_case(Case::Snake), convert_non_anchor_field_type_to_rust(&field.field_type))
+                            })
+                            .collect()
+                    })
+                    .collect();
+                utils::account_variants_value(&resolved, &layout_fields, 0)
+            })
+        }
+        _ => None,
+    };
+
     context! {
         name => account.name.to_case(Case::Pascal),
+        zero_copy_view => zero_copy_view,
+        variants => variants,
+        lazy_view => lazy_view,
+        field_offsets => field_offsets,
         fields => fields,
         discriminator => account.discriminator.as_ref().unwrap_or(&Vec::new()),
         packed_size => packed_size,
-        docs => account.docs.as_ref().map(|docs| docs.join("\n")).unwrap_or_default()
+        min_len => len_range.min,
+        max_len => len_range.max,
+        variable_len => !len_range.is_fixed(),
+        layout_fixed => layout_fixed,
+        can_eq => is_non_anchor_struct_eq_compatible(account.fields.as_deref().unwrap_or_default()),
+        can_hash => is_non_anchor_struct_hash_compatible(account.fields.as_deref().unwrap_or_default(), unhashable),
+        docs => join_docs(account.docs.as_ref())
     }
 }
 
+/// C 布局结构体上下文：`members` 为声明的字段及其间 / 末尾的 `_padding{偏移}` 填充字段
+///
+/// 布局已在渲染前校验，解析失败时返回 None
+fn c_layout_value(fields: &[NonAnchorField], layout: &CLayout, idl: &NonAnchorIdl) -> Option<Value> {
+    let resolved = c_struct_layout(fields, layout, idl).ok()?;
+    let padding = |offset: usize, size: usize| context! {
+        name => format!("_padding{}", offset),
+        rust_type => format!("[u8; {}]", size),
+        is_padding => true,
+        offset => offset,
+        size => size,
+    };
+    let mut members = Vec::with_capacity(fields.len());
+    let mut end = 0;
+    for (field, slot) in fields.iter().zip(&resolved.fields) {
+        if slot.offset > end {
+            members.push(padding(end, slot.offset - end));
+        }
+        members.push(context! { offset => slot.offset, size => slot.size, ..build_non_anchor_field_value(field) });
+        end = slot.offset + slot.size;
+    }
+    if resolved.size > end {
+        members.push(padding(end, resolved.size - end));
+    }
+    let field_offsets: Vec<Value> = resolved
+        .fields
+        .iter()
+        .map(|slot| context! { name => slot.name, offset => slot.offset, size => slot.size })
+        .collect();
+    Some(context! {
+        len => resolved.size,
+        members => members,
+        field_offsets => field_offsets,
+    })
+}
+
 /// NonAnchor指令构建方法 - 完整实现
-pub fn build_non_anchor_instruction_value(instruction: &NonAnchorInstruction, index: usize) -> Value {
+///
+/// `has_discriminator` 为 false 时（无 discriminator 的单指令程序）指令数据只包含参数
+pub fn build_non_anchor_instruction_value(
+    instruction: &NonAnchorInstruction,
+    index: usize,
+    has_discriminator: bool,
+    idl_enum: &crate::idl_format::IdlFormatEnum,
+    unhashable: &HashSet<String>,
+) -> Value {
     let args: Vec<Value> = instruction.args.as_ref().unwrap_or(&Vec::new()).iter().map(|field| {
         build_non_anchor_field_value(field)
     }).collect();
@@ -42,7 +167,9 @@ pub fn build_non_anchor_instruction_value(instruction: &NonAnchorInstruction, in
     }).collect();
 
     // 确保discriminator存在，如果没有则用索引
-    let discriminator_value = if let Some(ref disc) = instruction.discriminator {
+    let discriminator_value = if !has_discriminator {
+        Vec::new()
+    } else if let Some(ref disc) = instruction.discriminator {
         if !disc.is_empty() {
             disc.clone()
         } else {
@@ -52,18 +179,27 @@ pub fn build_non_anchor_instruction_value(instruction: &NonAnchorInstruction, in
         vec![index as u8]  // 使用索引作为discriminator
     };
 
+    let has_fixed_addresses = instruction.accounts.iter().flatten().any(|acc| acc.address.is_some());
+    let data_min_len = discriminator_value.len()
+        + utils::non_anchor_args_min_len(instruction.args.as_deref().unwrap_or_default(), idl_enum);
+
     context! {
         name => instruction.name.to_case(Case::Pascal),
+        data_min_len => data_min_len,
         discriminator => discriminator_value,
+        has_discriminator => has_discriminator,
+        has_fixed_addresses => has_fixed_addresses,
         args => args.clone(),
         fields => args,
         accounts => accounts,
-        docs => instruction.docs.as_ref().map(|docs| docs.join("\n")).unwrap_or_default()
+        can_eq => is_non_anchor_struct_eq_compatible(instruction.args.as_deref().unwrap_or_default()),
+        can_hash => is_non_anchor_struct_hash_compatible(instruction.args.as_deref().unwrap_or_default(), unhashable),
+        docs => join_docs(instruction.docs.as_ref())
     }
 }
 
 /// NonAnchor事件构建方法 - 完整实现
-pub fn build_non_anchor_event_value(event: &NonAnchorEvent) -> Value {
+pub fn build_non_anchor_event_value(event: &NonAnchorEvent, unhashable: &HashSet<String>) -> Value {
     let fields: Vec<Value> = if let Some(ref fields_vec) = event.fields {
         fields_vec.iter().map(|field| {
             build_non_anchor_field_value(field)
@@ -76,13 +212,25 @@ pub fn build_non_anchor_event_value(event: &NonAnchorEvent) -> Value {
         name => event.name.to_case(Case::Pascal),
         discriminator => event.discriminator.as_ref().unwrap_or(&Vec::new()),
         fields => fields,
-        docs => event.docs.as_ref().map(|docs| docs.join("\n")).unwrap_or_default()
+        can_eq => is_non_anchor_struct_eq_compatible(event.fields.as_deref().unwrap_or_default()),
+        can_hash => is_non_anchor_struct_hash_compatible(event.fields.as_deref().unwrap_or_default(), unhashable),
+        docs => join_docs(event.docs.as_ref())
     }
 }
 
 /// NonAnchor类型构建方法 - 完整实现
-pub fn build_non_anchor_type_value(type_def: &NonAnchorType) -> Value {
+pub fn build_non_anchor_type_value(type_def: &NonAnchorType, idl: &NonAnchorIdl, unhashable: &HashSet<String>) -> Value {
     match &type_def.type_def {
+        NonAnchorTypeKind::Struct { fields } if type_def.c_layout.is_some() => {
+            let c_layout = type_def.c_layout.as_ref().and_then(|layout| c_layout_value(fields, layout, idl));
+            context! {
+                name => type_def.name.to_case(Case::Pascal),
+                fields => fields.iter().map(build_non_anchor_field_value).collect::<Vec<_>>(),
+                kind => "struct",
+                c_layout => c_layout,
+                docs => join_docs(type_def.docs.as_ref())
+            }
+        },
         NonAnchorTypeKind::Struct { fields } => {
             let fields_values: Vec<Value> = fields.iter().map(|field| {
                 build_non_anchor_field_value(field)
@@ -91,46 +239,55 @@ pub fn build_non_anchor_type_value(type_def: &NonAnchorType) -> Value {
             // 检查struct是否支持Copy和Eq traits
             let can_copy = is_non_anchor_struct_copy_compatible(fields);
             let can_eq = is_non_anchor_struct_eq_compatible(fields);
+            let can_hash = is_non_anchor_struct_hash_compatible(fields, unhashable);
             
             context! {
                 name => type_def.name.to_case(Case::Pascal),
                 fields => fields_values,
                 kind => "struct",
+                shape => utils::struct_shape(fields.iter().map(|field| field.name.as_str())),
                 can_copy => can_copy,
                 can_eq => can_eq,
-                docs => type_def.docs.as_ref().map(|docs| docs.join("\n")).unwrap_or_default()
+                can_hash => can_hash,
+                docs => join_docs(type_def.docs.as_ref())
             }
         },
         NonAnchorTypeKind::Enum { variants } => {
-            let variants_values: Vec<Value> = variants.iter().map(|variant| {
+            let tags = EnumTagLayout::from_values(variants.iter().map(|variant| variant.value));
+            let variants_values: Vec<Value> = variants.iter().zip(&tags.values).map(|(variant, discriminant)| {
                 let fields: Vec<Value> = variant.fields.as_ref().unwrap_or(&Vec::new()).iter().map(|f| {
                     build_non_anchor_field_value(f)
                 }).collect();
                 context! {
                     name => variant.name.clone(),
                     fields => fields,
-                    docs => variant.docs.as_ref().map(|docs| docs.join("\n")).unwrap_or_default()
+                    discriminant => discriminant,
+                    docs => join_docs(variant.docs.as_ref())
                 }
             }).collect();
             
             // 检查enum是否支持Copy和Eq traits
             let can_copy = is_non_anchor_enum_copy_compatible(variants);
             let can_eq = is_non_anchor_enum_eq_compatible(variants);
+            let can_hash = is_non_anchor_enum_hash_compatible(variants, unhashable);
             
             context! {
                 name => type_def.name.to_case(Case::Pascal),
                 variants => variants_values,
                 kind => "enum",
+                tag_mode => tags.mode.as_str(),
+                tag_type => tags.tag_type(),
                 can_copy => can_copy,
                 can_eq => can_eq,
-                docs => type_def.docs.as_ref().map(|docs| docs.join("\n")).unwrap_or_default()
+                can_hash => can_hash,
+                docs => join_docs(type_def.docs.as_ref())
             }
         },
         NonAnchorTypeKind::Alias { value: _ } => {
             context! {
                 name => type_def.name.to_case(Case::Pascal),
                 kind => "alias",
-                docs => type_def.docs.as_ref().map(|docs| docs.join("\n")).unwrap_or_default()
+                docs => join_docs(type_def.docs.as_ref())
             }
         }
     }
@@ -139,86 +296,44 @@ pub fn build_non_anchor_type_value(type_def: &NonAnchorType) -> Value {
 /// 构建NonAnchor字段Value
 pub fn build_non_anchor_field_value(field: &NonAnchorField) -> Value {
     let rust_type = convert_non_anchor_field_type_to_rust(&field.field_type);
+    // 替换类型的字段按替换类型生成，编解码经由 IDL 原类型（idl_rust_type）
+    if let Some(substitute) = &field.substitute {
+        return context! {
+            name => field.name.to_case(Case::Snake),
+            idl_name => field.name.clone(),
+            rust_type => substitute.clone(),
+            idl_rust_type => rust_type,
+            is_pubkey => false,
+            is_big_array => false,
+            big_array_as => None::<String>,
+            default_expr => "Default::default()",
+            is_coption => false,
+            small_vec_len => None::<&str>,
+            raw_tail => false,
+            docs => join_docs(field.docs.as_ref())
+        };
+    }
+    let big_array_as = utils::nested_big_array_serde_as(&rust_type);
+    let default_expr = utils::field_default_expr(&rust_type);
     
     context! {
         name => field.name.to_case(Case::Snake),
+        idl_name => field.name.clone(),
         rust_type => rust_type,
         is_pubkey => is_non_anchor_field_pubkey(&field.field_type),
         is_big_array => is_non_anchor_big_array(&field.field_type),
-        docs => field.docs.as_ref().map(|docs| docs.join("\n")).unwrap_or_default()
+        big_array_as => big_array_as,
+        default_expr => default_expr,
+        is_coption => matches!(field.field_type, NonAnchorFieldType::COption { .. }),
+        small_vec_len => utils::non_anchor_small_vec_len(&field.field_type),
+        raw_tail => field.encoding == Some(ArgEncoding::RawTail),
+        docs => join_docs(field.docs.as_ref())
     }
 }
 
 /// 将NonAnchor字段类型转换为Rust类型字符串
 pub fn convert_non_anchor_field_type_to_rust(field_type: &NonAnchorFieldType) -> String {
-    match field_type {
-        NonAnchorFieldType::Basic(type_name) => {
-            match type_name.as_str() {
-                "u8" => "u8".to_string(),
-                "i8" => "i8".to_string(),
-                "u16" => "u16".to_string(),
-                "i16" => "i16".to_string(),
-                "u32" => "u32".to_string(),
-                "i32" => "i32".to_string(),
-                "u64" => "u64".to_string(),
-                "i64" => "i64".to_string(),
-                "u128" => "u128".to_string(),
-                "i128" => "i128".to_string(),
-                "bool" => "bool".to_string(),
-                "f32" => "f32".to_string(),
-                "f64" => "f64".to_string(),
-                "string" => "std::string::String".to_string(),
-                "publicKey" => "solana_pubkey::Pubkey".to_string(),
-                "pubkey" => "solana_pubkey::Pubkey".to_string(),
-                "bytes" => "std::vec::Vec<u8>".to_string(),
-                _ => type_name.clone(),
-            }
-        },
-        NonAnchorFieldType::Option { option } => {
-            let inner_rust_type = convert_non_anchor_field_type_to_rust(option);
-            format!("std::option::Option<{}>", inner_rust_type)
-        },
-        NonAnchorFieldType::Vec { vec } => {
-            let element_rust_type = convert_non_anchor_field_type_to_rust(vec);
-            format!("std::vec::Vec<{}>", element_rust_type)
-        },
-        NonAnchorFieldType::Array { array } => {
-            let (element_type, size) = array;
-            let element_rust_type = convert_non_anchor_field_type_to_rust(element_type);
-            format!("[{}; {}]", element_rust_type, size)
-        },
-        NonAnchorFieldType::Defined { defined } => {
-            // 检查是否是基础数组类型语法，避免为内置数组类型添加前缀
-            if (defined.starts_with('[') && defined.ends_with(']')) ||
-               defined.starts_with("std::") ||
-               defined.starts_with("solana_") ||
-               // 检查是否是基础类型
-               matches!(defined.as_str(), "u8" | "i8" | "u16" | "i16" | "u32" | "i32" | 
-                                          "u64" | "i64" | "u128" | "i128" | "bool" | 
-                                          "f32" | "f64" | "string") ||
-               // 检查是否包含数组语法模式，如 "[u64; 8]"
-               (defined.contains('[') && defined.contains(';') && defined.contains(']')) {
-                // 这是内置类型或数组，不需要添加前缀
-                defined.clone()
-            } else {
-                // 这是真正的用户定义类型，需要添加前缀
-                format!("crate::types::{}", defined)
-            }
-        },
-        NonAnchorFieldType::HashMap { key, value } => {
-            let key_rust_type = convert_non_anchor_field_type_to_rust(key);
-            let value_rust_type = convert_non_anchor_field_type_to_rust(value);
-            format!("std::collections::HashMap<{}, {}>", key_rust_type, value_rust_type)
-        },
-        NonAnchorFieldType::Complex { kind, params: _ } => {
-            match kind.as_str() {
-                "option" => "std::option::Option<T>".to_string(),
-                "vec" => "std::vec::Vec<T>".to_string(),
-                "array" => "[T; N]".to_string(),
-                _ => kind.clone(),
-            }
-        },
-    }
+    type_mapper::map_field_type(field_type)
 }
 
 /// 检查NonAnchor字段是否为Pubkey类型
@@ -258,7 +373,7 @@ pub fn is_non_anchor_field_copy_compatible(field_type: &NonAnchorFieldType) -> b
             // 数组支持Copy当且仅当元素支持Copy：移除32字节限制（Rust 1.51+支持任意大小数组Copy）
             is_non_anchor_field_copy_compatible(element_type)
         },
-        NonAnchorFieldType::Option { option } => {
+        NonAnchorFieldType::Option { option } | NonAnchorFieldType::COption { coption: option } => {
             // Option支持Copy当且仅当内部类型支持Copy
             is_non_anchor_field_copy_compatible(option)
         },
@@ -312,7 +427,7 @@ pub fn is_non_anchor_field_eq_compatible(field_type: &NonAnchorFieldType) -> boo
             // 数组支持Eq当且仅当元素支持Eq
             is_non_anchor_field_eq_compatible(element_type)
         },
-        NonAnchorFieldType::Option { option } => {
+        NonAnchorFieldType::Option { option } | NonAnchorFieldType::COption { coption: option } => {
             // Option支持Eq当且仅当内部类型支持Eq
             is_non_anchor_field_eq_compatible(option)
         },
@@ -374,6 +489,21 @@ pub fn is_non_anchor_struct_eq_compatible(fields: &[NonAnchorField]) -> bool {
     fields.iter().all(|field| is_non_anchor_field_eq_compatible(&field.field_type))
 }
 
+/// 检查NonAnchor struct是否支持Hash trait：Eq 兼容的字段中只有 HashMap 不支持 Hash
+pub fn is_non_anchor_struct_hash_compatible(fields: &[NonAnchorField], unhashable: &HashSet<String>) -> bool {
+    is_non_anchor_struct_eq_compatible(fields)
+        && utils::are_field_types_hashable(fields.iter().map(|field| &field.field_type), unhashable)
+}
+
+/// 检查NonAnchor enum是否支持Hash trait
+pub fn is_non_anchor_enum_hash_compatible(variants: &[NonAnchorEnumVariant], unhashable: &HashSet<String>) -> bool {
+    is_non_anchor_enum_eq_compatible(variants)
+        && utils::are_field_types_hashable(
+            variants.iter().flat_map(|variant| variant.fields.iter().flatten()).map(|field| &field.field_type),
+            unhashable,
+        )
+}
+
 /// 辅助函数：从字符串中提取NonAnchor数组部分
 fn extract_non_anchor_array_parts_from_string(value: &str) -> Option<(String, String)> {
     if !value.starts_with("[") || !value.ends_with("]") {
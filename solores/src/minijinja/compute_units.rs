@@ -0,0 +1,102 @@
+//! 指令计算单元估算
+//!
+//! 通过 `--compute-units Instruction=units` 为指令配置计算单元估算值，
+//! 未配置的指令使用 `--default-compute-units`。生成的 compute.rs 中每个指令
+//! 对应一个常量，运行时可再通过 `ComputeUnitEstimates` 覆盖。
+
+use std::str::FromStr;
+
+use convert_case::{Case, Casing};
+use minijinja::{context, Value};
+
+use crate::error::SoloresError;
+
+/// 单条计算单元配置：`Instruction=units`
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct ComputeUnitHint {
+    pub instruction: String,
+    pub units: u32,
+}
+
+impl FromStr for ComputeUnitHint {
+    type Err = String;
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        let (instruction, units) = s
+            .split_once('=')
+            .filter(|(instruction, _)| !instruction.trim().is_empty())
+            .ok_or_else(|| format!("计算单元配置 '{}' 格式应为 Instruction=units", s))?;
+        let units = units
+            .trim()
+            .replace('_', "")
+            .parse()
+            .map_err(|_| format!("计算单元 '{}' 不是有效的 u32", units.trim()))?;
+        Ok(ComputeUnitHint { instruction: instruction.trim().to_string(), units })
+    }
+}
+
+/// 为上下文中的每个指令解析估算值：`[{name, units}]`
+///
+/// 配置的指令不存在时返回 ValidationError
+pub fn resolve_compute_units(
+    hints: &[ComputeUnitHint],
+    default_units: u32,
+    context: &Value,
+) -> Result<Vec<Value>, SoloresError> {
+    let names: Vec<String> = context
+        .get_attr("instructions")
+        .ok()
+        .and_then(|instructions| instructions.try_iter().ok())
+        .map(|instructions| {
+            instructions
+                .filter_map(|ix| ix.get_attr("name").ok().and_then(|name| name.as_str().map(str::to_string)))
+                .collect()
+        })
+        .unwrap_or_default();
+
+    for hint in hints {
+        let name = hint.instruction.to_case(Case::Pascal);
+        if !names.contains(&name) {
+            return Err(SoloresError::ValidationError {
+                message: format!("未找到指令 '{}'", hint.instruction),
+                field_path: Some(format!("--compute-units {}={}", hint.instruction, hint.units)),
+                expected: Some(names.join(", ")),
+                actual: Some(name),
+            });
+        }
+    }
+
+    Ok(names
+        .into_iter()
+        .map(|name| {
+            // 同一指令配置多次时以最后一次为准
+            let units = hints
+                .iter()
+                .rev()
+                .find(|hint| hint.instruction.to_case(Case::Pascal) == name)
+                .map_or(default_units, |hint| hint.units);
+            context! { name => name, units => units }
+        })
+        .collect())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn parse_and_resolve_compute_units() {
+        let hint: ComputeUnitHint = "swap_exact_in=45_000".parse().unwrap();
+        assert_eq!(hint.units, 45_000);
+        assert!("Swap".parse::<ComputeUnitHint>().is_err());
+        assert!("Swap=lots".parse::<ComputeUnitHint>().is_err());
+
+        let context = context! { instructions => vec![context! { name => "SwapExactIn" }, context! { name => "Close" }] };
+        let resolved = resolve_compute_units(&[hint], 200_000, &context).unwrap();
+        let units: Vec<u32> = resolved.iter().map(|v| v.get_attr("units").unwrap().try_into().unwrap()).collect();
+        assert_eq!(units, [45_000, 200_000]);
+
+        let missing: ComputeUnitHint = "Deposit=1".parse().unwrap();
+        assert!(resolve_compute_units(&[missing], 200_000, &context).is_err());
+    }
+}
@@ -3,7 +3,7 @@
 //! 负责从 IDL 数据创建模板渲染所需的上下文数据
 
 use crate::error::SoloresError;
-use crate::idl_format::IdlFormatEnum;
+use crate::idl_format::{docs::join_docs, program_ids, IdlFormat, IdlFormatEnum};
 use convert_case::{Case, Casing};
 use minijinja::{context, Value};
 use log;
@@ -20,8 +20,12 @@ pub fn create_template_context(
     no_empty_workspace: bool,
     is_unified_library: bool,
 ) -> std::result::Result<Value, SoloresError> {
+    // 地址在生成时校验，避免生成无法编译的 pubkey! 常量
+    super::utils::validate_idl_addresses(idl_enum)?;
+
     // 从IDL中提取实际数据
     let (accounts, instructions, events, types) = extract_idl_data(idl_enum)?;
+    let errors = extract_idl_errors(idl_enum);
     
     // 获取程序ID
     let program_id = match idl_enum {
@@ -29,9 +33,23 @@ pub fn create_template_context(
         IdlFormatEnum::NonAnchor(non_anchor_idl) => &non_anchor_idl.address,
     };
     
-    // 获取当前时间戳
-    let generation_time = chrono::Utc::now().format("%Y-%m-%d %H:%M:%S UTC").to_string();
-    
+    // 仅在存在顶层大数组时依赖 serde-big-array
+    let has_big_arrays = [&accounts, &instructions, &events, &types]
+        .into_iter()
+        .any(|items| super::utils::items_have_big_array(items));
+
+    // 多集群部署地址（默认集群在首位）
+    let clusters = program_ids::cluster_values(&idl_enum.program_ids());
+
+    // 指令中声明的PDA（仅Anchor IDL）
+    let pdas = super::generators::pda::collect_program_pdas(idl_enum);
+
+    // 旧版 Anchor `state` 段声明的单例状态账户
+    let state_account = match idl_enum {
+        IdlFormatEnum::Anchor(idl) => idl.accounts.iter().flatten().find(|account| account.is_state).map(|account| account.name.to_case(Case::Pascal)),
+        IdlFormatEnum::NonAnchor(_) => None,
+    };
+
     // 使用官方context!宏构建上下文
     let context = context! {
         features => if serde_feature { vec!["serde".to_string()] } else { Vec::<String>::new() },
@@ -43,24 +61,91 @@ pub fn create_template_context(
         crate_name => program_name,
         program_name => program_name.to_case(Case::Pascal),
         program_id => program_id,
-        generation_time => generation_time,
+        has_clusters => clusters.len() > 1,
+        clusters => clusters,
+        idl_version => idl_enum.program_version(),
         accounts => accounts,
         instructions => instructions, 
         events => events,
         types => types,
+        errors => errors,
         has_accounts => !accounts.is_empty(),
         has_instructions => !instructions.is_empty(),
         has_events => !events.is_empty(),
-        has_types => !types.is_empty()
+        has_types => !types.is_empty(),
+        has_errors => !errors.is_empty(),
+        has_coption => super::utils::idl_has_coption(idl_enum),
+        has_small_vec => super::utils::idl_has_small_vec(idl_enum),
+        has_raw_tail => super::utils::idl_has_raw_tail(idl_enum),
+        has_field_types => idl_enum.has_field_types(),
+        has_big_arrays => has_big_arrays,
+        has_c_layout => matches!(idl_enum, IdlFormatEnum::NonAnchor(idl) if idl.has_c_layout()),
+        has_pdas => !pdas.is_empty(),
+        pdas => pdas,
+        has_event_cpi => anchor::idl_has_event_cpi(idl_enum),
+        state_account => state_account,
+        // --typed-keys 由生成器填充，默认 Keys 字段均为 Pubkey
+        typed_key_types => std::collections::BTreeMap::<String, String>::new(),
+        // --min-rust-version 由生成器覆盖，默认使用全部特性
+        rust => minijinja::Value::from_serialize(super::rust_version::RustFeatures::LATEST)
     };
     
     Ok(context)
 }
 
+/// IDL错误条目：(名称, 错误码, 消息, 文档)
+type IdlError<'a> = (&'a str, u32, Option<&'a str>, Option<&'a Vec<String>>);
+
+/// 提取IDL中的程序错误，保留原始错误码（可稀疏、乱序）
+///
+/// 重复的错误码或名称无法生成合法的显式判别值枚举，保留首个定义并告警
+pub fn extract_idl_errors(idl_enum: &IdlFormatEnum) -> Vec<Value> {
+    let errors: Vec<IdlError> = match idl_enum {
+        IdlFormatEnum::Anchor(idl) => idl.errors.iter().flatten()
+            .map(|e| (e.name.as_str(), e.code, e.msg.as_deref(), e.docs.as_ref()))
+            .collect(),
+        IdlFormatEnum::NonAnchor(idl) => idl.errors.iter().flatten()
+            .map(|e| (e.name.as_str(), e.code, Some(e.msg.as_str()), e.docs.as_ref()))
+            .collect(),
+    };
+
+    let mut codes = std::collections::HashMap::new();
+    let mut names = std::collections::HashSet::new();
+    errors
+        .into_iter()
+        .filter_map(|(name, code, msg, docs)| {
+            let variant = name.to_case(Case::Pascal);
+            if let Some(existing) = codes.get(&code) {
+                log::warn!("⚠️ 错误码 {} 重复（{} 与 {}），已跳过 {}", code, existing, name, name);
+                return None;
+            }
+            if !names.insert(variant.clone()) {
+                log::warn!("⚠️ 错误名称 {} 重复（错误码 {}），已跳过", name, code);
+                return None;
+            }
+            codes.insert(code, name.to_string());
+            let msg = msg.filter(|msg| !msg.is_empty()).unwrap_or(name);
+            Some(context! {
+                name => variant,
+                code => code,
+                // thiserror 的 #[error] 是格式化字符串，需转义花括号
+                msg_literal => format!("{:?}", msg.replace('{', "{{").replace('}', "}}")),
+                // 错误信息表中的原样字符串字面量
+                msg_str => format!("{:?}", msg),
+                docs_str => format!("{:?}", join_docs(docs)),
+                docs => join_docs(docs)
+            })
+        })
+        .collect()
+}
+
+/// 提取结果：(accounts, instructions, events, types)
+pub type IdlData = (Vec<Value>, Vec<Value>, Vec<Value>, Vec<Value>);
+
 /// 从IDL中提取数据 - 修复数据分类错误和字段丢失问题
 pub fn extract_idl_data(
     idl_enum: &IdlFormatEnum,
-) -> std::result::Result<(Vec<Value>, Vec<Value>, Vec<Value>, Vec<Value>), SoloresError> {
+) -> std::result::Result<IdlData, SoloresError> {
     match idl_enum {
         IdlFormatEnum::Anchor(anchor_idl) => {
             log::debug!("🔍 开始提取Anchor IDL数据 - 使用类型注册表修复版本");
@@ -76,7 +161,7 @@ pub fn extract_idl_data(
                 .iter()
                 .map(|account| {
                     log::debug!("📋 处理Account: {}", account.name);
-                    anchor::build_account_value(account, idl_enum)
+                    anchor::build_account_value(account, idl_enum, &type_registry)
                 })
                 .collect();
             
@@ -84,7 +169,7 @@ pub fn extract_idl_data(
                 .iter()
                 .map(|instruction| {
                     log::debug!("📝 处理Instruction: {}", instruction.name);
-                    anchor::build_instruction_value(instruction)
+                    anchor::build_instruction_value(instruction, idl_enum, &type_registry)
                 })
                 .collect();
             
@@ -92,7 +177,7 @@ pub fn extract_idl_data(
                 .iter()
                 .map(|event| {
                     log::debug!("🎯 处理Event: {}", event.name);
-                    anchor::build_event_value(event, idl_enum)
+                    anchor::build_event_value(event, idl_enum, &type_registry)
                 })
                 .collect();
             
@@ -133,23 +218,58 @@ pub fn extract_idl_data(
             Ok((accounts, instructions, events, types))
         },
         IdlFormatEnum::NonAnchor(non_anchor_idl) => {
+            let unhashable = super::utils::idl_unhashable_types(idl_enum);
             let accounts: Vec<Value> = non_anchor_idl.accounts.as_ref().unwrap_or(&vec![]).iter().map(|account| {
-                non_anchor::build_non_anchor_account_value(account, idl_enum)
+                non_anchor::build_non_anchor_account_value(account, idl_enum, &unhashable)
             }).collect();
             
+            let has_discriminator = non_anchor_idl.undiscriminated_instruction().is_none();
             let instructions: Vec<Value> = non_anchor_idl.instructions().iter().enumerate().map(|(index, instruction)| {
-                non_anchor::build_non_anchor_instruction_value(instruction, index)
+                non_anchor::build_non_anchor_instruction_value(instruction, index, has_discriminator, idl_enum, &unhashable)
             }).collect();
             
             let events: Vec<Value> = non_anchor_idl.events.as_ref().unwrap_or(&vec![]).iter().map(|event| {
-                non_anchor::build_non_anchor_event_value(event)
+                non_anchor::build_non_anchor_event_value(event, &unhashable)
             }).collect();
             
-            let types: Vec<Value> = non_anchor_idl.types.as_ref().unwrap_or(&vec![]).iter().map(|type_def| {
-                non_anchor::build_non_anchor_type_value(type_def)
-            }).collect();
+            // 与Anchor一致：排除已作为账户生成的同名类型
+            let account_names: std::collections::HashSet<&str> = non_anchor_idl.accounts.iter().flatten()
+                .map(|account| account.name.as_str())
+                .collect();
+            let types: Vec<Value> = non_anchor_idl.types.as_ref().unwrap_or(&vec![]).iter()
+                .filter(|type_def| !account_names.contains(type_def.name.as_str()))
+                .map(|type_def| {
+                    non_anchor::build_non_anchor_type_value(type_def, non_anchor_idl, &unhashable)
+                }).collect();
             
             Ok((accounts, instructions, events, types))
         }
     }
-}
\ No newline at end of file
+}
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::idl_format::non_anchor_idl::NonAnchorIdl;
+
+    #[test]
+    fn idl_errors_keep_sparse_codes_and_skip_duplicates() {
+        let idl = NonAnchorIdl::parse_json(&serde_json::json!({
+            "address": "11111111111111111111111111111111",
+            "metadata": {"name": "vault", "version": "0.1.0", "spec": "0.1.0"},
+            "errors": [
+                {"code": 6010, "name": "late", "msg": "late {x}"},
+                {"code": 6000, "name": "first", "msg": ""},
+                {"code": 6010, "name": "duplicate", "msg": "duplicate"}
+            ]
+        }).to_string())
+        .unwrap();
+
+        let errors = extract_idl_errors(&IdlFormatEnum::NonAnchor(idl));
+        assert_eq!(errors.len(), 2);
+        assert_eq!(errors[0].get_attr("name").unwrap().as_str(), Some("Late"));
+        assert_eq!(errors[0].get_attr("msg_literal").unwrap().as_str(), Some("\"late {{x}}\""));
+        assert_eq!(errors[0].get_attr("msg_str").unwrap().as_str(), Some("\"late {x}\""));
+        assert_eq!(errors[1].get_attr("code").unwrap(), Value::from(6000));
+        assert_eq!(errors[1].get_attr("msg_literal").unwrap().as_str(), Some("\"first\""));
+    }
+}
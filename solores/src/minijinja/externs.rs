@@ -0,0 +1,286 @@
+//! 外部接口 crate 的类型导入
+//!
+//! 共享库程序的 IDL 常引用其他程序 IDL 中定义的类型。`--extern name=path_or_version`
+//! 指定提供这些类型的接口 crate：引用但未定义的类型不再报 unknown-type，而是在
+//! `types/mod.rs` 中以 `pub use name::types::X;` 重新导出，生成的 Cargo.toml 添加该依赖，
+//! 并把 serde / arbitrary feature 转发给它。
+//!
+//! 来源为目录时作为 path 依赖，从其 Cargo.toml 读取包名、从 manifest.json 读取导出的
+//! 类型与账户，按此把类型分配给提供它的 crate；否则视为 crates.io 版本要求，省略时取
+//! IDL `metadata.dependencies` 中声明的版本。没有类型表的来源只有一个时，其余未定义
+//! 的类型都从它导入。
+
+use std::collections::{BTreeMap, BTreeSet};
+use std::fs;
+use std::path::{Component, Path, PathBuf};
+use std::str::FromStr;
+
+use minijinja::{context, Value};
+
+use crate::error::SoloresError;
+use crate::idl_format::IdlFormatEnum;
+use crate::warnings::{self, Warning};
+
+/// `--extern` 选项：`name` 或 `name=path_or_version`
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct ExternCrate {
+    /// 依赖名，生成代码中以 `-` 替换为 `_` 后的标识符引用
+    pub name: String,
+    /// crate 目录或版本要求；None 时取 IDL 依赖声明中的版本
+    pub source: Option<String>,
+}
+
+impl FromStr for ExternCrate {
+    type Err = String;
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        let (name, source) = match s.split_once('=') {
+            Some((name, source)) => (name.trim(), Some(source.trim())),
+            None => (s.trim(), None),
+        };
+        let valid = name.starts_with(|c: char| c.is_ascii_alphabetic())
+            && name.chars().all(|c| c.is_ascii_alphanumeric() || c == '_' || c == '-');
+        if !valid || source.is_some_and(str::is_empty) {
+            return Err(format!("外部 crate 配置 '{}' 格式应为 name 或 name=path_or_version", s));
+        }
+        Ok(ExternCrate { name: name.to_string(), source: source.map(str::to_string) })
+    }
+}
+
+/// 外部 crate 的依赖来源
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum ExternSource {
+    /// 本地 crate 目录
+    Path(PathBuf),
+    /// crates.io 版本要求
+    Version(String),
+}
+
+/// 解析后的外部 crate 及从中导入的类型
+#[derive(Debug, Clone)]
+pub struct ResolvedExtern {
+    pub name: String,
+    /// 包名与依赖名不同时的 `package`
+    pub package: Option<String>,
+    pub source: ExternSource,
+    /// 导出的条目：类型名 → 所在模块（types / accounts）；无 manifest.json 时为 None
+    exports: Option<BTreeMap<String, &'static str>>,
+    /// 重新导出的完整路径
+    pub imports: Vec<String>,
+}
+
+impl ResolvedExtern {
+    /// 生成代码中引用该 crate 的标识符
+    pub fn ident(&self) -> String {
+        self.name.replace('-', "_")
+    }
+}
+
+/// 全部外部 crate 的导入结果
+#[derive(Debug, Clone, Default)]
+pub struct ExternImports {
+    pub crates: Vec<ResolvedExtern>,
+}
+
+impl ExternImports {
+    /// 解析 `--extern` 并把 IR 中未定义的类型分配给提供它的外部 crate
+    pub fn resolve(externs: &[ExternCrate], ir: &IdlFormatEnum) -> Result<Self, SoloresError> {
+        let mut crates = externs.iter().map(|extern_crate| resolve_crate(extern_crate, ir)).collect::<Result<Vec<_>, _>>()?;
+        if crates.is_empty() {
+            return Ok(ExternImports::default());
+        }
+
+        let undefined: BTreeSet<String> = warnings::collect(ir)
+            .into_iter()
+            .filter_map(|warning| match warning {
+                Warning::UnknownType { type_name, .. } => Some(type_name),
+                _ => None,
+            })
+            .collect();
+        let fallback = match crates.iter().filter(|c| c.exports.is_none()).count() {
+            1 => crates.iter().position(|c| c.exports.is_none()),
+            _ => None,
+        };
+        for type_name in undefined {
+            let exported = crates.iter().enumerate().find_map(|(index, c)| {
+                c.exports.as_ref()?.get(&type_name).map(|module| (index, *module))
+            });
+            match exported.or(fallback.map(|index| (index, "types"))) {
+                Some((index, module)) => {
+                    let path = format!("{}::{}::{}", crates[index].ident(), module, type_name);
+                    crates[index].imports.push(path);
+                }
+                None => log::debug!("🔍 未定义的类型 {} 不由任何 --extern crate 提供", type_name),
+            }
+        }
+        for c in crates.iter().filter(|c| c.imports.is_empty()) {
+            log::warn!("⚠️ --extern {}：IDL 未引用该 crate 提供的类型，仍添加依赖", c.name);
+        }
+        Ok(ExternImports { crates })
+    }
+
+    /// 类型是否由外部 crate 提供
+    pub fn provides(&self, type_name: &str) -> bool {
+        let suffix = format!("::{}", type_name);
+        self.crates.iter().flat_map(|c| &c.imports).any(|path| path.ends_with(&suffix))
+    }
+
+    /// `types/mod.rs` 中重新导出的路径
+    pub fn reexports(&self) -> Vec<String> {
+        self.crates.iter().flat_map(|c| c.imports.iter().cloned()).collect()
+    }
+
+    /// Cargo.toml 依赖：`{name, package, path, version}`，path 相对于生成的 crate 目录
+    pub fn dependencies(&self, crate_dir: &Path) -> Vec<Value> {
+        let crate_dir = crate_dir.canonicalize().unwrap_or_else(|_| crate_dir.to_path_buf());
+        self.crates
+            .iter()
+            .map(|c| {
+                let (path, version) = match &c.source {
+                    ExternSource::Path(path) => (Some(relative_path(path, &crate_dir).display().to_string()), None),
+                    ExternSource::Version(version) => (None, Some(version.clone())),
+                };
+                context! { name => c.name, package => c.package, path => path, version => version }
+            })
+            .collect()
+    }
+}
+
+/// 解析单个外部 crate 的来源（目录或版本要求）
+pub(crate) fn resolve_crate(extern_crate: &ExternCrate, ir: &IdlFormatEnum) -> Result<ResolvedExtern, SoloresError> {
+    let source = match extern_crate.source.as_deref() {
+        Some(source) if looks_like_path(source) => {
+            let path = Path::new(source);
+            path.canonicalize()
+                .map_err(|e| SoloresError::file_operation_error("读取 --extern crate 目录", source, e))?
+        }
+        Some(version) => {
+            return Ok(version_crate(extern_crate, version.to_string()));
+        }
+        None => {
+            let version = ir
+                .dependencies()
+                .iter()
+                .find(|dependency| dependency.name == extern_crate.name)
+                .and_then(|dependency| dependency.version.clone())
+                .ok_or_else(|| SoloresError::ValidationError {
+                    message: format!("--extern {} 未指定来源，IDL metadata.dependencies 中也没有其版本", extern_crate.name),
+                    field_path: Some(format!("--extern {}", extern_crate.name)),
+                    expected: Some("name=path_or_version".to_string()),
+                    actual: None,
+                })?;
+            return Ok(version_crate(extern_crate, version));
+        }
+    };
+
+    let manifest_path = source.join("Cargo.toml");
+    let manifest = fs::read_to_string(&manifest_path)
+        .map_err(|e| SoloresError::file_operation_error("读取 --extern crate 的 Cargo.toml", manifest_path.display().to_string(), e))?;
+    let package = toml::from_str::<toml::Value>(&manifest)
+        .ok()
+        .and_then(|value| value.get("package")?.get("name")?.as_str().map(str::to_string))
+        .ok_or_else(|| SoloresError::ValidationError {
+            message: format!("--extern {} 的 Cargo.toml 缺少 package.name", extern_crate.name),
+            field_path: Some(manifest_path.display().to_string()),
+            expected: Some("[package] name = \"...\"".to_string()),
+            actual: None,
+        })?;
+
+    Ok(ResolvedExtern {
+        name: extern_crate.name.clone(),
+        package: (package != extern_crate.name).then_some(package),
+        exports: read_exports(&source.join("manifest.json")),
+        source: ExternSource::Path(source),
+        imports: Vec::new(),
+    })
+}
+
+fn version_crate(extern_crate: &ExternCrate, version: String) -> ResolvedExtern {
+    ResolvedExtern {
+        name: extern_crate.name.clone(),
+        package: None,
+        source: ExternSource::Version(version),
+        exports: None,
+        imports: Vec::new(),
+    }
+}
+
+/// 版本要求不含路径分隔符，也不以 `.` 开头
+fn looks_like_path(source: &str) -> bool {
+    source.contains(['/', '\\']) || source.starts_with('.') || Path::new(source).is_dir()
+}
+
+/// 从 Solores 生成的 manifest.json 读取导出的类型与账户，文件不存在或无法解析时返回 None
+fn read_exports(path: &Path) -> Option<BTreeMap<String, &'static str>> {
+    let manifest: serde_json::Value = serde_json::from_str(&fs::read_to_string(path).ok()?).ok()?;
+    let exports = manifest
+        .get("items")?
+        .as_array()?
+        .iter()
+        .filter_map(|item| {
+            let module = match item.get("kind")?.as_str()? {
+                "type" => "types",
+                "account" => "accounts",
+                _ => return None,
+            };
+            Some((item.get("type_name")?.as_str()?.to_string(), module))
+        })
+        .collect();
+    Some(exports)
+}
+
+/// `path` 相对于 `base` 目录的路径，两者应为规范化的绝对路径
+fn relative_path(path: &Path, base: &Path) -> PathBuf {
+    let path: Vec<Component> = path.components().collect();
+    let base: Vec<Component> = base.components().collect();
+    let common = path.iter().zip(&base).take_while(|(a, b)| a == b).count();
+    let relative: PathBuf = std::iter::repeat_n(Component::ParentDir, base.len() - common)
+        .chain(path[common..].iter().copied())
+        .collect();
+    if relative.as_os_str().is_empty() {
+        PathBuf::from(".")
+    } else {
+        relative
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::idl_format::parse_idl_json;
+
+    #[test]
+    fn undefined_types_are_imported_from_extern_crates() {
+        let ir = parse_idl_json(
+            r#"{
+                "address": "11111111111111111111111111111111",
+                "metadata": {"name": "router", "version": "0.1.0", "spec": "0.1.0",
+                             "dependencies": [{"name": "shared-types", "version": "0.3"}]},
+                "instructions": [],
+                "accounts": [{"name": "Route", "discriminator": [1, 2, 3, 4, 5, 6, 7, 8],
+                              "type": {"kind": "struct", "fields": [
+                                  {"name": "fees", "type": {"defined": "Fees"}},
+                                  {"name": "pool", "type": {"defined": "Pool"}}
+                              ]}}]
+            }"#,
+        )
+        .unwrap();
+
+        let shared: ExternCrate = "shared-types".parse().unwrap();
+        let imports = ExternImports::resolve(&[shared], &ir).unwrap();
+        assert_eq!(imports.reexports(), ["shared_types::types::Fees", "shared_types::types::Pool"]);
+        assert!(imports.provides("Fees"));
+        assert_eq!(imports.crates[0].source, ExternSource::Version("0.3".to_string()));
+
+        let missing: ExternCrate = "other".parse().unwrap();
+        assert!(ExternImports::resolve(&[missing], &ir).is_err());
+        assert!("=0.3".parse::<ExternCrate>().is_err());
+    }
+
+    #[test]
+    fn relative_paths_between_crates() {
+        let path = relative_path(Path::new("/work/interfaces/shared"), Path::new("/work/interfaces/router"));
+        assert_eq!(path, PathBuf::from("../shared"));
+        assert_eq!(relative_path(Path::new("/a/b"), Path::new("/a/b")), PathBuf::from("."));
+    }
+}
@@ -0,0 +1,305 @@
+//! 字段语义标注
+//!
+//! 通过 `--field-hint Owner.field=kind` 将账户/类型字段标注为 unix 时间戳、
+//! 基点、Q64.64 定点数或代币数量，生成 chrono / rust_decimal 便捷访问方法。
+//! 代币数量写作 `decimals:N`（固定精度）或 `mint:field`（精度取自同一结构体中
+//! 记录的 mint）。标注较多时可写入 `--field-hints-file`，每行一条，`#` 开头为注释。
+
+use std::path::Path;
+use std::str::FromStr;
+
+use convert_case::{Case, Casing};
+use minijinja::{context, Value};
+use serde::Serialize;
+
+use crate::error::SoloresError;
+
+/// 字段语义种类
+#[derive(Serialize, Debug, Clone, Copy, PartialEq, Eq)]
+#[serde(rename_all = "snake_case")]
+pub enum FieldHintKind {
+    /// unix 时间戳（秒），生成 `<field>_utc() -> Option<DateTime<Utc>>`
+    UnixTimestamp,
+    /// 基点（1/10000），生成 `<field>_decimal() -> Decimal`
+    Bps,
+    /// Q64.64 定点数（u128），生成 `<field>_decimal() -> Decimal`
+    Q64_64,
+    /// 代币最小单位数量，生成 `<field>_ui_amount() -> f64` / `<field>_ui_decimal() -> Decimal`
+    TokenAmount,
+}
+
+/// 代币数量的精度来源
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum AmountDecimals {
+    /// 固定精度
+    Fixed(u8),
+    /// 精度取自同一结构体中 mint 字段对应的 mint 账户，由调用方传入
+    Mint(String),
+}
+
+impl FieldHintKind {
+    /// 该语义允许的字段类型
+    fn accepts(self, rust_type: &str) -> bool {
+        const INTEGERS: [&str; 8] = ["u8", "u16", "u32", "u64", "i8", "i16", "i32", "i64"];
+        match self {
+            FieldHintKind::UnixTimestamp | FieldHintKind::Bps | FieldHintKind::TokenAmount => {
+                INTEGERS.contains(&rust_type)
+            }
+            FieldHintKind::Q64_64 => rust_type == "u128",
+        }
+    }
+
+    fn expected_types(self) -> &'static str {
+        match self {
+            FieldHintKind::UnixTimestamp | FieldHintKind::Bps | FieldHintKind::TokenAmount => "u8..u64 / i8..i64",
+            FieldHintKind::Q64_64 => "u128",
+        }
+    }
+}
+
+impl FromStr for FieldHintKind {
+    type Err = String;
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        match s {
+            "unix_timestamp" | "timestamp" => Ok(FieldHintKind::UnixTimestamp),
+            "bps" | "basis_points" => Ok(FieldHintKind::Bps),
+            "q64_64" | "q64.64" => Ok(FieldHintKind::Q64_64),
+            other => Err(format!(
+                "未知的字段语义 '{}'，可选值: unix_timestamp, bps, q64_64, decimals:N, mint:field",
+                other
+            )),
+        }
+    }
+}
+
+/// 单条字段标注：`Owner.field=kind`
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct FieldHint {
+    pub owner: String,
+    pub field: String,
+    pub kind: FieldHintKind,
+    /// 代币数量的精度来源（仅 TokenAmount）
+    pub decimals: Option<AmountDecimals>,
+}
+
+impl FromStr for FieldHint {
+    type Err = String;
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        let (path, kind) = s
+            .split_once('=')
+            .ok_or_else(|| format!("字段标注 '{}' 格式应为 Owner.field=kind", s))?;
+        let (owner, field) = path
+            .split_once('.')
+            .filter(|(owner, field)| !owner.is_empty() && !field.is_empty())
+            .ok_or_else(|| format!("字段标注 '{}' 格式应为 Owner.field=kind", s))?;
+        let (kind, decimals) = match kind.trim().split_once(':') {
+            Some(("decimals", value)) => {
+                let decimals = value
+                    .trim()
+                    .parse::<u8>()
+                    .ok()
+                    .filter(|decimals| *decimals <= MAX_DECIMALS)
+                    .ok_or_else(|| format!("字段标注 '{}' 的精度应为 0..={}", s, MAX_DECIMALS))?;
+                (FieldHintKind::TokenAmount, Some(AmountDecimals::Fixed(decimals)))
+            }
+            Some(("mint", field)) if !field.trim().is_empty() => {
+                (FieldHintKind::TokenAmount, Some(AmountDecimals::Mint(field.trim().to_string())))
+            }
+            _ => (kind.trim().parse()?, None),
+        };
+        Ok(FieldHint {
+            owner: owner.trim().to_string(),
+            field: field.trim().to_string(),
+            kind,
+            decimals,
+        })
+    }
+}
+
+/// rust_decimal 支持的最大小数位数
+const MAX_DECIMALS: u8 = 28;
+
+/// 读取 `--field-hints-file`：每行一条 `Owner.field=kind`，忽略空行与 `#` 注释
+pub fn load_field_hints_file(path: &Path) -> Result<Vec<FieldHint>, SoloresError> {
+    let content = std::fs::read_to_string(path)
+        .map_err(|e| SoloresError::file_operation_error("读取字段标注文件", path.display().to_string(), e))?;
+    content
+        .lines()
+        .enumerate()
+        .map(|(index, line)| (index, line.split('#').next().unwrap_or_default().trim()))
+        .filter(|(_, line)| !line.is_empty())
+        .map(|(index, line)| {
+            line.parse().map_err(|message| SoloresError::ValidationError {
+                message,
+                field_path: Some(format!("{}:{}", path.display(), index + 1)),
+                expected: Some("Owner.field=kind".to_string()),
+                actual: Some(line.to_string()),
+            })
+        })
+        .collect()
+}
+
+/// 将标注解析到上下文中的账户与struct类型，返回访问方法模板数据
+///
+/// 标注的账户/字段不存在或字段类型与语义不符时返回 ValidationError
+pub fn resolve_field_hints(
+    hints: &[FieldHint],
+    context: &Value,
+) -> Result<Vec<Value>, SoloresError> {
+    let owners = [("accounts", "crate::accounts"), ("types", "crate::types")];
+
+    hints
+        .iter()
+        .map(|hint| {
+            let field_path = format!("{}.{}", hint.owner, hint.field);
+            let owner_name = hint.owner.to_case(Case::Pascal);
+            let field_name = hint.field.to_case(Case::Snake);
+
+            let (owner, module) = owners
+                .iter()
+                .find_map(|(key, module)| {
+                    let items = context.get_attr(key).ok()?;
+                    items
+                        .try_iter()
+                        .ok()?
+                        .find(|item| attr_str(item, "name").as_deref() == Some(owner_name.as_str()))
+                        .map(|item| (item, *module))
+                })
+                .ok_or_else(|| validation_error(&field_path, format!("未找到账户或类型 '{}'", hint.owner)))?;
+
+            let field = owner
+                .get_attr("fields")
+                .ok()
+                .and_then(|fields| fields.try_iter().ok())
+                .and_then(|mut fields| {
+                    fields.find(|field| {
+                        attr_str(field, "name").map(|name| name.to_case(Case::Snake)).as_deref()
+                            == Some(field_name.as_str())
+                    })
+                })
+                .ok_or_else(|| validation_error(&field_path, format!("'{}' 中未找到字段 '{}'", owner_name, hint.field)))?;
+
+            let rust_type = attr_str(&field, "rust_type").unwrap_or_default();
+            if !hint.kind.accepts(&rust_type) {
+                return Err(SoloresError::ValidationError {
+                    message: format!("字段类型与 {:?} 标注不符", hint.kind),
+                    field_path: Some(format!("--field-hint {}", field_path)),
+                    expected: Some(hint.kind.expected_types().to_string()),
+                    actual: Some(rust_type),
+                });
+            }
+
+            let (decimals, mint_field) = match &hint.decimals {
+                Some(AmountDecimals::Fixed(decimals)) => (Some(*decimals), None),
+                Some(AmountDecimals::Mint(mint)) => {
+                    let mint_name = mint.to_case(Case::Snake);
+                    let mint_field = owner
+                        .get_attr("fields")
+                        .ok()
+                        .and_then(|fields| fields.try_iter().ok())
+                        .and_then(|mut fields| {
+                            fields.find(|field| {
+                                attr_str(field, "name").map(|name| name.to_case(Case::Snake)).as_deref()
+                                    == Some(mint_name.as_str())
+                            })
+                        })
+                        .ok_or_else(|| validation_error(&field_path, format!("'{}' 中未找到 mint 字段 '{}'", owner_name, mint)))?;
+                    let mint_type = attr_str(&mint_field, "rust_type").unwrap_or_default();
+                    if mint_type != "solana_pubkey::Pubkey" {
+                        return Err(SoloresError::ValidationError {
+                            message: format!("mint 字段 '{}' 不是 Pubkey", mint),
+                            field_path: Some(format!("--field-hint {}", field_path)),
+                            expected: Some("solana_pubkey::Pubkey".to_string()),
+                            actual: Some(mint_type),
+                        });
+                    }
+                    (None, attr_str(&mint_field, "name"))
+                }
+                None => (None, None),
+            };
+
+            Ok(context! {
+                owner_path => format!("{}::{}", module, owner_name),
+                field => attr_str(&field, "name").unwrap_or_default(),
+                accessor => field_name,
+                rust_type => rust_type,
+                kind => Value::from_serialize(hint.kind),
+                decimals => decimals,
+                mint_field => mint_field,
+            })
+        })
+        .collect()
+}
+
+fn attr_str(value: &Value, name: &str) -> Option<String> {
+    value
+        .get_attr(name)
+        .ok()
+        .and_then(|attr| attr.as_str().map(str::to_string))
+}
+
+fn validation_error(field_path: &str, message: String) -> SoloresError {
+    SoloresError::ValidationError {
+        message,
+        field_path: Some(format!("--field-hint {}", field_path)),
+        expected: None,
+        actual: None,
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn parses_field_hint() {
+        let hint: FieldHint = "Pool.startTime=unix_timestamp".parse().unwrap();
+        assert_eq!(hint.owner, "Pool");
+        assert_eq!(hint.field, "startTime");
+        assert_eq!(hint.kind, FieldHintKind::UnixTimestamp);
+        assert!("Pool=bps".parse::<FieldHint>().is_err());
+        assert!("Pool.fee=percent".parse::<FieldHint>().is_err());
+
+        let hint: FieldHint = "Pool.baseReserve=decimals:6".parse().unwrap();
+        assert_eq!((hint.kind, hint.decimals), (FieldHintKind::TokenAmount, Some(AmountDecimals::Fixed(6))));
+        let hint: FieldHint = "Pool.quote_reserve=mint:quote_mint".parse().unwrap();
+        assert_eq!(hint.decimals, Some(AmountDecimals::Mint("quote_mint".to_string())));
+        assert!("Pool.amount=decimals:40".parse::<FieldHint>().is_err());
+        assert!("Pool.amount=mint:".parse::<FieldHint>().is_err());
+    }
+
+    #[test]
+    fn rejects_mismatched_field_type() {
+        let context = context! {
+            accounts => vec![context! {
+                name => "Pool",
+                fields => vec![context! { name => "sqrtPrice", rust_type => "u64" }],
+            }],
+        };
+        let hint: FieldHint = "Pool.sqrt_price=q64_64".parse().unwrap();
+        assert!(resolve_field_hints(&[hint], &context).is_err());
+        let hint: FieldHint = "Pool.sqrt_price=bps".parse().unwrap();
+        assert_eq!(resolve_field_hints(&[hint], &context).unwrap().len(), 1);
+    }
+
+    #[test]
+    fn token_amount_mint_must_be_pubkey_field() {
+        let context = context! {
+            types => vec![context! {
+                name => "Pool",
+                fields => vec![
+                    context! { name => "baseReserve", rust_type => "u64" },
+                    context! { name => "baseMint", rust_type => "solana_pubkey::Pubkey" },
+                    context! { name => "lpSupply", rust_type => "u64" },
+                ],
+            }],
+        };
+        let hint: FieldHint = "Pool.base_reserve=mint:base_mint".parse().unwrap();
+        let accessors = resolve_field_hints(&[hint], &context).unwrap();
+        assert_eq!(accessors[0].get_attr("mint_field").unwrap().as_str(), Some("baseMint"));
+        let hint: FieldHint = "Pool.base_reserve=mint:lp_supply".parse().unwrap();
+        assert!(resolve_field_hints(&[hint], &context).is_err());
+    }
+}
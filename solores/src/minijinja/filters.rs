@@ -37,195 +37,48 @@ pub fn to_pascal_case_filter(value: String) -> String {
 
 /// 处理类型路径的过滤器
 pub fn type_path_filter(value: String) -> String {
-    // 添加调试输出
-    if value.starts_with("SmallVec") {
-        eprintln!("🔍 type_path_filter 检测到 SmallVec: {}", value);
-    }
-    
-    // 基础类型和已包含命名空间的类型直接返回
-    match value.as_str() {
-        // Rust基础类型
-        "u8" | "u16" | "u32" | "u64" | "u128" |
-        "i8" | "i16" | "i32" | "i64" | "i128" |
-        "f32" | "f64" | "bool" | "String" => value,
-        _ => {
-            // 首先尝试递归处理复杂类型
-            if let Some(result) = process_complex_type(&value) {
-                result
-            } else if value.contains("::") {
-                // 已经包含命名空间的简单类型直接返回
-                value
-            } else {
-                // 其他都视为自定义类型，添加crate::types::前缀
-                format!("crate::types::{}", value)
-            }
-        }
-    }
-}
-
-/// 递归处理复杂类型（Option, Vec, 数组等）
-pub fn process_complex_type(value: &str) -> Option<String> {
-    // SmallVec类型处理 - 转换为Vec
-    if value.starts_with("SmallVec<") {
-        if let Some(inner) = extract_smallvec_inner(value) {
-            let processed_inner = type_path_filter(inner);
-            return Some(format!("Vec<{}>", processed_inner));
-        }
-    }
-    
-    // Vec类型处理
-    if value.starts_with("Vec<") {
-        if let Some(inner) = extract_generic_inner(value, "Vec") {
-            let processed_inner = type_path_filter(inner);
-            return Some(format!("Vec<{}>", processed_inner));
-        }
-    }
-    if value.starts_with("std::vec::Vec<") {
-        if let Some(inner) = extract_generic_inner(value, "std::vec::Vec") {
-            let processed_inner = type_path_filter(inner);
-            return Some(format!("std::vec::Vec<{}>", processed_inner));
-        }
-    }
-    
-    // Option类型处理
-    if value.starts_with("Option<") {
-        if let Some(inner) = extract_generic_inner(value, "Option") {
-            let processed_inner = type_path_filter(inner);
-            return Some(format!("Option<{}>", processed_inner));
-        }
-    }
-    if value.starts_with("std::option::Option<") {
-        if let Some(inner) = extract_generic_inner(value, "std::option::Option") {
-            let processed_inner = type_path_filter(inner);
-            return Some(format!("std::option::Option<{}>", processed_inner));
-        }
-    }
-    
-    // 数组类型处理
-    if value.starts_with("[") && value.contains(";") {
-        if let Some((inner_type, size)) = extract_array_parts(value) {
-            let processed_inner = type_path_filter(inner_type);
-            return Some(format!("[{}; {}]", processed_inner, size));
-        }
-    }
-    
-    None
+    super::type_mapper::map_type_name(&value)
 }
 
-/// 提取泛型类型的内部类型
-pub fn extract_generic_inner(value: &str, prefix: &str) -> Option<String> {
-    if !value.starts_with(prefix) {
-        return None;
-    }
-    
-    let start = prefix.len() + 1; // +1 for '<'
-    if start >= value.len() {
-        return None;
-    }
-    
-    let end = value.rfind('>')?;
-    if end <= start {
-        return None;
+/// 处理 Rust 关键字字段名
+pub fn rust_field_filter(value: String) -> String {
+    // 元组结构体的位置字段在命名结构体（账户/事件）中展开为 field_N
+    if !value.is_empty() && value.chars().all(|c| c.is_ascii_digit()) {
+        return format!("field_{}", value);
     }
-    
-    Some(value[start..end].to_string())
+    crate::idl_format::identifiers::rust_ident(&value)
 }
 
-/// 提取SmallVec的内部类型（忽略尺寸参数，只返回实际类型）
-/// SmallVec<u8, Pubkey> -> Pubkey
-/// SmallVec<16, CompiledInstruction> -> CompiledInstruction
-pub fn extract_smallvec_inner(value: &str) -> Option<String> {
-    if !value.starts_with("SmallVec<") {
-        return None;
-    }
-    
-    let start = "SmallVec<".len();
-    if start >= value.len() {
-        return None;
-    }
-    
-    let end = value.rfind('>')?;
-    if end <= start {
-        return None;
-    }
-    
-    let inner = &value[start..end];
-    
-    // 查找第一个逗号，之后的部分是实际类型
-    if let Some(comma_pos) = inner.find(',') {
-        let type_part = inner[comma_pos + 1..].trim();
-        Some(type_part.to_string())
-    } else {
-        // 如果没有逗号，说明格式不对，返回None
-        None
-    }
+/// `--serde-rename` 下字段的 serde 名称：original 保留 IDL 原名，camel 转为 camelCase；
+/// 与字段名相同（serde 默认名称）或为元组位置字段时返回 None
+pub fn serde_rename_filter(idl_name: Option<String>, field: String, mode: Option<String>) -> Option<String> {
+    let idl_name = idl_name.filter(|name| !name.is_empty() && !name.chars().all(|c| c.is_ascii_digit()))?;
+    let renamed = match mode.as_deref() {
+        Some("original") => idl_name,
+        Some("camel") => idl_name.to_case(Case::Camel),
+        _ => return None,
+    };
+    (field.trim_start_matches("r#") != renamed).then_some(renamed)
 }
 
-/// 提取数组类型的组成部分，正确处理嵌套数组
-pub fn extract_array_parts(value: &str) -> Option<(String, String)> {
-    if !value.starts_with("[") || !value.ends_with("]") {
-        return None;
-    }
-    
-    let inner = &value[1..value.len()-1];
-    
-    // 找到最后一个分号，这样可以正确处理嵌套数组
-    // 对于 "[[u64; 8]; 12]"，inner = "[u64; 8]; 12"
-    // 我们需要找到最后的分号来分离 "[u64; 8]" 和 "12"
-    if let Some(semicolon_pos) = inner.rfind(';') {
-        let type_part = inner[..semicolon_pos].trim().to_string();
-        let size_part = inner[semicolon_pos+1..].trim().to_string();
-        
-        // 验证 size_part 是否为纯数字
-        if size_part.chars().all(|c| c.is_ascii_digit()) {
-            Some((type_part, size_part))
-        } else {
-            // 如果不是纯数字，说明这不是一个有效的数组尺寸
-            None
-        }
-    } else {
-        None
-    }
+/// `--gen-jsonschema` 下字段的 `schemars(with = ...)` 类型，字段类型可直接派生 `JsonSchema` 时返回 None；
+/// `string_pubkey_containers` 表示模板以字符串序列化 `Option` / `Vec<Pubkey>` 字段，替换了类型的字段
+/// 传入 IDL 原类型 `idl_rust_type`
+pub fn schemars_with_filter(
+    rust_type: String,
+    string_pubkey_containers: Option<bool>,
+    idl_rust_type: Option<String>,
+) -> Option<String> {
+    super::generators::jsonschema::field_schemars_with(
+        &rust_type,
+        idl_rust_type.as_deref(),
+        string_pubkey_containers.unwrap_or(false),
+    )
 }
 
-/// 处理 Rust 关键字字段名
-pub fn rust_field_filter(value: String) -> String {
-    match value.as_str() {
-        "type" => "r#type".to_string(),
-        "async" => "r#async".to_string(),
-        "await" => "r#await".to_string(),
-        "match" => "r#match".to_string(),
-        "impl" => "r#impl".to_string(),
-        "trait" => "r#trait".to_string(),
-        "struct" => "r#struct".to_string(),
-        "enum" => "r#enum".to_string(),
-        "fn" => "r#fn".to_string(),
-        "let" => "r#let".to_string(),
-        "mut" => "r#mut".to_string(),
-        "ref" => "r#ref".to_string(),
-        "if" => "r#if".to_string(),
-        "else" => "r#else".to_string(),
-        "loop" => "r#loop".to_string(),
-        "while" => "r#while".to_string(),
-        "for" => "r#for".to_string(),
-        "in" => "r#in".to_string(),
-        "break" => "r#break".to_string(),
-        "continue" => "r#continue".to_string(),
-        "return" => "r#return".to_string(),
-        "const" => "r#const".to_string(),
-        "static" => "r#static".to_string(),
-        "pub" => "r#pub".to_string(),
-        "mod" => "r#mod".to_string(),
-        "use" => "r#use".to_string(),
-        "crate" => "r#crate".to_string(),
-        "super" => "r#super".to_string(),
-        "self" => "r#self".to_string(),
-        "Self" => "r#Self".to_string(),
-        "where" => "r#where".to_string(),
-        "extern" => "r#extern".to_string(),
-        "unsafe" => "r#unsafe".to_string(),
-        _ => value,
-    }
+/// 替换了类型的字段（`--field-type`）经由 IDL 原类型序列化时，原类型的 serde_with 适配类型
+pub fn serde_as_filter(idl_rust_type: String, string_pubkey_containers: Option<bool>) -> String {
+    super::generators::field_types::serde_as(&idl_rust_type, string_pubkey_containers.unwrap_or(false))
 }
 
 /// 检查字符串是否以指定前缀开始
@@ -233,23 +86,9 @@ pub fn starts_with_filter(value: String, prefix: String) -> bool {
     value.starts_with(&prefix)
 }
 
-/// 处理多行文档字符串，为每行添加///前缀
+/// 处理多行文档字符串，转义后为每行添加///前缀
 pub fn multiline_docs_filter(value: String) -> String {
-    if value.is_empty() {
-        return String::new();
-    }
-    
-    value
-        .lines()
-        .map(|line| {
-            if line.trim().is_empty() {
-                "///".to_string()
-            } else {
-                format!("/// {}", line.trim())
-            }
-        })
-        .collect::<Vec<String>>()
-        .join("\n")
+    crate::idl_format::docs::doc_comment(&value)
 }
 
 /// 正则表达式替换过滤器 - 简化版本，直接从字段类型中提取数字
@@ -327,6 +166,48 @@ pub fn is_eq_compatible_filter(type_name: String) -> bool {
     true
 }
 
+/// 为变长字段选择arbitrary长度上限函数，返回空字符串表示无需限制；替换了类型的字段由 IDL 原类型生成后转换
+///
+/// 上限作用于任意嵌套层级：`Vec<Vec<u8>>`、`Option<Vec<T>>`、数组与 HashMap 中的 Vec / String 同样受限，
+/// 已定义类型的字段由该类型自身的派生处理
+pub fn arbitrary_with_filter(rust_type: String, idl_rust_type: Option<String>) -> String {
+    match idl_rust_type {
+        Some(idl_rust_type) => match capped_arbitrary(&idl_rust_type) {
+            Some(generate) => format!("crate::field_types::arbitrary_with::<{}, _>({})", idl_rust_type, generate),
+            None => format!("crate::field_types::arbitrary::<{}, _>", idl_rust_type),
+        },
+        None => capped_arbitrary(&rust_type).unwrap_or_default(),
+    }
+}
+
+/// 类型中含变长部分时，返回按 `crate::fuzz` 上限生成该类型的表达式
+fn capped_arbitrary(rust_type: &str) -> Option<String> {
+    const ANY: &str = "crate::fuzz::any";
+    let rust_type = rust_type.trim();
+    if matches!(rust_type, "std::string::String" | "String") {
+        return Some("crate::fuzz::capped_string".to_string());
+    }
+    if let Some((element, size)) = super::utils::split_array_type(rust_type) {
+        return capped_arbitrary(element).map(|element| format!("crate::fuzz::array_of::<_, {}>({})", size, element));
+    }
+    let (base, args) = super::type_mapper::split_generic(rust_type)?;
+    match (base, args.as_slice()) {
+        ("std::vec::Vec" | "Vec", [element]) => Some(match capped_arbitrary(element) {
+            Some(element) => format!("crate::fuzz::capped_vec_of({})", element),
+            None => "crate::fuzz::capped_vec".to_string(),
+        }),
+        ("std::option::Option" | "Option", [inner]) => {
+            capped_arbitrary(inner).map(|inner| format!("crate::fuzz::option_of({})", inner))
+        }
+        ("std::collections::HashMap" | "HashMap", [key, value]) => Some(format!(
+            "crate::fuzz::capped_map_of({}, {})",
+            capped_arbitrary(key).as_deref().unwrap_or(ANY),
+            capped_arbitrary(value).as_deref().unwrap_or(ANY)
+        )),
+        _ => None,
+    }
+}
+
 /// 辅助函数：从过滤器中提取数组部分
 fn extract_array_parts_from_filter(value: &str) -> Option<(String, String)> {
     if !value.starts_with("[") || !value.ends_with("]") {
@@ -347,4 +228,47 @@ fn extract_array_parts_from_filter(value: &str) -> Option<(String, String)> {
     } else {
         None
     }
-}
\ No newline at end of file
+}
+/// 将字符串转为带引号并已转义的 TOML 字符串字面量
+pub fn toml_str_filter(value: String) -> String {
+    toml::Value::String(value).to_string()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn arbitrary_caps_apply_at_every_nesting_level() {
+        let with = |rust_type: &str| arbitrary_with_filter(rust_type.to_string(), None);
+        assert_eq!(with("u64"), "");
+        assert_eq!(with("[std::option::Option<u8>; 4]"), "");
+        assert_eq!(with("std::vec::Vec<u8>"), "crate::fuzz::capped_vec");
+        assert_eq!(with("std::vec::Vec<std::vec::Vec<u8>>"), "crate::fuzz::capped_vec_of(crate::fuzz::capped_vec)");
+        assert_eq!(with("std::option::Option<std::vec::Vec<crate::types::Fees>>"), "crate::fuzz::option_of(crate::fuzz::capped_vec)");
+        assert_eq!(with("[std::string::String; 2]"), "crate::fuzz::array_of::<_, 2>(crate::fuzz::capped_string)");
+        assert_eq!(
+            with("std::collections::HashMap<solana_pubkey::Pubkey, std::vec::Vec<u64>>"),
+            "crate::fuzz::capped_map_of(crate::fuzz::any, crate::fuzz::capped_vec)"
+        );
+        assert_eq!(
+            arbitrary_with_filter("my::Hash".to_string(), Some("[u8; 32]".to_string())),
+            "crate::field_types::arbitrary::<[u8; 32], _>"
+        );
+        assert_eq!(
+            arbitrary_with_filter("my::Names".to_string(), Some("std::vec::Vec<std::string::String>".to_string())),
+            "crate::field_types::arbitrary_with::<std::vec::Vec<std::string::String>, _>(crate::fuzz::capped_vec_of(crate::fuzz::capped_string))"
+        );
+    }
+
+    #[test]
+    fn serde_rename_only_emits_names_that_differ_from_the_field() {
+        let rename = |idl: &str, field: &str, mode: &str| serde_rename_filter(Some(idl.to_string()), field.to_string(), Some(mode.to_string()));
+        assert_eq!(rename("aToB", "a_to_b", "original").as_deref(), Some("aToB"));
+        assert_eq!(rename("base_mint", "base_mint", "original"), None);
+        assert_eq!(rename("base_mint", "base_mint", "camel").as_deref(), Some("baseMint"));
+        assert_eq!(rename("type", "r#type", "camel"), None);
+        assert_eq!(rename("aToB", "a_to_b", "snake"), None);
+        assert_eq!(rename("0", "field_0", "original"), None);
+    }
+}
@@ -3,6 +3,7 @@
 //! 核心生成器类，协调各个专门模块完成代码生成
 
 use crate::error::SoloresError;
+use crate::fixtures::InstructionFixture;
 use crate::idl_format::{IdlFormat, IdlFormatEnum};
 use log::info;
 use minijinja::{Environment, Value, Error};
@@ -12,7 +13,14 @@ use std::path::Path;
 use super::{
     filters::*,
     context,
-    generators::{accounts, instructions, events, types, parsers, errors, config, common}
+    utils,
+    field_hints::{self, FieldHint},
+    compute_units::{self, ComputeUnitHint},
+    externs::ExternImports,
+    pubkey_crate::{self, PubkeyCrate},
+    rust_version::{RustFeatures, RustVersion},
+    account_roles,
+    generators::{accounts, instructions, events, types, parsers, errors, coption, small_vec, raw_tail, field_types, accessors, fuzz, layout, proposal, program_test, benches, compile_fail, rpc_fixtures, anchor_cpi, compute, versioned_tx, manifest, sizes, jsonschema, actions, config, common, pda, compat, typed_keys, account_ext, history, cache, db, interop, state, upgrade, web, pubkey}
 };
 
 // 统一库相关结构体定义
@@ -42,10 +50,84 @@ pub struct UnifiedLibraryConfig {
     pub base_args: crate::Args,
 }
 
+/// 账户数据尾部多余字节的处理策略
+#[derive(clap::ValueEnum, serde::Serialize, Debug, Default, Clone, Copy, PartialEq, Eq)]
+#[serde(rename_all = "snake_case")]
+pub enum TrailingBytes {
+    /// 要求数据与已知布局完全一致
+    Strict,
+    /// 只读取已知布局前缀，忽略账户扩容后追加的字节
+    #[default]
+    Allow,
+}
+
+/// 生成的 serde 字段名风格
+#[derive(clap::ValueEnum, serde::Serialize, Debug, Default, Clone, Copy, PartialEq, Eq)]
+#[serde(rename_all = "snake_case")]
+pub enum SerdeRename {
+    /// 保留 IDL 中的原始字段名
+    Original,
+    /// camelCase，与 Anchor TS 客户端一致
+    Camel,
+    /// Rust 字段名（snake_case），不生成 rename 属性
+    #[default]
+    Snake,
+}
+
+/// 兼容旧版 solores 的 crate 布局
+#[derive(clap::ValueEnum, Debug, Clone, Copy, PartialEq, Eq)]
+pub enum CompatLayout {
+    /// solores 0.2：单文件 instructions.rs / typedefs.rs，`*_keys_to_meta` 等旧 API 名称
+    #[value(name = "0.2")]
+    V02,
+}
+
+/// 写入生成 crate lib.rs 顶部的可选 lint
+#[derive(clap::ValueEnum, Debug, Clone, Copy, PartialEq, Eq)]
+pub enum Lint {
+    /// `#![forbid(unsafe_code)]`；零拷贝视图以 `unsafe` 读取未对齐字段，存在时改为 deny 并在读取处局部允许
+    ForbidUnsafe,
+    /// `#![deny(missing_docs)]`：lib.rs 自身的条目全部带文档，IDL 生成的模块局部允许缺少文档
+    DenyMissingDocs,
+}
+
+/// 未配置估算值的指令默认使用的计算单元（与运行时默认上限一致）
+pub const DEFAULT_COMPUTE_UNITS: u32 = 200_000;
+
 /// MiniJinja模板生成器
 pub struct MinijinjaTemplateGenerator {
     idl_enum: IdlFormatEnum,
     env: Environment<'static>,
+    package: crate::cargo::PackageMetadata,
+    field_hints: Vec<FieldHint>,
+    trailing_bytes: TrailingBytes,
+    serde_rename: SerdeRename,
+    program_tests: bool,
+    benches: bool,
+    compile_fail_tests: bool,
+    history_stats: bool,
+    rpc_fixtures: Vec<InstructionFixture>,
+    anchor_cpi: bool,
+    cache: bool,
+    db: Option<db::DbTarget>,
+    interop: Option<interop::InteropCrate>,
+    upgrade: bool,
+    web: bool,
+    readme_graph: bool,
+    compat: Option<CompatLayout>,
+    typed_keys: bool,
+    account_traits: bool,
+    pubkey_crate: PubkeyCrate,
+    externs: ExternImports,
+    min_rust_version: Option<RustVersion>,
+    lints: Vec<Lint>,
+    non_exhaustive: bool,
+    lossy_parser: bool,
+    compute_units: Vec<ComputeUnitHint>,
+    default_compute_units: u32,
+    size_report: Option<u64>,
+    jsonschema: bool,
+    source_idl: Option<IdlFormatEnum>,
 }
 
 impl MinijinjaTemplateGenerator {
@@ -60,13 +142,226 @@ impl MinijinjaTemplateGenerator {
         env.add_filter("lower", |value: String| -> String { value.to_lowercase() });
         env.add_filter("type_path", type_path_filter);
         env.add_filter("rust_field", rust_field_filter);
+        env.add_filter("serde_rename", serde_rename_filter);
+        env.add_filter("schemars_with", schemars_with_filter);
+        env.add_filter("serde_as", serde_as_filter);
         env.add_filter("starts_with", starts_with_filter);
         env.add_filter("multiline_docs", multiline_docs_filter);
         env.add_filter("regex_replace", regex_replace_filter);
         env.add_filter("is_copy_compatible", is_copy_compatible_filter);
         env.add_filter("is_eq_compatible", is_eq_compatible_filter);
+        env.add_filter("arbitrary_with", arbitrary_with_filter);
+        env.add_filter("toml_str", toml_str_filter);
         
-        Ok(Self { idl_enum, env })
+        Ok(Self {
+            idl_enum,
+            env,
+            package: Default::default(),
+            field_hints: Vec::new(),
+            trailing_bytes: TrailingBytes::default(),
+            serde_rename: SerdeRename::default(),
+            program_tests: false,
+            benches: false,
+            compile_fail_tests: false,
+            history_stats: false,
+            rpc_fixtures: Vec::new(),
+            anchor_cpi: false,
+            cache: false,
+            db: None,
+            interop: None,
+            upgrade: false,
+            web: false,
+            readme_graph: false,
+            compat: None,
+            typed_keys: false,
+            account_traits: false,
+            pubkey_crate: PubkeyCrate::default(),
+            externs: ExternImports::default(),
+            min_rust_version: None,
+            lints: Vec::new(),
+            non_exhaustive: false,
+            lossy_parser: false,
+            compute_units: Vec::new(),
+            default_compute_units: DEFAULT_COMPUTE_UNITS,
+            size_report: None,
+            jsonschema: false,
+            source_idl: None,
+        })
+    }
+
+    /// 设置生成 Cargo.toml 使用的包元数据
+    pub fn with_package_metadata(mut self, package: crate::cargo::PackageMetadata) -> Self {
+        self.package = package;
+        self
+    }
+
+    /// 设置字段语义标注，用于生成便捷访问方法
+    pub fn with_field_hints(mut self, field_hints: Vec<FieldHint>) -> Self {
+        self.field_hints = field_hints;
+        self
+    }
+
+    /// 设置生成的账户解析方法对尾部多余字节的处理策略
+    pub fn with_trailing_bytes(mut self, trailing_bytes: TrailingBytes) -> Self {
+        self.trailing_bytes = trailing_bytes;
+        self
+    }
+
+    /// 设置重命名前的原始 IR，用于在 manifest.json 中记录来源 IDL 条目
+    pub fn with_source_idl(mut self, source_idl: IdlFormatEnum) -> Self {
+        self.source_idl = Some(source_idl);
+        self
+    }
+
+    /// 设置 serde 序列化使用的字段名风格
+    pub fn with_serde_rename(mut self, serde_rename: SerdeRename) -> Self {
+        self.serde_rename = serde_rename;
+        self
+    }
+
+    /// 设置是否生成基于 solana-program-test 的集成测试
+    pub fn with_program_tests(mut self, program_tests: bool) -> Self {
+        self.program_tests = program_tests;
+        self
+    }
+
+    /// 设置是否生成基于 criterion 的解析器基准测试
+    pub fn with_benches(mut self, benches: bool) -> Self {
+        self.benches = benches;
+        self
+    }
+
+    /// 设置是否生成基于解析器统计历史指令数量、大小与失败率的 history 模块
+    pub fn with_history_stats(mut self, history_stats: bool) -> Self {
+        self.history_stats = history_stats;
+        self
+    }
+
+    /// 设置从真实交易提取的指令，生成 tests/rpc_fixtures.rs
+    pub fn with_rpc_fixtures(mut self, rpc_fixtures: Vec<InstructionFixture>) -> Self {
+        self.rpc_fixtures = rpc_fixtures;
+        self
+    }
+
+    /// 设置是否生成 trybuild 编译失败测试
+    pub fn with_compile_fail_tests(mut self, compile_fail_tests: bool) -> Self {
+        self.compile_fail_tests = compile_fail_tests;
+        self
+    }
+
+    /// 设置是否生成 Anchor CPI 模块
+    pub fn with_anchor_cpi(mut self, anchor_cpi: bool) -> Self {
+        self.anchor_cpi = anchor_cpi;
+        self
+    }
+
+    /// 设置是否生成账户缓存模块
+    pub fn with_cache(mut self, cache: bool) -> Self {
+        self.cache = cache;
+        self
+    }
+
+    /// 设置数据库行映射的目标，生成 db.rs 与建表迁移文件
+    pub fn with_db(mut self, db: Option<db::DbTarget>) -> Self {
+        self.db = db;
+        self
+    }
+
+    /// 设置互转的 Anchor 接口 crate，生成与其账户 / 类型之间的 `From` 实现
+    pub fn with_interop(mut self, interop: Option<interop::InteropCrate>) -> Self {
+        self.interop = interop;
+        self
+    }
+
+    /// 设置是否生成程序升级检查模块
+    pub fn with_upgrade(mut self, upgrade: bool) -> Self {
+        self.upgrade = upgrade;
+        self
+    }
+
+    /// 设置是否生成钱包适配器用的未签名交易构建模块
+    pub fn with_web(mut self, web: bool) -> Self {
+        self.web = web;
+        self
+    }
+
+    /// 设置是否在 README.md 中嵌入 mermaid 依赖图
+    pub fn with_readme_graph(mut self, readme_graph: bool) -> Self {
+        self.readme_graph = readme_graph;
+        self
+    }
+
+    /// 按旧版 solores 的布局与 API 名称输出
+    pub fn with_compat(mut self, compat: Option<CompatLayout>) -> Self {
+        self.compat = compat;
+        self
+    }
+
+    /// 设置是否为多个指令共用的账户角色生成 Pubkey 新类型
+    pub fn with_typed_keys(mut self, typed_keys: bool) -> Self {
+        self.typed_keys = typed_keys;
+        self
+    }
+
+    /// 设置是否生成所有账户结构体共同实现的 `ProgramAccountExt` trait
+    pub fn with_account_traits(mut self, account_traits: bool) -> Self {
+        self.account_traits = account_traits;
+        self
+    }
+
+    /// 设置生成代码使用的 Pubkey 来源
+    pub fn with_pubkey_crate(mut self, pubkey_crate: PubkeyCrate) -> Self {
+        self.pubkey_crate = pubkey_crate;
+        self
+    }
+
+    /// 设置提供未定义类型的外部接口 crate
+    pub fn with_externs(mut self, externs: ExternImports) -> Self {
+        self.externs = externs;
+        self
+    }
+
+    /// 设置生成代码的最低 Rust 版本，模板据此避免更新的语法与 API
+    pub fn with_min_rust_version(mut self, min_rust_version: Option<RustVersion>) -> Self {
+        self.min_rust_version = min_rust_version;
+        self
+    }
+
+    /// 设置写入 lib.rs 顶部的可选 lint
+    pub fn with_lints(mut self, lints: Vec<Lint>) -> Self {
+        self.lints = lints;
+        self
+    }
+
+    /// 将解析器枚举与错误枚举标记为 `#[non_exhaustive]`，并为解析器枚举添加 `__Unknown` 兜底变体
+    pub fn with_non_exhaustive(mut self, non_exhaustive: bool) -> Self {
+        self.non_exhaustive = non_exhaustive;
+        self
+    }
+
+    /// 生成 `parse_instruction_lossy`，未知 discriminator 的指令解析为 `ParsedIx::Unknown`
+    pub fn with_lossy_parser(mut self, lossy_parser: bool) -> Self {
+        self.lossy_parser = lossy_parser;
+        self
+    }
+
+    /// 设置指令计算单元估算值，未配置的指令使用 `default_units`
+    pub fn with_compute_units(mut self, compute_units: Vec<ComputeUnitHint>, default_units: u32) -> Self {
+        self.compute_units = compute_units;
+        self.default_compute_units = default_units;
+        self
+    }
+
+    /// 设置是否生成账户大小与免租余额报告（SIZES.md / sizes.json），按 `lamports_per_byte_year` 计算租金
+    pub fn with_size_report(mut self, size_report: bool, lamports_per_byte_year: u64) -> Self {
+        self.size_report = size_report.then_some(lamports_per_byte_year);
+        self
+    }
+
+    /// 设置是否派生 `schemars::JsonSchema`（jsonschema feature）并生成 schemas/ 下的 JSON Schema 与 OpenAPI components
+    pub fn with_jsonschema(mut self, jsonschema: bool) -> Self {
+        self.jsonschema = jsonschema;
+        self
     }
     
     /// 生成多文件夹架构的完整Rust代码
@@ -99,6 +394,69 @@ impl MinijinjaTemplateGenerator {
             no_empty_workspace,
             false  // is_unified_library
         )?;
+        let field_accessors = field_hints::resolve_field_hints(&self.field_hints, &context)?;
+        let has_accessors = !field_accessors.is_empty();
+        // 时间戳访问方法依赖 chrono，其余语义的 *_decimal 访问方法依赖 rust_decimal
+        let has_chrono = self.field_hints.iter().any(|hint| hint.kind == field_hints::FieldHintKind::UnixTimestamp);
+        let has_decimal = self.field_hints.iter().any(|hint| hint.kind != field_hints::FieldHintKind::UnixTimestamp);
+        let compute_units = compute_units::resolve_compute_units(&self.compute_units, self.default_compute_units, &context)?;
+        let typed_keys = if self.typed_keys { account_roles::resolve_typed_keys(&context) } else { Vec::new() };
+        let typed_key_types = account_roles::typed_key_types(&typed_keys);
+        // 互转依赖账户结构体的 discriminator 字段与 Anchor 的账户 / 类型布局
+        let interop = match &self.interop {
+            // 替换类型与对方类型之间没有对称的转换；跳过时由 warnings::skipped_outputs 报告
+            Some(spec) if self.is_anchor_idl() && !self.idl_enum.has_field_types() => {
+                Some(interop::resolve_interop(spec, &self.idl_enum, output_dir)?)
+            }
+            _ => None,
+        };
+        let context = minijinja::context! {
+            compute_units => compute_units,
+            default_compute_units => self.default_compute_units,
+            has_accessors => has_accessors,
+            has_chrono => has_chrono,
+            has_decimal => has_decimal,
+            accessors => field_accessors,
+            trailing_bytes => Value::from_serialize(self.trailing_bytes),
+            serde_rename => Value::from_serialize(self.serde_rename),
+            has_program_tests => self.program_tests && generate_parser,
+            has_benches => self.benches && generate_parser,
+            has_compile_fail_tests => self.compile_fail_tests,
+            has_history => self.history_stats && generate_parser,
+            has_anchor_cpi => self.anchor_cpi,
+            has_cache => self.cache,
+            has_db => self.db.is_some(),
+            has_interop => interop.is_some(),
+            interop => interop,
+            has_upgrade => self.upgrade,
+            // 链上IDL账户仅 Anchor 程序存在
+            has_idl_verify => self.upgrade && self.is_anchor_idl() && self.package.idl_json_hash.is_some(),
+            has_web => self.web,
+            // wasm 导出按 JSON 反序列化指令参数并调用 {name}_ix，仅 Anchor 模板生成该构建函数
+            has_web_exports => self.web && self.is_anchor_idl() && serde_feature,
+            dependency_graph => self.readme_graph.then(|| crate::graph::render(&self.idl_enum, crate::graph::GraphFormat::Mermaid)),
+            legacy_layout => self.compat == Some(CompatLayout::V02),
+            has_typed_keys => !typed_keys.is_empty(),
+            typed_keys => typed_keys,
+            typed_key_types => typed_key_types,
+            // 非 Anchor 账户没有 discriminator，无法实现 ProgramAccountExt；跳过时由 warnings::skipped_outputs 报告
+            has_account_traits => self.account_traits && self.is_anchor_idl(),
+            // JsonSchema 派生依赖字段上的 serde 属性
+            has_jsonschema => self.jsonschema && serde_feature,
+            pubkey_crate => self.pubkey_crate.to_string(),
+            pubkey_alias => self.pubkey_crate.alias_path(),
+            extern_crates => self.externs.dependencies(output_dir),
+            extern_reexports => self.externs.reexports(),
+            lints => minijinja::context! {
+                forbid_unsafe => self.lints.contains(&Lint::ForbidUnsafe),
+                deny_missing_docs => self.lints.contains(&Lint::DenyMissingDocs),
+                zero_copy => self.idl_enum.has_zero_copy_accounts(),
+            },
+            non_exhaustive => self.non_exhaustive,
+            lossy_parser => self.lossy_parser,
+            rust => Value::from_serialize(self.min_rust_version.map_or(RustFeatures::LATEST, |version| version.features())),
+            ..context
+        };
         
         // 确定使用的模板类型
         let template_type = if self.is_anchor_idl() { "anchor" } else { "non_anchor" };
@@ -112,12 +470,111 @@ impl MinijinjaTemplateGenerator {
             parsers::generate_parsers_folder(&mut self.env, &src_dir, &context, template_type)?;
         }
         errors::generate_errors_single_file(&mut self.env, &src_dir, &context)?;
-        common::generate_lib_multi_folder(&mut self.env, &src_dir, &context, template_type)?;
+        if utils::idl_has_coption(&self.idl_enum) {
+            coption::generate_coption_single_file(&mut self.env, &src_dir, &context)?;
+        }
+        if utils::idl_has_small_vec(&self.idl_enum) {
+            small_vec::generate_small_vec_single_file(&mut self.env, &src_dir, &context)?;
+        }
+        if utils::idl_has_raw_tail(&self.idl_enum) {
+            raw_tail::generate_raw_tail_single_file(&mut self.env, &src_dir, &context)?;
+        }
+        if self.idl_enum.has_field_types() {
+            field_types::generate_field_types_single_file(&mut self.env, &src_dir, &context)?;
+        }
+        if context.get_attr("has_pdas").map(|v| v.is_true()).unwrap_or(false) {
+            pda::generate_pdas_single_file(&mut self.env, &src_dir, &context)?;
+        }
+        if has_accessors {
+            accessors::generate_accessors_single_file(&mut self.env, &src_dir, &context)?;
+        }
+        fuzz::generate_fuzz_single_file(&mut self.env, &src_dir, &context)?;
+        layout::generate_layout_single_file(&mut self.env, &src_dir, &context)?;
+        proposal::generate_proposal_single_file(&mut self.env, &src_dir, &context)?;
+        versioned_tx::generate_versioned_tx_single_file(&mut self.env, &src_dir, &context, template_type)?;
+        if context.get_attr("has_instructions").map(|v| v.is_true()).unwrap_or(false) {
+            actions::generate_actions_single_file(&mut self.env, &src_dir, &context)?;
+            compute::generate_compute_single_file(&mut self.env, &src_dir, &context)?;
+        }
+        if self.history_stats && generate_parser {
+            history::generate_history_single_file(&mut self.env, &src_dir, &context)?;
+        }
+        if context.get_attr("has_typed_keys").map(|v| v.is_true()).unwrap_or(false) {
+            typed_keys::generate_typed_keys_single_file(&mut self.env, &src_dir, &context)?;
+        }
+        if context.get_attr("has_account_traits").map(|v| v.is_true()).unwrap_or(false) {
+            account_ext::generate_account_ext_single_file(&mut self.env, &src_dir, &context)?;
+        }
+        if self.anchor_cpi {
+            anchor_cpi::generate_anchor_cpi_single_file(&mut self.env, &src_dir, &context)?;
+        }
+        if self.cache {
+            cache::generate_cache_single_file(&mut self.env, &src_dir, &context, template_type)?;
+        }
+        if context.get_attr("state_account").map(|v| !v.is_none()).unwrap_or(false) {
+            state::generate_state_single_file(&mut self.env, &src_dir, &context)?;
+        }
+        if self.web {
+            web::generate_web_single_file(&mut self.env, &src_dir, &context)?;
+        }
+        if context.get_attr("has_jsonschema").map(|v| v.is_true()).unwrap_or(false) {
+            jsonschema::generate_jsonschema_single_file(&mut self.env, &src_dir, &context)?;
+        }
+        if context.get_attr("has_interop").map(|v| v.is_true()).unwrap_or(false) {
+            interop::generate_interop_single_file(&mut self.env, &src_dir, &context)?;
+        }
+        if let Some(target) = self.db {
+            db::generate_db_files(&mut self.env, output_dir, &context, target)?;
+        }
+        // lib.rs 文档与 Cargo.toml 共用包元数据（IDL 哈希、生成器版本）
+        let package_context = minijinja::context! { package => Value::from_serialize(&self.package), ..context.clone() };
+        if self.upgrade {
+            upgrade::generate_upgrade_single_file(&mut self.env, &src_dir, &package_context)?;
+        }
+        common::generate_lib_multi_folder(&mut self.env, &src_dir, &package_context, template_type)?;
+        if self.compat == Some(CompatLayout::V02) {
+            compat::generate_legacy_layout(&mut self.env, &src_dir, &context, template_type)?;
+        }
+        // 所有 src 文件渲染完成后再改写，覆盖模板与构建器中拼接的 solana_pubkey 路径
+        if self.pubkey_crate.alias_path().is_some() {
+            pubkey::generate_pubkey_single_file(&mut self.env, &src_dir, &context)?;
+            pubkey_crate::rewrite_pubkey_paths(&src_dir)?;
+        }
+        
+        // 依赖解析器；跳过时由 warnings::skipped_outputs 报告
+        if self.program_tests && generate_parser {
+            program_test::generate_program_test_file(&mut self.env, output_dir, &context, template_type)?;
+        }
+        
+        // 依赖解析器；跳过时由 warnings::skipped_outputs 报告
+        if self.benches && generate_parser {
+            benches::generate_parser_benches_file(&mut self.env, output_dir, &context)?;
+        }
+        
+        if self.compile_fail_tests {
+            compile_fail::generate_compile_fail_tests(&mut self.env, output_dir, &context)?;
+        }
+        
+        // 依赖解析器；跳过时由 warnings::skipped_outputs 报告
+        if !self.rpc_fixtures.is_empty() && generate_parser {
+            rpc_fixtures::generate_rpc_fixtures_file(&mut self.env, output_dir, &context, &self.rpc_fixtures)?;
+        }
         
         // 生成配置文件
-        config::generate_cargo_toml(&mut self.env, output_dir, &context)?;
+        config::generate_cargo_toml(&mut self.env, output_dir, &package_context)?;
+        if self.min_rust_version.is_some() {
+            config::generate_cargo_config(output_dir)?;
+        }
         config::generate_readme(&mut self.env, output_dir, &context)?;
-        config::generate_claude_md(&mut self.env, output_dir, &context)?;
+        config::generate_claude_md(&mut self.env, output_dir, &package_context)?;
+        let source_idl = self.source_idl.as_ref().unwrap_or(&self.idl_enum);
+        manifest::generate_manifest_file(&self.idl_enum, source_idl, output_dir, &context)?;
+        if let Some(lamports_per_byte_year) = self.size_report {
+            sizes::generate_size_report(&mut self.env, output_dir, &context, lamports_per_byte_year)?;
+        }
+        if context.get_attr("has_jsonschema").map(|v| v.is_true()).unwrap_or(false) {
+            jsonschema::generate_schema_files(output_dir, &context, self.is_anchor_idl())?;
+        }
         
         info!("MiniJinja多文件夹架构生成完成");
         Ok(())
@@ -137,6 +594,10 @@ impl MinijinjaTemplateGenerator {
             args.no_empty_workspace,
             true  // is_unified_library
         )?;
+        let context = minijinja::context! {
+            serde_rename => Value::from_serialize(args.serde_rename),
+            ..context
+        };
         
         // Debug: Check if instructions are in context
         if let Ok(instructions) = context.get_attr("instructions") {
@@ -145,6 +606,10 @@ impl MinijinjaTemplateGenerator {
             log::warn!("⚠️ Unified generation - no instructions in context");
         }
         
+        if utils::idl_has_raw_tail(&self.idl_enum) {
+            raw_tail::generate_raw_tail_single_file(&mut self.env, output_dir, &context)?;
+        }
+        
         let template_type = if self.is_anchor_idl() { "anchor" } else { "non_anchor" };
         instructions::generate_instructions_folder(&mut self.env, output_dir, &context, template_type)
     }
@@ -163,6 +628,11 @@ impl MinijinjaTemplateGenerator {
             args.no_empty_workspace,
             true  // is_unified_library
         )?;
+        let context = minijinja::context! {
+            trailing_bytes => Value::from_serialize(args.trailing_bytes),
+            serde_rename => Value::from_serialize(args.serde_rename),
+            ..context
+        };
         let template_type = if self.is_anchor_idl() { "anchor" } else { "non_anchor" };
         accounts::generate_accounts_folder(&mut self.env, output_dir, &context, template_type)
     }
@@ -181,6 +651,10 @@ impl MinijinjaTemplateGenerator {
             args.no_empty_workspace,
             true  // is_unified_library
         )?;
+        let context = minijinja::context! {
+            serde_rename => Value::from_serialize(args.serde_rename),
+            ..context
+        };
         let template_type = if self.is_anchor_idl() { "anchor" } else { "non_anchor" };
         types::generate_types_folder(&mut self.env, output_dir, &context, template_type)
     }
@@ -199,6 +673,12 @@ impl MinijinjaTemplateGenerator {
             args.no_empty_workspace,
             true  // is_unified_library
         )?;
+        if utils::idl_has_coption(&self.idl_enum) {
+            coption::generate_coption_single_file(&mut self.env, output_dir, &context)?;
+        }
+        if utils::idl_has_small_vec(&self.idl_enum) {
+            small_vec::generate_small_vec_single_file(&mut self.env, output_dir, &context)?;
+        }
         errors::generate_errors_single_file(&mut self.env, output_dir, &context)
     }
     
@@ -216,6 +696,10 @@ impl MinijinjaTemplateGenerator {
             args.no_empty_workspace,
             true  // is_unified_library
         )?;
+        let context = minijinja::context! {
+            serde_rename => Value::from_serialize(args.serde_rename),
+            ..context
+        };
         let template_type = if self.is_anchor_idl() { "anchor" } else { "non_anchor" };
         events::generate_events_folder(&mut self.env, output_dir, &context, template_type)
     }
@@ -234,6 +718,10 @@ impl MinijinjaTemplateGenerator {
             args.no_empty_workspace,
             true  // is_unified_library
         )?;
+        let context = minijinja::context! {
+            trailing_bytes => Value::from_serialize(args.trailing_bytes),
+            ..context
+        };
         let template_type = if self.is_anchor_idl() { "anchor" } else { "non_anchor" };
         parsers::generate_parsers_folder(&mut self.env, output_dir, &context, template_type)
     }
@@ -268,6 +756,16 @@ impl MinijinjaTemplateGenerator {
             has_parsers = true;
         }
         
+        if utils::idl_has_coption(&self.idl_enum) {
+            mod_content.push_str("pub mod coption;\n");
+        }
+        if utils::idl_has_small_vec(&self.idl_enum) {
+            mod_content.push_str("pub mod small_vec;\n");
+        }
+        if utils::idl_has_raw_tail(&self.idl_enum) {
+            mod_content.push_str("pub mod raw_tail;\n");
+        }
+        
         // Re-export all public items
         mod_content.push_str("\n// Re-export all public items\n");
         for module in &modules {
@@ -312,6 +810,7 @@ impl MinijinjaTemplateGenerator {
         // 添加自定义过滤器
         env.add_filter("snake_case", to_snake_case_filter);
         env.add_filter("pascal_case", to_pascal_case_filter);
+        env.add_filter("toml_str", toml_str_filter);
         env.add_filter("upper", |value: Value| -> Result<String, Error> {
             Ok(value.to_string().to_uppercase())
         });
@@ -347,6 +846,10 @@ impl MinijinjaTemplateGenerator {
         
         // 生成根 lib.rs
         Self::generate_unified_lib_rs(&mut env, &lib_output_dir, config)?;
+        fuzz::generate_fuzz_single_file(&mut env, &src_dir, &Value::from(()))?;
+        layout::generate_layout_single_file(&mut env, &src_dir, &Value::from(()))?;
+        proposal::generate_proposal_single_file(&mut env, &src_dir, &Value::from(()))?;
+        versioned_tx::generate_versioned_tx_single_file(&mut env, &src_dir, &Value::from(()), "anchor")?;
         
         // 生成每个协议模块
         for group in &config.protocol_groups {
@@ -398,7 +901,7 @@ impl MinijinjaTemplateGenerator {
                     }).collect::<Vec<_>>()
                 })
             }).collect::<Vec<_>>(),
-            "generation_time": chrono::Utc::now().format("%Y-%m-%d %H:%M:%S UTC").to_string(),
+            "generation_time": crate::provenance::generation_time(),
         });
 
         let rendered = template.render(&context)
@@ -435,31 +938,37 @@ impl MinijinjaTemplateGenerator {
         // 使用 UnifiedCodeGenerator trait 生成每个IDL的实际代码
         use crate::idl_format::UnifiedCodeGenerator;
         
+        let naming = crate::idl_format::naming::NamingConfig::new(
+            base_args.type_prefix.clone(),
+            base_args.type_suffix.clone(),
+        );
         for idl_info in &group.idls {
             log::info!("为 {} 生成代码模块", idl_info.program_name);
+            let mut idl = idl_info.idl.clone();
+            idl.apply_naming(&naming);
             
             // 生成指令模块
-            idl_info.idl.generate_instructions(&protocol_dir, base_args)?;
+            idl.generate_instructions(&protocol_dir, base_args)?;
             
             // 生成账户模块
-            idl_info.idl.generate_accounts(&protocol_dir, base_args)?;
+            idl.generate_accounts(&protocol_dir, base_args)?;
             
             // 生成类型模块
-            idl_info.idl.generate_types(&protocol_dir, base_args)?;
+            idl.generate_types(&protocol_dir, base_args)?;
             
             // 生成错误模块
-            idl_info.idl.generate_errors(&protocol_dir, base_args)?;
+            idl.generate_errors(&protocol_dir, base_args)?;
             
             // 生成事件模块
-            idl_info.idl.generate_events(&protocol_dir, base_args)?;
+            idl.generate_events(&protocol_dir, base_args)?;
             
             // 如果需要生成解析器
             if base_args.generate_parser {
-                idl_info.idl.generate_parsers(&protocol_dir, base_args)?;
+                idl.generate_parsers(&protocol_dir, base_args)?;
             }
             
             // 生成模块的 mod.rs 文件
-            idl_info.idl.generate_mod_file(&protocol_dir, base_args)?;
+            idl.generate_mod_file(&protocol_dir, base_args)?;
         }
 
         Ok(())
@@ -496,8 +1005,9 @@ impl MinijinjaTemplateGenerator {
             "serde_big_array_vers": config.base_args.serde_big_array_vers,
             "serde_json_vers": config.base_args.serde_json_vers,
             "bytemuck_vers": config.base_args.bytemuck_vers,
-            "generation_time": chrono::Utc::now().format("%Y-%m-%d %H:%M:%S UTC").to_string(),
+            "generation_time": crate::provenance::generation_time(),
             "solores_version": env!("CARGO_PKG_VERSION"),
+            "package": crate::cargo::PackageMetadata::from_args(&config.base_args),
         });
 
         let rendered = template.render(&context)
@@ -547,7 +1057,7 @@ impl MinijinjaTemplateGenerator {
                     }).collect::<Vec<_>>()
                 })
             }).collect::<Vec<_>>(),
-            "generation_time": chrono::Utc::now().format("%Y-%m-%d %H:%M:%S UTC").to_string(),
+            "generation_time": crate::provenance::generation_time(),
         });
 
         let rendered = template.render(&context)
@@ -568,8 +1078,11 @@ impl MinijinjaTemplateGenerator {
     }
 }
 
-/// 自动分组协议
-pub fn auto_group_protocols(idl_files: &[PathBuf]) -> Result<Vec<ProtocolGroup>, SoloresError> {
+/// 自动分组协议，`force_format` 覆盖每个 IDL 的格式判定
+pub fn auto_group_protocols(
+    idl_files: &[PathBuf],
+    force_format: Option<crate::idl_format::FormatKind>,
+) -> Result<Vec<ProtocolGroup>, SoloresError> {
     use std::fs::File;
     use std::io::Read;
     
@@ -593,11 +1106,12 @@ pub fn auto_group_protocols(idl_files: &[PathBuf]) -> Result<Vec<ProtocolGroup>,
             ))?;
         
         // 解析IDL
-        let idl = crate::idl_format::parse_idl_json(&content)
+        let (idl, detection) = crate::idl_format::parse_idl_json_with_format(&content, force_format)
             .map_err(|e| SoloresError::InvalidIdlFormat {
                 details: format!("无法解析IDL文件 {}: {}", idl_path.display(), e),
                 expected_format: None,
             })?;
+        log::info!("🔍 {} IDL格式: {}", idl_path.display(), detection);
         
         // 获取程序名称作为协议名
         let program_name = idl.program_name().to_string();
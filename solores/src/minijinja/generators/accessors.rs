@@ -0,0 +1,95 @@
+//! 字段访问方法模块生成器
+//! 
+//! 存在 --field-hint 标注时，生成 accessors.rs 时间戳/定点数便捷访问方法
+
+use crate::error::SoloresError;
+use minijinja::{Environment, Value};
+use std::fs;
+use std::path::Path;
+
+/// 生成字段访问方法模块
+pub fn generate_accessors_single_file(
+    env: &mut Environment,
+    src_dir: &Path,
+    context: &Value,
+) -> std::result::Result<(), SoloresError> {
+    let template_content = include_str!("../templates/common/accessors.rs.jinja");
+    
+    let tmpl = env.template_from_str(template_content)
+        .map_err(|e| SoloresError::TemplateError {
+            template_name: Some("common/accessors.rs.jinja".to_string()),
+            message: format!("模板解析失败: {}", e),
+            context: Some("解析accessors模板".to_string()),
+        })?;
+    
+    let rendered = tmpl.render(context)
+        .map_err(|e| SoloresError::TemplateError {
+            template_name: Some("common/accessors.rs.jinja".to_string()),
+            message: format!("模板渲染失败: {}", e),
+            context: Some("渲染accessors模板".to_string()),
+        })?;
+    
+    let output_path = src_dir.join("accessors.rs");
+    fs::write(&output_path, rendered)
+        .map_err(|e| SoloresError::FileOperationError {
+            operation: "write accessors file".to_string(),
+            path: output_path.display().to_string(),
+            current_dir: std::env::current_dir().ok().map(|p| p.display().to_string()),
+            resolved_path: None,
+            source: e,
+            suggestion: Some("检查文件权限".to_string()),
+        })?;
+    
+    Ok(())
+}
+
+#[cfg(test)]
+mod tests {
+    use std::fs;
+
+    use crate::test_support::{generate_crate, run_generated_test};
+
+    const POOL_IDL: &str = r#"{"address": "Poo1111111111111111111111111111111111111111", "metadata": {"name": "pool", "version": "0.1.0", "spec": "0.1.0"},
+        "instructions": [{"name": "sync", "discriminator": [1, 2, 3, 4, 5, 6, 7, 8], "accounts": [{"name": "pool", "writable": true}], "args": []}],
+        "accounts": [{"name": "Pool", "discriminator": [9, 9, 9, 9, 9, 9, 9, 9]}],
+        "types": [{"name": "Pool", "type": {"kind": "struct", "fields": [{"name": "updated_at", "type": "i64"}, {"name": "fee_bps", "type": "u16"}]}}]}"#;
+
+    /// 生成 crate 的 Cargo.toml 中 chrono / rust_decimal 依赖与同名 feature 是否存在
+    fn manifest_flags(name: &str, generator_args: &[&str]) -> (bool, bool) {
+        let output_dir = generate_crate(name, POOL_IDL, generator_args);
+        let manifest = fs::read_to_string(output_dir.join("Cargo.toml")).unwrap();
+        let _ = fs::remove_dir_all(output_dir.parent().unwrap());
+        let chrono = manifest.contains("[dependencies.chrono]");
+        assert_eq!(manifest.contains("chrono = [\"dep:chrono\"]"), chrono);
+        let decimal = manifest.contains("decimal = [\"dep:rust_decimal\"]");
+        assert_eq!(manifest.contains("[dependencies.rust_decimal]"), decimal);
+        (chrono, decimal)
+    }
+
+    #[test]
+    fn chrono_and_decimal_follow_the_field_hints() {
+        assert_eq!(manifest_flags("hints_none", &[]), (false, false));
+        assert_eq!(manifest_flags("hints_chrono", &["--field-hint", "Pool.updated_at=unix_timestamp"]), (true, false));
+        assert_eq!(manifest_flags("hints_decimal", &["--field-hint", "Pool.fee_bps=bps"]), (false, true));
+    }
+
+    #[test]
+    fn gated_accessors_compile_with_their_features() {
+        let output_dir = generate_crate(
+            "hints_both",
+            POOL_IDL,
+            &["--field-hint", "Pool.updated_at=unix_timestamp", "--field-hint", "Pool.fee_bps=bps"],
+        );
+        let test_source = r#"
+use sol_pool_interface::accounts::Pool;
+
+#[test]
+fn accessors_convert_the_raw_fields() {
+    let pool = Pool { updated_at: 86_400, fee_bps: 25, ..Pool::default() };
+    assert_eq!(pool.updated_at_utc().unwrap().timestamp(), 86_400);
+    assert_eq!(pool.fee_bps_decimal(), rust_decimal::Decimal::new(25, 4));
+}
+"#;
+        run_generated_test("hints_both", &output_dir, &["chrono", "decimal"], test_source);
+    }
+}
@@ -0,0 +1,92 @@
+//! 账户公共 trait 生成器
+//! 
+//! `--accounts-as-traits` 时生成 account_ext.rs：`ProgramAccountExt` trait（名称、discriminator、owner 与解码）及每个账户结构体的实现，供下游对“本程序任意账户”编写泛型代码
+
+use crate::error::SoloresError;
+use minijinja::{Environment, Value};
+use std::fs;
+use std::path::Path;
+
+/// 生成账户公共 trait 模块
+pub fn generate_account_ext_single_file(
+    env: &mut Environment,
+    src_dir: &Path,
+    context: &Value,
+) -> std::result::Result<(), SoloresError> {
+    let template_content = include_str!("../templates/common/account_ext.rs.jinja");
+    
+    let tmpl = env.template_from_str(template_content)
+        .map_err(|e| SoloresError::TemplateError {
+            template_name: Some("common/account_ext.rs.jinja".to_string()),
+            message: format!("模板解析失败: {}", e),
+            context: Some("解析account_ext模板".to_string()),
+        })?;
+    
+    let rendered = tmpl.render(context)
+        .map_err(|e| SoloresError::TemplateError {
+            template_name: Some("common/account_ext.rs.jinja".to_string()),
+            message: format!("模板渲染失败: {}", e),
+            context: Some("渲染account_ext模板".to_string()),
+        })?;
+    
+    let output_path = src_dir.join("account_ext.rs");
+    fs::write(&output_path, rendered)
+        .map_err(|e| SoloresError::FileOperationError {
+            operation: "write account_ext file".to_string(),
+            path: output_path.display().to_string(),
+            current_dir: std::env::current_dir().ok().map(|p| p.display().to_string()),
+            resolved_path: None,
+            source: e,
+            suggestion: Some("检查文件权限".to_string()),
+        })?;
+    
+    Ok(())
+}
+
+#[cfg(test)]
+mod tests {
+    use crate::test_support::run_decode_test;
+
+    #[test]
+    fn account_trait_decodes_any_account_generically() {
+        let idl = r#"{"address": "Poo1111111111111111111111111111111111111111", "metadata": {"name": "pool", "version": "0.1.0", "spec": "0.1.0"},
+            "instructions": [],
+            "accounts": [{"name": "Pool", "discriminator": [1, 1, 1, 1, 1, 1, 1, 1]}, {"name": "Position", "discriminator": [2, 2, 2, 2, 2, 2, 2, 2]}],
+            "types": [{"name": "Pool", "type": {"kind": "struct", "fields": [{"name": "owner", "type": "pubkey"}]}},
+                      {"name": "Position", "type": {"kind": "struct", "fields": [{"name": "amount", "type": "u64"}]}}]}"#;
+        let test_source = r#"
+use sol_pool_interface::{account_ext::ProgramAccountExt, accounts::{Pool, Position}, errors::AccountParseError};
+use solana_pubkey::Pubkey;
+
+/// Written once for any account of the program
+fn unpack<A: ProgramAccountExt>(data: &[u8]) -> Option<(&'static str, A)> {
+    A::matches(data).then(|| A::try_unpack(data).map(|account| (A::NAME, account)).unwrap())
+}
+
+#[test]
+fn generic_code_decodes_every_account_type() {
+    let pool = Pool { owner: Pubkey::new_unique(), ..Pool::default() };
+    let position = Position { amount: 7, ..Position::default() };
+    let pool_data = pool.try_to_vec().unwrap();
+    let position_data = position.try_to_vec().unwrap();
+
+    assert_eq!(unpack::<Pool>(&pool_data), Some(("Pool", pool)));
+    assert_eq!(unpack::<Position>(&position_data), Some(("Position", position)));
+    assert_eq!(unpack::<Pool>(&position_data), None);
+    assert_eq!(unpack::<Position>(&pool_data), None);
+
+    assert_eq!((Pool::DISCRIMINATOR, Position::DISCRIMINATOR), ([1; 8], [2; 8]));
+    assert_eq!(Pool::owner(), sol_pool_interface::ID);
+    assert_eq!(Position::owner(), sol_pool_interface::ID);
+}
+
+#[test]
+fn try_unpack_checks_the_discriminator() {
+    let data = Position { amount: 7, ..Position::default() }.try_to_vec().unwrap();
+    assert!(matches!(Pool::try_unpack(&data), Err(AccountParseError::DiscriminatorMismatch { .. })));
+    assert!(Position::try_unpack(&data[..4]).is_err());
+}
+"#;
+        run_decode_test("accounts_as_traits", idl, &["--accounts-as-traits"], &[], test_source);
+    }
+}
@@ -3,10 +3,10 @@
 //! 负责生成账户相关的文件夹结构和单个账户文件
 
 use crate::error::SoloresError;
+use crate::idl_format::identifiers::ModuleNames;
 use minijinja::{context, Environment, Value};
 use std::fs;
 use std::path::Path;
-use convert_case::{Case, Casing};
 use log;
 
 /// 生成accounts文件夹和每个账户文件 - 简化版本
@@ -49,6 +49,7 @@ pub fn generate_accounts_folder(
     
     // 收集账户文件名用于mod.rs
     let mut account_names = Vec::new();
+    let mut modules = ModuleNames::new("accounts");
     
     // 为每个账户生成单独文件
     for i in 0..accounts.len().unwrap_or(0) {
@@ -56,15 +57,21 @@ pub fn generate_accounts_folder(
             if account != Value::UNDEFINED {
                 if let Ok(name_value) = account.get_attr("name") {
                     if let Some(account_name) = name_value.as_str() {
-                        let filename = struct_name_to_filename(account_name);
-                        account_names.push(filename.trim_end_matches(".rs").to_string());
+                        let module = modules.allocate(account_name);
+                        let filename = format!("{}.rs", module.file_stem);
+                        account_names.push(module.ident);
                         
                         // 创建单个账户上下文 - 使用官方API
                         let account_context = context! {
                             account => account.clone(),
                             crate_name => context.get_attr("crate_name").unwrap_or(Value::from("")),
                             has_serde => context.get_attr("has_serde").unwrap_or(Value::from(false)),
-                            is_unified_library => context.get_attr("is_unified_library").unwrap_or(Value::from(false))
+                            serde_rename => context.get_attr("serde_rename").unwrap_or(Value::UNDEFINED),
+                            has_jsonschema => context.get_attr("has_jsonschema").unwrap_or(Value::from(false)),
+                            is_unified_library => context.get_attr("is_unified_library").unwrap_or(Value::from(false)),
+                            trailing_bytes => context.get_attr("trailing_bytes").unwrap_or_default(),
+                            rust => context.get_attr("rust").unwrap_or_default(),
+                            lints => context.get_attr("lints").unwrap_or_default()
                         };
                         
                         // 生成账户文件
@@ -123,7 +130,57 @@ pub fn generate_single_account_file(
     Ok(())
 }
 
-/// 结构体名到文件名转换
-fn struct_name_to_filename(name: &str) -> String {
-    name.to_case(Case::Snake) + ".rs"
-}
\ No newline at end of file
+
+#[cfg(test)]
+mod tests {
+    use crate::test_support::run_decode_test;
+
+    #[test]
+    fn trailing_bytes_are_allowed_by_default() {
+        let idl = r#"{"address": "Poo1111111111111111111111111111111111111111", "metadata": {"name": "pool", "version": "0.1.0", "spec": "0.1.0"},
+            "instructions": [],
+            "accounts": [{"name": "Pool", "discriminator": [9, 9, 9, 9, 9, 9, 9, 9]}],
+            "types": [{"name": "Pool", "type": {"kind": "struct", "fields": [{"name": "amount", "type": "u64"}]}}]}"#;
+        let test_source = r#"
+use sol_pool_interface::{accounts::Pool, errors::AccountParseError};
+
+#[test]
+fn known_prefix_is_read_and_extra_data_kept() {
+    let pool = Pool { amount: 42, ..Pool::default() };
+    let mut bytes = pool.try_to_vec().unwrap();
+    bytes.extend([1, 2, 3]);
+
+    assert_eq!(Pool::from_bytes(&bytes).unwrap(), pool);
+    assert_eq!(Pool::deserialize_with_extra_data(&bytes).unwrap(), (pool, vec![1, 2, 3]));
+    assert!(matches!(
+        Pool::deserialize_strict(&bytes),
+        Err(AccountParseError::IncorrectLength { expected: 16, found: 19 })
+    ));
+}
+"#;
+        run_decode_test("trailing_allow", idl, &[], &[], test_source);
+    }
+
+    #[test]
+    fn strict_policy_rejects_trailing_bytes() {
+        let idl = r#"{"name": "vault", "version": "0.1.0", "metadata": {"name": "vault", "version": "0.1.0", "spec": "0.1.0", "origin": "shank", "address": "Vau1t11111111111111111111111111111111111111"},
+            "instructions": [],
+            "accounts": [{"name": "Vault", "type": {"kind": "struct", "fields": [{"name": "amount", "type": "u64"}, {"name": "bump", "type": "u8"}]}}]}"#;
+        let test_source = r#"
+use sol_vault_interface::accounts::Vault;
+
+#[test]
+fn from_bytes_requires_the_exact_layout() {
+    let vault = Vault { amount: 42, bump: 7 };
+    let mut bytes = vault.try_to_vec().unwrap();
+    assert_eq!(Vault::from_bytes(&bytes).unwrap(), vault);
+
+    bytes.push(0);
+    assert!(Vault::from_bytes(&bytes).is_err());
+    assert_eq!(Vault::deserialize_allow_trailing(&bytes).unwrap(), vault);
+    assert_eq!(Vault::deserialize_with_extra_data(&bytes).unwrap().1, vec![0]);
+}
+"#;
+        run_decode_test("trailing_strict", idl, &["--trailing-bytes", "strict"], &[], test_source);
+    }
+}
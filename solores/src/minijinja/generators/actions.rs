@@ -0,0 +1,149 @@
+//! Solana Actions 参数模块生成器
+//!
+//! 生成 actions.rs，为 actions feature 提供每个指令的参数 schema（名称、类型、约束）
+//! 以及从字符串输入构建指令数据的函数，可直接用于 Actions / Blink 端点。
+//! schema 完全由 IDL 指令参数推导。
+
+use crate::error::SoloresError;
+use minijinja::{context, Environment, Value};
+use std::fs;
+use std::path::Path;
+
+use crate::minijinja::filters::type_path_filter;
+
+/// 可由单个文本字段解析的参数类型：(Rust 类型, 输入类型, 解析方式, 最小值, 最大值)
+type ScalarType = (&'static str, &'static str, &'static str, Option<&'static str>, Option<&'static str>);
+
+const SCALAR_TYPES: [ScalarType; 15] = [
+    ("u8", "number", "int", Some("0"), Some("255")),
+    ("u16", "number", "int", Some("0"), Some("65535")),
+    ("u32", "number", "int", Some("0"), Some("4294967295")),
+    ("u64", "number", "int", Some("0"), Some("18446744073709551615")),
+    ("u128", "number", "int", Some("0"), Some("340282366920938463463374607431768211455")),
+    ("i8", "number", "int", Some("-128"), Some("127")),
+    ("i16", "number", "int", Some("-32768"), Some("32767")),
+    ("i32", "number", "int", Some("-2147483648"), Some("2147483647")),
+    ("i64", "number", "int", Some("-9223372036854775808"), Some("9223372036854775807")),
+    (
+        "i128",
+        "number",
+        "int",
+        Some("-170141183460469231731687303715884105728"),
+        Some("170141183460469231731687303715884105727"),
+    ),
+    ("f32", "number", "float", None, None),
+    ("f64", "number", "float", None, None),
+    ("bool", "checkbox", "bool", None, None),
+    ("std::string::String", "text", "string", None, None),
+    ("solana_pubkey::Pubkey", "text", "pubkey", None, None),
+];
+
+/// 将指令参数转换为 Actions 参数描述，`Option<T>` 视为可选参数
+fn action_parameter(field: &Value) -> Value {
+    let name = field.get_attr("name").ok().and_then(|v| v.as_str().map(str::to_string)).unwrap_or_default();
+    let rust_type = type_path_filter(
+        field.get_attr("rust_type").ok().and_then(|v| v.as_str().map(str::to_string)).unwrap_or_default(),
+    );
+    let (inner, required) = match rust_type
+        .strip_prefix("std::option::Option<")
+        .and_then(|inner| inner.strip_suffix('>'))
+    {
+        Some(inner) => (inner.to_string(), false),
+        None => (rust_type.clone(), true),
+    };
+    let scalar = SCALAR_TYPES.iter().find(|(ty, ..)| *ty == inner);
+
+    context! {
+        name => name,
+        rust_type => rust_type,
+        inner_type => inner,
+        required => required,
+        supported => scalar.is_some(),
+        input_type => scalar.map(|s| s.1).unwrap_or("text"),
+        parse => scalar.map(|s| s.2).unwrap_or(""),
+        min => scalar.and_then(|s| s.3),
+        max => scalar.and_then(|s| s.4),
+        pattern => (inner == "solana_pubkey::Pubkey").then_some("^[1-9A-HJ-NP-Za-km-z]{32,44}$"),
+    }
+}
+
+/// 为每个指令计算 Actions 参数
+fn action_instructions(context: &Value) -> Vec<Value> {
+    let instructions = context.get_attr("instructions").unwrap_or(Value::UNDEFINED);
+    let Ok(instructions) = instructions.try_iter() else {
+        return Vec::new();
+    };
+    instructions
+        .map(|instruction| {
+            let parameters: Vec<Value> = instruction
+                .get_attr("fields")
+                .ok()
+                .and_then(|fields| fields.try_iter().ok().map(|fields| fields.map(|f| action_parameter(&f)).collect()))
+                .unwrap_or_default();
+            let buildable = parameters
+                .iter()
+                .all(|p| p.get_attr("supported").map(|v| v.is_true()).unwrap_or(false));
+            context! {
+                name => instruction.get_attr("name").unwrap_or(Value::UNDEFINED),
+                parameters => parameters,
+                buildable => buildable,
+            }
+        })
+        .collect()
+}
+
+/// 生成 Actions 参数模块
+pub fn generate_actions_single_file(
+    env: &mut Environment,
+    src_dir: &Path,
+    context: &Value,
+) -> std::result::Result<(), SoloresError> {
+    let template_content = include_str!("../templates/common/actions.rs.jinja");
+
+    let tmpl = env.template_from_str(template_content)
+        .map_err(|e| SoloresError::TemplateError {
+            template_name: Some("common/actions.rs.jinja".to_string()),
+            message: format!("模板解析失败: {}", e),
+            context: Some("解析actions模板".to_string()),
+        })?;
+
+    let actions_context = context! {
+        actions => action_instructions(context),
+        ..context.clone()
+    };
+    let rendered = tmpl.render(&actions_context)
+        .map_err(|e| SoloresError::TemplateError {
+            template_name: Some("common/actions.rs.jinja".to_string()),
+            message: format!("模板渲染失败: {}", e),
+            context: Some("渲染actions模板".to_string()),
+        })?;
+
+    let output_path = src_dir.join("actions.rs");
+    fs::write(&output_path, rendered)
+        .map_err(|e| SoloresError::FileOperationError {
+            operation: "write actions file".to_string(),
+            path: output_path.display().to_string(),
+            current_dir: std::env::current_dir().ok().map(|p| p.display().to_string()),
+            resolved_path: None,
+            source: e,
+            suggestion: Some("检查文件权限".to_string()),
+        })?;
+
+    Ok(())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn option_args_are_optional_and_vectors_unsupported() {
+        let amount = action_parameter(&context! { name => "amount", rust_type => "std::option::Option<u64>" });
+        assert_eq!(amount.get_attr("required").unwrap(), Value::from(false));
+        assert_eq!(amount.get_attr("inner_type").unwrap().as_str(), Some("u64"));
+        assert_eq!(amount.get_attr("max").unwrap().as_str(), Some("18446744073709551615"));
+
+        let path = action_parameter(&context! { name => "path", rust_type => "std::vec::Vec<u8>" });
+        assert_eq!(path.get_attr("supported").unwrap(), Value::from(false));
+    }
+}
@@ -0,0 +1,103 @@
+//! Anchor CPI 模块生成器
+//! 
+//! 启用 --gen-anchor-cpi 时生成 anchor_cpi.rs：实现 ToAccountMetas / ToAccountInfos 的账户结构体
+//! 与每个指令的 CPI 函数，供 Anchor 程序以 CpiContext 调用非 Anchor 程序。
+
+use crate::error::SoloresError;
+use minijinja::{Environment, Value};
+use std::fs;
+use std::path::Path;
+
+/// 生成Anchor CPI模块
+pub fn generate_anchor_cpi_single_file(
+    env: &mut Environment,
+    src_dir: &Path,
+    context: &Value,
+) -> std::result::Result<(), SoloresError> {
+    let template_content = include_str!("../templates/common/anchor_cpi.rs.jinja");
+    
+    let tmpl = env.template_from_str(template_content)
+        .map_err(|e| SoloresError::TemplateError {
+            template_name: Some("common/anchor_cpi.rs.jinja".to_string()),
+            message: format!("模板解析失败: {}", e),
+            context: Some("解析anchor_cpi模板".to_string()),
+        })?;
+    
+    let rendered = tmpl.render(context)
+        .map_err(|e| SoloresError::TemplateError {
+            template_name: Some("common/anchor_cpi.rs.jinja".to_string()),
+            message: format!("模板渲染失败: {}", e),
+            context: Some("渲染anchor_cpi模板".to_string()),
+        })?;
+    
+    let output_path = src_dir.join("anchor_cpi.rs");
+    fs::write(&output_path, rendered)
+        .map_err(|e| SoloresError::FileOperationError {
+            operation: "write anchor_cpi file".to_string(),
+            path: output_path.display().to_string(),
+            current_dir: std::env::current_dir().ok().map(|p| p.display().to_string()),
+            resolved_path: None,
+            source: e,
+            suggestion: Some("检查文件权限".to_string()),
+        })?;
+    
+    Ok(())
+}
+
+#[cfg(test)]
+mod tests {
+    use crate::test_support::run_decode_test;
+
+    #[test]
+    fn cpi_account_structs_compile_and_order_their_accounts() {
+        let idl = r#"{"address": "Poo1111111111111111111111111111111111111111", "metadata": {"name": "pool", "version": "0.1.0", "spec": "0.1.0"},
+            "instructions": [{"name": "deposit", "discriminator": [1, 0, 0, 0, 0, 0, 0, 0],
+                    "accounts": [{"name": "pool", "writable": true}, {"name": "owner", "signer": true}, {"name": "referrer", "writable": true, "optional": true}],
+                    "args": [{"name": "amount", "type": "u64"}]},
+                {"name": "sync", "discriminator": [2, 0, 0, 0, 0, 0, 0, 0], "accounts": [], "args": []}]}"#;
+        let test_source = r#"
+use anchor_lang::prelude::*;
+use sol_pool_interface::anchor_cpi::{self, accounts};
+
+#[test]
+fn account_structs_produce_metas_and_infos_in_idl_order() {
+    let keys: Vec<Pubkey> = (0..3).map(|_| Pubkey::new_unique()).collect();
+    let owner = Pubkey::default();
+    let mut lamports = [0u64; 3];
+    let mut data = [[0u8; 0]; 3];
+    let mut infos = keys
+        .iter()
+        .zip(lamports.iter_mut())
+        .zip(data.iter_mut())
+        .map(|((key, lamports), data)| AccountInfo::new(key, false, false, lamports, data, &owner, false, 0));
+    let (pool, signer, referrer) = (infos.next().unwrap(), infos.next().unwrap(), infos.next().unwrap());
+
+    let deposit = accounts::Deposit { pool: pool.clone(), owner: signer.clone(), referrer: Some(referrer.clone()) };
+    let metas = deposit.to_account_metas(None);
+    assert_eq!(
+        metas.iter().map(|meta| (meta.pubkey, meta.is_writable, meta.is_signer)).collect::<Vec<_>>(),
+        [(keys[0], true, false), (keys[1], false, true), (keys[2], true, false)]
+    );
+    assert!(deposit.to_account_metas(Some(false)).iter().all(|meta| !meta.is_signer));
+    assert_eq!(deposit.to_account_infos().iter().map(|info| *info.key).collect::<Vec<_>>(), keys);
+
+    // 缺省的可选账户以只读的程序 ID 占位，且不出现在 AccountInfo 列表中
+    let without_referrer = accounts::Deposit { pool, owner: signer, referrer: None };
+    let placeholder = &without_referrer.to_account_metas(None)[2];
+    assert_eq!((placeholder.pubkey, placeholder.is_writable, placeholder.is_signer), (sol_pool_interface::ID, false, false));
+    assert_eq!(without_referrer.to_account_infos().len(), 2);
+
+    let sync = accounts::Sync { _marker: std::marker::PhantomData };
+    assert!(sync.to_account_metas(None).is_empty());
+    assert!(sync.to_account_infos().is_empty());
+}
+
+#[test]
+fn cpi_functions_take_a_cpi_context() {
+    let _: for<'info> fn(CpiContext<'_, '_, '_, 'info, accounts::Deposit<'info>>, u64) -> Result<()> = anchor_cpi::deposit;
+    let _: for<'info> fn(CpiContext<'_, '_, '_, 'info, accounts::Sync<'info>>) -> Result<()> = anchor_cpi::sync;
+}
+"#;
+        run_decode_test("anchor_cpi", idl, &["--gen-anchor-cpi"], &["anchor-cpi"], test_source);
+    }
+}
@@ -0,0 +1,116 @@
+//! 解析器基准测试生成器
+//!
+//! 生成 benches/parsers.rs，基于 criterion 测量各指令、账户及未知判别码的解析耗时，
+//! 用于对比模板改动前后生成代码的解析性能
+
+use crate::error::SoloresError;
+use minijinja::{Environment, Value};
+use std::fs;
+use std::path::Path;
+
+/// 生成 benches/parsers.rs
+pub fn generate_parser_benches_file(
+    env: &mut Environment,
+    output_dir: &Path,
+    context: &Value,
+) -> std::result::Result<(), SoloresError> {
+    let template_content = include_str!("../templates/common/benches/parsers.rs.jinja");
+
+    let tmpl = env.template_from_str(template_content)
+        .map_err(|e| SoloresError::TemplateError {
+            template_name: Some("common/benches/parsers.rs.jinja".to_string()),
+            message: format!("模板解析失败: {}", e),
+            context: Some("解析benches模板".to_string()),
+        })?;
+
+    let rendered = tmpl.render(context)
+        .map_err(|e| SoloresError::TemplateError {
+            template_name: Some("common/benches/parsers.rs.jinja".to_string()),
+            message: format!("模板渲染失败: {}", e),
+            context: Some("渲染benches模板".to_string()),
+        })?;
+
+    let benches_dir = output_dir.join("benches");
+    fs::create_dir_all(&benches_dir).map_err(|e| SoloresError::FileOperationError {
+        operation: "create benches directory".to_string(),
+        path: benches_dir.display().to_string(),
+        current_dir: std::env::current_dir().ok().map(|p| p.display().to_string()),
+        resolved_path: None,
+        source: e,
+        suggestion: Some("检查目录权限".to_string()),
+    })?;
+
+    let output_path = benches_dir.join("parsers.rs");
+    fs::write(&output_path, rendered)
+        .map_err(|e| SoloresError::FileOperationError {
+            operation: "write parser benches file".to_string(),
+            path: output_path.display().to_string(),
+            current_dir: std::env::current_dir().ok().map(|p| p.display().to_string()),
+            resolved_path: None,
+            source: e,
+            suggestion: Some("检查文件权限".to_string()),
+        })?;
+
+    Ok(())
+}
+
+#[cfg(test)]
+mod tests {
+    use crate::test_support::generate_crate;
+    use std::fs;
+    use std::process::Command;
+
+    const ANCHOR_IDL: &str = r#"{"address": "Poo1111111111111111111111111111111111111111", "metadata": {"name": "pool", "version": "0.1.0", "spec": "0.1.0"},
+        "instructions": [{"name": "deposit", "discriminator": [1, 2, 3, 4, 5, 6, 7, 8], "accounts": [{"name": "pool", "writable": true}, {"name": "owner", "signer": true}],
+                          "args": [{"name": "amount", "type": "u64"}]},
+                         {"name": "sync", "discriminator": [8, 7, 6, 5, 4, 3, 2, 1], "accounts": [], "args": []}],
+        "accounts": [{"name": "Pool", "discriminator": [9, 9, 9, 9, 9, 9, 9, 9]}],
+        "types": [{"name": "Pool", "type": {"kind": "struct", "fields": [{"name": "owner", "type": "pubkey"}, {"name": "labels", "type": {"vec": "string"}}]}}]}"#;
+
+    const SHANK_IDL: &str = r#"{"name": "vault", "version": "0.1.0",
+        "metadata": {"name": "vault", "version": "0.1.0", "spec": "0.1.0", "origin": "shank", "address": "Vau1t11111111111111111111111111111111111111"},
+        "instructions": [{"name": "withdraw", "discriminator": [1], "accounts": [{"name": "vault", "isMut": true, "isSigner": false}],
+                          "args": [{"name": "amount", "type": "u64"}]}],
+        "accounts": [{"name": "Vault", "type": {"kind": "struct", "fields": [{"name": "authority", "type": "publicKey"}, {"name": "balance", "type": "u64"}]}}]}"#;
+
+    /// 以 criterion 的 `--test` 模式把每个基准各运行一次，确认生成的基准可编译且解析路径可用
+    fn run_benches_once(name: &str, idl: &str) {
+        let output_dir = generate_crate(name, idl, &["--gen-benches"]);
+        let manifest = fs::read_to_string(output_dir.join("Cargo.toml")).unwrap();
+        assert!(manifest.contains("[dev-dependencies.criterion]"));
+        assert!(manifest.contains("name = \"parsers\""));
+
+        let output = Command::new("cargo")
+            .args(["bench", "--quiet", "--bench", "parsers", "--", "--test"])
+            .env("CARGO_TARGET_DIR", std::env::temp_dir().join("solores-decode-target"))
+            .current_dir(&output_dir)
+            .output()
+            .unwrap();
+        let passed = output.status.success();
+        if passed {
+            let _ = fs::remove_dir_all(output_dir.parent().unwrap());
+        }
+        assert!(
+            passed,
+            "{} 的基准测试失败（生成目录保留在 {}）\n{}\n{}",
+            name,
+            output_dir.display(),
+            String::from_utf8_lossy(&output.stdout),
+            String::from_utf8_lossy(&output.stderr)
+        );
+    }
+
+    #[test]
+    fn parser_benches_run_for_both_idl_flavours() {
+        run_benches_once("bench_anchor", ANCHOR_IDL);
+        run_benches_once("bench_shank", SHANK_IDL);
+    }
+
+    #[test]
+    fn benches_are_only_generated_on_request() {
+        let output_dir = generate_crate("bench_off", SHANK_IDL, &[]);
+        assert!(!output_dir.join("benches").exists());
+        assert!(!fs::read_to_string(output_dir.join("Cargo.toml")).unwrap().contains("criterion"));
+        let _ = fs::remove_dir_all(output_dir.parent().unwrap());
+    }
+}
@@ -0,0 +1,157 @@
+//! 账户缓存模块生成器
+//! 
+//! `--gen-cache` 时生成 cache.rs：按地址缓存解码后的账户，支持 RPC 刷新、推送更新与过期检查
+
+use crate::error::SoloresError;
+use minijinja::{context, Environment, Value};
+use std::fs;
+use std::path::Path;
+
+/// 生成账户缓存模块
+pub fn generate_cache_single_file(
+    env: &mut Environment,
+    src_dir: &Path,
+    context: &Value,
+    template_type: &str,
+) -> std::result::Result<(), SoloresError> {
+    let template_content = include_str!("../templates/common/cache.rs.jinja");
+    
+    let tmpl = env.template_from_str(template_content)
+        .map_err(|e| SoloresError::TemplateError {
+            template_name: Some("common/cache.rs.jinja".to_string()),
+            message: format!("模板解析失败: {}", e),
+            context: Some("解析cache模板".to_string()),
+        })?;
+    
+    // Anchor 账户按 discriminator 路由，NonAnchor 账户按数据长度路由
+    let context = context! { is_anchor => template_type == "anchor", ..context.clone() };
+    let rendered = tmpl.render(context)
+        .map_err(|e| SoloresError::TemplateError {
+            template_name: Some("common/cache.rs.jinja".to_string()),
+            message: format!("模板渲染失败: {}", e),
+            context: Some("渲染cache模板".to_string()),
+        })?;
+    
+    let output_path = src_dir.join("cache.rs");
+    fs::write(&output_path, rendered)
+        .map_err(|e| SoloresError::FileOperationError {
+            operation: "write cache file".to_string(),
+            path: output_path.display().to_string(),
+            current_dir: std::env::current_dir().ok().map(|p| p.display().to_string()),
+            resolved_path: None,
+            source: e,
+            suggestion: Some("检查文件权限".to_string()),
+        })?;
+    
+    Ok(())
+}
+
+#[cfg(test)]
+mod tests {
+    use crate::test_support::run_decode_test;
+
+    #[test]
+    fn cache_inserts_updates_and_evicts_stale_entries() {
+        let idl = r#"{"address": "Poo1111111111111111111111111111111111111111", "metadata": {"name": "pool", "version": "0.1.0", "spec": "0.1.0"},
+            "instructions": [],
+            "accounts": [{"name": "Pool", "discriminator": [1, 1, 1, 1, 1, 1, 1, 1]}],
+            "types": [{"name": "Pool", "type": {"kind": "struct", "fields": [{"name": "fee_bps", "type": "u16"}]}}]}"#;
+        let test_source = r#"
+use std::collections::HashMap;
+use std::future::Future;
+use std::task::{Context, Poll, Waker};
+use std::time::Duration;
+
+use sol_pool_interface::{accounts::Pool, cache::*};
+use solana_pubkey::Pubkey;
+
+/// 取数据的 future 立即就绪，轮询一次即可
+fn block_on<F: Future>(future: F) -> F::Output {
+    match std::pin::pin!(future).poll(&mut Context::from_waker(Waker::noop())) {
+        Poll::Ready(output) => output,
+        Poll::Pending => panic!("fetcher future was not ready"),
+    }
+}
+
+/// Accounts as the RPC node would return them; missing keys are closed accounts
+struct StaticFetcher {
+    slot: u64,
+    accounts: HashMap<Pubkey, Vec<u8>>,
+}
+
+impl AccountFetcher for StaticFetcher {
+    async fn fetch_accounts(&self, pubkeys: &[Pubkey]) -> Result<FetchedAccounts, CacheError> {
+        Ok((self.slot, pubkeys.iter().map(|pubkey| self.accounts.get(pubkey).cloned()).collect()))
+    }
+}
+
+fn pool(fee_bps: u16) -> Pool {
+    Pool { fee_bps, ..Pool::default() }
+}
+
+fn update(pubkey: Pubkey, fee_bps: u16, slot: u64) -> AccountUpdate {
+    AccountUpdate { pubkey, owner: sol_pool_interface::ID, data: pool(fee_bps).try_to_vec().unwrap(), slot, ..AccountUpdate::default() }
+}
+
+#[test]
+fn inserts_and_updates_keep_the_newest_slot() {
+    let cache = ProgramAccountCache::new();
+    let key = Pubkey::new_unique();
+    assert!(cache.apply_update(&update(key, 10, 5)).unwrap());
+    assert_eq!(cache.get_pool(&key).unwrap().fee_bps, 10);
+
+    assert!(cache.apply_update(&update(key, 20, 6)).unwrap());
+    assert_eq!(cache.pool.get(&key).map(|cached| (cached.value.fee_bps, cached.slot)), Some((20, 6)));
+
+    // 旧 slot 的更新被忽略，同 slot 的更新覆盖
+    assert!(!cache.apply_update(&update(key, 30, 4)).unwrap());
+    assert_eq!(cache.get_pool(&key).unwrap().fee_bps, 20);
+    assert!(cache.pool.insert(key, pool(40), 6));
+    assert_eq!(cache.get_pool(&key).unwrap().fee_bps, 40);
+
+    // 其他程序的账户与未知 discriminator 不进入缓存
+    let foreign = AccountUpdate { owner: Pubkey::new_unique(), ..update(Pubkey::new_unique(), 1, 9) };
+    assert!(!cache.apply_update(&foreign).unwrap());
+    let unknown = AccountUpdate { data: vec![9; 10], ..update(Pubkey::new_unique(), 1, 9) };
+    assert!(!cache.apply_update(&unknown).unwrap());
+    assert_eq!(cache.pool.keys(), [key]);
+}
+
+#[test]
+fn stale_entries_are_refreshed_or_evicted() {
+    let cache = AccountCache::new(Pool::from_bytes);
+    let (kept, closed) = (Pubkey::new_unique(), Pubkey::new_unique());
+    cache.insert(kept, pool(1), 1);
+    cache.insert(closed, pool(2), 1);
+    std::thread::sleep(Duration::from_millis(5));
+
+    assert!(cache.get_fresh(&kept, Duration::from_secs(60)).is_some());
+    assert!(cache.get_fresh(&kept, Duration::ZERO).is_none());
+    let mut stale = cache.stale_keys(Duration::ZERO);
+    stale.sort();
+    let mut expected = vec![kept, closed];
+    expected.sort();
+    assert_eq!(stale, expected);
+    assert!(cache.stale_keys(Duration::from_secs(60)).is_empty());
+
+    let fetcher = StaticFetcher { slot: 7, accounts: HashMap::from([(kept, pool(3).try_to_vec().unwrap())]) };
+    block_on(cache.refresh_stale(&fetcher, Duration::ZERO)).unwrap();
+    assert_eq!(cache.keys(), [kept]);
+    let refreshed = cache.get(&kept).unwrap();
+    assert_eq!((refreshed.value.fee_bps, refreshed.slot), (3, 7));
+    assert!(cache.get_fresh(&kept, Duration::from_secs(60)).is_some());
+    assert!(cache.get(&closed).is_none());
+}
+
+#[test]
+fn undecodable_refreshes_report_the_account() {
+    let cache = AccountCache::new(Pool::from_bytes);
+    let key = Pubkey::new_unique();
+    let fetcher = StaticFetcher { slot: 1, accounts: HashMap::from([(key, vec![0; 3])]) };
+    assert!(matches!(block_on(cache.refresh(&fetcher, &[key])), Err(CacheError::Decode { pubkey, .. }) if pubkey == key));
+    assert!(cache.is_empty());
+}
+"#;
+        run_decode_test("cache", idl, &["--gen-cache"], &[], test_source);
+    }
+}
@@ -14,6 +14,18 @@ pub fn generate_folder_mod_file(
     items: &[String],
     module_name: &str,
     template_type: &str,
+) -> std::result::Result<(), SoloresError> {
+    generate_folder_mod_file_with_reexports(env, folder, items, &[], module_name, template_type)
+}
+
+/// 生成文件夹的mod.rs，并重新导出外部 crate 中的条目（完整路径）
+pub fn generate_folder_mod_file_with_reexports(
+    env: &mut Environment,
+    folder: &Path,
+    items: &[String],
+    reexports: &[String],
+    module_name: &str,
+    template_type: &str,
 ) -> std::result::Result<(), SoloresError> {
     let template_content = match (template_type, module_name) {
         ("anchor", "accounts") => include_str!("../templates/anchor/accounts/mod.rs.jinja"),
@@ -33,6 +45,7 @@ pub fn generate_folder_mod_file(
     let mod_context = context! {
         module_name => module_name,
         items => items,
+        reexports => reexports,
         crate_name => ""
     };
     
@@ -65,6 +78,7 @@ pub fn generate_folder_mod_file(
 }
 
 /// 生成多文件夹模式的lib.rs
+#[allow(clippy::if_same_then_else)] // 两套模板目前内容相同，但保持独立以便分别演进
 pub fn generate_lib_multi_folder(
     env: &mut Environment,
     src_dir: &Path,
@@ -103,4 +117,100 @@ pub fn generate_lib_multi_folder(
         })?;
     
     Ok(())
-}
\ No newline at end of file
+}
+
+#[cfg(test)]
+mod tests {
+    use std::fs;
+
+    use crate::test_support::{generate_crate, run_decode_test, run_generated_test};
+
+    const POOL_IDL: &str = r#"{"address": "Poo1111111111111111111111111111111111111111", "metadata": {"name": "pool", "version": "0.1.0", "spec": "0.1.0"},
+        "instructions": [{"name": "deposit", "discriminator": [1, 2, 3, 4, 5, 6, 7, 8], "accounts": [{"name": "pool", "writable": true}], "args": [{"name": "amount", "type": "u64"}]}],
+        "accounts": [{"name": "Pool", "discriminator": [9, 9, 9, 9, 9, 9, 9, 9]}],
+        "types": [{"name": "Pool", "type": {"kind": "struct", "fields": [{"name": "amount", "type": "u64"}]}}]}"#;
+
+    /// forbid-unsafe 与 deny-missing-docs 下生成的 crate 在有无零拷贝视图时都能编译
+    #[test]
+    fn lint_headers_build_with_and_without_zero_copy() {
+        let lints = ["--lints", "forbid-unsafe,deny-missing-docs"];
+
+        let crate_dir = generate_crate("lints_plain", POOL_IDL, &lints);
+        let lib = fs::read_to_string(crate_dir.join("src/lib.rs")).unwrap();
+        assert!(lib.contains("#![forbid(unsafe_code)]") && !lib.contains("#![deny(unsafe_code)]"));
+        assert!(lib.contains("#![deny(missing_docs)]"));
+        let test_source = r#"
+use sol_pool_interface::accounts::Pool;
+
+#[test]
+fn account_round_trips() {
+    let pool = Pool { amount: 42, ..Pool::default() };
+    assert_eq!(Pool::from_bytes(&pool.try_to_vec().unwrap()).unwrap(), pool);
+}
+"#;
+        run_generated_test("lints_plain", &crate_dir, &[], test_source);
+
+        // 零拷贝视图以 unsafe 读取未对齐字段，只能 deny 后在读取处局部允许
+        let zero_copy = [lints[0], lints[1], "--zero-copy", "Pool"];
+        let crate_dir = generate_crate("lints_zero_copy", POOL_IDL, &zero_copy);
+        let lib = fs::read_to_string(crate_dir.join("src/lib.rs")).unwrap();
+        assert!(lib.contains("#![deny(unsafe_code)]") && !lib.contains("#![forbid(unsafe_code)]"));
+        assert!(lib.contains("#![deny(missing_docs)]"));
+        let test_source = r#"
+use sol_pool_interface::accounts::{Pool, PoolRef};
+
+#[test]
+fn view_reads_the_account() {
+    let bytes = Pool { amount: 42, ..Pool::default() }.try_to_vec().unwrap();
+    assert_eq!(PoolRef::try_from_bytes(&bytes).unwrap().amount(), 42);
+}
+"#;
+        run_generated_test("lints_zero_copy", &crate_dir, &[], test_source);
+    }
+
+    /// 只导入 prelude 即可构建并解析指令，glob 导入与常用 solana 类型不冲突
+    #[test]
+    fn prelude_is_enough_to_build_and_parse_instructions() {
+        let idl = r#"{"address": "Poo1111111111111111111111111111111111111111", "metadata": {"name": "pool", "version": "0.1.0", "spec": "0.1.0"},
+            "instructions": [
+                {"name": "deposit", "discriminator": [1, 2, 3, 4, 5, 6, 7, 8], "accounts": [{"name": "pool", "writable": true}, {"name": "owner", "signer": true}], "args": [{"name": "amount", "type": "u64"}]},
+                {"name": "ping", "discriminator": [2, 2, 3, 4, 5, 6, 7, 8], "accounts": [], "args": []}
+            ],
+            "accounts": [{"name": "Pool", "discriminator": [9, 9, 9, 9, 9, 9, 9, 9]}],
+            "events": [{"name": "Deposited", "discriminator": [8, 8, 8, 8, 8, 8, 8, 8]}],
+            "types": [
+                {"name": "Pool", "type": {"kind": "struct", "fields": [{"name": "amount", "type": "u64"}]}},
+                {"name": "Deposited", "type": {"kind": "struct", "fields": [{"name": "amount", "type": "u64"}]}}
+            ]}"#;
+        let test_source = r#"
+use sol_pool_interface::prelude::*;
+use solana_instruction::{AccountMeta, Instruction};
+use solana_pubkey::Pubkey;
+
+fn deposit(pool: Pubkey, owner: Pubkey, amount: u64) -> InterfaceResult<Instruction> {
+    deposit_ix(DepositKeys { pool, owner }, DepositIxData::new(amount))
+}
+
+#[test]
+fn prelude_builds_and_parses() {
+    let (pool, owner) = (Pubkey::new_unique(), Pubkey::new_unique());
+    let ix = deposit(pool, owner, 42).unwrap();
+    assert_eq!(ix.program_id, ID);
+    assert_eq!(ix.accounts[1], AccountMeta::new_readonly(owner, true));
+
+    let accounts: Vec<Pubkey> = ix.accounts.iter().map(|meta| meta.pubkey).collect();
+    match ProgramInstruction::try_parse(&ix.data, &accounts).unwrap() {
+        ProgramInstruction::Deposit(keys, args) => assert_eq!((keys.pool, args.amount), (pool, 42)),
+        other => panic!("unexpected instruction: {:?}", other),
+    }
+    let ping = PingIxData::new().try_to_vec().unwrap();
+    assert!(matches!(ProgramInstruction::try_parse(&ping, &[]), Ok(ProgramInstruction::Ping(..))));
+
+    let state = Pool { amount: 7, ..Pool::default() };
+    assert!(matches!(ProgramAccount::try_parse(&state.try_to_vec().unwrap()), Ok(ProgramAccount::Pool(parsed)) if parsed == state));
+    let _: Option<ProgramEvent> = None;
+}
+"#;
+        run_decode_test("prelude", idl, &[], &[], test_source);
+    }
+}
@@ -0,0 +1,146 @@
+//! 旧版布局兼容生成器
+//!
+//! `--compat 0.2` 时在正常生成后把 instructions/、accounts/、events/、types/ 目录
+//! 合并为单文件（types 改名为 typedefs.rs），子模块以内联 `pub mod x { .. }` 保留，
+//! `super::` 路径含义不变；并在 instructions.rs 末尾补充 solores 0.2 的 API 名称。
+
+use crate::error::SoloresError;
+use minijinja::{context, Environment, Value};
+use std::fs;
+use std::path::Path;
+
+/// 合并的目录与旧版文件名
+const LEGACY_FILES: [(&str, &str); 4] = [
+    ("instructions", "instructions"),
+    ("accounts", "accounts"),
+    ("events", "events"),
+    ("types", "typedefs"),
+];
+
+/// 将多文件夹模块合并为旧版单文件布局
+pub fn generate_legacy_layout(
+    env: &mut Environment,
+    src_dir: &Path,
+    context: &Value,
+    template_type: &str,
+) -> std::result::Result<(), SoloresError> {
+    for (folder, file) in LEGACY_FILES {
+        let dir = src_dir.join(folder);
+        if !dir.join("mod.rs").exists() {
+            continue;
+        }
+        let mut content = inline_module_dir(&dir)?;
+        if folder == "instructions" {
+            content.push_str(&render_legacy_names(env, context, template_type)?);
+        }
+
+        let output_path = src_dir.join(format!("{}.rs", file));
+        fs::write(&output_path, content)
+            .map_err(|e| SoloresError::file_operation_error("写入旧版单文件模块", output_path.display().to_string(), e))?;
+        fs::remove_dir_all(&dir)
+            .map_err(|e| SoloresError::file_operation_error("删除模块目录", dir.display().to_string(), e))?;
+    }
+    Ok(())
+}
+
+/// 读取目录的 mod.rs，把 `pub mod x;` 替换为内联的 x.rs（或 x/mod.rs）内容
+fn inline_module_dir(dir: &Path) -> std::result::Result<String, SoloresError> {
+    let mod_path = dir.join("mod.rs");
+    let source = fs::read_to_string(&mod_path)
+        .map_err(|e| SoloresError::file_operation_error("读取mod.rs", mod_path.display().to_string(), e))?;
+
+    let mut merged = String::new();
+    for line in source.lines() {
+        let child = line
+            .trim()
+            .strip_prefix("pub mod ")
+            .and_then(|rest| rest.strip_suffix(';'))
+            .filter(|name| name.trim_start_matches("r#").chars().all(|c| c.is_ascii_alphanumeric() || c == '_'));
+        // 关键字模块以原始标识符声明（`pub mod r#type;`），文件名不含 `r#`
+        let file = child.map(|name| name.trim_start_matches("r#"));
+        let inlined = match file {
+            Some(file) if dir.join(file).join("mod.rs").exists() => Some(inline_module_dir(&dir.join(file))?),
+            Some(file) if dir.join(format!("{}.rs", file)).exists() => {
+                let path = dir.join(format!("{}.rs", file));
+                Some(
+                    fs::read_to_string(&path)
+                        .map_err(|e| SoloresError::file_operation_error("读取子模块", path.display().to_string(), e))?,
+                )
+            }
+            _ => None,
+        };
+        match (child, inlined) {
+            (Some(name), Some(body)) => {
+                merged.push_str(&format!("pub mod {} {{\n{}\n}}\n", name, body));
+            }
+            _ => {
+                merged.push_str(line);
+                merged.push('\n');
+            }
+        }
+    }
+    Ok(merged)
+}
+
+/// 渲染 solores 0.2 的指令 API 名称
+fn render_legacy_names(env: &mut Environment, context: &Value, template_type: &str) -> std::result::Result<String, SoloresError> {
+    let instructions = context.get_attr("instructions").unwrap_or(Value::UNDEFINED);
+    let legacy_instructions: Vec<Value> = instructions
+        .try_iter()
+        .map(|iter| iter.collect::<Vec<_>>())
+        .unwrap_or_default()
+        .into_iter()
+        .filter(|instruction| {
+            instruction.get_attr("accounts").ok().and_then(|accounts| accounts.len()).unwrap_or(0) > 0
+        })
+        .map(|instruction| {
+            context! {
+                name => instruction.get_attr("name").unwrap_or(Value::UNDEFINED),
+                accounts => instruction.get_attr("accounts").unwrap_or(Value::UNDEFINED),
+                // 只有 Anchor 的 Keys 带 remaining_accounts 并实现到 Vec<AccountMeta> 的转换
+                has_remaining_accounts_info => template_type == "anchor"
+                    && super::instructions::detect_remaining_accounts_info(&instruction),
+            }
+        })
+        .collect();
+
+    let template_content = include_str!("../templates/common/compat.rs.jinja");
+    let tmpl = env.template_from_str(template_content)
+        .map_err(|e| SoloresError::TemplateError {
+            template_name: Some("common/compat.rs.jinja".to_string()),
+            message: format!("模板解析失败: {}", e),
+            context: Some("解析compat模板".to_string()),
+        })?;
+    tmpl.render(context! {
+        legacy_instructions => legacy_instructions,
+        typed_key_types => context.get_attr("typed_key_types").unwrap_or(Value::UNDEFINED),
+    })
+        .map_err(|e| SoloresError::TemplateError {
+            template_name: Some("common/compat.rs.jinja".to_string()),
+            message: format!("模板渲染失败: {}", e),
+            context: Some("渲染compat模板".to_string()),
+        })
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn inlines_child_modules_in_place() {
+        let dir = std::env::temp_dir().join(format!("solores-compat-{}", std::process::id()));
+        fs::create_dir_all(dir.join("nested")).unwrap();
+        fs::write(dir.join("mod.rs"), "//! Module\n#[cfg(test)]\npub mod a;\npub mod nested;\npub use a::*;\n").unwrap();
+        fs::write(dir.join("a.rs"), "pub struct A;").unwrap();
+        fs::write(dir.join("nested/mod.rs"), "pub mod b;").unwrap();
+        fs::write(dir.join("nested/b.rs"), "pub struct B;").unwrap();
+
+        let merged = inline_module_dir(&dir).unwrap();
+        let _ = fs::remove_dir_all(&dir);
+
+        assert_eq!(
+            merged,
+            "//! Module\n#[cfg(test)]\npub mod a {\npub struct A;\n}\npub mod nested {\npub mod b {\npub struct B;\n}\n\n}\npub use a::*;\n"
+        );
+    }
+}
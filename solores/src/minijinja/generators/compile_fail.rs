@@ -0,0 +1,151 @@
+//! 编译失败测试生成器
+//!
+//! `--gen-compile-fail-tests` 时生成 tests/compile_fail.rs 与 tests/compile_fail/*.rs：基于 trybuild
+//! 断言生成的 API 拒绝错误用法（Keys 缺少账户字段、不同指令的 Keys / IxData 互相替换），
+//! 重新生成后 API 若被放宽，测试会失败而不是静默影响下游代码。
+//!
+//! 期望的编译错误（.stderr）由用户以 `TRYBUILD=overwrite` 记录，重新生成时不会覆盖。
+
+use crate::error::SoloresError;
+use crate::minijinja::filters::to_snake_case_filter;
+use minijinja::{context, Environment, Value};
+use std::fs;
+use std::path::Path;
+
+/// 编译失败用例：文件名、模板与用例参数
+struct Case {
+    name: &'static str,
+    template: &'static str,
+    values: Value,
+}
+
+/// 按 IDL 选取用例：缺少字段取第一个至少有两个账户的指令，互换取前两个指令
+fn select_cases(instructions: &[Value]) -> Vec<Case> {
+    let account_names = |instruction: &Value| -> Vec<String> {
+        context_items(instruction, "accounts").iter().map(|account| to_snake_case_filter(attr_str(account, "name"))).collect()
+    };
+    let with_accounts: Vec<&Value> = instructions.iter().filter(|instruction| !account_names(instruction).is_empty()).collect();
+
+    let mut cases = Vec::new();
+    if let Some(instruction) = with_accounts.iter().find(|instruction| account_names(instruction).len() >= 2) {
+        cases.push(Case {
+            name: "keys_missing_field",
+            template: include_str!("../templates/common/tests/compile_fail/keys_missing_field.rs.jinja"),
+            values: context! { instruction => attr_str(instruction, "name"), account => account_names(instruction)[0].clone() },
+        });
+    }
+    if let [first, second, ..] = with_accounts.as_slice() {
+        cases.push(Case {
+            name: "keys_not_interchangeable",
+            template: include_str!("../templates/common/tests/compile_fail/keys_not_interchangeable.rs.jinja"),
+            values: context! { first => attr_str(first, "name"), second => attr_str(second, "name") },
+        });
+    }
+    if let [first, second, ..] = instructions {
+        cases.push(Case {
+            name: "args_not_interchangeable",
+            template: include_str!("../templates/common/tests/compile_fail/args_not_interchangeable.rs.jinja"),
+            values: context! { first => attr_str(first, "name"), second => attr_str(second, "name") },
+        });
+    }
+    cases
+}
+
+/// 生成 tests/compile_fail.rs 与各用例文件
+pub fn generate_compile_fail_tests(
+    env: &mut Environment,
+    output_dir: &Path,
+    context: &Value,
+) -> std::result::Result<(), SoloresError> {
+    let cases = select_cases(&context_items(context, "instructions"));
+    if cases.is_empty() {
+        log::warn!("⚠️ IDL 中没有可用于编译失败测试的指令，已跳过 tests/compile_fail.rs 生成");
+        return Ok(());
+    }
+
+    let cases_dir = output_dir.join("tests").join("compile_fail");
+    fs::create_dir_all(&cases_dir)
+        .map_err(|e| SoloresError::file_operation_error("create compile_fail directory", cases_dir.display().to_string(), e))?;
+
+    let lib_name = format!("sol_{}_interface", to_snake_case_filter(attr_str(context, "crate_name")));
+    write_rendered(
+        env,
+        "common/tests/compile_fail.rs.jinja",
+        include_str!("../templates/common/tests/compile_fail.rs.jinja"),
+        context.clone(),
+        &output_dir.join("tests").join("compile_fail.rs"),
+    )?;
+    for case in cases {
+        write_rendered(
+            env,
+            &format!("common/tests/compile_fail/{}.rs.jinja", case.name),
+            case.template,
+            context! { lib_name => lib_name, case => case.values },
+            &cases_dir.join(format!("{}.rs", case.name)),
+        )?;
+    }
+    Ok(())
+}
+
+fn write_rendered(
+    env: &mut Environment,
+    template_name: &str,
+    template_content: &str,
+    context: Value,
+    output_path: &Path,
+) -> std::result::Result<(), SoloresError> {
+    let tmpl = env.template_from_str(template_content)
+        .map_err(|e| SoloresError::TemplateError {
+            template_name: Some(template_name.to_string()),
+            message: format!("模板解析失败: {}", e),
+            context: Some("解析compile_fail模板".to_string()),
+        })?;
+    let rendered = tmpl.render(context)
+        .map_err(|e| SoloresError::TemplateError {
+            template_name: Some(template_name.to_string()),
+            message: format!("模板渲染失败: {}", e),
+            context: Some("渲染compile_fail模板".to_string()),
+        })?;
+    fs::write(output_path, rendered)
+        .map_err(|e| SoloresError::file_operation_error("write compile_fail test", output_path.display().to_string(), e))
+}
+
+fn context_items(value: &Value, key: &str) -> Vec<Value> {
+    value
+        .get_attr(key)
+        .ok()
+        .and_then(|items| items.try_iter().ok().map(|items| items.collect()))
+        .unwrap_or_default()
+}
+
+fn attr_str(value: &Value, name: &str) -> String {
+    value
+        .get_attr(name)
+        .ok()
+        .and_then(|attr| attr.as_str().map(str::to_string))
+        .unwrap_or_default()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn cases_skip_instructions_without_enough_accounts() {
+        let account = |name: &str| context! { name => name };
+        let instructions = vec![
+            context! { name => "Ping", accounts => Vec::<Value>::new() },
+            context! { name => "Close", accounts => vec![account("owner")] },
+            context! { name => "Swap", accounts => vec![account("userAuthority"), account("pool")] },
+        ];
+        let cases = select_cases(&instructions);
+        let names: Vec<&str> = cases.iter().map(|case| case.name).collect();
+        assert_eq!(names, ["keys_missing_field", "keys_not_interchangeable", "args_not_interchangeable"]);
+        assert_eq!(attr_str(&cases[0].values, "instruction"), "Swap");
+        assert_eq!(attr_str(&cases[0].values, "account"), "user_authority");
+        assert_eq!(attr_str(&cases[1].values, "first"), "Close");
+        assert_eq!(attr_str(&cases[2].values, "second"), "Close");
+
+        assert!(select_cases(&instructions[..1]).is_empty());
+    }
+}
@@ -0,0 +1,44 @@
+//! 计算单元估算模块生成器
+//! 
+//! 为每个指令生成计算单元估算常量，以及组合交易估算值的 compute budget 辅助函数
+
+use crate::error::SoloresError;
+use minijinja::{Environment, Value};
+use std::fs;
+use std::path::Path;
+
+/// 生成compute计算单元估算模块
+pub fn generate_compute_single_file(
+    env: &mut Environment,
+    src_dir: &Path,
+    context: &Value,
+) -> std::result::Result<(), SoloresError> {
+    let template_content = include_str!("../templates/common/compute.rs.jinja");
+    
+    let tmpl = env.template_from_str(template_content)
+        .map_err(|e| SoloresError::TemplateError {
+            template_name: Some("common/compute.rs.jinja".to_string()),
+            message: format!("模板解析失败: {}", e),
+            context: Some("解析compute模板".to_string()),
+        })?;
+    
+    let rendered = tmpl.render(context)
+        .map_err(|e| SoloresError::TemplateError {
+            template_name: Some("common/compute.rs.jinja".to_string()),
+            message: format!("模板渲染失败: {}", e),
+            context: Some("渲染compute模板".to_string()),
+        })?;
+    
+    let output_path = src_dir.join("compute.rs");
+    fs::write(&output_path, rendered)
+        .map_err(|e| SoloresError::FileOperationError {
+            operation: "write compute file".to_string(),
+            path: output_path.display().to_string(),
+            current_dir: std::env::current_dir().ok().map(|p| p.display().to_string()),
+            resolved_path: None,
+            source: e,
+            suggestion: Some("检查文件权限".to_string()),
+        })?;
+    
+    Ok(())
+}
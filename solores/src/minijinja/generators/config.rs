@@ -44,6 +44,22 @@ pub fn generate_cargo_toml(
     Ok(())
 }
 
+/// 生成 .cargo/config.toml：依赖解析时回退到兼容 `rust-version` 的版本（Cargo 1.84+）
+pub fn generate_cargo_config(output_dir: &Path) -> std::result::Result<(), SoloresError> {
+    let config_dir = output_dir.join(".cargo");
+    let output_path = config_dir.join("config.toml");
+    fs::create_dir_all(&config_dir)
+        .and_then(|_| fs::write(&output_path, "[resolver]\nincompatible-rust-versions = \"fallback\"\n"))
+        .map_err(|e| SoloresError::FileOperationError {
+            operation: "write .cargo/config.toml".to_string(),
+            path: output_path.display().to_string(),
+            current_dir: std::env::current_dir().ok().map(|p| p.display().to_string()),
+            resolved_path: None,
+            source: e,
+            suggestion: Some("检查文件权限".to_string()),
+        })
+}
+
 /// Cargo.toml生成的后备方案
 pub fn generate_cargo_toml_fallback(
     env: &mut Environment,
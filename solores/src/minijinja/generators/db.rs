@@ -0,0 +1,274 @@
+//! 数据库行映射生成器
+//!
+//! `--gen-db sqlx-postgres` 时生成 db.rs（`sqlx-postgres` feature）：每个账户与事件一个
+//! `{Name}Row` 行结构体、`to_row()` 转换、建表 DDL 与插入语句，并写出包含全部建表语句的
+//! `migrations/0001_create_{crate}_tables.sql`，使索引器的表结构随 IDL 一起更新。
+//!
+//! 列类型：Pubkey 为 TEXT（base58），u64 为 NUMERIC，超出 rust_decimal 96 位范围的
+//! u128 / i128 为 TEXT（十进制字符串），字节数组为 BYTEA，Option 为可空列，
+//! 其余复合类型（数组、Vec、自定义类型）为 JSONB。
+
+use crate::error::SoloresError;
+use crate::idl_format::identifiers::rust_ident;
+use crate::minijinja::filters::type_path_filter;
+use convert_case::{Case, Casing};
+use minijinja::{context, Environment, Value};
+use std::fs;
+use std::path::Path;
+
+/// `--gen-db` 的目标数据库与客户端
+#[derive(clap::ValueEnum, Debug, Clone, Copy, PartialEq, Eq)]
+pub enum DbTarget {
+    /// PostgreSQL + sqlx：行结构体派生 `sqlx::FromRow`，插入语句使用 `$n` 占位符
+    #[value(name = "sqlx-postgres")]
+    SqlxPostgres,
+}
+
+/// 一列的 SQL 类型、行结构体字段类型与由字段值构造列值的表达式
+struct ColumnType {
+    sql_type: &'static str,
+    row_type: String,
+    /// `{}` 为字段值：Copy 类型为值，其余为可自动借用的位置表达式或引用
+    convert: &'static str,
+    /// 字段类型是否为 Copy（可空列按值映射 Option）
+    copy: bool,
+}
+
+impl ColumnType {
+    fn new(sql_type: &'static str, row_type: &str, convert: &'static str, copy: bool) -> Self {
+        Self { sql_type, row_type: row_type.to_string(), convert, copy }
+    }
+
+    fn expr(&self, value: &str) -> String {
+        self.convert.replace("{}", value)
+    }
+}
+
+/// 标量类型的列映射，复合类型为 JSONB
+fn scalar_column(rust_type: &str) -> ColumnType {
+    match rust_type {
+        "solana_pubkey::Pubkey" => ColumnType::new("TEXT", "String", "{}.to_string()", true),
+        "bool" => ColumnType::new("BOOLEAN", "bool", "{}", true),
+        "u8" | "i8" | "i16" => ColumnType::new("SMALLINT", "i16", "i16::from({})", true),
+        "u16" | "i32" => ColumnType::new("INTEGER", "i32", "i32::from({})", true),
+        "u32" | "i64" => ColumnType::new("BIGINT", "i64", "i64::from({})", true),
+        "u64" => ColumnType::new("NUMERIC(20, 0)", "rust_decimal::Decimal", "rust_decimal::Decimal::from({})", true),
+        "u128" | "i128" => ColumnType::new("TEXT", "String", "{}.to_string()", true),
+        "f32" => ColumnType::new("REAL", "f32", "{}", true),
+        "f64" => ColumnType::new("DOUBLE PRECISION", "f64", "{}", true),
+        "std::string::String" | "String" => ColumnType::new("TEXT", "String", "{}.clone()", false),
+        "std::vec::Vec<u8>" => ColumnType::new("BYTEA", "Vec<u8>", "{}.to_vec()", false),
+        bytes if bytes.starts_with("[u8;") => ColumnType::new("BYTEA", "Vec<u8>", "{}.to_vec()", false),
+        // 长度超过 32 的数组没有 Serialize 实现，按切片序列化
+        array if array.starts_with('[') => {
+            ColumnType::new("JSONB", "serde_json::Value", "serde_json::to_value({}.as_slice()).unwrap_or_default()", false)
+        }
+        _ => ColumnType::new("JSONB", "serde_json::Value", "serde_json::to_value({}).unwrap_or_default()", false),
+    }
+}
+
+/// 字段对应的列：`{column, sql_type, nullable, row_type, expr}`
+fn column(field: &Value) -> Value {
+    let name = attr_str(field, "name");
+    let field_ident = crate::minijinja::filters::rust_field_filter(name.clone());
+    // 替换了类型的字段（`--field-type`）先转换回 IDL 原类型
+    let idl_rust_type = attr_str(field, "idl_rust_type");
+    let (rust_type, access) = if idl_rust_type.is_empty() {
+        (type_path_filter(attr_str(field, "rust_type")), format!("self.{}", field_ident))
+    } else {
+        let access = format!("std::convert::Into::<{}>::into(self.{}.clone())", idl_rust_type, field_ident);
+        (type_path_filter(idl_rust_type), access)
+    };
+    let inner = rust_type
+        .strip_prefix("std::option::Option<")
+        .and_then(|inner| inner.strip_suffix('>'))
+        .filter(|inner| !inner.starts_with("std::option::Option<"));
+
+    let (column_type, nullable, expr) = match inner {
+        Some(inner) => {
+            let column_type = scalar_column(inner);
+            let option = if column_type.copy { access } else { format!("{}.as_ref()", access) };
+            let expr = format!("{}.map(|value| {})", option, column_type.expr("value"));
+            let row_type = format!("Option<{}>", column_type.row_type);
+            (ColumnType { row_type, ..column_type }, true, expr)
+        }
+        None => {
+            let column_type = scalar_column(&rust_type);
+            let by_reference = column_type.sql_type == "JSONB" && !rust_type.starts_with('[');
+            let value = if by_reference { format!("&{}", access) } else { access };
+            let expr = column_type.expr(&value);
+            (column_type, false, expr)
+        }
+    };
+    context! {
+        column => name.to_case(Case::Snake),
+        sql_type => column_type.sql_type,
+        nullable => nullable,
+        row_type => column_type.row_type,
+        expr => expr,
+    }
+}
+
+/// 账户或事件的表：`{name, path, table, kind, columns, create_table, insert}`，
+/// 列另含行结构体字段名 `row_field`
+fn table(item: &Value, module: &str, kind: &str) -> Value {
+    let name = attr_str(item, "name");
+    let table = name.to_case(Case::Snake);
+    // 账户以地址为主键并保留最新 slot；事件以交易签名与事件序号为主键
+    let (key_columns, key_sql, conflict) = match kind {
+        "account" => (
+            vec!["address", "slot"],
+            "    \"address\" TEXT PRIMARY KEY,\n    \"slot\" BIGINT NOT NULL".to_string(),
+            "(\"address\") DO UPDATE SET {} WHERE \"{}\".\"slot\" <= EXCLUDED.\"slot\"",
+        ),
+        _ => (
+            vec!["signature", "event_index", "slot"],
+            "    \"signature\" TEXT NOT NULL,\n    \"event_index\" INTEGER NOT NULL,\n    \"slot\" BIGINT NOT NULL".to_string(),
+            "(\"signature\", \"event_index\") DO NOTHING",
+        ),
+    };
+    // 与键列同名的字段列加 `data_` 前缀
+    let columns: Vec<Value> = context_items(item, "fields")
+        .iter()
+        .filter(|field| attr_str(field, "name") != "discriminator")
+        .map(|field| {
+            let column = column(field);
+            let mut name = attr_str(&column, "column");
+            if key_columns.contains(&name.as_str()) {
+                name = format!("data_{}", name);
+            }
+            context! { column => name, row_field => rust_ident(&name), ..column }
+        })
+        .collect();
+    let column_names: Vec<String> = key_columns
+        .iter()
+        .map(|column| column.to_string())
+        .chain(columns.iter().map(|column| attr_str(column, "column")))
+        .collect();
+
+    let mut definitions = vec![key_sql];
+    definitions.extend(columns.iter().map(|column| {
+        let not_null = if column.get_attr("nullable").map(|v| v.is_true()).unwrap_or(false) { "" } else { " NOT NULL" };
+        format!("    \"{}\" {}{}", attr_str(column, "column"), attr_str(column, "sql_type"), not_null)
+    }));
+    if kind == "event" {
+        definitions.push("    PRIMARY KEY (\"signature\", \"event_index\")".to_string());
+    }
+    let create_table = format!("CREATE TABLE IF NOT EXISTS \"{}\" (\n{}\n);", table, definitions.join(",\n"));
+
+    let quoted: Vec<String> = column_names.iter().map(|column| format!("\"{}\"", column)).collect();
+    let placeholders: Vec<String> = (1..=column_names.len()).map(|index| format!("${}", index)).collect();
+    let conflict = if kind == "account" {
+        let updates: Vec<String> =
+            quoted.iter().skip(1).map(|column| format!("{} = EXCLUDED.{}", column, column)).collect();
+        conflict.replacen("{}", &updates.join(", "), 1).replacen("{}", &table, 1)
+    } else {
+        conflict.to_string()
+    };
+    let insert = format!(
+        "INSERT INTO \"{}\" ({}) VALUES ({}) ON CONFLICT {}",
+        table,
+        quoted.join(", "),
+        placeholders.join(", "),
+        conflict
+    );
+
+    context! {
+        name => name,
+        path => format!("crate::{}::{}", module, name),
+        table => table,
+        kind => kind,
+        columns => columns,
+        create_table => create_table,
+        insert => insert,
+    }
+}
+
+/// 生成 db.rs 与建表迁移文件
+pub fn generate_db_files(
+    env: &mut Environment,
+    output_dir: &Path,
+    context: &Value,
+    target: DbTarget,
+) -> std::result::Result<(), SoloresError> {
+    let DbTarget::SqlxPostgres = target;
+    let tables: Vec<Value> = context_items(context, "accounts")
+        .iter()
+        .map(|account| table(account, "accounts", "account"))
+        .chain(context_items(context, "events").iter().map(|event| table(event, "events", "event")))
+        .collect();
+    let migration: Vec<String> = tables.iter().map(|table| attr_str(table, "create_table")).collect();
+    let migration = migration.join("\n\n");
+
+    let template_name = "common/db.rs.jinja";
+    let tmpl = env.template_from_str(include_str!("../templates/common/db.rs.jinja"))
+        .map_err(|e| SoloresError::TemplateError {
+            template_name: Some(template_name.to_string()),
+            message: format!("模板解析失败: {}", e),
+            context: Some("解析db模板".to_string()),
+        })?;
+    let rendered = tmpl.render(context! { tables => tables, migration => migration, ..context.clone() })
+        .map_err(|e| SoloresError::TemplateError {
+            template_name: Some(template_name.to_string()),
+            message: format!("模板渲染失败: {}", e),
+            context: Some("渲染db模板".to_string()),
+        })?;
+    let output_path = output_dir.join("src").join("db.rs");
+    fs::write(&output_path, rendered)
+        .map_err(|e| SoloresError::file_operation_error("write db file", output_path.display().to_string(), e))?;
+
+    let crate_name = attr_str(context, "crate_name").to_case(Case::Snake);
+    let migrations_dir = output_dir.join("migrations");
+    fs::create_dir_all(&migrations_dir)
+        .map_err(|e| SoloresError::file_operation_error("create migrations directory", migrations_dir.display().to_string(), e))?;
+    let migration_path = migrations_dir.join(format!("0001_create_{}_tables.sql", crate_name));
+    let sql = format!("-- Generated by Solores from the {} IDL; regenerate instead of editing\n\n{}\n", crate_name, migration);
+    fs::write(&migration_path, sql)
+        .map_err(|e| SoloresError::file_operation_error("write migration file", migration_path.display().to_string(), e))?;
+
+    Ok(())
+}
+
+fn context_items(value: &Value, key: &str) -> Vec<Value> {
+    value
+        .get_attr(key)
+        .ok()
+        .and_then(|items| items.try_iter().ok().map(|items| items.collect()))
+        .unwrap_or_default()
+}
+
+fn attr_str(value: &Value, name: &str) -> String {
+    value
+        .get_attr(name)
+        .ok()
+        .and_then(|attr| attr.as_str().map(str::to_string))
+        .unwrap_or_default()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn account_table_maps_idl_types_to_postgres_columns() {
+        let account = context! {
+            name => "Pool",
+            fields => vec![
+                context! { name => "authority", rust_type => "solana_pubkey::Pubkey" },
+                context! { name => "liquidity", rust_type => "u64" },
+                context! { name => "delegate", rust_type => "std::option::Option<solana_pubkey::Pubkey>" },
+                context! { name => "fees", rust_type => "crate::types::Fees" },
+            ],
+        };
+        let table = table(&account, "accounts", "account");
+        assert_eq!(
+            attr_str(&table, "create_table"),
+            "CREATE TABLE IF NOT EXISTS \"pool\" (\n    \"address\" TEXT PRIMARY KEY,\n    \"slot\" BIGINT NOT NULL,\n    \
+             \"authority\" TEXT NOT NULL,\n    \"liquidity\" NUMERIC(20, 0) NOT NULL,\n    \"delegate\" TEXT,\n    \
+             \"fees\" JSONB NOT NULL\n);"
+        );
+        assert!(attr_str(&table, "insert").ends_with("WHERE \"pool\".\"slot\" <= EXCLUDED.\"slot\""));
+        let columns = context_items(&table, "columns");
+        assert_eq!(attr_str(&columns[2], "expr"), "self.delegate.map(|value| value.to_string())");
+    }
+}
@@ -95,4 +95,106 @@ pub enum InterfaceError {
         })?;
     
     Ok(())
-}
\ No newline at end of file
+}
+#[cfg(test)]
+mod tests {
+    use crate::test_support::run_decode_test;
+
+    const TREASURY_IDL: &str = r#"{"name": "treasury", "version": "0.1.0",
+            "metadata": {"name": "treasury", "version": "0.1.0", "spec": "0.1.0", "origin": "shank", "address": "Treasury11111111111111111111111111111111111"},
+            "instructions": [{"name": "withdraw", "discriminator": [1],
+                "accounts": [{"name": "vault", "isMut": true, "isSigner": false}, {"name": "payer", "isMut": true, "isSigner": true}],
+                "args": [{"name": "amount", "type": "u64"}]}],
+            "errors": [{"code": 6000, "name": "InsufficientFunds", "msg": "Not enough funds"}]}"#;
+
+    #[test]
+    fn builder_and_parser_errors_share_interface_error() {
+        let test_source = r#"
+use sol_treasury_interface::errors::*;
+use sol_treasury_interface::instructions::*;
+use sol_treasury_interface::parsers::instructions::ProgramInstruction;
+use solana_program_error::ProgramError;
+use solana_pubkey::Pubkey;
+
+fn round_trip(keys: WithdrawKeys, amount: u64) -> InterfaceResult<u64> {
+    let ix = withdraw_ix(keys, WithdrawIxData::new(amount))?;
+    let accounts: Vec<Pubkey> = ix.accounts.iter().map(|meta| meta.pubkey).collect();
+    match ProgramInstruction::try_parse(&ix.data, &accounts)? {
+        ProgramInstruction::Withdraw(_, ix_data) => Ok(WithdrawIxData::from_bytes(&ix_data.try_to_vec()?)?.amount),
+    }
+}
+
+fn parse_truncated(data: &[u8]) -> InterfaceResult<ProgramInstruction> {
+    Ok(ProgramInstruction::try_parse(data, &[])?)
+}
+
+#[test]
+fn question_mark_propagates_through_builders_and_parsers() {
+    let keys = WithdrawKeys { vault: Pubkey::new_unique(), payer: Pubkey::new_unique() };
+    assert_eq!(round_trip(keys, 7).unwrap(), 7);
+
+    let err = parse_truncated(&[1, 0]).unwrap_err();
+    assert!(matches!(err, InterfaceError::InstructionParse(InstructionParseError::InstructionDataTooShort { .. })));
+    assert!(matches!(WithdrawIxData::from_bytes(&[1, 0]), Err(InterfaceError::Io(_))));
+}
+
+#[test]
+fn interface_errors_map_to_program_errors() {
+    let program: ProgramError = InterfaceError::from(TreasuryError::InsufficientFunds).into();
+    assert_eq!(program, ProgramError::Custom(6000));
+    let account: ProgramError = InterfaceError::from(AccountParseError::DataTooShort { expected: 8, found: 0 }).into();
+    assert_eq!(account, ProgramError::InvalidAccountData);
+    let instruction: ProgramError = parse_truncated(&[]).unwrap_err().into();
+    assert_eq!(instruction, ProgramError::InvalidInstructionData);
+    let io: ProgramError = WithdrawIxData::from_bytes(&[]).unwrap_err().into();
+    assert_eq!(io, ProgramError::InvalidInstructionData);
+}
+"#;
+        run_decode_test("interface_error", TREASURY_IDL, &[], &[], test_source);
+    }
+
+    #[test]
+    fn message_overrides_apply_until_cleared() {
+        let idl = r#"{"name": "treasury", "version": "0.1.0",
+            "metadata": {"name": "treasury", "version": "0.1.0", "spec": "0.1.0", "origin": "shank", "address": "Treasury11111111111111111111111111111111111"},
+            "instructions": [],
+            "errors": [{"code": 6000, "name": "InsufficientFunds", "msg": "Not enough funds"}, {"code": 6001, "name": "VaultLocked", "msg": "Vault is locked"}]}"#;
+        // 覆盖回调是进程级状态，整个流程放在同一个测试函数中，避免并行测试互相干扰
+        let test_source = r#"
+use sol_treasury_interface::errors::*;
+
+#[test]
+fn overrides_replace_idl_messages_until_cleared() {
+    assert_eq!(TreasuryError::InsufficientFunds.localized_message(), "Not enough funds");
+    assert_eq!(TreasuryError::VaultLocked.localized_message(), "Vault is locked");
+    assert_eq!(localized_message(42), None);
+
+    set_message_override(|code| match code {
+        6000 => Some("余额不足".to_string()),
+        42 => Some("foreign error".to_string()),
+        _ => None,
+    });
+    assert_eq!(TreasuryError::InsufficientFunds.localized_message(), "余额不足");
+    assert_eq!(localized_message(6000).as_deref(), Some("余额不足"));
+    // 回调返回 None 的代码回退到 IDL 消息
+    assert_eq!(TreasuryError::VaultLocked.localized_message(), "Vault is locked");
+    assert_eq!(localized_message(6001).as_deref(), Some("Vault is locked"));
+    // 未声明的代码也会询问回调，回调不处理时为 None
+    assert_eq!(localized_message(42).as_deref(), Some("foreign error"));
+    assert_eq!(localized_message(43), None);
+    // 覆盖不影响原始 IDL 消息
+    assert_eq!(message(6000), Some("Not enough funds"));
+
+    set_message_override(|code| (code == 6001).then(|| "vault locked".to_string()));
+    assert_eq!(TreasuryError::InsufficientFunds.localized_message(), "Not enough funds");
+    assert_eq!(TreasuryError::VaultLocked.localized_message(), "vault locked");
+
+    clear_message_override();
+    assert_eq!(TreasuryError::VaultLocked.localized_message(), "Vault is locked");
+    assert_eq!(localized_message(6000).as_deref(), Some("Not enough funds"));
+    assert_eq!(localized_message(42), None);
+}
+"#;
+        run_decode_test("message_override", idl, &[], &[], test_source);
+    }
+}
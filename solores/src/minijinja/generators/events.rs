@@ -3,10 +3,10 @@
 //! 负责生成事件相关的文件夹结构和单个事件文件
 
 use crate::error::SoloresError;
+use crate::idl_format::identifiers::ModuleNames;
 use minijinja::{context, Environment, Value};
 use std::fs;
 use std::path::Path;
-use convert_case::{Case, Casing};
 use log;
 
 /// 生成events文件夹和每个事件文件
@@ -51,6 +51,7 @@ pub fn generate_events_folder(
     
     // 收集事件文件名用于mod.rs
     let mut event_names = Vec::new();
+    let mut modules = ModuleNames::new("events");
     
     // 为每个事件生成单独文件（仅当events_len > 0时）
     if events_len > 0 {
@@ -59,14 +60,17 @@ pub fn generate_events_folder(
                 if event != Value::UNDEFINED {
                     if let Ok(name_value) = event.get_attr("name") {
                         if let Some(event_name) = name_value.as_str() {
-                            let filename = struct_name_to_filename(event_name);
-                            event_names.push(filename.trim_end_matches(".rs").to_string());
+                            let module = modules.allocate(event_name);
+                            let filename = format!("{}.rs", module.file_stem);
+                            event_names.push(module.ident);
                             
                             // 创建单个事件上下文 - 使用官方API
                             let event_context = context! {
                                 event => event.clone(),
                                 crate_name => context.get_attr("crate_name").unwrap_or(Value::from("")),
                                 has_serde => context.get_attr("has_serde").unwrap_or(Value::from(false)),
+                                serde_rename => context.get_attr("serde_rename").unwrap_or(Value::UNDEFINED),
+                                has_jsonschema => context.get_attr("has_jsonschema").unwrap_or(Value::from(false)),
                                 is_unified_library => context.get_attr("is_unified_library").unwrap_or(Value::from(false))
                             };
                             
@@ -127,7 +131,51 @@ pub fn generate_single_event_file(
     Ok(())
 }
 
-/// 结构体名到文件名转换
-fn struct_name_to_filename(name: &str) -> String {
-    name.to_case(Case::Snake) + ".rs"
-}
\ No newline at end of file
+
+#[cfg(test)]
+mod tests {
+    use crate::test_support::run_decode_test;
+
+    /// content_hash / dedupe_key 对同一事件稳定，对不同内容不同
+    const DEDUPE_TEST: &str = r#"
+use sol_CRATE_interface::{events::Deposited, parsers::ProgramEvent};
+
+#[test]
+fn content_hash_is_stable_and_tracks_the_payload() {
+    let event = Deposited::new(42);
+    let hash = event.content_hash().unwrap();
+    assert_eq!(Deposited::new(42).content_hash().unwrap(), hash);
+    assert_eq!(hash, sol_CRATE_interface::events::content_hash(&event.try_to_vec().unwrap()));
+    assert_ne!(Deposited::new(43).content_hash().unwrap(), hash);
+    assert_eq!(ProgramEvent::Deposited(event).content_hash().unwrap(), hash);
+}
+
+#[test]
+fn dedupe_key_is_stable_and_tracks_the_payload() {
+    let key = Deposited::new(42).dedupe_key("sig", 3).unwrap();
+    assert_eq!(key, format!("sig:3:{:016x}", Deposited::new(42).content_hash().unwrap()));
+    assert_eq!(Deposited::new(42).dedupe_key("sig", 3).unwrap(), key);
+    assert_ne!(Deposited::new(43).dedupe_key("sig", 3).unwrap(), key);
+    assert_ne!(Deposited::new(42).dedupe_key("sig", 4).unwrap(), key);
+    assert_eq!(ProgramEvent::Deposited(Deposited::new(42)).dedupe_key("sig", 3).unwrap(), key);
+}
+"#;
+
+    #[test]
+    fn anchor_event_hashes_are_stable() {
+        let idl = r#"{"address": "Poo1111111111111111111111111111111111111111", "metadata": {"name": "pool", "version": "0.1.0", "spec": "0.1.0"},
+            "instructions": [{"name": "deposit", "discriminator": [1, 2, 3, 4, 5, 6, 7, 8], "accounts": [{"name": "pool", "writable": true}], "args": [{"name": "amount", "type": "u64"}]}],
+            "events": [{"name": "Deposited", "discriminator": [7, 7, 7, 7, 7, 7, 7, 7]}],
+            "types": [{"name": "Deposited", "type": {"kind": "struct", "fields": [{"name": "amount", "type": "u64"}]}}]}"#;
+        run_decode_test("event_hash_anchor", idl, &[], &[], &DEDUPE_TEST.replace("CRATE", "pool"));
+    }
+
+    #[test]
+    fn non_anchor_event_hashes_are_stable() {
+        let idl = r#"{"name": "treasury", "version": "0.1.0",
+            "metadata": {"name": "treasury", "version": "0.1.0", "spec": "0.1.0", "origin": "shank", "address": "Treasury11111111111111111111111111111111111"},
+            "instructions": [{"name": "withdraw", "discriminator": [1], "accounts": [{"name": "vault", "isMut": true, "isSigner": false}], "args": [{"name": "amount", "type": "u64"}]}],
+            "events": [{"name": "Deposited", "fields": [{"name": "amount", "type": "u64"}]}]}"#;
+        run_decode_test("event_hash_non_anchor", idl, &[], &[], &DEDUPE_TEST.replace("CRATE", "treasury"));
+    }
+}
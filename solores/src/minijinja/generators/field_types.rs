@@ -0,0 +1,149 @@
+//! 字段类型替换辅助模块生成器
+//!
+//! `--field-type` 替换了字段类型时生成 field_types.rs：Borsh 编解码、serde 适配类型 `Via` 与
+//! arbitrary 生成均先得到 IDL 原类型的值，再经 `Into` / `TryFrom` 与替换类型互转，
+//! 编码与未替换时逐字节一致。
+
+use crate::error::SoloresError;
+use crate::minijinja::type_mapper::split_generic;
+use crate::minijinja::utils::{nested_big_array_serde_as, split_array_type, SERDE_ARRAY_LIMIT};
+use minijinja::{Environment, Value};
+use std::fs;
+use std::path::Path;
+
+const PUBKEY: &str = "solana_pubkey::Pubkey";
+const DISPLAY_FROM_STR: &str = "serde_with::DisplayFromStr";
+const SAME: &str = "serde_with::Same";
+
+/// IDL 原类型的 serde_with 适配类型，与模板为未替换字段选择的 serde 属性一致
+///
+/// `string_pubkey_containers` 对应模板以字符串序列化 `Option` / `Vec<Pubkey>` 的位置
+pub fn serde_as(rust_type: &str, string_pubkey_containers: bool) -> String {
+    let rust_type = rust_type.trim();
+    if rust_type == PUBKEY {
+        return DISPLAY_FROM_STR.to_string();
+    }
+    if let Some((element, size)) = split_array_type(rust_type) {
+        if split_array_type(element).is_none() && size > SERDE_ARRAY_LIMIT {
+            return format!("[{}; {}]", SAME, size);
+        }
+    }
+    match split_generic(rust_type).as_ref().map(|(base, args)| (*base, args.as_slice())) {
+        Some((container @ ("std::option::Option" | "std::vec::Vec"), [PUBKEY])) if string_pubkey_containers => {
+            format!("{}<{}>", container, DISPLAY_FROM_STR)
+        }
+        Some(("std::collections::HashMap", [_, _])) => {
+            format!("std::collections::HashMap<{}, {}>", DISPLAY_FROM_STR, SAME)
+        }
+        _ => nested_big_array_serde_as(rust_type).unwrap_or_else(|| SAME.to_string()),
+    }
+}
+
+/// 生成字段类型替换辅助模块
+pub fn generate_field_types_single_file(
+    env: &mut Environment,
+    src_dir: &Path,
+    context: &Value,
+) -> std::result::Result<(), SoloresError> {
+    let template_content = include_str!("../templates/common/field_types.rs.jinja");
+
+    let tmpl = env.template_from_str(template_content)
+        .map_err(|e| SoloresError::TemplateError {
+            template_name: Some("common/field_types.rs.jinja".to_string()),
+            message: format!("模板解析失败: {}", e),
+            context: Some("解析字段类型替换模板".to_string()),
+        })?;
+
+    let rendered = tmpl.render(context)
+        .map_err(|e| SoloresError::TemplateError {
+            template_name: Some("common/field_types.rs.jinja".to_string()),
+            message: format!("模板渲染失败: {}", e),
+            context: Some("渲染字段类型替换模板".to_string()),
+        })?;
+
+    let output_path = src_dir.join("field_types.rs");
+    fs::write(&output_path, rendered)
+        .map_err(|e| SoloresError::file_operation_error("写入字段类型替换模块", output_path.display().to_string(), e))?;
+
+    Ok(())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::test_support::run_decode_test;
+
+    #[test]
+    fn serde_adapter_matches_the_unsubstituted_representation() {
+        assert_eq!(serde_as("solana_pubkey::Pubkey", false), DISPLAY_FROM_STR);
+        assert_eq!(serde_as("i64", false), SAME);
+        assert_eq!(serde_as("[u8; 32]", false), SAME);
+        assert_eq!(serde_as("[u8; 64]", false), "[serde_with::Same; 64]");
+        assert_eq!(serde_as("std::vec::Vec<solana_pubkey::Pubkey>", true), "std::vec::Vec<serde_with::DisplayFromStr>");
+        assert_eq!(serde_as("std::vec::Vec<solana_pubkey::Pubkey>", false), SAME);
+        assert_eq!(serde_as("std::option::Option<[u8; 64]>", false), "std::option::Option<[serde_with::Same; 64]>");
+    }
+
+    #[test]
+    fn substituted_fields_keep_the_idl_encoding() {
+        let idl = r#"{"address": "Poo1111111111111111111111111111111111111111", "metadata": {"name": "pool", "version": "0.1.0", "spec": "0.1.0"},
+            "instructions": [{"name": "initialize", "discriminator": [1, 2, 3, 4, 5, 6, 7, 8], "accounts": [{"name": "pool", "writable": true}], "args": [{"name": "openTime", "type": "i64"}]}],
+            "accounts": [{"name": "Pool", "discriminator": [8, 7, 6, 5, 4, 3, 2, 1]}],
+            "types": [{"name": "Pool", "type": {"kind": "struct", "fields": [{"name": "owner", "type": "pubkey"}, {"name": "openTime", "type": "i64"}]}}]}"#;
+        // u32 满足 Into<i64> / TryFrom<i64>，负数与超出 u32 的值被拒绝
+        let test_source = r#"
+use borsh::BorshDeserialize;
+use sol_pool_interface::{accounts::Pool, field_types, instructions::InitializeIxData};
+use solana_pubkey::Pubkey;
+
+fn pool_bytes(owner: Pubkey, open_time: i64) -> Vec<u8> {
+    [&[8u8, 7, 6, 5, 4, 3, 2, 1][..], owner.as_ref(), &open_time.to_le_bytes()].concat()
+}
+
+#[test]
+fn borsh_uses_the_idl_type() {
+    let owner = Pubkey::new_unique();
+    let bytes = pool_bytes(owner, 1_700_000_000);
+    let pool = Pool::from_bytes(&bytes).unwrap();
+    assert_eq!((pool.owner, pool.open_time), (owner, 1_700_000_000u32));
+    assert_eq!(pool.try_to_vec().unwrap(), bytes);
+
+    let data = InitializeIxData::new(7);
+    assert_eq!(data.try_to_vec().unwrap(), [&[1u8, 2, 3, 4, 5, 6, 7, 8][..], &7i64.to_le_bytes()].concat());
+    assert_eq!(InitializeIxData::from_bytes(&data.try_to_vec().unwrap()).unwrap(), data);
+
+    let mut encoded = Vec::new();
+    field_types::serialize::<i64, u32, _>(&9, &mut encoded).unwrap();
+    assert_eq!(encoded, 9i64.to_le_bytes());
+    assert_eq!(field_types::deserialize::<i64, u32, _>(&mut encoded.as_slice()).unwrap(), 9);
+}
+
+#[test]
+fn values_the_substituted_type_rejects_fail_to_decode() {
+    assert!(Pool::from_bytes(&pool_bytes(Pubkey::new_unique(), -1)).is_err());
+    assert!(Pool::try_from_slice(&pool_bytes(Pubkey::new_unique(), i64::from(u32::MAX) + 1)).is_err());
+    let error = field_types::deserialize::<i64, u32, _>(&mut (-1i64).to_le_bytes().as_slice()).unwrap_err();
+    assert_eq!(error.kind(), std::io::ErrorKind::InvalidData);
+}
+
+#[test]
+fn serde_uses_the_idl_type() {
+    let pool = Pool { open_time: 42, ..Pool::default() };
+    let json = serde_json::to_value(&pool).unwrap();
+    assert_eq!(json["open_time"], serde_json::json!(42));
+    assert_eq!(serde_json::from_value::<Pool>(json.clone()).unwrap(), pool);
+
+    let mut negative = json;
+    negative["open_time"] = serde_json::json!(-5);
+    assert!(serde_json::from_value::<Pool>(negative).is_err());
+}
+"#;
+        run_decode_test(
+            "field-types",
+            idl,
+            &["--field-type", "Pool.open_time=u32", "--field-type", "initialize.openTime=u32"],
+            &["serde"],
+            test_source,
+        );
+    }
+}
@@ -0,0 +1,95 @@
+//! arbitrary 辅助模块生成器
+//! 
+//! 生成 fuzz.rs，为 arbitrary feature 提供变长字段的长度上限函数
+
+use crate::error::SoloresError;
+use minijinja::{Environment, Value};
+use std::fs;
+use std::path::Path;
+
+/// 生成arbitrary辅助模块
+pub fn generate_fuzz_single_file(
+    env: &mut Environment,
+    src_dir: &Path,
+    context: &Value,
+) -> std::result::Result<(), SoloresError> {
+    let template_content = include_str!("../templates/common/fuzz.rs.jinja");
+    
+    let tmpl = env.template_from_str(template_content)
+        .map_err(|e| SoloresError::TemplateError {
+            template_name: Some("common/fuzz.rs.jinja".to_string()),
+            message: format!("模板解析失败: {}", e),
+            context: Some("解析fuzz模板".to_string()),
+        })?;
+    
+    let rendered = tmpl.render(context)
+        .map_err(|e| SoloresError::TemplateError {
+            template_name: Some("common/fuzz.rs.jinja".to_string()),
+            message: format!("模板渲染失败: {}", e),
+            context: Some("渲染fuzz模板".to_string()),
+        })?;
+    
+    let output_path = src_dir.join("fuzz.rs");
+    fs::write(&output_path, rendered)
+        .map_err(|e| SoloresError::FileOperationError {
+            operation: "write fuzz file".to_string(),
+            path: output_path.display().to_string(),
+            current_dir: std::env::current_dir().ok().map(|p| p.display().to_string()),
+            resolved_path: None,
+            source: e,
+            suggestion: Some("检查文件权限".to_string()),
+        })?;
+    
+    Ok(())
+}
+
+#[cfg(test)]
+mod tests {
+    use crate::test_support::run_decode_test;
+
+    #[test]
+    fn nested_variable_length_fields_respect_the_caps() {
+        let idl = r#"{"address": "Poo1111111111111111111111111111111111111111", "metadata": {"name": "pool", "version": "0.1.0", "spec": "0.1.0"},
+            "instructions": [{"name": "configure", "discriminator": [1, 2, 3, 4, 5, 6, 7, 8], "accounts": [{"name": "pool", "writable": true}],
+                "args": [{"name": "labels", "type": {"option": {"vec": "string"}}}]}],
+            "accounts": [{"name": "Pool", "discriminator": [8, 7, 6, 5, 4, 3, 2, 1]}],
+            "types": [{"name": "Pool", "type": {"kind": "struct", "fields": [
+                          {"name": "rows", "type": {"vec": {"vec": "u8"}}},
+                          {"name": "limits", "type": {"option": {"vec": "u64"}}},
+                          {"name": "names", "type": {"array": ["string", 2]}},
+                          {"name": "tiers", "type": {"vec": {"defined": {"name": "Tier"}}}}]}},
+                      {"name": "Tier", "type": {"kind": "struct", "fields": [{"name": "fees", "type": {"vec": "u16"}}]}}]}"#;
+        let test_source = r#"
+use arbitrary::{Arbitrary, Unstructured};
+use sol_pool_interface::{accounts::Pool, fuzz::{MAX_ARBITRARY_STRING_LEN, MAX_ARBITRARY_VEC_LEN}, instructions::ConfigureIxData};
+
+fn inputs() -> impl Iterator<Item = Vec<u8>> {
+    (0..32u64).map(|seed| {
+        let mut state = seed.wrapping_mul(0x9e37_79b9_7f4a_7c15) | 1;
+        (0..1 << 16).map(|_| { state ^= state << 13; state ^= state >> 7; state ^= state << 17; state as u8 }).collect()
+    })
+}
+
+#[test]
+fn every_nesting_level_is_capped() {
+    let mut longest_row = 0;
+    for data in inputs() {
+        let pool = Pool::arbitrary(&mut Unstructured::new(&data)).unwrap();
+        assert!(pool.rows.len() <= MAX_ARBITRARY_VEC_LEN);
+        assert!(pool.rows.iter().all(|row| row.len() <= MAX_ARBITRARY_VEC_LEN));
+        longest_row = longest_row.max(pool.rows.iter().map(Vec::len).max().unwrap_or(0));
+        assert!(pool.limits.iter().all(|limits| limits.len() <= MAX_ARBITRARY_VEC_LEN));
+        assert!(pool.names.iter().all(|name| name.chars().count() <= MAX_ARBITRARY_STRING_LEN));
+        assert!(pool.tiers.iter().all(|tier| tier.fees.len() <= MAX_ARBITRARY_VEC_LEN));
+
+        let args = ConfigureIxData::arbitrary(&mut Unstructured::new(&data)).unwrap();
+        let labels = args.labels.unwrap_or_default();
+        assert!(labels.len() <= MAX_ARBITRARY_VEC_LEN);
+        assert!(labels.iter().all(|label| label.chars().count() <= MAX_ARBITRARY_STRING_LEN));
+    }
+    assert!(longest_row > 0);
+}
+"#;
+        run_decode_test("arbitrary", idl, &[], &["arbitrary"], test_source);
+    }
+}
@@ -0,0 +1,63 @@
+//! 历史指令统计生成器
+//!
+//! `--gen-history-stats` 时生成 history.rs：`analyze_history(dir)` 读取目录中抓取的原始指令数据
+//! （每个文件一条指令的 data），用生成的指令解析器分类，统计每个指令的数量、数据大小与解析失败率，
+//! 供评估索引 / 存储容量使用。
+//!
+//! 历史数据通常只保存指令 data，解析时以占位账户补足最多账户的指令所需的数量。
+
+use crate::error::SoloresError;
+use minijinja::{context, Environment, Value};
+use std::fs;
+use std::path::Path;
+
+/// 所有指令中最多的账户数，作为占位账户数组的长度
+fn max_ix_accounts(context: &Value) -> usize {
+    context
+        .get_attr("instructions")
+        .ok()
+        .and_then(|instructions| instructions.try_iter().ok())
+        .into_iter()
+        .flatten()
+        .filter_map(|instruction| instruction.get_attr("accounts").ok().and_then(|accounts| accounts.len()))
+        .max()
+        .unwrap_or(0)
+}
+
+/// 生成 history.rs
+pub fn generate_history_single_file(
+    env: &mut Environment,
+    src_dir: &Path,
+    context: &Value,
+) -> std::result::Result<(), SoloresError> {
+    let template_name = "common/history.rs.jinja";
+    let tmpl = env.template_from_str(include_str!("../templates/common/history.rs.jinja"))
+        .map_err(|e| SoloresError::TemplateError {
+            template_name: Some(template_name.to_string()),
+            message: format!("模板解析失败: {}", e),
+            context: Some("解析history模板".to_string()),
+        })?;
+    let rendered = tmpl.render(context! { max_ix_accounts => max_ix_accounts(context), ..context.clone() })
+        .map_err(|e| SoloresError::TemplateError {
+            template_name: Some(template_name.to_string()),
+            message: format!("模板渲染失败: {}", e),
+            context: Some("渲染history模板".to_string()),
+        })?;
+    let output_path = src_dir.join("history.rs");
+    fs::write(&output_path, rendered)
+        .map_err(|e| SoloresError::file_operation_error("write history file", output_path.display().to_string(), e))?;
+    Ok(())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn placeholder_accounts_cover_the_largest_instruction() {
+        let instruction = |accounts: usize| context! { accounts => vec![context! { name => "owner" }; accounts] };
+        let context = context! { instructions => vec![instruction(2), instruction(0), instruction(5)] };
+        assert_eq!(max_ix_accounts(&context), 5);
+        assert_eq!(max_ix_accounts(&context! { instructions => Vec::<Value>::new() }), 0);
+    }
+}
@@ -3,7 +3,7 @@
 //! 负责生成指令相关的文件夹结构和单个指令文件
 
 use crate::error::SoloresError;
-use convert_case::{Case, Casing};
+use crate::idl_format::identifiers::ModuleNames;
 use minijinja::{context, Environment, Value};
 use std::fs;
 use std::path::Path;
@@ -38,6 +38,7 @@ pub fn generate_instructions_folder(
 
     // 收集指令文件名用于mod.rs
     let mut instruction_names = Vec::new();
+    let mut modules = ModuleNames::new("instructions");
 
     // 为每个指令生成单独文件
     for i in 0..instructions.len().unwrap_or(0) {
@@ -45,8 +46,9 @@ pub fn generate_instructions_folder(
             if instruction != Value::UNDEFINED {
                 if let Ok(name_value) = instruction.get_attr("name") {
                     if let Some(instruction_name) = name_value.as_str() {
-                        let filename = struct_name_to_filename(instruction_name);
-                        instruction_names.push(filename.trim_end_matches(".rs").to_string());
+                        let module = modules.allocate(instruction_name);
+                        let filename = format!("{}.rs", module.file_stem);
+                        instruction_names.push(module.ident);
 
                         // 检测是否有remainingAccountsInfo参数
                         let has_remaining_accounts_info =
@@ -62,7 +64,11 @@ pub fn generate_instructions_folder(
                             instruction => instruction.clone(),
                             crate_name => context.get_attr("crate_name").unwrap_or(Value::from("")),
                             has_serde => context.get_attr("has_serde").unwrap_or(Value::from(false)),
-                            has_remaining_accounts_info => has_remaining_accounts_info
+                            serde_rename => context.get_attr("serde_rename").unwrap_or(Value::UNDEFINED),
+                            has_jsonschema => context.get_attr("has_jsonschema").unwrap_or(Value::from(false)),
+                            has_remaining_accounts_info => has_remaining_accounts_info,
+                            has_clusters => context.get_attr("has_clusters").unwrap_or(Value::from(false)),
+                            typed_key_types => context.get_attr("typed_key_types").unwrap_or(Value::UNDEFINED)
                         };
 
                         // 生成指令文件
@@ -79,6 +85,10 @@ pub fn generate_instructions_folder(
         }
     }
 
+    // 生成CompiledInstruction解码适配器与账户角色表
+    generate_compiled_file(env, &instructions_dir, context, template_type)?;
+    generate_account_meta_file(env, &instructions_dir, context)?;
+
     // 生成instructions/mod.rs
     super::common::generate_folder_mod_file(
         env,
@@ -136,13 +146,77 @@ pub fn generate_single_instruction_file(
     Ok(())
 }
 
-/// 结构体名到文件名转换
-fn struct_name_to_filename(name: &str) -> String {
-    name.to_case(Case::Snake) + ".rs"
+/// 生成instructions/compiled.rs（versioned-tx feature）
+pub fn generate_compiled_file(
+    env: &mut Environment,
+    folder: &Path,
+    context: &Value,
+    template_type: &str,
+) -> std::result::Result<(), SoloresError> {
+    let template_content = include_str!("../templates/common/compiled.rs.jinja");
+
+    let tmpl = env.template_from_str(template_content)
+        .map_err(|e| SoloresError::TemplateError {
+            template_name: Some("common/compiled.rs.jinja".to_string()),
+            message: format!("模板解析失败: {}", e),
+            context: Some("解析compiled指令模板".to_string()),
+        })?;
+
+    let compiled_context = context! {
+        anchor => template_type == "anchor",
+        ..context.clone()
+    };
+    let rendered = tmpl.render(&compiled_context)
+        .map_err(|e| SoloresError::TemplateError {
+            template_name: Some("common/compiled.rs.jinja".to_string()),
+            message: format!("模板渲染失败: {}", e),
+            context: Some("渲染compiled指令模板".to_string()),
+        })?;
+
+    let output_path = folder.join("compiled.rs");
+    fs::write(&output_path, rendered)
+        .map_err(|e| SoloresError::FileOperationError {
+            operation: "write compiled instructions file".to_string(),
+            path: output_path.display().to_string(),
+            current_dir: std::env::current_dir().ok().map(|p| p.display().to_string()),
+            resolved_path: None,
+            source: e,
+            suggestion: Some("检查文件权限".to_string()),
+        })?;
+
+    Ok(())
 }
 
+/// 生成instructions/account_meta.rs：各指令账户角色的运行时表
+pub fn generate_account_meta_file(
+    env: &mut Environment,
+    folder: &Path,
+    context: &Value,
+) -> std::result::Result<(), SoloresError> {
+    let template_name = "common/account_meta.rs.jinja";
+    let tmpl = env.template_from_str(include_str!("../templates/common/account_meta.rs.jinja"))
+        .map_err(|e| SoloresError::TemplateError {
+            template_name: Some(template_name.to_string()),
+            message: format!("模板解析失败: {}", e),
+            context: Some("解析账户角色模板".to_string()),
+        })?;
+    let rendered = tmpl.render(context)
+        .map_err(|e| SoloresError::TemplateError {
+            template_name: Some(template_name.to_string()),
+            message: format!("模板渲染失败: {}", e),
+            context: Some("渲染账户角色模板".to_string()),
+        })?;
+
+    let output_path = folder.join("account_meta.rs");
+    fs::write(&output_path, rendered)
+        .map_err(|e| SoloresError::file_operation_error("write account meta file", output_path.display().to_string(), e))?;
+
+    Ok(())
+}
+
+
 /// 检测指令是否包含remainingAccountsInfo参数
-fn detect_remaining_accounts_info(instruction: &Value) -> bool {
+pub(super) fn detect_remaining_accounts_info(instruction: &Value) -> bool {
     // 获取指令的args字段
     if let Ok(args) = instruction.get_attr("args") {
         // 遍历所有参数
@@ -164,3 +238,307 @@ fn detect_remaining_accounts_info(instruction: &Value) -> bool {
     }
     false
 }
+
+#[cfg(test)]
+mod tests {
+    use crate::test_support::run_decode_test;
+
+    const TREASURY_IDL: &str = r#"{"name": "treasury", "version": "0.1.0",
+            "metadata": {"name": "treasury", "version": "0.1.0", "spec": "0.1.0", "origin": "shank", "address": "Treasury11111111111111111111111111111111111"},
+            "instructions": [{"name": "withdraw", "discriminator": [1],
+                "accounts": [{"name": "vault", "isMut": true, "isSigner": true}, {"name": "payer", "isMut": true, "isSigner": true}, {"name": "destination", "isMut": true, "isSigner": false}],
+                "args": [{"name": "amount", "type": "u64"}]}]}"#;
+
+    #[test]
+    fn non_anchor_proposal_ix_clears_only_the_vault_signer() {
+        let test_source = r#"
+use sol_treasury_interface::instructions::*;
+use solana_pubkey::Pubkey;
+
+#[test]
+fn proposal_ix_downgrades_the_vault() {
+    let keys = WithdrawKeys { vault: Pubkey::new_unique(), payer: Pubkey::new_unique(), destination: Pubkey::new_unique() };
+    let args = WithdrawIxData::new(5);
+    let ix = withdraw_ix(keys.clone(), args.clone()).unwrap();
+    assert_eq!(ix.program_id, sol_treasury_interface::ID);
+    assert_eq!(ix.data, [&[1u8][..], &5u64.to_le_bytes()].concat());
+    assert!(ix.accounts.iter().all(|meta| meta.is_writable));
+    assert_eq!(ix.accounts.iter().map(|meta| meta.is_signer).collect::<Vec<_>>(), [true, true, false]);
+
+    let proposal = withdraw_proposal_ix(keys.vault, keys.clone(), args).unwrap();
+    assert_eq!((proposal.program_id, &proposal.data), (ix.program_id, &ix.data));
+    assert_eq!(proposal.accounts.iter().map(|meta| meta.is_signer).collect::<Vec<_>>(), [false, true, false]);
+    assert_eq!(sol_treasury_interface::proposal::external_signers(&proposal, &keys.vault), [keys.payer]);
+}
+"#;
+        run_decode_test("proposal", TREASURY_IDL, &[], &["proposal"], test_source);
+    }
+
+    #[test]
+    fn cluster_builders_use_the_cluster_program_id() {
+        let test_source = r#"
+use sol_treasury_interface::{instructions::*, program_ids, Cluster};
+use solana_pubkey::Pubkey;
+
+#[test]
+fn ix_for_cluster_targets_the_cluster_deployment() {
+    let keys = WithdrawKeys { vault: Pubkey::new_unique(), payer: Pubkey::new_unique(), destination: Pubkey::new_unique() };
+    let ix = withdraw_ix_for_cluster(Cluster::Devnet, keys.clone(), WithdrawIxData::new(5)).unwrap();
+    assert_eq!(ix.program_id, program_ids::DEVNET);
+    assert_eq!(ix.data, withdraw_ix(keys, WithdrawIxData::new(5)).unwrap().data);
+}
+"#;
+        run_decode_test(
+            "clusters",
+            TREASURY_IDL,
+            &["--program-id", "devnet=11111111111111111111111111111111"],
+            &[],
+            test_source,
+        );
+    }
+
+    #[test]
+    fn fixed_account_addresses_are_constants_and_key_defaults() {
+        let idl = r#"{"name": "treasury", "version": "0.1.0",
+            "metadata": {"name": "treasury", "version": "0.1.0", "spec": "0.1.0", "origin": "shank", "address": "Treasury11111111111111111111111111111111111"},
+            "instructions": [{"name": "open", "discriminator": [0],
+                "accounts": [{"name": "vault", "isMut": true, "isSigner": false}, {"name": "rent", "isMut": false, "isSigner": false, "address": "SysvarRent111111111111111111111111111111111"}],
+                "args": []}]}"#;
+        let test_source = r#"
+use sol_treasury_interface::instructions::*;
+use solana_pubkey::Pubkey;
+
+#[test]
+fn addresses_match_the_idl() {
+    assert_eq!(sol_treasury_interface::ID, "Treasury11111111111111111111111111111111111".parse::<Pubkey>().unwrap());
+    assert_eq!(OPEN_RENT_ADDRESS, "SysvarRent111111111111111111111111111111111".parse::<Pubkey>().unwrap());
+
+    let keys = OpenKeys::default();
+    assert_eq!((keys.vault, keys.rent), (Pubkey::default(), OPEN_RENT_ADDRESS));
+}
+"#;
+        run_decode_test("fixed_addresses", idl, &[], &[], test_source);
+    }
+
+    const POOL_IDL: &str = r#"{"address": "Poo1111111111111111111111111111111111111111", "metadata": {"name": "pool", "version": "0.1.0", "spec": "0.1.0"},
+            "instructions": [{"name": "deposit", "discriminator": [1, 0, 0, 0, 0, 0, 0, 0],
+                    "accounts": [{"name": "pool", "writable": true}, {"name": "owner", "signer": true}, {"name": "referrer", "writable": true, "optional": true}, {"name": "payer", "writable": true, "signer": true}],
+                    "args": [{"name": "amount", "type": "u64"}]},
+                {"name": "sync", "discriminator": [2, 0, 0, 0, 0, 0, 0, 0], "accounts": [], "args": []}]}"#;
+
+    const LEDGER_IDL: &str = r#"{"name": "ledger", "version": "0.1.0",
+            "metadata": {"name": "ledger", "version": "0.1.0", "spec": "0.1.0", "origin": "shank", "address": "Ledger1111111111111111111111111111111111111"},
+            "instructions": [{"name": "transfer", "discriminator": [3],
+                "accounts": [{"name": "source", "isMut": true, "isSigner": false}, {"name": "authority", "isMut": false, "isSigner": true}, {"name": "destination", "isMut": true, "isSigner": false}, {"name": "clock", "isMut": false, "isSigner": false}],
+                "args": [{"name": "amount", "type": "u64"}]}]}"#;
+
+    #[test]
+    fn expected_accounts_follow_the_idl_flags() {
+        let anchor_source = r#"
+use sol_pool_interface::{instructions::*, parsers::instructions::ProgramInstruction};
+use solana_instruction::AccountMeta;
+use solana_pubkey::Pubkey;
+
+#[test]
+fn parsed_instructions_report_their_account_shape() {
+    let accounts: Vec<Pubkey> = (0..4).map(|_| Pubkey::new_unique()).collect();
+    let deposit = ProgramInstruction::try_parse(&DepositIxData::new(1).try_to_vec().unwrap(), &accounts).unwrap();
+    let shape = deposit.expected_accounts();
+    assert_eq!(shape, &DEPOSIT_IX_ACCOUNT_SHAPE);
+    assert_eq!(account_shape("Deposit"), Some(shape));
+    assert_eq!((shape.len, deposit.accounts_len()), (4, 4));
+    assert_eq!(shape.writable, [0, 2, 3]);
+    assert_eq!(shape.signers, [1, 3]);
+    assert_eq!(shape.optional, [2]);
+
+    let sync = ProgramInstruction::try_parse(&SyncIxData::new().try_to_vec().unwrap(), &[]).unwrap();
+    assert_eq!(*sync.expected_accounts(), AccountShape { len: 0, writable: &[], signers: &[], optional: &[] });
+    assert_eq!(account_shape("Missing"), None);
+}
+
+#[test]
+fn shape_checks_assembled_account_lists() {
+    let metas = |flags: [(bool, bool); 4]| -> Vec<AccountMeta> {
+        flags.iter().map(|&(writable, signer)| AccountMeta { pubkey: Pubkey::new_unique(), is_writable: writable, is_signer: signer }).collect()
+    };
+    let valid = metas([(true, false), (false, true), (true, false), (true, true)]);
+    assert_eq!(DEPOSIT_IX_ACCOUNT_SHAPE.check(&valid), Ok(()));
+    assert_eq!(DEPOSIT_IX_ACCOUNT_SHAPE.check(&valid[..3]), Err(AccountShapeError::TooFewAccounts { expected: 4, found: 3 }));
+    let read_only_pool = metas([(false, false), (false, true), (true, false), (true, true)]);
+    assert_eq!(DEPOSIT_IX_ACCOUNT_SHAPE.check(&read_only_pool), Err(AccountShapeError::NotWritable { index: 0 }));
+    let unsigned_owner = metas([(true, false), (false, false), (true, false), (true, true)]);
+    assert_eq!(DEPOSIT_IX_ACCOUNT_SHAPE.check(&unsigned_owner), Err(AccountShapeError::NotSigner { index: 1 }));
+
+    // 缺省的可选账户以程序 ID 占位，不要求可写
+    let mut absent_referrer = valid.clone();
+    absent_referrer[2] = AccountMeta::new_readonly(sol_pool_interface::ID, false);
+    assert_eq!(DEPOSIT_IX_ACCOUNT_SHAPE.check(&absent_referrer), Ok(()));
+}
+"#;
+        run_decode_test("shape_anchor", POOL_IDL, &[], &[], anchor_source);
+
+        let non_anchor_source = r#"
+use sol_ledger_interface::{instructions::*, parsers::instructions::ProgramInstruction};
+use solana_pubkey::Pubkey;
+
+#[test]
+fn parsed_instructions_report_their_account_shape() {
+    let accounts: Vec<Pubkey> = (0..4).map(|_| Pubkey::new_unique()).collect();
+    let transfer = ProgramInstruction::try_parse(&TransferIxData::new(1).try_to_vec().unwrap(), &accounts).unwrap();
+    let shape = transfer.expected_accounts();
+    assert_eq!(shape, &TRANSFER_IX_ACCOUNT_SHAPE);
+    assert_eq!(account_shape("Transfer"), Some(shape));
+    assert_eq!((shape.len, transfer.accounts_len()), (4, 4));
+    assert_eq!(shape.writable, [0, 2]);
+    assert_eq!(shape.signers, [1]);
+    assert!(shape.optional.is_empty());
+}
+"#;
+        run_decode_test("shape_non_anchor", LEDGER_IDL, &[], &[], non_anchor_source);
+    }
+
+    #[test]
+    fn account_roles_follow_idl_flags_and_keys_order() {
+        let non_anchor_source = r#"
+use sol_ledger_interface::{instructions::*, parsers::instructions::ProgramInstruction};
+use solana_pubkey::Pubkey;
+
+#[test]
+fn roles_match_is_mut_and_is_signer() {
+    let role = |name, writable, signer| AccountRole { name, writable, signer, optional: false };
+    assert_eq!(
+        TRANSFER_IX_ACCOUNT_META,
+        [role("source", true, false), role("authority", false, true), role("destination", true, false), role("clock", false, false)]
+    );
+    assert_eq!(account_roles("Transfer"), Some(TRANSFER_IX_ACCOUNT_META));
+    assert_eq!(account_role("Transfer", 1).map(|role| role.name), Some("authority"));
+    assert_eq!(account_role("Transfer", 4), None);
+}
+
+#[test]
+fn roles_are_in_keys_order() {
+    let accounts: Vec<Pubkey> = (0..TRANSFER_IX_ACCOUNTS_LEN).map(|_| Pubkey::new_unique()).collect();
+    let keys = TransferKeys::from(accounts.as_slice());
+    assert_eq!(keys.to_vec(), accounts);
+    for (role, account) in TRANSFER_IX_ACCOUNT_META.iter().zip(&accounts) {
+        let field = match role.name {
+            "source" => keys.source,
+            "authority" => keys.authority,
+            "destination" => keys.destination,
+            "clock" => keys.clock,
+            other => panic!("unexpected account {}", other),
+        };
+        assert_eq!(field, *account, "{}", role.name);
+    }
+
+    let parsed = ProgramInstruction::try_parse(&TransferIxData::new(1).try_to_vec().unwrap(), &accounts).unwrap();
+    assert_eq!(parsed_account_roles(&parsed), TRANSFER_IX_ACCOUNT_META);
+}
+"#;
+        run_decode_test("roles_non_anchor", LEDGER_IDL, &[], &[], non_anchor_source);
+
+        let anchor_source = r#"
+use sol_pool_interface::{instructions::*, parsers::instructions::ProgramInstruction};
+use solana_pubkey::Pubkey;
+
+#[test]
+fn roles_match_writable_signer_and_optional() {
+    let role = |name, writable, signer, optional| AccountRole { name, writable, signer, optional };
+    assert_eq!(
+        DEPOSIT_IX_ACCOUNT_META,
+        [role("pool", true, false, false), role("owner", false, true, false), role("referrer", true, false, true), role("payer", true, true, false)]
+    );
+    assert!(SYNC_IX_ACCOUNT_META.is_empty());
+    assert_eq!(INSTRUCTION_ACCOUNT_ROLES.iter().map(|(name, _)| *name).collect::<Vec<_>>(), ["Deposit", "Sync"]);
+}
+
+#[test]
+fn roles_are_in_keys_order() {
+    let accounts: Vec<Pubkey> = (0..DEPOSIT_IX_ACCOUNTS_LEN).map(|_| Pubkey::new_unique()).collect();
+    let keys = DepositKeys::from(accounts.as_slice());
+    let ix = deposit_ix(keys, DepositIxData::new(1)).unwrap();
+    for ((role, meta), account) in DEPOSIT_IX_ACCOUNT_META.iter().zip(&ix.accounts).zip(&accounts) {
+        assert_eq!(meta.pubkey, *account, "{}", role.name);
+        assert_eq!((meta.is_writable, meta.is_signer), (role.writable, role.signer), "{}", role.name);
+    }
+
+    let parsed = ProgramInstruction::try_parse(&ix.data, &accounts).unwrap();
+    assert_eq!(parsed_account_roles(&parsed), DEPOSIT_IX_ACCOUNT_META);
+}
+"#;
+        run_decode_test("roles_anchor", POOL_IDL, &[], &[], anchor_source);
+    }
+
+    #[test]
+    fn compiled_instructions_resolve_through_message_keys() {
+        let anchor_source = r#"
+use sol_pool_interface::{errors::InstructionParseError, instructions::*, parsers::ProgramInstruction};
+use solana_message::compiled_instruction::CompiledInstruction;
+use solana_pubkey::Pubkey;
+
+/// Account keys of the enclosing message; compiled instructions refer to them by index
+fn message_keys() -> Vec<Pubkey> {
+    (0..6).map(|_| Pubkey::new_unique()).collect()
+}
+
+fn compiled(data: Vec<u8>, accounts: Vec<u8>) -> CompiledInstruction {
+    CompiledInstruction { program_id_index: 1, accounts, data }
+}
+
+#[test]
+fn compiled_deposit_decodes_keys_and_args() {
+    let keys = message_keys();
+    let ix = compiled(DepositIxData::new(9).try_to_vec().unwrap(), vec![5, 4, 3, 2]);
+    let (decoded_keys, args) = decode_deposit_compiled(&ix, &keys).unwrap();
+    assert_eq!(decoded_keys, DepositKeys { pool: keys[5], owner: keys[4], referrer: keys[3], payer: keys[2] });
+    assert_eq!(args.amount, 9);
+    assert_eq!(DepositKeys::from_compiled(&ix, &keys).unwrap(), decoded_keys);
+    assert_eq!(compiled_accounts(&ix, &keys).unwrap(), [keys[5], keys[4], keys[3], keys[2]]);
+    assert!(matches!(decode_compiled(&ix, &keys).unwrap(), ProgramInstruction::Deposit(parsed, _) if parsed == decoded_keys));
+
+    let sync = compiled(SyncIxData::new().try_to_vec().unwrap(), vec![]);
+    assert!(decode_sync_compiled(&sync, &keys).is_ok());
+}
+
+#[test]
+fn out_of_range_indices_are_reported() {
+    let keys = message_keys();
+    let ix = compiled(DepositIxData::new(9).try_to_vec().unwrap(), vec![5, 4, 6, 2]);
+    for result in [decode_deposit_compiled(&ix, &keys).map(|_| ()), DepositKeys::from_compiled(&ix, &keys).map(|_| ()), decode_compiled(&ix, &keys).map(|_| ())] {
+        assert!(matches!(result, Err(InstructionParseError::AccountIndexOutOfBounds { index: 6, len: 6 })));
+    }
+    let sync = compiled(SyncIxData::new().try_to_vec().unwrap(), vec![200]);
+    assert!(matches!(decode_sync_compiled(&sync, &keys), Err(InstructionParseError::AccountIndexOutOfBounds { index: 200, len: 6 })));
+}
+
+#[test]
+fn short_account_lists_and_wrong_discriminators_are_rejected() {
+    let keys = message_keys();
+    let ix = compiled(DepositIxData::new(9).try_to_vec().unwrap(), vec![5, 4]);
+    assert!(matches!(DepositKeys::from_compiled(&ix, &keys), Err(InstructionParseError::DataTooShort { expected: 4, found: 2 })));
+    let sync = compiled(SyncIxData::new().try_to_vec().unwrap(), vec![5, 4, 3, 2]);
+    assert!(matches!(decode_deposit_compiled(&sync, &keys), Err(InstructionParseError::DiscriminatorMismatch { .. })));
+}
+"#;
+        run_decode_test("compiled_anchor", POOL_IDL, &[], &["versioned-tx"], anchor_source);
+
+        let non_anchor_source = r#"
+use sol_ledger_interface::{errors::InstructionParseError, instructions::*};
+use solana_message::compiled_instruction::CompiledInstruction;
+use solana_pubkey::Pubkey;
+
+#[test]
+fn compiled_transfer_resolves_through_the_message_keys() {
+    let keys: Vec<Pubkey> = (0..5).map(|_| Pubkey::new_unique()).collect();
+    let ix = CompiledInstruction { program_id_index: 0, accounts: vec![1, 2, 3, 4], data: TransferIxData::new(3).try_to_vec().unwrap() };
+    let (decoded_keys, args) = decode_transfer_compiled(&ix, &keys).unwrap();
+    assert_eq!(decoded_keys, TransferKeys { source: keys[1], authority: keys[2], destination: keys[3], clock: keys[4] });
+    assert_eq!(args.amount, 3);
+
+    let ix = CompiledInstruction { accounts: vec![1, 2, 3, 5], ..ix };
+    assert!(matches!(decode_transfer_compiled(&ix, &keys), Err(InstructionParseError::AccountIndexOutOfBounds { index: 5, len: 5 })));
+}
+"#;
+        run_decode_test("compiled_non_anchor", LEDGER_IDL, &[], &["versioned-tx"], non_anchor_source);
+    }
+}
@@ -0,0 +1,289 @@
+//! Anchor 接口 crate 互转生成器
+//!
+//! `--interop-with name[::module][=path_or_version]` 时生成 interop.rs（`interop` feature）：
+//! 为每个账户与自定义类型生成与另一个 crate 中同名类型之间的双向 `From` 实现，
+//! 便于从 Anchor 生成的 CPI crate 逐步迁移到 Solores 接口。
+//!
+//! 字段按名称一一对应：Pubkey 经字节数组转换（兼容不同版本的 Pubkey 类型），自定义类型
+//! 递归使用生成的 `From`，数组 / Option / Vec / Map 逐元素转换，其余标量原样移动。
+//! 省略模块路径时按 `declare_program!` 布局，在 `name::accounts` / `name::types` 中查找同名类型。
+
+use std::fs;
+use std::path::Path;
+use std::str::FromStr;
+
+use minijinja::{context, Environment, Value};
+
+use crate::error::SoloresError;
+use crate::idl_format::IdlFormatEnum;
+use crate::minijinja::externs::{resolve_crate, ExternCrate, ExternImports};
+use crate::minijinja::filters::{rust_field_filter, to_snake_case_filter, type_path_filter};
+
+/// `--interop-with` 选项：对方 crate、可选的模块路径与依赖来源
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct InteropCrate {
+    /// 依赖名与来源，格式同 `--extern`
+    pub dependency: ExternCrate,
+    /// crate 内的模块路径（如 `states`），None 时使用 `accounts` / `types`
+    pub module: Option<String>,
+}
+
+impl FromStr for InteropCrate {
+    type Err = String;
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        let (path, source) = match s.split_once('=') {
+            Some((path, source)) => (path.trim(), Some(source)),
+            None => (s.trim(), None),
+        };
+        let (name, module) = match path.split_once("::") {
+            Some((name, module)) => (name, Some(module.to_string())),
+            None => (path, None),
+        };
+        let valid_module = module.as_deref().is_none_or(|module| {
+            module.split("::").all(|segment| {
+                segment.starts_with(|c: char| c.is_ascii_alphabetic() || c == '_')
+                    && segment.chars().all(|c| c.is_ascii_alphanumeric() || c == '_')
+            })
+        });
+        let spec = match source {
+            Some(source) => format!("{}={}", name, source),
+            None => name.to_string(),
+        };
+        match spec.parse::<ExternCrate>() {
+            Ok(dependency) if valid_module => Ok(InteropCrate { dependency, module }),
+            _ => Err(format!("互转 crate 配置 '{}' 格式应为 name[::module][=path_or_version]", s)),
+        }
+    }
+}
+
+/// 解析后的互转 crate：`{ident, accounts_path, types_path, dependencies}`
+pub fn resolve_interop(interop: &InteropCrate, ir: &IdlFormatEnum, crate_dir: &Path) -> Result<Value, SoloresError> {
+    let resolved = resolve_crate(&interop.dependency, ir)?;
+    let ident = resolved.ident();
+    let (accounts_path, types_path) = match &interop.module {
+        Some(module) => (format!("{}::{}", ident, module), format!("{}::{}", ident, module)),
+        None => (format!("{}::accounts", ident), format!("{}::types", ident)),
+    };
+    let dependencies = ExternImports { crates: vec![resolved] }.dependencies(crate_dir);
+    Ok(context! {
+        ident => ident,
+        accounts_path => accounts_path,
+        types_path => types_path,
+        dependencies => dependencies,
+    })
+}
+
+/// 不需要转换的标量类型
+const SCALAR_TYPES: &[&str] = &[
+    "bool", "u8", "i8", "u16", "i16", "u32", "i32", "u64", "i64", "u128", "i128", "f32", "f64", "String",
+    "std::string::String",
+];
+
+/// 由 `value` 构造对方类型值的表达式，两个方向相同；不需要转换时返回 `value` 本身
+fn convert_expr(rust_type: &str, value: &str) -> String {
+    let rust_type = rust_type.trim();
+    if rust_type == "solana_pubkey::Pubkey" {
+        return format!("{}.to_bytes().into()", value);
+    }
+    if SCALAR_TYPES.contains(&rust_type) {
+        return value.to_string();
+    }
+    if let Some((element, _)) = split_array(rust_type) {
+        let element = convert_expr(element, "v");
+        return if element == "v" { value.to_string() } else { format!("{}.map(|v| {})", value, element) };
+    }
+    if let Some(inner) = generic_args(rust_type, "std::option::Option") {
+        let inner = convert_expr(&inner[0], "v");
+        return if inner == "v" { value.to_string() } else { format!("{}.map(|v| {})", value, inner) };
+    }
+    if let Some(inner) = generic_args(rust_type, "std::vec::Vec") {
+        let inner = convert_expr(&inner[0], "v");
+        return if inner == "v" { value.to_string() } else { format!("{}.into_iter().map(|v| {}).collect()", value, inner) };
+    }
+    let map = generic_args(rust_type, "std::collections::HashMap")
+        .or_else(|| generic_args(rust_type, "std::collections::BTreeMap"))
+        .filter(|args| args.len() == 2);
+    if let Some(args) = map {
+        let (key, item) = (convert_expr(&args[0], "k"), convert_expr(&args[1], "v"));
+        return if key == "k" && item == "v" {
+            value.to_string()
+        } else {
+            format!("{}.into_iter().map(|(k, v)| ({}, {})).collect()", value, key, item)
+        };
+    }
+    format!("{}.into()", value)
+}
+
+/// 拆分 `[T; N]`
+fn split_array(rust_type: &str) -> Option<(&str, &str)> {
+    let inner = rust_type.strip_prefix('[')?.strip_suffix(']')?;
+    let (element, size) = inner.rsplit_once(';')?;
+    Some((element.trim(), size.trim()))
+}
+
+/// `container<A, B>` 的顶层泛型参数
+fn generic_args(rust_type: &str, container: &str) -> Option<Vec<String>> {
+    let inner = rust_type.strip_prefix(container)?.strip_prefix('<')?.strip_suffix('>')?;
+    let mut args = Vec::new();
+    let (mut depth, mut start) = (0usize, 0usize);
+    for (index, c) in inner.char_indices() {
+        match c {
+            '<' | '[' | '(' => depth += 1,
+            '>' | ']' | ')' => depth = depth.saturating_sub(1),
+            ',' if depth == 0 => {
+                args.push(inner[start..index].trim().to_string());
+                start = index + 1;
+            }
+            _ => {}
+        }
+    }
+    args.push(inner[start..].trim().to_string());
+    Some(args)
+}
+
+/// 结构体字段的转换：`{member, expr}`，`member` 为字段名或元组位置
+fn struct_fields(fields: &[Value], tuple: bool) -> Vec<Value> {
+    fields
+        .iter()
+        .filter(|field| attr_str(field, "name") != "discriminator")
+        .enumerate()
+        .map(|(index, field)| {
+            let member = if tuple { index.to_string() } else { rust_field_filter(attr_str(field, "name")) };
+            let rust_type = type_path_filter(attr_str(field, "rust_type"));
+            context! { member => member, expr => convert_expr(&rust_type, &format!("value.{}", member)) }
+        })
+        .collect()
+}
+
+/// 枚举变体的转换：`{name, bindings, fields}`，字段以 `f{n}` 绑定
+fn enum_variants(variants: &[Value]) -> Vec<Value> {
+    variants
+        .iter()
+        .map(|variant| {
+            let fields: Vec<Value> = context_items(variant, "fields")
+                .iter()
+                .enumerate()
+                .map(|(index, field)| {
+                    let binding = format!("f{}", index);
+                    let rust_type = type_path_filter(attr_str(field, "rust_type"));
+                    context! {
+                        member => attr_str(field, "name"),
+                        binding => binding.clone(),
+                        expr => convert_expr(&rust_type, &binding),
+                    }
+                })
+                .collect();
+            context! { name => attr_str(variant, "name"), fields => fields }
+        })
+        .collect()
+}
+
+/// 账户与自定义类型的转换条目：`{name, path, other_path, kind, shape, fields, variants, discriminator}`
+fn conversions(context: &Value, interop: &Value) -> Vec<Value> {
+    let accounts_path = attr_str(interop, "accounts_path");
+    let types_path = attr_str(interop, "types_path");
+    let accounts = context_items(context, "accounts").into_iter().map(|account| {
+        let name = attr_str(&account, "name");
+        context! {
+            path => format!("crate::accounts::{}", name),
+            other_path => format!("{}::{}", accounts_path, name),
+            kind => "struct",
+            shape => "named",
+            fields => struct_fields(&context_items(&account, "fields"), false),
+            discriminator => format!("crate::accounts::{}_ACCOUNT_DISCM", to_snake_case_filter(name.clone()).to_uppercase()),
+            name => name,
+        }
+    });
+    let types = context_items(context, "types").into_iter().filter_map(|type_def| {
+        let name = attr_str(&type_def, "name");
+        let kind = attr_str(&type_def, "kind");
+        let shape = attr_str(&type_def, "shape");
+        let (fields, variants) = match kind.as_str() {
+            "struct" => (struct_fields(&context_items(&type_def, "fields"), shape == "tuple"), Vec::new()),
+            "enum" => (Vec::new(), enum_variants(&context_items(&type_def, "variants"))),
+            // 别名与无法解析的类型没有对应的结构
+            _ => return None,
+        };
+        Some(context! {
+            path => format!("crate::types::{}", name),
+            other_path => format!("{}::{}", types_path, name),
+            kind => kind,
+            shape => shape,
+            fields => fields,
+            variants => variants,
+            name => name,
+        })
+    });
+    accounts.chain(types).collect()
+}
+
+/// 生成 interop.rs
+pub fn generate_interop_single_file(
+    env: &mut Environment,
+    src_dir: &Path,
+    context: &Value,
+) -> std::result::Result<(), SoloresError> {
+    let interop = context.get_attr("interop").unwrap_or_default();
+    let conversions = conversions(context, &interop);
+
+    let template_name = "common/interop.rs.jinja";
+    let tmpl = env.template_from_str(include_str!("../templates/common/interop.rs.jinja"))
+        .map_err(|e| SoloresError::TemplateError {
+            template_name: Some(template_name.to_string()),
+            message: format!("模板解析失败: {}", e),
+            context: Some("解析interop模板".to_string()),
+        })?;
+    let rendered = tmpl.render(context! { conversions => conversions, ..context.clone() })
+        .map_err(|e| SoloresError::TemplateError {
+            template_name: Some(template_name.to_string()),
+            message: format!("模板渲染失败: {}", e),
+            context: Some("渲染interop模板".to_string()),
+        })?;
+    let output_path = src_dir.join("interop.rs");
+    fs::write(&output_path, rendered)
+        .map_err(|e| SoloresError::file_operation_error("write interop file", output_path.display().to_string(), e))?;
+    Ok(())
+}
+
+fn context_items(value: &Value, key: &str) -> Vec<Value> {
+    value
+        .get_attr(key)
+        .ok()
+        .and_then(|items| items.try_iter().ok().map(|items| items.collect()))
+        .unwrap_or_default()
+}
+
+fn attr_str(value: &Value, name: &str) -> String {
+    value
+        .get_attr(name)
+        .ok()
+        .and_then(|attr| attr.as_str().map(str::to_string))
+        .unwrap_or_default()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn field_conversions_follow_the_type_structure() {
+        assert_eq!(convert_expr("u64", "value.amount"), "value.amount");
+        assert_eq!(convert_expr("[u8; 32]", "value.seed"), "value.seed");
+        assert_eq!(convert_expr("solana_pubkey::Pubkey", "value.mint"), "value.mint.to_bytes().into()");
+        assert_eq!(convert_expr("[crate::types::RewardInfo; 3]", "value.rewards"), "value.rewards.map(|v| v.into())");
+        assert_eq!(
+            convert_expr("std::option::Option<std::vec::Vec<solana_pubkey::Pubkey>>", "value.keys"),
+            "value.keys.map(|v| v.into_iter().map(|v| v.to_bytes().into()).collect())"
+        );
+        assert_eq!(
+            convert_expr("std::collections::HashMap<solana_pubkey::Pubkey, u64>", "value.balances"),
+            "value.balances.into_iter().map(|(k, v)| (k.to_bytes().into(), v)).collect()"
+        );
+
+        let spec: InteropCrate = "raydium_amm_v3::states=../raydium-clmm/programs/amm".parse().unwrap();
+        assert_eq!(spec.dependency.name, "raydium_amm_v3");
+        assert_eq!(spec.module.as_deref(), Some("states"));
+        assert!("raydium::3states".parse::<InteropCrate>().is_err());
+    }
+}
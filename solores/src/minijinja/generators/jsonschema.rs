@@ -0,0 +1,447 @@
+//! JSON Schema 生成器
+//!
+//! `--gen-jsonschema` 时为账户、事件、指令参数（IxData）与自定义类型派生 `schemars::JsonSchema`
+//! （`jsonschema` feature，依赖 `serde`），生成 jsonschema.rs（`openapi_components()`），并在 crate
+//! 根目录生成 schemas/：每个账户 / 事件 / 指令参数一个独立的 JSON Schema 文件（引用的类型放在
+//! `$defs` 中），以及包含全部定义的 OpenAPI components（openapi.json），供发布解码数据的 REST
+//! 服务直接引用。
+//!
+//! 两者描述同一 serde 表示：经 `DisplayFromStr` 序列化的 Pubkey 为 base58 字符串，其余 Pubkey 为
+//! 32 字节数组；超过 32 元素的数组由 `crate::jsonschema::FixedArray` 描述；映射的键为字符串；
+//! 本 crate 与标准库以外的类型（`--extern`）不限制结构。
+
+use crate::error::SoloresError;
+use crate::minijinja::filters::{rust_field_filter, serde_rename_filter};
+use crate::minijinja::type_mapper::split_generic;
+use crate::minijinja::utils::{split_array_type, SERDE_ARRAY_LIMIT};
+use convert_case::{Case, Casing};
+use minijinja::{context, Environment, Value};
+use serde_json::json;
+use std::collections::BTreeMap;
+use std::fs;
+use std::path::Path;
+
+const PUBKEY: &str = "solana_pubkey::Pubkey";
+const STRING: &str = "std::string::String";
+const OPTION: &str = "std::option::Option";
+const VEC: &str = "std::vec::Vec";
+const MAP: &str = "std::collections::HashMap";
+const FIXED_ARRAY: &str = "crate::jsonschema::FixedArray";
+const ANY: &str = "serde_json::Value";
+
+/// schemars 直接实现 `JsonSchema` 的标量类型
+const SCALAR_TYPES: &[&str] = &[
+    "bool", "u8", "i8", "u16", "i16", "u32", "i32", "u64", "i64", "u128", "i128", "f32", "f64", STRING,
+];
+
+/// 字段的 `schemars(with = ...)` 类型，字段类型本身即可派生 `JsonSchema` 时返回 None
+///
+/// 顶层 Pubkey 经 `DisplayFromStr` 序列化为字符串；`string_pubkey_containers` 对应模板同样以字符串
+/// 序列化 `Option<Pubkey>` / `Vec<Pubkey>` 的位置。映射字段总带 serde 适配器，总是返回显式类型
+pub fn schemars_with(rust_type: &str, string_pubkey_containers: bool) -> Option<String> {
+    let rust_type = rust_type.trim();
+    let generic = split_generic(rust_type);
+    let rewritten = match generic.as_ref().map(|(base, args)| (*base, args.as_slice())) {
+        _ if rust_type == PUBKEY => STRING.to_string(),
+        Some((container @ (OPTION | VEC), [PUBKEY])) if string_pubkey_containers => format!("{}<{}>", container, STRING),
+        _ => schema_type(rust_type),
+    };
+    let is_map = matches!(generic, Some((MAP, _)));
+    (rewritten != rust_type || is_map).then_some(rewritten)
+}
+
+/// 字段的 `schemars(with = ...)` 类型：替换了类型的字段（`--field-type`）按 IDL 原类型的 serde 表示描述
+pub fn field_schemars_with(rust_type: &str, idl_rust_type: Option<&str>, string_pubkey_containers: bool) -> Option<String> {
+    match idl_rust_type {
+        Some(idl_rust_type) => {
+            Some(schemars_with(idl_rust_type, string_pubkey_containers).unwrap_or_else(|| idl_rust_type.to_string()))
+        }
+        None => schemars_with(rust_type, string_pubkey_containers),
+    }
+}
+
+/// 嵌套位置的 `JsonSchema` 类型：Pubkey 按 32 字节数组，大数组改为 FixedArray，映射键为字符串
+fn schema_type(rust_type: &str) -> String {
+    let rust_type = rust_type.trim();
+    if rust_type == PUBKEY {
+        return "[u8; 32]".to_string();
+    }
+    if let Some((element, size)) = split_array_type(rust_type) {
+        let element = schema_type(element);
+        return if size > SERDE_ARRAY_LIMIT {
+            format!("{}<{}, {}>", FIXED_ARRAY, element, size)
+        } else {
+            format!("[{}; {}]", element, size)
+        };
+    }
+    match split_generic(rust_type).as_ref().map(|(base, args)| (*base, args.as_slice())) {
+        Some((container @ (OPTION | VEC), [inner])) => format!("{}<{}>", container, schema_type(inner)),
+        Some((MAP, [_, value])) => format!("{}<{}, {}>", MAP, STRING, schema_type(value)),
+        _ if SCALAR_TYPES.contains(&rust_type) || rust_type.starts_with("crate::") => rust_type.to_string(),
+        _ => ANY.to_string(),
+    }
+}
+
+/// `schema_type` 改写后类型的 JSON Schema，已定义类型引用 `refs` 前缀下的同名定义
+fn type_schema(rust_type: &str, refs: &str) -> serde_json::Value {
+    let rust_type = rust_type.trim();
+    let array = |element: &str, size: usize| {
+        json!({ "type": "array", "items": type_schema(element, refs), "minItems": size, "maxItems": size })
+    };
+    if let Some((element, size)) = split_array_type(rust_type) {
+        return array(element, size);
+    }
+    match split_generic(rust_type).as_ref().map(|(base, args)| (*base, args.as_slice())) {
+        Some((FIXED_ARRAY, [element, size])) => return array(element, size.parse().unwrap_or_default()),
+        Some((OPTION, [inner])) => return nullable(type_schema(inner, refs)),
+        Some((VEC, [inner])) => return json!({ "type": "array", "items": type_schema(inner, refs) }),
+        Some((MAP, [_, value])) => return json!({ "type": "object", "additionalProperties": type_schema(value, refs) }),
+        _ => {}
+    }
+    match rust_type {
+        "bool" => json!({ "type": "boolean" }),
+        "u8" | "u16" => {
+            let bits: u32 = rust_type[1..].parse().unwrap_or_default();
+            json!({ "type": "integer", "format": format!("uint{}", bits), "minimum": 0, "maximum": (1u64 << bits) - 1 })
+        }
+        "i8" | "i16" => {
+            let bits: u32 = rust_type[1..].parse().unwrap_or_default();
+            let max = (1i64 << (bits - 1)) - 1;
+            json!({ "type": "integer", "format": format!("int{}", bits), "minimum": -max - 1, "maximum": max })
+        }
+        "u32" | "u64" | "u128" => json!({ "type": "integer", "format": format!("uint{}", &rust_type[1..]), "minimum": 0 }),
+        "i32" | "i64" | "i128" => json!({ "type": "integer", "format": format!("int{}", &rust_type[1..]) }),
+        "f32" => json!({ "type": "number", "format": "float" }),
+        "f64" => json!({ "type": "number", "format": "double" }),
+        STRING => json!({ "type": "string" }),
+        _ => match rust_type.strip_prefix("crate::types::") {
+            Some(name) => json!({ "$ref": format!("{}{}", refs, name) }),
+            None => json!({}),
+        },
+    }
+}
+
+/// `Option<T>` 的 Schema：与 schemars 相同，单一类型的 Schema 在 `type` 中加入 null，其余包裹为 anyOf
+fn nullable(mut schema: serde_json::Value) -> serde_json::Value {
+    match schema.get("type").and_then(|schema_type| schema_type.as_str()).map(str::to_string) {
+        Some(schema_type) => {
+            schema["type"] = json!([schema_type, "null"]);
+            schema
+        }
+        None => json!({ "anyOf": [schema, { "type": "null" }] }),
+    }
+}
+
+/// 字段的 JSON Schema：与派生宏相同，按 `schemars_with` 改写后的类型描述
+fn field_schema(field: &Value, string_pubkey_containers: bool, refs: &str) -> serde_json::Value {
+    let rust_type = attr_str(field, "rust_type");
+    let idl_rust_type = Some(attr_str(field, "idl_rust_type")).filter(|idl_rust_type| !idl_rust_type.is_empty());
+    let rust_type = field_schemars_with(&rust_type, idl_rust_type.as_deref(), string_pubkey_containers).unwrap_or(rust_type);
+    let mut schema = type_schema(&rust_type, refs);
+    let docs = attr_str(field, "docs");
+    if !docs.is_empty() {
+        schema["description"] = json!(docs);
+    }
+    schema
+}
+
+/// 命名字段结构体的 Schema；Option 字段缺省时反序列化为 None，不列入 `required`
+fn object_schema(
+    fields: &[Value],
+    discriminator: Option<&str>,
+    string_pubkey_containers: bool,
+    serde_rename: &str,
+    refs: &str,
+    rust_field: impl Fn(&str) -> String,
+) -> serde_json::Value {
+    let mut properties = serde_json::Map::new();
+    let mut required = Vec::new();
+    if let Some(discriminator) = discriminator {
+        properties.insert("discriminator".to_string(), type_schema(discriminator, refs));
+        required.push("discriminator".to_string());
+    }
+    for field in fields {
+        let member = rust_field(&attr_str(field, "name"));
+        let idl_name = field.get_attr("idl_name").ok().and_then(|name| name.as_str().map(str::to_string));
+        let name = serde_rename_filter(idl_name, member.clone(), Some(serde_rename.to_string()))
+            .unwrap_or_else(|| member.trim_start_matches("r#").to_string());
+        if !attr_str(field, "rust_type").starts_with(OPTION) {
+            required.push(name.clone());
+        }
+        properties.insert(name, field_schema(field, string_pubkey_containers, refs));
+    }
+    let mut schema = json!({ "type": "object", "properties": properties });
+    if !required.is_empty() {
+        schema["required"] = json!(required);
+    }
+    schema
+}
+
+/// 自定义类型的 Schema；别名与无法解析的类型没有对应的 Rust 定义，返回 None
+fn type_def_schema(type_def: &Value, is_anchor: bool, serde_rename: &str, refs: &str) -> Option<serde_json::Value> {
+    let rust_field = |name: &str| rust_field_filter(name.to_string());
+    match attr_str(type_def, "kind").as_str() {
+        "struct" => {
+            let fields = context_items(type_def, "fields");
+            // 非 Anchor 模板对结构体的 Option / Vec<Pubkey> 字段同样按字符串序列化
+            let string_pubkey_containers = !is_anchor;
+            Some(match attr_str(type_def, "shape").as_str() {
+                "unit" => json!({ "type": "null" }),
+                "tuple" if fields.len() == 1 => field_schema(&fields[0], string_pubkey_containers, refs),
+                "tuple" => {
+                    let items: Vec<serde_json::Value> =
+                        fields.iter().map(|field| field_schema(field, string_pubkey_containers, refs)).collect();
+                    json!({ "type": "array", "prefixItems": items, "minItems": fields.len(), "maxItems": fields.len() })
+                }
+                _ => object_schema(&fields, None, string_pubkey_containers, serde_rename, refs, rust_field),
+            })
+        }
+        "enum" => {
+            let variants = context_items(type_def, "variants");
+            let units: Vec<String> = variants
+                .iter()
+                .filter(|variant| context_items(variant, "fields").is_empty())
+                .map(|variant| attr_str(variant, "name"))
+                .collect();
+            let mut one_of = Vec::new();
+            if !units.is_empty() {
+                one_of.push(json!({ "type": "string", "enum": units }));
+            }
+            for variant in &variants {
+                let fields = context_items(variant, "fields");
+                if fields.is_empty() {
+                    continue;
+                }
+                let name = attr_str(variant, "name");
+                // 变体字段按 IDL 名称直接生成，不经 rust_field 转换
+                let payload = object_schema(&fields, None, false, serde_rename, refs, str::to_string);
+                one_of.push(json!({
+                    "type": "object",
+                    "properties": { name.clone(): payload },
+                    "required": [name],
+                    "additionalProperties": false,
+                }));
+            }
+            Some(if one_of.len() == 1 { one_of.remove(0) } else { json!({ "oneOf": one_of }) })
+        }
+        _ => None,
+    }
+}
+
+/// 附带条目文档的 Schema
+fn with_docs(mut schema: serde_json::Value, item: &Value) -> serde_json::Value {
+    let docs = attr_str(item, "docs");
+    if !docs.is_empty() {
+        schema["description"] = json!(docs);
+    }
+    schema
+}
+
+/// schemas/ 的全部定义：`(分类, 名称, Schema)`，分类为 accounts / events / instructions / types
+fn definitions(context: &Value, is_anchor: bool, refs: &str) -> Vec<(&'static str, String, serde_json::Value)> {
+    let serde_rename = attr_str(context, "serde_rename");
+    let rust_field = |name: &str| rust_field_filter(name.to_string());
+    let mut definitions = Vec::new();
+    for account in context_items(context, "accounts") {
+        let discriminator = is_anchor.then_some("[u8; 8]");
+        let schema = object_schema(&context_items(&account, "fields"), discriminator, true, &serde_rename, refs, rust_field);
+        definitions.push(("accounts", attr_str(&account, "name"), with_docs(schema, &account)));
+    }
+    for event in context_items(context, "events") {
+        let discriminator = is_anchor.then_some("[u8; 8]");
+        let schema = object_schema(&context_items(&event, "fields"), discriminator, !is_anchor, &serde_rename, refs, rust_field);
+        definitions.push(("events", attr_str(&event, "name"), with_docs(schema, &event)));
+    }
+    for instruction in context_items(context, "instructions") {
+        let discriminator = if is_anchor {
+            Some("[u8; 8]")
+        } else {
+            instruction.get_attr("has_discriminator").is_ok_and(|value| value.is_true()).then_some("u8")
+        };
+        let schema = object_schema(&context_items(&instruction, "fields"), discriminator, true, &serde_rename, refs, rust_field);
+        definitions.push(("instructions", format!("{}IxData", attr_str(&instruction, "name")), with_docs(schema, &instruction)));
+    }
+    for type_def in context_items(context, "types") {
+        if let Some(schema) = type_def_schema(&type_def, is_anchor, &serde_rename, refs) {
+            definitions.push(("types", attr_str(&type_def, "name"), with_docs(schema, &type_def)));
+        }
+    }
+    definitions
+}
+
+/// Schema 中引用的类型名
+fn collect_refs(schema: &serde_json::Value, refs: &str, names: &mut Vec<String>) {
+    match schema {
+        serde_json::Value::Object(map) => {
+            if let Some(name) = map.get("$ref").and_then(|target| target.as_str()).and_then(|target| target.strip_prefix(refs)) {
+                names.push(name.to_string());
+            }
+            map.values().for_each(|value| collect_refs(value, refs, names));
+        }
+        serde_json::Value::Array(items) => items.iter().for_each(|value| collect_refs(value, refs, names)),
+        _ => {}
+    }
+}
+
+/// 独立的 JSON Schema 文档：`schema` 引用的类型（传递闭包）放入 `$defs`
+fn standalone_schema(name: &str, schema: &serde_json::Value, types: &BTreeMap<String, serde_json::Value>) -> serde_json::Value {
+    let mut defs = serde_json::Map::new();
+    let mut pending = Vec::new();
+    collect_refs(schema, "#/$defs/", &mut pending);
+    while let Some(type_name) = pending.pop() {
+        if defs.contains_key(&type_name) {
+            continue;
+        }
+        if let Some(type_schema) = types.get(&type_name) {
+            collect_refs(type_schema, "#/$defs/", &mut pending);
+            defs.insert(type_name, type_schema.clone());
+        }
+    }
+    let mut document = json!({ "$schema": "https://json-schema.org/draft/2020-12/schema", "title": name });
+    if let (Some(document), Some(schema)) = (document.as_object_mut(), schema.as_object()) {
+        document.extend(schema.clone());
+        if !defs.is_empty() {
+            document.insert("$defs".to_string(), serde_json::Value::Object(defs));
+        }
+    }
+    document
+}
+
+/// OpenAPI 3.1 文档，只包含 components（其 Schema 即 JSON Schema 2020-12）
+fn openapi_document(context: &Value, definitions: Vec<(&'static str, String, serde_json::Value)>) -> serde_json::Value {
+    let mut schemas = serde_json::Map::new();
+    for (category, name, schema) in definitions {
+        // 账户 / 事件与自定义类型同名时以分类区分
+        let key = if schemas.contains_key(&name) { format!("{}{}", name, category.trim_end_matches('s').to_case(Case::Pascal)) } else { name };
+        schemas.insert(key, schema);
+    }
+    let package = context.get_attr("package").unwrap_or_default();
+    let version = package.get_attr("version").ok().and_then(|version| version.as_str().map(str::to_string));
+    json!({
+        "openapi": "3.1.0",
+        "info": {
+            "title": attr_str(context, "program_name"),
+            "version": version.unwrap_or_else(|| "0.2.0".to_string()),
+        },
+        "paths": {},
+        "components": { "schemas": schemas },
+    })
+}
+
+/// 生成 jsonschema.rs
+pub fn generate_jsonschema_single_file(
+    env: &mut Environment,
+    src_dir: &Path,
+    context: &Value,
+) -> std::result::Result<(), SoloresError> {
+    let template_name = "common/jsonschema.rs.jinja";
+    let tmpl = env.template_from_str(include_str!("../templates/common/jsonschema.rs.jinja"))
+        .map_err(|e| SoloresError::TemplateError {
+            template_name: Some(template_name.to_string()),
+            message: format!("模板解析失败: {}", e),
+            context: Some("解析jsonschema模板".to_string()),
+        })?;
+    let rendered = tmpl.render(context! { components => component_paths(context), ..context.clone() })
+        .map_err(|e| SoloresError::TemplateError {
+            template_name: Some(template_name.to_string()),
+            message: format!("模板渲染失败: {}", e),
+            context: Some("渲染jsonschema模板".to_string()),
+        })?;
+    let output_path = src_dir.join("jsonschema.rs");
+    fs::write(&output_path, rendered)
+        .map_err(|e| SoloresError::file_operation_error("write jsonschema file", output_path.display().to_string(), e))?;
+    Ok(())
+}
+
+/// 生成 schemas/：每个账户 / 事件 / 指令参数一个 JSON Schema 文件与 openapi.json
+pub fn generate_schema_files(output_dir: &Path, context: &Value, is_anchor: bool) -> std::result::Result<(), SoloresError> {
+    let schemas_dir = output_dir.join("schemas");
+    let (types, items): (Vec<_>, Vec<_>) =
+        definitions(context, is_anchor, "#/$defs/").into_iter().partition(|(category, _, _)| *category == "types");
+    let types: BTreeMap<String, serde_json::Value> = types.into_iter().map(|(_, name, schema)| (name, schema)).collect();
+    let mut files: Vec<_> = items
+        .into_iter()
+        .map(|(category, name, schema)| {
+            (schemas_dir.join(category).join(format!("{}.json", name)), standalone_schema(&name, &schema, &types))
+        })
+        .collect();
+    let openapi = openapi_document(context, definitions(context, is_anchor, "#/components/schemas/"));
+    files.push((schemas_dir.join("openapi.json"), openapi));
+
+    for (path, document) in files {
+        if let Some(parent) = path.parent() {
+            fs::create_dir_all(parent)
+                .map_err(|e| SoloresError::file_operation_error("create schemas directory", parent.display().to_string(), e))?;
+        }
+        let json = serde_json::to_string_pretty(&document).map_err(|e| SoloresError::TemplateError {
+            template_name: None,
+            message: format!("JSON Schema序列化失败: {}", e),
+            context: Some("生成schemas".to_string()),
+        })?;
+        fs::write(&path, json + "\n")
+            .map_err(|e| SoloresError::file_operation_error("write JSON Schema", path.display().to_string(), e))?;
+    }
+    Ok(())
+}
+
+/// jsonschema.rs 中注册的类型路径
+fn component_paths(context: &Value) -> Vec<String> {
+    let names = |key: &str, module: &str, suffix: &str| -> Vec<String> {
+        context_items(context, key)
+            .iter()
+            .filter(|item| key != "types" || matches!(attr_str(item, "kind").as_str(), "struct" | "enum"))
+            .map(|item| format!("crate::{}::{}{}", module, attr_str(item, "name"), suffix))
+            .collect()
+    };
+    [
+        names("accounts", "accounts", ""),
+        names("events", "events", ""),
+        names("instructions", "instructions", "IxData"),
+        names("types", "types", ""),
+    ]
+    .concat()
+}
+
+fn context_items(value: &Value, key: &str) -> Vec<Value> {
+    value
+        .get_attr(key)
+        .ok()
+        .and_then(|items| items.try_iter().ok().map(|items| items.collect()))
+        .unwrap_or_default()
+}
+
+fn attr_str(value: &Value, name: &str) -> String {
+    value
+        .get_attr(name)
+        .ok()
+        .and_then(|attr| attr.as_str().map(str::to_string))
+        .unwrap_or_default()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn pubkeys_and_large_arrays_are_rewritten_to_their_serde_representation() {
+        assert_eq!(schemars_with("solana_pubkey::Pubkey", false).as_deref(), Some(STRING));
+        assert_eq!(
+            schemars_with("std::option::Option<solana_pubkey::Pubkey>", true).as_deref(),
+            Some("std::option::Option<std::string::String>")
+        );
+        assert_eq!(
+            schemars_with("std::vec::Vec<solana_pubkey::Pubkey>", false).as_deref(),
+            Some("std::vec::Vec<[u8; 32]>")
+        );
+        assert_eq!(schemars_with("[u8; 64]", false).as_deref(), Some("crate::jsonschema::FixedArray<u8, 64>"));
+        assert_eq!(schemars_with("u64", false), None);
+        assert_eq!(schemars_with("crate::types::Fees", false), None);
+
+        let schema = type_schema("std::option::Option<crate::types::Fees>", "#/$defs/");
+        assert_eq!(schema, json!({ "anyOf": [{ "$ref": "#/$defs/Fees" }, { "type": "null" }] }));
+        assert_eq!(
+            type_schema("std::option::Option<u32>", "#/$defs/"),
+            json!({ "type": ["integer", "null"], "format": "uint32", "minimum": 0 })
+        );
+    }
+}
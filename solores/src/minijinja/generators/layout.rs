@@ -0,0 +1,128 @@
+//! 布局断言辅助模块生成器
+//! 
+//! 生成 layout.rs，为 layout-checks feature 提供编译期紧凑布局计算
+
+use crate::error::SoloresError;
+use minijinja::{Environment, Value};
+use std::fs;
+use std::path::Path;
+
+/// 生成布局断言辅助模块
+pub fn generate_layout_single_file(
+    env: &mut Environment,
+    src_dir: &Path,
+    context: &Value,
+) -> std::result::Result<(), SoloresError> {
+    let template_content = include_str!("../templates/common/layout.rs.jinja");
+    
+    let tmpl = env.template_from_str(template_content)
+        .map_err(|e| SoloresError::TemplateError {
+            template_name: Some("common/layout.rs.jinja".to_string()),
+            message: format!("模板解析失败: {}", e),
+            context: Some("解析layout模板".to_string()),
+        })?;
+    
+    let rendered = tmpl.render(context)
+        .map_err(|e| SoloresError::TemplateError {
+            template_name: Some("common/layout.rs.jinja".to_string()),
+            message: format!("模板渲染失败: {}", e),
+            context: Some("渲染layout模板".to_string()),
+        })?;
+    
+    let output_path = src_dir.join("layout.rs");
+    fs::write(&output_path, rendered)
+        .map_err(|e| SoloresError::FileOperationError {
+            operation: "write layout file".to_string(),
+            path: output_path.display().to_string(),
+            current_dir: std::env::current_dir().ok().map(|p| p.display().to_string()),
+            resolved_path: None,
+            source: e,
+            suggestion: Some("检查文件权限".to_string()),
+        })?;
+    
+    Ok(())
+}
+
+#[cfg(test)]
+mod tests {
+    use crate::test_support::run_decode_test;
+
+    #[test]
+    fn layout_checks_compare_field_types_with_idl_offsets() {
+        let idl = r#"{"name": "ledger", "version": "0.1.0",
+            "metadata": {"name": "ledger", "version": "0.1.0", "spec": "0.1.0", "origin": "shank", "address": "Ledger1111111111111111111111111111111111111"},
+            "instructions": [],
+            "accounts": [{"name": "Position", "type": {"kind": "struct", "fields": [
+                {"name": "bump", "type": "u8"}, {"name": "owner", "type": "publicKey"},
+                {"name": "amount", "type": "u64"}, {"name": "flags", "type": {"array": ["u8", 3]}}]}}]}"#;
+        let test_source = r#"
+use sol_ledger_interface::{accounts::Position, layout::PackedLayout};
+
+#[test]
+fn packed_layout_matches_field_offsets() {
+    let (layout, bump) = PackedLayout::new().field::<u8>();
+    let (layout, owner) = layout.field::<solana_pubkey::Pubkey>();
+    let (layout, amount) = layout.field::<u64>();
+    let (layout, flags) = layout.field::<[u8; 3]>();
+    let offsets: Vec<usize> = Position::field_offsets().iter().map(|(_, offset, _)| *offset).collect();
+    assert_eq!(offsets, [bump, owner, amount, flags]);
+    assert_eq!((bump, owner, amount, flags), (0, 1, 33, 41));
+    assert_eq!(Position::PACKED_LEN, layout.packed_size());
+}
+"#;
+        run_decode_test("layout", idl, &["--min-rust-version", "1.79"], &["layout-checks"], test_source);
+    }
+
+    /// 按 `field_offsets()` 从序列化字节中切出每个字段，与反序列化后的字段值编码比较
+    const OFFSETS_TEST: &str = r#"
+use sol_CRATE_interface::accounts::Position;
+use solana_pubkey::Pubkey;
+
+#[test]
+fn field_offsets_slice_the_serialized_fields() {
+    let position = Position { bump: 7, owner: Pubkey::new_unique(), amount: 1_234_567, flags: [1, 2, 3], label: "tail".to_string(), ..Position::default() };
+    let data = position.try_to_vec().unwrap();
+    let decoded = Position::from_bytes(&data).unwrap();
+
+    let mut names = Vec::new();
+    for &(name, offset, size) in Position::field_offsets() {
+        let expected = match name {
+            "discriminator" => Position::discriminator().to_vec(),
+            "bump" => borsh::to_vec(&decoded.bump).unwrap(),
+            "owner" => borsh::to_vec(&decoded.owner).unwrap(),
+            "amount" => borsh::to_vec(&decoded.amount).unwrap(),
+            "flags" => borsh::to_vec(&decoded.flags).unwrap(),
+            other => panic!("unexpected field {}", other),
+        };
+        assert_eq!(&data[offset..offset + size], &expected[..], "{}", name);
+        names.push(name);
+    }
+    // label 是变长字段，之后的偏移依赖数据，不在表中
+    assert!(names.ends_with(&["bump", "owner", "amount", "flags"]));
+}
+"#;
+
+    #[test]
+    fn field_offsets_match_serialized_accounts() {
+        let anchor_idl = r#"{"address": "Poo1111111111111111111111111111111111111111", "metadata": {"name": "pool", "version": "0.1.0", "spec": "0.1.0"},
+            "instructions": [],
+            "accounts": [{"name": "Position", "discriminator": [9, 9, 9, 9, 9, 9, 9, 9]}],
+            "types": [{"name": "Position", "type": {"kind": "struct", "fields": [
+                {"name": "bump", "type": "u8"}, {"name": "owner", "type": "pubkey"}, {"name": "amount", "type": "u64"},
+                {"name": "flags", "type": {"array": ["u8", 3]}}, {"name": "label", "type": "string"}]}}]}"#;
+        let anchor_source = OFFSETS_TEST.replace("CRATE", "pool");
+        run_decode_test("offsets_anchor", anchor_idl, &[], &[], &anchor_source);
+
+        let shank_idl = r#"{"name": "ledger", "version": "0.1.0",
+            "metadata": {"name": "ledger", "version": "0.1.0", "spec": "0.1.0", "origin": "shank", "address": "Ledger1111111111111111111111111111111111111"},
+            "instructions": [],
+            "accounts": [{"name": "Position", "type": {"kind": "struct", "fields": [
+                {"name": "bump", "type": "u8"}, {"name": "owner", "type": "publicKey"}, {"name": "amount", "type": "u64"},
+                {"name": "flags", "type": {"array": ["u8", 3]}}, {"name": "label", "type": "string"}]}}]}"#;
+        // 非 Anchor 账户没有 discriminator
+        let shank_source = OFFSETS_TEST
+            .replace("CRATE", "ledger")
+            .replace("            \"discriminator\" => Position::discriminator().to_vec(),\n", "");
+        run_decode_test("offsets_non_anchor", shank_idl, &[], &[], &shank_source);
+    }
+}
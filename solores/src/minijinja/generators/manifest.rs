@@ -0,0 +1,246 @@
+//! 生成物清单生成器
+//!
+//! 生成 manifest.json，列出生成 crate 中的全部条目（模块路径、类型名、种类、
+//! discriminator 与来源 IDL 条目），供文档站点、API 网关等下游生成器直接读取，
+//! 无需解析 Rust 源码。
+
+use crate::error::SoloresError;
+use crate::idl_format::IdlFormatEnum;
+use convert_case::{Case, Casing};
+use minijinja::Value;
+use serde_json::json;
+use std::collections::HashMap;
+use std::fs;
+use std::path::Path;
+
+use crate::idl_format::identifiers::ModuleNames;
+use crate::minijinja::filters::{rust_field_filter, type_path_filter};
+
+/// manifest.json 的结构版本，字段含义变化时递增
+pub const MANIFEST_SCHEMA_VERSION: u32 = 1;
+
+/// 生成 manifest.json
+///
+/// `source_idl` 为应用命名前后缀之前的 IR，与 `idl_enum` 条目一一对应
+pub fn generate_manifest_file(
+    idl_enum: &IdlFormatEnum,
+    source_idl: &IdlFormatEnum,
+    output_dir: &Path,
+    context: &Value,
+) -> std::result::Result<(), SoloresError> {
+    let manifest = build_manifest(idl_enum, source_idl, context);
+    let rendered = serde_json::to_string_pretty(&manifest).map_err(|e| SoloresError::TemplateError {
+        template_name: None,
+        message: format!("manifest序列化失败: {}", e),
+        context: Some("生成manifest.json".to_string()),
+    })?;
+
+    let output_path = output_dir.join("manifest.json");
+    fs::write(&output_path, rendered + "\n")
+        .map_err(|e| SoloresError::FileOperationError {
+            operation: "write manifest file".to_string(),
+            path: output_path.display().to_string(),
+            current_dir: std::env::current_dir().ok().map(|p| p.display().to_string()),
+            resolved_path: None,
+            source: e,
+            suggestion: Some("检查文件权限".to_string()),
+        })?;
+
+    Ok(())
+}
+
+/// 由模板上下文构建清单，来源名称取自 IDL 原始条目
+pub fn build_manifest(
+    idl_enum: &IdlFormatEnum,
+    source_idl: &IdlFormatEnum,
+    context: &Value,
+) -> serde_json::Value {
+    let sources: HashMap<_, _> = idl_names(idl_enum)
+        .into_iter()
+        .zip(idl_names(source_idl))
+        .map(|((section, name), (_, idl_name))| ((section, name.to_case(Case::Pascal)), idl_name.to_string()))
+        .collect();
+    let source = |section: &str, name: &str| {
+        let idl_name = sources
+            .get(&(section, name.to_string()))
+            .cloned()
+            .unwrap_or_else(|| name.to_string());
+        json!({ "section": section, "name": idl_name })
+    };
+
+    let mut items = Vec::new();
+    let mut modules = ModuleNames::new("accounts");
+    for account in context_items(context, "accounts") {
+        let name = attr_str(&account, "name");
+        items.push(json!({
+            "kind": "account",
+            "module_path": format!("crate::accounts::{}", modules.allocate(&name).ident),
+            "type_name": name,
+            "discriminator": discriminator(&account),
+            "fields": fields(&account),
+            "source": source("accounts", &name),
+        }));
+    }
+    let mut modules = ModuleNames::new("instructions");
+    for instruction in context_items(context, "instructions") {
+        let name = attr_str(&instruction, "name");
+        let accounts: Vec<String> = context_items(&instruction, "accounts")
+            .iter()
+            .map(|account| attr_str(account, "name").to_case(Case::Snake))
+            .collect();
+        items.push(json!({
+            "kind": "instruction",
+            "module_path": format!("crate::instructions::{}", modules.allocate(&name).ident),
+            "type_name": format!("{}IxData", name),
+            "keys_type_name": format!("{}Keys", name),
+            "discriminator": discriminator(&instruction),
+            "fields": fields(&instruction),
+            "accounts": accounts,
+            "source": source("instructions", &name),
+        }));
+    }
+    let mut modules = ModuleNames::new("events");
+    for event in context_items(context, "events") {
+        let name = attr_str(&event, "name");
+        items.push(json!({
+            "kind": "event",
+            "module_path": format!("crate::events::{}", modules.allocate(&name).ident),
+            "type_name": name,
+            "discriminator": discriminator(&event),
+            "fields": fields(&event),
+            "source": source("events", &name),
+        }));
+    }
+    let mut modules = ModuleNames::new("types");
+    for type_def in context_items(context, "types") {
+        let name = attr_str(&type_def, "name");
+        let variants: Vec<String> = context_items(&type_def, "variants")
+            .iter()
+            .map(|variant| attr_str(variant, "name"))
+            .collect();
+        items.push(json!({
+            "kind": "type",
+            "type_kind": attr_str(&type_def, "kind"),
+            "module_path": format!("crate::types::{}", modules.allocate(&name).ident),
+            "type_name": name,
+            "discriminator": null,
+            "fields": fields(&type_def),
+            "variants": variants,
+            "source": source("types", &name),
+        }));
+    }
+    if context.get_attr("generate_parser").map(|v| v.is_true()).unwrap_or(false) {
+        for (module, type_name) in [
+            ("accounts", "ProgramAccount"),
+            ("instructions", "ProgramInstruction"),
+            ("events", "ProgramEvent"),
+        ] {
+            items.push(json!({
+                "kind": "parser",
+                "module_path": format!("crate::parsers::{}", module),
+                "type_name": type_name,
+                "discriminator": null,
+                "source": null,
+            }));
+        }
+    }
+
+    json!({
+        "schema_version": MANIFEST_SCHEMA_VERSION,
+        "solores_version": env!("CARGO_PKG_VERSION"),
+        "crate_name": attr_str(context, "crate_name"),
+        "program_name": attr_str(context, "program_name"),
+        "program_id": attr_str(context, "program_id"),
+        "idl_format": if matches!(idl_enum, IdlFormatEnum::Anchor(_)) { "anchor" } else { "non_anchor" },
+        "items": items,
+    })
+}
+
+/// 按 IDL 顺序列出 (区段, 名称)，重命名不改变条目顺序
+fn idl_names(idl_enum: &IdlFormatEnum) -> Vec<(&'static str, &str)> {
+    match idl_enum {
+        IdlFormatEnum::Anchor(idl) => idl.accounts.iter().flatten().map(|a| ("accounts", a.name.as_str()))
+            .chain(idl.instructions.iter().flatten().map(|i| ("instructions", i.name.as_str())))
+            .chain(idl.events.iter().flatten().map(|e| ("events", e.name.as_str())))
+            .chain(idl.types.iter().flatten().map(|t| ("types", t.name.as_str())))
+            .collect(),
+        IdlFormatEnum::NonAnchor(idl) => idl.accounts.iter().flatten().map(|a| ("accounts", a.name.as_str()))
+            .chain(idl.instructions.iter().flatten().map(|i| ("instructions", i.name.as_str())))
+            .chain(idl.events.iter().flatten().map(|e| ("events", e.name.as_str())))
+            .chain(idl.types.iter().flatten().map(|t| ("types", t.name.as_str())))
+            .collect(),
+    }
+}
+
+fn context_items(value: &Value, key: &str) -> Vec<Value> {
+    value
+        .get_attr(key)
+        .ok()
+        .and_then(|items| items.try_iter().ok().map(|items| items.collect()))
+        .unwrap_or_default()
+}
+
+fn attr_str(value: &Value, name: &str) -> String {
+    value
+        .get_attr(name)
+        .ok()
+        .and_then(|attr| attr.as_str().map(str::to_string))
+        .unwrap_or_default()
+}
+
+/// 空 discriminator（NonAnchor 账户/事件）输出为 null
+fn discriminator(value: &Value) -> serde_json::Value {
+    let bytes: Vec<u8> = context_items(value, "discriminator")
+        .iter()
+        .filter_map(|byte| u8::try_from(byte.clone()).ok())
+        .collect();
+    if bytes.is_empty() {
+        serde_json::Value::Null
+    } else {
+        json!(bytes)
+    }
+}
+
+fn fields(value: &Value) -> Vec<serde_json::Value> {
+    context_items(value, "fields")
+        .iter()
+        .map(|field| {
+            json!({
+                "name": rust_field_filter(attr_str(field, "name")),
+                "rust_type": type_path_filter(attr_str(field, "rust_type")),
+            })
+        })
+        .collect()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::idl_format::non_anchor_idl::NonAnchorIdl;
+
+    #[test]
+    fn manifest_lists_generated_items() {
+        let idl = NonAnchorIdl::parse_json(&json!({
+            "address": "11111111111111111111111111111111",
+            "metadata": {"name": "vault", "version": "0.1.0", "spec": "0.1.0"},
+            "instructions": [{"name": "deposit", "accounts": [{"name": "owner"}], "args": [{"name": "amount", "type": "u64"}]}],
+            "types": [{"name": "fee_tier", "type": {"kind": "struct", "fields": [{"name": "type", "type": "u16"}]}}]
+        }).to_string())
+        .unwrap();
+        let idl_enum = IdlFormatEnum::NonAnchor(idl);
+        let context = crate::minijinja::context::create_template_context(&idl_enum, "vault", false, true, false, false).unwrap();
+
+        let manifest = build_manifest(&idl_enum, &idl_enum, &context);
+        assert_eq!(manifest["schema_version"], MANIFEST_SCHEMA_VERSION);
+        let items = manifest["items"].as_array().unwrap();
+        assert_eq!(items[0]["module_path"], "crate::instructions::deposit");
+        assert_eq!(items[0]["type_name"], "DepositIxData");
+        // 唯一且未声明 discriminator 的指令，指令数据即参数
+        assert_eq!(items[0]["discriminator"], json!(null));
+        assert_eq!(items[0]["source"], json!({"section": "instructions", "name": "deposit"}));
+        assert_eq!(items[1]["type_name"], "FeeTier");
+        assert_eq!(items[1]["fields"][0]["name"], "r#type");
+        assert_eq!(items[1]["source"]["name"], "fee_tier");
+        assert_eq!(items.iter().filter(|item| item["kind"] == "parser").count(), 3);
+    }
+}
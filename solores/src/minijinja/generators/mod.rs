@@ -8,9 +8,39 @@ pub mod events;
 pub mod types;
 pub mod parsers;
 pub mod errors;
+pub mod coption;
+pub mod small_vec;
+pub mod raw_tail;
+pub mod field_types;
+pub mod accessors;
+pub mod fuzz;
+pub mod layout;
+pub mod proposal;
+pub mod program_test;
+pub mod benches;
+pub mod compile_fail;
+pub mod rpc_fixtures;
+pub mod versioned_tx;
+pub mod manifest;
+pub mod actions;
+pub mod anchor_cpi;
+pub mod compute;
 pub mod config;
 pub mod common;
 pub mod pda;
+pub mod compat;
+pub mod typed_keys;
+pub mod account_ext;
+pub mod history;
+pub mod sizes;
+pub mod jsonschema;
+pub mod cache;
+pub mod db;
+pub mod interop;
+pub mod state;
+pub mod upgrade;
+pub mod web;
+pub mod pubkey;
 
 // 重新导出主要功能
 pub use accounts::*;
@@ -19,6 +49,36 @@ pub use events::*;
 pub use types::*;
 pub use parsers::*;
 pub use errors::*;
+pub use coption::*;
+pub use small_vec::*;
+pub use raw_tail::*;
+pub use field_types::*;
+pub use accessors::*;
+pub use fuzz::*;
+pub use layout::*;
+pub use proposal::*;
+pub use program_test::*;
+pub use benches::*;
+pub use compile_fail::*;
+pub use rpc_fixtures::*;
+pub use versioned_tx::*;
+pub use manifest::*;
+pub use actions::*;
+pub use anchor_cpi::*;
+pub use compute::*;
 pub use config::*;
 pub use common::*;
-pub use pda::*;
\ No newline at end of file
+pub use pda::*;
+pub use compat::*;
+pub use typed_keys::*;
+pub use account_ext::*;
+pub use history::*;
+pub use sizes::*;
+pub use jsonschema::*;
+pub use cache::*;
+pub use db::*;
+pub use interop::*;
+pub use state::*;
+pub use upgrade::*;
+pub use web::*;
+pub use pubkey::*;
\ No newline at end of file
@@ -205,4 +205,355 @@ pub fn generate_parsers_events_file(
         })?;
     
     Ok(())
-}
\ No newline at end of file
+}
+#[cfg(test)]
+mod tests {
+    use std::fs;
+
+    use crate::test_support::{generate_crate, run_decode_test, run_generated_test};
+
+    #[test]
+    fn wrappers_encode_like_the_wrapped_structs() {
+        let idl = r#"{"address": "Poo1111111111111111111111111111111111111111", "metadata": {"name": "pool", "version": "0.1.0", "spec": "0.1.0"},
+            "instructions": [{"name": "deposit", "discriminator": [1, 2, 3, 4, 5, 6, 7, 8], "accounts": [{"name": "pool", "writable": true}], "args": [{"name": "amount", "type": "u64"}]}],
+            "accounts": [{"name": "Pool", "discriminator": [9, 9, 9, 9, 9, 9, 9, 9]}],
+            "events": [{"name": "Deposited", "discriminator": [7, 7, 7, 7, 7, 7, 7, 7]}],
+            "types": [{"name": "Pool", "type": {"kind": "struct", "fields": [{"name": "owner", "type": "pubkey"}, {"name": "fees", "type": {"defined": {"name": "Fees"}}}]}},
+                      {"name": "Fees", "type": {"kind": "struct", "fields": [{"name": "bps", "type": "u16"}]}},
+                      {"name": "Deposited", "type": {"kind": "struct", "fields": [{"name": "amount", "type": "u64"}]}}]}"#;
+        let test_source = r#"
+use std::collections::HashSet;
+
+use borsh::BorshDeserialize;
+use sol_pool_interface::{accounts::Pool, events::Deposited, parsers::{ProgramAccount, ProgramEvent}, types::Fees};
+use solana_pubkey::Pubkey;
+
+#[test]
+fn account_wrapper_round_trips_the_account_bytes() {
+    let pool = Pool { owner: Pubkey::new_unique(), fees: Fees { bps: 30 }, ..Pool::default() };
+    let bytes = pool.try_to_vec().unwrap();
+    let wrapper = ProgramAccount::try_parse(&bytes).unwrap();
+    assert_eq!(wrapper, ProgramAccount::Pool(pool.clone()));
+    assert_eq!(borsh::to_vec(&wrapper).unwrap(), bytes);
+    assert_eq!(ProgramAccount::try_from_slice(&bytes).unwrap(), wrapper);
+    assert!(ProgramAccount::try_from_slice(&[0; 8]).is_err());
+
+    let unique: HashSet<ProgramAccount> = [wrapper.clone(), wrapper, ProgramAccount::Pool(Pool::default())].into_iter().collect();
+    assert_eq!(unique.len(), 2);
+}
+
+#[test]
+fn event_wrapper_round_trips_the_event_bytes() {
+    let event = Deposited::new(42);
+    let bytes = event.try_to_vec().unwrap();
+    let wrapper = ProgramEvent::try_parse(&bytes).unwrap();
+    assert_eq!(wrapper, ProgramEvent::Deposited(event));
+    assert_eq!(borsh::to_vec(&wrapper).unwrap(), bytes);
+    assert_eq!(ProgramEvent::try_from_slice(&bytes).unwrap(), wrapper);
+
+    let unique: HashSet<ProgramEvent> = [wrapper.clone(), wrapper, ProgramEvent::Deposited(Deposited::new(1))].into_iter().collect();
+    assert_eq!(unique.len(), 2);
+}
+"#;
+        run_decode_test("wrappers", idl, &[], &[], test_source);
+    }
+
+    /// parse → `to_instruction` → parse，accounts 超出声明的部分经 `IxWithKeys` 原样保留
+    const REBUILD_TEST: &str = r#"
+use sol_CRATE_interface::errors::InstructionParseError;
+use sol_CRATE_interface::parsers::instructions::{IxWithKeys, ProgramInstruction};
+use solana_instruction::{AccountMeta, Instruction};
+use solana_pubkey::Pubkey;
+
+fn reparse(ix: &Instruction) -> ProgramInstruction {
+    let accounts: Vec<Pubkey> = ix.accounts.iter().map(|meta| meta.pubkey).collect();
+    ProgramInstruction::try_parse(&ix.data, &accounts).unwrap()
+}
+
+#[test]
+fn parsed_instructions_rebuild_and_parse_back() {
+    let keys = [Pubkey::new_unique(), Pubkey::new_unique()];
+    let original = Instruction::new_with_bytes(
+        sol_CRATE_interface::ID,
+        &DEPOSIT_DATA,
+        vec![AccountMeta::new(keys[0], false), AccountMeta::new_readonly(keys[1], false)],
+    );
+    let parsed = ProgramInstruction::try_from(&original).unwrap();
+    let rebuilt = parsed.to_instruction(sol_CRATE_interface::ID).unwrap();
+    assert_eq!((rebuilt.program_id, &rebuilt.data), (original.program_id, &original.data));
+    assert_eq!(rebuilt.accounts[0].pubkey, keys[0]);
+    assert_eq!(reparse(&rebuilt), parsed);
+
+    let remaining = vec![original.accounts[1].clone()];
+    let with_keys: IxWithKeys = parsed.clone().with_keys(sol_CRATE_interface::ID, remaining);
+    let replayed = Instruction::try_from(with_keys).unwrap();
+    assert_eq!(replayed.accounts.last().map(|meta| meta.pubkey), Some(keys[1]));
+    assert_eq!(reparse(&replayed), parsed);
+
+    // 发往其他程序的指令不按本程序解析
+    let foreign = Instruction { program_id: keys[1], ..original.clone() };
+    assert!(matches!(
+        ProgramInstruction::try_from(&foreign),
+        Err(InstructionParseError::ProgramIdMismatch { expected, found })
+            if expected == sol_CRATE_interface::ID && found == keys[1]
+    ));
+
+    let ping = reparse(&Instruction::new_with_bytes(sol_CRATE_interface::ID, &PING_DATA, Vec::new()));
+    let rebuilt = Instruction::try_from(ping.clone()).unwrap();
+    assert_eq!((rebuilt.data.as_slice(), rebuilt.accounts.len()), (&PING_DATA[..], 0));
+    assert_eq!(reparse(&rebuilt), ping);
+}
+"#;
+
+    #[test]
+    fn parsed_instructions_rebuild_for_both_idl_flavours() {
+        let anchor_idl = r#"{"address": "Poo1111111111111111111111111111111111111111", "metadata": {"name": "pool", "version": "0.1.0", "spec": "0.1.0"},
+            "instructions": [{"name": "deposit", "discriminator": [1, 2, 3, 4, 5, 6, 7, 8], "accounts": [{"name": "pool", "writable": true}], "args": [{"name": "amount", "type": "u64"}]},
+                             {"name": "ping", "discriminator": [2, 2, 2, 2, 2, 2, 2, 2], "accounts": [], "args": []}]}"#;
+        let anchor_source = format!(
+            "const DEPOSIT_DATA: [u8; 16] = [1, 2, 3, 4, 5, 6, 7, 8, 5, 0, 0, 0, 0, 0, 0, 0];\nconst PING_DATA: [u8; 8] = [2; 8];\n{}",
+            REBUILD_TEST.replace("CRATE", "pool")
+        );
+        run_decode_test("rebuild_anchor", anchor_idl, &[], &[], &anchor_source);
+
+        let shank_idl = r#"{"name": "treasury", "version": "0.1.0",
+            "metadata": {"name": "treasury", "version": "0.1.0", "spec": "0.1.0", "origin": "shank", "address": "Treasury11111111111111111111111111111111111"},
+            "instructions": [{"name": "deposit", "discriminator": [1], "accounts": [{"name": "vault", "isMut": true, "isSigner": false}], "args": [{"name": "amount", "type": "u64"}]},
+                             {"name": "ping", "discriminator": [2], "accounts": [], "args": []}]}"#;
+        let shank_source = format!(
+            "const DEPOSIT_DATA: [u8; 9] = [1, 5, 0, 0, 0, 0, 0, 0, 0];\nconst PING_DATA: [u8; 1] = [2];\n{}",
+            REBUILD_TEST.replace("CRATE", "treasury")
+        );
+        run_decode_test("rebuild_non_anchor", shank_idl, &[], &[], &shank_source);
+    }
+
+    /// 派生宏展开为 `crate::ID` / `crate::parsers`，测试 crate 根重新导出生成 crate 的这两项
+    #[test]
+    fn idl_traits_derives_compile_against_generated_parsers() {
+        let idl = r#"{"address": "Poo1111111111111111111111111111111111111111", "metadata": {"name": "pool", "version": "0.1.0", "spec": "0.1.0"},
+            "instructions": [{"name": "deposit", "discriminator": [1, 2, 3, 4, 5, 6, 7, 8], "accounts": [{"name": "pool", "writable": true}], "args": [{"name": "amount", "type": "u64"}]}],
+            "accounts": [{"name": "Pool", "discriminator": [9, 9, 9, 9, 9, 9, 9, 9]}],
+            "types": [{"name": "Pool", "type": {"kind": "struct", "fields": [{"name": "owner", "type": "pubkey"}, {"name": "bps", "type": "u16"}]}}]}"#;
+        let test_source = r#"
+pub use sol_pool_interface::{parsers, ID};
+
+use idl_traits::{AccountParser, AccountUpdate, InstructionParser, InstructionUpdate, Parser};
+use sol_pool_interface::{accounts::Pool, parsers::{ProgramAccount, ProgramInstruction}};
+use solana_pubkey::Pubkey;
+
+#[derive(InstructionParser)]
+struct Instructions;
+
+#[derive(AccountParser)]
+struct Accounts;
+
+#[test]
+fn derived_parsers_use_the_generated_parsers_and_prefilters() {
+    let pool = Pubkey::new_unique();
+    let update = InstructionUpdate { program: ID, data: [&[1, 2, 3, 4, 5, 6, 7, 8][..], &5u64.to_le_bytes()].concat(), accounts: vec![pool], stack_height: 1 };
+    let parsed = Instructions.parse(&update).unwrap();
+    assert_eq!(parsed.name(), "Deposit");
+    assert!(matches!(parsed.try_as::<ProgramInstruction>(), Some(ProgramInstruction::Deposit(keys, _)) if keys.pool == pool));
+
+    let account = Pool { owner: Pubkey::new_unique(), ..Pool::default() };
+    let data = account.try_to_vec().unwrap();
+    let update = AccountUpdate { pubkey: pool, owner: ID, data: data.clone(), lamports: 0, executable: false, rent_epoch: 0 };
+    let parsed = Accounts.parse(&update).unwrap();
+    assert_eq!(parsed.try_as::<ProgramAccount>(), Some(&ProgramAccount::Pool(account)));
+
+    let prefilter = Accounts.prefilter();
+    assert_eq!(prefilter.account_filters.len(), 1);
+    let filter = &prefilter.account_filters[0];
+    assert_eq!(filter.data_size, Some(42));
+    assert_eq!((filter.memcmp[0].offset, filter.memcmp[0].bytes.as_slice()), (0, &[9u8; 8][..]));
+    assert!(filter.matches(&data));
+}
+"#;
+        let crate_dir = generate_crate("derives", idl, &["--generate-parser", "--trailing-bytes", "strict"]);
+        let idl_traits = std::path::Path::new(env!("CARGO_MANIFEST_DIR")).join("../idls/idl-traits");
+        let manifest = crate_dir.join("Cargo.toml");
+        let dev_dependency = format!("\n[dev-dependencies.idl-traits]\npath = {:?}\n", idl_traits.canonicalize().unwrap());
+        fs::write(&manifest, fs::read_to_string(&manifest).unwrap() + &dev_dependency).unwrap();
+        run_generated_test("derives", &crate_dir, &[], test_source);
+    }
+
+    const NON_EXHAUSTIVE_IDL: &str = r#"{"address": "Poo1111111111111111111111111111111111111111", "metadata": {"name": "pool", "version": "0.1.0", "spec": "0.1.0"},
+        "instructions": [{"name": "deposit", "discriminator": [1, 2, 3, 4, 5, 6, 7, 8], "accounts": [{"name": "pool", "writable": true}], "args": [{"name": "amount", "type": "u64"}]}],
+        "accounts": [{"name": "Pool", "discriminator": [9, 9, 9, 9, 9, 9, 9, 9]}],
+        "errors": [{"code": 6000, "name": "PoolFull", "msg": "Pool is full"}],
+        "types": [{"name": "Pool", "type": {"kind": "struct", "fields": [{"name": "bps", "type": "u16"}]}}]}"#;
+
+    /// 生成文件中 `#[non_exhaustive]` 出现在 `pub enum <name>` 之前
+    fn is_non_exhaustive(source: &str, enum_name: &str) -> bool {
+        let declaration = source.find(&format!("pub enum {} ", enum_name)).unwrap_or_else(|| panic!("未找到 {}", enum_name));
+        let attributes_start = source[..declaration].rfind(";\n").or_else(|| source[..declaration].rfind("}\n")).unwrap_or(0);
+        source[attributes_start..declaration].contains("#[non_exhaustive]")
+    }
+
+    fn assert_enums_non_exhaustive(crate_dir: &std::path::Path, expected: bool) {
+        let instructions = fs::read_to_string(crate_dir.join("src/parsers/instructions.rs")).unwrap();
+        let accounts = fs::read_to_string(crate_dir.join("src/parsers/accounts.rs")).unwrap();
+        let errors = fs::read_to_string(crate_dir.join("src/errors.rs")).unwrap();
+        assert_eq!(is_non_exhaustive(&instructions, "ProgramInstruction"), expected);
+        assert_eq!(is_non_exhaustive(&accounts, "ProgramAccount"), expected);
+        for error_enum in ["PoolError", "InstructionParseError", "AccountParseError"] {
+            assert_eq!(is_non_exhaustive(&errors, error_enum), expected, "{}", error_enum);
+        }
+    }
+
+    #[test]
+    fn unknown_discriminators_decode_by_non_exhaustive_mode() {
+        let crate_dir = generate_crate("non_exhaustive_on", NON_EXHAUSTIVE_IDL, &["--non-exhaustive"]);
+        assert_enums_non_exhaustive(&crate_dir, true);
+        let test_source = r#"
+use sol_pool_interface::parsers::{ProgramAccount, ProgramInstruction};
+
+#[test]
+fn unknown_data_is_kept_verbatim() {
+    let data = [0xff; 12];
+    let parsed = ProgramInstruction::try_parse(&data, &[]).unwrap();
+    assert_eq!(parsed.name(), "Unknown");
+    assert!(matches!(&parsed, ProgramInstruction::__Unknown(raw) if raw[..] == data[..]));
+
+    let parsed = ProgramAccount::try_parse(&data).unwrap();
+    assert_eq!(parsed.name(), "Unknown");
+    assert_eq!(borsh::to_vec(&parsed).unwrap(), data);
+}
+"#;
+        run_generated_test("non_exhaustive_on", &crate_dir, &[], test_source);
+
+        let crate_dir = generate_crate("non_exhaustive_off", NON_EXHAUSTIVE_IDL, &[]);
+        assert_enums_non_exhaustive(&crate_dir, false);
+        let test_source = r#"
+use sol_pool_interface::{errors::{AccountParseError, InstructionParseError}, parsers::{ProgramAccount, ProgramInstruction}};
+
+#[test]
+fn unknown_data_is_rejected() {
+    let data = [0xff; 12];
+    assert!(matches!(
+        ProgramInstruction::try_parse(&data, &[]),
+        Err(InstructionParseError::DiscriminatorMismatch { found, .. }) if found == [0xff; 8]
+    ));
+    match ProgramAccount::try_parse(&data) {
+        Err(AccountParseError::UnknownDiscriminator { found, candidates }) => {
+            assert_eq!(found, [0xff; 8]);
+            assert_eq!(candidates[0].unwrap().name, "Pool");
+            assert_eq!(candidates[1..], [None, None]);
+        }
+        other => panic!("unexpected result: {:?}", other),
+    }
+}
+"#;
+        run_generated_test("non_exhaustive_off", &crate_dir, &[], test_source);
+    }
+
+    #[test]
+    fn unknown_discriminators_report_the_closest_accounts() {
+        let idl = r#"{"address": "Poo1111111111111111111111111111111111111111", "metadata": {"name": "pool", "version": "0.1.0", "spec": "0.1.0"},
+            "instructions": [],
+            "accounts": [
+                {"name": "Pool", "discriminator": [1, 0, 0, 0, 0, 0, 0, 0]},
+                {"name": "Vault", "discriminator": [3, 0, 0, 0, 0, 0, 0, 0]},
+                {"name": "Config", "discriminator": [255, 255, 255, 255, 255, 255, 255, 255]},
+                {"name": "Ticket", "discriminator": [7, 0, 0, 0, 0, 0, 0, 0]}
+            ],
+            "types": [
+                {"name": "Pool", "type": {"kind": "struct", "fields": [{"name": "bps", "type": "u16"}]}},
+                {"name": "Vault", "type": {"kind": "struct", "fields": [{"name": "bps", "type": "u16"}]}},
+                {"name": "Config", "type": {"kind": "struct", "fields": [{"name": "bps", "type": "u16"}]}},
+                {"name": "Ticket", "type": {"kind": "struct", "fields": [{"name": "bps", "type": "u16"}]}}
+            ]}"#;
+        let test_source = r#"
+use sol_pool_interface::errors::AccountParseError;
+use sol_pool_interface::parsers::ProgramAccount;
+
+#[test]
+fn near_miss_lists_candidates_nearest_first() {
+    // 0b101 differs from Pool (0b001) and Ticket (0b111) in one bit, from Vault (0b011) in two
+    let data = [5, 0, 0, 0, 0, 0, 0, 0, 0, 0];
+    let err = ProgramAccount::try_parse(&data).unwrap_err();
+    let AccountParseError::UnknownDiscriminator { found, candidates } = &err else {
+        panic!("unexpected error: {:?}", err);
+    };
+    assert_eq!(found, &[5, 0, 0, 0, 0, 0, 0, 0]);
+    let ranked: Vec<(&str, u32)> = candidates.iter().flatten().map(|c| (c.name, c.distance)).collect();
+    // 距离相同时保持IDL顺序，最远的 Config 被截掉
+    assert_eq!(ranked, [("Pool", 1), ("Ticket", 1), ("Vault", 2)]);
+    assert!(err.to_string().ends_with("closest known: Pool [1, 0, 0, 0, 0, 0, 0, 0] (1 bits off), Ticket [7, 0, 0, 0, 0, 0, 0, 0] (1 bits off), Vault [3, 0, 0, 0, 0, 0, 0, 0] (2 bits off)"));
+}
+"#;
+        run_decode_test("closest_discriminators", idl, &[], &[], test_source);
+    }
+
+    #[test]
+    fn lossy_parser_keeps_unknown_instructions() {
+        let test_source = r#"
+use sol_pool_interface::errors::InstructionParseError;
+use sol_pool_interface::parsers::instructions::{parse_instruction_lossy, ParsedIx, ProgramInstruction};
+use solana_pubkey::Pubkey;
+
+#[test]
+fn unknown_discriminators_keep_data_and_accounts() {
+    let accounts = [Pubkey::new_unique(), Pubkey::new_unique()];
+    let mut deposit = vec![1, 2, 3, 4, 5, 6, 7, 8];
+    deposit.extend_from_slice(&42u64.to_le_bytes());
+    assert!(matches!(
+        parse_instruction_lossy(&deposit, &accounts),
+        Ok(ParsedIx::Known(ProgramInstruction::Deposit(keys, args))) if keys.pool == accounts[0] && args.amount == 42
+    ));
+
+    let upgraded = [8, 7, 6, 5, 4, 3, 2, 1, 0xaa, 0xbb];
+    match parse_instruction_lossy(&upgraded, &accounts) {
+        Ok(ParsedIx::Unknown { discm, raw_data, accounts: keys }) => {
+            assert_eq!(discm, &upgraded[..8]);
+            assert_eq!(raw_data, upgraded);
+            assert_eq!(keys, accounts);
+        }
+        other => panic!("unexpected result: {:?}", other),
+    }
+
+    // 已知指令的数据不完整仍然是错误
+    assert!(matches!(
+        parse_instruction_lossy(&deposit[..11], &accounts),
+        Err(InstructionParseError::InstructionDataTooShort { instruction: "Deposit", expected: 16, found: 11 })
+    ));
+}
+"#;
+        run_decode_test("lossy_parser", NON_EXHAUSTIVE_IDL, &["--lossy-parser"], &[], test_source);
+    }
+
+    /// 同长度的布局依次尝试，前一个解码失败不影响后面的候选
+    #[test]
+    fn same_length_layouts_are_all_tried() {
+        let idl = r#"{"name": "vault", "version": "0.1.0", "metadata": {"name": "vault", "version": "0.1.0", "spec": "0.1.0", "origin": "shank", "address": "Vau1t11111111111111111111111111111111111111"},
+            "instructions": [],
+            "accounts": [
+                {"name": "Flag", "type": {"kind": "struct", "fields": [{"name": "enabled", "type": "bool"}, {"name": "bump", "type": "u8"}]}},
+                {"name": "Pair", "type": {"kind": "struct", "fields": [{"name": "first", "type": "u8"}, {"name": "second", "type": "u8"}]}},
+                {"name": "Switch", "type": {"kind": "struct", "fields": [{"name": "on", "type": "bool"}, {"name": "locked", "type": "bool"}, {"name": "bump", "type": "u8"}]}},
+                {"name": "Gate", "type": {"kind": "struct", "fields": [{"name": "bump", "type": "u8"}, {"name": "open", "type": "bool"}, {"name": "locked", "type": "bool"}]}}
+            ]}"#;
+        let test_source = r#"
+use sol_vault_interface::errors::AccountParseError;
+use sol_vault_interface::parsers::ProgramAccount;
+
+#[test]
+fn later_candidates_are_tried_after_a_decode_failure() {
+    // 2 不是合法的 bool，Flag 解码失败后仍按 Pair 解析
+    match ProgramAccount::try_parse(&[2, 5]).unwrap() {
+        ProgramAccount::Pair(pair) => assert_eq!((pair.first, pair.second), (2, 5)),
+        other => panic!("unexpected account: {:?}", other),
+    }
+    assert!(matches!(ProgramAccount::try_parse(&[1, 5]), Ok(ProgramAccount::Flag(flag)) if flag.enabled));
+    assert!(matches!(ProgramAccount::try_parse(&[2, 1, 1]), Ok(ProgramAccount::Gate(gate)) if gate.bump == 2 && gate.open));
+
+    // 所有同长度布局都失败时报告第一个失败
+    assert!(matches!(
+        ProgramAccount::try_parse(&[2, 2, 0]),
+        Err(AccountParseError::InvalidAccountData { account: "Switch", .. })
+    ));
+}
+"#;
+        run_decode_test("same_length_layouts", idl, &[], &[], test_source);
+    }
+}
@@ -3,10 +3,13 @@
 //! 负责分析IDL中的PDA定义，生成PDA相关的辅助函数和方法
 
 use crate::error::SoloresError;
-use crate::idl_format::anchor_idl::{PdaDefinition, PdaSeed, AnchorAccountConstraint};
-use minijinja::Value;
+use crate::idl_format::anchor_idl::{PdaDefinition, PdaSeed, AnchorAccountConstraint, AnchorField};
+use crate::idl_format::IdlFormatEnum;
+use minijinja::{context, Environment, Value};
 use log;
 use convert_case::{Case, Casing};
+use std::fs;
+use std::path::Path;
 
 /// PDA信息结构，用于模板渲染
 #[derive(Debug, Clone)]
@@ -229,6 +232,326 @@ pub fn get_pda_function_name(account_name: &str) -> String {
     format!("find_{}_pda", account_name.to_case(Case::Snake))
 }
 
+/// Keys 自动派生中一个账户的取值来源
+enum KeySource {
+    /// 调用方传入的外部账户
+    Param,
+    /// 由seeds派生的PDA
+    Derived,
+    /// 已知地址表达式（固定地址、`#[event_cpi]` 账户）
+    Known(String),
+}
+
+/// seed 引用指令参数时的字节表达式，参数类型不适合作seed时返回 None
+fn arg_seed_code(args_name: &str, path: &str, args: &[AnchorField]) -> Option<String> {
+    let path = path.strip_prefix("ix.").unwrap_or(path);
+    if path.contains('.') {
+        return None;
+    }
+    let arg = args
+        .iter()
+        .find(|arg| arg.name.to_case(Case::Snake) == path.to_case(Case::Snake))
+        .filter(|arg| arg.substitute.is_none())?;
+    let field = format!("{}.{}", args_name, crate::minijinja::filters::rust_field_filter(arg.name.to_case(Case::Snake)));
+    let rust_type = crate::minijinja::type_mapper::map_field_type(&arg.field_type);
+    let code = match rust_type.as_str() {
+        "u8" | "i8" | "u16" | "i16" | "u32" | "i32" | "u64" | "i64" | "u128" | "i128" => {
+            format!("{}.to_le_bytes().as_ref()", field)
+        }
+        "bool" => format!("&[{} as u8]", field),
+        "solana_pubkey::Pubkey" => format!("{}.as_ref()", field),
+        "std::string::String" => format!("{}.as_bytes()", field),
+        "std::vec::Vec<u8>" => format!("{}.as_slice()", field),
+        array if array.starts_with("[u8; ") => format!("{}.as_ref()", field),
+        _ => return None,
+    };
+    Some(code)
+}
+
+/// 常量seed的字节串字面量
+pub(crate) fn const_seed_code(value: &[u8]) -> String {
+    match std::str::from_utf8(value) {
+        Ok(s) if s.chars().all(|c| c.is_ascii_graphic() && c != '"' && c != '\\') => format!("b\"{}\"", s),
+        _ => format!("&{:?}", value),
+    }
+}
+
+/// 为指令生成 `{ix}_keys_derived` 的上下文
+///
+/// 带完整 `pda` 定义（常量、账户、参数seed）的账户按依赖顺序派生，只有无法派生的账户
+/// 作为参数；固定地址与 `#[event_cpi]` 账户直接填入。没有可派生的PDA时返回 None
+pub fn derived_keys_plan(
+    instruction_name: &str,
+    accounts: &[AnchorAccountConstraint],
+    args: &[AnchorField],
+    event_cpi: &[Option<&str>],
+) -> Option<Value> {
+    let instruction_upper = instruction_name.to_case(Case::Pascal).to_uppercase();
+    let names: Vec<String> = accounts.iter().map(|account| account.name.to_case(Case::Snake)).collect();
+    let args_name = if names.iter().any(|name| name == "args") { "ix_args" } else { "args" };
+
+    let mut sources: Vec<KeySource> = accounts
+        .iter()
+        .zip(&names)
+        .zip(event_cpi.iter().chain(std::iter::repeat(&None)))
+        .map(|((account, name), role)| match (role, &account.address) {
+            (Some("event_authority"), _) => KeySource::Known("crate::event_authority()".to_string()),
+            (Some(_), _) => KeySource::Known("crate::ID".to_string()),
+            (None, Some(_)) => KeySource::Known(format!("{}_{}_ADDRESS", instruction_upper, name.to_uppercase())),
+            (None, None) => KeySource::Param,
+        })
+        .collect();
+
+    // 账户seed引用的账户表达式：尚未派生的候选PDA不可用
+    let account_expr = |sources: &[KeySource], path: &str| -> Option<String> {
+        if path.contains('.') {
+            return None;
+        }
+        let index = names.iter().position(|name| *name == path.to_case(Case::Snake))?;
+        let candidate = matches!(sources[index], KeySource::Param) && accounts[index].pda.is_some();
+        match &sources[index] {
+            KeySource::Known(expr) => Some(expr.clone()),
+            KeySource::Param if candidate => None,
+            _ => Some(names[index].clone()),
+        }
+    };
+
+    let mut derivations = Vec::new();
+    let mut uses_args = false;
+    loop {
+        let mut progressed = false;
+        for index in 0..accounts.len() {
+            let Some(pda_def) = &accounts[index].pda else { continue };
+            if !matches!(sources[index], KeySource::Param) {
+                continue;
+            }
+            let mut seed_uses_args = false;
+            let seeds: Option<Vec<String>> = pda_def.seeds.iter().map(|seed| match seed {
+                PdaSeed::Const { value } => Some(const_seed_code(value)),
+                PdaSeed::Account { path } => account_expr(&sources, path).map(|expr| format!("{}.as_ref()", expr)),
+                PdaSeed::Arg { path } => {
+                    seed_uses_args = true;
+                    arg_seed_code(args_name, path, args)
+                }
+            }).collect();
+            let program = match &pda_def.program {
+                None => Some("&crate::ID".to_string()),
+                Some(PdaSeed::Const { value }) if value.len() == 32 => {
+                    Some(format!("&solana_pubkey::pubkey!(\"{}\")", bs58::encode(value).into_string()))
+                }
+                Some(PdaSeed::Const { .. }) => None,
+                Some(PdaSeed::Account { path }) => account_expr(&sources, path).map(|expr| format!("&{}", expr)),
+                Some(PdaSeed::Arg { .. }) => None,
+            };
+            let (Some(seeds), Some(program)) = (seeds, program) else { continue };
+
+            uses_args |= seed_uses_args;
+            sources[index] = KeySource::Derived;
+            derivations.push(context! {
+                name => names[index].clone(),
+                seeds_code => format!("&[{}]", seeds.join(", ")),
+                program => program,
+            });
+            progressed = true;
+        }
+        if !progressed {
+            break;
+        }
+    }
+
+    if derivations.is_empty() {
+        return None;
+    }
+    log::debug!("🔍 指令 {} 可自动派生 {} 个PDA账户", instruction_name, derivations.len());
+
+    let params: Vec<&String> = names
+        .iter()
+        .zip(&sources)
+        .filter(|(_, source)| matches!(source, KeySource::Param))
+        .map(|(name, _)| name)
+        .collect();
+    let fields: Vec<Value> = names
+        .iter()
+        .zip(&sources)
+        .map(|(name, source)| context! {
+            name => name,
+            expr => match source {
+                KeySource::Known(expr) => expr.clone(),
+                KeySource::Param | KeySource::Derived => name.clone(),
+            },
+        })
+        .collect();
+
+    Some(context! {
+        params => params,
+        args_name => args_name,
+        uses_args => uses_args,
+        pdas => derivations,
+        fields => fields,
+    })
+}
+
+/// 程序级PDA类型：pdas.rs 中每个类型对应一个去重后的PDA
+struct ProgramPda<'a> {
+    struct_name: String,
+    account_name: &'a str,
+    instructions: Vec<String>,
+    definition: &'a PdaDefinition,
+}
+
+/// seeds结构（常量值与动态seed种类），用于判断同名PDA是否为同一地址
+fn seeds_shape(pda_def: &PdaDefinition) -> Vec<(&'static str, &[u8])> {
+    pda_def.seeds.iter().map(|seed| match seed {
+        PdaSeed::Const { value } => ("const", value.as_slice()),
+        PdaSeed::Account { .. } => ("account", &[][..]),
+        PdaSeed::Arg { .. } => ("arg", &[][..]),
+    }).collect()
+}
+
+/// 收集所有指令中的PDA账户并去重
+///
+/// 同名且seeds结构相同的PDA合并为一个类型；同名但结构不同时以指令名作前缀区分
+fn program_pdas(idl: &crate::idl_format::anchor_idl::AnchorIdl) -> Vec<ProgramPda<'_>> {
+    let mut pdas: Vec<ProgramPda> = Vec::new();
+    for instruction in idl.instructions.iter().flatten() {
+        let instruction_name = instruction.name.to_case(Case::Pascal);
+        for account in instruction.accounts.iter().flatten() {
+            let Some(pda_def) = &account.pda else { continue };
+            let shape = seeds_shape(pda_def);
+            if let Some(existing) = pdas
+                .iter_mut()
+                .find(|pda| pda.account_name == account.name && seeds_shape(pda.definition) == shape)
+            {
+                existing.instructions.push(instruction_name.clone());
+                continue;
+            }
+
+            let mut struct_name = format!("{}Pda", account.name.to_case(Case::Pascal));
+            if pdas.iter().any(|pda| pda.struct_name == struct_name) {
+                struct_name = format!("{}{}", instruction_name, struct_name);
+            }
+            pdas.push(ProgramPda {
+                struct_name,
+                account_name: &account.name,
+                instructions: vec![instruction_name.clone()],
+                definition: pda_def,
+            });
+        }
+    }
+    pdas
+}
+
+/// 程序自身派生的PDA的seeds模式（不含跨程序PDA），供 workspace PDA 注册表使用
+#[derive(Debug, Clone)]
+pub struct PdaPattern {
+    pub struct_name: String,
+    pub account_name: String,
+    pub seeds: Vec<PdaSeed>,
+}
+
+/// 按 pdas.rs 的去重规则收集程序自身派生的PDA
+pub fn program_pda_patterns(idl_enum: &IdlFormatEnum) -> Vec<PdaPattern> {
+    let IdlFormatEnum::Anchor(idl) = idl_enum else {
+        return Vec::new();
+    };
+    program_pdas(idl)
+        .into_iter()
+        .filter(|pda| pda.definition.program.is_none())
+        .map(|pda| PdaPattern {
+            struct_name: pda.struct_name,
+            account_name: pda.account_name.to_string(),
+            seeds: pda.definition.seeds.clone(),
+        })
+        .collect()
+}
+
+/// 收集所有指令中的PDA账户，生成 pdas.rs 的模板上下文
+pub fn collect_program_pdas(idl_enum: &IdlFormatEnum) -> Vec<Value> {
+    let IdlFormatEnum::Anchor(idl) = idl_enum else {
+        return Vec::new();
+    };
+
+    program_pdas(idl).into_iter()
+        .filter_map(|pda| {
+            let info = PdaInfo::from_pda_definition(pda.account_name, pda.definition).ok()?;
+            let mut fields: Vec<&FunctionParam> = Vec::new();
+            for param in &info.function_params {
+                if !fields.iter().any(|field| field.name == param.name) {
+                    fields.push(param);
+                }
+            }
+            // key/bump 为结构体固定字段
+            if fields.iter().any(|field| field.name == "key" || field.name == "bump") {
+                log::warn!("⚠️ PDA {} 的seed参数与 key/bump 字段重名，跳过类型生成", pda.account_name);
+                return None;
+            }
+
+            let self_seeds: Vec<String> = info.seeds.iter().map(|seed| match seed.seed_type.as_str() {
+                "account" => format!("self.{}.as_ref()", seed.value),
+                "arg" => format!("self.{}.as_slice()", seed.value),
+                _ => seed.value.clone(),
+            }).collect();
+            let signer_seeds = info.seeds_code.strip_prefix("&[").and_then(|code| code.strip_suffix(']')).unwrap_or_default().to_string();
+
+            Some(context! {
+                struct_name => pda.struct_name,
+                account_name => pda.account_name,
+                instructions => pda.instructions,
+                external_program => pda.definition.program.is_some(),
+                fields => fields.iter().map(|field| {
+                    let is_pubkey = field.param_type == "&solana_pubkey::Pubkey";
+                    context! {
+                        name => field.name.clone(),
+                        param_type => field.param_type.clone(),
+                        field_type => if is_pubkey { "solana_pubkey::Pubkey" } else { "std::vec::Vec<u8>" },
+                        init => if is_pubkey { format!("*{}", field.name) } else { format!("{}.to_vec()", field.name) },
+                    }
+                }).collect::<Vec<_>>(),
+                seeds_code => info.seeds_code,
+                signer_seeds => signer_seeds,
+                self_seeds => self_seeds,
+                seeds_len => info.seeds.len() + 1,
+            })
+        })
+        .collect()
+}
+
+/// 生成pdas.rs：每个PDA一个带bump缓存与seeds()的类型
+pub fn generate_pdas_single_file(
+    env: &mut Environment,
+    src_dir: &Path,
+    context: &Value,
+) -> std::result::Result<(), SoloresError> {
+    let template_content = include_str!("../templates/anchor/pdas.rs.jinja");
+
+    let tmpl = env.template_from_str(template_content)
+        .map_err(|e| SoloresError::TemplateError {
+            template_name: Some("anchor/pdas.rs.jinja".to_string()),
+            message: format!("模板解析失败: {}", e),
+            context: Some("解析pdas模板".to_string()),
+        })?;
+
+    let rendered = tmpl.render(context)
+        .map_err(|e| SoloresError::TemplateError {
+            template_name: Some("anchor/pdas.rs.jinja".to_string()),
+            message: format!("模板渲染失败: {}", e),
+            context: Some("渲染pdas模板".to_string()),
+        })?;
+
+    let output_path = src_dir.join("pdas.rs");
+    fs::write(&output_path, rendered)
+        .map_err(|e| SoloresError::FileOperationError {
+            operation: "write pdas file".to_string(),
+            path: output_path.display().to_string(),
+            current_dir: std::env::current_dir().ok().map(|p| p.display().to_string()),
+            resolved_path: None,
+            source: e,
+            suggestion: Some("检查文件权限".to_string()),
+        })?;
+
+    Ok(())
+}
+
 #[cfg(test)]
 mod tests {
     use super::*;
@@ -276,4 +599,70 @@ mod tests {
         assert_eq!(pda_info.function_params[0].param_type, "&solana_pubkey::Pubkey");
         assert_eq!(pda_info.seeds_code, "&[b\"bonding_curve\", token_0_mint_account.as_ref()]");
     }
+
+    #[test]
+    fn test_program_pdas_merge_by_seed_shape() {
+        let idl = crate::idl_format::parse_idl_json(r#"{
+            "address": "11111111111111111111111111111111",
+            "metadata": {"name": "demo", "version": "0.1.0", "spec": "0.1.0"},
+            "instructions": [
+                {"name": "buy", "discriminator": [0,0,0,0,0,0,0,1], "args": [], "accounts": [
+                    {"name": "pool", "pda": {"seeds": [{"kind": "const", "value": [112]}, {"kind": "account", "path": "mint"}]}}
+                ]},
+                {"name": "sell", "discriminator": [0,0,0,0,0,0,0,2], "args": [], "accounts": [
+                    {"name": "pool", "pda": {"seeds": [{"kind": "const", "value": [112]}, {"kind": "account", "path": "base_mint"}]}},
+                    {"name": "vault", "pda": {"seeds": [{"kind": "account", "path": "pool"}, {"kind": "arg", "path": "index"}]}}
+                ]},
+                {"name": "close", "discriminator": [0,0,0,0,0,0,0,3], "args": [], "accounts": [
+                    {"name": "pool", "pda": {"seeds": [{"kind": "const", "value": [113]}]}}
+                ]}
+            ]
+        }"#).unwrap();
+
+        let pdas = collect_program_pdas(&idl);
+        let names: Vec<String> = pdas.iter().map(|p| p.get_attr("struct_name").unwrap().to_string()).collect();
+        assert_eq!(names, ["PoolPda", "VaultPda", "ClosePoolPda"]);
+        assert_eq!(pdas[0].get_attr("instructions").unwrap().len(), Some(2));
+        assert_eq!(pdas[1].get_attr("seeds_len").unwrap().as_usize(), Some(3));
+        assert_eq!(
+            pdas[1].get_attr("self_seeds").unwrap().to_string(),
+            r#"["self.pool_account.as_ref()", "self.index.as_slice()"]"#
+        );
+    }
+
+    #[test]
+    fn test_derived_keys_plan_orders_dependent_pdas() {
+        let idl = crate::idl_format::parse_idl_json(r#"{
+            "address": "11111111111111111111111111111111",
+            "metadata": {"name": "demo", "version": "0.1.0", "spec": "0.1.0"},
+            "instructions": [
+                {"name": "deposit", "discriminator": [0,0,0,0,0,0,0,1],
+                 "args": [{"name": "index", "type": "u16"}],
+                 "accounts": [
+                    {"name": "vault", "pda": {"seeds": [{"kind": "account", "path": "pool"}, {"kind": "arg", "path": "index"}]}},
+                    {"name": "pool", "pda": {"seeds": [{"kind": "const", "value": [112]}, {"kind": "account", "path": "mint"}]}},
+                    {"name": "mint"},
+                    {"name": "creator_vault", "pda": {"seeds": [{"kind": "account", "path": "pool.creator"}]}},
+                    {"name": "system_program", "address": "11111111111111111111111111111111"}
+                 ]}
+            ]
+        }"#).unwrap();
+        let IdlFormatEnum::Anchor(idl) = &idl else { panic!("expected anchor idl") };
+        let ix = &idl.instructions.as_ref().unwrap()[0];
+        let accounts = ix.accounts.as_deref().unwrap();
+
+        let plan = derived_keys_plan(&ix.name, accounts, ix.args.as_deref().unwrap(), &[None; 5]).unwrap();
+        assert_eq!(plan.get_attr("params").unwrap().to_string(), r#"["mint", "creator_vault"]"#);
+        assert!(plan.get_attr("uses_args").unwrap().is_true());
+
+        let pdas = plan.get_attr("pdas").unwrap();
+        let first = pdas.get_item(&Value::from(0)).unwrap();
+        let second = pdas.get_item(&Value::from(1)).unwrap();
+        assert_eq!(first.get_attr("name").unwrap().to_string(), "pool");
+        assert_eq!(first.get_attr("seeds_code").unwrap().to_string(), r#"&[b"p", mint.as_ref()]"#);
+        assert_eq!(second.get_attr("seeds_code").unwrap().to_string(), "&[pool.as_ref(), args.index.to_le_bytes().as_ref()]");
+
+        let system_program = plan.get_attr("fields").unwrap().get_item(&Value::from(4)).unwrap();
+        assert_eq!(system_program.get_attr("expr").unwrap().to_string(), "DEPOSIT_SYSTEM_PROGRAM_ADDRESS");
+    }
 }
\ No newline at end of file
@@ -0,0 +1,135 @@
+//! 程序集成测试生成器
+//!
+//! 生成 tests/program_test.rs，基于 solana-program-test 校验指令构建器与解析器互相兼容
+
+use crate::error::SoloresError;
+use minijinja::{Environment, Value};
+use std::fs;
+use std::path::Path;
+
+/// 生成 tests/program_test.rs
+pub fn generate_program_test_file(
+    env: &mut Environment,
+    output_dir: &Path,
+    context: &Value,
+    template_type: &str,
+) -> std::result::Result<(), SoloresError> {
+    let (template_name, template_content) = if template_type == "anchor" {
+        ("anchor/tests/program_test.rs.jinja", include_str!("../templates/anchor/tests/program_test.rs.jinja"))
+    } else {
+        ("non_anchor/tests/program_test.rs.jinja", include_str!("../templates/non_anchor/tests/program_test.rs.jinja"))
+    };
+
+    let tmpl = env.template_from_str(template_content)
+        .map_err(|e| SoloresError::TemplateError {
+            template_name: Some(template_name.to_string()),
+            message: format!("模板解析失败: {}", e),
+            context: Some("解析program_test模板".to_string()),
+        })?;
+
+    let rendered = tmpl.render(context)
+        .map_err(|e| SoloresError::TemplateError {
+            template_name: Some(template_name.to_string()),
+            message: format!("模板渲染失败: {}", e),
+            context: Some("渲染program_test模板".to_string()),
+        })?;
+
+    let tests_dir = output_dir.join("tests");
+    fs::create_dir_all(&tests_dir).map_err(|e| SoloresError::FileOperationError {
+        operation: "create tests directory".to_string(),
+        path: tests_dir.display().to_string(),
+        current_dir: std::env::current_dir().ok().map(|p| p.display().to_string()),
+        resolved_path: None,
+        source: e,
+        suggestion: Some("检查目录权限".to_string()),
+    })?;
+
+    let output_path = tests_dir.join("program_test.rs");
+    fs::write(&output_path, rendered)
+        .map_err(|e| SoloresError::FileOperationError {
+            operation: "write program_test file".to_string(),
+            path: output_path.display().to_string(),
+            current_dir: std::env::current_dir().ok().map(|p| p.display().to_string()),
+            resolved_path: None,
+            source: e,
+            suggestion: Some("检查文件权限".to_string()),
+        })?;
+
+    Ok(())
+}
+
+#[cfg(test)]
+mod tests {
+    use std::fs;
+
+    use crate::test_support::{generate_crate, run_decode_test};
+
+    const TREASURY_IDL: &str = r#"{"name": "treasury", "version": "0.1.0",
+            "metadata": {"name": "treasury", "version": "0.1.0", "spec": "0.1.0", "origin": "shank", "address": "Treasury11111111111111111111111111111111111"},
+            "instructions": [{"name": "withdraw", "discriminator": [1],
+                "accounts": [{"name": "vault", "isMut": true, "isSigner": false}, {"name": "payer", "isMut": true, "isSigner": true}],
+                "args": [{"name": "amount", "type": "u64"}]},
+              {"name": "ping", "discriminator": [2], "accounts": [], "args": []}]}"#;
+
+    #[test]
+    fn program_tests_cover_every_instruction_only_when_requested() {
+        let crate_dir = generate_crate("program_test", TREASURY_IDL, &["--generate-parser", "--gen-program-tests"]);
+        let source = fs::read_to_string(crate_dir.join("tests/program_test.rs")).unwrap();
+        assert!(source.contains("ProgramInstruction::Withdraw(keys, ix_data) =>"));
+        assert!(source.contains("ProgramInstruction::Ping(ix_data) =>"));
+        let manifest = fs::read_to_string(crate_dir.join("Cargo.toml")).unwrap();
+        assert!(manifest.contains("[dev-dependencies.solana-program-test]"));
+        fs::remove_dir_all(crate_dir.parent().unwrap()).unwrap();
+
+        let crate_dir = generate_crate("no_program_test", TREASURY_IDL, &["--generate-parser"]);
+        assert!(!crate_dir.join("tests/program_test.rs").exists());
+        assert!(!fs::read_to_string(crate_dir.join("Cargo.toml")).unwrap().contains("solana-program-test"));
+        fs::remove_dir_all(crate_dir.parent().unwrap()).unwrap();
+    }
+
+    /// 生成的 program_test 依赖 solana-program-test 的 bank；这里按同样的步骤编译消息并解析，
+    /// 校验它断言的构建器 / 解析器契约
+    #[test]
+    fn compiled_builder_output_parses_back() {
+        let test_source = r#"
+use sol_treasury_interface::instructions::*;
+use sol_treasury_interface::parsers::instructions::ProgramInstruction;
+use solana_message::legacy::Message;
+use solana_pubkey::Pubkey;
+
+fn process(ix: &solana_instruction::Instruction, payer: &Pubkey) -> (Vec<u8>, Vec<Pubkey>, ProgramInstruction) {
+    let message = Message::new(std::slice::from_ref(ix), Some(payer));
+    let compiled = &message.instructions[0];
+    assert_eq!(message.account_keys[usize::from(compiled.program_id_index)], sol_treasury_interface::ID);
+    let accounts: Vec<Pubkey> = compiled.accounts.iter().map(|index| message.account_keys[usize::from(*index)]).collect();
+    let parsed = ProgramInstruction::try_parse(&compiled.data, &accounts).unwrap();
+    (compiled.data.clone(), accounts, parsed)
+}
+
+#[test]
+fn instructions_round_trip_through_parser() {
+    let payer = Pubkey::new_unique();
+    let keys = WithdrawKeys { vault: Pubkey::new_unique(), payer };
+    let (data, accounts, parsed) = process(&withdraw_ix(keys.clone(), WithdrawIxData::new(9)).unwrap(), &payer);
+    match parsed {
+        ProgramInstruction::Withdraw(parsed_keys, ix_data) => {
+            assert_eq!(ix_data.try_to_vec().unwrap(), data);
+            assert_eq!(parsed_keys.to_vec(), accounts);
+            assert_eq!(parsed_keys.to_vec(), keys.to_vec());
+        }
+        other => panic!("expected Withdraw, parsed {:?}", other),
+    }
+
+    let ping = solana_instruction::Instruction::new_with_bytes(
+        sol_treasury_interface::ID,
+        &PingIxData::default().try_to_vec().unwrap(),
+        Vec::new(),
+    );
+    let (data, accounts, parsed) = process(&ping, &payer);
+    assert!(accounts.is_empty());
+    assert!(matches!(parsed, ProgramInstruction::Ping(ix_data) if ix_data.try_to_vec().unwrap() == data));
+}
+"#;
+        run_decode_test("program_contract", TREASURY_IDL, &[], &["versioned-tx"], test_source);
+    }
+}
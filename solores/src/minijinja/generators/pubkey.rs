@@ -0,0 +1,44 @@
+//! Pubkey 别名模块生成器
+//! 
+//! `--pubkey-crate` 选择非默认来源时生成 pubkey.rs，重新导出所选路径下的 Pubkey
+
+use crate::error::SoloresError;
+use minijinja::{Environment, Value};
+use std::fs;
+use std::path::Path;
+
+/// 生成 Pubkey 别名模块
+pub fn generate_pubkey_single_file(
+    env: &mut Environment,
+    src_dir: &Path,
+    context: &Value,
+) -> std::result::Result<(), SoloresError> {
+    let template_content = include_str!("../templates/common/pubkey.rs.jinja");
+    
+    let tmpl = env.template_from_str(template_content)
+        .map_err(|e| SoloresError::TemplateError {
+            template_name: Some("common/pubkey.rs.jinja".to_string()),
+            message: format!("模板解析失败: {}", e),
+            context: Some("解析pubkey模板".to_string()),
+        })?;
+    
+    let rendered = tmpl.render(context)
+        .map_err(|e| SoloresError::TemplateError {
+            template_name: Some("common/pubkey.rs.jinja".to_string()),
+            message: format!("模板渲染失败: {}", e),
+            context: Some("渲染pubkey模板".to_string()),
+        })?;
+    
+    let output_path = src_dir.join("pubkey.rs");
+    fs::write(&output_path, rendered)
+        .map_err(|e| SoloresError::FileOperationError {
+            operation: "write pubkey file".to_string(),
+            path: output_path.display().to_string(),
+            current_dir: std::env::current_dir().ok().map(|p| p.display().to_string()),
+            resolved_path: None,
+            source: e,
+            suggestion: Some("检查文件权限".to_string()),
+        })?;
+    
+    Ok(())
+}
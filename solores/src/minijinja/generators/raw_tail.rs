@@ -0,0 +1,44 @@
+//! raw_tail 辅助模块生成器
+//! 
+//! 当指令参数使用 raw_tail 编码时，生成 raw_tail.rs 编解码辅助函数
+
+use crate::error::SoloresError;
+use minijinja::{Environment, Value};
+use std::fs;
+use std::path::Path;
+
+/// 生成raw_tail辅助模块
+pub fn generate_raw_tail_single_file(
+    env: &mut Environment,
+    src_dir: &Path,
+    context: &Value,
+) -> std::result::Result<(), SoloresError> {
+    let template_content = include_str!("../templates/common/raw_tail.rs.jinja");
+    
+    let tmpl = env.template_from_str(template_content)
+        .map_err(|e| SoloresError::TemplateError {
+            template_name: Some("common/raw_tail.rs.jinja".to_string()),
+            message: format!("模板解析失败: {}", e),
+            context: Some("解析raw_tail模板".to_string()),
+        })?;
+    
+    let rendered = tmpl.render(context)
+        .map_err(|e| SoloresError::TemplateError {
+            template_name: Some("common/raw_tail.rs.jinja".to_string()),
+            message: format!("模板渲染失败: {}", e),
+            context: Some("渲染raw_tail模板".to_string()),
+        })?;
+    
+    let output_path = src_dir.join("raw_tail.rs");
+    fs::write(&output_path, rendered)
+        .map_err(|e| SoloresError::FileOperationError {
+            operation: "write raw_tail file".to_string(),
+            path: output_path.display().to_string(),
+            current_dir: std::env::current_dir().ok().map(|p| p.display().to_string()),
+            resolved_path: None,
+            source: e,
+            suggestion: Some("检查文件权限".to_string()),
+        })?;
+    
+    Ok(())
+}
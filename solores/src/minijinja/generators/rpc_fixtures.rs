@@ -0,0 +1,93 @@
+//! 主网交易解析测试生成器
+//!
+//! 生成 tests/rpc_fixtures.rs：`--fixtures-from-rpc` 获取的每条指令一个测试，
+//! 预期的指令名在生成时按 discriminator 前缀从上下文中的指令确定
+
+use crate::error::SoloresError;
+use crate::fixtures::InstructionFixture;
+use convert_case::{Case, Casing};
+use minijinja::{context, Environment, Value};
+use std::fs;
+use std::path::Path;
+
+/// 生成 tests/rpc_fixtures.rs
+pub fn generate_rpc_fixtures_file(
+    env: &mut Environment,
+    output_dir: &Path,
+    context: &Value,
+    fixtures: &[InstructionFixture],
+) -> std::result::Result<(), SoloresError> {
+    let template_name = "common/tests/rpc_fixtures.rs.jinja";
+    let tmpl = env.template_from_str(include_str!("../templates/common/tests/rpc_fixtures.rs.jinja"))
+        .map_err(|e| SoloresError::TemplateError {
+            template_name: Some(template_name.to_string()),
+            message: format!("模板解析失败: {}", e),
+            context: Some("解析rpc_fixtures模板".to_string()),
+        })?;
+
+    let discriminators = instruction_discriminators(context);
+    let fixtures: Vec<Value> = fixtures
+        .iter()
+        .map(|fixture| {
+            // 多个指令匹配时取 discriminator 最长的一个；空 discriminator 匹配无 discriminator 的单指令程序
+            let instruction = discriminators
+                .iter()
+                .filter(|(_, discriminator)| fixture.data.starts_with(discriminator))
+                .max_by_key(|(_, discriminator)| discriminator.len())
+                .map(|(name, _)| name.clone());
+            if instruction.is_none() {
+                log::warn!("⚠️ 交易 {} 的指令 #{} 不匹配任何指令 discriminator", fixture.signature, fixture.index);
+            }
+            let test_name = format!(
+                "{}_{}_{}",
+                instruction.as_deref().unwrap_or("unknown").to_case(Case::Snake),
+                fixture.signature.chars().take(8).collect::<String>().to_lowercase(),
+                fixture.index.replace('.', "_")
+            );
+            context! {
+                signature => fixture.signature,
+                index => fixture.index,
+                data => fixture.data,
+                accounts => fixture.accounts,
+                instruction => instruction,
+                test_name => test_name,
+            }
+        })
+        .collect();
+
+    let rendered = tmpl.render(context! { fixtures => fixtures, ..context.clone() })
+        .map_err(|e| SoloresError::TemplateError {
+            template_name: Some(template_name.to_string()),
+            message: format!("模板渲染失败: {}", e),
+            context: Some("渲染rpc_fixtures模板".to_string()),
+        })?;
+
+    let tests_dir = output_dir.join("tests");
+    fs::create_dir_all(&tests_dir)
+        .map_err(|e| SoloresError::file_operation_error("create tests directory", tests_dir.display().to_string(), e))?;
+    let output_path = tests_dir.join("rpc_fixtures.rs");
+    fs::write(&output_path, rendered)
+        .map_err(|e| SoloresError::file_operation_error("write rpc_fixtures file", output_path.display().to_string(), e))?;
+
+    Ok(())
+}
+
+/// 上下文中各指令的名称与 discriminator
+fn instruction_discriminators(context: &Value) -> Vec<(String, Vec<u8>)> {
+    let Ok(instructions) = context.get_attr("instructions").and_then(|v| v.try_iter()) else {
+        return Vec::new();
+    };
+    instructions
+        .filter_map(|instruction| {
+            let name = instruction.get_attr("name").ok()?.as_str()?.to_string();
+            let discriminator = instruction
+                .get_attr("discriminator")
+                .ok()?
+                .try_iter()
+                .ok()?
+                .map(|byte| u8::try_from(byte).ok())
+                .collect::<Option<Vec<u8>>>()?;
+            Some((name, discriminator))
+        })
+        .collect()
+}
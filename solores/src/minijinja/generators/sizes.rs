@@ -0,0 +1,152 @@
+//! 账户大小与租金报告生成器
+//!
+//! `--size-report` 时在 crate 根目录生成 SIZES.md 与 sizes.json：按布局引擎计算的长度范围列出
+//! 每个账户的最小 / 最大数据长度、是否定长，以及按 `--rent-lamports-per-byte-year` 计算的
+//! 免租最低余额，供集成方估算创建账户的成本。
+//!
+//! 免租余额与 `solana_rent::Rent::minimum_balance` 一致：
+//! `(128 + 数据长度) * 每字节每年 lamports * 2 年`。
+
+use crate::error::SoloresError;
+use minijinja::{context, Environment, Value};
+use serde_json::json;
+use std::fs;
+use std::path::Path;
+
+/// 主网当前的每字节每年租金（lamports）
+pub const DEFAULT_LAMPORTS_PER_BYTE_YEAR: u64 = 3_480;
+
+/// 每个账户在数据之外计入租金的元数据字节
+const ACCOUNT_STORAGE_OVERHEAD: u64 = 128;
+
+/// 免租所需预付的年数
+const EXEMPTION_THRESHOLD_YEARS: u64 = 2;
+
+const LAMPORTS_PER_SOL: f64 = 1_000_000_000.0;
+
+/// `data_len` 字节账户的免租最低余额
+pub fn rent_exempt_lamports(data_len: u64, lamports_per_byte_year: u64) -> u64 {
+    (ACCOUNT_STORAGE_OVERHEAD + data_len) * lamports_per_byte_year * EXEMPTION_THRESHOLD_YEARS
+}
+
+/// 每个账户的报告条目：`{name, min_size, max_size, fixed, min_lamports, max_lamports, min_sol, max_sol}`，
+/// 无上限的变长账户 `max_*` 为 None
+fn account_rows(context: &Value, lamports_per_byte_year: u64) -> Vec<Value> {
+    context
+        .get_attr("accounts")
+        .ok()
+        .and_then(|accounts| accounts.try_iter().ok())
+        .into_iter()
+        .flatten()
+        .map(|account| {
+            let min_size = account.get_attr("min_len").ok().and_then(|len| u64::try_from(len).ok()).unwrap_or(0);
+            let max_size = account.get_attr("max_len").ok().and_then(|len| u64::try_from(len).ok());
+            let min_lamports = rent_exempt_lamports(min_size, lamports_per_byte_year);
+            let max_lamports = max_size.map(|size| rent_exempt_lamports(size, lamports_per_byte_year));
+            context! {
+                name => account.get_attr("name").unwrap_or_default(),
+                min_size => min_size,
+                max_size => max_size,
+                fixed => max_size == Some(min_size),
+                min_lamports => min_lamports,
+                max_lamports => max_lamports,
+                min_sol => format!("{:.9}", min_lamports as f64 / LAMPORTS_PER_SOL),
+                max_sol => max_lamports.map(|lamports| format!("{:.9}", lamports as f64 / LAMPORTS_PER_SOL)),
+            }
+        })
+        .collect()
+}
+
+/// sizes.json 内容
+fn sizes_json(rows: &[Value], lamports_per_byte_year: u64) -> serde_json::Value {
+    let number = |row: &Value, key: &str| row.get_attr(key).ok().and_then(|value| u64::try_from(value).ok());
+    let accounts: Vec<serde_json::Value> = rows
+        .iter()
+        .map(|row| {
+            json!({
+                "name": row.get_attr("name").ok().and_then(|name| name.as_str().map(str::to_string)),
+                "min_size": number(row, "min_size"),
+                "max_size": number(row, "max_size"),
+                "fixed": row.get_attr("fixed").map(|fixed| fixed.is_true()).unwrap_or(false),
+                "rent_exempt_lamports_min": number(row, "min_lamports"),
+                "rent_exempt_lamports_max": number(row, "max_lamports"),
+            })
+        })
+        .collect();
+    json!({
+        "rent": {
+            "lamports_per_byte_year": lamports_per_byte_year,
+            "exemption_threshold_years": EXEMPTION_THRESHOLD_YEARS,
+            "account_storage_overhead": ACCOUNT_STORAGE_OVERHEAD,
+        },
+        "accounts": accounts,
+    })
+}
+
+/// 生成 SIZES.md 与 sizes.json
+pub fn generate_size_report(
+    env: &mut Environment,
+    output_dir: &Path,
+    context: &Value,
+    lamports_per_byte_year: u64,
+) -> std::result::Result<(), SoloresError> {
+    let rows = account_rows(context, lamports_per_byte_year);
+
+    let template_name = "common/sizes.md.jinja";
+    let tmpl = env.template_from_str(include_str!("../templates/common/sizes.md.jinja"))
+        .map_err(|e| SoloresError::TemplateError {
+            template_name: Some(template_name.to_string()),
+            message: format!("模板解析失败: {}", e),
+            context: Some("解析SIZES.md模板".to_string()),
+        })?;
+    let rendered = tmpl
+        .render(context! {
+            rows => rows,
+            lamports_per_byte_year => lamports_per_byte_year,
+            overhead => ACCOUNT_STORAGE_OVERHEAD,
+            threshold => EXEMPTION_THRESHOLD_YEARS,
+            ..context.clone()
+        })
+        .map_err(|e| SoloresError::TemplateError {
+            template_name: Some(template_name.to_string()),
+            message: format!("模板渲染失败: {}", e),
+            context: Some("渲染SIZES.md模板".to_string()),
+        })?;
+    let markdown_path = output_dir.join("SIZES.md");
+    fs::write(&markdown_path, rendered)
+        .map_err(|e| SoloresError::file_operation_error("write SIZES.md", markdown_path.display().to_string(), e))?;
+
+    let json = serde_json::to_string_pretty(&sizes_json(&rows, lamports_per_byte_year)).map_err(|e| SoloresError::TemplateError {
+        template_name: None,
+        message: format!("sizes.json序列化失败: {}", e),
+        context: Some("生成sizes.json".to_string()),
+    })?;
+    let json_path = output_dir.join("sizes.json");
+    fs::write(&json_path, json + "\n")
+        .map_err(|e| SoloresError::file_operation_error("write sizes.json", json_path.display().to_string(), e))?;
+    Ok(())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn rent_matches_the_runtime_formula_and_variable_accounts_have_no_max() {
+        // 0 字节账户在主网的免租余额为 890_880 lamports
+        assert_eq!(rent_exempt_lamports(0, DEFAULT_LAMPORTS_PER_BYTE_YEAR), 890_880);
+        assert_eq!(rent_exempt_lamports(165, DEFAULT_LAMPORTS_PER_BYTE_YEAR), 2_039_280);
+
+        let context = context! {
+            accounts => vec![
+                context! { name => "Pool", min_len => 165, max_len => 165 },
+                context! { name => "Registry", min_len => 12, max_len => Value::from(()) },
+            ],
+        };
+        let report = sizes_json(&account_rows(&context, DEFAULT_LAMPORTS_PER_BYTE_YEAR), DEFAULT_LAMPORTS_PER_BYTE_YEAR);
+        assert_eq!(report["accounts"][0]["fixed"], true);
+        assert_eq!(report["accounts"][0]["rent_exempt_lamports_max"], 2_039_280);
+        assert_eq!(report["accounts"][1]["fixed"], false);
+        assert!(report["accounts"][1]["max_size"].is_null());
+    }
+}
@@ -0,0 +1,89 @@
+//! 类型化账户键模块生成器
+//! 
+//! `--typed-keys` 时生成 typed_keys.rs，为多个指令共用的账户角色定义 Pubkey 新类型
+
+use crate::error::SoloresError;
+use minijinja::{Environment, Value};
+use std::fs;
+use std::path::Path;
+
+/// 生成类型化账户键模块
+pub fn generate_typed_keys_single_file(
+    env: &mut Environment,
+    src_dir: &Path,
+    context: &Value,
+) -> std::result::Result<(), SoloresError> {
+    let template_content = include_str!("../templates/common/typed_keys.rs.jinja");
+    
+    let tmpl = env.template_from_str(template_content)
+        .map_err(|e| SoloresError::TemplateError {
+            template_name: Some("common/typed_keys.rs.jinja".to_string()),
+            message: format!("模板解析失败: {}", e),
+            context: Some("解析typed_keys模板".to_string()),
+        })?;
+    
+    let rendered = tmpl.render(context)
+        .map_err(|e| SoloresError::TemplateError {
+            template_name: Some("common/typed_keys.rs.jinja".to_string()),
+            message: format!("模板渲染失败: {}", e),
+            context: Some("渲染typed_keys模板".to_string()),
+        })?;
+    
+    let output_path = src_dir.join("typed_keys.rs");
+    fs::write(&output_path, rendered)
+        .map_err(|e| SoloresError::FileOperationError {
+            operation: "write typed_keys file".to_string(),
+            path: output_path.display().to_string(),
+            current_dir: std::env::current_dir().ok().map(|p| p.display().to_string()),
+            resolved_path: None,
+            source: e,
+            suggestion: Some("检查文件权限".to_string()),
+        })?;
+    
+    Ok(())
+}
+
+#[cfg(test)]
+mod tests {
+    use crate::test_support::run_decode_test;
+
+    #[test]
+    fn parsed_keys_carry_the_typed_key() {
+        let idl = r#"{"address": "Poo1111111111111111111111111111111111111111", "metadata": {"name": "pool", "version": "0.1.0", "spec": "0.1.0"},
+            "instructions": [
+                {"name": "deposit", "discriminator": [1, 0, 0, 0, 0, 0, 0, 0], "accounts": [{"name": "pool", "writable": true}, {"name": "owner", "signer": true}], "args": [{"name": "amount", "type": "u64"}]},
+                {"name": "withdraw", "discriminator": [2, 0, 0, 0, 0, 0, 0, 0], "accounts": [{"name": "pool", "writable": true}, {"name": "owner", "signer": true}], "args": [{"name": "amount", "type": "u64"}]},
+                {"name": "sync", "discriminator": [3, 0, 0, 0, 0, 0, 0, 0], "accounts": [{"name": "pool", "writable": true}], "args": []}]}"#;
+        let test_source = r#"
+use sol_pool_interface::{instructions::*, parsers::instructions::ProgramInstruction, typed_keys::Pool};
+use solana_pubkey::Pubkey;
+
+#[test]
+fn decoded_pool_key_is_the_newtype() {
+    let pool = Pool::from(Pubkey::new_unique());
+    let owner = Pubkey::new_unique();
+    let ix = deposit_ix(DepositKeys { pool, owner }, DepositIxData::new(7)).unwrap();
+    let accounts: Vec<Pubkey> = ix.accounts.iter().map(|meta| meta.pubkey).collect();
+    assert_eq!(accounts, [*pool, owner]);
+
+    let ProgramInstruction::Deposit(keys, args) = ProgramInstruction::try_parse(&ix.data, &accounts).unwrap() else {
+        panic!("deposit data parsed as another instruction");
+    };
+    let decoded: Pool = keys.pool;
+    assert_eq!(decoded, pool);
+    assert_eq!(keys.owner, owner);
+    assert_eq!(args.amount, 7);
+}
+
+#[test]
+fn typed_key_round_trips_through_its_string_form() {
+    let pool = Pool::from(Pubkey::new_unique());
+    assert_eq!(pool.to_string(), pool.0.to_string());
+    assert_eq!(pool.to_string().parse::<Pool>().unwrap(), pool);
+    assert_eq!(Pubkey::from(pool), *pool.as_ref());
+    assert!("not a pubkey".parse::<Pool>().is_err());
+}
+"#;
+        run_decode_test("typed_keys", idl, &["--typed-keys"], &[], test_source);
+    }
+}
@@ -3,10 +3,10 @@
 //! 负责生成类型相关的文件夹结构和单个类型文件
 
 use crate::error::SoloresError;
+use crate::idl_format::identifiers::ModuleNames;
 use minijinja::{context, Environment, Value};
 use std::fs;
 use std::path::Path;
-use convert_case::{Case, Casing};
 use log;
 
 /// 生成types文件夹和每个类型文件
@@ -30,18 +30,27 @@ pub fn generate_types_folder(
         suggestion: Some("检查目录权限".to_string()),
     })?;
     
+    // 外部 crate 提供的类型在 mod.rs 中重新导出
+    let reexports: Vec<String> = context
+        .get_attr("extern_reexports")
+        .ok()
+        .and_then(|paths| paths.try_iter().ok())
+        .map(|paths| paths.filter_map(|path| path.as_str().map(str::to_string)).collect())
+        .unwrap_or_default();
+
     // 检查是否有有效的types数据
     let types_len = if types == Value::UNDEFINED { 0 } else { types.len().unwrap_or(0) };
     
     if types_len == 0 {
         log::debug!("📁 没有types数据，生成空的types模块");
         // 生成空的types/mod.rs
-        super::common::generate_folder_mod_file(env, &types_dir, &Vec::new(), "types", template_type)?;
+        super::common::generate_folder_mod_file_with_reexports(env, &types_dir, &[], &reexports, "types", template_type)?;
         return Ok(());
     }
     
     // 收集类型文件名用于mod.rs
     let mut type_names = Vec::new();
+    let mut modules = ModuleNames::new("types");
     
     // 为每个类型生成单独文件
     for i in 0..types.len().unwrap_or(0) {
@@ -49,14 +58,17 @@ pub fn generate_types_folder(
             if type_def != Value::UNDEFINED {
                 if let Ok(name_value) = type_def.get_attr("name") {
                     if let Some(type_name) = name_value.as_str() {
-                        let filename = struct_name_to_filename(type_name);
-                        type_names.push(filename.trim_end_matches(".rs").to_string());
+                        let module = modules.allocate(type_name);
+                        let filename = format!("{}.rs", module.file_stem);
+                        type_names.push(module.ident);
                         
                         // 创建单个类型上下文 - 使用官方API
                         let type_context = context! {
                             type_def => type_def.clone(),
                             crate_name => context.get_attr("crate_name").unwrap_or(Value::from("")),
                             has_serde => context.get_attr("has_serde").unwrap_or(Value::from(false)),
+                            serde_rename => context.get_attr("serde_rename").unwrap_or(Value::UNDEFINED),
+                            has_jsonschema => context.get_attr("has_jsonschema").unwrap_or(Value::from(false)),
                             is_unified_library => context.get_attr("is_unified_library").unwrap_or(Value::from(false))
                         };
                         
@@ -69,7 +81,7 @@ pub fn generate_types_folder(
     }
     
     // 生成types/mod.rs
-    super::common::generate_folder_mod_file(env, &types_dir, &type_names, "types", template_type)?;
+    super::common::generate_folder_mod_file_with_reexports(env, &types_dir, &type_names, &reexports, "types", template_type)?;
     
     Ok(())
 }
@@ -116,7 +128,3 @@ pub fn generate_single_type_file(
     Ok(())
 }
 
-/// 结构体名到文件名转换
-fn struct_name_to_filename(name: &str) -> String {
-    name.to_case(Case::Snake) + ".rs"
-}
\ No newline at end of file
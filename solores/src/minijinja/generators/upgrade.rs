@@ -0,0 +1,44 @@
+//! 程序升级检查模块生成器
+//! 
+//! `--gen-upgrade` 时生成 upgrade.rs：解析 ProgramData（升级权限、部署 slot），Anchor 程序另可比对链上IDL哈希
+
+use crate::error::SoloresError;
+use minijinja::{Environment, Value};
+use std::fs;
+use std::path::Path;
+
+/// 生成程序升级检查模块
+pub fn generate_upgrade_single_file(
+    env: &mut Environment,
+    src_dir: &Path,
+    context: &Value,
+) -> std::result::Result<(), SoloresError> {
+    let template_content = include_str!("../templates/common/upgrade.rs.jinja");
+    
+    let tmpl = env.template_from_str(template_content)
+        .map_err(|e| SoloresError::TemplateError {
+            template_name: Some("common/upgrade.rs.jinja".to_string()),
+            message: format!("模板解析失败: {}", e),
+            context: Some("解析upgrade模板".to_string()),
+        })?;
+    
+    let rendered = tmpl.render(context)
+        .map_err(|e| SoloresError::TemplateError {
+            template_name: Some("common/upgrade.rs.jinja".to_string()),
+            message: format!("模板渲染失败: {}", e),
+            context: Some("渲染upgrade模板".to_string()),
+        })?;
+    
+    let output_path = src_dir.join("upgrade.rs");
+    fs::write(&output_path, rendered)
+        .map_err(|e| SoloresError::FileOperationError {
+            operation: "write upgrade file".to_string(),
+            path: output_path.display().to_string(),
+            current_dir: std::env::current_dir().ok().map(|p| p.display().to_string()),
+            resolved_path: None,
+            source: e,
+            suggestion: Some("检查文件权限".to_string()),
+        })?;
+    
+    Ok(())
+}
@@ -0,0 +1,110 @@
+//! 版本化交易账户解析模块生成器
+//! 
+//! 生成 versioned_tx.rs，为 versioned-tx feature 提供 v0 消息（地址查找表）账户解析函数，
+//! 以及按指令从交易中提取类型化账户的 `extract_*_accounts`
+
+use crate::error::SoloresError;
+use minijinja::{Environment, Value};
+use std::fs;
+use std::path::Path;
+
+/// 生成版本化交易账户解析模块
+pub fn generate_versioned_tx_single_file(
+    env: &mut Environment,
+    src_dir: &Path,
+    context: &Value,
+    template_type: &str,
+) -> std::result::Result<(), SoloresError> {
+    let template_content = include_str!("../templates/common/versioned_tx.rs.jinja");
+    
+    let tmpl = env.template_from_str(template_content)
+        .map_err(|e| SoloresError::TemplateError {
+            template_name: Some("common/versioned_tx.rs.jinja".to_string()),
+            message: format!("模板解析失败: {}", e),
+            context: Some("解析versioned_tx模板".to_string()),
+        })?;
+    
+    let versioned_tx_context = minijinja::context! {
+        anchor => template_type == "anchor",
+        ..context.clone()
+    };
+    let rendered = tmpl.render(&versioned_tx_context)
+        .map_err(|e| SoloresError::TemplateError {
+            template_name: Some("common/versioned_tx.rs.jinja".to_string()),
+            message: format!("模板渲染失败: {}", e),
+            context: Some("渲染versioned_tx模板".to_string()),
+        })?;
+    
+    let output_path = src_dir.join("versioned_tx.rs");
+    fs::write(&output_path, rendered)
+        .map_err(|e| SoloresError::FileOperationError {
+            operation: "write versioned_tx file".to_string(),
+            path: output_path.display().to_string(),
+            current_dir: std::env::current_dir().ok().map(|p| p.display().to_string()),
+            resolved_path: None,
+            source: e,
+            suggestion: Some("检查文件权限".to_string()),
+        })?;
+    
+    Ok(())
+}
+
+#[cfg(test)]
+mod tests {
+    use crate::test_support::run_decode_test;
+
+    #[test]
+    fn lookup_table_accounts_are_resolved_in_runtime_order() {
+        let idl = r#"{"name": "treasury", "version": "0.1.0",
+            "metadata": {"name": "treasury", "version": "0.1.0", "spec": "0.1.0", "origin": "shank", "address": "Treasury11111111111111111111111111111111111"},
+            "instructions": [{"name": "withdraw", "discriminator": [1],
+                "accounts": [{"name": "vault", "isMut": true, "isSigner": false}, {"name": "payer", "isMut": true, "isSigner": true}, {"name": "destination", "isMut": true, "isSigner": false}],
+                "args": [{"name": "amount", "type": "u64"}]}]}"#;
+        let test_source = r#"
+use sol_treasury_interface::versioned_tx::*;
+use solana_message::compiled_instruction::CompiledInstruction;
+use solana_message::v0::LoadedAddresses;
+use solana_message::{legacy, v0, VersionedMessage};
+use solana_pubkey::Pubkey;
+use solana_transaction::versioned::VersionedTransaction;
+
+fn withdraw(accounts: Vec<u8>) -> CompiledInstruction {
+    CompiledInstruction { program_id_index: 1, accounts, data: [&[1u8][..], &5u64.to_le_bytes()].concat() }
+}
+
+#[test]
+fn loaded_addresses_follow_static_keys() {
+    let (payer, vault) = (Pubkey::new_unique(), Pubkey::new_unique());
+    let loaded = LoadedAddresses { writable: vec![Pubkey::new_unique()], readonly: vec![Pubkey::new_unique()] };
+    let message = VersionedMessage::V0(v0::Message {
+        account_keys: vec![payer, sol_treasury_interface::ID, vault],
+        // The second withdraw references a lookup table entry that was not loaded
+        instructions: vec![withdraw(vec![2, 0, 3]), withdraw(vec![2, 0, 5])],
+        ..Default::default()
+    });
+
+    assert_eq!(
+        resolve_accounts(&message, &loaded),
+        [payer, sol_treasury_interface::ID, vault, loaded.writable[0], loaded.readonly[0]]
+    );
+    let instructions = program_instructions(&message, &loaded, &sol_treasury_interface::ID);
+    assert_eq!(instructions.len(), 1);
+    assert_eq!(instructions[0].1, [vault, payer, loaded.writable[0]]);
+
+    let tx = VersionedTransaction { signatures: vec![], message };
+    let keys = extract_withdraw_accounts(&tx, &loaded);
+    assert_eq!(keys.len(), 1);
+    assert_eq!((keys[0].vault, keys[0].payer, keys[0].destination), (vault, payer, loaded.writable[0]));
+}
+
+#[test]
+fn legacy_messages_ignore_loaded_addresses() {
+    let keys = vec![Pubkey::new_unique(), sol_treasury_interface::ID];
+    let message = VersionedMessage::Legacy(legacy::Message { account_keys: keys.clone(), ..Default::default() });
+    let loaded = LoadedAddresses { writable: vec![Pubkey::new_unique()], readonly: vec![] };
+    assert_eq!(resolve_accounts(&message, &loaded), keys);
+}
+"#;
+        run_decode_test("versioned_tx", idl, &[], &["versioned-tx"], test_source);
+    }
+}
@@ -0,0 +1,109 @@
+//! Web 交易构建模块生成器
+//! 
+//! `--gen-web` 时生成 web.rs（web feature）：由指令构建未签名的 legacy / v0 交易并序列化为 base64，交给浏览器钱包适配器签名；wasm feature 另导出 wasm-bindgen 函数
+
+use crate::error::SoloresError;
+use minijinja::{Environment, Value};
+use std::fs;
+use std::path::Path;
+
+/// 生成 Web 交易构建模块
+pub fn generate_web_single_file(
+    env: &mut Environment,
+    src_dir: &Path,
+    context: &Value,
+) -> std::result::Result<(), SoloresError> {
+    let template_content = include_str!("../templates/common/web.rs.jinja");
+    
+    let tmpl = env.template_from_str(template_content)
+        .map_err(|e| SoloresError::TemplateError {
+            template_name: Some("common/web.rs.jinja".to_string()),
+            message: format!("模板解析失败: {}", e),
+            context: Some("解析web模板".to_string()),
+        })?;
+    
+    let rendered = tmpl.render(context)
+        .map_err(|e| SoloresError::TemplateError {
+            template_name: Some("common/web.rs.jinja".to_string()),
+            message: format!("模板渲染失败: {}", e),
+            context: Some("渲染web模板".to_string()),
+        })?;
+    
+    let output_path = src_dir.join("web.rs");
+    fs::write(&output_path, rendered)
+        .map_err(|e| SoloresError::FileOperationError {
+            operation: "write web file".to_string(),
+            path: output_path.display().to_string(),
+            current_dir: std::env::current_dir().ok().map(|p| p.display().to_string()),
+            resolved_path: None,
+            source: e,
+            suggestion: Some("检查文件权限".to_string()),
+        })?;
+    
+    Ok(())
+}
+
+#[cfg(test)]
+mod tests {
+    use std::fs;
+
+    use crate::test_support::{generate_crate, run_generated_test};
+
+    const POOL_IDL: &str = r#"{"address": "Poo1111111111111111111111111111111111111111", "metadata": {"name": "pool", "version": "0.1.0", "spec": "0.1.0"},
+        "instructions": [{"name": "deposit", "discriminator": [1, 2, 3, 4, 5, 6, 7, 8],
+            "accounts": [{"name": "pool", "writable": true}, {"name": "owner", "signer": true}],
+            "args": [{"name": "amount", "type": "u64"}]}]}"#;
+
+    /// base64 未签名交易解码后得到原指令的程序、账户与数据
+    #[test]
+    fn unsigned_transactions_decode_back_to_the_instruction() {
+        let crate_dir = generate_crate("web_round_trip", POOL_IDL, &["--gen-web"]);
+        // 生成的 crate 不直接依赖 bincode，测试中补充以反序列化消息
+        let manifest = crate_dir.join("Cargo.toml");
+        let mut contents = fs::read_to_string(&manifest).unwrap();
+        contents.push_str("\n[dev-dependencies.bincode]\nversion = \"1.3\"\n");
+        fs::write(&manifest, contents).unwrap();
+
+        let test_source = r#"
+use base64::prelude::{Engine as _, BASE64_STANDARD};
+use sol_pool_interface::instructions::{deposit_ix, DepositIxData, DepositKeys};
+use sol_pool_interface::parsers::instructions::ProgramInstruction;
+use sol_pool_interface::web::legacy_transaction_base64;
+use solana_hash::Hash;
+use solana_message::VersionedMessage;
+use solana_pubkey::Pubkey;
+
+#[test]
+fn legacy_transaction_round_trips() {
+    let (payer, pool, owner) = (Pubkey::new_unique(), Pubkey::new_unique(), Pubkey::new_unique());
+    let blockhash = Hash::new_from_array([7; 32]);
+    let ix = deposit_ix(DepositKeys { pool, owner }, DepositIxData::new(42)).unwrap();
+    let bytes = BASE64_STANDARD.decode(legacy_transaction_base64(&[ix.clone()], &payer, &blockhash)).unwrap();
+
+    // payer 与 owner 两个签名位，全部留空
+    assert_eq!(bytes[0], 2);
+    assert!(bytes[1..129].iter().all(|&byte| byte == 0));
+    let message: VersionedMessage = bincode::deserialize(&bytes[129..]).unwrap();
+    assert_eq!(message.header().num_required_signatures, 2);
+    assert_eq!(message.recent_blockhash(), &blockhash);
+
+    let keys = message.static_account_keys();
+    assert_eq!(keys[0], payer);
+    let [compiled] = message.instructions() else { panic!("expected one instruction") };
+    assert_eq!(keys[usize::from(compiled.program_id_index)], sol_pool_interface::ID);
+    let accounts: Vec<Pubkey> = compiled.accounts.iter().map(|&index| keys[usize::from(index)]).collect();
+    assert_eq!(accounts, [pool, owner]);
+    assert_eq!(compiled.data, ix.data);
+    assert!(message.is_maybe_writable(compiled.accounts[0] as usize, None));
+    assert!(message.is_signer(compiled.accounts[1] as usize));
+
+    match ProgramInstruction::try_parse(&compiled.data, &accounts).unwrap() {
+        ProgramInstruction::Deposit(keys, args) => {
+            assert_eq!((keys.pool, keys.owner, args.amount), (pool, owner, 42));
+        }
+    }
+}
+"#;
+        run_generated_test("web_round_trip", &crate_dir, &["web"], test_source);
+    }
+}
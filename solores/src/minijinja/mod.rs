@@ -8,6 +8,14 @@ pub mod generators;
 pub mod filters;
 pub mod context;
 pub mod utils;
+pub mod type_mapper;
+pub mod field_hints;
+pub mod compute_units;
+pub mod account_roles;
+pub mod zero_copy;
+pub mod externs;
+pub mod pubkey_crate;
+pub mod rust_version;
 
 // 主要导出
 pub use generator::MinijinjaTemplateGenerator;
\ No newline at end of file
@@ -0,0 +1,154 @@
+//! 生成代码使用的 Pubkey 来源
+//!
+//! 模板统一以 `solana_pubkey::` 引用 Pubkey。通过 `--pubkey-crate` 选择 solana-program
+//! 或自定义路径（如 SVM 分叉的 SDK crate）时，生成 `src/pubkey.rs` 别名模块重新导出
+//! 所选路径下的 Pubkey，并在渲染完成后把 src 中的 `solana_pubkey::` 改写为 `crate::pubkey::`，
+//! 生成的 crate 无需手动查找替换即可切换 Pubkey 类型。
+
+use std::fmt;
+use std::fs;
+use std::path::Path;
+use std::str::FromStr;
+
+use crate::error::SoloresError;
+
+/// 别名模块名，生成为 `src/pubkey.rs`
+pub const ALIAS_MODULE: &str = "pubkey";
+
+/// 模板中引用 Pubkey 所用的路径前缀
+const TEMPLATE_PATH: &str = "solana_pubkey::";
+
+/// `--pubkey-crate` 选项：`solana-pubkey`（默认）/ `solana-program` / 自定义 Rust 路径
+#[derive(Debug, Clone, Default, PartialEq, Eq)]
+pub enum PubkeyCrate {
+    #[default]
+    SolanaPubkey,
+    SolanaProgram,
+    /// 导出 `Pubkey`、`PubkeyError`、`ParsePubkeyError` 与 `pubkey!` 的模块路径
+    Path(String),
+}
+
+impl FromStr for PubkeyCrate {
+    type Err = String;
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        match s.trim() {
+            "solana-pubkey" | "solana_pubkey" => Ok(PubkeyCrate::SolanaPubkey),
+            "solana-program" | "solana_program" => Ok(PubkeyCrate::SolanaProgram),
+            path => {
+                let valid = path.split("::").all(|segment| {
+                    let mut chars = segment.chars();
+                    chars.next().is_some_and(|c| c.is_ascii_alphabetic() || c == '_')
+                        && chars.all(|c| c.is_ascii_alphanumeric() || c == '_')
+                });
+                if !valid || path.starts_with("crate") {
+                    return Err(format!(
+                        "Pubkey 来源 '{}' 应为 solana-pubkey、solana-program 或外部 crate 的模块路径（如 my_sdk::pubkey）",
+                        s
+                    ));
+                }
+                Ok(PubkeyCrate::Path(path.to_string()))
+            }
+        }
+    }
+}
+
+impl fmt::Display for PubkeyCrate {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            PubkeyCrate::SolanaPubkey => f.write_str("solana-pubkey"),
+            PubkeyCrate::SolanaProgram => f.write_str("solana-program"),
+            PubkeyCrate::Path(path) => f.write_str(path),
+        }
+    }
+}
+
+impl PubkeyCrate {
+    /// 别名模块重新导出的模块路径；默认来源不生成别名模块，返回 None
+    pub fn alias_path(&self) -> Option<&str> {
+        match self {
+            PubkeyCrate::SolanaPubkey => None,
+            // solana-program 2.x 的 pubkey 模块即 solana_pubkey，类型与指令等依赖一致
+            PubkeyCrate::SolanaProgram => Some("solana_program::pubkey"),
+            PubkeyCrate::Path(path) => Some(path),
+        }
+    }
+}
+
+/// 把 `src_dir` 下所有 Rust 文件（别名模块除外）中的 `solana_pubkey::` 改写为 `crate::pubkey::`
+pub fn rewrite_pubkey_paths(src_dir: &Path) -> Result<(), SoloresError> {
+    let entries = fs::read_dir(src_dir)
+        .map_err(|e| SoloresError::file_operation_error("读取生成目录", src_dir.display().to_string(), e))?;
+    for entry in entries {
+        let path = entry
+            .map_err(|e| SoloresError::file_operation_error("读取生成目录", src_dir.display().to_string(), e))?
+            .path();
+        if path.is_dir() {
+            rewrite_pubkey_paths(&path)?;
+            continue;
+        }
+        if path.extension().is_none_or(|ext| ext != "rs") || is_alias_module(&path) {
+            continue;
+        }
+        let content = fs::read_to_string(&path)
+            .map_err(|e| SoloresError::file_operation_error("读取生成文件", path.display().to_string(), e))?;
+        let rewritten = rewrite_source(&content);
+        if rewritten != content {
+            fs::write(&path, rewritten)
+                .map_err(|e| SoloresError::file_operation_error("改写Pubkey路径", path.display().to_string(), e))?;
+        }
+    }
+    Ok(())
+}
+
+fn is_alias_module(path: &Path) -> bool {
+    path.file_stem().is_some_and(|stem| stem == ALIAS_MODULE)
+        && path.parent().and_then(Path::file_name).is_some_and(|dir| dir == "src")
+}
+
+/// 只改写独立的 `solana_pubkey::` 路径，不影响 `my_solana_pubkey::` 等更长的标识符
+fn rewrite_source(source: &str) -> String {
+    let replacement = format!("crate::{}::", ALIAS_MODULE);
+    let mut out = String::with_capacity(source.len());
+    let mut rest = source;
+    while let Some(pos) = rest.find(TEMPLATE_PATH) {
+        let standalone = !rest[..pos]
+            .chars()
+            .next_back()
+            .is_some_and(|c| c.is_ascii_alphanumeric() || c == '_' || c == ':');
+        out.push_str(&rest[..pos]);
+        out.push_str(if standalone { &replacement } else { TEMPLATE_PATH });
+        rest = &rest[pos + TEMPLATE_PATH.len()..];
+    }
+    out.push_str(rest);
+    out
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn parses_pubkey_crate_and_rewrites_paths() {
+        assert_eq!("solana-pubkey".parse::<PubkeyCrate>().unwrap().alias_path(), None);
+        assert_eq!(
+            "solana-program".parse::<PubkeyCrate>().unwrap().alias_path(),
+            Some("solana_program::pubkey")
+        );
+        assert_eq!(
+            "eclipse_sdk::pubkey".parse::<PubkeyCrate>().unwrap(),
+            PubkeyCrate::Path("eclipse_sdk::pubkey".to_string())
+        );
+        assert!("my-sdk".parse::<PubkeyCrate>().is_err());
+        assert!("crate::pubkey".parse::<PubkeyCrate>().is_err());
+        assert!("sdk::".parse::<PubkeyCrate>().is_err());
+
+        let source = "use solana_pubkey::Pubkey;\nconst ID: solana_pubkey::Pubkey = solana_pubkey::pubkey!(\"x\");\n\
+                      type A = my_solana_pubkey::Pubkey;\ntype B = ::solana_pubkey::Pubkey;\n";
+        assert_eq!(
+            rewrite_source(source),
+            "use crate::pubkey::Pubkey;\nconst ID: crate::pubkey::Pubkey = crate::pubkey::pubkey!(\"x\");\n\
+             type A = my_solana_pubkey::Pubkey;\ntype B = ::solana_pubkey::Pubkey;\n"
+        );
+    }
+}
@@ -0,0 +1,121 @@
+//! 生成代码的最低 Rust 版本（MSRV）
+//!
+//! `--min-rust-version` 写入生成 crate 的 `rust-version`，模板按 [`RustFeatures`]
+//! 为较新的语法与标准库 API 选择等价的旧写法；未指定时使用全部特性。
+//! 生成 crate 依赖的 solana-* 2.x crate 没有兼容更旧工具链的版本，低于其最低版本的 MSRV 直接拒绝。
+
+use std::fmt;
+use std::str::FromStr;
+
+use serde::Serialize;
+
+/// 生成 crate 依赖的 solana-* 2.x crate 要求的最低版本（solana-program 2.3 的 rust-version），
+/// 低于它的 MSRV 无法通过选择旧依赖版本满足
+pub const MIN_SUPPORTED_RUST_VERSION: RustVersion = RustVersion { major: 1, minor: 79, patch: 0 };
+
+/// `major.minor[.patch]` 形式的 Rust 版本
+#[derive(Debug, Clone, Copy, PartialEq, Eq, PartialOrd, Ord, Hash)]
+pub struct RustVersion {
+    pub major: u32,
+    pub minor: u32,
+    pub patch: u32,
+}
+
+impl RustVersion {
+    const fn at_least(&self, minor: u32) -> bool {
+        self.major > 1 || self.minor >= minor
+    }
+
+    /// 该版本可用的语言与标准库特性
+    pub fn features(&self) -> RustFeatures {
+        RustFeatures {
+            let_else: self.at_least(65),
+            is_some_and: self.at_least(70),
+            once_lock: self.at_least(70),
+            div_ceil: self.at_least(73),
+            async_fn_in_trait: self.at_least(75),
+        }
+    }
+}
+
+impl FromStr for RustVersion {
+    type Err = String;
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        let invalid = || format!("Rust 版本 '{}' 格式应为 major.minor[.patch]，如 1.68", s);
+        let mut parts = s.trim().split('.').map(|part| part.parse::<u32>().map_err(|_| invalid()));
+        let major = parts.next().ok_or_else(invalid)??;
+        let minor = parts.next().ok_or_else(invalid)??;
+        let patch = parts.next().transpose()?.unwrap_or(0);
+        if parts.next().is_some() {
+            return Err(invalid());
+        }
+        let version = RustVersion { major, minor, patch };
+        if version < MIN_SUPPORTED_RUST_VERSION {
+            return Err(format!(
+                "Rust 版本 {} 低于生成 crate 依赖的 solana-* 2.x crate 要求的最低版本 {}",
+                version, MIN_SUPPORTED_RUST_VERSION
+            ));
+        }
+        Ok(version)
+    }
+}
+
+impl fmt::Display for RustVersion {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        if self.patch == 0 {
+            write!(f, "{}.{}", self.major, self.minor)
+        } else {
+            write!(f, "{}.{}.{}", self.major, self.minor, self.patch)
+        }
+    }
+}
+
+/// 模板可使用的较新特性，渲染为上下文中的 `rust`
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize)]
+pub struct RustFeatures {
+    /// `let ... else`（1.65）
+    pub let_else: bool,
+    /// `Option::is_some_and` / `Result::is_ok_and`（1.70）
+    pub is_some_and: bool,
+    /// `std::sync::OnceLock`（1.70）
+    pub once_lock: bool,
+    /// 无符号整数的 `div_ceil` / `next_multiple_of`（1.73）
+    pub div_ceil: bool,
+    /// trait 方法返回 `impl Future`（1.75）
+    pub async_fn_in_trait: bool,
+}
+
+impl RustFeatures {
+    /// 未指定 MSRV 时的特性集合
+    pub const LATEST: RustFeatures = RustFeatures {
+        let_else: true,
+        is_some_and: true,
+        once_lock: true,
+        div_ceil: true,
+        async_fn_in_trait: true,
+    };
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn parses_versions_and_selects_features() {
+        let version: RustVersion = "1.79".parse().unwrap();
+        assert_eq!(version.to_string(), "1.79");
+        assert_eq!("1.81.1".parse::<RustVersion>().unwrap().to_string(), "1.81.1");
+        // solana-* 2.x 依赖不支持更旧的工具链
+        let err = "1.68".parse::<RustVersion>().unwrap_err();
+        assert!(err.contains("solana-*") && err.contains("1.79"), "{}", err);
+        assert!("1".parse::<RustVersion>().is_err());
+        assert!("1.70.0.1".parse::<RustVersion>().is_err());
+
+        assert_eq!(version.features(), RustFeatures::LATEST);
+        let features = RustVersion { major: 1, minor: 68, patch: 0 }.features();
+        assert!(features.let_else);
+        assert!(!features.is_some_and && !features.div_ceil && !features.async_fn_in_trait);
+        assert_eq!(RustVersion { major: 1, minor: 75, patch: 0 }.features(), RustFeatures::LATEST);
+    }
+}
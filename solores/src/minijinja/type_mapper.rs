@@ -0,0 +1,352 @@
+//! 字段类型映射
+//!
+//! IDL 字段类型 → Rust 类型路径的唯一实现。账户、指令、事件、类型定义以及
+//! `type_path` 过滤器都经由此处，保证任意嵌套容器在各模块中生成一致的代码。
+
+use convert_case::{Case, Casing};
+use serde::de::DeserializeOwned;
+
+use crate::idl_format::{anchor_idl::AnchorFieldType, non_anchor_idl::NonAnchorFieldType};
+
+/// 与IDL格式无关的字段类型结构视图
+pub enum TypeShape<'a, T> {
+    /// 基础类型或类型名字符串
+    Named(&'a str),
+    /// 已定义类型
+    Defined(&'a str),
+    /// 定长数组
+    Array(&'a T, usize),
+    /// 变长向量
+    Vec(&'a T),
+    /// 可选值（Option 与 COption 在 Rust 中同为 Option）
+    Option(&'a T),
+    /// 哈希映射
+    HashMap(&'a T, &'a T),
+    /// `{"kind": ..., "params": ...}` 形式的复合类型
+    Complex(&'a str, Option<&'a serde_json::Value>),
+}
+
+/// IDL字段类型到结构视图的转换
+pub trait IdlTypeShape: DeserializeOwned + Sized {
+    fn shape(&self) -> TypeShape<'_, Self>;
+}
+
+impl IdlTypeShape for AnchorFieldType {
+    fn shape(&self) -> TypeShape<'_, Self> {
+        match self {
+            AnchorFieldType::Basic(name) | AnchorFieldType::PrimitiveOrPubkey(name) => TypeShape::Named(name),
+            AnchorFieldType::defined(name) => TypeShape::Defined(name),
+            AnchorFieldType::array(inner, size) => TypeShape::Array(inner, *size),
+            AnchorFieldType::vec(inner) => TypeShape::Vec(inner),
+            AnchorFieldType::option(inner) | AnchorFieldType::coption(inner) => TypeShape::Option(inner),
+            AnchorFieldType::Complex { kind, params } => {
+                TypeShape::Complex(kind, params.as_ref().and_then(|p| p.first()))
+            }
+        }
+    }
+}
+
+impl IdlTypeShape for NonAnchorFieldType {
+    fn shape(&self) -> TypeShape<'_, Self> {
+        match self {
+            NonAnchorFieldType::Basic(name) => TypeShape::Named(name),
+            NonAnchorFieldType::Defined { defined } => TypeShape::Defined(defined),
+            NonAnchorFieldType::Array { array: (inner, size) } => TypeShape::Array(inner, *size),
+            NonAnchorFieldType::Vec { vec } => TypeShape::Vec(vec),
+            NonAnchorFieldType::Option { option } | NonAnchorFieldType::COption { coption: option } => {
+                TypeShape::Option(option)
+            }
+            NonAnchorFieldType::HashMap { key, value } => TypeShape::HashMap(key, value),
+            NonAnchorFieldType::Complex { kind, params } => {
+                TypeShape::Complex(kind, params.as_ref().and_then(|p| p.first()))
+            }
+        }
+    }
+}
+
+/// 将IDL字段类型递归映射为完整路径的Rust类型
+pub fn map_field_type<T: IdlTypeShape>(field_type: &T) -> String {
+    match field_type.shape() {
+        TypeShape::Named(name) => map_type_name(name),
+        TypeShape::Defined(name) => map_type_name(name),
+        TypeShape::Array(inner, size) => format!("[{}; {}]", map_field_type(inner), size),
+        TypeShape::Vec(inner) => format!("std::vec::Vec<{}>", map_field_type(inner)),
+        TypeShape::Option(inner) => format!("std::option::Option<{}>", map_field_type(inner)),
+        TypeShape::HashMap(key, value) => format!(
+            "std::collections::HashMap<{}, {}>",
+            map_field_type(key),
+            map_field_type(value)
+        ),
+        TypeShape::Complex(kind, params) => map_complex_type::<T>(kind, params),
+    }
+}
+
+/// `{"kind": "vec", "params": [...]}` 形式：按参数解析内部类型后复用同一映射
+fn map_complex_type<T: IdlTypeShape>(kind: &str, params: Option<&serde_json::Value>) -> String {
+    let params: Vec<serde_json::Value> = match params {
+        Some(serde_json::Value::Array(values)) => values.clone(),
+        Some(value) => vec![value.clone()],
+        None => Vec::new(),
+    };
+    let param_type = |index: usize| {
+        params
+            .get(index)
+            .and_then(|value| serde_json::from_value::<T>(value.clone()).ok())
+            .map(|inner| map_field_type(&inner))
+    };
+
+    let mapped = match kind {
+        "vec" => param_type(0).map(|inner| format!("std::vec::Vec<{}>", inner)),
+        "option" | "coption" => param_type(0).map(|inner| format!("std::option::Option<{}>", inner)),
+        "array" => param_type(0).zip(params.get(1).and_then(|size| size.as_u64()))
+            .map(|(inner, size)| format!("[{}; {}]", inner, size)),
+        "hashMap" => param_type(0).zip(param_type(1))
+            .map(|(key, value)| format!("std::collections::HashMap<{}, {}>", key, value)),
+        _ => None,
+    };
+
+    mapped.unwrap_or_else(|| {
+        log::warn!("⚠️ 无法解析复合类型 '{}' 的参数 {:?}，按类型名处理", kind, params);
+        map_type_name(kind)
+    })
+}
+
+/// 将类型名字符串映射为完整路径的Rust类型
+///
+/// 同时接受IDL类型名（`u64`、`publicKey`、`MyStruct`）和内联的Rust语法
+/// （`[u64; 4]`、`Vec<Pubkey>`、`SmallVec<u8, Pubkey>`），对已映射的结果幂等。
+pub fn map_type_name(name: &str) -> String {
+    let name = name.trim();
+
+    if let Some(primitive) = map_primitive(name) {
+        return primitive.to_string();
+    }
+
+    if let Some(inner) = name.strip_prefix('[').and_then(|s| s.strip_suffix(']')) {
+        if let Some((element, size)) = inner.rsplit_once(';') {
+            let size = size.trim();
+            if !size.is_empty() && size.chars().all(|c| c.is_ascii_digit()) {
+                return format!("[{}; {}]", map_type_name(element), size);
+            }
+        }
+    }
+
+    if let Some((base, args)) = split_generic(name) {
+        match (base, args.as_slice()) {
+            // SmallVec<长度类型, T> 生成为 Vec<T>，长度前缀由字段级编解码 crate::small_vec 处理
+            ("SmallVec", [_, inner]) | ("Vec" | "std::vec::Vec", [inner]) => {
+                return format!("std::vec::Vec<{}>", map_type_name(inner));
+            }
+            ("Option" | "std::option::Option" | "COption", [inner]) => {
+                return format!("std::option::Option<{}>", map_type_name(inner));
+            }
+            ("HashMap" | "std::collections::HashMap", [key, value]) => {
+                return format!(
+                    "std::collections::HashMap<{}, {}>",
+                    map_type_name(key),
+                    map_type_name(value)
+                );
+            }
+            _ => {}
+        }
+    }
+
+    if name.contains("::") || name.contains('<') {
+        // 已带路径或无法识别的泛型，保持原样
+        return name.to_string();
+    }
+
+    format!("crate::types::{}", name.to_case(Case::Pascal))
+}
+
+/// `SmallVec<长度类型, T>` 的长度前缀类型；只认 u8 / u16 / u32 前缀
+pub fn small_vec_len_type(name: &str) -> Option<&'static str> {
+    match split_generic(name.trim())? {
+        ("SmallVec", args) if args.len() == 2 => ["u8", "u16", "u32"].into_iter().find(|len| *len == args[0]),
+        _ => None,
+    }
+}
+
+/// IDL基础类型到Rust类型
+fn map_primitive(name: &str) -> Option<&'static str> {
+    let mapped = match name {
+        "u8" => "u8",
+        "i8" => "i8",
+        "u16" => "u16",
+        "i16" => "i16",
+        "u32" => "u32",
+        "i32" => "i32",
+        "u64" => "u64",
+        "i64" => "i64",
+        "u128" => "u128",
+        "i128" => "i128",
+        "bool" => "bool",
+        "f32" => "f32",
+        "f64" => "f64",
+        "string" | "String" => "std::string::String",
+        "publicKey" | "pubkey" | "Pubkey" => "solana_pubkey::Pubkey",
+        "bytes" => "std::vec::Vec<u8>",
+        _ => return None,
+    };
+    Some(mapped)
+}
+
+/// 拆分 `Base<A, B>` 为基础名与顶层泛型参数
+pub(crate) fn split_generic(name: &str) -> Option<(&str, Vec<&str>)> {
+    let open = name.find('<')?;
+    let inner = name.strip_suffix('>')?.get(open + 1..)?;
+    let mut args = Vec::new();
+    let mut depth = 0usize;
+    let mut start = 0;
+    for (i, c) in inner.char_indices() {
+        match c {
+            '<' | '[' | '(' => depth += 1,
+            '>' | ']' | ')' => depth = depth.checked_sub(1)?,
+            ',' if depth == 0 => {
+                args.push(inner[start..i].trim());
+                start = i + 1;
+            }
+            _ => {}
+        }
+    }
+    if depth != 0 {
+        return None;
+    }
+    args.push(inner[start..].trim());
+    Some((name[..open].trim(), args))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn anchor(json: &str) -> String {
+        let field_type: AnchorFieldType = serde_json::from_str(json).unwrap();
+        map_field_type(&field_type)
+    }
+
+    fn non_anchor(json: &str) -> String {
+        let field_type: NonAnchorFieldType = serde_json::from_str(json).unwrap();
+        map_field_type(&field_type)
+    }
+
+    /// 两种IDL格式对同一JSON类型必须生成相同的Rust类型
+    fn both(json: &str) -> String {
+        let mapped = anchor(json);
+        assert_eq!(mapped, non_anchor(json), "Anchor/NonAnchor 映射不一致: {}", json);
+        mapped
+    }
+
+    #[test]
+    fn test_primitives() {
+        assert_eq!(both(r#""u64""#), "u64");
+        assert_eq!(both(r#""i128""#), "i128");
+        assert_eq!(both(r#""bool""#), "bool");
+        assert_eq!(both(r#""string""#), "std::string::String");
+        assert_eq!(both(r#""publicKey""#), "solana_pubkey::Pubkey");
+        assert_eq!(both(r#""pubkey""#), "solana_pubkey::Pubkey");
+        assert_eq!(both(r#""bytes""#), "std::vec::Vec<u8>");
+    }
+
+    #[test]
+    fn test_defined() {
+        assert_eq!(both(r#"{"defined": "Fees"}"#), "crate::types::Fees");
+        assert_eq!(both(r#"{"defined": {"name": "Fees"}}"#), "crate::types::Fees");
+        assert_eq!(both(r#"{"defined": "[u64; 8]"}"#), "[u64; 8]");
+        assert_eq!(both(r#"{"defined": "string"}"#), "std::string::String");
+    }
+
+    #[test]
+    fn test_nested_arrays() {
+        assert_eq!(both(r#"{"array": [{"array": ["u64", 4]}, 2]}"#), "[[u64; 4]; 2]");
+        assert_eq!(
+            both(r#"{"array": [{"array": [{"defined": "Tick"}, 3]}, 2]}"#),
+            "[[crate::types::Tick; 3]; 2]"
+        );
+        assert_eq!(both(r#"{"array": [{"defined": "Fees"}, 5]}"#), "[crate::types::Fees; 5]");
+        assert_eq!(
+            both(r#"{"array": [{"option": "publicKey"}, 3]}"#),
+            "[std::option::Option<solana_pubkey::Pubkey>; 3]"
+        );
+    }
+
+    #[test]
+    fn test_nested_vecs_and_options() {
+        assert_eq!(
+            both(r#"{"vec": {"array": ["publicKey", 5]}}"#),
+            "std::vec::Vec<[solana_pubkey::Pubkey; 5]>"
+        );
+        assert_eq!(
+            both(r#"{"option": {"vec": {"defined": "Fees"}}}"#),
+            "std::option::Option<std::vec::Vec<crate::types::Fees>>"
+        );
+        assert_eq!(
+            both(r#"{"vec": {"vec": "u8"}}"#),
+            "std::vec::Vec<std::vec::Vec<u8>>"
+        );
+        assert_eq!(
+            both(r#"{"option": {"option": "u64"}}"#),
+            "std::option::Option<std::option::Option<u64>>"
+        );
+        assert_eq!(
+            both(r#"{"vec": {"option": {"array": [{"defined": "Fees"}, 2]}}}"#),
+            "std::vec::Vec<std::option::Option<[crate::types::Fees; 2]>>"
+        );
+        assert_eq!(
+            both(r#"{"coption": "publicKey"}"#),
+            "std::option::Option<solana_pubkey::Pubkey>"
+        );
+    }
+
+    #[test]
+    fn test_hash_map() {
+        assert_eq!(
+            non_anchor(r#"{"hashMap": ["publicKey", {"vec": {"defined": "Fees"}}]}"#),
+            "std::collections::HashMap<solana_pubkey::Pubkey, std::vec::Vec<crate::types::Fees>>"
+        );
+    }
+
+    #[test]
+    fn test_complex_kind_params() {
+        assert_eq!(both(r#"{"kind": "vec", "params": ["u64"]}"#), "std::vec::Vec<u64>");
+        assert_eq!(
+            both(r#"{"kind": "option", "params": [{"defined": "Fees"}]}"#),
+            "std::option::Option<crate::types::Fees>"
+        );
+        assert_eq!(both(r#"{"kind": "array", "params": ["u8", 32]}"#), "[u8; 32]");
+    }
+
+    #[test]
+    fn test_type_name_strings() {
+        assert_eq!(map_type_name("SmallVec<u8, Pubkey>"), "std::vec::Vec<solana_pubkey::Pubkey>");
+        assert_eq!(small_vec_len_type("SmallVec<u16,u8>"), Some("u16"));
+        assert_eq!(small_vec_len_type("SmallVec<u64, u8>"), None);
+        assert_eq!(small_vec_len_type("Vec<u8>"), None);
+        assert_eq!(
+            map_type_name("Vec<[Pubkey; 5]>"),
+            "std::vec::Vec<[solana_pubkey::Pubkey; 5]>"
+        );
+        assert_eq!(
+            map_type_name("Option<Vec<Fees>>"),
+            "std::option::Option<std::vec::Vec<crate::types::Fees>>"
+        );
+        assert_eq!(map_type_name("[[u64; 4]; 2]"), "[[u64; 4]; 2]");
+        assert_eq!(
+            map_type_name("HashMap<Pubkey, [u8; 2]>"),
+            "std::collections::HashMap<solana_pubkey::Pubkey, [u8; 2]>"
+        );
+    }
+
+    #[test]
+    fn test_mapping_is_idempotent() {
+        for json in [
+            r#"{"vec": {"array": ["publicKey", 5]}}"#,
+            r#"{"option": {"vec": {"defined": "Fees"}}}"#,
+            r#"{"array": [{"array": [{"defined": "Tick"}, 3]}, 2]}"#,
+            r#""bytes""#,
+        ] {
+            let mapped = both(json);
+            assert_eq!(map_type_name(&mapped), mapped);
+        }
+    }
+}
@@ -2,11 +2,25 @@
 //! 
 //! 提供字段查找、大小计算等实用工具函数
 
-use crate::idl_format::{IdlFormatEnum, anchor_idl::*, non_anchor_idl::*};
+use crate::idl_format::{IdlFormatEnum, account_variants::ResolvedVariants, anchor_idl::*, arg_encoding::ArgEncoding, enum_tags::EnumTagLayout, non_anchor_idl::*};
+use convert_case::{Case, Casing};
 use minijinja::Value;
 use log;
 use super::builders::anchor;
-use std::collections::HashMap;
+use super::type_mapper::{self, IdlTypeShape};
+use std::collections::{HashMap, HashSet};
+
+/// 结构体形态：字段名均为位置序号时为 "tuple"，无字段为 "unit"，否则为 "named"
+pub fn struct_shape<'a>(field_names: impl IntoIterator<Item = &'a str>) -> &'static str {
+    let mut field_names = field_names.into_iter().peekable();
+    if field_names.peek().is_none() {
+        "unit"
+    } else if field_names.all(|name| !name.is_empty() && name.chars().all(|c| c.is_ascii_digit())) {
+        "tuple"
+    } else {
+        "named"
+    }
+}
 
 /// 从types中查找同名类型的字段（解决IDL中账户定义缺少字段的问题）
 pub fn find_fields_from_types(account_name: &str, idl_enum: &IdlFormatEnum) -> Option<Vec<Value>> {
@@ -41,41 +55,17 @@ pub fn calculate_anchor_account_packed_size(account: &AnchorAccount, idl_enum: &
     
     log::debug!("🧮 计算账户 {} 的大小，开始大小: {} (discriminator)", account.name, size);
     
-    // 统一字段获取逻辑：优先使用直接字段，否则从字段分配获取
-    if let Some(fields) = &account.fields {
-        log::debug!("  🎯 使用直接字段 ({} 个)", fields.len());
-        for field in fields {
-            let field_size = calculate_anchor_field_size_recursive(&field.field_type, idl_enum, &mut cache);
-            log::debug!("  📐 字段 {} ({:?}): {} 字节", field.name, field.field_type, field_size);
-            size += field_size;
-        }
-    } else {
-        log::debug!("  🔍 账户无直接字段，尝试从types中查找");
-        // 尝试从当前IDL的types中查找同名类型的字段
-        if find_fields_from_types(&account.name, idl_enum).is_some() {
-            log::debug!("  🎯 从types获取字段");
-            // 这里需要计算实际的字段大小，而不是Value类型
-            // 我们需要访问IDL数据来获取实际的字段类型信息
-            size += calculate_fields_size_from_types(&account.name, idl_enum, &mut cache);
-        } else {
-            log::debug!("  ❌ 无法获取字段分配，只有 discriminator");
-        }
+    // 字段已在解析阶段统一（内联type或引用同名types）
+    for field in account.fields.iter().flatten() {
+        let field_size = calculate_anchor_field_size_recursive(&field.field_type, idl_enum, &mut cache);
+        log::debug!("  📐 字段 {} ({:?}): {} 字节", field.name, field.field_type, field_size);
+        size += field_size;
     }
     
     log::debug!("🏁 账户 {} 总大小: {} 字节", account.name, size);
     size
 }
 
-/// 从types中计算字段大小（递归版本）
-pub fn calculate_fields_size_from_types(
-    type_name: &str, 
-    idl_enum: &IdlFormatEnum, 
-    cache: &mut HashMap<String, usize>
-) -> usize {
-    // 直接使用递归函数
-    calculate_defined_type_size_recursive(type_name, idl_enum, cache)
-}
-
 
 /// 计算自定义类型大小（递归查找IDL中的类型定义）
 pub fn calculate_defined_type_size_recursive(
@@ -132,6 +122,9 @@ pub fn lookup_type_size_from_idl(
         crate::idl_format::IdlFormatEnum::NonAnchor(non_anchor_idl) => {
             if let Some(types) = &non_anchor_idl.types {
                 if let Some(type_def) = types.iter().find(|t| t.name == type_name) {
+                    if let Some(size) = non_anchor_idl.c_type_size(type_name) {
+                        return Some(size);
+                    }
                     return calculate_non_anchor_kind_size(&type_def.type_def, idl_enum, cache);
                 }
             }
@@ -212,6 +205,11 @@ pub fn calculate_anchor_field_size_recursive(
             let inner_size = calculate_anchor_field_size_recursive(inner_type, idl_enum, cache);
             1 + inner_size
         },
+        AnchorFieldType::coption(inner_type) => {
+            // COption: 4字节标签 + 始终占位的载荷
+            let inner_size = calculate_anchor_field_size_recursive(inner_type, idl_enum, cache);
+            4 + inner_size
+        },
         AnchorFieldType::defined(type_name) => {
             calculate_defined_type_size_recursive(type_name, idl_enum, cache)
         },
@@ -247,6 +245,11 @@ pub fn calculate_non_anchor_field_size_recursive(
             let inner_size = calculate_non_anchor_field_size_recursive(option, idl_enum, cache);
             1 + inner_size
         },
+        NonAnchorFieldType::COption { coption } => {
+            // COption: 4字节标签 + 始终占位的载荷
+            let inner_size = calculate_non_anchor_field_size_recursive(coption, idl_enum, cache);
+            4 + inner_size
+        },
         NonAnchorFieldType::Defined { defined } => {
             calculate_defined_type_size_recursive(defined, idl_enum, cache)
         },
@@ -338,6 +341,12 @@ pub fn parse_and_calculate_element_type(
             // 复杂类型对象，如 {"defined": "TypeName"} 或 {"array": [...]}
             if let Some(defined_name) = obj.get("defined").and_then(|v| v.as_str()) {
                 calculate_defined_type_size_recursive(defined_name, idl_enum, cache)
+            } else if let Some(inner) = obj.get("option") {
+                1 + parse_and_calculate_element_type(inner, idl_enum, cache)
+            } else if let Some(inner) = obj.get("coption") {
+                4 + parse_and_calculate_element_type(inner, idl_enum, cache)
+            } else if obj.contains_key("vec") {
+                4
             } else if let Some(array_data) = obj.get("array") {
                 if let Some(array_vec) = array_data.as_array() {
                     calculate_complex_type_size("array", Some(array_vec), idl_enum, cache)
@@ -423,4 +432,699 @@ pub fn calculate_non_anchor_account_packed_size(account: &NonAnchorAccount, idl_
     
     log::debug!("🏁 NonAnchor账户 {} 总大小: {} 字节", account.name, size);
     size
-}
\ No newline at end of file
+}
+
+/// 序列化长度范围的最大展开深度，防止自引用类型无限递归
+const LEN_RANGE_MAX_DEPTH: usize = 16;
+
+/// Borsh 序列化长度范围；`max` 为 None 表示无上限（Vec / String 等）
+///
+/// Option 的前导标签字节只在此处处理：None 时只占 1 字节，Some 时为 1 + 载荷，
+/// 嵌套 Option 逐层叠加
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct LenRange {
+    pub min: usize,
+    pub max: Option<usize>,
+}
+
+impl LenRange {
+    pub const fn fixed(len: usize) -> Self {
+        Self { min: len, max: Some(len) }
+    }
+
+    /// 至少 `min` 字节、没有上限
+    pub const fn unbounded(min: usize) -> Self {
+        Self { min, max: None }
+    }
+
+    pub fn is_fixed(&self) -> bool {
+        self.max == Some(self.min)
+    }
+
+    /// 顺序拼接
+    pub fn then(self, next: Self) -> Self {
+        Self {
+            min: self.min + next.min,
+            max: self.max.zip(next.max).map(|(a, b)| a + b),
+        }
+    }
+
+    /// 两种布局取其一（枚举变体）
+    pub fn either(self, other: Self) -> Self {
+        Self {
+            min: self.min.min(other.min),
+            max: self.max.zip(other.max).map(|(a, b)| a.max(b)),
+        }
+    }
+
+    pub fn repeat(self, count: usize) -> Self {
+        Self {
+            min: self.min * count,
+            max: self.max.map(|max| max * count),
+        }
+    }
+
+    /// `Option<T>`：1 字节标签，None 时没有载荷
+    pub fn option(inner: Self) -> Self {
+        Self { min: 1, max: inner.max.map(|max| 1 + max) }
+    }
+
+    /// `COption<T>`：4 字节标签，载荷始终占位
+    pub fn coption(inner: Self) -> Self {
+        Self::fixed(4).then(inner)
+    }
+}
+
+fn sum_len_ranges(ranges: impl IntoIterator<Item = LenRange>) -> LenRange {
+    ranges.into_iter().fold(LenRange::fixed(0), LenRange::then)
+}
+
+/// 基础类型长度；string / bytes 为 4 字节长度前缀加变长内容
+fn basic_len_range(type_name: &str) -> LenRange {
+    match type_name {
+        "string" | "bytes" => LenRange::unbounded(4),
+        _ => LenRange::fixed(calculate_basic_type_size(type_name)),
+    }
+}
+
+/// Anchor 字段的序列化长度范围
+pub fn anchor_len_range(field_type: &AnchorFieldType, idl_enum: &IdlFormatEnum) -> LenRange {
+    anchor_len_range_at(field_type, idl_enum, 0)
+}
+
+fn anchor_len_range_at(field_type: &AnchorFieldType, idl_enum: &IdlFormatEnum, depth: usize) -> LenRange {
+    if depth > LEN_RANGE_MAX_DEPTH {
+        return LenRange::unbounded(0);
+    }
+    match field_type {
+        AnchorFieldType::Basic(type_name) | AnchorFieldType::PrimitiveOrPubkey(type_name) => basic_len_range(type_name),
+        AnchorFieldType::array(inner, len) => anchor_len_range_at(inner, idl_enum, depth + 1).repeat(*len),
+        AnchorFieldType::vec(_) => LenRange::unbounded(4),
+        AnchorFieldType::option(inner) => LenRange::option(anchor_len_range_at(inner, idl_enum, depth + 1)),
+        AnchorFieldType::coption(inner) => LenRange::coption(anchor_len_range_at(inner, idl_enum, depth + 1)),
+        AnchorFieldType::defined(type_name) => defined_len_range(type_name, idl_enum, depth + 1),
+        AnchorFieldType::Complex { kind, params } => complex_len_range(kind, params.as_deref(), idl_enum, depth + 1),
+    }
+}
+
+/// NonAnchor 字段的序列化长度范围
+pub fn non_anchor_len_range(field_type: &NonAnchorFieldType, idl_enum: &IdlFormatEnum) -> LenRange {
+    non_anchor_len_range_at(field_type, idl_enum, 0)
+}
+
+fn non_anchor_len_range_at(field_type: &NonAnchorFieldType, idl_enum: &IdlFormatEnum, depth: usize) -> LenRange {
+    if depth > LEN_RANGE_MAX_DEPTH {
+        return LenRange::unbounded(0);
+    }
+    match field_type {
+        NonAnchorFieldType::Basic(type_name) => basic_len_range(type_name),
+        NonAnchorFieldType::Array { array: (inner, len) } => non_anchor_len_range_at(inner, idl_enum, depth + 1).repeat(*len),
+        NonAnchorFieldType::Vec { .. } | NonAnchorFieldType::HashMap { .. } => LenRange::unbounded(4),
+        NonAnchorFieldType::Option { option } => LenRange::option(non_anchor_len_range_at(option, idl_enum, depth + 1)),
+        NonAnchorFieldType::COption { coption } => LenRange::coption(non_anchor_len_range_at(coption, idl_enum, depth + 1)),
+        NonAnchorFieldType::Defined { defined } => defined_len_range(defined, idl_enum, depth + 1),
+        NonAnchorFieldType::Complex { kind, params } => complex_len_range(kind, params.as_deref(), idl_enum, depth + 1),
+    }
+}
+
+/// 自定义类型长度；未知类型不限制长度
+fn defined_len_range(type_name: &str, idl_enum: &IdlFormatEnum, depth: usize) -> LenRange {
+    if let Some(len_type) = super::type_mapper::small_vec_len_type(type_name) {
+        return LenRange::unbounded(calculate_basic_type_size(len_type));
+    }
+    match idl_enum {
+        IdlFormatEnum::Anchor(idl) => {
+            let Some(kind) = idl.types.iter().flatten().find(|t| t.name == type_name).and_then(|t| t.kind.as_ref()) else {
+                return LenRange::unbounded(0);
+            };
+            let fields_len = |fields: &[AnchorField]| {
+                sum_len_ranges(fields.iter().map(|f| anchor_len_range_at(&f.field_type, idl_enum, depth + 1)))
+            };
+            match kind {
+                AnchorTypeKind::Struct(fields) => fields_len(fields),
+                AnchorTypeKind::Enum(variants) => LenRange::fixed(EnumTagLayout::from_values(variants.iter().map(|v| v.value)).width).then(
+                    variants
+                        .iter()
+                        .map(|v| fields_len(v.fields.as_deref().unwrap_or_default()))
+                        .reduce(LenRange::either)
+                        .unwrap_or(LenRange::fixed(0)),
+                ),
+                AnchorTypeKind::Alias(inner) => anchor_len_range_at(inner, idl_enum, depth + 1),
+            }
+        }
+        IdlFormatEnum::NonAnchor(idl) => {
+            if let Some(size) = idl.c_type_size(type_name) {
+                return LenRange::fixed(size);
+            }
+            let Some(type_def) = idl.types.iter().flatten().find(|t| t.name == type_name) else {
+                return LenRange::unbounded(0);
+            };
+            let fields_len = |fields: &[NonAnchorField]| {
+                sum_len_ranges(fields.iter().map(|f| non_anchor_len_range_at(&f.field_type, idl_enum, depth + 1)))
+            };
+            match &type_def.type_def {
+                NonAnchorTypeKind::Struct { fields } => fields_len(fields),
+                NonAnchorTypeKind::Enum { variants } => LenRange::fixed(EnumTagLayout::from_values(variants.iter().map(|v| v.value)).width).then(
+                    variants
+                        .iter()
+                        .map(|v| fields_len(v.fields.as_deref().unwrap_or_default()))
+                        .reduce(LenRange::either)
+                        .unwrap_or(LenRange::fixed(0)),
+                ),
+                NonAnchorTypeKind::Alias { value } => non_anchor_len_range_at(value, idl_enum, depth + 1),
+            }
+        }
+    }
+}
+
+/// 复合类型 `{kind, params}`，参数按 JSON 类型描述解析
+fn complex_len_range(kind: &str, params: Option<&[serde_json::Value]>, idl_enum: &IdlFormatEnum, depth: usize) -> LenRange {
+    let params = params.unwrap_or_default();
+    let inner = || params.first().map_or(LenRange::unbounded(0), |param| json_len_range(param, idl_enum, depth + 1));
+    match kind {
+        "array" => match params.get(1).and_then(|len| len.as_u64()) {
+            Some(len) => inner().repeat(len as usize),
+            None => LenRange::unbounded(0),
+        },
+        "vec" => LenRange::unbounded(4),
+        "option" => LenRange::option(inner()),
+        "coption" => LenRange::coption(inner()),
+        _ => LenRange::unbounded(0),
+    }
+}
+
+/// JSON 类型描述：`"u64"`、`{"defined": ..}`、`{"option": ..}`、`{"array": [.., n]}` 等
+fn json_len_range(value: &serde_json::Value, idl_enum: &IdlFormatEnum, depth: usize) -> LenRange {
+    if depth > LEN_RANGE_MAX_DEPTH {
+        return LenRange::unbounded(0);
+    }
+    let Some(object) = value.as_object() else {
+        return value.as_str().map_or(LenRange::unbounded(0), basic_len_range);
+    };
+    let Some((kind, param)) = object.iter().next() else {
+        return LenRange::unbounded(0);
+    };
+    match (kind.as_str(), param) {
+        ("defined", serde_json::Value::String(name)) => defined_len_range(name, idl_enum, depth + 1),
+        // Anchor 0.30 形式：{"defined": {"name": ..}}
+        ("defined", serde_json::Value::Object(defined)) => defined
+            .get("name")
+            .and_then(|name| name.as_str())
+            .map_or(LenRange::unbounded(0), |name| defined_len_range(name, idl_enum, depth + 1)),
+        ("array", serde_json::Value::Array(params)) => complex_len_range("array", Some(params), idl_enum, depth),
+        (kind, param) => complex_len_range(kind, Some(std::slice::from_ref(param)), idl_enum, depth),
+    }
+}
+
+/// Anchor 账户数据的长度范围（含 8 字节 discriminator）
+pub fn anchor_account_len_range(account: &AnchorAccount, idl_enum: &IdlFormatEnum) -> LenRange {
+    LenRange::fixed(8).then(sum_len_ranges(
+        account.fields.iter().flatten().map(|f| anchor_len_range(&f.field_type, idl_enum)),
+    ))
+}
+
+/// NonAnchor 账户数据的长度范围
+pub fn non_anchor_account_len_range(account: &NonAnchorAccount, idl_enum: &IdlFormatEnum) -> LenRange {
+    sum_len_ranges(account.fields.iter().flatten().map(|f| non_anchor_len_range(&f.field_type, idl_enum)))
+}
+
+/// Anchor 指令参数的最短编码长度（不含 discriminator）；raw_tail 参数可以为空
+pub fn anchor_args_min_len(args: &[AnchorField], idl_enum: &IdlFormatEnum) -> usize {
+    sum_len_ranges(args.iter().map(|arg| match arg.encoding {
+        Some(ArgEncoding::RawTail) => LenRange::unbounded(0),
+        _ => anchor_len_range(&arg.field_type, idl_enum),
+    }))
+    .min
+}
+
+/// NonAnchor 指令参数的最短编码长度（不含 discriminator）；raw_tail 参数可以为空
+pub fn non_anchor_args_min_len(args: &[NonAnchorField], idl_enum: &IdlFormatEnum) -> usize {
+    sum_len_ranges(args.iter().map(|arg| match arg.encoding {
+        Some(ArgEncoding::RawTail) => LenRange::unbounded(0),
+        _ => non_anchor_len_range(&arg.field_type, idl_enum),
+    }))
+    .min
+}
+
+/// 检查IDL中是否存在COption字段（决定是否生成coption辅助模块）
+pub fn idl_has_coption(idl_enum: &IdlFormatEnum) -> bool {
+    match idl_enum {
+        IdlFormatEnum::Anchor(anchor_idl) => {
+            let account_fields = anchor_idl.accounts.iter().flatten()
+                .flat_map(|account| account.fields.iter().flatten());
+            let type_fields = anchor_idl.types.iter().flatten()
+                .flat_map(|type_def| match &type_def.kind {
+                    Some(AnchorTypeKind::Struct(fields)) => fields.as_slice(),
+                    _ => &[],
+                });
+            let event_fields = anchor_idl.events.iter().flatten()
+                .flat_map(|event| event.fields.iter().flatten());
+            let arg_fields = anchor_idl.instructions.iter().flatten()
+                .flat_map(|ix| ix.args.iter().flatten());
+            account_fields.chain(type_fields).chain(event_fields).chain(arg_fields)
+                .any(|field| matches!(field.field_type, AnchorFieldType::coption(_)))
+        },
+        IdlFormatEnum::NonAnchor(non_anchor_idl) => {
+            let account_fields = non_anchor_idl.accounts.iter().flatten()
+                .flat_map(|account| account.fields.iter().flatten());
+            let type_fields = non_anchor_idl.types.iter().flatten()
+                .flat_map(|type_def| match &type_def.type_def {
+                    NonAnchorTypeKind::Struct { fields } => fields.as_slice(),
+                    _ => &[],
+                });
+            let event_fields = non_anchor_idl.events.iter().flatten()
+                .flat_map(|event| event.fields.iter().flatten());
+            let arg_fields = non_anchor_idl.instructions.iter().flatten()
+                .flat_map(|ix| ix.args.iter().flatten());
+            account_fields.chain(type_fields).chain(event_fields).chain(arg_fields)
+                .any(|field| matches!(field.field_type, NonAnchorFieldType::COption { .. }))
+        }
+    }
+}
+
+/// 检查IDL中是否存在SmallVec字段（决定是否生成small_vec辅助模块）
+pub fn idl_has_small_vec(idl_enum: &IdlFormatEnum) -> bool {
+    match idl_enum {
+        IdlFormatEnum::Anchor(anchor_idl) => {
+            let account_fields = anchor_idl.accounts.iter().flatten()
+                .flat_map(|account| account.fields.iter().flatten());
+            let type_fields = anchor_idl.types.iter().flatten()
+                .flat_map(|type_def| match &type_def.kind {
+                    Some(AnchorTypeKind::Struct(fields)) => fields.as_slice(),
+                    _ => &[],
+                });
+            let event_fields = anchor_idl.events.iter().flatten()
+                .flat_map(|event| event.fields.iter().flatten());
+            let arg_fields = anchor_idl.instructions.iter().flatten()
+                .flat_map(|ix| ix.args.iter().flatten());
+            account_fields.chain(type_fields).chain(event_fields).chain(arg_fields)
+                .any(|field| anchor_small_vec_len(&field.field_type).is_some())
+        },
+        IdlFormatEnum::NonAnchor(non_anchor_idl) => {
+            let account_fields = non_anchor_idl.accounts.iter().flatten()
+                .flat_map(|account| account.fields.iter().flatten());
+            let type_fields = non_anchor_idl.types.iter().flatten()
+                .flat_map(|type_def| match &type_def.type_def {
+                    NonAnchorTypeKind::Struct { fields } => fields.as_slice(),
+                    _ => &[],
+                });
+            let event_fields = non_anchor_idl.events.iter().flatten()
+                .flat_map(|event| event.fields.iter().flatten());
+            let arg_fields = non_anchor_idl.instructions.iter().flatten()
+                .flat_map(|ix| ix.args.iter().flatten());
+            account_fields.chain(type_fields).chain(event_fields).chain(arg_fields)
+                .any(|field| non_anchor_small_vec_len(&field.field_type).is_some())
+        }
+    }
+}
+
+/// 字段类型本身为 `SmallVec<L, T>` 时的长度前缀类型（字段级编解码 `crate::small_vec`）
+pub fn anchor_small_vec_len(field_type: &AnchorFieldType) -> Option<&'static str> {
+    match field_type {
+        AnchorFieldType::defined(name) => super::type_mapper::small_vec_len_type(name),
+        _ => None,
+    }
+}
+
+pub fn non_anchor_small_vec_len(field_type: &NonAnchorFieldType) -> Option<&'static str> {
+    match field_type {
+        NonAnchorFieldType::Defined { defined } => super::type_mapper::small_vec_len_type(defined),
+        _ => None,
+    }
+}
+
+/// 不能派生 Hash 的已定义类型（Rust 类型名）：直接或经由其他已定义类型包含 HashMap，
+/// 或为只派生 PartialEq 的 C 布局结构体。HashMap 支持 Eq 但不支持 Hash，其余 Eq 兼容的字段类型均支持 Hash
+pub fn idl_unhashable_types(idl_enum: &IdlFormatEnum) -> HashSet<String> {
+    // (类型名, 该类型各字段的 Rust 类型)
+    let (mut unhashable, types): (HashSet<String>, Vec<(String, Vec<String>)>) = match idl_enum {
+        IdlFormatEnum::Anchor(anchor_idl) => {
+            let types = anchor_idl.types.iter().flatten().map(|type_def| {
+                let field_types = match &type_def.kind {
+                    Some(AnchorTypeKind::Struct(fields)) => fields.iter().map(|f| type_mapper::map_field_type(&f.field_type)).collect(),
+                    Some(AnchorTypeKind::Enum(variants)) => variants.iter()
+                        .flat_map(|variant| variant.fields.iter().flatten())
+                        .map(|f| type_mapper::map_field_type(&f.field_type))
+                        .collect(),
+                    Some(AnchorTypeKind::Alias(field_type)) => vec![type_mapper::map_field_type(field_type)],
+                    None => Vec::new(),
+                };
+                (type_def.name.to_case(Case::Pascal), field_types)
+            });
+            (HashSet::new(), types.collect())
+        },
+        IdlFormatEnum::NonAnchor(non_anchor_idl) => {
+            let c_layout = non_anchor_idl.types.iter().flatten()
+                .filter(|type_def| type_def.c_layout.is_some())
+                .map(|type_def| type_def.name.to_case(Case::Pascal))
+                .collect();
+            let types = non_anchor_idl.types.iter().flatten().map(|type_def| {
+                let field_types = match &type_def.type_def {
+                    NonAnchorTypeKind::Struct { fields } => fields.iter().map(|f| type_mapper::map_field_type(&f.field_type)).collect(),
+                    NonAnchorTypeKind::Enum { variants } => variants.iter()
+                        .flat_map(|variant| variant.fields.iter().flatten())
+                        .map(|f| type_mapper::map_field_type(&f.field_type))
+                        .collect(),
+                    NonAnchorTypeKind::Alias { value } => vec![type_mapper::map_field_type(value)],
+                };
+                (type_def.name.to_case(Case::Pascal), field_types)
+            });
+            (c_layout, types.collect())
+        }
+    };
+
+    // 传递闭包：反复标记引用了不可 Hash 类型的类型，直到不再变化
+    loop {
+        let newly: Vec<String> = types.iter()
+            .filter(|(name, field_types)| {
+                !unhashable.contains(name)
+                    && field_types.iter().any(|rust_type| !is_rust_type_hashable(rust_type, &unhashable))
+            })
+            .map(|(name, _)| name.clone())
+            .collect();
+        if newly.is_empty() {
+            return unhashable;
+        }
+        unhashable.extend(newly);
+    }
+}
+
+/// 字段类型是否均支持 Hash
+pub fn are_field_types_hashable<'a, T: IdlTypeShape + 'a>(
+    field_types: impl IntoIterator<Item = &'a T>,
+    unhashable: &HashSet<String>,
+) -> bool {
+    field_types.into_iter().all(|field_type| is_rust_type_hashable(&type_mapper::map_field_type(field_type), unhashable))
+}
+
+/// 映射后的 Rust 类型是否支持 Hash（Eq 兼容性另行判断）
+pub fn is_rust_type_hashable(rust_type: &str, unhashable: &HashSet<String>) -> bool {
+    !rust_type.contains("std::collections::HashMap<")
+        && !rust_type
+            .split(|c: char| !(c.is_alphanumeric() || c == '_' || c == ':'))
+            .filter_map(|segment| segment.strip_prefix("crate::types::"))
+            .any(|name| unhashable.contains(name))
+}
+
+/// 检查IDL中是否存在 raw_tail 编码的指令参数（决定是否生成 raw_tail.rs）
+pub fn idl_has_raw_tail(idl_enum: &IdlFormatEnum) -> bool {
+    match idl_enum {
+        IdlFormatEnum::Anchor(anchor_idl) => anchor_idl.instructions.iter().flatten()
+            .flat_map(|ix| ix.args.iter().flatten())
+            .any(|arg| arg.encoding == Some(ArgEncoding::RawTail)),
+        IdlFormatEnum::NonAnchor(non_anchor_idl) => non_anchor_idl.instructions.iter().flatten()
+            .flat_map(|ix| ix.args.iter().flatten())
+            .any(|arg| arg.encoding == Some(ArgEncoding::RawTail)),
+    }
+}
+
+/// 检查类型的内存布局是否与序列化布局一致（基础数值、bool、Pubkey 及其定长数组）
+pub fn is_fixed_layout_type(rust_type: &str) -> bool {
+    let rust_type = rust_type.trim();
+    if let Some(inner) = rust_type.strip_prefix('[').and_then(|s| s.strip_suffix(']')) {
+        return match inner.rsplit_once(';') {
+            Some((element, size)) => {
+                size.trim().parse::<usize>().is_ok() && is_fixed_layout_type(element)
+            }
+            None => false,
+        };
+    }
+    matches!(
+        rust_type,
+        "u8" | "u16" | "u32" | "u64" | "u128"
+            | "i8" | "i16" | "i32" | "i64" | "i128"
+            | "f32" | "f64" | "bool"
+            | "solana_pubkey::Pubkey"
+    )
+}
+
+/// serde 与 `Default` 只为长度不超过该值的数组提供实现
+pub const SERDE_ARRAY_LIMIT: usize = 32;
+
+/// 拆分 `[T; N]` 为元素类型与长度
+pub(crate) fn split_array_type(rust_type: &str) -> Option<(&str, usize)> {
+    let inner = rust_type.trim().strip_prefix('[')?.strip_suffix(']')?;
+    let (element, size) = inner.rsplit_once(';')?;
+    Some((element.trim(), size.trim().parse().ok()?))
+}
+
+/// 拆分 `Option<T>` / `Vec<T>`，返回容器路径与内部类型
+fn split_container_type(rust_type: &str) -> Option<(&'static str, &str)> {
+    let rust_type = rust_type.trim();
+    ["std::option::Option", "std::vec::Vec"].into_iter().find_map(|container| {
+        let inner = rust_type.strip_prefix(container)?.strip_prefix('<')?.strip_suffix('>')?;
+        Some((container, inner))
+    })
+}
+
+/// 嵌套在 Option / Vec / 数组内部的大数组的 serde_with 适配类型
+///
+/// 使用 serde_with 的 const 泛型数组适配 `[serde_with::Same; N]`；顶层大数组由
+/// `serde_big_array::BigArray` 处理，不含嵌套大数组时返回 None
+pub fn nested_big_array_serde_as(rust_type: &str) -> Option<String> {
+    fn adapter(rust_type: &str) -> Option<String> {
+        if let Some((element, size)) = split_array_type(rust_type) {
+            let element_adapter = adapter(element);
+            return (size > SERDE_ARRAY_LIMIT || element_adapter.is_some()).then(|| {
+                format!("[{}; {}]", element_adapter.unwrap_or_else(|| "serde_with::Same".to_string()), size)
+            });
+        }
+        let (container, inner) = split_container_type(rust_type)?;
+        adapter(inner).map(|inner| format!("{}<{}>", container, inner))
+    }
+
+    match split_array_type(rust_type) {
+        Some((element, _)) if split_array_type(element).is_none() => None,
+        _ => adapter(rust_type),
+    }
+}
+
+/// 字段默认值表达式：超过32元素的数组没有 `Default` 实现，逐元素构造
+pub fn field_default_expr(rust_type: &str) -> String {
+    match split_array_type(rust_type) {
+        Some((element, size)) => {
+            let element_default = field_default_expr(element);
+            if size > SERDE_ARRAY_LIMIT || element_default != "Default::default()" {
+                format!("core::array::from_fn(|_| {})", element_default)
+            } else {
+                "Default::default()".to_string()
+            }
+        }
+        None => "Default::default()".to_string(),
+    }
+}
+
+/// 检查条目字段（含枚举变体字段）中是否存在顶层大数组（决定是否依赖 serde-big-array）
+pub fn items_have_big_array(items: &[Value]) -> bool {
+    let is_big_array = |field: Value| field.get_attr("is_big_array").map(|v| v.is_true()).unwrap_or(false);
+    let fields_of = |value: &Value| -> Vec<Value> {
+        value
+            .get_attr("fields")
+            .ok()
+            .and_then(|fields| fields.try_iter().ok().map(|fields| fields.collect()))
+            .unwrap_or_default()
+    };
+    items.iter().any(|item| {
+        let variant_fields = item
+            .get_attr("variants")
+            .ok()
+            .and_then(|variants| variants.try_iter().ok())
+            .into_iter()
+            .flatten()
+            .flat_map(|variant| fields_of(&variant));
+        fields_of(item).into_iter().chain(variant_fields).any(is_big_array)
+    })
+}
+
+/// 检查账户字段是否全部为定长布局（决定是否生成布局断言）
+pub fn fields_have_fixed_layout(fields: &[Value]) -> bool {
+    !fields.is_empty()
+        && fields.iter().all(|field| {
+            field
+                .get_attr("rust_type")
+                .ok()
+                .and_then(|rust_type| rust_type.as_str().map(is_fixed_layout_type))
+                .unwrap_or(false)
+        })
+}
+
+/// 校验程序ID及指令中的固定账户地址，确保生成的 `pubkey!` 常量可以编译
+pub fn validate_idl_addresses(idl_enum: &IdlFormatEnum) -> Result<(), crate::error::SoloresError> {
+    let (program_id, fixed_addresses): (&str, Vec<(String, &str)>) = match idl_enum {
+        IdlFormatEnum::Anchor(anchor_idl) => (
+            &anchor_idl.address,
+            anchor_idl.instructions.iter().flatten()
+                .flat_map(|ix| ix.accounts.iter().flatten().filter_map(move |acc| {
+                    acc.address.as_deref().map(|address| (format!("instructions.{}.accounts.{}.address", ix.name, acc.name), address))
+                }))
+                .collect(),
+        ),
+        IdlFormatEnum::NonAnchor(non_anchor_idl) => (
+            &non_anchor_idl.address,
+            non_anchor_idl.instructions.iter().flatten()
+                .flat_map(|ix| ix.accounts.iter().flatten().filter_map(move |acc| {
+                    acc.address.as_deref().map(|address| (format!("instructions.{}.accounts.{}.address", ix.name, acc.name), address))
+                }))
+                .collect(),
+        ),
+    };
+
+    std::iter::once(("address".to_string(), program_id))
+        .chain(fixed_addresses)
+        .try_for_each(|(field_path, address)| {
+            if crate::utils::is_valid_pubkey(address) {
+                Ok(())
+            } else {
+                Err(crate::error::SoloresError::ValidationError {
+                    message: format!("无效的公钥地址: {}", address),
+                    field_path: Some(field_path),
+                    expected: Some("base58编码的32字节公钥".to_string()),
+                    actual: Some(address.to_string()),
+                })
+            }
+        })
+}
+
+/// 账户布局变体的模板上下文
+///
+/// `layout_fields` 为每个布局（与 `resolved.layouts` 同序）的 `(字段名, Rust 类型)`；
+/// 所有布局中同名同类型的字段成为公共字段，按第一个布局的顺序排列。
+/// `header_len` 为布局数据之前的字节数（Anchor 账户的 8 字节 discriminator）
+pub fn account_variants_value(
+    resolved: &ResolvedVariants,
+    layout_fields: &[Vec<(String, String)>],
+    header_len: usize,
+) -> Value {
+    let common_fields: Vec<Value> = layout_fields
+        .first()
+        .into_iter()
+        .flatten()
+        .filter(|field| layout_fields.iter().all(|fields| fields.contains(field)))
+        .map(|(name, rust_type)| minijinja::context! { name => name, rust_type => rust_type })
+        .collect();
+    let layouts: Vec<Value> = resolved
+        .layouts
+        .iter()
+        .map(|(value, name)| minijinja::context! { value => value, name => name.to_case(Case::Pascal) })
+        .collect();
+    minijinja::context! {
+        field => resolved.field,
+        offset => header_len + resolved.offset,
+        int_type => resolved.int_type,
+        header_len => header_len,
+        layouts => layouts,
+        common_fields => common_fields,
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn hash_maps_make_types_unhashable_transitively() {
+        let idl = crate::idl_format::parse_idl_json(
+            r#"{"name": "vault", "version": "0.1.0", "metadata": {"name": "vault", "version": "0.1.0", "spec": "0.1.0", "origin": "shank", "address": "Vau1t11111111111111111111111111111111111111"},
+                "instructions": [],
+                "types": [
+                    {"name": "Balances", "type": {"kind": "struct", "fields": [{"name": "by_owner", "type": {"hashMap": ["publicKey", "u64"]}}]}},
+                    {"name": "Ledger", "type": {"kind": "enum", "variants": [{"name": "Open", "fields": [{"name": "balances", "type": {"vec": {"defined": "Balances"}}}]}]}},
+                    {"name": "Fees", "type": {"kind": "struct", "fields": [{"name": "bps", "type": "u16"}]}}
+                ]}"#,
+        )
+        .unwrap();
+        let unhashable = idl_unhashable_types(&idl);
+        assert_eq!(unhashable, HashSet::from(["Balances".to_string(), "Ledger".to_string()]));
+
+        assert!(is_rust_type_hashable("std::option::Option<crate::types::Fees>", &unhashable));
+        assert!(!is_rust_type_hashable("[crate::types::Ledger; 2]", &unhashable));
+        assert!(!is_rust_type_hashable("std::collections::HashMap<u8, u8>", &unhashable));
+    }
+
+    #[test]
+    fn nested_big_arrays_get_serde_with_adapter_and_array_default() {
+        assert_eq!(nested_big_array_serde_as("[u8; 64]"), None);
+        assert_eq!(nested_big_array_serde_as("[[u8; 4]; 2]"), None);
+        assert_eq!(
+            nested_big_array_serde_as("std::option::Option<[u64; 40]>").as_deref(),
+            Some("std::option::Option<[serde_with::Same; 40]>")
+        );
+        assert_eq!(
+            nested_big_array_serde_as("[[u8; 64]; 2]").as_deref(),
+            Some("[[serde_with::Same; 64]; 2]")
+        );
+
+        assert_eq!(field_default_expr("[u8; 32]"), "Default::default()");
+        assert_eq!(
+            field_default_expr("[[u8; 64]; 2]"),
+            "core::array::from_fn(|_| core::array::from_fn(|_| Default::default()))"
+        );
+    }
+
+    #[test]
+    fn len_range_handles_optional_nested_structs() {
+        let idl = crate::idl_format::parse_idl_json(
+            r#"{
+                "metadata": {"name": "opt_demo", "version": "0.1.0", "spec": "0.1.0", "origin": "non-anchor"},
+                "address": "11111111111111111111111111111111",
+                "instructions": [],
+                "accounts": [{"name": "Vault", "type": {"kind": "struct", "fields": [
+                    {"name": "owner", "type": "publicKey"},
+                    {"name": "limits", "type": {"option": {"defined": "Limits"}}},
+                    {"name": "nested", "type": {"option": {"option": "u32"}}},
+                    {"name": "mode", "type": {"defined": "Mode"}},
+                    {"name": "tags", "type": {"vec": "u8"}}
+                ]}}],
+                "types": [
+                    {"name": "Limits", "type": {"kind": "struct", "fields": [{"name": "max", "type": "u64"}]}},
+                    {"name": "Mode", "type": {"kind": "enum", "variants": [{"name": "Off"}, {"name": "On"}]}}
+                ]
+            }"#,
+        )
+        .unwrap();
+        let IdlFormatEnum::NonAnchor(non_anchor_idl) = &idl else {
+            panic!("expected NonAnchor IDL");
+        };
+        let fields = non_anchor_idl.accounts.as_ref().unwrap()[0].fields.as_ref().unwrap();
+        let range = |index: usize| non_anchor_len_range(&fields[index].field_type, &idl);
+
+        assert_eq!(range(1), LenRange { min: 1, max: Some(9) });
+        assert_eq!(range(2), LenRange { min: 1, max: Some(6) });
+        assert_eq!(range(3), LenRange::fixed(1));
+        assert_eq!(range(4), LenRange::unbounded(4));
+        let account = non_anchor_account_len_range(&non_anchor_idl.accounts.as_ref().unwrap()[0], &idl);
+        assert_eq!(account, LenRange::unbounded(32 + 1 + 1 + 1 + 4));
+    }
+
+    #[test]
+    fn invalid_addresses_are_rejected_with_their_idl_path() {
+        let idl = |program_id: &str, rent: &str| {
+            crate::idl_format::parse_idl_json(&format!(
+                r#"{{"name": "vault", "version": "0.1.0", "metadata": {{"name": "vault", "version": "0.1.0", "spec": "0.1.0", "origin": "shank", "address": "{}"}},
+                    "instructions": [{{"name": "init", "discriminator": [0], "args": [],
+                        "accounts": [{{"name": "rent", "isMut": false, "isSigner": false, "address": "{}"}}]}}]}}"#,
+                program_id, rent
+            ))
+            .unwrap()
+        };
+        let rent = "SysvarRent111111111111111111111111111111111";
+        assert!(validate_idl_addresses(&idl("Vau1t11111111111111111111111111111111111111", rent)).is_ok());
+
+        for (program_id, rent, path, actual) in [
+            ("Vau1t0", rent, "address", "Vau1t0"),
+            ("Vau1t11111111111111111111111111111111111111", "SysvarRent", "instructions.init.accounts.rent.address", "SysvarRent"),
+        ] {
+            let error = validate_idl_addresses(&idl(program_id, rent)).unwrap_err();
+            assert!(
+                matches!(
+                    &error,
+                    crate::error::SoloresError::ValidationError { field_path: Some(found), actual: Some(value), .. }
+                        if found == path && value == actual
+                ),
+                "{:?}",
+                error
+            );
+        }
+    }
+
+}
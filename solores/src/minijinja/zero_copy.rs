@@ -0,0 +1,187 @@
+//! 零拷贝账户视图
+//!
+//! 为零拷贝账户生成借用账户数据的 `{Account}Ref<'a>`：`try_from_bytes` 只校验长度与
+//! discriminator，字段按紧凑布局偏移读取。数值、Pubkey 及其数组用 `read_unaligned`，
+//! bool 按字节比较，其余定长类型（结构体、无数据枚举、bool 数组）从字段字节区间
+//! Borsh 解码，避免对未对齐数据做引用转换。
+//!
+//! 同一紧凑布局也用于生成每个账户的 `field_offsets()` 字段偏移表，以及大账户
+//! （订单簿、bin array 等）的 `{Account}View<'a>` 惰性视图：头部字段按需解码，
+//! 大数组字段按元素或区间解码，避免每次更新都物化数百 KB 的结构体。
+
+use minijinja::{context, Value};
+
+use super::utils::is_fixed_layout_type;
+
+/// 生成惰性视图的最小账户长度
+pub const LAZY_VIEW_MIN_LEN: usize = 8 * 1024;
+
+/// 惰性视图中按元素解码的数组字段的最小长度，更短的数组与其他头部字段一起整体解码
+pub const LAZY_SECTION_MIN_LEN: usize = 1024;
+
+/// 视图字段：名称、Rust 类型与紧凑布局长度（None 表示变长）
+pub struct ViewField {
+    pub name: String,
+    pub rust_type: String,
+    pub size: Option<usize>,
+}
+
+/// 可直接 `read_unaligned` 的类型（任意位模式均合法）的自然对齐
+fn read_align(rust_type: &str) -> Option<usize> {
+    let rust_type = rust_type.trim();
+    if let Some(inner) = rust_type.strip_prefix('[').and_then(|s| s.strip_suffix(']')) {
+        return read_align(inner.rsplit_once(';')?.0);
+    }
+    match rust_type {
+        "u8" | "i8" | "solana_pubkey::Pubkey" => Some(1),
+        "u16" | "i16" => Some(2),
+        "u32" | "i32" | "f32" => Some(4),
+        "u64" | "i64" | "f64" => Some(8),
+        "u128" | "i128" => Some(16),
+        _ => None,
+    }
+}
+
+/// 定长字段前缀的偏移表 `{name, offset, size}`，遇到第一个变长字段时停止
+///
+/// 之后字段的偏移取决于运行时数据，不在表中
+pub fn field_offsets(fields: &[ViewField], header_len: usize) -> Vec<Value> {
+    let mut offset = header_len;
+    fields
+        .iter()
+        .map_while(|field| {
+            let size = field.size?;
+            let value = context! { name => field.name, offset => offset, size => size };
+            offset += size;
+            Some(value)
+        })
+        .collect()
+}
+
+/// 构建账户视图上下文；存在变长字段时返回 None
+///
+/// `header_len` 为字段之前的字节数（Anchor 账户的 8 字节 discriminator）
+pub fn account_view(account_name: &str, fields: Vec<ViewField>, header_len: usize) -> Option<Value> {
+    let mut offset = header_len;
+    let mut values = Vec::with_capacity(fields.len());
+    let mut has_misaligned = false;
+
+    for field in fields {
+        let Some(size) = field.size else {
+            log::warn!("⚠️ 零拷贝账户 {} 的字段 {} 不是定长类型，跳过零拷贝视图生成", account_name, field.name);
+            return None;
+        };
+        let (access, misaligned) = match read_align(&field.rust_type) {
+            Some(align) if is_fixed_layout_type(&field.rust_type) => ("read", !offset.is_multiple_of(align)),
+            _ if field.rust_type == "bool" => ("bool", false),
+            _ => ("decode", false),
+        };
+        has_misaligned |= misaligned;
+        values.push(context! {
+            name => field.name,
+            rust_type => field.rust_type,
+            offset => offset,
+            size => size,
+            access => access,
+            misaligned => misaligned,
+        });
+        offset += size;
+    }
+
+    Some(context! {
+        fields => values,
+        len => offset,
+        has_misaligned => has_misaligned,
+    })
+}
+
+/// 构建大账户的惰性视图上下文；布局不定长或短于 [`LAZY_VIEW_MIN_LEN`] 时返回 None
+///
+/// 长度不小于 [`LAZY_SECTION_MIN_LEN`] 的定长数组字段带 `section`
+/// （`{elem_type, count, elem_size}`），其余字段整体解码
+pub fn lazy_view(fields: &[ViewField], header_len: usize) -> Option<Value> {
+    let mut offset = header_len;
+    let mut values = Vec::with_capacity(fields.len());
+    for field in fields {
+        let size = field.size?;
+        let section = array_element(&field.rust_type)
+            .filter(|(_, count)| size >= LAZY_SECTION_MIN_LEN && *count > 0 && size % count == 0)
+            .map(|(elem_type, count)| context! { elem_type => elem_type, count => count, elem_size => size / count });
+        values.push(context! {
+            name => field.name,
+            rust_type => field.rust_type,
+            offset => offset,
+            size => size,
+            section => section,
+        });
+        offset += size;
+    }
+    (offset >= LAZY_VIEW_MIN_LEN).then(|| context! { fields => values, len => offset })
+}
+
+/// `[T; N]` 的元素类型与长度
+fn array_element(rust_type: &str) -> Option<(&str, usize)> {
+    let inner = rust_type.trim().strip_prefix('[')?.strip_suffix(']')?;
+    let (elem_type, count) = inner.rsplit_once(';')?;
+    Some((elem_type.trim(), count.trim().parse().ok()?))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn view_and_field_offsets() {
+        let field = |name: &str, rust_type: &str, size| ViewField {
+            name: name.to_string(),
+            rust_type: rust_type.to_string(),
+            size: Some(size),
+        };
+        let variable_field = || ViewField { name: "data".to_string(), rust_type: "std::vec::Vec<u8>".to_string(), size: None };
+        let view = account_view(
+            "Pool",
+            vec![field("flag", "bool", 1), field("amount", "u64", 8), field("params", "crate::types::Params", 3)],
+            8,
+        )
+        .unwrap();
+
+        let fields = view.get_attr("fields").unwrap();
+        let attr = |index: usize, name: &str| fields.get_item(&Value::from(index)).unwrap().get_attr(name).unwrap();
+        assert_eq!(attr(0, "access").as_str(), Some("bool"));
+        assert_eq!(attr(1, "offset").as_usize(), Some(9));
+        assert!(attr(1, "misaligned").is_true());
+        assert_eq!(attr(2, "access").as_str(), Some("decode"));
+        assert_eq!(view.get_attr("len").unwrap().as_usize(), Some(20));
+
+        let offsets = field_offsets(&[field("owner", "solana_pubkey::Pubkey", 32), variable_field(), field("bump", "u8", 1)], 8);
+        assert_eq!(offsets.len(), 1);
+        assert_eq!(offsets[0].get_attr("offset").unwrap().as_usize(), Some(8));
+
+        assert!(account_view("Pool", vec![variable_field()], 8).is_none());
+    }
+
+    #[test]
+    fn lazy_view_splits_large_arrays_into_sections() {
+        let field = |name: &str, rust_type: &str, size| ViewField {
+            name: name.to_string(),
+            rust_type: rust_type.to_string(),
+            size: Some(size),
+        };
+        let fields = [
+            field("lb_pair", "solana_pubkey::Pubkey", 32),
+            field("bins", "[crate::types::Bin; 70]", 70 * 144),
+            field("padding", "[[u8; 32]; 40]", 1280),
+        ];
+        let view = lazy_view(&fields, 8).unwrap();
+        assert_eq!(view.get_attr("len").unwrap().as_usize(), Some(8 + 32 + 70 * 144 + 1280));
+
+        let fields_value = view.get_attr("fields").unwrap();
+        let section = |index: usize| fields_value.get_item(&Value::from(index)).unwrap().get_attr("section").unwrap();
+        assert!(section(0).is_none());
+        assert_eq!(section(1).get_attr("elem_type").unwrap().as_str(), Some("crate::types::Bin"));
+        assert_eq!(section(1).get_attr("elem_size").unwrap().as_usize(), Some(144));
+        assert_eq!(section(2).get_attr("elem_type").unwrap().as_str(), Some("[u8; 32]"));
+
+        assert!(lazy_view(&fields[..1], 8).is_none());
+    }
+}
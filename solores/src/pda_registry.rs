@@ -0,0 +1,157 @@
+//! workspace 级 PDA 注册表
+//!
+//! `--workspace --pda-registry` 时汇总各成员 IDL 中由程序自身派生的 PDA（seeds 模式 → 所属程序），
+//! 生成 `pda_registry` 成员 crate：按常量前缀查找模式，并通过派生校验判断任意地址可能属于哪个程序，
+//! 供需要同时处理多个协议的浏览器、索引器使用。
+
+use std::fs;
+use std::path::Path;
+
+use minijinja::{context, Environment, Value};
+
+use crate::batch::GeneratedCrate;
+use crate::error::SoloresError;
+use crate::idl_format::anchor_idl::PdaSeed;
+use crate::idl_format::{parse_idl_json, IdlFormat};
+use crate::minijinja::generators::pda::{const_seed_code, program_pda_patterns};
+
+/// 注册表 crate 名称，即 workspace 成员目录名
+pub const REGISTRY_CRATE: &str = "pda_registry";
+
+/// 生成成员的注册表上下文：`[{crate_name, program_id, pdas: [{name, account, seeds: [{kind, value}]}]}]`
+///
+/// 缺少程序地址的 IDL 无法派生，跳过并记录警告
+fn registry_programs(generated: &[GeneratedCrate]) -> Result<Vec<Value>, SoloresError> {
+    let mut programs = Vec::new();
+    for member in generated {
+        let content = fs::read_to_string(&member.idl_path).map_err(|e| {
+            SoloresError::file_operation_error("读取IDL文件", member.idl_path.display().to_string(), e)
+        })?;
+        let idl = parse_idl_json(&content).map_err(|e| SoloresError::ValidationError {
+            message: format!("无法解析IDL: {}", e),
+            field_path: Some(member.idl_path.display().to_string()),
+            expected: None,
+            actual: None,
+        })?;
+        let Some(program_id) = idl.program_address().map(str::to_string) else {
+            log::warn!("⚠️ {} 的IDL缺少程序地址，未加入PDA注册表", member.crate_name);
+            continue;
+        };
+
+        let pdas: Vec<Value> = program_pda_patterns(&idl)
+            .into_iter()
+            .map(|pattern| {
+                let seeds: Vec<Value> = pattern
+                    .seeds
+                    .iter()
+                    .map(|seed| match seed {
+                        PdaSeed::Const { value } => context! { kind => "Const", value => const_seed_code(value) },
+                        PdaSeed::Account { path } => context! { kind => "Account", value => format!("{:?}", path) },
+                        PdaSeed::Arg { path } => context! { kind => "Arg", value => format!("{:?}", path) },
+                    })
+                    .collect();
+                context! { name => pattern.struct_name, account => pattern.account_name, seeds => seeds }
+            })
+            .collect();
+        programs.push(context! { crate_name => member.crate_name, program_id => program_id, pdas => pdas });
+    }
+    Ok(programs)
+}
+
+/// 在 `output_dir` 下生成注册表 crate；没有任何带地址的程序时不生成并返回 false
+pub fn generate_pda_registry(output_dir: &Path, generated: &[GeneratedCrate]) -> Result<bool, SoloresError> {
+    let programs = registry_programs(generated)?;
+    if programs.is_empty() {
+        log::warn!("⚠️ 没有可加入PDA注册表的程序，跳过 {} 生成", REGISTRY_CRATE);
+        return Ok(false);
+    }
+
+    let crate_dir = output_dir.join(REGISTRY_CRATE);
+    let src_dir = crate_dir.join("src");
+    fs::create_dir_all(&src_dir)
+        .map_err(|e| SoloresError::file_operation_error("创建PDA注册表目录", src_dir.display().to_string(), e))?;
+
+    let template_content = include_str!("minijinja/templates/workspace/pda_registry.rs.jinja");
+    let env = Environment::new();
+    let rendered = env
+        .template_from_str(template_content)
+        .and_then(|tmpl| tmpl.render(context! { programs => programs }))
+        .map_err(|e| SoloresError::TemplateError {
+            template_name: Some("workspace/pda_registry.rs.jinja".to_string()),
+            message: format!("模板渲染失败: {}", e),
+            context: Some("渲染PDA注册表模板".to_string()),
+        })?;
+    let lib_path = src_dir.join("lib.rs");
+    fs::write(&lib_path, rendered)
+        .map_err(|e| SoloresError::file_operation_error("写入PDA注册表", lib_path.display().to_string(), e))?;
+    crate::format_rust_files_with_prettyplease(&src_dir)?;
+
+    let cargo_toml_path = crate_dir.join("Cargo.toml");
+    fs::write(&cargo_toml_path, registry_cargo_toml())
+        .map_err(|e| SoloresError::file_operation_error("写入PDA注册表 Cargo.toml", cargo_toml_path.display().to_string(), e))?;
+
+    log::info!("✅ PDA注册表已生成: {}（{} 个程序）", crate_dir.display(), programs.len());
+    Ok(true)
+}
+
+/// 注册表 crate 的 Cargo.toml，依赖版本由 workspace 统一
+fn registry_cargo_toml() -> String {
+    format!(
+        r#"[package]
+name = "{}"
+version = "0.1.0"
+edition = "2021"
+description = "PDA registry of the programs in this workspace"
+
+[dependencies.solana-pubkey]
+features = ["curve25519"]
+version = "2.4.0"
+"#,
+        REGISTRY_CRATE
+    )
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn registry_lists_own_program_pdas() {
+        let dir = std::env::temp_dir().join(format!("solores-pda-registry-{}", std::process::id()));
+        fs::create_dir_all(&dir).unwrap();
+        let idl_path = dir.join("demo.json");
+        fs::write(
+            &idl_path,
+            r#"{
+                "address": "11111111111111111111111111111111",
+                "metadata": {"name": "demo", "version": "0.1.0", "spec": "0.1.0"},
+                "instructions": [
+                    {"name": "buy", "discriminator": [0,0,0,0,0,0,0,1], "args": [], "accounts": [
+                        {"name": "pool", "pda": {"seeds": [{"kind": "const", "value": [112, 111, 111, 108]}, {"kind": "account", "path": "mint"}]}},
+                        {"name": "ata", "pda": {"seeds": [{"kind": "account", "path": "pool"}], "program": {"kind": "const", "value": [1,2,3]}}}
+                    ]}
+                ]
+            }"#,
+        )
+        .unwrap();
+        let generated = [GeneratedCrate {
+            idl_path,
+            crate_name: "sol_demo_interface".to_string(),
+            output_dir: dir.join("sol_demo_interface"),
+            warnings: Vec::new(),
+        }];
+
+        let programs = registry_programs(&generated).unwrap();
+        let _ = fs::remove_dir_all(&dir);
+
+        assert_eq!(programs.len(), 1);
+        let pdas = programs[0].get_attr("pdas").unwrap();
+        assert_eq!(pdas.len(), Some(1));
+        let pool = pdas.get_item(&Value::from(0)).unwrap();
+        assert_eq!(pool.get_attr("name").unwrap().to_string(), "PoolPda");
+        assert_eq!(
+            pool.get_attr("seeds").unwrap().to_string(),
+            r#"[{"kind": "Const", "value": "b\"pool\""}, {"kind": "Account", "value": "\"mint\""}]"#
+        );
+    }
+}
@@ -0,0 +1,308 @@
+//! 代码生成流水线
+//!
+//! 单文件、批量模式与 `write_lib` 共用的唯一生成路径：
+//! IR（`IdlFormatEnum`）→ 渲染后端 → 复制IDL → prettyplease 格式化 → CHANGELOG → 写入生成文件头。
+//! 新功能只需接入渲染后端即可在所有入口生效。
+
+use std::{path::Path, process::Command};
+
+use crate::{
+    cargo::PackageMetadata,
+    changelog,
+    error::SoloresError,
+    fixtures,
+    idl_format::{
+        field_types::{self, FieldTypeOverride},
+        naming::NamingConfig,
+        IdlFormat, IdlFormatEnum,
+    },
+    minijinja::{externs::ExternImports, field_hints, MinijinjaTemplateGenerator},
+    provenance::{self, GenerationStamp},
+    warnings::{self, Warning},
+    write_gitignore::write_gitignore,
+    write_readme::write_readme,
+    Args,
+};
+
+/// 渲染引擎选择（过渡期参数）
+#[derive(clap::ValueEnum, Debug, Default, Clone, Copy, PartialEq, Eq)]
+pub enum Engine {
+    /// MiniJinja 模板系统，所有文件（含 Cargo.toml / README.md）均来自模板
+    #[default]
+    Minijinja,
+    /// 过渡兼容：MiniJinja 渲染后用旧版 write_readme 覆盖 README.md
+    Legacy,
+}
+
+/// 渲染后端：将 IR 渲染为输出目录中的 crate 文件
+pub trait RenderBackend {
+    /// 后端名称，用于日志
+    fn name(&self) -> &'static str;
+
+    /// 将 [`prepare`] 得到的 IR 渲染到 `args.output_dir`
+    fn render(&self, ir: &PreparedIr, args: &Args) -> Result<(), SoloresError>;
+}
+
+/// 应用全部 CLI 变换后的 IR，警告检查与渲染后端共用同一份
+#[derive(Debug, Clone)]
+pub struct PreparedIr {
+    /// 类型筛选与布局标注后、参数编码标注与重命名前的 IR，作为生成代码引用的源 IDL
+    pub source: IdlFormatEnum,
+    /// 在 `source` 上应用编码标注、字段类型替换、程序 ID 与命名后的 IR
+    pub named: IdlFormatEnum,
+}
+
+/// 按 CLI 参数变换 IR，校验失败时不写入任何文件
+pub fn prepare(ir: &IdlFormatEnum, args: &Args) -> Result<PreparedIr, SoloresError> {
+    let mut source = ir.clone();
+    source.apply_type_selection(&args.include_types, &args.exclude_types)?;
+    source.validate_coption_positions()?;
+    source.validate_small_vec_positions()?;
+    source.apply_zero_copy(&args.zero_copy)?;
+    source.apply_c_layouts(args.layout_file.as_deref())?;
+    source.apply_account_variants(args.account_variants.as_deref())?;
+    // 参数编码标注使用原始名称，先于重命名应用
+    let mut named = source.clone();
+    named.apply_arg_encodings(&args.arg_encoding)?;
+    named.apply_field_types(&field_types(args)?)?;
+    named.apply_program_ids(args.program_id.as_deref().filter(|id| *id != crate::DEFAULT_PROGRAM_ID_MSG))?;
+    for (from, to) in apply_names(&mut named, args) {
+        log::info!("🔤 {} 与 Rust 关键字、标准库条目或同类别的其他名称冲突，生成为 {}", from, to);
+    }
+    Ok(PreparedIr { source, named })
+}
+
+/// MiniJinja 模板渲染后端（默认）
+pub struct MinijinjaBackend;
+
+impl RenderBackend for MinijinjaBackend {
+    fn name(&self) -> &'static str {
+        "minijinja"
+    }
+
+    fn render(&self, ir: &PreparedIr, args: &Args) -> Result<(), SoloresError> {
+        let package = PackageMetadata::from_args(args)
+            .with_idl_provenance(&args.idl_path, ir.source.program_address());
+        let mut field_hints = args.field_hint.clone();
+        if let Some(path) = &args.field_hints_file {
+            field_hints.extend(field_hints::load_field_hints_file(path)?);
+        }
+        let externs = ExternImports::resolve(&args.externs, &ir.named)?;
+        let rpc_fixtures = match &args.fixtures_from_rpc {
+            Some(path) => fixtures::fetch_fixtures(path, &args.rpc_url, ir.named.program_address().unwrap_or_default())?,
+            None => Vec::new(),
+        };
+        let mut generator = MinijinjaTemplateGenerator::new(ir.named.clone())?
+            .with_source_idl(ir.source.clone())
+            .with_package_metadata(package)
+            .with_field_hints(field_hints)
+            .with_trailing_bytes(args.trailing_bytes)
+            .with_serde_rename(args.serde_rename)
+            .with_program_tests(args.gen_program_tests)
+            .with_benches(args.gen_benches)
+            .with_compile_fail_tests(args.gen_compile_fail_tests)
+            .with_history_stats(args.gen_history_stats)
+            .with_rpc_fixtures(rpc_fixtures)
+            .with_anchor_cpi(args.gen_anchor_cpi)
+            .with_cache(args.gen_cache)
+            .with_db(args.gen_db)
+            .with_interop(args.interop_with.clone())
+            .with_upgrade(args.gen_upgrade)
+            .with_web(args.gen_web)
+            .with_readme_graph(args.readme_graph)
+            .with_compat(args.compat)
+            .with_pubkey_crate(args.pubkey_crate.clone())
+            .with_externs(externs)
+            .with_min_rust_version(args.min_rust_version)
+            .with_lints(args.lints.clone())
+            .with_non_exhaustive(args.non_exhaustive)
+            .with_lossy_parser(args.lossy_parser)
+            .with_typed_keys(args.typed_keys)
+            .with_account_traits(args.accounts_as_traits)
+            .with_compute_units(args.compute_units.clone(), args.default_compute_units)
+            .with_size_report(args.size_report, args.rent_lamports_per_byte_year)
+            .with_jsonschema(args.gen_jsonschema);
+        generator.generate_multi_file_structure(
+            &args.output_dir,
+            args.generate_to_json, // 使用generate_to_json作为serde特性标志
+            args.generate_parser,
+            args.no_empty_workspace,
+        )
+    }
+}
+
+/// 过渡期的兼容后端，保留旧版 README 输出
+pub struct LegacyBackend;
+
+impl RenderBackend for LegacyBackend {
+    fn name(&self) -> &'static str {
+        "legacy"
+    }
+
+    fn render(&self, ir: &PreparedIr, args: &Args) -> Result<(), SoloresError> {
+        MinijinjaBackend.render(ir, args)?;
+        write_readme(args, &ir.named).map_err(|e| {
+            SoloresError::file_operation_error(
+                "创建README.md文件",
+                args.output_dir.join("README.md").display().to_string(),
+                e,
+            )
+        })
+    }
+}
+
+/// 根据引擎选择渲染后端
+pub fn backend_for(engine: Engine) -> Box<dyn RenderBackend> {
+    match engine {
+        Engine::Minijinja => Box::new(MinijinjaBackend),
+        Engine::Legacy => Box::new(LegacyBackend),
+    }
+}
+
+/// `--field-type` 与 `--field-types-file` 中的全部字段类型替换
+fn field_types(args: &Args) -> Result<Vec<FieldTypeOverride>, SoloresError> {
+    let mut field_types = args.field_type.clone();
+    if let Some(path) = &args.field_types_file {
+        field_types.extend(field_types::load_field_types_file(path)?);
+    }
+    Ok(field_types)
+}
+
+/// 应用命名前后缀，再重命名与 prelude 条目同名或互相重名的条目，返回后者的 (原名称, 新名称)
+fn apply_names(ir: &mut IdlFormatEnum, args: &Args) -> Vec<(String, String)> {
+    ir.apply_naming(&NamingConfig::new(args.type_prefix.clone(), args.type_suffix.clone()));
+    ir.resolve_name_conflicts()
+}
+
+/// prettyplease 格式化失败时的处理方式
+#[derive(Debug, Default, Clone, Copy, PartialEq, Eq)]
+pub enum FormatFallback {
+    /// 返回错误
+    #[default]
+    Fail,
+    /// 记录警告并改用 `cargo fmt`，批量模式中单个 crate 的格式化问题不中断生成
+    CargoFmt,
+}
+
+/// 执行完整的生成流水线，格式化失败时返回错误
+///
+/// `args.output_dir` 应已指向最终的 crate 目录；返回未被 `--allow` 屏蔽的警告
+pub fn run(args: &Args, ir: &IdlFormatEnum) -> Result<Vec<Warning>, SoloresError> {
+    run_with(args, ir, FormatFallback::Fail)
+}
+
+/// 执行完整的生成流水线，`format_fallback` 决定 prettyplease 失败时的处理
+pub fn run_with(args: &Args, ir: &IdlFormatEnum, format_fallback: FormatFallback) -> Result<Vec<Warning>, SoloresError> {
+    // 重名等问题可能由命名前后缀引入，在渲染使用的同一 IR 上检查；--deny-warnings 时不写入任何文件
+    let prepared = prepare(ir, args)?;
+    let warnings = warnings::check(args, &prepared.named)?;
+    let stamp = GenerationStamp::from_args(args)?;
+
+    std::fs::create_dir_all(args.output_dir.join("src")).map_err(|e| {
+        SoloresError::file_operation_error("创建输出目录", args.output_dir.display().to_string(), e)
+    })?;
+
+    write_gitignore(args).map_err(|e| {
+        SoloresError::file_operation_error(
+            "创建.gitignore文件",
+            args.output_dir.join(".gitignore").display().to_string(),
+            e,
+        )
+    })?;
+
+    // 上次生成复制的 idl.json 会被本次覆盖，渲染前读取
+    let previous_idl = changelog::previous_idl(&args.output_dir, args.force_format);
+
+    let backend = backend_for(args.engine);
+    log::info!("🚀 使用 {} 渲染后端生成代码", backend.name());
+    backend.render(&prepared, args)?;
+
+    copy_idl(&args.idl_path, &args.output_dir);
+    format_sources(&args.output_dir, format_fallback)?;
+
+    if let Some(previous) = &previous_idl {
+        changelog::update_changelog(&args.output_dir, previous, ir, &stamp.idl_hash)?;
+    }
+
+    // prettyplease 会丢弃普通注释，文件头在格式化之后写入
+    provenance::stamp_generated_files(&args.output_dir, &stamp)?;
+
+    crate::post_process::run(&args.output_dir, args)?;
+
+    Ok(warnings)
+}
+
+/// 格式化 crate 中所有生成的Rust文件
+fn format_sources(crate_dir: &Path, fallback: FormatFallback) -> Result<(), SoloresError> {
+    let src_dir = crate_dir.join("src");
+    if !src_dir.exists() {
+        log::warn!("⚠️ src目录不存在，跳过格式化");
+        return Ok(());
+    }
+    log::debug!("🎨 使用prettyplease格式化生成的代码...");
+    match crate::format_rust_files_with_prettyplease(&src_dir) {
+        Ok(()) => log::debug!("✅ 代码格式化完成 (prettyplease)"),
+        Err(e) if fallback == FormatFallback::CargoFmt => {
+            log::warn!("⚠️ prettyplease格式化失败: {}，尝试使用cargo fmt", e);
+            let _ = Command::new("cargo").arg("fmt").current_dir(crate_dir).output();
+        }
+        Err(e) => return Err(e),
+    }
+    Ok(())
+}
+
+/// 复制IDL文件到输出目录，失败仅记录警告
+fn copy_idl(idl_path: &Path, output_dir: &Path) {
+    let idl_dest = output_dir.join("idl.json");
+    match std::fs::copy(idl_path, &idl_dest) {
+        Ok(_) => log::info!("IDL file copied to {}", idl_dest.display()),
+        Err(e) => log::warn!("Failed to copy IDL file: {}", e),
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use clap::Parser;
+
+    fn args(extra: &[&str]) -> Args {
+        let idl = Path::new(env!("CARGO_MANIFEST_DIR")).join("../idls/pump_amm.json");
+        let mut argv = vec!["solores".to_string(), idl.display().to_string()];
+        argv.extend(extra.iter().map(|arg| arg.to_string()));
+        Args::try_parse_from(argv).unwrap()
+    }
+
+    #[test]
+    fn warnings_and_backends_share_the_prepared_ir() {
+        let args = args(&[
+            "--program-id",
+            "11111111111111111111111111111111",
+            "--field-type",
+            "Pool.creator=my_types::Creator",
+            "--type-prefix",
+            "Amm",
+        ]);
+        let ir = crate::idl_format::parse_idl_json(&std::fs::read_to_string(&args.idl_path).unwrap()).unwrap();
+        let prepared = prepare(&ir, &args).unwrap();
+
+        assert_eq!(prepared.named.program_address(), Some("11111111111111111111111111111111"));
+        assert!(prepared.named.has_field_types());
+        assert!(!prepared.source.has_field_types());
+        let IdlFormatEnum::Anchor(named) = &prepared.named else { panic!("pump_amm 是 Anchor IDL") };
+        assert!(named.accounts.iter().flatten().any(|account| account.name == "AmmPool"));
+        // 程序 ID 等变换失败时在写入任何文件之前报错
+        assert!(prepare(&ir, &self::args(&["--program-id", "not-base58"])).is_err());
+    }
+
+    #[test]
+    fn format_failure_falls_back_to_cargo_fmt_when_requested() {
+        let dir = std::env::temp_dir().join(format!("solores-pipeline-fmt-{}", std::process::id()));
+        std::fs::create_dir_all(dir.join("src")).unwrap();
+        // 语法错误只会跳过该文件，无法读取的文件才会让 prettyplease 步骤失败
+        std::fs::write(dir.join("src/lib.rs"), [0xff, 0xfe]).unwrap();
+
+        assert!(format_sources(&dir, FormatFallback::Fail).is_err());
+        assert!(format_sources(&dir, FormatFallback::CargoFmt).is_ok());
+        std::fs::remove_dir_all(&dir).unwrap();
+    }
+}
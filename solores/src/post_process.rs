@@ -0,0 +1,143 @@
+//! 生成文件后处理
+//!
+//! `--post-process <COMMAND>` 在生成文件写入文件头之后、流水线返回之前，对每个匹配
+//! `--post-process-files`（默认 `*.rs`）的生成文件运行一次命令，用于自定义 rustfmt
+//! 配置、插入许可证头或运行 codemod。命令经 shell 执行，文件路径作为最后一个参数；
+//! `--post-process-stdin` 时改为从标准输入读取文件内容，以标准输出替换文件。
+//!
+//! 嵌入方可以直接调用 [`apply`]，以回调代替外部命令。
+
+use std::fs;
+use std::io::Write;
+use std::path::{Path, PathBuf};
+use std::process::{Command, Stdio};
+
+use crate::{error::SoloresError, Args};
+
+/// 对 `dir` 下匹配 `patterns` 的生成文件按路径顺序调用 `process`
+///
+/// `patterns` 按文件名匹配，支持 `*` 通配符，为空时匹配全部文件；
+/// 第一个失败的文件中止处理，错误中带有文件路径
+pub fn apply(
+    dir: &Path,
+    patterns: &[String],
+    mut process: impl FnMut(&Path) -> Result<(), String>,
+) -> Result<usize, SoloresError> {
+    let mut files = Vec::new();
+    collect_files(dir, &mut files)?;
+    let mut processed = 0;
+    for path in files {
+        let name = path.file_name().and_then(|name| name.to_str()).unwrap_or_default();
+        if !patterns.is_empty() && !patterns.iter().any(|pattern| crate::filename_matches_pattern(name, pattern)) {
+            continue;
+        }
+        process(&path).map_err(|reason| SoloresError::ValidationError {
+            message: format!("生成文件 {} 后处理失败: {}", path.display(), reason),
+            field_path: None,
+            expected: None,
+            actual: None,
+        })?;
+        processed += 1;
+    }
+    Ok(processed)
+}
+
+/// 按 `--post-process` 参数处理 crate 目录，未配置命令时不做任何事
+pub fn run(dir: &Path, args: &Args) -> Result<(), SoloresError> {
+    let Some(command) = args.post_process.as_deref() else { return Ok(()) };
+    log::info!("🔧 运行后处理命令: {}", command);
+    let processed = apply(dir, &args.post_process_files, |path| run_command(command, path, args.post_process_stdin))?;
+    log::debug!("✅ 后处理完成，共 {} 个文件", processed);
+    Ok(())
+}
+
+/// 对单个文件运行命令，失败时返回包含退出状态与标准错误的说明
+fn run_command(command: &str, path: &Path, stdin: bool) -> Result<(), String> {
+    let mut shell = shell_command(command);
+    if stdin {
+        shell.stdin(Stdio::piped()).stdout(Stdio::piped());
+    } else {
+        shell.arg(path);
+    }
+    let mut child = shell
+        .stderr(Stdio::piped())
+        .spawn()
+        .map_err(|e| format!("无法运行后处理命令 `{}`: {}", command, e))?;
+
+    if stdin {
+        let content = fs::read(path).map_err(|e| format!("读取生成文件失败: {}", e))?;
+        let mut child_stdin = child.stdin.take().expect("已配置 piped stdin");
+        // 命令可能不读取全部输入便退出，写入失败以退出状态为准
+        let _ = child_stdin.write_all(&content);
+    }
+
+    let output = child.wait_with_output().map_err(|e| format!("等待后处理命令 `{}` 失败: {}", command, e))?;
+    if !output.status.success() {
+        return Err(format!(
+            "后处理命令 `{}` 失败（{}）: {}",
+            command,
+            output.status,
+            String::from_utf8_lossy(&output.stderr).trim()
+        ));
+    }
+    if stdin {
+        fs::write(path, &output.stdout).map_err(|e| format!("写回后处理结果失败: {}", e))?;
+    }
+    Ok(())
+}
+
+/// 经 shell 执行命令，附加的参数依次成为 `$1`、`$2`…
+fn shell_command(command: &str) -> Command {
+    if cfg!(windows) {
+        let mut shell = Command::new("cmd");
+        shell.arg("/C").arg(command);
+        shell
+    } else {
+        let mut shell = Command::new("sh");
+        shell.arg("-c").arg(format!("{} \"$@\"", command)).arg("solores");
+        shell
+    }
+}
+
+/// 递归收集目录下的文件，跳过构建产物，按路径排序
+fn collect_files(dir: &Path, files: &mut Vec<PathBuf>) -> Result<(), SoloresError> {
+    let mut entries: Vec<_> = fs::read_dir(dir)
+        .map_err(|e| SoloresError::file_operation_error("读取目录", dir.display().to_string(), e))?
+        .flatten()
+        .map(|entry| entry.path())
+        .collect();
+    entries.sort();
+    for path in entries {
+        if path.is_dir() {
+            if path.file_name().is_some_and(|name| name == "target") {
+                continue;
+            }
+            collect_files(&path, files)?;
+        } else {
+            files.push(path);
+        }
+    }
+    Ok(())
+}
+
+#[cfg(all(test, unix))]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn commands_rewrite_files_and_report_failures() {
+        let dir = std::env::temp_dir().join(format!("solores-post-process-{}", std::process::id()));
+        fs::create_dir_all(dir.join("src")).unwrap();
+        fs::write(dir.join("src/lib.rs"), "pub mod a;\n").unwrap();
+        fs::write(dir.join("Cargo.toml"), "[package]\n").unwrap();
+        let patterns = vec!["*.rs".to_string()];
+
+        apply(&dir, &patterns, |path| run_command("sed 's/pub/pub(crate)/'", path, true)).unwrap();
+        assert_eq!(fs::read_to_string(dir.join("src/lib.rs")).unwrap(), "pub(crate) mod a;\n");
+        assert_eq!(fs::read_to_string(dir.join("Cargo.toml")).unwrap(), "[package]\n");
+
+        let error = apply(&dir, &patterns, |path| run_command("false", path, false)).unwrap_err();
+        assert!(error.to_string().contains("lib.rs"));
+        fs::remove_dir_all(&dir).ok();
+    }
+}
@@ -0,0 +1,330 @@
+//! 项目配置与 `cargo solores`
+//!
+//! `cargo solores` 从当前目录向上查找项目配置：`Solores.toml`，或 Cargo.toml 中
+//! 包含 `interfaces` 的 `[package.metadata.solores]` / `[workspace.metadata.solores]`，
+//! 按配置重新生成各接口 crate 并对结果运行 `cargo fmt`。配置中的相对路径（含
+//! `args` 中的路径）相对配置文件所在目录。
+//!
+//! ```toml
+//! # Solores.toml；写在 Cargo.toml 中时为 [[package.metadata.solores.interfaces]]
+//! [[interfaces]]
+//! idl = "idls/pump_amm.json"
+//! output = "crates"                  # crate 生成在 crates/<crate 名>，默认 "."
+//! crate-name = "pump_amm_interface"  # 可选，默认 sol_<程序名>_interface
+//! args = ["--generate-parser"]       # 其余生成参数
+//! ```
+//!
+//! `--check` 在临时目录生成并与现有 crate 比较，生成结果过期时返回非零退出码，
+//! 可用于 CI 检查提交的接口代码是否与 IDL 一致。
+
+use std::ffi::OsString;
+use std::fs;
+use std::path::{Path, PathBuf};
+use std::process::Command;
+
+use clap::Parser;
+use serde::Deserialize;
+
+use crate::{error::SoloresError, watch, Args};
+
+/// 项目配置文件名
+pub const CONFIG_FILE_NAME: &str = "Solores.toml";
+
+/// `cargo solores` 参数
+#[derive(clap::Args, Debug, Clone, Default)]
+pub struct ProjectArgs {
+    /// 项目配置文件（Solores.toml 或 Cargo.toml），缺省时从当前目录向上查找
+    #[arg(long)]
+    pub config: Option<PathBuf>,
+
+    /// 只重新生成指定的接口（按 IDL 文件名或 crate 名匹配），可重复
+    #[arg(long)]
+    pub only: Vec<String>,
+
+    /// 不对生成结果运行 cargo fmt
+    #[arg(long)]
+    pub no_fmt: bool,
+
+    /// 只检查生成结果是否最新，不写入文件；过期时返回错误
+    #[arg(long)]
+    pub check: bool,
+}
+
+/// `cargo solores` 作为 cargo 子命令调用时，第一个参数为子命令名
+#[derive(Parser, Debug)]
+#[command(name = "cargo", bin_name = "cargo")]
+pub enum CargoCli {
+    /// 按项目配置重新生成 Solores 接口 crate
+    Solores(ProjectArgs),
+}
+
+/// 单个接口的生成配置
+#[derive(Debug, Clone, PartialEq, Eq, Deserialize)]
+#[serde(rename_all = "kebab-case", deny_unknown_fields)]
+pub struct InterfaceConfig {
+    /// IDL 文件
+    pub idl: PathBuf,
+    /// 输出目录，crate 生成在其下的 `<crate 名>` 子目录
+    #[serde(default = "default_output")]
+    pub output: PathBuf,
+    /// crate 名称，缺省为 `sol_<程序名>_interface`
+    pub crate_name: Option<String>,
+    /// 其余生成参数（如 `--generate-parser`）
+    #[serde(default)]
+    pub args: Vec<String>,
+}
+
+fn default_output() -> PathBuf {
+    PathBuf::from(".")
+}
+
+/// `interfaces` 表；Cargo.toml 的 `metadata.solores` 中其他键（生成 crate 的元数据）被忽略
+#[derive(Debug, Deserialize)]
+struct InterfacesTable {
+    interfaces: Vec<InterfaceConfig>,
+}
+
+/// 已发现的项目配置
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct ProjectConfig {
+    /// 配置文件路径
+    pub path: PathBuf,
+    pub interfaces: Vec<InterfaceConfig>,
+}
+
+impl ProjectConfig {
+    /// 配置文件所在目录，相对路径的基准
+    pub fn root(&self) -> &Path {
+        self.path.parent().unwrap_or(Path::new("."))
+    }
+}
+
+/// 从 `start` 向上查找项目配置，同一目录中 `Solores.toml` 优先于 Cargo.toml
+pub fn discover(start: &Path) -> Result<ProjectConfig, SoloresError> {
+    for dir in start.ancestors() {
+        for name in [CONFIG_FILE_NAME, "Cargo.toml"] {
+            let path = dir.join(name);
+            if path.is_file() {
+                if let Some(config) = load(&path)? {
+                    return Ok(config);
+                }
+            }
+        }
+    }
+    Err(SoloresError::ValidationError {
+        message: format!("未找到 Solores 项目配置（从 {} 向上查找）", start.display()),
+        field_path: None,
+        expected: Some(format!("{} 或 Cargo.toml 中的 [package.metadata.solores] interfaces", CONFIG_FILE_NAME)),
+        actual: None,
+    })
+}
+
+/// 读取配置文件；Cargo.toml 中没有 `metadata.solores.interfaces` 时返回 None
+pub fn load(path: &Path) -> Result<Option<ProjectConfig>, SoloresError> {
+    let content = fs::read_to_string(path)
+        .map_err(|e| SoloresError::file_operation_error("读取项目配置", path.display().to_string(), e))?;
+    let invalid = |message: String| SoloresError::ValidationError {
+        message: format!("项目配置无效: {}", message),
+        field_path: Some(path.display().to_string()),
+        expected: None,
+        actual: None,
+    };
+    let value: toml::Value = toml::from_str(&content).map_err(|e| invalid(e.to_string()))?;
+
+    let table = if path.file_name().is_some_and(|name| name == CONFIG_FILE_NAME) {
+        Some(value)
+    } else {
+        ["package", "workspace"]
+            .into_iter()
+            .filter_map(|section| value.get(section)?.get("metadata")?.get("solores"))
+            .find(|solores| solores.get("interfaces").is_some())
+            .cloned()
+    };
+    let Some(table) = table else {
+        return Ok(None);
+    };
+    let InterfacesTable { interfaces } = table.try_into().map_err(|e: toml::de::Error| invalid(e.to_string()))?;
+    Ok(Some(ProjectConfig { path: path.to_path_buf(), interfaces }))
+}
+
+impl InterfaceConfig {
+    /// 是否匹配 `--only` 名称（IDL 文件名或 crate 名）
+    fn matches(&self, name: &str) -> bool {
+        self.idl.file_stem().is_some_and(|stem| stem == name)
+            || self.idl.file_name().is_some_and(|file| file == name)
+            || self.crate_name.as_deref() == Some(name)
+    }
+
+    /// 生成参数，输出到 `output_dir`
+    fn generator_args(&self, output_dir: &Path) -> Result<Args, SoloresError> {
+        let mut argv: Vec<OsString> = vec!["solores".into(), self.idl.clone().into()];
+        if let Some(crate_name) = &self.crate_name {
+            argv.extend(["--output-crate-name".into(), crate_name.into()]);
+        }
+        argv.extend(self.args.iter().map(OsString::from));
+        let mut args = Args::try_parse_from(argv).map_err(|e| SoloresError::ValidationError {
+            message: format!("生成参数无效: {}", e),
+            field_path: Some(format!("interfaces[{}].args", self.idl.display())),
+            expected: None,
+            actual: None,
+        })?;
+        args.batch_output_dir = output_dir.to_path_buf();
+        Ok(args)
+    }
+}
+
+/// 按项目配置重新生成接口 crate
+pub fn run(args: &ProjectArgs) -> Result<(), SoloresError> {
+    let config = match &args.config {
+        Some(path) => load(path)?.ok_or_else(|| SoloresError::ValidationError {
+            message: "配置文件中没有 interfaces".to_string(),
+            field_path: Some(path.display().to_string()),
+            expected: Some("[[interfaces]] 或 [[package.metadata.solores.interfaces]]".to_string()),
+            actual: None,
+        })?,
+        None => {
+            let cwd = std::env::current_dir()
+                .map_err(|e| SoloresError::file_operation_error("读取当前目录", ".".to_string(), e))?;
+            discover(&cwd)?
+        }
+    };
+    println!("📦 项目配置: {}", config.path.display());
+
+    // 配置中的相对路径以配置文件所在目录为基准
+    let root = config.root().to_path_buf();
+    if !root.as_os_str().is_empty() {
+        std::env::set_current_dir(&root)
+            .map_err(|e| SoloresError::file_operation_error("切换到项目目录", root.display().to_string(), e))?;
+    }
+
+    let selected: Vec<&InterfaceConfig> = config
+        .interfaces
+        .iter()
+        .filter(|interface| args.only.is_empty() || args.only.iter().any(|name| interface.matches(name)))
+        .collect();
+    if let Some(unknown) = args.only.iter().find(|name| !config.interfaces.iter().any(|i| i.matches(name))) {
+        return Err(SoloresError::ValidationError {
+            message: format!("配置中没有接口 '{}'", unknown),
+            field_path: Some(format!("--only {}", unknown)),
+            expected: None,
+            actual: None,
+        });
+    }
+
+    let mut stale = Vec::new();
+    for interface in selected {
+        if args.check {
+            if let Some(crate_dir) = check_interface(interface, !args.no_fmt)? {
+                stale.push(crate_dir);
+            }
+        } else {
+            let (crate_dir, warnings) = crate::process_single_idl_file(&interface.generator_args(&interface.output)?, &interface.idl)?;
+            crate::warnings::print(&warnings);
+            if !args.no_fmt {
+                cargo_fmt(&crate_dir);
+            }
+            println!("✅ {} -> {}", interface.idl.display(), crate_dir.display());
+        }
+    }
+
+    if stale.is_empty() {
+        return Ok(());
+    }
+    Err(SoloresError::ValidationError {
+        message: format!("{} 个接口 crate 与 IDL 不一致，请运行 cargo solores 重新生成", stale.len()),
+        field_path: Some(stale.iter().map(|dir| dir.display().to_string()).collect::<Vec<_>>().join(", ")),
+        expected: None,
+        actual: None,
+    })
+}
+
+/// 在临时目录中生成并与现有 crate 比较，过期时打印差异文件并返回 crate 目录
+fn check_interface(interface: &InterfaceConfig, fmt: bool) -> Result<Option<PathBuf>, SoloresError> {
+    let scratch_dir = std::env::temp_dir().join(format!("solores-check-{}", std::process::id()));
+    let result = crate::process_single_idl_file(&interface.generator_args(&scratch_dir)?, &interface.idl).map(|(generated_dir, _)| {
+        if fmt {
+            cargo_fmt(&generated_dir);
+        }
+        let crate_dir = interface.output.join(generated_dir.file_name().unwrap_or_default());
+        let existing = watch::snapshot(&crate_dir);
+        let changed: Vec<PathBuf> = watch::snapshot(&generated_dir)
+            .into_iter()
+            .filter(|(path, content)| existing.get(path) != Some(content))
+            .map(|(path, _)| path)
+            .collect();
+        (crate_dir, changed)
+    });
+    let _ = fs::remove_dir_all(&scratch_dir);
+
+    let (crate_dir, changed) = result?;
+    if changed.is_empty() {
+        println!("✅ {} 已是最新", crate_dir.display());
+        return Ok(None);
+    }
+    println!("❌ {} 已过期:", crate_dir.display());
+    for path in changed {
+        println!("  ~ {}", path.display());
+    }
+    Ok(Some(crate_dir))
+}
+
+/// 对生成的 crate 运行 `cargo fmt`；rustfmt 不可用时只告警
+fn cargo_fmt(crate_dir: &Path) {
+    let status = Command::new(std::env::var_os("CARGO").unwrap_or_else(|| "cargo".into()))
+        .arg("fmt")
+        .arg("--manifest-path")
+        .arg(crate_dir.join("Cargo.toml"))
+        .status();
+    match status {
+        Ok(status) if status.success() => {}
+        Ok(status) => eprintln!("⚠️  cargo fmt 失败（{}）: {}", status, crate_dir.display()),
+        Err(e) => eprintln!("⚠️  无法运行 cargo fmt: {}", e),
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn discovery_reads_solores_toml_and_cargo_metadata() {
+        let dir = std::env::temp_dir().join(format!("solores-project-test-{}", std::process::id()));
+        let nested = dir.join("app/src");
+        fs::create_dir_all(&nested).unwrap();
+        fs::write(
+            dir.join("Cargo.toml"),
+            r#"
+[workspace]
+members = ["app"]
+
+[[workspace.metadata.solores.interfaces]]
+idl = "idls/pump_amm.json"
+output = "crates"
+args = ["--generate-parser"]
+"#,
+        )
+        .unwrap();
+        // 生成 crate 的 Cargo.toml 也有 metadata.solores，但没有 interfaces
+        fs::write(
+            dir.join("app/Cargo.toml"),
+            "[package]\nname = \"app\"\n\n[package.metadata.solores]\ngenerator-version = \"0.8.0\"\n",
+        )
+        .unwrap();
+
+        let config = discover(&nested).unwrap();
+        assert_eq!(config.path, dir.join("Cargo.toml"));
+        assert_eq!(config.interfaces[0].output, PathBuf::from("crates"));
+        assert!(config.interfaces[0].matches("pump_amm"));
+
+        fs::write(dir.join("app").join(CONFIG_FILE_NAME), "[[interfaces]]\nidl = \"dlmm.json\"\ncrate-name = \"dlmm\"\n").unwrap();
+        let config = discover(&nested).unwrap();
+        assert_eq!(config.root(), dir.join("app"));
+        assert_eq!(config.interfaces[0].output, PathBuf::from("."));
+        assert!(config.interfaces[0].matches("dlmm"));
+
+        fs::write(dir.join("app").join(CONFIG_FILE_NAME), "[[interfaces]]\nidl = \"dlmm.json\"\nunknown = 1\n").unwrap();
+        assert!(discover(&nested).is_err());
+
+        fs::remove_dir_all(&dir).unwrap();
+    }
+}
@@ -0,0 +1,164 @@
+//! 生成文件头与可复现输出
+//!
+//! 每个生成文件首行写入 `@generated` 标记：solores 版本、源IDL sha256 与生成选项哈希。
+//! 相同版本、相同IDL、相同选项必然得到逐字节一致的 crate，便于缓存与 diff；
+//! 生成内容中不再包含墙钟时间，统一库的时间戳遵循 `SOURCE_DATE_EPOCH`。
+
+use std::fs;
+use std::path::Path;
+
+use crate::{cargo::idl_sha256, error::SoloresError, idl_format::field_types, minijinja::field_hints, Args};
+
+/// 文件头标记，重复生成时据此替换旧的文件头
+const HEADER_MARKER: &str = "@generated by solores";
+
+/// 一次生成的溯源信息
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct GenerationStamp {
+    pub solores_version: String,
+    /// 源IDL的 sha256，格式为 `sha256:<hex>`
+    pub idl_hash: String,
+    /// 影响生成内容的选项的 sha256，格式为 `sha256:<hex>`
+    pub options_hash: String,
+}
+
+impl GenerationStamp {
+    /// 由命令行参数计算，读取源IDL与 `--field-hints-file`
+    pub fn from_args(args: &Args) -> Result<Self, SoloresError> {
+        let idl = fs::read(&args.idl_path).map_err(|e| {
+            SoloresError::file_operation_error("读取IDL文件计算哈希", args.idl_path.display().to_string(), e)
+        })?;
+        Ok(Self {
+            solores_version: env!("CARGO_PKG_VERSION").to_string(),
+            idl_hash: idl_sha256(&idl),
+            options_hash: options_hash(args)?,
+        })
+    }
+
+    /// 按注释前缀渲染文件头（不含换行）
+    fn line(&self, comment: (&str, &str)) -> String {
+        format!(
+            "{} {} {} | idl {} | options {}{}",
+            comment.0, HEADER_MARKER, self.solores_version, self.idl_hash, self.options_hash, comment.1
+        )
+    }
+}
+
+/// 生成选项哈希
+///
+/// 只包含影响生成内容的参数：路径、批量扫描、警告与预演等参数被规范化，
+/// `--field-hints-file` / `--field-types-file` 以文件中的条目计入。
+pub fn options_hash(args: &Args) -> Result<String, SoloresError> {
+    let mut options = args.clone();
+    if let Some(path) = options.field_hints_file.take() {
+        options.field_hint.extend(field_hints::load_field_hints_file(&path)?);
+    }
+    if let Some(path) = options.field_types_file.take() {
+        options.field_type.extend(field_types::load_field_types_file(&path)?);
+    }
+    options.idl_path = Default::default();
+    options.output_dir = Default::default();
+    options.batch_output_dir = Default::default();
+    options.batch_include = Default::default();
+    options.batch_exclude = Default::default();
+    options.resume = false;
+    options.group_output_by = Default::default();
+    options.allow = Vec::new();
+    options.deny_warnings = false;
+    options.dry_run = false;
+    options.stdout_single_file = false;
+    options.single_file = None;
+    Ok(idl_sha256(format!("{:?}", options).as_bytes()))
+}
+
+/// 各类文件的注释语法，不支持注释的文件（如 JSON）不写入文件头
+fn comment_syntax(path: &Path) -> Option<(&'static str, &'static str)> {
+    let name = path.file_name()?.to_str()?;
+    match path.extension().and_then(|ext| ext.to_str()) {
+        Some("rs") => Some(("//", "")),
+        Some("toml") => Some(("#", "")),
+        Some("md") => Some(("<!--", " -->")),
+        _ if name == ".gitignore" => Some(("#", "")),
+        _ => None,
+    }
+}
+
+/// 为 crate 目录下所有生成文件写入文件头，按路径排序遍历
+pub fn stamp_generated_files(dir: &Path, stamp: &GenerationStamp) -> Result<(), SoloresError> {
+    let mut entries: Vec<_> = fs::read_dir(dir)
+        .map_err(|e| SoloresError::file_operation_error("读取目录", dir.display().to_string(), e))?
+        .flatten()
+        .map(|entry| entry.path())
+        .collect();
+    entries.sort();
+
+    for path in entries {
+        if path.is_dir() {
+            // 生成 crate 的构建产物不属于生成文件
+            if path.file_name().is_some_and(|name| name == "target") {
+                continue;
+            }
+            stamp_generated_files(&path, stamp)?;
+        } else if let Some(comment) = comment_syntax(&path) {
+            let content = fs::read_to_string(&path)
+                .map_err(|e| SoloresError::file_operation_error("读取生成文件", path.display().to_string(), e))?;
+            fs::write(&path, with_header(&content, &stamp.line(comment)))
+                .map_err(|e| SoloresError::file_operation_error("写入文件头", path.display().to_string(), e))?;
+        }
+    }
+    Ok(())
+}
+
+/// 在内容首行写入文件头，已有文件头时替换
+fn with_header(content: &str, header: &str) -> String {
+    let body = match content.split_once('\n') {
+        Some((first, rest)) if first.contains(HEADER_MARKER) => rest,
+        None if content.contains(HEADER_MARKER) => "",
+        _ => content,
+    };
+    format!("{}\n{}", header, body)
+}
+
+/// 统一库文件中的生成时间：设置了 `SOURCE_DATE_EPOCH` 时使用该时间以保证可复现
+pub fn generation_time() -> String {
+    let time = std::env::var("SOURCE_DATE_EPOCH")
+        .ok()
+        .and_then(|epoch| epoch.trim().parse::<i64>().ok())
+        .and_then(|epoch| chrono::DateTime::from_timestamp(epoch, 0))
+        .unwrap_or_else(chrono::Utc::now);
+    time.format("%Y-%m-%d %H:%M:%S UTC").to_string()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn header_is_replaced_not_stacked() {
+        let stamp = GenerationStamp {
+            solores_version: "1.0.0".to_string(),
+            idl_hash: "sha256:aa".to_string(),
+            options_hash: "sha256:bb".to_string(),
+        };
+        let header = stamp.line(("//", ""));
+        assert_eq!(header, "// @generated by solores 1.0.0 | idl sha256:aa | options sha256:bb");
+
+        let once = with_header("pub struct A;\n", &header);
+        assert_eq!(with_header(&once, &header), once);
+        assert_eq!(stamp.line(("<!--", " -->")), format!("<!-- {} -->", &header[3..]));
+    }
+
+    #[test]
+    fn options_hash_ignores_paths() {
+        let args = Args { output_crate_name: "demo".to_string(), ..Default::default() };
+        let moved = Args {
+            idl_path: "a/idl.json".into(),
+            output_dir: "out".into(),
+            ..args.clone()
+        };
+        let parser = Args { generate_parser: true, ..args.clone() };
+
+        assert_eq!(options_hash(&args).unwrap(), options_hash(&moved).unwrap());
+        assert_ne!(options_hash(&args).unwrap(), options_hash(&parser).unwrap());
+    }
+}
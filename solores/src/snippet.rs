@@ -0,0 +1,225 @@
+//! 单条目代码片段
+//!
+//! `solores snippet idl.json --item Deposit --kind instruction` 在临时目录中生成完整 crate，
+//! 只打印该条目所在的生成文件（指令为 IxData、Keys 与构建函数，账户为结构体与解码方法），
+//! 指令另附解析器中对应的 `ProgramInstruction::parse_x`。用于只需要某个条目、
+//! 要粘贴进已有代码库的场景；片段中的 `crate::` 路径指向生成 crate 的其他模块。
+
+use std::ffi::OsString;
+use std::fs::{self, OpenOptions};
+use std::path::{Path, PathBuf};
+
+use clap::Parser;
+use convert_case::{Case, Casing};
+use syn::{AttrStyle, ImplItem, Item};
+
+use crate::{error::SoloresError, idl_format::IdlFormat, pipeline, Args};
+
+/// 片段条目类别
+#[derive(clap::ValueEnum, Debug, Default, Clone, Copy, PartialEq, Eq)]
+pub enum SnippetKind {
+    #[default]
+    Instruction,
+    Account,
+    Type,
+    Event,
+}
+
+impl SnippetKind {
+    /// 生成 crate 中的模块目录
+    fn module(self) -> &'static str {
+        match self {
+            SnippetKind::Instruction => "instructions",
+            SnippetKind::Account => "accounts",
+            SnippetKind::Type => "types",
+            SnippetKind::Event => "events",
+        }
+    }
+
+    /// 标识条目所在文件的类型名
+    fn type_name(self, item: &str) -> String {
+        match self {
+            SnippetKind::Instruction => format!("{}IxData", item),
+            _ => item.to_string(),
+        }
+    }
+}
+
+/// `solores snippet` 参数
+#[derive(clap::Args, Debug, Clone)]
+pub struct SnippetArgs {
+    /// IDL 文件
+    pub idl: PathBuf,
+    /// 条目名称，按生成的类型名（PascalCase）匹配
+    #[arg(long)]
+    pub item: String,
+    /// 条目类别
+    #[arg(long, value_enum, default_value_t = SnippetKind::Instruction)]
+    pub kind: SnippetKind,
+    /// 传给生成器的参数（如 `-- --type-prefix My`），与生成 crate 时相同
+    #[arg(last = true)]
+    pub generator_args: Vec<String>,
+}
+
+/// 执行 `solores snippet`，片段打印到标准输出
+pub fn run(args: &SnippetArgs) -> Result<(), SoloresError> {
+    print!("{}", snippet(&args.idl, args.kind, &args.item, &args.generator_args)?);
+    Ok(())
+}
+
+/// 生成单个条目的代码片段
+pub fn snippet<I, S>(idl_path: &Path, kind: SnippetKind, item: &str, generator_args: I) -> Result<String, SoloresError>
+where
+    I: IntoIterator<Item = S>,
+    S: Into<OsString>,
+{
+    let mut args = Args::try_parse_from(
+        [OsString::from("solores"), idl_path.as_os_str().to_owned()]
+            .into_iter()
+            .chain(generator_args.into_iter().map(Into::into))
+            .chain([OsString::from("--generate-parser")]),
+    )
+    .map_err(|e| SoloresError::ValidationError {
+        message: format!("生成参数无效: {}", e),
+        field_path: None,
+        expected: None,
+        actual: None,
+    })?;
+
+    let mut file = OpenOptions::new()
+        .read(true)
+        .open(idl_path)
+        .map_err(|e| SoloresError::file_operation_error("读取IDL文件", idl_path.display().to_string(), e))?;
+    let (idl, _) = crate::load_idl_with_format(&mut file, args.force_format)?;
+
+    if args.output_crate_name == crate::DEFAULT_OUTPUT_CRATE_NAME_MSG {
+        args.output_crate_name = format!("sol_{}_interface", idl.program_name());
+    }
+    args.program_id = args.program_id.filter(|id| id != crate::DEFAULT_PROGRAM_ID_MSG);
+    // 片段按单文件模块布局查找
+    args.compat = None;
+    let scratch_dir = std::env::temp_dir().join(format!("solores-snippet-{}", std::process::id()));
+    args.output_dir = scratch_dir.join(&args.output_crate_name);
+
+    let item = item.to_case(Case::Pascal);
+    let result = pipeline::run(&args, &idl).and_then(|_| extract(&args.output_dir.join("src"), kind, &item));
+    let _ = fs::remove_dir_all(&scratch_dir);
+    result
+}
+
+/// 在生成的 `src_dir` 中提取条目文件与解析函数
+fn extract(src_dir: &Path, kind: SnippetKind, item: &str) -> Result<String, SoloresError> {
+    let module_dir = src_dir.join(kind.module());
+    let type_name = kind.type_name(item);
+    let mut candidates = Vec::new();
+    let mut files: Vec<PathBuf> = fs::read_dir(&module_dir)
+        .map_err(|e| SoloresError::file_operation_error("读取生成代码", module_dir.display().to_string(), e))?
+        .flatten()
+        .map(|entry| entry.path())
+        .filter(|path| path.extension().is_some_and(|ext| ext == "rs"))
+        .collect();
+    files.sort();
+
+    for path in files {
+        let mut file = parse_file(&path)?;
+        let defined = |name: &str| {
+            file.items.iter().any(|item| match item {
+                Item::Struct(s) => s.ident == name,
+                Item::Enum(e) => e.ident == name,
+                _ => false,
+            })
+        };
+        if !defined(&type_name) {
+            candidates.extend(main_type(&file, kind));
+            continue;
+        }
+
+        // 粘贴到已有文件中时不能携带 `//!` 文档与 crate 级属性
+        file.attrs.retain(|attr| matches!(attr.style, AttrStyle::Outer));
+        let relative = path.strip_prefix(src_dir).unwrap_or(&path).display().to_string();
+        let mut snippet = format!("// {} `{}` from src/{}\n{}", kind.module(), item, relative, prettyplease::unparse(&file));
+        if kind == SnippetKind::Instruction {
+            if let Some(parser) = instruction_parser(&src_dir.join("parsers/instructions.rs"), item)? {
+                snippet.push_str(&format!("\n// parser from src/parsers/instructions.rs\n{}", parser));
+            }
+        }
+        return Ok(snippet);
+    }
+
+    Err(SoloresError::ValidationError {
+        message: format!("未找到{} '{}'", kind.module(), item),
+        field_path: Some("--item".to_string()),
+        expected: Some(candidates.join(", ")),
+        actual: Some(item.to_string()),
+    })
+}
+
+/// 文件定义的条目名称：指令取 `{Name}IxData` 的前缀，其他类别取第一个结构体或枚举
+fn main_type(file: &syn::File, kind: SnippetKind) -> Option<String> {
+    file.items.iter().find_map(|item| {
+        let ident = match item {
+            Item::Struct(s) => s.ident.to_string(),
+            Item::Enum(e) if kind != SnippetKind::Instruction => e.ident.to_string(),
+            _ => return None,
+        };
+        match kind {
+            SnippetKind::Instruction => ident.strip_suffix("IxData").map(str::to_string),
+            _ => Some(ident),
+        }
+    })
+}
+
+/// `impl ProgramInstruction` 中构造 `Self::{item}` 的解析函数，单独包在 impl 块中
+fn instruction_parser(path: &Path, item: &str) -> Result<Option<String>, SoloresError> {
+    if !path.is_file() {
+        return Ok(None);
+    }
+    let file = parse_file(path)?;
+    let variant = format!("Self :: {} (", item);
+    for parsed in &file.items {
+        let Item::Impl(block) = parsed else { continue };
+        let Some(function) = block.items.iter().find(|impl_item| match impl_item {
+            ImplItem::Fn(function) => {
+                function.sig.ident.to_string().starts_with("parse_")
+                    && quote::ToTokens::to_token_stream(&function.block).to_string().contains(&variant)
+            }
+            _ => false,
+        }) else {
+            continue;
+        };
+        let mut block = block.clone();
+        block.items = vec![function.clone()];
+        let file = syn::File { shebang: None, attrs: Vec::new(), items: vec![Item::Impl(block)] };
+        return Ok(Some(prettyplease::unparse(&file)));
+    }
+    Ok(None)
+}
+
+fn parse_file(path: &Path) -> Result<syn::File, SoloresError> {
+    let content = fs::read_to_string(path)
+        .map_err(|e| SoloresError::file_operation_error("读取生成代码", path.display().to_string(), e))?;
+    syn::parse_file(&content).map_err(|e| SoloresError::CodeGenError {
+        module: path.display().to_string(),
+        reason: format!("生成代码解析失败: {}", e),
+        context: None,
+    })
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn instruction_snippet_contains_item_and_parser_only() {
+        let idl = Path::new(env!("CARGO_MANIFEST_DIR")).join("../idls/pump_amm.json");
+        let buy = snippet(&idl, SnippetKind::Instruction, "buy", std::iter::empty::<String>()).unwrap();
+        assert!(buy.contains("pub struct BuyIxData"));
+        assert!(buy.contains("pub struct BuyKeys"));
+        assert!(buy.contains("fn parse_buy("));
+        assert!(!buy.contains("SellIxData"));
+        assert!(!buy.contains("//!"));
+
+        let missing = snippet(&idl, SnippetKind::Account, "Nope", std::iter::empty::<String>()).unwrap_err();
+        assert!(matches!(missing, SoloresError::ValidationError { expected: Some(expected), .. } if expected.contains("Pool")));
+    }
+}
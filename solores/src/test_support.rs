@@ -0,0 +1,67 @@
+//! 生成 crate 的解码测试
+//!
+//! 编译通过不能说明编解码正确。这里把 IDL 生成到临时目录，写入一个集成测试文件，
+//! 再对生成的 crate 运行 `cargo test`，让断言直接作用于生成代码。
+//! 所有调用共享同一个 target 目录，依赖只编译一次。
+
+use std::ffi::OsString;
+use std::fs;
+use std::path::{Path, PathBuf};
+use std::process::Command;
+
+use clap::Parser;
+
+use crate::{idl_format::IdlFormat, pipeline, Args};
+
+/// 把 `idl_json` 生成到临时目录，返回生成的 crate 目录
+pub(crate) fn generate_crate(name: &str, idl_json: &str, generator_args: &[&str]) -> PathBuf {
+    let scratch_dir = std::env::temp_dir().join(format!("solores-decode-{}-{}", name, std::process::id()));
+    let _ = fs::remove_dir_all(&scratch_dir);
+    fs::create_dir_all(&scratch_dir).unwrap();
+    let idl_path = scratch_dir.join(format!("{}.json", name));
+    fs::write(&idl_path, idl_json).unwrap();
+
+    let argv = [OsString::from("solores"), idl_path.clone().into_os_string()]
+        .into_iter()
+        .chain(generator_args.iter().map(OsString::from));
+    let mut args = Args::try_parse_from(argv).unwrap();
+    let idl = crate::load_idl_as_enum(&mut fs::File::open(&idl_path).unwrap()).unwrap();
+    args.output_crate_name = format!("sol_{}_interface", idl.program_name());
+    args.program_id = args.program_id.filter(|id| id != crate::DEFAULT_PROGRAM_ID_MSG);
+    args.output_dir = scratch_dir.join(&args.output_crate_name);
+    pipeline::run(&args, &idl).unwrap();
+    args.output_dir
+}
+
+/// 生成 `idl_json` 对应的 crate，写入 `tests/decode.rs` 并运行，失败时打印 cargo 输出
+pub(crate) fn run_decode_test(name: &str, idl_json: &str, generator_args: &[&str], features: &[&str], test_source: &str) {
+    let output_dir = generate_crate(name, idl_json, generator_args);
+    run_generated_test(name, &output_dir, features, test_source);
+}
+
+/// 在 [`generate_crate`] 生成的 crate 中写入 `tests/decode.rs` 并运行，通过后删除临时目录
+pub(crate) fn run_generated_test(name: &str, output_dir: &Path, features: &[&str], test_source: &str) {
+    let tests_dir = output_dir.join("tests");
+    fs::create_dir_all(&tests_dir).unwrap();
+    fs::write(tests_dir.join("decode.rs"), test_source).unwrap();
+
+    let output = Command::new("cargo")
+        .args(["test", "--quiet", "--test", "decode"])
+        .arg(format!("--features={}", features.join(",")))
+        .env("CARGO_TARGET_DIR", std::env::temp_dir().join("solores-decode-target"))
+        .current_dir(output_dir)
+        .output()
+        .unwrap();
+    let passed = output.status.success();
+    if passed {
+        let _ = fs::remove_dir_all(output_dir.parent().unwrap());
+    }
+    assert!(
+        passed,
+        "{} 的解码测试失败（生成目录保留在 {}）\n{}\n{}",
+        name,
+        output_dir.display(),
+        String::from_utf8_lossy(&output.stdout),
+        String::from_utf8_lossy(&output.stderr)
+    );
+}
@@ -21,11 +21,28 @@ use void::Void;
 
 pub const PUBKEY_TOKEN: &str = "Pubkey";
 
+const MAX_BASE58_LEN: usize = 44;
+const PUBKEY_BYTES_SIZE: usize = 32;
+
+/// Copied from solana_program::Pubkey::from_str()
+/// so that we dont have to have solana_program as a dep
+pub fn is_valid_pubkey(s: &str) -> bool {
+    if s.len() > MAX_BASE58_LEN {
+        return false;
+    }
+    let pubkey_vec = match bs58::decode(s).into_vec() {
+        Ok(v) => v,
+        Err(_) => return false,
+    };
+    pubkey_vec.len() == PUBKEY_BYTES_SIZE
+}
+
 pub fn primitive_or_pubkey_to_token(s: &str) -> String {
     match s {
         "publicKey" | "pubkey" | "Pubkey" => PUBKEY_TOKEN.to_owned(),
         "string" => s.to_pascal_case(),
-        "bytes" => "Vec<u8>".to_owned(),
+        #[cfg(feature = "bytes_to_u8")]
+        "bytes" => "u8".to_owned(),
         _ => s.to_owned(),
     }
 }
@@ -132,7 +149,7 @@ where
 
 pub fn conditional_pascal_case(s: &str) -> String {
     // Only apply PascalCase if the string does not start with an uppercase letter.
-    if s.chars().next().map_or(false, |c| c.is_uppercase()) {
+    if s.chars().next().is_some_and(|c| c.is_uppercase()) {
         s.to_string()
     } else {
         s.to_pascal_case()
@@ -0,0 +1,579 @@
+//! 结构化生成警告
+//!
+//! 解析与生成过程中的降级处理（未定义的类型按 `crate::types` 引用、无字段事件、
+//! 只能靠长度区分的账户、生成后重名的条目、超出 u8 的枚举标签、缺失的指令 discriminator）
+//! 以及因IDL格式或缺少依赖选项而跳过的输出，以往只记录在 debug 日志中。
+//! 渲染前在命名后的 IR 上统一检查，收集为 [`Warning`]，在生成结束时和批量报告中输出。
+//! `--allow <code>` 按代码屏蔽，`--deny-warnings` 将剩余警告视为错误。
+
+use std::collections::{BTreeMap, HashSet};
+use std::fmt;
+
+use convert_case::{Case, Casing};
+use regex::Regex;
+
+use crate::{
+    error::SoloresError,
+    idl_format::{
+        anchor_idl::{AnchorField, AnchorTypeKind},
+        enum_tags::{EnumTagLayout, EnumTagMode},
+        non_anchor_idl::{NonAnchorField, NonAnchorTypeKind},
+        IdlFormatEnum,
+    },
+    minijinja::{externs::ExternImports, type_mapper, utils},
+    Args,
+};
+
+/// 警告代码，供 `--allow` 使用
+#[derive(clap::ValueEnum, Debug, Clone, Copy, PartialEq, Eq, Hash, PartialOrd, Ord)]
+pub enum WarningCode {
+    /// 字段引用了 IDL 中未定义的类型
+    UnknownType,
+    /// 事件没有可用的字段定义
+    EmptyEvent,
+    /// 多个账户长度相同，按长度解析时无法区分
+    AmbiguousAccountSize,
+    /// 多个条目生成相同的 Rust 名称
+    NameCollision,
+    /// 枚举判别值超出 u8 或重复
+    EnumTag,
+    /// NonAnchor 指令未声明 discriminator
+    MissingDiscriminator,
+    /// 请求的输出与IDL或其他选项不兼容，未生成
+    SkippedOutput,
+}
+
+impl WarningCode {
+    pub fn as_str(&self) -> &'static str {
+        match self {
+            WarningCode::UnknownType => "unknown-type",
+            WarningCode::EmptyEvent => "empty-event",
+            WarningCode::AmbiguousAccountSize => "ambiguous-account-size",
+            WarningCode::NameCollision => "name-collision",
+            WarningCode::EnumTag => "enum-tag",
+            WarningCode::MissingDiscriminator => "missing-discriminator",
+            WarningCode::SkippedOutput => "skipped-output",
+        }
+    }
+}
+
+impl fmt::Display for WarningCode {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.write_str(self.as_str())
+    }
+}
+
+/// 单条生成警告
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum Warning {
+    /// 未定义的类型按 `crate::types::<Name>` 生成，生成的 crate 无法编译
+    UnknownType { location: String, type_name: String },
+    /// 事件既无字段也找不到同名类型，生成的结构体不解码任何数据
+    EmptyEvent { event: String },
+    /// 无 discriminator 的账户长度相同，解析器总是匹配第一个
+    AmbiguousAccountSize { size: usize, accounts: Vec<String> },
+    /// 多个条目生成同一名称，后生成的覆盖先生成的
+    NameCollision { scope: String, name: String, sources: Vec<String> },
+    /// 变体数或判别值超出 u8，生成手写的宽标签 borsh 实现
+    WideEnumTag { type_name: String, variants: usize, tag_type: &'static str },
+    /// 多个变体使用同一判别值，生成的枚举无法编译
+    DuplicateEnumTag { type_name: String, values: Vec<u64> },
+    /// 单指令程序没有 discriminator，指令数据按参数直接解析
+    UndiscriminatedInstruction { instruction: String },
+    /// 多指令程序中缺失 discriminator 的指令按索引生成1字节 discriminator
+    IndexDiscriminator { instruction: String, index: usize },
+    /// 选项请求的输出因IDL格式或缺少依赖的选项而未生成
+    SkippedOutput { option: &'static str, output: &'static str, reason: &'static str },
+}
+
+impl Warning {
+    pub fn code(&self) -> WarningCode {
+        match self {
+            Warning::UnknownType { .. } => WarningCode::UnknownType,
+            Warning::EmptyEvent { .. } => WarningCode::EmptyEvent,
+            Warning::AmbiguousAccountSize { .. } => WarningCode::AmbiguousAccountSize,
+            Warning::NameCollision { .. } => WarningCode::NameCollision,
+            Warning::WideEnumTag { .. } | Warning::DuplicateEnumTag { .. } => WarningCode::EnumTag,
+            Warning::UndiscriminatedInstruction { .. } | Warning::IndexDiscriminator { .. } => {
+                WarningCode::MissingDiscriminator
+            }
+            Warning::SkippedOutput { .. } => WarningCode::SkippedOutput,
+        }
+    }
+}
+
+impl fmt::Display for Warning {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(f, "[{}] ", self.code())?;
+        match self {
+            Warning::UnknownType { location, type_name } => write!(
+                f,
+                "{}: 类型 '{}' 未在IDL中定义，按 crate::types::{} 生成",
+                location, type_name, type_name
+            ),
+            Warning::EmptyEvent { event } => {
+                write!(f, "事件 {} 没有字段定义，也找不到同名类型，生成的事件不包含任何数据", event)
+            }
+            Warning::AmbiguousAccountSize { size, accounts } => write!(
+                f,
+                "账户 {} 长度均为 {} 字节，按长度解析时总是匹配 {}",
+                accounts.join(", "),
+                size,
+                accounts[0]
+            ),
+            Warning::NameCollision { scope, name, sources } => {
+                write!(f, "{} 中的 {} 都生成为 {}", scope, sources.join(", "), name)
+            }
+            Warning::WideEnumTag { type_name, variants, tag_type } => write!(
+                f,
+                "枚举 {} 有 {} 个变体或判别值超出 u8，按 {} 标签手写 borsh 实现，与 borsh 默认编码不兼容",
+                type_name, variants, tag_type
+            ),
+            Warning::DuplicateEnumTag { type_name, values } => write!(
+                f,
+                "枚举 {} 的判别值 {} 被多个变体使用",
+                type_name,
+                values.iter().map(|v| v.to_string()).collect::<Vec<_>>().join(", ")
+            ),
+            Warning::UndiscriminatedInstruction { instruction } => write!(
+                f,
+                "指令 {} 没有 discriminator，作为程序唯一的指令，解析器直接按参数反序列化指令数据",
+                instruction
+            ),
+            Warning::IndexDiscriminator { instruction, index } => write!(
+                f,
+                "指令 {} 没有 discriminator，按其在IDL中的位置使用 {} 作为1字节 discriminator",
+                instruction, index
+            ),
+            Warning::SkippedOutput { option, output, reason } => {
+                write!(f, "{} {}，未生成 {}", option, reason, output)
+            }
+        }
+    }
+}
+
+/// 收集 IR 中的全部警告（不做过滤）
+pub fn collect(ir: &IdlFormatEnum) -> Vec<Warning> {
+    let inventory = Inventory::from_ir(ir);
+    let mut warnings = Vec::new();
+
+    let type_ref = Regex::new(r"crate::types::(\w+)").unwrap();
+    for (location, rust_type) in &inventory.field_types {
+        for captures in type_ref.captures_iter(rust_type) {
+            let type_name = &captures[1];
+            if !inventory.defined.contains(type_name) {
+                warnings.push(Warning::UnknownType {
+                    location: location.clone(),
+                    type_name: type_name.to_string(),
+                });
+            }
+        }
+    }
+
+    warnings.extend(inventory.empty_events.into_iter().map(|event| Warning::EmptyEvent { event }));
+
+    let mut by_size: BTreeMap<usize, Vec<String>> = BTreeMap::new();
+    for (name, size) in inventory.length_identified_accounts {
+        by_size.entry(size).or_default().push(name);
+    }
+    warnings.extend(
+        by_size
+            .into_iter()
+            .filter(|(_, accounts)| accounts.len() > 1)
+            .map(|(size, accounts)| Warning::AmbiguousAccountSize { size, accounts }),
+    );
+
+    for (scope, names, case) in &inventory.name_groups {
+        let mut generated: BTreeMap<String, Vec<String>> = BTreeMap::new();
+        for name in names {
+            generated.entry(name.to_case(*case)).or_default().push(name.clone());
+        }
+        warnings.extend(
+            generated
+                .into_iter()
+                .filter(|(_, sources)| sources.len() > 1)
+                .map(|(name, sources)| Warning::NameCollision { scope: scope.clone(), name, sources }),
+        );
+    }
+
+    for (type_name, layout) in inventory.enum_tags {
+        if layout.mode == EnumTagMode::Wide {
+            warnings.push(Warning::WideEnumTag {
+                type_name: type_name.clone(),
+                variants: layout.values.len(),
+                tag_type: layout.tag_type(),
+            });
+        }
+        if !layout.duplicates.is_empty() {
+            warnings.push(Warning::DuplicateEnumTag { type_name, values: layout.duplicates });
+        }
+    }
+
+    if let IdlFormatEnum::NonAnchor(idl) = ir {
+        if let Some(instruction) = idl.undiscriminated_instruction() {
+            warnings.push(Warning::UndiscriminatedInstruction { instruction: instruction.name.clone() });
+        } else {
+            warnings.extend(
+                idl.instructions()
+                    .iter()
+                    .enumerate()
+                    .filter(|(_, instruction)| instruction.discriminator.as_ref().is_none_or(|disc| disc.is_empty()))
+                    .map(|(index, instruction)| Warning::IndexDiscriminator {
+                        instruction: instruction.name.clone(),
+                        index,
+                    }),
+            );
+        }
+    }
+
+    warnings
+}
+
+/// 与IDL格式或其他选项不兼容、渲染时会跳过的输出
+///
+/// 条件与 [`crate::minijinja::MinijinjaTemplateGenerator`] 中的跳过条件一致
+pub fn skipped_outputs(args: &Args, ir: &IdlFormatEnum) -> Vec<Warning> {
+    let is_anchor = matches!(ir, IdlFormatEnum::Anchor(_));
+    let mut warnings = Vec::new();
+    if args.accounts_as_traits && !is_anchor {
+        warnings.push(Warning::SkippedOutput {
+            option: "--accounts-as-traits",
+            output: "account_ext.rs",
+            reason: "仅支持 Anchor IDL（非 Anchor 账户没有 discriminator）",
+        });
+    }
+    if args.interop_with.is_some() {
+        // 替换类型与对方类型之间没有对称的转换
+        let reason = if !is_anchor {
+            Some("仅支持 Anchor IDL")
+        } else if ir.has_field_types() {
+            Some("不支持 --field-type 替换的字段")
+        } else {
+            None
+        };
+        if let Some(reason) = reason {
+            warnings.push(Warning::SkippedOutput { option: "--interop-with", output: "interop.rs", reason });
+        }
+    }
+    // 以下输出都基于生成的解析器
+    if !args.generate_parser {
+        let parser_outputs = [
+            (args.gen_history_stats, "--gen-history-stats", "history.rs"),
+            (args.gen_program_tests, "--gen-program-tests", "tests/program_test.rs"),
+            (args.gen_benches, "--gen-benches", "benches/parsers.rs"),
+            (args.fixtures_from_rpc.is_some(), "--fixtures-from-rpc", "tests/rpc_fixtures.rs"),
+        ];
+        for (_, option, output) in parser_outputs.into_iter().filter(|(requested, ..)| *requested) {
+            warnings.push(Warning::SkippedOutput { option, output, reason: "需要 --generate-parser" });
+        }
+    }
+    // JsonSchema 派生依赖字段上的 serde 属性
+    if args.gen_jsonschema && !args.generate_to_json {
+        warnings.push(Warning::SkippedOutput {
+            option: "--gen-jsonschema",
+            output: "JSON Schema（schemas/ 与 JsonSchema 派生）",
+            reason: "需要 serde 特性（--generate-to-json）",
+        });
+    }
+    warnings
+}
+
+/// 按 `--allow` 过滤警告；启用 `--deny-warnings` 且仍有警告时返回错误
+pub fn check(args: &Args, ir: &IdlFormatEnum) -> Result<Vec<Warning>, SoloresError> {
+    // --extern 提供的类型不是未定义类型
+    let externs = ExternImports::resolve(&args.externs, ir)?;
+    let warnings: Vec<Warning> = collect(ir)
+        .into_iter()
+        .chain(skipped_outputs(args, ir))
+        .filter(|warning| !matches!(warning, Warning::UnknownType { type_name, .. } if externs.provides(type_name)))
+        .filter(|warning| !args.allow.contains(&warning.code()))
+        .collect();
+    for warning in &warnings {
+        log::warn!("⚠️  {}", warning);
+    }
+
+    if args.deny_warnings && !warnings.is_empty() {
+        let codes: std::collections::BTreeSet<_> = warnings.iter().map(|w| w.code().as_str()).collect();
+        return Err(SoloresError::ValidationError {
+            message: format!(
+                "存在 {} 条生成警告（--deny-warnings）:\n{}",
+                warnings.len(),
+                warnings.iter().map(|w| format!("  - {}", w)).collect::<Vec<_>>().join("\n")
+            ),
+            field_path: None,
+            expected: Some("无警告，或使用 --allow <code> 屏蔽".to_string()),
+            actual: Some(codes.into_iter().collect::<Vec<_>>().join(", ")),
+        });
+    }
+    Ok(warnings)
+}
+
+/// 打印警告列表（生成结束时）
+pub fn print(warnings: &[Warning]) {
+    for warning in warnings {
+        eprintln!("⚠️  {}", warning);
+    }
+}
+
+/// 与IDL格式无关的检查素材
+#[derive(Default)]
+struct Inventory {
+    /// 可被 `crate::types::X` 引用的名称（PascalCase）
+    defined: HashSet<String>,
+    /// (位置, 映射后的Rust类型)
+    field_types: Vec<(String, String)>,
+    /// 无可用字段的事件
+    empty_events: Vec<String>,
+    /// 只靠长度识别的账户及其长度
+    length_identified_accounts: Vec<(String, usize)>,
+    /// (范围, 原始名称, 生成时使用的命名风格)
+    name_groups: Vec<(String, Vec<String>, Case)>,
+    /// 枚举及其标签布局
+    enum_tags: Vec<(String, EnumTagLayout)>,
+}
+
+impl Inventory {
+    fn from_ir(ir: &IdlFormatEnum) -> Self {
+        let mut inventory = Inventory::default();
+        match ir {
+            IdlFormatEnum::Anchor(idl) => {
+                let accounts = idl.accounts.as_deref().unwrap_or_default();
+                let types = idl.types.as_deref().unwrap_or_default();
+                let events = idl.events.as_deref().unwrap_or_default();
+                let instructions = idl.instructions.as_deref().unwrap_or_default();
+
+                let struct_types: HashSet<&str> = types
+                    .iter()
+                    .filter(|t| matches!(t.kind, Some(AnchorTypeKind::Struct(_))))
+                    .map(|t| t.name.as_str())
+                    .collect();
+                inventory.define(accounts.iter().map(|a| &a.name).chain(types.iter().map(|t| &t.name)));
+
+                for account in accounts {
+                    inventory.anchor_fields(&account.name, account.fields.as_deref());
+                }
+                for type_def in types {
+                    match &type_def.kind {
+                        Some(AnchorTypeKind::Struct(fields)) => inventory.anchor_fields(&type_def.name, Some(fields)),
+                        Some(AnchorTypeKind::Enum(variants)) => {
+                            for variant in variants {
+                                let owner = format!("{}::{}", type_def.name, variant.name);
+                                inventory.anchor_fields(&owner, variant.fields.as_deref());
+                            }
+                            inventory.group(format!("{} 的变体", type_def.name), variants.iter().map(|v| &v.name), Case::Pascal);
+                            let layout = EnumTagLayout::from_values(variants.iter().map(|v| v.value));
+                            inventory.enum_tags.push((type_def.name.clone(), layout));
+                        }
+                        Some(AnchorTypeKind::Alias(value)) => inventory
+                            .field_types
+                            .push((type_def.name.clone(), type_mapper::map_field_type(value))),
+                        None => {}
+                    }
+                }
+                for instruction in instructions {
+                    inventory.anchor_fields(&instruction.name, instruction.args.as_deref());
+                    let accounts = instruction.accounts.iter().flatten().map(|a| &a.name);
+                    inventory.group(format!("{} 的账户", instruction.name), accounts, Case::Snake);
+                }
+                for event in events {
+                    let fields = event.fields.as_deref().unwrap_or_default();
+                    if fields.is_empty() && !struct_types.contains(event.name.as_str()) {
+                        inventory.empty_events.push(event.name.clone());
+                    }
+                    inventory.anchor_fields(&event.name, Some(fields));
+                }
+
+                inventory.group("accounts".to_string(), accounts.iter().map(|a| &a.name), Case::Pascal);
+                inventory.group("types".to_string(), types.iter().map(|t| &t.name), Case::Pascal);
+                inventory.group("events".to_string(), events.iter().map(|e| &e.name), Case::Pascal);
+                inventory.group("instructions".to_string(), instructions.iter().map(|i| &i.name), Case::Pascal);
+            }
+            IdlFormatEnum::NonAnchor(idl) => {
+                let accounts = idl.accounts.as_deref().unwrap_or_default();
+                let types = idl.types.as_deref().unwrap_or_default();
+                let events = idl.events.as_deref().unwrap_or_default();
+                let instructions = idl.instructions.as_deref().unwrap_or_default();
+
+                inventory.define(accounts.iter().map(|a| &a.name).chain(types.iter().map(|t| &t.name)));
+
+                for account in accounts {
+                    inventory.non_anchor_fields(&account.name, account.fields.as_deref());
+                    let size = utils::calculate_non_anchor_account_packed_size(account, ir);
+                    inventory.length_identified_accounts.push((account.name.clone(), size));
+                }
+                for type_def in types {
+                    match &type_def.type_def {
+                        NonAnchorTypeKind::Struct { fields } => inventory.non_anchor_fields(&type_def.name, Some(fields)),
+                        NonAnchorTypeKind::Enum { variants } => {
+                            for variant in variants {
+                                let owner = format!("{}::{}", type_def.name, variant.name);
+                                inventory.non_anchor_fields(&owner, variant.fields.as_deref());
+                            }
+                            inventory.group(format!("{} 的变体", type_def.name), variants.iter().map(|v| &v.name), Case::Pascal);
+                            let layout = EnumTagLayout::from_values(variants.iter().map(|v| v.value));
+                            inventory.enum_tags.push((type_def.name.clone(), layout));
+                        }
+                        NonAnchorTypeKind::Alias { value } => inventory
+                            .field_types
+                            .push((type_def.name.clone(), type_mapper::map_field_type(value))),
+                    }
+                }
+                for instruction in instructions {
+                    inventory.non_anchor_fields(&instruction.name, instruction.args.as_deref());
+                    let accounts = instruction.accounts.iter().flatten().map(|a| &a.name);
+                    inventory.group(format!("{} 的账户", instruction.name), accounts, Case::Snake);
+                }
+                for event in events {
+                    let fields = event.fields.as_deref().unwrap_or_default();
+                    if fields.is_empty() {
+                        inventory.empty_events.push(event.name.clone());
+                    }
+                    inventory.non_anchor_fields(&event.name, Some(fields));
+                }
+
+                inventory.group("accounts".to_string(), accounts.iter().map(|a| &a.name), Case::Pascal);
+                inventory.group("types".to_string(), types.iter().map(|t| &t.name), Case::Pascal);
+                inventory.group("events".to_string(), events.iter().map(|e| &e.name), Case::Pascal);
+                inventory.group("instructions".to_string(), instructions.iter().map(|i| &i.name), Case::Pascal);
+            }
+        }
+        inventory
+    }
+
+    fn define<'a>(&mut self, names: impl Iterator<Item = &'a String>) {
+        self.defined.extend(names.map(|name| name.to_case(Case::Pascal)));
+    }
+
+    fn group<'a>(&mut self, scope: String, names: impl Iterator<Item = &'a String>, case: Case) {
+        self.name_groups.push((scope, names.cloned().collect(), case));
+    }
+
+    fn anchor_fields(&mut self, owner: &str, fields: Option<&[AnchorField]>) {
+        let fields = fields.unwrap_or_default();
+        for field in fields {
+            let rust_type = type_mapper::map_field_type(&field.field_type);
+            self.field_types.push((format!("{}.{}", owner, field.name), rust_type));
+        }
+        self.group(format!("{} 的字段", owner), fields.iter().map(|f| &f.name), Case::Snake);
+    }
+
+    fn non_anchor_fields(&mut self, owner: &str, fields: Option<&[NonAnchorField]>) {
+        let fields = fields.unwrap_or_default();
+        for field in fields {
+            let rust_type = type_mapper::map_field_type(&field.field_type);
+            self.field_types.push((format!("{}.{}", owner, field.name), rust_type));
+        }
+        self.group(format!("{} 的字段", owner), fields.iter().map(|f| &f.name), Case::Snake);
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::idl_format::parse_idl_json;
+
+    #[test]
+    fn collects_unknown_types_empty_events_and_collisions() {
+        let idl = parse_idl_json(
+            r#"{
+                "address": "11111111111111111111111111111111",
+                "metadata": {"name": "demo", "version": "0.1.0", "spec": "0.1.0"},
+                "instructions": [
+                    {"name": "swap", "discriminator": [1,2,3,4,5,6,7,8], "accounts": [],
+                     "args": [{"name": "config", "type": {"defined": {"name": "Missing"}}}]},
+                    {"name": "Swap", "discriminator": [2,2,3,4,5,6,7,8], "accounts": [], "args": []}
+                ],
+                "events": [{"name": "Traded", "discriminator": [3,2,3,4,5,6,7,8]}]
+            }"#,
+        )
+        .unwrap();
+
+        let codes: Vec<_> = collect(&idl).iter().map(Warning::code).collect();
+        assert_eq!(
+            codes,
+            [WarningCode::UnknownType, WarningCode::EmptyEvent, WarningCode::NameCollision]
+        );
+    }
+
+    #[test]
+    fn reports_missing_instruction_discriminators() {
+        let single = parse_idl_json(
+            r#"{
+                "address": "11111111111111111111111111111111",
+                "metadata": {"name": "demo", "version": "0.1.0", "spec": "0.1.0"},
+                "instructions": [{"name": "log", "accounts": [], "args": [{"name": "amount", "type": "u64"}]}]
+            }"#,
+        )
+        .unwrap();
+        assert_eq!(
+            collect(&single),
+            [Warning::UndiscriminatedInstruction { instruction: "log".to_string() }]
+        );
+
+        let multi = parse_idl_json(
+            r#"{
+                "address": "11111111111111111111111111111111",
+                "metadata": {"name": "demo", "version": "0.1.0", "spec": "0.1.0"},
+                "instructions": [
+                    {"name": "init", "discriminator": [0], "accounts": [], "args": []},
+                    {"name": "log", "accounts": [], "args": []}
+                ]
+            }"#,
+        )
+        .unwrap();
+        assert_eq!(
+            collect(&multi),
+            [Warning::IndexDiscriminator { instruction: "log".to_string(), index: 1 }]
+        );
+    }
+
+    #[test]
+    fn reports_outputs_skipped_for_non_anchor_idls() {
+        let shank = parse_idl_json(
+            r#"{
+                "name": "demo", "version": "0.1.0",
+                "metadata": {"name": "demo", "version": "0.1.0", "spec": "0.1.0", "origin": "shank", "address": "11111111111111111111111111111111"},
+                "instructions": [{"name": "log", "discriminator": [0], "accounts": [], "args": []}]
+            }"#,
+        )
+        .unwrap();
+        let args = Args { accounts_as_traits: true, generate_parser: true, generate_to_json: true, ..Default::default() };
+        let codes: Vec<_> = skipped_outputs(&args, &shank).iter().map(Warning::code).collect();
+        assert_eq!(codes, [WarningCode::SkippedOutput]);
+
+        let without_serde = Args { gen_jsonschema: true, generate_to_json: false, ..args.clone() };
+        assert!(matches!(
+            skipped_outputs(&without_serde, &shank)[..],
+            [_, Warning::SkippedOutput { option: "--gen-jsonschema", .. }]
+        ));
+        let interop = Args { interop_with: Some("other_interface".parse().unwrap()), ..args.clone() };
+        assert!(matches!(
+            skipped_outputs(&interop, &shank)[..],
+            [_, Warning::SkippedOutput { option: "--interop-with", reason: "仅支持 Anchor IDL", .. }]
+        ));
+        let without_parser = Args {
+            gen_history_stats: true,
+            gen_program_tests: true,
+            gen_benches: true,
+            fixtures_from_rpc: Some("fixtures.json".into()),
+            generate_parser: false,
+            ..args.clone()
+        };
+        let options: Vec<_> = skipped_outputs(&without_parser, &shank)
+            .into_iter()
+            .filter_map(|w| match w {
+                Warning::SkippedOutput { option, .. } => Some(option),
+                _ => None,
+            })
+            .collect();
+        assert_eq!(
+            options,
+            ["--accounts-as-traits", "--gen-history-stats", "--gen-program-tests", "--gen-benches", "--fixtures-from-rpc"]
+        );
+
+        let allowed = Args { allow: vec![WarningCode::SkippedOutput], ..args.clone() };
+        assert!(check(&allowed, &shank).unwrap().is_empty());
+        let denied = Args { deny_warnings: true, ..args };
+        assert!(check(&denied, &shank).is_err());
+    }
+}
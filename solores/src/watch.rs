@@ -0,0 +1,187 @@
+//! IDL 监听模式
+//!
+//! `solores watch <dir>` 启动时为目录中的每个 IDL 生成一次 crate，随后监听
+//! 文件变化，只重新生成被修改的 IDL 对应的 crate，并打印生成文件的精简 diff。
+//! `--` 之后的参数原样传给生成器（如 `--generate-parser`）。
+
+use std::collections::{BTreeMap, HashMap, HashSet};
+use std::fs;
+use std::path::{Path, PathBuf};
+use std::sync::mpsc;
+use std::time::Duration;
+
+use clap::Parser;
+use notify::{RecursiveMode, Watcher};
+use similar::{ChangeTag, TextDiff};
+
+use crate::{error::SoloresError, Args};
+
+/// `solores watch` 参数
+#[derive(clap::Args, Debug, Clone)]
+pub struct WatchArgs {
+    /// 存放 IDL 文件的目录
+    pub dir: PathBuf,
+
+    /// 生成 crate 的输出目录
+    #[arg(long, short, default_value = "./")]
+    pub output_dir: PathBuf,
+
+    /// 合并连续文件事件的等待时间（毫秒）
+    #[arg(long, default_value_t = 300)]
+    pub debounce_ms: u64,
+
+    /// 每个文件最多打印的 diff 行数
+    #[arg(long, default_value_t = 20)]
+    pub diff_lines: usize,
+
+    /// 传给生成器的其余参数（写在 `--` 之后）
+    #[arg(last = true)]
+    pub generator_args: Vec<String>,
+}
+
+/// 生成结果快照：相对路径 -> 文件内容
+pub(crate) type Snapshot = BTreeMap<PathBuf, String>;
+
+/// 启动监听，直到监听通道关闭
+pub fn run(args: &WatchArgs) -> Result<(), SoloresError> {
+    let mut base_args = Args::parse_from(
+        ["solores".into(), args.dir.display().to_string()]
+            .into_iter()
+            .chain(args.generator_args.iter().cloned()),
+    );
+    base_args.batch_output_dir = args.output_dir.clone();
+
+    // 监听事件返回绝对路径，扫描时使用同一规范路径以便对应快照
+    let dir = fs::canonicalize(&args.dir)
+        .map_err(|e| SoloresError::file_operation_error("读取IDL目录", args.dir.display().to_string(), e))?;
+    let mut snapshots = HashMap::new();
+    for idl_path in crate::scan_idl_files_with_filters(&dir, &base_args) {
+        if let Some((crate_dir, snapshot)) = regenerate(&base_args, &idl_path) {
+            println!("✅ {} -> {}", idl_path.display(), crate_dir.display());
+            snapshots.insert(idl_path, snapshot);
+        }
+    }
+
+    let (tx, rx) = mpsc::channel();
+    let mut watcher = notify::recommended_watcher(tx).map_err(|e| watch_error(&dir, e))?;
+    watcher.watch(&dir, RecursiveMode::NonRecursive).map_err(|e| watch_error(&dir, e))?;
+    println!("👀 正在监听 {}（Ctrl+C 退出）", dir.display());
+
+    let debounce = Duration::from_millis(args.debounce_ms);
+    while let Ok(event) = rx.recv() {
+        // 编辑器保存时通常触发多个事件，等待片刻后合并处理
+        let mut changed = changed_idl_paths(event);
+        while let Ok(event) = rx.recv_timeout(debounce) {
+            changed.extend(changed_idl_paths(event));
+        }
+
+        let mut changed: Vec<_> = changed.into_iter().collect();
+        changed.sort();
+        for idl_path in changed {
+            if !idl_path.is_file() {
+                log::info!("🗑️  IDL已删除，保留已生成的crate: {}", idl_path.display());
+                snapshots.remove(&idl_path);
+                continue;
+            }
+            let Some((crate_dir, snapshot)) = regenerate(&base_args, &idl_path) else {
+                continue;
+            };
+            println!("🔄 {} -> {}", idl_path.display(), crate_dir.display());
+            match snapshots.get(&idl_path) {
+                Some(previous) => print_diff(previous, &snapshot, args.diff_lines),
+                None => println!("  + {} 个文件", snapshot.len()),
+            }
+            snapshots.insert(idl_path, snapshot);
+        }
+    }
+    Ok(())
+}
+
+/// 重新生成单个 IDL 的 crate，失败时打印错误并返回 None
+fn regenerate(base_args: &Args, idl_path: &Path) -> Option<(PathBuf, Snapshot)> {
+    match crate::process_single_idl_file(base_args, idl_path) {
+        Ok((crate_dir, warnings)) => {
+            crate::warnings::print(&warnings);
+            let snapshot = snapshot(&crate_dir);
+            Some((crate_dir, snapshot))
+        }
+        Err(e) => {
+            eprintln!("❌ {}: {}", idl_path.display(), e);
+            None
+        }
+    }
+}
+
+fn watch_error(dir: &Path, e: notify::Error) -> SoloresError {
+    SoloresError::file_operation_error("监听IDL目录", dir.display().to_string(), std::io::Error::other(e))
+}
+
+/// 事件涉及的 JSON 文件；访问类事件不会改变内容，直接忽略
+fn changed_idl_paths(event: notify::Result<notify::Event>) -> HashSet<PathBuf> {
+    match event {
+        Ok(event) if !event.kind.is_access() => event
+            .paths
+            .into_iter()
+            .filter(|path| path.extension().is_some_and(|ext| ext == "json"))
+            .collect(),
+        Ok(_) => HashSet::new(),
+        Err(e) => {
+            log::warn!("⚠️  监听事件错误: {}", e);
+            HashSet::new()
+        }
+    }
+}
+
+/// 读取 crate 目录下除 target 外的全部文本文件
+pub(crate) fn snapshot(crate_dir: &Path) -> Snapshot {
+    let mut files = Snapshot::new();
+    let mut pending = vec![crate_dir.to_path_buf()];
+    while let Some(dir) = pending.pop() {
+        let Ok(entries) = fs::read_dir(&dir) else { continue };
+        for path in entries.flatten().map(|entry| entry.path()) {
+            if path.is_dir() {
+                if path.file_name().is_some_and(|name| name != "target") {
+                    pending.push(path);
+                }
+            } else if let Ok(content) = fs::read_to_string(&path) {
+                files.insert(path.strip_prefix(crate_dir).unwrap_or(&path).to_path_buf(), content);
+            }
+        }
+    }
+    files
+}
+
+/// 打印两次生成之间的文件变化，每个文件的 diff 截断到 `max_lines` 行
+fn print_diff(previous: &Snapshot, current: &Snapshot, max_lines: usize) {
+    let mut unchanged = true;
+    for (path, content) in current {
+        let Some(old) = previous.get(path) else {
+            println!("  + {}", path.display());
+            unchanged = false;
+            continue;
+        };
+        if old == content {
+            continue;
+        }
+        unchanged = false;
+        let diff = TextDiff::from_lines(old.as_str(), content.as_str());
+        let count = |tag| diff.iter_all_changes().filter(|change| change.tag() == tag).count();
+        println!("  ~ {} (+{} -{})", path.display(), count(ChangeTag::Insert), count(ChangeTag::Delete));
+
+        let unified = diff.unified_diff().context_radius(1).to_string();
+        let lines: Vec<_> = unified.lines().filter(|line| !line.starts_with("@@")).collect();
+        for line in lines.iter().take(max_lines) {
+            println!("      {}", line);
+        }
+        if lines.len() > max_lines {
+            println!("      … 省略 {} 行", lines.len() - max_lines);
+        }
+    }
+    for path in previous.keys().filter(|path| !current.contains_key(*path)) {
+        println!("  - {}", path.display());
+        unchanged = false;
+    }
+    if unchanged {
+        println!("  （生成结果无变化）");
+    }
+}
@@ -4,10 +4,13 @@
 //! when multiple crates need to be organized together.
 
 use std::{
+    collections::BTreeMap,
     fs,
     path::PathBuf,
 };
 
+use toml_edit::{value, DocumentMut, InlineTable, Item};
+
 use crate::error::SoloresError;
 
 /// Configuration for workspace generation
@@ -21,6 +24,26 @@ pub struct WorkspaceConfig {
     pub members: Vec<String>,
     /// Dependency versions to be shared across the workspace
     pub dependency_versions: DependencyVersions,
+    /// Dependencies collected from member manifests, emitted as `[workspace.dependencies]`
+    pub dependencies: BTreeMap<String, WorkspaceDependency>,
+}
+
+/// A dependency shared through `[workspace.dependencies]`
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct WorkspaceDependency {
+    pub version: String,
+    /// Set when any member disables default features
+    pub default_features: bool,
+}
+
+impl WorkspaceDependency {
+    fn to_toml(&self) -> String {
+        if self.default_features {
+            format!("\"{}\"", self.version)
+        } else {
+            format!("{{ version = \"{}\", default-features = false }}", self.version)
+        }
+    }
 }
 
 /// Shared dependency versions for the workspace
@@ -60,6 +83,30 @@ pub fn generate_workspace_cargo_toml(config: &WorkspaceConfig) -> Result<String,
         .collect::<Vec<_>>()
         .join(",\n");
 
+    // Versions collected from members take precedence over the CLI defaults
+    let mut dependencies: BTreeMap<String, String> = [
+        ("solana-program", format!("\"{}\"", config.dependency_versions.solana_program_vers)),
+        ("solana-pubkey", "\"2.0\"".to_string()),
+        ("borsh", format!("\"{}\"", config.dependency_versions.borsh_vers)),
+        ("serde", format!("{{ version = \"{}\", features = [\"derive\"] }}", config.dependency_versions.serde_vers)),
+        ("serde_with", format!("{{ version = \"{}\" }}", config.dependency_versions.serde_with_vers)),
+        ("thiserror", format!("\"{}\"", config.dependency_versions.thiserror_vers)),
+        ("num-derive", format!("\"{}\"", config.dependency_versions.num_derive_vers)),
+        ("num-traits", format!("\"{}\"", config.dependency_versions.num_traits_vers)),
+        ("bytemuck", format!("{{ version = \"{}\", features = [\"derive\"] }}", config.dependency_versions.bytemuck_vers)),
+    ]
+    .into_iter()
+    .map(|(name, spec)| (name.to_string(), spec))
+    .collect();
+    for (name, dependency) in &config.dependencies {
+        dependencies.insert(name.clone(), dependency.to_toml());
+    }
+    let dependencies_list = dependencies
+        .iter()
+        .map(|(name, spec)| format!("{} = {}", name, spec))
+        .collect::<Vec<_>>()
+        .join("\n");
+
     let workspace_cargo_toml = format!(
         r#"[workspace]
 resolver = "2"
@@ -78,24 +125,7 @@ keywords = ["solana", "blockchain", "web3"]
 categories = ["cryptography::cryptocurrencies"]
 
 [workspace.dependencies]
-# Solana dependencies
-solana-program = "{}"
-solana-pubkey = "2.0"
-
-# Serialization
-borsh = "{}"
-serde = {{ version = "{}", features = ["derive"] }}
-serde_with = {{ version = "{}" }}
-
-# Error handling
-thiserror = "{}"
-
-# Numeric types
-num-derive = "{}"
-num-traits = "{}"
-
-# Zero-copy serialization
-bytemuck = {{ version = "{}", features = ["derive"] }}
+{}
 
 [profile.release]
 overflow-checks = true
@@ -108,14 +138,7 @@ incremental = false
 codegen-units = 1
 "#,
         members_list,
-        config.dependency_versions.solana_program_vers,
-        config.dependency_versions.borsh_vers,
-        config.dependency_versions.serde_vers,
-        config.dependency_versions.serde_with_vers,
-        config.dependency_versions.thiserror_vers,
-        config.dependency_versions.num_derive_vers,
-        config.dependency_versions.num_traits_vers,
-        config.dependency_versions.bytemuck_vers,
+        dependencies_list,
     );
 
     Ok(workspace_cargo_toml)
@@ -151,7 +174,7 @@ pub fn generate_member_cargo_toml(
     let mut features = Vec::new();
     let mut optional_deps = Vec::new();
 
-    if zero_copy.len() > 0 {
+    if !zero_copy.is_empty() {
         features.push("bytemuck");
         optional_deps.push("bytemuck = { workspace = true, optional = true }");
     }
@@ -222,6 +245,7 @@ pub fn validate_workspace_config(args: &crate::Args) -> Result<Option<WorkspaceC
         output_dir: args.batch_output_dir.clone(),
         members: Vec::new(), // Will be populated during batch processing
         dependency_versions: DependencyVersions::from_args(args),
+        dependencies: BTreeMap::new(), // Collected from member manifests in finalize_workspace
     };
 
     Ok(Some(config))
@@ -235,7 +259,103 @@ pub fn add_workspace_member(config: &mut WorkspaceConfig, member_name: String) {
     }
 }
 
-/// Finalize workspace by writing the workspace Cargo.toml
+/// Dependency tables rewritten to `workspace = true`
+const DEPENDENCY_TABLES: [&str; 3] = ["dependencies", "dev-dependencies", "build-dependencies"];
+
+/// Move registry dependency versions of a member manifest into `dependencies`
+/// and replace them with `workspace = true`
+///
+/// Path / git / renamed dependencies are left untouched. The member's empty
+/// `[workspace]` table is removed so it joins the generated workspace.
+pub fn unify_member_manifest(
+    manifest: &mut DocumentMut,
+    dependencies: &mut BTreeMap<String, WorkspaceDependency>,
+) {
+    manifest.remove("workspace");
+
+    for table_name in DEPENDENCY_TABLES {
+        let Some(table) = manifest.get_mut(table_name).and_then(Item::as_table_like_mut) else {
+            continue;
+        };
+        for (name, item) in table.iter_mut() {
+            let name = name.get().to_string();
+            let (version, default_features) = match item.as_str() {
+                Some(version) => (version.to_string(), true),
+                None => {
+                    let Some(spec) = item.as_table_like() else { continue };
+                    if ["path", "git", "package", "workspace"].iter().any(|key| spec.contains_key(key)) {
+                        continue;
+                    }
+                    let Some(version) = spec.get("version").and_then(Item::as_str) else { continue };
+                    let default_features = spec
+                        .get("default-features")
+                        .and_then(Item::as_bool)
+                        .unwrap_or(true);
+                    (version.to_string(), default_features)
+                }
+            };
+
+            let entry = dependencies.entry(name.clone()).or_insert_with(|| WorkspaceDependency {
+                version: version.clone(),
+                default_features,
+            });
+            if entry.version != version {
+                log::warn!(
+                    "⚠️  {} 版本不一致：workspace 使用 {}，成员声明 {}",
+                    name, entry.version, version
+                );
+            }
+            entry.default_features &= default_features;
+
+            rewrite_as_workspace_dependency(item);
+        }
+    }
+}
+
+/// Replace version keys with `workspace = true`, keeping member-specific keys
+/// such as `features` and `optional`
+fn rewrite_as_workspace_dependency(item: &mut Item) {
+    if item.is_str() {
+        let mut inline = InlineTable::new();
+        inline.insert("workspace", true.into());
+        *item = value(inline);
+        return;
+    }
+    if let Some(spec) = item.as_table_like_mut() {
+        spec.remove("version");
+        spec.insert("workspace", value(true));
+        // workspace 放在首位，便于阅读
+        let workspace_first = |a: &toml_edit::Key, b: &toml_edit::Key| (b.get() == "workspace").cmp(&(a.get() == "workspace"));
+        if let Some(table) = item.as_table_mut() {
+            table.sort_values_by(|a, _, b, _| workspace_first(a, b));
+        } else if let Some(inline) = item.as_inline_table_mut() {
+            inline.sort_values_by(|a, _, b, _| workspace_first(a, b));
+        }
+    }
+}
+
+/// Rewrite every member Cargo.toml to inherit workspace dependencies
+fn unify_member_manifests(config: &mut WorkspaceConfig) -> Result<(), SoloresError> {
+    for member in config.members.clone() {
+        let manifest_path = config.output_dir.join(&member).join("Cargo.toml");
+        let content = fs::read_to_string(&manifest_path)
+            .map_err(|e| SoloresError::file_operation_error("read member Cargo.toml", manifest_path.display().to_string(), e))?;
+        let mut manifest: DocumentMut = content.parse().map_err(|e| SoloresError::ValidationError {
+            message: format!("无法解析成员 Cargo.toml: {}", e),
+            field_path: Some(manifest_path.display().to_string()),
+            expected: None,
+            actual: None,
+        })?;
+
+        unify_member_manifest(&mut manifest, &mut config.dependencies);
+
+        fs::write(&manifest_path, manifest.to_string())
+            .map_err(|e| SoloresError::file_operation_error("write member Cargo.toml", manifest_path.display().to_string(), e))?;
+    }
+    Ok(())
+}
+
+/// Finalize workspace by unifying member dependencies and writing the workspace Cargo.toml
 pub fn finalize_workspace(config: &WorkspaceConfig) -> Result<(), SoloresError> {
     if config.members.is_empty() {
         log::warn!("⚠️  No workspace members found. Skipping workspace Cargo.toml generation.");
@@ -247,7 +367,9 @@ pub fn finalize_workspace(config: &WorkspaceConfig) -> Result<(), SoloresError>
         log::info!("   - {}", member);
     }
 
-    write_workspace_cargo_toml(config)?;
+    let mut config = config.clone();
+    unify_member_manifests(&mut config)?;
+    write_workspace_cargo_toml(&config)?;
     
     log::info!("✅ Workspace '{}' created successfully at: {}", 
                config.name, config.output_dir.display());
@@ -275,6 +397,7 @@ mod tests {
                 serde_with_vers: "^3.0".to_string(),
                 bytemuck_vers: "^1.16".to_string(),
             },
+            dependencies: BTreeMap::new(),
         };
 
         let result = generate_workspace_cargo_toml(&config).unwrap();
@@ -301,4 +424,37 @@ mod tests {
         assert!(result.contains("11111111111111111111111111111112"));
         assert!(result.contains("borsh = { workspace = true }"));
     }
-}
\ No newline at end of file
+
+    #[test]
+    fn test_unify_member_manifest() {
+        let mut manifest: DocumentMut = r#"[package]
+name = "member"
+
+[workspace]
+
+[dependencies.borsh]
+version = "^1.5"
+[dependencies.chrono]
+default-features = false
+optional = true
+version = "^0.4.31"
+[dependencies]
+local = { path = "../local" }
+thiserror = "^1.0"
+"#
+        .parse()
+        .unwrap();
+        let mut dependencies = BTreeMap::new();
+
+        unify_member_manifest(&mut manifest, &mut dependencies);
+
+        assert!(manifest.get("workspace").is_none());
+        assert_eq!(dependencies["borsh"].version, "^1.5");
+        assert!(!dependencies["chrono"].default_features);
+        assert!(!dependencies.contains_key("local"));
+        let rendered = manifest.to_string();
+        assert!(rendered.contains("[dependencies.chrono]\nworkspace = true\ndefault-features = false\noptional = true"));
+        assert!(rendered.contains("thiserror = { workspace = true }"));
+        assert!(rendered.contains("local = { path = \"../local\" }"));
+    }
+}
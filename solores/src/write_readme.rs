@@ -51,7 +51,7 @@ fn scan_directory_recursive(dir: &Path, base_dir: &Path, files: &mut Vec<PathBuf
         
         if path.is_dir() {
             scan_directory_recursive(&path, base_dir, files)?;
-        } else if path.extension().map_or(false, |ext| ext == "rs") {
+        } else if path.extension().is_some_and(|ext| ext == "rs") {
             // Store relative path from src directory
             if let Ok(relative_path) = path.strip_prefix(base_dir) {
                 files.push(relative_path.to_path_buf());
@@ -81,11 +81,11 @@ fn build_file_tree(files: &[PathBuf]) -> FileNode {
                 
                 // Add comments for special files  
                 if !is_directory {
-                    node = match component {
-                        &"lib.rs" => node.with_comment("Program ID declaration and module exports".to_string()),
-                        &"mod.rs" => node.with_comment("Module declarations".to_string()),
-                        &"errors.rs" => node.with_comment("Program error definitions".to_string()),
-                        &"constants.rs" => node.with_comment("Program constants".to_string()),
+                    node = match *component {
+                        "lib.rs" => node.with_comment("Program ID declaration and module exports".to_string()),
+                        "mod.rs" => node.with_comment("Module declarations".to_string()),
+                        "errors.rs" => node.with_comment("Program error definitions".to_string()),
+                        "constants.rs" => node.with_comment("Program constants".to_string()),
                         _ => node,
                     };
                 }
@@ -184,14 +184,21 @@ pub fn write_readme(args: &Args, idl: &dyn IdlFormat) -> std::io::Result<()> {
     let directory_structure = format_file_tree(&file_tree, crate_name);
     
     // Determine what modules exist (for usage examples)
-    let modules = idl.modules(args);
-    let has_instructions = modules.iter().any(|m| m.name() == "instructions");
-    let has_accounts = modules.iter().any(|m| m.name() == "accounts");  
-    let has_types = modules.iter().any(|m| m.name() == "types");
-    let has_events = modules.iter().any(|m| m.name() == "events");
-    let has_errors = modules.iter().any(|m| m.name() == "errors");
-    let has_constants = modules.iter().any(|m| m.name() == "constants");
-    let has_parsers = modules.iter().any(|m| m.name() == "accounts_parser" || m.name() == "instructions_parser");
+    let has_module = |name: &str| {
+        generated_files.iter().any(|f| {
+            f.components().next().is_some_and(|c| {
+                let c = c.as_os_str().to_string_lossy();
+                c == name || c == format!("{}.rs", name)
+            })
+        })
+    };
+    let has_instructions = has_module("instructions");
+    let has_accounts = has_module("accounts");
+    let has_types = has_module("types");
+    let has_events = has_module("events");
+    let has_errors = has_module("errors");
+    let has_constants = has_module("constants");
+    let has_parsers = has_module("parsers");
     
     let readme_content = format!(
 r#"# {crate_name}
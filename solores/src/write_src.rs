@@ -2,40 +2,12 @@
 use crate::{
     error::SoloresError,
     idl_format::{IdlFormat, IdlFormatEnum}, 
+    utils::is_valid_pubkey,
     Args
 };
 
 const DEFAULT_PROGRAM_ID_STR: &str = "TH1S1SNoTAVAL1DPUBKEYDoNoTUSE11111111111111";
 
-/// 检查是否应该使用MiniJinja模板系统
-/// 现在固定使用MiniJinja模板系统，保留函数接口以便将来扩展
-#[allow(dead_code)]
-fn should_use_minijinja(_args: &Args) -> bool {
-    log::debug!("🔧 使用 MiniJinja 模板系统（唯一选项）");
-    true
-}
-
-
-const MAX_BASE58_LEN: usize = 44;
-const PUBKEY_BYTES_SIZE: usize = 32;
-
-/// Copied from solana_program::Pubkey::from_str()
-/// so that we dont have to have solana_program as a dep
-fn is_valid_pubkey(s: &str) -> bool {
-    if s.len() > MAX_BASE58_LEN {
-        return false;
-    }
-    let pubkey_vec = match bs58::decode(s).into_vec() {
-        Ok(v) => v,
-        Err(_) => return false,
-    };
-    if pubkey_vec.len() != PUBKEY_BYTES_SIZE {
-        return false;
-    }
-    true
-}
-
-
 
 /// writes the lib.rs file
 pub fn write_lib(args: &Args, idl: &dyn IdlFormat) -> std::io::Result<()> {
@@ -43,8 +15,7 @@ pub fn write_lib(args: &Args, idl: &dyn IdlFormat) -> std::io::Result<()> {
         Ok(()) => Ok(()),
         Err(e) => {
             eprintln!("{}", crate::error::format_user_error(&e));
-            Err(std::io::Error::new(
-                std::io::ErrorKind::Other,
+            Err(std::io::Error::other(
                 e.to_string()
             ))
         }
@@ -52,47 +23,12 @@ pub fn write_lib(args: &Args, idl: &dyn IdlFormat) -> std::io::Result<()> {
 }
 
 
-/// 使用 MiniJinja 模板系统生成代码（默认模板系统）
-pub fn write_lib_with_minijinja(args: &Args, _idl: &dyn IdlFormat) -> Result<(), SoloresError> {
-    log::info!("🚀 使用 MiniJinja 模板系统生成代码（默认）");
-    
+/// 渲染代码的入口函数，经由 pipeline 中按 `--engine` 选择的渲染后端
+pub fn write_lib_with_diagnostics(args: &Args, _idl: &dyn IdlFormat) -> Result<(), SoloresError> {
     // 通过重新解析 IDL 文件来获取完整数据
     let idl_format = convert_dyn_idl_to_enum_with_reparse(args)?;
-    
-    // 创建 MiniJinja 模板生成器
-    let mut generator = crate::minijinja::MinijinjaTemplateGenerator::new(idl_format)?;
-    
-    // 生成多文件架构
-    generator.generate_multi_file_structure(
-        &args.output_dir,
-        args.generate_to_json, // 使用generate_to_json作为serde特性标志
-        args.generate_parser,
-        args.no_empty_workspace,
-    )?;
-    
-    // 复制IDL文件到输出目录
-    if let Ok(content) = std::fs::read_to_string(&args.idl_path) {
-        let idl_output_path = args.output_dir.join("idl.json");
-        std::fs::write(&idl_output_path, content).map_err(|e| SoloresError::FileOperationError {
-            operation: "copy IDL file".to_string(),
-            path: idl_output_path.display().to_string(),
-            current_dir: std::env::current_dir().ok().map(|p| p.display().to_string()),
-            resolved_path: None,
-            source: e,
-            suggestion: Some("检查目录权限".to_string()),
-        })?;
-    }
-    
-    log::info!("✅ MiniJinja 代码生成完成");
-    Ok(())
-}
-
-
-/// 模板系统选择的主要入口函数
-pub fn write_lib_with_diagnostics(args: &Args, idl: &dyn IdlFormat) -> Result<(), SoloresError> {
-    // 现在直接使用 MiniJinja 模板系统
-    log::info!("🚀 使用 MiniJinja 模板系统 - 现代化多文件架构生成");
-    write_lib_with_minijinja(args, idl)
+    let prepared = crate::pipeline::prepare(&idl_format, args)?;
+    crate::pipeline::backend_for(args.engine).render(&prepared, args)
 }
 
 /// 将 dyn IdlFormat 转换为 IdlFormatEnum
@@ -112,15 +48,15 @@ fn convert_dyn_idl_to_enum_with_reparse(args: &Args) -> Result<IdlFormatEnum, So
         })?;
     
     // 使用新的解析器直接获取 IdlFormatEnum
-    match crate::idl_format::parse_idl_json(&content) {
-        Ok(idl_format) => {
+    match crate::idl_format::parse_idl_json_with_format(&content, args.force_format) {
+        Ok((idl_format, _)) => {
             log::info!("✅ 成功重新解析 IDL 文件用于模板系统");
             Ok(idl_format)
         }
         Err(e) => {
             log::error!("❌ 重新解析 IDL 文件失败: {}", e);
             Err(SoloresError::IdlParseError {
-                message: format!("Failed to parse IDL for Askama: {}", e),
+                message: format!("Failed to parse IDL for template system: {}", e),
                 line: None,
                 column: None,
                 file_path: Some(args.idl_path.clone()),